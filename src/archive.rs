@@ -0,0 +1,478 @@
+//! Archive listings, reading as little of the archive as the format allows
+//!
+//! A ZIP file ends with a directory of everything in it, so its contents can be
+//! listed by reading the last few kilobytes instead of the whole archive. That
+//! matters on network mounts, where a preview shouldn't pull a multi-gigabyte
+//! backup over the link. Office documents, EPUBs and JARs are ZIPs too. The `zip`
+//! crate reads the directory, ZIP64 archives (over 4 GiB or 65535 entries)
+//! included.
+//!
+//! A 7z file keeps its directory in a header at the end too. A tar file has a
+//! header before each entry, and the entries in between are skipped with seeks;
+//! a compressed tar has to be decompressed to find them, so only the first
+//! `MAX_STREAM_BYTES` of it are. A gzip file says its original size in its last
+//! four bytes, which gives the compression ratio without decompressing.
+
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Extensions of files that are ZIP archives underneath
+const ZIP_EXTENSIONS: &[&str] = &[
+    "zip", "jar", "war", "apk", "epub", "docx", "xlsx", "pptx", "odt", "ods", "odp", "whl",
+];
+/// Most of a compressed tar decompressed for one listing
+const MAX_STREAM_BYTES: u64 = 64 * 1024 * 1024;
+
+/// The archive formats that can be listed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    Zip,
+    Tar,
+    /// `.tar.gz` / `.tgz`
+    TarGz,
+    /// A single gzipped file
+    Gzip,
+    SevenZ,
+}
+
+impl ArchiveKind {
+    /// How the preview names the format
+    pub fn label(self) -> &'static str {
+        match self {
+            ArchiveKind::Zip => "ZIP archive",
+            ArchiveKind::Tar => "tar archive",
+            ArchiveKind::TarGz => "tar.gz archive",
+            ArchiveKind::Gzip => "gzip file",
+            ArchiveKind::SevenZ => "7z archive",
+        }
+    }
+}
+
+/// One file in an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveEntry {
+    pub name: String,
+    /// Uncompressed size
+    pub size: u64,
+}
+
+/// The first entries of an archive, and what's known of the rest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveListing {
+    pub kind: ArchiveKind,
+    /// How many entries there are in all; `None` when listing stopped before the end
+    pub total_entries: Option<usize>,
+    pub entries: Vec<ArchiveEntry>,
+    /// Size of everything in the archive unpacked, when it's known. For gzip it's
+    /// the trailer's, which wraps around past 4 GiB.
+    pub unpacked_size: Option<u64>,
+    /// Bytes read from the file to list it
+    pub bytes_read: u64,
+}
+
+/// Whether `path` names a ZIP-based format
+pub fn is_zip(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ZIP_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+/// The archive format `path` names by its extension, if it's one that can be listed
+pub fn archive_kind(path: &Path) -> Option<ArchiveKind> {
+    if is_zip(path) {
+        return Some(ArchiveKind::Zip);
+    }
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    match ext.as_str() {
+        "tar" => Some(ArchiveKind::Tar),
+        "tgz" => Some(ArchiveKind::TarGz),
+        "gz" => {
+            let inner = Path::new(path.file_stem()?).extension();
+            if inner.is_some_and(|ext| ext.eq_ignore_ascii_case("tar")) {
+                Some(ArchiveKind::TarGz)
+            } else {
+                Some(ArchiveKind::Gzip)
+            }
+        }
+        "7z" => Some(ArchiveKind::SevenZ),
+        _ => None,
+    }
+}
+
+/// Lists up to `max_entries` entries of the archive at `path` in the format its
+/// extension names
+pub fn list_archive(path: &Path, max_entries: usize) -> io::Result<ArchiveListing> {
+    match archive_kind(path) {
+        Some(ArchiveKind::Zip) => read_listing(path, max_entries),
+        Some(ArchiveKind::Tar) => read_tar_listing(path, max_entries),
+        Some(ArchiveKind::TarGz) => read_tar_gz_listing(path, max_entries),
+        Some(ArchiveKind::Gzip) => read_gzip_listing(path),
+        Some(ArchiveKind::SevenZ) => read_7z_listing(path, max_entries),
+        None => Err(invalid("not an archive fswp can list")),
+    }
+}
+
+/// Lists up to `max_entries` entries of the archive at `path`, reading only its
+/// end record and central directory
+pub fn read_listing(path: &Path, max_entries: usize) -> io::Result<ArchiveListing> {
+    let archive = zip::ZipArchive::new(Paced::new(File::open(path)?))?;
+    let mut entries = Vec::new();
+    let mut unpacked = 0;
+    for index in 0..archive.len() {
+        let entry = archive.by_index_data(index)?;
+        if entries.len() < max_entries {
+            let name = match entry.name() {
+                Ok(name) => name.into_owned(),
+                Err(_) => String::from_utf8_lossy(entry.name_raw()).into_owned(),
+            };
+            entries.push(ArchiveEntry {
+                name,
+                size: entry.size(),
+            });
+        }
+        unpacked += entry.size();
+    }
+    Ok(ArchiveListing {
+        kind: ArchiveKind::Zip,
+        total_entries: Some(archive.len()),
+        entries,
+        unpacked_size: Some(unpacked),
+        bytes_read: archive.into_inner().read,
+    })
+}
+
+/// Lists a tar file's entries, seeking past each one's contents
+pub fn read_tar_listing(path: &Path, max_entries: usize) -> io::Result<ArchiveListing> {
+    let mut archive = tar::Archive::new(Paced::new(File::open(path)?));
+    let (entries, total, unpacked) = tar_entries(archive.entries_with_seek()?, max_entries);
+    Ok(ArchiveListing {
+        kind: ArchiveKind::Tar,
+        total_entries: Some(total),
+        entries,
+        unpacked_size: Some(unpacked),
+        bytes_read: archive.into_inner().read,
+    })
+}
+
+/// Lists a compressed tar's entries found in its first `MAX_STREAM_BYTES`
+/// decompressed
+pub fn read_tar_gz_listing(path: &Path, max_entries: usize) -> io::Result<ArchiveListing> {
+    let unpacked_size = gzip_size(path)?;
+    let decoder = GzDecoder::new(Paced::new(File::open(path)?)).take(MAX_STREAM_BYTES);
+    let mut archive = tar::Archive::new(decoder);
+    let (entries, total, _) = tar_entries(archive.entries()?, max_entries);
+    let decoder = archive.into_inner();
+    // Cut off by the cap rather than at the end of the archive
+    let complete = decoder.limit() > 0;
+    Ok(ArchiveListing {
+        kind: ArchiveKind::TarGz,
+        total_entries: complete.then_some(total),
+        entries,
+        unpacked_size: Some(unpacked_size),
+        bytes_read: decoder.into_inner().into_inner().read,
+    })
+}
+
+/// A gzipped file as its one entry: the name stored in its header (or the file's
+/// own, less `.gz`) and the size in its trailer
+pub fn read_gzip_listing(path: &Path) -> io::Result<ArchiveListing> {
+    let mut decoder = GzDecoder::new(Paced::new(File::open(path)?));
+    let header = decoder.header().ok_or_else(|| invalid("not a gzip file"))?;
+    let name = match header.filename() {
+        Some(name) => String::from_utf8_lossy(name).into_owned(),
+        None => path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let bytes_read = decoder.get_mut().read;
+    let size = gzip_size(path)?;
+    Ok(ArchiveListing {
+        kind: ArchiveKind::Gzip,
+        total_entries: Some(1),
+        entries: vec![ArchiveEntry { name, size }],
+        unpacked_size: Some(size),
+        bytes_read: bytes_read + 4,
+    })
+}
+
+/// Lists a 7z archive's entries from its header
+pub fn read_7z_listing(path: &Path, max_entries: usize) -> io::Result<ArchiveListing> {
+    let mut file = Paced::new(File::open(path)?);
+    let len = file.inner.metadata()?.len();
+    let archive = sevenz_rust::Archive::read(&mut file, len, &[]).map_err(|e| match e {
+        sevenz_rust::Error::PasswordRequired => invalid("7z header is encrypted"),
+        e => invalid(&e.to_string()),
+    })?;
+    let entries = archive
+        .files
+        .iter()
+        .take(max_entries)
+        .map(|entry| ArchiveEntry {
+            name: if entry.is_directory {
+                format!("{}/", entry.name)
+            } else {
+                entry.name.clone()
+            },
+            size: entry.size,
+        })
+        .collect();
+    Ok(ArchiveListing {
+        kind: ArchiveKind::SevenZ,
+        total_entries: Some(archive.files.len()),
+        entries,
+        unpacked_size: Some(archive.files.iter().map(|entry| entry.size).sum()),
+        bytes_read: file.read,
+    })
+}
+
+/// The first `max_entries` of a tar's entries, how many it has and their total
+/// size; an unreadable entry ends the walk
+fn tar_entries<R: Read>(
+    entries: tar::Entries<'_, R>,
+    max_entries: usize,
+) -> (Vec<ArchiveEntry>, usize, u64) {
+    let mut listed = Vec::new();
+    let mut total = 0;
+    let mut unpacked = 0;
+    for entry in entries {
+        let Ok(entry) = entry else {
+            break;
+        };
+        let size = entry.header().size().unwrap_or(0);
+        if listed.len() < max_entries {
+            let name = entry.path_bytes();
+            listed.push(ArchiveEntry {
+                name: String::from_utf8_lossy(&name).into_owned(),
+                size,
+            });
+        }
+        total += 1;
+        unpacked += size;
+    }
+    (listed, total, unpacked)
+}
+
+/// The uncompressed size a gzip file's trailer records (modulo 4 GiB)
+fn gzip_size(path: &Path) -> io::Result<u64> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    if len < 18 {
+        return Err(invalid("too short for a gzip file"));
+    }
+    let trailer = read_at(&mut file, len - 4, 4)?;
+    u32_at(&trailer, 0)
+        .map(u64::from)
+        .ok_or_else(|| invalid("gzip trailer missing"))
+}
+
+/// A reader paced by the configured read rate, counting the bytes it reads
+struct Paced<R> {
+    inner: R,
+    read: u64,
+}
+
+impl<R> Paced<R> {
+    fn new(inner: R) -> Self {
+        Self { inner, read: 0 }
+    }
+}
+
+impl<R: Read> Read for Paced<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        crate::limits::throttle(n as u64);
+        self.read += n as u64;
+        Ok(n)
+    }
+}
+
+impl<R: Seek> Seek for Paced<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Reads `len` bytes at `offset`, paced by the configured read rate
+fn read_at(file: &mut File, offset: u64, len: u64) -> io::Result<Vec<u8>> {
+    crate::limits::throttle(len);
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::with_capacity(len as usize);
+    file.take(len).read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A stored (uncompressed) archive with the given files
+    fn build_zip(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(io::Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        for (name, contents) in files {
+            writer.start_file(*name, options).unwrap();
+            io::Write::write_all(&mut writer, contents).unwrap();
+        }
+        writer.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_read_listing_from_the_central_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("backup.zip");
+        let big = vec![7u8; 200_000];
+        let zip = build_zip(&[
+            ("notes.txt", b"hello"),
+            ("photos/big.raw", &big),
+            ("c", b""),
+        ]);
+        std::fs::write(&path, &zip).unwrap();
+
+        let listing = read_listing(&path, 2).unwrap();
+        assert_eq!(listing.total_entries, Some(3));
+        assert_eq!(
+            listing.entries,
+            [
+                ArchiveEntry {
+                    name: "notes.txt".to_string(),
+                    size: 5
+                },
+                ArchiveEntry {
+                    name: "photos/big.raw".to_string(),
+                    size: 200_000
+                },
+            ]
+        );
+        assert_eq!(listing.unpacked_size, Some(200_005));
+        // Only the tail was read, not the stored data
+        assert!(listing.bytes_read < 70_000);
+
+        assert!(is_zip(&path));
+        assert!(is_zip(Path::new("Report.DOCX")));
+        assert!(!is_zip(Path::new("notes.txt")));
+    }
+
+    #[test]
+    fn test_read_listing_of_a_zip64_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("many.zip");
+        // More entries than the end record's 16-bit count holds
+        let names: Vec<String> = (0..=u16::MAX as usize).map(|i| i.to_string()).collect();
+        let files: Vec<(&str, &[u8])> = names
+            .iter()
+            .map(|name| (name.as_str(), &b"x"[..]))
+            .collect();
+        std::fs::write(&path, build_zip(&files)).unwrap();
+
+        let listing = read_listing(&path, 2).unwrap();
+        assert_eq!(listing.total_entries, Some(65536));
+        assert_eq!(listing.entries[1].name, "1");
+        assert_eq!(listing.unpacked_size, Some(65536));
+    }
+
+    #[test]
+    fn test_list_tar_gzip_and_7z_archives() {
+        let temp_dir = TempDir::new().unwrap();
+        let files: [(&str, Vec<u8>); 3] = [
+            ("notes.txt", b"hello".to_vec()),
+            ("logs/big.log", vec![b'a'; 100_000]),
+            ("c", Vec::new()),
+        ];
+
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, contents) in &files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, contents.as_slice())
+                .unwrap();
+        }
+        let tar = builder.into_inner().unwrap();
+        let tar_path = temp_dir.path().join("backup.tar");
+        std::fs::write(&tar_path, &tar).unwrap();
+        let listing = list_archive(&tar_path, 2).unwrap();
+        assert_eq!(listing.kind, ArchiveKind::Tar);
+        assert_eq!(listing.total_entries, Some(3));
+        assert_eq!(listing.unpacked_size, Some(100_005));
+        assert_eq!(
+            listing.entries[1],
+            ArchiveEntry {
+                name: "logs/big.log".to_string(),
+                size: 100_000
+            }
+        );
+        // The contents were seeked past, not read
+        assert!(listing.bytes_read < 10_000);
+
+        let gzip = |data: &[u8]| {
+            let mut encoder =
+                flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+            std::io::Write::write_all(&mut encoder, data).unwrap();
+            encoder.finish().unwrap()
+        };
+        let tgz_path = temp_dir.path().join("backup.tar.gz");
+        std::fs::write(&tgz_path, gzip(&tar)).unwrap();
+        let listing = list_archive(&tgz_path, 10).unwrap();
+        assert_eq!(listing.kind, ArchiveKind::TarGz);
+        assert_eq!(listing.total_entries, Some(3));
+        assert_eq!(listing.entries[0].name, "notes.txt");
+        assert_eq!(listing.unpacked_size, Some(tar.len() as u64));
+
+        let gz_path = temp_dir.path().join("big.log.gz");
+        std::fs::write(&gz_path, gzip(&files[1].1)).unwrap();
+        let listing = list_archive(&gz_path, 10).unwrap();
+        assert_eq!(listing.kind, ArchiveKind::Gzip);
+        assert_eq!(
+            listing.entries,
+            [ArchiveEntry {
+                name: "big.log".to_string(),
+                size: 100_000
+            }]
+        );
+
+        let sevenz_path = temp_dir.path().join("backup.7z");
+        let mut writer = sevenz_rust::SevenZWriter::create(&sevenz_path).unwrap();
+        for (name, contents) in &files {
+            let mut entry = sevenz_rust::SevenZArchiveEntry::new();
+            entry.name = name.to_string();
+            writer
+                .push_archive_entry(entry, Some(contents.as_slice()))
+                .unwrap();
+        }
+        writer.finish().unwrap();
+        let listing = list_archive(&sevenz_path, 10).unwrap();
+        assert_eq!(listing.kind, ArchiveKind::SevenZ);
+        assert_eq!(listing.total_entries, Some(3));
+        assert_eq!(listing.entries[1].size, 100_000);
+        assert_eq!(listing.unpacked_size, Some(100_005));
+
+        assert_eq!(
+            archive_kind(Path::new("site.TGZ")),
+            Some(ArchiveKind::TarGz)
+        );
+        assert_eq!(archive_kind(Path::new("notes.txt")), None);
+    }
+
+    #[test]
+    fn test_read_listing_rejects_non_zip_data() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("fake.zip");
+        std::fs::write(&path, b"not an archive at all").unwrap();
+        let err = read_listing(&path, 10).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
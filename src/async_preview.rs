@@ -2,10 +2,18 @@
 #![allow(dead_code)]
 
 use crate::domain::FileEntry;
-use crate::preview::{generate_preview, PreviewContent};
+use crate::domain::FileType;
+use crate::preview::{
+    generate_preview_with_options, load_preview_image, render_image_preview, ImageCommand,
+    ImageDetails, ImageView, PreviewContent, PreviewOptions, PreviewScroll, ScrollCommand,
+    MAX_IMAGE_HEIGHT, MAX_IMAGE_WIDTH,
+};
+use image::{DynamicImage, GenericImageView};
 use std::collections::HashMap;
+use std::io;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::{mpsc, oneshot, Mutex};
 
 /// Maximum number of cached previews
@@ -116,11 +124,17 @@ pub struct PreviewLoader {
     cache: Arc<Mutex<PreviewCache>>,
     /// Track current loading path to allow cancellation
     current_loading: Arc<Mutex<Option<PathBuf>>>,
+    options: PreviewOptions,
 }
 
 impl PreviewLoader {
     /// Create a new preview loader with a background task
     pub fn new() -> Self {
+        Self::with_options(PreviewOptions::default())
+    }
+
+    /// Create a preview loader that generates previews within the given limits
+    pub fn with_options(options: PreviewOptions) -> Self {
         let (request_tx, request_rx) = mpsc::channel(32);
         let cache = Arc::new(Mutex::new(PreviewCache::new(CACHE_SIZE)));
         let current_loading = Arc::new(Mutex::new(None));
@@ -129,13 +143,14 @@ impl PreviewLoader {
             request_tx,
             cache: Arc::clone(&cache),
             current_loading: Arc::clone(&current_loading),
+            options,
         };
 
         // Spawn the background worker
         let cache_clone = Arc::clone(&cache);
         let current_loading_clone = Arc::clone(&current_loading);
         tokio::spawn(async move {
-            Self::worker(request_rx, cache_clone, current_loading_clone).await;
+            Self::worker(request_rx, cache_clone, current_loading_clone, options).await;
         });
 
         loader
@@ -146,6 +161,7 @@ impl PreviewLoader {
         mut request_rx: mpsc::Receiver<PreviewRequest>,
         cache: Arc<Mutex<PreviewCache>>,
         current_loading: Arc<Mutex<Option<PathBuf>>>,
+        options: PreviewOptions,
     ) {
         while let Some(request) = request_rx.recv().await {
             match request {
@@ -171,8 +187,11 @@ impl PreviewLoader {
                     }
 
                     // Generate preview (this is the expensive part)
-                    let result =
-                        tokio::task::spawn_blocking(move || generate_preview(&file_entry)).await;
+                    let result = tokio::task::spawn_blocking(move || {
+                        let _permit = crate::limits::open_files(1);
+                        generate_preview_with_options(&file_entry, &options)
+                    })
+                    .await;
 
                     // Check if cancelled
                     {
@@ -193,7 +212,7 @@ impl PreviewLoader {
                             }
                             PreviewState::Ready(preview)
                         }
-                        Ok(Err(e)) => PreviewState::Error(e.to_string()),
+                        Ok(Err(e)) => PreviewState::Error(crate::diagnostics::error_chain(&e)),
                         Err(e) => PreviewState::Error(format!("Task panicked: {}", e)),
                     };
 
@@ -246,7 +265,7 @@ impl PreviewLoader {
         }
 
         // Wait for response with timeout
-        match tokio::time::timeout(std::time::Duration::from_secs(5), response_rx).await {
+        match tokio::time::timeout(self.options.timeout, response_rx).await {
             Ok(Ok(state)) => state,
             Ok(Err(_)) => PreviewState::Error("Response channel closed".to_string()),
             Err(_) => PreviewState::Error("Preview timed out".to_string()),
@@ -317,13 +336,45 @@ pub struct SyncPreviewManager {
     current_path: Option<PathBuf>,
     /// Receiver for the current pending preview request
     receiver: Option<oneshot::Receiver<PreviewState>>,
+    /// When the pending request was sent, for the loading timeout
+    loading_since: Option<Instant>,
+    options: PreviewOptions,
+    /// Decoded image and its view while the current image is zoomed or panned
+    zoom: Option<ZoomedImage>,
+    /// The preview kept beside the current one while a file is pinned
+    pinned: Option<PinnedPreview>,
+    /// How far the current preview is scrolled; back to the top for each file
+    scroll: PreviewScroll,
+}
+
+/// A pinned file's preview, loaded alongside the current one
+#[derive(Debug)]
+struct PinnedPreview {
+    path: PathBuf,
+    state: PreviewState,
+    /// Pending request, when the preview wasn't cached or on screen at pin time
+    receiver: Option<oneshot::Receiver<PreviewState>>,
+}
+
+/// The image being zoomed, decoded once for all adjustments
+#[derive(Debug)]
+struct ZoomedImage {
+    path: PathBuf,
+    image: DynamicImage,
+    details: ImageDetails,
+    view: ImageView,
 }
 
 impl SyncPreviewManager {
     /// Create a new sync preview manager
     pub fn new() -> Self {
+        Self::with_options(PreviewOptions::default())
+    }
+
+    /// Create a sync preview manager that generates previews within the given limits
+    pub fn with_options(options: PreviewOptions) -> Self {
         let runtime = tokio::runtime::Runtime::new().expect("Failed to create tokio runtime");
-        let loader = runtime.block_on(async { PreviewLoader::new() });
+        let loader = runtime.block_on(async { PreviewLoader::with_options(options) });
 
         Self {
             loader,
@@ -331,9 +382,19 @@ impl SyncPreviewManager {
             current_state: PreviewState::Loading,
             current_path: None,
             receiver: None,
+            loading_since: None,
+            options,
+            zoom: None,
+            pinned: None,
+            scroll: PreviewScroll::default(),
         }
     }
 
+    /// The limits previews are generated with
+    pub fn options(&self) -> &PreviewOptions {
+        &self.options
+    }
+
     /// Request a preview for a file, returns current state (non-blocking)
     pub fn request_preview(&mut self, file_entry: &FileEntry) -> &PreviewState {
         let path = file_entry.path.clone();
@@ -347,6 +408,7 @@ impl SyncPreviewManager {
 
             self.current_path = Some(path.clone());
             self.receiver = None;
+            self.scroll = PreviewScroll::default();
 
             // Check cache first (sync/block_on lock but fast)
             if let Some(cached) = self.runtime.block_on(self.loader.get_cached(&path)) {
@@ -373,6 +435,7 @@ impl SyncPreviewManager {
                 .block_on(async move { loader.request_tx.send(request).await });
 
             self.receiver = Some(rx);
+            self.loading_since = Some(Instant::now());
         }
 
         // If we're loading, check if the receiver has a value
@@ -388,6 +451,14 @@ impl SyncPreviewManager {
                         if let Some(cached) = self.runtime.block_on(self.loader.get_cached(&path)) {
                             self.current_state = PreviewState::Ready(cached);
                             self.receiver = None;
+                        } else if self
+                            .loading_since
+                            .is_some_and(|since| since.elapsed() >= self.options.timeout)
+                        {
+                            // Give up rather than spin forever on a hung read
+                            self.current_state =
+                                PreviewState::Error("Preview timed out".to_string());
+                            self.receiver = None;
                         }
                     }
                     Err(oneshot::error::TryRecvError::Closed) => {
@@ -412,11 +483,74 @@ impl SyncPreviewManager {
         self.request_preview(file_entry)
     }
 
+    /// Preview of the pinned file, shown beside the current one (non-blocking).
+    ///
+    /// A file pinned while on screen keeps exactly what was shown (a zoomed image
+    /// stays zoomed); otherwise it comes from the cache or its own request, which
+    /// is sent again if moving on cancels it.
+    pub fn pinned_preview(&mut self, file_entry: &FileEntry) -> &PreviewState {
+        let path = &file_entry.path;
+        if self
+            .pinned
+            .as_ref()
+            .is_some_and(|pinned| &pinned.path != path)
+        {
+            self.pinned = None;
+        }
+        let pinned = self.pinned.get_or_insert_with(|| PinnedPreview {
+            path: path.clone(),
+            state: if self.current_path.as_ref() == Some(path) {
+                self.current_state.clone()
+            } else {
+                PreviewState::Loading
+            },
+            receiver: None,
+        });
+
+        if matches!(pinned.state, PreviewState::Loading) {
+            if self.current_path.as_ref() == Some(path)
+                && !matches!(self.current_state, PreviewState::Loading)
+            {
+                pinned.state = self.current_state.clone();
+            } else if let Some(cached) = self.runtime.block_on(self.loader.get_cached(path)) {
+                pinned.state = PreviewState::Ready(cached);
+            } else if let Some(rx) = pinned.receiver.as_mut() {
+                match rx.try_recv() {
+                    Ok(state) => pinned.state = state,
+                    Err(oneshot::error::TryRecvError::Empty) => {}
+                    // Cancelled by a newer request for the current file; ask again
+                    Err(oneshot::error::TryRecvError::Closed) => pinned.receiver = None,
+                }
+            } else {
+                let (tx, rx) = oneshot::channel();
+                let request = PreviewRequest::Load {
+                    file_entry: file_entry.clone(),
+                    response_tx: tx,
+                };
+                let loader = self.loader.clone();
+                let _ = self
+                    .runtime
+                    .block_on(async move { loader.request_tx.send(request).await });
+                pinned.receiver = Some(rx);
+            }
+            if !matches!(pinned.state, PreviewState::Loading) {
+                pinned.receiver = None;
+            }
+        }
+
+        &pinned.state
+    }
+
     /// Get the current preview state
     pub fn current_state(&self) -> &PreviewState {
         &self.current_state
     }
 
+    /// Whether a preview is still being generated (the UI shows a spinner meanwhile)
+    pub fn is_loading(&self) -> bool {
+        matches!(self.current_state, PreviewState::Loading)
+    }
+
     /// Reset the manager (e.g., when changing files)
     pub fn reset(&mut self) {
         if self.current_path.is_some() {
@@ -425,12 +559,93 @@ impl SyncPreviewManager {
         self.current_path = None;
         self.current_state = PreviewState::Loading;
         self.receiver = None;
+        self.loading_since = None;
+        self.zoom = None;
+        self.scroll = PreviewScroll::default();
+    }
+
+    /// Scrolls the current preview, within what it took when last drawn
+    pub fn scroll_preview(&mut self, command: ScrollCommand) {
+        self.scroll.apply(command);
+    }
+
+    /// Records the preview pane's height and the current preview's wrapped rows,
+    /// returning the scroll position to draw with
+    pub fn set_viewport(&mut self, page: u16, rows: u16) -> PreviewScroll {
+        self.scroll.set_viewport(page, rows);
+        self.scroll
+    }
+
+    /// Scroll position of the current preview
+    pub fn preview_scroll(&self) -> PreviewScroll {
+        self.scroll
+    }
+
+    /// Zooms or pans the preview of an image, re-rendering it synchronously.
+    ///
+    /// The image is decoded on the first adjustment and kept until `reset()`;
+    /// the cache keeps the fitted preview, so coming back to the file starts fitted.
+    pub fn adjust_image(
+        &mut self,
+        file_entry: &FileEntry,
+        command: ImageCommand,
+    ) -> io::Result<()> {
+        let too_large = self
+            .options
+            .max_media_bytes
+            .is_some_and(|max| file_entry.size > max);
+        if file_entry.file_type != FileType::Image || too_large {
+            return Ok(());
+        }
+
+        let mut zoom = match self.zoom.take() {
+            Some(zoom) if zoom.path == file_entry.path => zoom,
+            _ => {
+                let (image, details) = load_preview_image(&file_entry.path)?;
+                ZoomedImage {
+                    path: file_entry.path.clone(),
+                    image,
+                    details,
+                    view: ImageView::default(),
+                }
+            }
+        };
+        zoom.view.apply(
+            command,
+            zoom.image.dimensions(),
+            (MAX_IMAGE_WIDTH, MAX_IMAGE_HEIGHT),
+        );
+
+        self.current_path = Some(file_entry.path.clone());
+        self.current_state = PreviewState::Ready(render_image_preview(
+            file_entry,
+            &zoom.image,
+            &zoom.details,
+            &zoom.view,
+        ));
+        self.receiver = None;
+        self.loading_since = None;
+        self.zoom = Some(zoom);
+        Ok(())
+    }
+
+    /// View of the current image, if it has been zoomed or panned
+    pub fn image_view(&self) -> Option<&ImageView> {
+        self.zoom.as_ref().map(|zoom| &zoom.view)
     }
 
     /// Get cache size
     pub fn cache_size(&self) -> usize {
         self.runtime.block_on(self.loader.cache_size())
     }
+
+    /// Drop all cached previews, e.g. after files may have changed on disk
+    pub fn clear_cache(&mut self) {
+        self.runtime.block_on(self.loader.clear_cache());
+        self.reset();
+        // Reload the pinned preview too, in case the pinned file changed
+        self.pinned = None;
+    }
 }
 
 impl Default for SyncPreviewManager {
@@ -454,6 +669,7 @@ mod tests {
             size: 100,
             modified_date: Utc::now(),
             file_type,
+            disk: Default::default(),
         }
     }
 
@@ -703,6 +919,59 @@ mod tests {
             assert!(ready, "Preview should be ready within timeout");
         }
 
+        #[test]
+        fn test_sync_manager_with_degraded_options() {
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("long.txt");
+            let content: String = (0..100).map(|i| format!("line {}\n", i)).collect();
+            fs::write(&file_path, content).unwrap();
+
+            let file_entry = create_test_file_entry(file_path, "long.txt", FileType::Text);
+            let options = PreviewOptions::degraded();
+            let mut manager = SyncPreviewManager::with_options(options);
+            assert_eq!(manager.options(), &options);
+
+            let mut lines = None;
+            for _ in 0..20 {
+                if let PreviewState::Ready(PreviewContent::Text(text)) =
+                    manager.poll_preview(&file_entry)
+                {
+                    lines = Some(text.len());
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+
+            assert_eq!(lines, Some(options.max_lines));
+        }
+
+        #[test]
+        fn test_sync_manager_scroll_resets_for_next_file() {
+            let temp_dir = TempDir::new().unwrap();
+            let first = temp_dir.path().join("first.txt");
+            let second = temp_dir.path().join("second.txt");
+            fs::write(&first, "one").unwrap();
+            fs::write(&second, "two").unwrap();
+            let first = create_test_file_entry(first, "first.txt", FileType::Text);
+            let second = create_test_file_entry(second, "second.txt", FileType::Text);
+
+            let mut manager = SyncPreviewManager::new();
+            let _ = manager.request_preview(&first);
+            manager.set_viewport(10, 40);
+            manager.scroll_preview(ScrollCommand::PageDown);
+            assert_eq!(manager.preview_scroll().offset, 9);
+            // Redrawing the same file keeps the position
+            let _ = manager.request_preview(&first);
+            assert_eq!(manager.set_viewport(10, 40).offset, 9);
+
+            let _ = manager.request_preview(&second);
+            assert_eq!(manager.preview_scroll(), PreviewScroll::default());
+            manager.set_viewport(10, 40);
+            manager.scroll_preview(ScrollCommand::HalfPageDown);
+            manager.reset();
+            assert_eq!(manager.preview_scroll().offset, 0);
+        }
+
         #[test]
         fn test_sync_manager_caches_result() {
             let temp_dir = TempDir::new().unwrap();
@@ -732,6 +1001,39 @@ mod tests {
             assert!(matches!(state, PreviewState::Ready(_)));
         }
 
+        #[test]
+        fn test_sync_manager_pinned_preview_loads_alongside_current() {
+            let temp_dir = TempDir::new().unwrap();
+            let pinned_path = temp_dir.path().join("draft.txt");
+            let other_path = temp_dir.path().join("final.txt");
+            fs::write(&pinned_path, "first draft").unwrap();
+            fs::write(&other_path, "final version").unwrap();
+            let pinned = create_test_file_entry(pinned_path, "draft.txt", FileType::Text);
+            let other = create_test_file_entry(other_path, "final.txt", FileType::Text);
+
+            let mut manager = SyncPreviewManager::new();
+            let mut ready = (false, false);
+            for _ in 0..20 {
+                ready = (
+                    matches!(manager.pinned_preview(&pinned), PreviewState::Ready(_)),
+                    matches!(manager.request_preview(&other), PreviewState::Ready(_)),
+                );
+                if ready == (true, true) {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            assert_eq!(ready, (true, true));
+
+            // The pinned preview doesn't follow the current file
+            match manager.pinned_preview(&pinned) {
+                PreviewState::Ready(PreviewContent::Text(lines)) => {
+                    assert!(lines.iter().any(|line| line.contains("first draft")))
+                }
+                state => panic!("unexpected pinned preview {:?}", state),
+            }
+        }
+
         #[test]
         fn test_sync_manager_reset() {
             let temp_dir = TempDir::new().unwrap();
@@ -747,6 +1049,40 @@ mod tests {
             assert!(matches!(manager.current_state(), PreviewState::Loading));
         }
 
+        #[test]
+        fn test_sync_manager_loading_after_reset() {
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("test.txt");
+            fs::write(&file_path, "Test content").unwrap();
+
+            let file_entry = create_test_file_entry(file_path, "test.txt", FileType::Text);
+
+            let mut manager = SyncPreviewManager::new();
+            let _ = manager.request_preview(&file_entry);
+
+            manager.reset();
+            assert!(manager.is_loading());
+        }
+
+        #[test]
+        fn test_sync_manager_not_loading_once_ready() {
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("test.txt");
+            fs::write(&file_path, "Test content").unwrap();
+
+            let file_entry = create_test_file_entry(file_path, "test.txt", FileType::Text);
+
+            let mut manager = SyncPreviewManager::new();
+            for _ in 0..10 {
+                if !matches!(manager.poll_preview(&file_entry), PreviewState::Loading) {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+
+            assert!(!manager.is_loading());
+        }
+
         #[test]
         fn test_sync_manager_handles_file_change() {
             let temp_dir = TempDir::new().unwrap();
@@ -792,5 +1128,62 @@ mod tests {
             // Both should be cached
             assert_eq!(manager.cache_size(), 2);
         }
+
+        #[test]
+        fn test_sync_manager_clear_cache() {
+            let temp_dir = TempDir::new().unwrap();
+            let file_path = temp_dir.path().join("test.txt");
+            fs::write(&file_path, "Test content").unwrap();
+
+            let file_entry = create_test_file_entry(file_path, "test.txt", FileType::Text);
+
+            let mut manager = SyncPreviewManager::new();
+            let mut ready = false;
+            for _ in 0..10 {
+                if matches!(manager.poll_preview(&file_entry), PreviewState::Ready(_)) {
+                    ready = true;
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(50));
+            }
+            assert!(ready, "File should be ready");
+            assert_eq!(manager.cache_size(), 1);
+
+            manager.clear_cache();
+            assert_eq!(manager.cache_size(), 0);
+            assert!(manager.is_loading());
+        }
+
+        #[test]
+        fn test_sync_manager_adjust_image() {
+            let temp_dir = TempDir::new().unwrap();
+            let image_path = temp_dir.path().join("photo.png");
+            image::RgbImage::new(400, 400).save(&image_path).unwrap();
+            let entry = create_test_file_entry(image_path, "photo.png", FileType::Image);
+
+            let mut manager = SyncPreviewManager::new();
+            manager
+                .adjust_image(&entry, ImageCommand::CycleFit)
+                .unwrap();
+            manager.adjust_image(&entry, ImageCommand::ZoomIn).unwrap();
+            let view = *manager.image_view().unwrap();
+            assert_eq!(view.zoom_steps, 1);
+            let PreviewState::Ready(PreviewContent::Styled(lines)) =
+                manager.request_preview(&entry)
+            else {
+                panic!("zoomed preview should stay ready");
+            };
+            assert!(lines[2].to_string().starts_with("Zoom: fill"));
+
+            // Moving to another file drops the zoom
+            manager.reset();
+            assert!(manager.image_view().is_none());
+
+            // Text files aren't zoomable
+            let text =
+                create_test_file_entry(temp_dir.path().join("a.txt"), "a.txt", FileType::Text);
+            manager.adjust_image(&text, ImageCommand::ZoomIn).unwrap();
+            assert!(manager.image_view().is_none());
+        }
     }
 }
@@ -0,0 +1,245 @@
+//! What an audio file says about itself, without decoding any of it
+//!
+//! A folder of downloaded audio is mostly `track01.mp3` and `audio (3).m4a`, so
+//! the name says little. The tags do: title, artist and album, how long it plays,
+//! the codec and its average bitrate, and whether cover art is embedded. They are
+//! read with `symphonia`'s demuxers for MP3 (ID3v2 / ID3v1), FLAC and Ogg (Vorbis
+//! comments) and M4A (iTunes atoms); only headers, tags and, for an MP3 without a
+//! Xing header, a few frames to estimate its length are read.
+
+use std::fs::File;
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+use symphonia::core::codecs::{
+    CodecType, CODEC_TYPE_AAC, CODEC_TYPE_ALAC, CODEC_TYPE_FLAC, CODEC_TYPE_MP3, CODEC_TYPE_OPUS,
+    CODEC_TYPE_VORBIS,
+};
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey};
+use symphonia::core::probe::Hint;
+
+/// Details read from an audio file's headers and tags
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AudioInfo {
+    /// Codec name, e.g. "MP3", "FLAC", "Vorbis", "AAC"
+    pub codec: &'static str,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration: Option<Duration>,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<usize>,
+    /// Average bits per second of the audio: the file size, less cover art,
+    /// over the duration
+    pub bitrate: Option<u64>,
+    /// Embedded pictures (cover art)
+    pub pictures: usize,
+}
+
+impl AudioInfo {
+    /// The technical line, e.g. "MP3 · 3:25 · 128 kbps · 44.1 kHz stereo"
+    pub fn summary(&self) -> String {
+        let mut parts = vec![self.codec.to_string()];
+        parts.extend(self.duration.map(format_duration));
+        parts.extend(
+            self.bitrate
+                .map(|bps| format!("{} kbps", (bps + 500) / 1000)),
+        );
+        let channels = match self.channels {
+            Some(1) => " mono".to_string(),
+            Some(2) => " stereo".to_string(),
+            Some(n) => format!(" {} channels", n),
+            None => String::new(),
+        };
+        if let Some(rate) = self.sample_rate {
+            parts.push(format!("{} kHz{}", f64::from(rate) / 1000.0, channels));
+        }
+        parts.join(" · ")
+    }
+}
+
+/// Reads the tags and stream details of an MP3, FLAC, Ogg or M4A file
+pub fn read_audio_info(path: &Path) -> io::Result<AudioInfo> {
+    let file = File::open(path)?;
+    let size = file.metadata()?.len();
+    let mut hint = Hint::new();
+    if let Some(ext) = path.extension().and_then(|ext| ext.to_str()) {
+        hint.with_extension(ext);
+    }
+    let mut probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            MediaSourceStream::new(Box::new(file), Default::default()),
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(io::Error::other)?;
+
+    let mut info = AudioInfo::default();
+    // Tags in the container (FLAC, Ogg, M4A) win over those found while probing
+    // (an MP3's ID3v2 tag)
+    let mut art_bytes = 0;
+    if let Some(revision) = probed.format.metadata().current() {
+        art_bytes += apply_tags(&mut info, revision);
+    }
+    if let Some(revision) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
+        art_bytes += apply_tags(&mut info, revision);
+    }
+
+    let track = probed
+        .format
+        .default_track()
+        .ok_or_else(|| io::Error::other("No audio track found"))?;
+    let params = &track.codec_params;
+    info.codec = codec_name(params.codec);
+    info.sample_rate = params.sample_rate;
+    info.channels = params.channels.map(|channels| channels.count());
+    info.duration = match (params.n_frames, params.sample_rate) {
+        (Some(frames), Some(rate)) if rate > 0 => {
+            Some(Duration::from_secs_f64(frames as f64 / f64::from(rate)))
+        }
+        _ => None,
+    };
+    info.bitrate = info
+        .duration
+        .filter(|duration| duration.as_secs_f64() >= 1.0)
+        .map(|duration| {
+            (size.saturating_sub(art_bytes) as f64 * 8.0 / duration.as_secs_f64()) as u64
+        });
+    Ok(info)
+}
+
+/// Fills in the fields of `info` still empty from one metadata revision and
+/// counts its pictures; returns their size in bytes
+fn apply_tags(info: &mut AudioInfo, revision: &MetadataRevision) -> u64 {
+    for tag in revision.tags() {
+        let field = match tag.std_key {
+            Some(StandardTagKey::TrackTitle) => &mut info.title,
+            Some(StandardTagKey::Artist) => &mut info.artist,
+            Some(StandardTagKey::Album) => &mut info.album,
+            _ => continue,
+        };
+        let value = tag.value.to_string();
+        let value = value.trim();
+        if field.is_none() && !value.is_empty() {
+            *field = Some(value.to_string());
+        }
+    }
+    info.pictures += revision.visuals().len();
+    revision
+        .visuals()
+        .iter()
+        .map(|visual| visual.data.len() as u64)
+        .sum()
+}
+
+fn codec_name(codec: CodecType) -> &'static str {
+    match codec {
+        CODEC_TYPE_MP3 => "MP3",
+        CODEC_TYPE_FLAC => "FLAC",
+        CODEC_TYPE_VORBIS => "Vorbis",
+        CODEC_TYPE_OPUS => "Opus",
+        CODEC_TYPE_AAC => "AAC",
+        CODEC_TYPE_ALAC => "ALAC",
+        _ => "Audio",
+    }
+}
+
+/// A duration as "m:ss", or "h:mm:ss" from an hour up
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64().round() as u64;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// An ID3v2.3 frame
+    fn id3_frame(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = id.to_vec();
+        out.extend((body.len() as u32).to_be_bytes());
+        out.extend([0, 0]);
+        out.extend(body);
+        out
+    }
+
+    /// A tagged CBR MP3: an ID3v2.3 tag with title, artist, album and a cover,
+    /// then `frames` silent 128 kbps / 44.1 kHz stereo frames
+    pub(crate) fn mp3(frames: usize) -> Vec<u8> {
+        let mut tags = Vec::new();
+        for (id, text) in [
+            (b"TIT2", "Harbour Lights"),
+            (b"TPE1", "The Tide"),
+            (b"TALB", "Low Water"),
+        ] {
+            let mut body = vec![0];
+            body.extend(text.as_bytes());
+            tags.extend(id3_frame(id, &body));
+        }
+        let mut cover = b"\0image/png\0\x03\0".to_vec();
+        cover.extend([0x89; 2048]);
+        tags.extend(id3_frame(b"APIC", &cover));
+
+        let mut data = b"ID3\x03\0\0".to_vec();
+        let size = tags.len() as u32;
+        // Sync-safe: 7 bits per byte
+        data.extend([21, 14, 7, 0].map(|shift| ((size >> shift) & 0x7F) as u8));
+        data.extend(tags);
+        for _ in 0..frames {
+            // MPEG-1 Layer III, no CRC, 128 kbps, 44.1 kHz, stereo: 417 bytes
+            data.extend([0xFF, 0xFB, 0x90, 0x00]);
+            data.extend([0; 413]);
+        }
+        data
+    }
+
+    #[test]
+    fn test_read_audio_info_mp3() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("track01.mp3");
+        fs::write(&path, mp3(400)).unwrap();
+
+        let info = read_audio_info(&path).unwrap();
+        assert_eq!(info.codec, "MP3");
+        assert_eq!(info.title.as_deref(), Some("Harbour Lights"));
+        assert_eq!(info.artist.as_deref(), Some("The Tide"));
+        assert_eq!(info.album.as_deref(), Some("Low Water"));
+        assert_eq!(info.pictures, 1);
+        assert_eq!(info.sample_rate, Some(44100));
+        assert_eq!(info.channels, Some(2));
+        // 400 frames of 1152 samples
+        let seconds = info.duration.unwrap().as_secs_f64();
+        assert!(
+            (seconds - 400.0 * 1152.0 / 44100.0).abs() < 0.1,
+            "{}",
+            seconds
+        );
+        let kbps = info.bitrate.unwrap() / 1000;
+        assert!((127..=130).contains(&kbps), "{}", kbps);
+        assert_eq!(info.summary(), "MP3 · 0:10 · 128 kbps · 44.1 kHz stereo");
+
+        let not_audio = temp_dir.path().join("fake.mp3");
+        fs::write(&not_audio, b"just some text, not audio").unwrap();
+        assert!(read_audio_info(&not_audio).is_err());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "0:05");
+        assert_eq!(format_duration(Duration::from_secs_f64(205.6)), "3:26");
+        assert_eq!(
+            format_duration(Duration::from_secs(3 * 3600 + 62)),
+            "3:01:02"
+        );
+    }
+}
@@ -1,8 +1,9 @@
 // CLI module for argument parsing and configuration
 #![allow(dead_code)]
 
-use crate::domain::FileType;
-use clap::{ArgAction, Parser, ValueEnum};
+use crate::domain::{FileType, SessionGoal};
+use clap::builder::BoolishValueParser;
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
 use std::path::PathBuf;
 
 /// Fswp - A terminal-based file decluttering tool
@@ -15,50 +16,355 @@ pub struct Args {
     /// Directory to scan for files
     ///
     /// If not specified, defaults to the current directory.
-    #[arg(default_value = ".")]
+    #[arg(default_value = ".", env = "FILE_TINDER_DIRECTORY")]
     pub directory: PathBuf,
 
     /// Filter by file type(s)
     ///
     /// Can be specified multiple times to include multiple types.
     /// Example: --type text --type image
-    #[arg(short = 't', long = "type", value_enum)]
+    #[arg(
+        short = 't',
+        long = "type",
+        value_enum,
+        env = "FILE_TINDER_TYPE",
+        value_delimiter = ','
+    )]
     pub file_types: Vec<FileTypeFilter>,
 
+    /// Filter by extension(s), with or without the dot
+    ///
+    /// Combined with --type, files matching either are listed.
+    /// Example: --ext pdf,docx
+    #[arg(long = "ext", env = "FILE_TINDER_EXT", value_delimiter = ',')]
+    pub extensions: Vec<String>,
+
     /// Dry run mode - preview actions without actually moving files to trash
     ///
     /// In dry run mode, no files will be moved or deleted.
     /// Useful for testing or seeing what would happen.
-    #[arg(short = 'n', long = "dry-run", action = ArgAction::SetTrue)]
+    #[arg(
+        short = 'n',
+        long = "dry-run",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_DRY_RUN",
+        value_parser = BoolishValueParser::new()
+    )]
     pub dry_run: bool,
 
     /// Sort files by specified criteria
-    #[arg(short = 's', long = "sort", value_enum, default_value = "date")]
+    #[arg(
+        short = 's',
+        long = "sort",
+        value_enum,
+        default_value = "date",
+        env = "FILE_TINDER_SORT"
+    )]
     pub sort_by: SortOrder,
 
     /// Reverse sort order
-    #[arg(short = 'r', long = "reverse", action = ArgAction::SetTrue)]
+    #[arg(
+        short = 'r',
+        long = "reverse",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_REVERSE",
+        value_parser = BoolishValueParser::new()
+    )]
     pub reverse: bool,
 
     /// Show hidden files (files starting with .)
-    #[arg(long = "hidden", action = ArgAction::SetTrue)]
+    #[arg(
+        long = "hidden",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_HIDDEN",
+        value_parser = BoolishValueParser::new()
+    )]
     pub show_hidden: bool,
 
     /// Minimum file size filter (e.g., "1KB", "5MB", "1GB")
-    #[arg(long = "min-size")]
+    #[arg(long = "min-size", env = "FILE_TINDER_MIN_SIZE")]
     pub min_size: Option<String>,
 
     /// Maximum file size filter (e.g., "100MB", "1GB")
-    #[arg(long = "max-size")]
+    #[arg(long = "max-size", env = "FILE_TINDER_MAX_SIZE")]
     pub max_size: Option<String>,
 
     /// Skip confirmation prompts for trash actions
-    #[arg(short = 'y', long = "yes", action = ArgAction::SetTrue)]
+    #[arg(
+        short = 'y',
+        long = "yes",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_YES",
+        value_parser = BoolishValueParser::new()
+    )]
     pub yes: bool,
 
     /// Show welcome dialog on startup
-    #[arg(long = "welcome", action = ArgAction::SetTrue)]
+    #[arg(
+        long = "welcome",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_WELCOME",
+        value_parser = BoolishValueParser::new()
+    )]
     pub welcome: bool,
+
+    /// Two-phase review: a fast metadata-only triage pass, then previews for unsure files
+    #[arg(
+        long = "triage",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_TRIAGE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub triage: bool,
+
+    /// Session goal: stop once this much space is freed (e.g., "5GB")
+    #[arg(
+        long = "goal-free",
+        value_name = "SIZE",
+        conflicts_with = "goal_files",
+        env = "FILE_TINDER_GOAL_FREE"
+    )]
+    pub goal_free: Option<String>,
+
+    /// Session goal: stop once this many files have been reviewed
+    #[arg(
+        long = "goal-files",
+        value_name = "COUNT",
+        env = "FILE_TINDER_GOAL_FILES"
+    )]
+    pub goal_files: Option<usize>,
+
+    /// Move older trash decisions to the system trash while you keep reviewing
+    #[arg(
+        long = "background-apply",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_BACKGROUND_APPLY",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub background_apply: bool,
+
+    /// Move each trash decision to the system trash once its undo grace period
+    /// (`undo_grace_secs` in the config) is over
+    #[arg(
+        long = "apply-on-decide",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_APPLY_ON_DECIDE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub apply_on_decide: bool,
+
+    /// After applying, check that trashed files are gone and kept files are untouched
+    #[arg(
+        long = "verify",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_VERIFY",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub verify: bool,
+
+    /// Scan every file in full instead of reusing unchanged results from the last run
+    #[arg(
+        long = "no-index-cache",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_NO_INDEX_CACHE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub no_index_cache: bool,
+
+    /// Start with a saved filter preset from the config (see `presets`)
+    #[arg(long = "preset", value_name = "NAME", env = "FILE_TINDER_PRESET")]
+    pub preset: Option<String>,
+
+    /// Label recorded with the session's journal (e.g. a ticket number)
+    #[arg(long = "annotate", value_name = "TEXT", env = "FILE_TINDER_ANNOTATE")]
+    pub annotate: Option<String>,
+
+    /// Use a separate config, journals and reports under this profile name
+    #[arg(
+        long = "profile",
+        value_name = "NAME",
+        env = "FILE_TINDER_PROFILE",
+        global = true
+    )]
+    pub profile: Option<String>,
+
+    /// Also list subdirectories as items, each trashed or kept as a whole
+    #[arg(
+        long = "dirs",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_DIRS",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub dirs: bool,
+
+    /// Also scan subdirectories, reviewing the files in them one by one
+    #[arg(
+        short = 'R',
+        long = "recursive",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_RECURSIVE",
+        value_parser = BoolishValueParser::new(),
+        conflicts_with = "dirs"
+    )]
+    pub recursive: bool,
+
+    /// When applying, offer to fix kept files' names that break elsewhere
+    /// (trailing spaces, reserved Windows names, mixed Unicode forms)
+    #[arg(
+        long = "normalize-names",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_NORMALIZE_NAMES",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub normalize_names: bool,
+
+    /// After applying, note what was trashed or moved away in a
+    /// FILE_TINDER_SUMMARY.md in the reviewed directory
+    #[arg(
+        long = "leave-summary",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_LEAVE_SUMMARY",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub leave_summary: bool,
+
+    /// Before reviewing, offer to trash junk, empty files, identical copies and
+    /// old installers in bulk
+    #[arg(
+        long = "easy-wins",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_EASY_WINS",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub easy_wins: bool,
+
+    /// Before reviewing, go through groups of files with identical contents,
+    /// keeping one of each
+    #[arg(
+        long = "duplicates",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_DUPLICATES",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub duplicates: bool,
+
+    /// Start in the size explorer: subdirectories biggest first, with a review
+    /// of any of them a key away
+    #[arg(
+        long = "explore",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_EXPLORE",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub explore: bool,
+
+    /// Hash-chain the session journal so later edits to it can be detected
+    #[arg(
+        long = "audit-chain",
+        action = ArgAction::SetTrue,
+        env = "FILE_TINDER_AUDIT_CHAIN",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub audit_chain: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcommands other than the default review session
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Step through a past session's decisions (read-only)
+    Replay {
+        /// Session name from the sessions directory, or a path to a journal file
+        session: String,
+    },
+    /// Check a journal written with --audit-chain for tampering
+    Audit {
+        /// Session name from the sessions directory, or a path to a journal file
+        session: String,
+    },
+    /// Permanently delete trashed files whose retention period (see `retention`) is over
+    Purge {
+        /// Delete them; without this the files are only listed
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Pick files fswp trashed, grouped by how long they've been in the trash, and
+    /// delete them from it for good
+    EmptyTrash,
+    /// Put back what a session's apply took, from the backup manifest written before it
+    Restore {
+        /// Manifest file; the latest one in the manifests directory when left out
+        manifest: Option<PathBuf>,
+        /// Restore them; without this the files are only listed
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Summarize past sessions' decisions over the last week or month
+    Digest {
+        /// How far back to look
+        #[arg(long, value_enum, default_value_t = DigestPeriod::Week)]
+        period: DigestPeriod,
+        /// Output format
+        #[arg(long, value_enum, default_value_t = DigestFormat::Markdown)]
+        format: DigestFormat,
+        /// Write the digest to this file instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Check the terminal, system trash, config and optional tools, and say what to fix
+    Doctor,
+    /// Work with the config's rules (default decisions)
+    Rules {
+        #[command(subcommand)]
+        command: RulesCommand,
+    },
+    /// Print the preview the review shows for a file, then exit
+    Preview {
+        /// File or directory to preview
+        file: PathBuf,
+        /// Show lines that look sensitive instead of blacking them out
+        #[arg(long)]
+        reveal: bool,
+    },
+}
+
+/// Time window of a digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum DigestPeriod {
+    /// The last 7 days
+    #[default]
+    Week,
+    /// The last 30 days
+    Month,
+}
+
+impl DigestPeriod {
+    pub fn days(self) -> i64 {
+        match self {
+            DigestPeriod::Week => 7,
+            DigestPeriod::Month => 30,
+        }
+    }
+}
+
+/// Output format of a digest
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Default)]
+pub enum DigestFormat {
+    #[default]
+    Markdown,
+    Html,
+}
+
+/// Subcommands of `fswp rules`
+#[derive(Subcommand, Debug, Clone, PartialEq, Eq)]
+pub enum RulesCommand {
+    /// Print, for each file in a directory, which rules match it and what they'd do
+    Test {
+        /// Directory whose files the rules are run over (nothing is changed)
+        directory: PathBuf,
+    },
 }
 
 /// File type filter options
@@ -70,6 +376,10 @@ pub enum FileTypeFilter {
     Image,
     /// PDF files
     Pdf,
+    /// Archives (zip, tar, tar.gz, gz, 7z)
+    Archive,
+    /// Audio files (mp3, flac, ogg, m4a)
+    Audio,
     /// Binary/other files
     Binary,
 }
@@ -80,6 +390,8 @@ impl From<FileTypeFilter> for FileType {
             FileTypeFilter::Text => FileType::Text,
             FileTypeFilter::Image => FileType::Image,
             FileTypeFilter::Pdf => FileType::Pdf,
+            FileTypeFilter::Archive => FileType::Archive,
+            FileTypeFilter::Audio => FileType::Audio,
             FileTypeFilter::Binary => FileType::Binary,
         }
     }
@@ -97,6 +409,8 @@ pub enum SortOrder {
     Size,
     /// Sort by file type
     Type,
+    /// Shuffle (a new order each run)
+    Random,
 }
 
 impl Args {
@@ -114,6 +428,17 @@ impl Args {
         }
     }
 
+    /// Get the extension filters, lowercase and without the dot
+    pub fn get_extensions(&self) -> Option<Vec<String>> {
+        let extensions: Vec<String> = self
+            .extensions
+            .iter()
+            .map(|ext| ext.trim().trim_start_matches('.').to_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .collect();
+        (!extensions.is_empty()).then_some(extensions)
+    }
+
     /// Parse a size string (e.g., "5MB", "100KB") into bytes
     pub fn parse_size(size_str: &str) -> Option<u64> {
         let size_str = size_str.trim().to_uppercase();
@@ -154,6 +479,14 @@ impl Args {
         self.max_size.as_ref().and_then(|s| Self::parse_size(s))
     }
 
+    /// Get the session goal, if one was requested
+    pub fn get_goal(&self) -> Option<SessionGoal> {
+        if let Some(ref size) = self.goal_free {
+            return Self::parse_size(size).map(SessionGoal::FreeBytes);
+        }
+        self.goal_files.map(SessionGoal::ReviewFiles)
+    }
+
     /// Validate the arguments and return any errors
     pub fn validate(&self) -> Result<(), String> {
         // Check if directory exists
@@ -190,6 +523,15 @@ impl Args {
             }
         }
 
+        if let Some(ref goal) = self.goal_free {
+            if Self::parse_size(goal).is_none() {
+                return Err(format!(
+                    "Invalid goal-free format: '{}'. Use format like '5MB', '100KB', '1GB'",
+                    goal
+                ));
+            }
+        }
+
         // Check min <= max if both specified
         if let (Some(min), Some(max)) = (self.get_min_size(), self.get_max_size()) {
             if min > max {
@@ -210,6 +552,8 @@ impl Args {
 pub struct AppConfig {
     pub directory: PathBuf,
     pub file_type_filters: Option<Vec<FileType>>,
+    /// Extensions to review (`--ext`), lowercase and without the dot
+    pub extensions: Option<Vec<String>>,
     pub dry_run: bool,
     pub sort_by: SortOrder,
     pub reverse: bool,
@@ -218,6 +562,33 @@ pub struct AppConfig {
     pub max_size: Option<u64>,
     pub skip_confirm: bool,
     pub show_welcome: bool,
+    pub triage: bool,
+    pub goal: Option<SessionGoal>,
+    pub background_apply: bool,
+    /// Apply trash decisions once their undo grace period is over (`--apply-on-decide`)
+    pub apply_on_decide: bool,
+    pub verify: bool,
+    /// Reuse the last scan's results for unchanged files (see `scan_index`)
+    pub index_cache: bool,
+    /// Filter preset to apply once the files are loaded
+    pub preset: Option<String>,
+    pub annotation: Option<String>,
+    /// Write a hash-chained journal (`--audit-chain`)
+    pub audit_chain: bool,
+    /// Review subdirectories as items too (`--dirs`)
+    pub include_dirs: bool,
+    /// Scan subdirectories for files too (`--recursive`)
+    pub recursive: bool,
+    /// Offer bulk trash suggestions before the review (`--easy-wins`)
+    pub easy_wins: bool,
+    /// Review groups of identical files before the rest (`--duplicates`)
+    pub duplicates: bool,
+    /// Offer to fix kept files' names when applying (`--normalize-names`)
+    pub normalize_names: bool,
+    /// Leave a FILE_TINDER_SUMMARY.md of what the session removed (`--leave-summary`)
+    pub leave_summary: bool,
+    /// Start in the size explorer (`--explore`)
+    pub explore: bool,
 }
 
 impl From<Args> for AppConfig {
@@ -225,6 +596,7 @@ impl From<Args> for AppConfig {
         AppConfig {
             directory: args.directory.clone(),
             file_type_filters: args.get_file_type_filters(),
+            extensions: args.get_extensions(),
             dry_run: args.dry_run,
             sort_by: args.sort_by,
             reverse: args.reverse,
@@ -233,6 +605,22 @@ impl From<Args> for AppConfig {
             max_size: args.get_max_size(),
             skip_confirm: args.yes,
             show_welcome: args.welcome,
+            triage: args.triage,
+            goal: args.get_goal(),
+            background_apply: args.background_apply,
+            apply_on_decide: args.apply_on_decide,
+            verify: args.verify,
+            index_cache: !args.no_index_cache,
+            preset: args.preset.clone(),
+            annotation: args.annotate.clone(),
+            audit_chain: args.audit_chain,
+            include_dirs: args.dirs,
+            recursive: args.recursive,
+            easy_wins: args.easy_wins,
+            duplicates: args.duplicates,
+            normalize_names: args.normalize_names,
+            leave_summary: args.leave_summary,
+            explore: args.explore,
         }
     }
 }
@@ -242,6 +630,7 @@ impl Default for AppConfig {
         AppConfig {
             directory: PathBuf::from("."),
             file_type_filters: None,
+            extensions: None,
             dry_run: false,
             sort_by: SortOrder::Date,
             reverse: false,
@@ -250,6 +639,22 @@ impl Default for AppConfig {
             max_size: None,
             skip_confirm: false,
             show_welcome: false,
+            triage: false,
+            goal: None,
+            background_apply: false,
+            apply_on_decide: false,
+            verify: false,
+            index_cache: true,
+            preset: None,
+            annotation: None,
+            audit_chain: false,
+            include_dirs: false,
+            recursive: false,
+            easy_wins: false,
+            duplicates: false,
+            normalize_names: false,
+            leave_summary: false,
+            explore: false,
         }
     }
 }
@@ -258,6 +663,42 @@ impl Default for AppConfig {
 mod tests {
     use super::*;
 
+    /// Args as clap parses them with no flags given
+    fn base_args() -> Args {
+        Args {
+            command: None,
+            directory: PathBuf::from("."),
+            file_types: vec![],
+            extensions: vec![],
+            dry_run: false,
+            sort_by: SortOrder::Date,
+            reverse: false,
+            show_hidden: false,
+            min_size: None,
+            max_size: None,
+            yes: false,
+            welcome: false,
+            triage: false,
+            goal_free: None,
+            goal_files: None,
+            background_apply: false,
+            verify: false,
+            no_index_cache: false,
+            preset: None,
+            annotate: None,
+            profile: None,
+            audit_chain: false,
+            dirs: false,
+            explore: false,
+            easy_wins: false,
+            duplicates: false,
+            apply_on_decide: false,
+            recursive: false,
+            normalize_names: false,
+            leave_summary: false,
+        }
+    }
+
     mod args_tests {
         use super::*;
 
@@ -310,18 +751,7 @@ mod tests {
 
         #[test]
         fn test_args_default_values() {
-            let args = Args {
-                directory: PathBuf::from("."),
-                file_types: vec![],
-                dry_run: false,
-                sort_by: SortOrder::Date,
-                reverse: false,
-                show_hidden: false,
-                min_size: None,
-                max_size: None,
-                yes: false,
-                welcome: false,
-            };
+            let args = base_args();
 
             assert_eq!(args.directory, PathBuf::from("."));
             assert!(!args.dry_run);
@@ -335,16 +765,8 @@ mod tests {
         #[test]
         fn test_args_yes_flag() {
             let args_with_yes = Args {
-                directory: PathBuf::from("."),
-                file_types: vec![],
-                dry_run: false,
-                sort_by: SortOrder::Date,
-                reverse: false,
-                show_hidden: false,
-                min_size: None,
-                max_size: None,
                 yes: true,
-                welcome: false,
+                ..base_args()
             };
 
             assert!(args_with_yes.yes);
@@ -353,33 +775,14 @@ mod tests {
         #[test]
         fn test_config_skip_confirm_propagation() {
             // Test that skip_confirm is properly set from args.yes
-            let args_no = Args {
-                directory: PathBuf::from("."),
-                file_types: vec![],
-                dry_run: false,
-                sort_by: SortOrder::Date,
-                reverse: false,
-                show_hidden: false,
-                min_size: None,
-                max_size: None,
-                yes: false,
-                welcome: false,
-            };
+            let args_no = base_args();
 
             let config: AppConfig = args_no.into();
             assert!(!config.skip_confirm);
 
             let args_yes = Args {
-                directory: PathBuf::from("."),
-                file_types: vec![],
-                dry_run: false,
-                sort_by: SortOrder::Date,
-                reverse: false,
-                show_hidden: false,
-                min_size: None,
-                max_size: None,
                 yes: true,
-                welcome: false,
+                ..base_args()
             };
 
             let config: AppConfig = args_yes.into();
@@ -394,18 +797,7 @@ mod tests {
 
         #[test]
         fn test_args_get_file_type_filters_empty() {
-            let args = Args {
-                directory: PathBuf::from("."),
-                file_types: vec![],
-                dry_run: false,
-                sort_by: SortOrder::Date,
-                reverse: false,
-                show_hidden: false,
-                min_size: None,
-                max_size: None,
-                yes: false,
-                welcome: false,
-            };
+            let args = base_args();
 
             assert!(args.get_file_type_filters().is_none());
         }
@@ -413,16 +805,8 @@ mod tests {
         #[test]
         fn test_args_get_file_type_filters_multiple() {
             let args = Args {
-                directory: PathBuf::from("."),
                 file_types: vec![FileTypeFilter::Text, FileTypeFilter::Image],
-                dry_run: false,
-                sort_by: SortOrder::Date,
-                reverse: false,
-                show_hidden: false,
-                min_size: None,
-                max_size: None,
-                yes: false,
-                welcome: false,
+                ..base_args()
             };
 
             let filters = args.get_file_type_filters().unwrap();
@@ -435,15 +819,7 @@ mod tests {
         fn test_args_validate_nonexistent_directory() {
             let args = Args {
                 directory: PathBuf::from("/nonexistent/path/12345"),
-                file_types: vec![],
-                dry_run: false,
-                sort_by: SortOrder::Date,
-                reverse: false,
-                show_hidden: false,
-                min_size: None,
-                max_size: None,
-                yes: false,
-                welcome: false,
+                ..base_args()
             };
 
             let result = args.validate();
@@ -454,16 +830,8 @@ mod tests {
         #[test]
         fn test_args_validate_invalid_size_format() {
             let args = Args {
-                directory: PathBuf::from("."),
-                file_types: vec![],
-                dry_run: false,
-                sort_by: SortOrder::Date,
-                reverse: false,
-                show_hidden: false,
                 min_size: Some("invalid".to_string()),
-                max_size: None,
-                yes: false,
-                welcome: false,
+                ..base_args()
             };
 
             let result = args.validate();
@@ -474,16 +842,9 @@ mod tests {
         #[test]
         fn test_args_validate_min_greater_than_max() {
             let args = Args {
-                directory: PathBuf::from("."),
-                file_types: vec![],
-                dry_run: false,
-                sort_by: SortOrder::Date,
-                reverse: false,
-                show_hidden: false,
                 min_size: Some("10MB".to_string()),
                 max_size: Some("1MB".to_string()),
-                yes: false,
-                welcome: false,
+                ..base_args()
             };
 
             let result = args.validate();
@@ -494,16 +855,9 @@ mod tests {
         #[test]
         fn test_args_validate_success() {
             let args = Args {
-                directory: PathBuf::from("."),
-                file_types: vec![],
-                dry_run: false,
-                sort_by: SortOrder::Date,
-                reverse: false,
-                show_hidden: false,
                 min_size: Some("1KB".to_string()),
                 max_size: Some("100MB".to_string()),
-                yes: false,
-                welcome: false,
+                ..base_args()
             };
 
             assert!(args.validate().is_ok());
@@ -524,8 +878,7 @@ mod tests {
                 show_hidden: true,
                 min_size: Some("1KB".to_string()),
                 max_size: Some("1MB".to_string()),
-                yes: false,
-                welcome: false,
+                ..base_args()
             };
 
             let config: AppConfig = args.into();
@@ -554,6 +907,234 @@ mod tests {
             assert!(config.file_type_filters.is_none());
         }
 
+        #[test]
+        fn test_app_config_triage_from_args() {
+            let args = Args::parse_from(["fswp", "--triage"]);
+            let config: AppConfig = args.into();
+            assert!(config.triage);
+            assert!(!AppConfig::default().triage);
+        }
+
+        #[test]
+        fn test_app_config_goal_from_args() {
+            let args = Args::parse_from(["fswp", "--goal-free", "5GB"]);
+            let config: AppConfig = args.into();
+            assert_eq!(
+                config.goal,
+                Some(SessionGoal::FreeBytes(5 * 1024 * 1024 * 1024))
+            );
+
+            let args = Args::parse_from(["fswp", "--goal-files", "100"]);
+            let config: AppConfig = args.into();
+            assert_eq!(config.goal, Some(SessionGoal::ReviewFiles(100)));
+
+            assert_eq!(AppConfig::default().goal, None);
+        }
+
+        #[test]
+        fn test_app_config_background_apply_from_args() {
+            let args = Args::parse_from(["fswp", "--background-apply"]);
+            let config: AppConfig = args.into();
+            assert!(config.background_apply);
+            assert!(!AppConfig::default().background_apply);
+        }
+
+        #[test]
+        fn test_app_config_explore_from_args() {
+            let args = Args::parse_from(["fswp", "--explore", "~/Downloads"]);
+            let config: AppConfig = args.into();
+            assert!(config.explore);
+            assert_eq!(config.directory, PathBuf::from("~/Downloads"));
+            assert!(!AppConfig::default().explore);
+        }
+
+        #[test]
+        fn test_app_config_apply_on_decide_from_args() {
+            let args = Args::parse_from(["fswp", "--apply-on-decide"]);
+            let config: AppConfig = args.into();
+            assert!(config.apply_on_decide);
+            assert!(!config.background_apply);
+            assert!(!AppConfig::default().apply_on_decide);
+        }
+
+        #[test]
+        fn test_app_config_recursive_from_args() {
+            let args = Args::parse_from(["fswp", "~/Downloads", "-R", "--sort", "size"]);
+            assert_eq!(args.sort_by, SortOrder::Size);
+            let config: AppConfig = args.into();
+            assert!(config.recursive);
+            assert!(!AppConfig::default().recursive);
+
+            // Reviewing subdirectories whole and descending into them don't mix
+            assert!(Args::try_parse_from(["fswp", "--recursive", "--dirs"]).is_err());
+        }
+
+        #[test]
+        fn test_app_config_extensions_from_args() {
+            let config: AppConfig = Args::parse_from(["fswp", "--ext", ".PDF,docx,"]).into();
+            assert_eq!(
+                config.extensions,
+                Some(vec!["pdf".to_string(), "docx".to_string()])
+            );
+            assert!(AppConfig::default().extensions.is_none());
+        }
+
+        #[test]
+        fn test_app_config_normalize_names_from_args() {
+            let config: AppConfig = Args::parse_from(["fswp", "--normalize-names"]).into();
+            assert!(config.normalize_names);
+            assert!(!AppConfig::default().normalize_names);
+        }
+
+        #[test]
+        fn test_app_config_verify_from_args() {
+            let args = Args::parse_from(["fswp", "--verify"]);
+            let config: AppConfig = args.into();
+            assert!(config.verify);
+            assert!(!AppConfig::default().verify);
+        }
+
+        #[test]
+        fn test_app_config_index_cache_from_args() {
+            let args = Args::parse_from(["fswp", "--no-index-cache"]);
+            let config: AppConfig = args.into();
+            assert!(!config.index_cache);
+            assert!(AppConfig::default().index_cache);
+        }
+
+        #[test]
+        fn test_app_config_preset_from_args() {
+            let args = Args::parse_from(["fswp", "--preset", "screenshots"]);
+            let config: AppConfig = args.into();
+            assert_eq!(config.preset, Some("screenshots".to_string()));
+            assert_eq!(AppConfig::default().preset, None);
+        }
+
+        #[test]
+        fn test_app_config_annotation_from_args() {
+            let args = Args::parse_from(["fswp", "--annotate", "ticket-1234"]);
+            let config: AppConfig = args.into();
+            assert_eq!(config.annotation, Some("ticket-1234".to_string()));
+            assert_eq!(AppConfig::default().annotation, None);
+        }
+
+        #[test]
+        fn test_every_option_has_env_var() {
+            use clap::CommandFactory;
+
+            let command = Args::command();
+            for arg in command.get_arguments() {
+                let id = arg.get_id().as_str();
+                if id == "help" || id == "version" {
+                    continue;
+                }
+                let env = arg.get_env().and_then(|env| env.to_str()).unwrap_or("");
+                assert!(env.starts_with("FILE_TINDER_"), "{} has no env var", id);
+            }
+        }
+
+        #[test]
+        fn test_replay_subcommand() {
+            let args = Args::parse_from(["fswp", "replay", "20240101-120000"]);
+            assert_eq!(
+                args.command,
+                Some(Command::Replay {
+                    session: "20240101-120000".to_string()
+                })
+            );
+
+            let args =
+                Args::parse_from(["fswp", "digest", "--period", "month", "--format", "html"]);
+            assert_eq!(
+                args.command,
+                Some(Command::Digest {
+                    period: DigestPeriod::Month,
+                    format: DigestFormat::Html,
+                    output: None
+                })
+            );
+            let args = Args::parse_from(["fswp", "audit", "20240101-120000"]);
+            assert_eq!(
+                args.command,
+                Some(Command::Audit {
+                    session: "20240101-120000".to_string()
+                })
+            );
+            // --profile applies to subcommands too, on either side of them
+            let args = Args::parse_from(["fswp", "--profile", "work", "purge"]);
+            assert_eq!(args.profile, Some("work".to_string()));
+            let args = Args::parse_from(["fswp", "digest", "--profile", "work"]);
+            assert_eq!(args.profile, Some("work".to_string()));
+            let args = Args::parse_from(["fswp", "purge", "--yes"]);
+            assert_eq!(args.command, Some(Command::Purge { yes: true }));
+            let args = Args::parse_from(["fswp", "empty-trash"]);
+            assert_eq!(args.command, Some(Command::EmptyTrash));
+            let args = Args::parse_from(["fswp", "restore"]);
+            assert_eq!(
+                args.command,
+                Some(Command::Restore {
+                    manifest: None,
+                    yes: false
+                })
+            );
+            let args = Args::parse_from(["fswp", "restore", "20240501-093000.json", "-y"]);
+            assert_eq!(
+                args.command,
+                Some(Command::Restore {
+                    manifest: Some(PathBuf::from("20240501-093000.json")),
+                    yes: true
+                })
+            );
+            let args = Args::parse_from(["fswp", "preview", "notes.txt", "--reveal"]);
+            assert_eq!(
+                args.command,
+                Some(Command::Preview {
+                    file: PathBuf::from("notes.txt"),
+                    reveal: true
+                })
+            );
+            let args = Args::parse_from(["fswp", "digest", "-o", "week.md"]);
+            assert_eq!(
+                args.command,
+                Some(Command::Digest {
+                    period: DigestPeriod::Week,
+                    format: DigestFormat::Markdown,
+                    output: Some(PathBuf::from("week.md"))
+                })
+            );
+
+            // A plain directory is still the positional argument
+            let args = Args::parse_from(["fswp", "/tmp"]);
+            assert_eq!(args.command, None);
+            assert_eq!(args.directory, PathBuf::from("/tmp"));
+        }
+
+        #[test]
+        fn test_goal_options_conflict() {
+            let result = Args::try_parse_from(["fswp", "--goal-free", "1GB", "--goal-files", "10"]);
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn test_validate_invalid_goal_free() {
+            let args = Args::parse_from(["fswp", "--goal-free", "lots"]);
+            assert!(args.validate().unwrap_err().contains("goal-free"));
+        }
+
+        #[test]
+        fn test_rules_test_subcommand() {
+            let args = Args::parse_from(["fswp", "rules", "test", "~/Downloads"]);
+            assert_eq!(
+                args.command,
+                Some(Command::Rules {
+                    command: RulesCommand::Test {
+                        directory: PathBuf::from("~/Downloads")
+                    }
+                })
+            );
+            assert_eq!(Args::parse_from(["fswp"]).command, None);
+        }
+
         #[test]
         fn test_sort_order_default() {
             assert_eq!(SortOrder::default(), SortOrder::Date);
@@ -0,0 +1,34 @@
+//! Module for copying text to the clipboard from the terminal
+//!
+//! The summary screen copies kept or trashed paths with an OSC 52 escape
+//! sequence: the terminal itself puts the text on the system clipboard, so it
+//! works over SSH and needs no X11/Wayland/macOS clipboard library. Terminals
+//! that don't support OSC 52 (or tmux without `set-clipboard on`) ignore it.
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use std::io::{self, Write};
+
+/// The OSC 52 sequence that sets the clipboard to `text`
+pub fn osc52(text: &str) -> String {
+    format!("\x1b]52;c;{}\x07", STANDARD.encode(text))
+}
+
+/// Asks the terminal behind `out` to put `text` on the clipboard
+pub fn copy(out: &mut impl Write, text: &str) -> io::Result<()> {
+    out.write_all(osc52(text).as_bytes())?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_encodes_text() {
+        assert_eq!(osc52("/a b\n/c"), "\x1b]52;c;L2EgYgovYw==\x07");
+        let mut out = Vec::new();
+        copy(&mut out, "").unwrap();
+        assert_eq!(out, b"\x1b]52;c;\x07");
+    }
+}
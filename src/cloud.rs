@@ -0,0 +1,121 @@
+//! Module for detecting cloud-synced directories
+//!
+//! Trashing a file inside a Dropbox, OneDrive, iCloud Drive or Google Drive
+//! folder deletes it on every synced device too, and those clients leave
+//! "online-only" placeholders behind: files with their full apparent size but no
+//! data on this disk, so trashing them frees nothing locally. Sync roots are
+//! recognized by the client's marker files or by their well-known folder names.
+
+use std::fmt;
+use std::path::Path;
+
+/// Files a sync client keeps at the root of the folder it syncs
+const MARKER_FILES: &[(&str, CloudProvider)] = &[
+    (".dropbox", CloudProvider::Dropbox),
+    (".dropbox.cache", CloudProvider::Dropbox),
+    (".tmp.drivedownload", CloudProvider::GoogleDrive),
+    (".tmp.driveupload", CloudProvider::GoogleDrive),
+];
+
+/// A file sync service
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloudProvider {
+    Dropbox,
+    OneDrive,
+    ICloud,
+    GoogleDrive,
+}
+
+impl fmt::Display for CloudProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CloudProvider::Dropbox => "Dropbox",
+            CloudProvider::OneDrive => "OneDrive",
+            CloudProvider::ICloud => "iCloud Drive",
+            CloudProvider::GoogleDrive => "Google Drive",
+        })
+    }
+}
+
+/// The service syncing `path`, if it or one of its parents is a sync root
+pub fn detect(path: &Path) -> Option<CloudProvider> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    path.ancestors().find_map(|dir| {
+        MARKER_FILES
+            .iter()
+            .find(|(marker, _)| dir.join(marker).exists())
+            .map(|(_, provider)| *provider)
+            .or_else(|| provider_for_folder(&dir.file_name()?.to_string_lossy()))
+    })
+}
+
+/// The service a sync root with this name belongs to.
+///
+/// Covers the clients' default folders ("Dropbox (Team)", "OneDrive - Contoso",
+/// "My Drive") and macOS File Provider roots under `~/Library/CloudStorage`
+/// ("OneDrive-Personal", "GoogleDrive-me@example.com"); iCloud Drive lives in
+/// `~/Library/Mobile Documents`.
+fn provider_for_folder(name: &str) -> Option<CloudProvider> {
+    let provider = if name == "Dropbox"
+        || name.starts_with("Dropbox (")
+        || name.starts_with("Dropbox-")
+    {
+        CloudProvider::Dropbox
+    } else if name == "OneDrive" || name.starts_with("OneDrive - ") || name.starts_with("OneDrive-")
+    {
+        CloudProvider::OneDrive
+    } else if matches!(name, "Mobile Documents" | "iCloud Drive" | "iCloudDrive") {
+        CloudProvider::ICloud
+    } else if matches!(name, "Google Drive" | "My Drive") || name.starts_with("GoogleDrive-") {
+        CloudProvider::GoogleDrive
+    } else {
+        return None;
+    };
+    Some(provider)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_provider_for_folder_names() {
+        let cases = [
+            ("Dropbox", Some(CloudProvider::Dropbox)),
+            ("Dropbox (Acme)", Some(CloudProvider::Dropbox)),
+            ("OneDrive - Contoso", Some(CloudProvider::OneDrive)),
+            ("OneDrive-Personal", Some(CloudProvider::OneDrive)),
+            ("Mobile Documents", Some(CloudProvider::ICloud)),
+            (
+                "GoogleDrive-me@example.com",
+                Some(CloudProvider::GoogleDrive),
+            ),
+            ("My Drive", Some(CloudProvider::GoogleDrive)),
+            ("Dropboxes", None),
+            ("OneDriveBackup", None),
+            ("Documents", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(provider_for_folder(name), expected, "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_detect_by_marker_or_parent_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let plain = temp_dir.path().join("plain");
+        fs::create_dir(&plain).unwrap();
+        assert_eq!(detect(&plain), None);
+
+        let synced = temp_dir.path().join("work");
+        fs::create_dir_all(synced.join("photos")).unwrap();
+        fs::write(synced.join(".dropbox"), "{}").unwrap();
+        assert_eq!(detect(&synced.join("photos")), Some(CloudProvider::Dropbox));
+
+        let nested = temp_dir.path().join("OneDrive - Contoso/Reports/2024");
+        fs::create_dir_all(&nested).unwrap();
+        assert_eq!(detect(&nested), Some(CloudProvider::OneDrive));
+    }
+}
@@ -1,20 +1,229 @@
 //! User configuration and preferences
 
+use crate::cli::Args;
+use crate::domain::{parse_age, DefaultDecision, FileType, QueueFilter, SortBy};
 use crate::error::{FileTinderError, Result};
+use crate::filesystem::StorageKind;
+use crate::hashing::HashAlgorithm;
+use crate::limits::ResourceLimits;
+use crate::tui::glyphs::Glyphs;
+use crate::tui::input::KeyLayout;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
 
-#[derive(Debug, Serialize, Deserialize, Default)]
+/// Default input poll interval while something is animating (milliseconds)
+pub const DEFAULT_TICK_RATE_MS: u64 = 100;
+/// Default input poll interval while the app is idle (milliseconds)
+pub const DEFAULT_IDLE_TICK_RATE_MS: u64 = 1000;
+/// Default time a trash decision can still be undone with `--apply-on-decide` (seconds)
+pub const DEFAULT_UNDO_GRACE_SECS: u64 = 5;
+/// Lower bound for either poll interval, to avoid a busy loop
+const MIN_TICK_RATE_MS: u64 = 10;
+/// Prefix of environment variables that override config file settings
+pub const ENV_PREFIX: &str = "FILE_TINDER_";
+
+/// A named filter bar expression and queue order, e.g. "big old archives"
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FilterPreset {
+    /// Filter bar expression, e.g. `ext:zip size>100MB age>1y`
+    pub filter: String,
+    /// Queue order ("date", "name", "size" or "type"); unset keeps the current order
+    pub sort: Option<SortBy>,
+}
+
+/// How long files matching a filter stay in the trash before `fswp purge` deletes them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionRule {
+    /// Filter bar expression, e.g. `type:image` or `ext:log`
+    pub filter: String,
+    /// Age like the filter's `age>` takes, e.g. `90d` or `1w`
+    pub keep: String,
+}
+
+/// Caps on the background workers, for running on a busy machine; unset means unlimited
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LimitsConfig {
+    /// Files the preview, search and copy-detection workers may hold open at once
+    pub max_open_files: Option<usize>,
+    /// Largest decoded preview image (width × height × 4 bytes), e.g. `256MB`
+    pub max_image_memory: Option<String>,
+    /// Bytes per second previews, copy detection and content search may read, e.g. `20MB`
+    pub max_read_rate: Option<String>,
+    /// Storage to tune parallel reads for when detection gets it wrong: `ssd`,
+    /// `hdd` or `network`
+    pub storage: Option<StorageKind>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct UserConfig {
     /// Whether the welcome dialog has been shown
     pub welcome_shown: bool,
+    /// Poll interval while a preview is loading and the spinner animates
+    pub tick_rate_ms: u64,
+    /// Poll interval when nothing is animating; key presses still wake the loop immediately
+    pub idle_tick_rate_ms: u64,
+    /// Move to the next file after keep/trash (toggle in-session with `a`)
+    pub auto_advance: bool,
+    /// Mark a file as seen when browsing away from it with the arrow keys
+    pub mark_seen_on_browse: bool,
+    /// Show what's left in the queue above the header ("214 left · 38 img · 1.9 GB")
+    pub queue_stats: bool,
+    /// With `--apply-on-decide`, how long a trash decision waits (and can be undone)
+    /// before the file goes to the system trash
+    pub undo_grace_secs: u64,
+    /// Per-type defaults keyed by type name, e.g. `{"Image": "suggest-trash", "Pdf": "always-ask"}`
+    pub default_decision: HashMap<String, DefaultDecision>,
+    /// Quick-move directories keyed by digit, e.g. `{"1": "~/Archive", "2": "~/Pictures/Keep"}`
+    pub move_targets: HashMap<String, String>,
+    /// Saved filters by name, applied with `--preset` or Tab in the filter bar
+    pub presets: HashMap<String, FilterPreset>,
+    /// Trash retention, first matching rule wins; files matching none are never purged
+    pub retention: Vec<RetentionRule>,
+    /// The recorded macro (`M` records, `@` replays), e.g. `["move:1", "next"]`
+    pub macro_steps: Vec<String>,
+    /// Resource caps, applied at startup
+    pub limits: LimitsConfig,
+    /// Browsing keys by printed letter (`letters`) or at their QWERTY positions
+    /// on an `azerty` or `dvorak` keyboard
+    pub key_layout: KeyLayout,
+    /// `ascii` draws plain-text stand-ins for symbols and emoji, `unicode` never
+    /// does, `auto` picks ASCII for the Linux console and non-UTF-8 locales
+    pub glyphs: Glyphs,
+    /// Hash for chained journals and report checksums: `blake3`, `xxhash128`
+    /// (fastest) or `sha256` (where an audit asks for it)
+    pub hash_algorithm: HashAlgorithm,
+}
+
+impl Default for UserConfig {
+    fn default() -> Self {
+        Self {
+            welcome_shown: false,
+            tick_rate_ms: DEFAULT_TICK_RATE_MS,
+            idle_tick_rate_ms: DEFAULT_IDLE_TICK_RATE_MS,
+            auto_advance: true,
+            mark_seen_on_browse: true,
+            queue_stats: false,
+            undo_grace_secs: DEFAULT_UNDO_GRACE_SECS,
+            default_decision: HashMap::new(),
+            move_targets: HashMap::new(),
+            presets: HashMap::new(),
+            retention: Vec::new(),
+            macro_steps: Vec::new(),
+            limits: LimitsConfig::default(),
+            key_layout: KeyLayout::default(),
+            glyphs: Glyphs::default(),
+            hash_algorithm: HashAlgorithm::default(),
+        }
+    }
 }
 
 impl UserConfig {
+    /// How long the event loop should wait for input before redrawing
+    pub fn poll_interval(&self, animating: bool) -> Duration {
+        let ms = if animating {
+            self.tick_rate_ms
+        } else {
+            self.idle_tick_rate_ms.max(self.tick_rate_ms)
+        };
+        Duration::from_millis(ms.max(MIN_TICK_RATE_MS))
+    }
+
+    /// Grace period before a trash decision is applied with `--apply-on-decide`
+    pub fn undo_grace(&self) -> Duration {
+        Duration::from_secs(self.undo_grace_secs)
+    }
+
+    /// Per-type defaults with the type names resolved; unknown names are skipped
+    pub fn default_decisions(&self) -> HashMap<FileType, DefaultDecision> {
+        self.default_decision
+            .iter()
+            .filter_map(|(name, decision)| Some((FileType::from_name(name)?, *decision)))
+            .collect()
+    }
+
+    /// Directory bound to digit key `slot` (1-9), with a leading `~` expanded
+    pub fn move_target(&self, slot: u8) -> Option<PathBuf> {
+        let target = self.move_targets.get(&slot.to_string())?.trim();
+        if target.is_empty() {
+            return None;
+        }
+        match target.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+                let home = dirs::home_dir()?;
+                Some(home.join(rest.trim_start_matches('/')))
+            }
+            _ => Some(PathBuf::from(target)),
+        }
+    }
+
+    /// Every configured quick-move target, in key order
+    pub fn move_target_list(&self) -> Vec<(u8, PathBuf)> {
+        (1..=9)
+            .filter_map(|slot| Some((slot, self.move_target(slot)?)))
+            .collect()
+    }
+
+    /// Preset names in alphabetical order
+    pub fn preset_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.presets.keys().map(String::as_str).collect();
+        names.sort_unstable();
+        names
+    }
+
+    /// The preset called `name`, with its filter checked
+    pub fn preset(&self, name: &str) -> Result<&FilterPreset> {
+        let preset = self.presets.get(name).ok_or_else(|| {
+            let known = self.preset_names();
+            FileTinderError::ConfigError(if known.is_empty() {
+                format!("No preset named '{}' (none are configured)", name)
+            } else {
+                format!("No preset named '{}' (have: {})", name, known.join(", "))
+            })
+        })?;
+        QueueFilter::parse(&preset.filter)?;
+        Ok(preset)
+    }
+
+    /// The configured resource caps with their sizes parsed; zero counts as a mistake
+    pub fn resource_limits(&self) -> Result<ResourceLimits> {
+        let size = |name: &str, value: &Option<String>| match value {
+            None => Ok(None),
+            Some(text) => match Args::parse_size(text) {
+                Some(bytes) if bytes > 0 => Ok(Some(bytes)),
+                _ => Err(FileTinderError::ConfigError(format!(
+                    "Invalid limits.{} '{}' (expected a size like 256MB)",
+                    name, text
+                ))),
+            },
+        };
+        if self.limits.max_open_files == Some(0) {
+            return Err(FileTinderError::ConfigError(
+                "Invalid limits.max_open_files 0 (must be at least 1)".to_string(),
+            ));
+        }
+        Ok(ResourceLimits {
+            max_open_files: self.limits.max_open_files,
+            max_image_bytes: size("max_image_memory", &self.limits.max_image_memory)?,
+            max_read_rate: size("max_read_rate", &self.limits.max_read_rate)?,
+            storage: self.limits.storage,
+        })
+    }
+
     /// Get the config file path (~/.config/fswp/config.json)
     pub fn config_path() -> Option<PathBuf> {
-        dirs::config_dir().map(|dir| dir.join("fswp").join("config.json"))
+        dirs::config_dir().map(|dir| crate::profile::scoped(dir.join("fswp")).join("config.json"))
+    }
+
+    /// True when no config file has been written yet (triggers the setup wizard)
+    pub fn is_first_run() -> bool {
+        Self::config_path().is_some_and(|path| !path.exists())
     }
 
     /// Load config from file, or create default if doesn't exist
@@ -23,11 +232,16 @@ impl UserConfig {
             FileTinderError::ConfigError("Could not determine config directory".to_string())
         })?;
 
+        Self::load_from(&path)
+    }
+
+    /// Load config from `path`, or the defaults if it doesn't exist
+    pub fn load_from(path: &Path) -> Result<Self> {
         if !path.exists() {
             return Ok(Self::default());
         }
 
-        let contents = fs::read_to_string(&path).map_err(|e| {
+        let contents = fs::read_to_string(path).map_err(|e| {
             FileTinderError::ConfigError(format!("Failed to read config file: {}", e))
         })?;
 
@@ -36,6 +250,142 @@ impl UserConfig {
         })
     }
 
+    /// Overrides settings from `FILE_TINDER_*` variables, e.g. `FILE_TINDER_AUTO_ADVANCE=false`.
+    ///
+    /// `FILE_TINDER_DEFAULT_DECISION` takes `Type=decision` pairs separated by commas,
+    /// and `FILE_TINDER_MOVE_TARGETS` takes `digit=directory` pairs the same way.
+    /// Variables that don't name a setting (such as the CLI's) are ignored; invalid
+    /// values are skipped and reported together in the error.
+    pub fn apply_env_overrides(
+        &mut self,
+        vars: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<()> {
+        let mut invalid = Vec::new();
+        for (key, value) in vars {
+            let Some(name) = key.strip_prefix(ENV_PREFIX) else {
+                continue;
+            };
+            let applied = match name {
+                "TICK_RATE_MS" => value.parse().map(|v| self.tick_rate_ms = v).is_ok(),
+                "IDLE_TICK_RATE_MS" => value.parse().map(|v| self.idle_tick_rate_ms = v).is_ok(),
+                "AUTO_ADVANCE" => parse_bool(&value).map(|v| self.auto_advance = v).is_some(),
+                "MARK_SEEN_ON_BROWSE" => parse_bool(&value)
+                    .map(|v| self.mark_seen_on_browse = v)
+                    .is_some(),
+                "QUEUE_STATS" => parse_bool(&value).map(|v| self.queue_stats = v).is_some(),
+                "UNDO_GRACE_SECS" => value.parse().map(|v| self.undo_grace_secs = v).is_ok(),
+                "DEFAULT_DECISION" => parse_default_decisions(&value)
+                    .map(|v| self.default_decision = v)
+                    .is_some(),
+                "MOVE_TARGETS" => parse_move_targets(&value)
+                    .map(|v| self.move_targets = v)
+                    .is_some(),
+                "MAX_OPEN_FILES" => value
+                    .parse()
+                    .map(|v| self.limits.max_open_files = Some(v))
+                    .is_ok(),
+                "MAX_IMAGE_MEMORY" => Args::parse_size(&value)
+                    .map(|_| self.limits.max_image_memory = Some(value.clone()))
+                    .is_some(),
+                "MAX_READ_RATE" => Args::parse_size(&value)
+                    .map(|_| self.limits.max_read_rate = Some(value.clone()))
+                    .is_some(),
+                "STORAGE" => serde_json::from_value(value.trim().into())
+                    .map(|v| self.limits.storage = Some(v))
+                    .is_ok(),
+                "KEY_LAYOUT" => serde_json::from_value(value.trim().into())
+                    .map(|v| self.key_layout = v)
+                    .is_ok(),
+                "GLYPHS" => serde_json::from_value(value.trim().into())
+                    .map(|v| self.glyphs = v)
+                    .is_ok(),
+                "HASH_ALGORITHM" => serde_json::from_value(value.trim().into())
+                    .map(|v| self.hash_algorithm = v)
+                    .is_ok(),
+                _ => true,
+            };
+            if !applied {
+                invalid.push(format!("{}={}", key, value));
+            }
+        }
+
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(FileTinderError::ConfigError(format!(
+                "Invalid environment override(s): {}",
+                invalid.join(", ")
+            )))
+        }
+    }
+
+    /// Takes over the settings that are safe to change mid-session and returns their names.
+    ///
+    /// `welcome_shown` is left alone since the app writes it itself.
+    pub fn apply_reload(&mut self, other: UserConfig) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.tick_rate_ms != other.tick_rate_ms
+            || self.idle_tick_rate_ms != other.idle_tick_rate_ms
+        {
+            self.tick_rate_ms = other.tick_rate_ms;
+            self.idle_tick_rate_ms = other.idle_tick_rate_ms;
+            changed.push("tick rate");
+        }
+        if self.auto_advance != other.auto_advance {
+            self.auto_advance = other.auto_advance;
+            changed.push("auto_advance");
+        }
+        if self.mark_seen_on_browse != other.mark_seen_on_browse {
+            self.mark_seen_on_browse = other.mark_seen_on_browse;
+            changed.push("mark_seen_on_browse");
+        }
+        if self.queue_stats != other.queue_stats {
+            self.queue_stats = other.queue_stats;
+            changed.push("queue_stats");
+        }
+        if self.undo_grace_secs != other.undo_grace_secs {
+            self.undo_grace_secs = other.undo_grace_secs;
+            changed.push("undo_grace_secs");
+        }
+        if self.default_decision != other.default_decision {
+            self.default_decision = other.default_decision;
+            changed.push("default_decision");
+        }
+        if self.move_targets != other.move_targets {
+            self.move_targets = other.move_targets;
+            changed.push("move_targets");
+        }
+        if self.presets != other.presets {
+            self.presets = other.presets;
+            changed.push("presets");
+        }
+        if self.macro_steps != other.macro_steps {
+            self.macro_steps = other.macro_steps;
+            changed.push("macro_steps");
+        }
+        if self.key_layout != other.key_layout {
+            self.key_layout = other.key_layout;
+            changed.push("key_layout");
+        }
+        if self.glyphs != other.glyphs {
+            self.glyphs = other.glyphs;
+            changed.push("glyphs");
+        }
+        if self.hash_algorithm != other.hash_algorithm {
+            self.hash_algorithm = other.hash_algorithm;
+            changed.push("hash_algorithm");
+        }
+        changed
+    }
+
+    /// Retention rules with their filters and ages parsed
+    pub fn retention_rules(&self) -> Result<Vec<(QueueFilter, chrono::Duration)>> {
+        self.retention
+            .iter()
+            .map(|rule| Ok((QueueFilter::parse(&rule.filter)?, parse_age(&rule.keep)?)))
+            .collect()
+    }
+
     /// Save config to file
     pub fn save(&self) -> Result<()> {
         let path = Self::config_path().ok_or_else(|| {
@@ -61,6 +411,77 @@ impl UserConfig {
     }
 }
 
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.trim().to_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses `Image=suggest-trash,Pdf=always-ask`
+fn parse_default_decisions(value: &str) -> Option<HashMap<String, DefaultDecision>> {
+    value
+        .split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (name, decision) = pair.split_once('=')?;
+            let decision = serde_json::from_value(decision.trim().into()).ok()?;
+            Some((name.trim().to_string(), decision))
+        })
+        .collect()
+}
+
+/// Parses `1=~/Archive,2=~/Pictures/Keep`; slots must be digits 1-9
+fn parse_move_targets(value: &str) -> Option<HashMap<String, String>> {
+    value
+        .split(',')
+        .filter(|pair| !pair.trim().is_empty())
+        .map(|pair| {
+            let (slot, dir) = pair.split_once('=')?;
+            let slot = slot.trim();
+            matches!(slot, "1" | "2" | "3" | "4" | "5" | "6" | "7" | "8" | "9")
+                .then(|| (slot.to_string(), dir.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Notices edits to the config file during a session by polling its modification time
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+}
+
+impl ConfigWatcher {
+    /// Watches the default config path
+    pub fn new() -> Option<Self> {
+        UserConfig::config_path().map(|path| Self::at(&path))
+    }
+
+    /// Watches `path`, treating its current state as already loaded
+    pub fn at(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            modified: modified_time(path),
+        }
+    }
+
+    /// Re-reads the config if the file changed since the last poll
+    pub fn poll(&mut self) -> Option<Result<UserConfig>> {
+        let modified = modified_time(&self.path);
+        if modified == self.modified {
+            return None;
+        }
+        self.modified = modified;
+        Some(UserConfig::load_from(&self.path))
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -75,9 +496,268 @@ mod tests {
     fn test_config_serialization() {
         let config = UserConfig {
             welcome_shown: true,
+            ..Default::default()
         };
         let json = serde_json::to_string(&config).unwrap();
         let deserialized: UserConfig = serde_json::from_str(&json).unwrap();
         assert_eq!(deserialized.welcome_shown, true);
     }
+
+    #[test]
+    fn test_config_missing_fields_use_defaults() {
+        // Config files written by older versions only contain welcome_shown
+        let config: UserConfig = serde_json::from_str(r#"{"welcome_shown": true}"#).unwrap();
+        assert!(config.welcome_shown);
+        assert_eq!(config.tick_rate_ms, DEFAULT_TICK_RATE_MS);
+        assert_eq!(config.idle_tick_rate_ms, DEFAULT_IDLE_TICK_RATE_MS);
+        assert!(config.auto_advance);
+        assert!(config.mark_seen_on_browse);
+    }
+
+    #[test]
+    fn test_config_default_decisions() {
+        let config: UserConfig = serde_json::from_str(
+            r#"{"default_decision": {"Image": "suggest-trash", "pdf": "always-ask", "Archive": "suggest-keep", "Video": "suggest-keep"}}"#,
+        )
+        .unwrap();
+        let defaults = config.default_decisions();
+
+        assert_eq!(defaults.len(), 3);
+        assert_eq!(
+            defaults.get(&FileType::Archive),
+            Some(&DefaultDecision::SuggestKeep)
+        );
+        assert_eq!(
+            defaults.get(&FileType::Image),
+            Some(&DefaultDecision::SuggestTrash)
+        );
+        assert_eq!(
+            defaults.get(&FileType::Pdf),
+            Some(&DefaultDecision::AlwaysAsk)
+        );
+    }
+
+    #[test]
+    fn test_config_move_targets() {
+        let config: UserConfig = serde_json::from_str(
+            r#"{"move_targets": {"1": "~/Archive", "2": "/srv/keep", "3": ""}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.move_target(1),
+            dirs::home_dir().map(|home| home.join("Archive"))
+        );
+        assert_eq!(config.move_target(2), Some(PathBuf::from("/srv/keep")));
+        assert_eq!(config.move_target(3), None);
+        assert_eq!(config.move_target(4), None);
+    }
+
+    #[test]
+    fn test_config_retention_rules() {
+        let config: UserConfig = serde_json::from_str(
+            r#"{"retention": [
+                {"filter": "type:image", "keep": "90d"},
+                {"filter": "ext:log", "keep": "1w"}
+            ]}"#,
+        )
+        .unwrap();
+        let rules = config.retention_rules().unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[0].1, chrono::Duration::days(90));
+        assert_eq!(rules[1].1, chrono::Duration::days(7));
+
+        let config: UserConfig =
+            serde_json::from_str(r#"{"retention": [{"filter": "ext:log", "keep": "soon"}]}"#)
+                .unwrap();
+        assert!(matches!(
+            config.retention_rules(),
+            Err(FileTinderError::InvalidFilter(_))
+        ));
+    }
+
+    #[test]
+    fn test_config_presets() {
+        let config: UserConfig = serde_json::from_str(
+            r#"{"presets": {
+                "screenshots": {"filter": "screenshot type:image"},
+                "big old archives": {"filter": "ext:zip size>100MB age>1y", "sort": "size"},
+                "broken": {"filter": "size>lots"}
+            }}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.preset_names(),
+            vec!["big old archives", "broken", "screenshots"]
+        );
+        let archives = config.preset("big old archives").unwrap();
+        assert_eq!(archives.sort, Some(SortBy::Size));
+        assert_eq!(config.preset("screenshots").unwrap().sort, None);
+        assert!(matches!(
+            config.preset("broken"),
+            Err(FileTinderError::InvalidFilter(_))
+        ));
+        assert!(config
+            .preset("missing")
+            .unwrap_err()
+            .to_string()
+            .contains("have: big old archives, broken, screenshots"));
+    }
+
+    #[test]
+    fn test_apply_reload_reports_changed_settings() {
+        let mut config = UserConfig {
+            welcome_shown: true,
+            ..Default::default()
+        };
+        let edited: UserConfig =
+            serde_json::from_str(r#"{"auto_advance": false, "tick_rate_ms": 50}"#).unwrap();
+
+        assert_eq!(
+            config.apply_reload(edited),
+            vec!["tick rate", "auto_advance"]
+        );
+        assert!(!config.auto_advance);
+        assert_eq!(config.tick_rate_ms, 50);
+        assert!(config.welcome_shown);
+        assert!(config
+            .apply_reload(UserConfig {
+                auto_advance: false,
+                tick_rate_ms: 50,
+                ..Default::default()
+            })
+            .is_empty());
+    }
+
+    #[test]
+    fn test_apply_env_overrides() {
+        let mut config = UserConfig::default();
+        let vars = [
+            ("FILE_TINDER_AUTO_ADVANCE", "off"),
+            ("FILE_TINDER_TICK_RATE_MS", "50"),
+            ("FILE_TINDER_UNDO_GRACE_SECS", "10"),
+            (
+                "FILE_TINDER_DEFAULT_DECISION",
+                "Image=suggest-trash, Pdf=always-ask",
+            ),
+            ("FILE_TINDER_MOVE_TARGETS", "1=/archive, 2 = /keep"),
+            ("FILE_TINDER_SORT", "size"),
+            ("FILE_TINDER_KEY_LAYOUT", "dvorak"),
+            ("FILE_TINDER_GLYPHS", "ascii"),
+            ("FILE_TINDER_HASH_ALGORITHM", "sha256"),
+            ("HOME", "/root"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        config.apply_env_overrides(vars).unwrap();
+        assert!(!config.auto_advance);
+        assert_eq!(config.tick_rate_ms, 50);
+        assert_eq!(config.key_layout, KeyLayout::Dvorak);
+        assert_eq!(config.glyphs, Glyphs::Ascii);
+        assert_eq!(config.hash_algorithm, HashAlgorithm::Sha256);
+        assert_eq!(config.undo_grace(), Duration::from_secs(10));
+        assert_eq!(
+            config.default_decisions().get(&FileType::Pdf),
+            Some(&DefaultDecision::AlwaysAsk)
+        );
+        assert_eq!(config.move_target(2), Some(PathBuf::from("/keep")));
+    }
+
+    #[test]
+    fn test_apply_env_overrides_reports_invalid_values() {
+        let mut config = UserConfig::default();
+        let vars = [
+            ("FILE_TINDER_MARK_SEEN_ON_BROWSE", "false"),
+            ("FILE_TINDER_IDLE_TICK_RATE_MS", "soon"),
+            ("FILE_TINDER_DEFAULT_DECISION", "Image=maybe"),
+            ("FILE_TINDER_MOVE_TARGETS", "0=/nowhere"),
+        ]
+        .map(|(k, v)| (k.to_string(), v.to_string()));
+
+        let err = config.apply_env_overrides(vars).unwrap_err().to_string();
+        assert!(err.contains("FILE_TINDER_IDLE_TICK_RATE_MS=soon"));
+        assert!(err.contains("FILE_TINDER_DEFAULT_DECISION=Image=maybe"));
+        assert!(err.contains("FILE_TINDER_MOVE_TARGETS=0=/nowhere"));
+        // Valid overrides still apply
+        assert!(!config.mark_seen_on_browse);
+        assert_eq!(config.idle_tick_rate_ms, DEFAULT_IDLE_TICK_RATE_MS);
+    }
+
+    #[test]
+    fn test_resource_limits_from_file_and_env() {
+        let mut config: UserConfig = serde_json::from_str(
+            r#"{"limits": {"max_open_files": 8, "max_image_memory": "256MB", "storage": "hdd"}}"#,
+        )
+        .unwrap();
+        let vars =
+            [("FILE_TINDER_MAX_READ_RATE", "20MB")].map(|(k, v)| (k.to_string(), v.to_string()));
+        config.apply_env_overrides(vars).unwrap();
+        assert_eq!(
+            config.resource_limits().unwrap(),
+            ResourceLimits {
+                max_open_files: Some(8),
+                max_image_bytes: Some(256 * 1024 * 1024),
+                max_read_rate: Some(20 * 1024 * 1024),
+                storage: Some(StorageKind::Hdd),
+            }
+        );
+        assert_eq!(
+            UserConfig::default().resource_limits().unwrap(),
+            ResourceLimits::default()
+        );
+
+        config.limits.max_read_rate = Some("fast".to_string());
+        let err = config.resource_limits().unwrap_err().to_string();
+        assert!(err.contains("limits.max_read_rate 'fast'"));
+        config.limits.max_read_rate = None;
+        config.limits.max_open_files = Some(0);
+        assert!(config.resource_limits().is_err());
+    }
+
+    #[test]
+    fn test_config_watcher_detects_edits() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        fs::write(&path, r#"{"auto_advance": true}"#).unwrap();
+        let mut watcher = ConfigWatcher::at(&path);
+        assert!(watcher.poll().is_none());
+
+        fs::write(&path, r#"{"auto_advance": false}"#).unwrap();
+        let file = fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(5))
+            .unwrap();
+        let reloaded = watcher.poll().unwrap().unwrap();
+        assert!(!reloaded.auto_advance);
+        assert!(watcher.poll().is_none());
+
+        fs::write(&path, "not json").unwrap();
+        file.set_modified(SystemTime::now() + Duration::from_secs(10))
+            .unwrap();
+        assert!(watcher.poll().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_poll_interval_idle_is_slower() {
+        let config = UserConfig::default();
+        assert_eq!(config.poll_interval(true), Duration::from_millis(100));
+        assert_eq!(config.poll_interval(false), Duration::from_millis(1000));
+    }
+
+    #[test]
+    fn test_poll_interval_clamped() {
+        let config = UserConfig {
+            tick_rate_ms: 0,
+            idle_tick_rate_ms: 5,
+            ..Default::default()
+        };
+        assert_eq!(config.poll_interval(true), Duration::from_millis(10));
+        assert_eq!(config.poll_interval(false), Duration::from_millis(10));
+
+        // Idle polling is never faster than the active tick rate
+        let config = UserConfig {
+            tick_rate_ms: 250,
+            idle_tick_rate_ms: 50,
+            ..Default::default()
+        };
+        assert_eq!(config.poll_interval(false), Duration::from_millis(250));
+    }
 }
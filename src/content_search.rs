@@ -0,0 +1,186 @@
+//! Module for searching file contents across the queue
+//!
+//! `/` asks for a string; every text file in the queue is searched for it on a
+//! few worker threads while the session stays responsive, and the queue is then
+//! narrowed to the files that mention it.
+
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Only the start of each file is searched, so one huge log can't stall the search
+pub const MAX_SEARCH_BYTES: u64 = 8 * 1024 * 1024;
+
+/// Bytes read at a time, so a read rate limit can pace the search
+const READ_CHUNK: usize = 64 * 1024;
+
+/// A search running in the background over (file index, path) pairs
+#[derive(Debug)]
+pub struct ContentSearch {
+    query: String,
+    total: usize,
+    done: Arc<AtomicUsize>,
+    cancel: Arc<AtomicBool>,
+    results: Receiver<usize>,
+    matches: Vec<usize>,
+    finished: bool,
+}
+
+impl ContentSearch {
+    /// Starts searching `files` for `query` (ASCII case-insensitive)
+    pub fn start(query: &str, files: Vec<(usize, PathBuf)>) -> Self {
+        let total = files.len();
+        let done = Arc::new(AtomicUsize::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, results) = mpsc::channel();
+        let needle = query.as_bytes().to_ascii_lowercase();
+        let pending = Arc::new(Mutex::new(files));
+
+        let workers = crate::limits::workers().min(total);
+        for _ in 0..workers {
+            let pending = Arc::clone(&pending);
+            let done = Arc::clone(&done);
+            let cancel = Arc::clone(&cancel);
+            let sender = sender.clone();
+            let needle = needle.clone();
+            thread::spawn(move || {
+                while !cancel.load(Ordering::Relaxed) {
+                    let Some((index, path)) = pending.lock().ok().and_then(|mut p| p.pop()) else {
+                        break;
+                    };
+                    if contains(&path, &needle, MAX_SEARCH_BYTES).unwrap_or(false) {
+                        let _ = sender.send(index);
+                    }
+                    // Release: a match is sent before its file counts as done
+                    done.fetch_add(1, Ordering::Release);
+                }
+            });
+        }
+
+        Self {
+            query: query.to_string(),
+            total,
+            done,
+            cancel,
+            results,
+            matches: Vec::new(),
+            finished: total == 0,
+        }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Collects results found so far; true once every file has been searched
+    pub fn poll(&mut self) -> bool {
+        self.matches.extend(self.results.try_iter());
+        if !self.finished && self.done.load(Ordering::Acquire) >= self.total {
+            // Workers count a file only after sending its match, so nothing is left behind
+            self.matches.extend(self.results.try_iter());
+            self.finished = true;
+        }
+        self.finished
+    }
+
+    /// (files searched, files to search)
+    pub fn progress(&self) -> (usize, usize) {
+        (
+            self.done.load(Ordering::Relaxed).min(self.total),
+            self.total,
+        )
+    }
+
+    /// Indices of the files that mention the query, in no particular order
+    pub fn matches(&self) -> &[usize] {
+        &self.matches
+    }
+}
+
+impl Drop for ContentSearch {
+    /// Workers stop after their current file once nobody is waiting for results
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Whether the first `limit` bytes of the file contain `needle` (already lowercase ASCII)
+pub fn contains(path: &Path, needle: &[u8], limit: u64) -> io::Result<bool> {
+    if needle.is_empty() {
+        return Ok(true);
+    }
+    let _permit = crate::limits::open_files(1);
+    let mut file = File::open(path)?.take(limit);
+    let mut data = Vec::new();
+    let mut chunk = vec![0; READ_CHUNK];
+    loop {
+        crate::limits::throttle(READ_CHUNK as u64);
+        match file.read(&mut chunk)? {
+            0 => break,
+            n => data.extend_from_slice(&chunk[..n]),
+        }
+    }
+    data.make_ascii_lowercase();
+    Ok(data.windows(needle.len()).any(|window| window == needle))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_contains_is_case_insensitive_and_bounded() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("notes.txt");
+        fs::write(&path, "Paid INVOICE 2021 in March").unwrap();
+
+        assert!(contains(&path, b"invoice 2021", MAX_SEARCH_BYTES).unwrap());
+        assert!(!contains(&path, b"invoice 2022", MAX_SEARCH_BYTES).unwrap());
+        // Past the limit doesn't count
+        assert!(!contains(&path, b"march", 10).unwrap());
+    }
+
+    #[test]
+    fn test_content_search_finds_matching_files() {
+        let dir = TempDir::new().unwrap();
+        let files: Vec<(usize, PathBuf)> = (0..12)
+            .map(|i| {
+                let path = dir.path().join(format!("file{}.txt", i));
+                let text = if i % 3 == 0 {
+                    "invoice 2021"
+                } else {
+                    "nothing here"
+                };
+                fs::write(&path, text).unwrap();
+                (i, path)
+            })
+            .collect();
+
+        let mut search = ContentSearch::start("Invoice 2021", files);
+        let started = Instant::now();
+        while !search.poll() {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let mut matches = search.matches().to_vec();
+        matches.sort();
+        assert_eq!(matches, vec![0, 3, 6, 9]);
+        assert_eq!(search.progress(), (12, 12));
+        assert_eq!(search.query(), "Invoice 2021");
+    }
+
+    #[test]
+    fn test_content_search_with_nothing_to_search() {
+        let mut search = ContentSearch::start("x", Vec::new());
+        assert!(search.poll());
+        assert!(search.matches().is_empty());
+    }
+}
@@ -0,0 +1,299 @@
+//! Module for the details behind a preview that failed
+//!
+//! The preview pane has room for the error and not much else. `e` on a failed
+//! preview opens the full picture: the error and what caused it, the file's first
+//! bytes (which say what the file really is, whatever its extension) and what the
+//! filesystem reports about it. `b` there writes the same details, plus the fswp
+//! version, platform and terminal, to
+//! `<data dir>/fswp/diagnostics/preview-<YYYYMMDD-HHMMSS>.txt` for a bug report.
+//! The bundle names the file's path, so it's worth a look before sharing.
+
+use crate::domain::{FileEntry, FileType};
+use crate::error::{FileTinderError, Result};
+use chrono::{DateTime, Local};
+use std::error::Error;
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Leading bytes of the file shown, enough for any common signature
+const MAGIC_LEN: usize = 16;
+
+/// Everything known about a failed preview
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreviewFailure {
+    pub path: PathBuf,
+    pub file_type: FileType,
+    /// The error as the preview reported it
+    pub error: String,
+    /// The file's first bytes, or why they couldn't be read
+    pub magic: std::result::Result<Vec<u8>, String>,
+    /// Filesystem metadata as label and value, or why it couldn't be read
+    pub stat: std::result::Result<Vec<(&'static str, String)>, String>,
+}
+
+impl PreviewFailure {
+    /// Reads what's needed from the disk for `file`, whose preview failed with `error`
+    pub fn collect(file: &FileEntry, error: &str) -> Self {
+        Self {
+            path: file.path.clone(),
+            file_type: file.file_type.clone(),
+            error: error.to_string(),
+            magic: read_magic(&file.path).map_err(|e| e.to_string()),
+            stat: fs::symlink_metadata(&file.path)
+                .map(|metadata| stat_lines(&file.path, &metadata))
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    /// The error split into what went wrong and each cause under it
+    pub fn chain(&self) -> Vec<&str> {
+        self.error
+            .split(": ")
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .collect()
+    }
+
+    /// The details as headed sections of plain lines, for the overlay and the bundle
+    pub fn sections(&self) -> Vec<(&'static str, Vec<String>)> {
+        let chain = self
+            .chain()
+            .into_iter()
+            .enumerate()
+            .map(|(depth, part)| match depth {
+                0 => part.to_string(),
+                _ => format!("{}caused by: {}", "  ".repeat(depth - 1), part),
+            })
+            .collect();
+        let file = vec![
+            format!("path: {}", self.path.display()),
+            format!("detected as: {:?}", self.file_type),
+        ];
+        let magic = match self.magic {
+            Ok(ref bytes) if bytes.is_empty() => vec!["(empty file)".to_string()],
+            Ok(ref bytes) => vec![hex(bytes), printable(bytes)],
+            Err(ref e) => vec![format!("unreadable: {}", e)],
+        };
+        let stat = match self.stat {
+            Ok(ref lines) => lines
+                .iter()
+                .map(|(label, value)| format!("{}: {}", label, value))
+                .collect(),
+            Err(ref e) => vec![format!("unavailable: {}", e)],
+        };
+        vec![
+            ("Error", chain),
+            ("File", file),
+            ("First bytes", magic),
+            ("Metadata", stat),
+        ]
+    }
+
+    /// The text written by `b`: the environment, then every section
+    pub fn to_bundle(&self, now: DateTime<Local>) -> String {
+        let mut out = format!(
+            "fswp preview diagnostics\n\
+             written: {}\nfswp: {}\nplatform: {} {}\nterminal: {}\n",
+            now.format("%Y-%m-%d %H:%M:%S %z"),
+            env!("CARGO_PKG_VERSION"),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            std::env::var("TERM").unwrap_or_else(|_| "(TERM not set)".to_string())
+        );
+        for (heading, lines) in self.sections() {
+            let _ = write!(out, "\n[{}]\n", heading);
+            for line in lines {
+                let _ = writeln!(out, "{}", line);
+            }
+        }
+        out
+    }
+
+    /// Writes the bundle to the diagnostics directory, named after the current time
+    pub fn save(&self) -> Result<PathBuf> {
+        let dir = diagnostics_dir().ok_or_else(|| {
+            FileTinderError::ConfigError("Could not determine data directory".to_string())
+        })?;
+        Ok(self.save_to(&dir, Local::now())?)
+    }
+
+    /// Writes the bundle dated `now` into `dir`, creating it if needed
+    pub fn save_to(&self, dir: &Path, now: DateTime<Local>) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!("preview-{}.txt", now.format("%Y%m%d-%H%M%S")));
+        fs::write(&path, self.to_bundle(now))?;
+        Ok(path)
+    }
+}
+
+/// Directory holding diagnostic bundles (`<data dir>/fswp/diagnostics`)
+pub fn diagnostics_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| crate::profile::scoped(dir.join("fswp")).join("diagnostics"))
+}
+
+/// `error` and each of its sources, joined with ": " as in the error's own message
+pub fn error_chain(error: &dyn Error) -> String {
+    let mut text = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let cause_text = cause.to_string();
+        if !text.contains(&cause_text) {
+            text.push_str(": ");
+            text.push_str(&cause_text);
+        }
+        source = cause.source();
+    }
+    text
+}
+
+/// Up to `MAGIC_LEN` bytes from the start of the file
+fn read_magic(path: &Path) -> io::Result<Vec<u8>> {
+    let _permit = crate::limits::open_files(1);
+    let mut bytes = Vec::with_capacity(MAGIC_LEN);
+    File::open(path)?
+        .take(MAGIC_LEN as u64)
+        .read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn stat_lines(path: &Path, metadata: &fs::Metadata) -> Vec<(&'static str, String)> {
+    let kind = if metadata.is_symlink() {
+        match fs::read_link(path) {
+            Ok(target) => format!("symlink to {}", target.display()),
+            Err(_) => "symlink".to_string(),
+        }
+    } else if metadata.is_dir() {
+        "directory".to_string()
+    } else if metadata.is_file() {
+        "regular file".to_string()
+    } else {
+        "special file".to_string()
+    };
+    let mut lines = vec![
+        ("kind", kind),
+        ("size", format!("{} bytes", metadata.len())),
+        ("permissions", permissions(metadata)),
+    ];
+    for (label, time) in [
+        ("modified", metadata.modified()),
+        ("accessed", metadata.accessed()),
+        ("created", metadata.created()),
+    ] {
+        if let Ok(time) = time {
+            lines.push((label, local_time(time)));
+        }
+    }
+    lines
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &fs::Metadata) -> String {
+    use std::os::unix::fs::MetadataExt;
+    format!(
+        "{:o} (owner uid {}, gid {})",
+        metadata.mode() & 0o7777,
+        metadata.uid(),
+        metadata.gid()
+    )
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &fs::Metadata) -> String {
+    if metadata.permissions().readonly() {
+        "read-only".to_string()
+    } else {
+        "read-write".to_string()
+    }
+}
+
+fn local_time(time: SystemTime) -> String {
+    DateTime::<Local>::from(time)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+/// Bytes as spaced hex pairs, e.g. "89 50 4e 47"
+fn hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Bytes as text, with anything unprintable shown as '.'
+fn printable(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&byte| match byte {
+            0x20..=0x7e => byte as char,
+            _ => '.',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_preview_failure_details_and_bundle() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("holiday.png");
+        fs::write(&path, b"GIF89a\x01\x00 not a png at all").unwrap();
+        let file = FileEntry::from_path(&path).unwrap();
+
+        let failure = PreviewFailure::collect(
+            &file,
+            "Image loading error: Format error decoding Png: Invalid PNG signature.",
+        );
+        assert_eq!(
+            failure.chain(),
+            [
+                "Image loading error",
+                "Format error decoding Png",
+                "Invalid PNG signature."
+            ]
+        );
+        assert_eq!(failure.magic.as_ref().unwrap().len(), MAGIC_LEN);
+
+        let sections = failure.sections();
+        assert_eq!(sections[0].1[1], "caused by: Format error decoding Png");
+        assert_eq!(sections[0].1[2], "  caused by: Invalid PNG signature.");
+        assert_eq!(
+            sections[2].1[0],
+            "47 49 46 38 39 61 01 00 20 6e 6f 74 20 61 20 70"
+        );
+        assert_eq!(sections[2].1[1], "GIF89a.. not a p");
+        assert!(sections[3]
+            .1
+            .contains(&format!("size: {} bytes", file.size)));
+
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap();
+        let bundle_dir = temp_dir.path().join("diagnostics");
+        let saved = failure.save_to(&bundle_dir, now).unwrap();
+        assert_eq!(saved, bundle_dir.join("preview-20240501-093000.txt"));
+        let text = fs::read_to_string(saved).unwrap();
+        assert!(text.starts_with("fswp preview diagnostics\n"));
+        assert!(text.contains(&format!("path: {}", path.display())));
+        assert!(text.contains("[First bytes]\n47 49 46"));
+
+        fs::remove_file(&path).unwrap();
+        let missing = PreviewFailure::collect(&file, "gone");
+        assert!(missing.magic.is_err() && missing.stat.is_err());
+    }
+
+    #[test]
+    fn test_error_chain_adds_unrepeated_sources() {
+        let inner = io::Error::new(io::ErrorKind::InvalidData, "bad header");
+        let outer = io::Error::other(FileTinderError::Io(inner));
+        assert_eq!(error_chain(&outer), "IO error: bad header");
+        let plain = io::Error::other("Preview timed out");
+        assert_eq!(error_chain(&plain), "Preview timed out");
+    }
+}
@@ -0,0 +1,447 @@
+//! Module for weekly / monthly digests of past sessions
+//!
+//! `fswp digest` reads every journal in the sessions directory, nets out undone
+//! decisions, and summarizes what was decided within the period: files reviewed,
+//! space freed, the kinds of files trashed most, and the directories where the
+//! most junk turned up. The summary renders as Markdown or as a standalone HTML page.
+//!
+//! Only trash that reached the system trash counts: dry-run sessions are left out,
+//! and so are trash decisions whose apply was cancelled or failed.
+
+use crate::error::Result;
+use crate::journal::{self, JournalAction, JournalRecord, SessionLog};
+use crate::tui::format_file_size;
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How many categories and directories a digest lists
+const TOP_COUNT: usize = 5;
+
+/// Files and bytes trashed under one label (an extension or a directory)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tally {
+    pub label: String,
+    pub files: usize,
+    pub bytes: u64,
+}
+
+/// What the sessions within a period decided, after undos
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Digest {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// Sessions with at least one decision in the period
+    pub sessions: usize,
+    pub kept: usize,
+    pub trashed: usize,
+    pub moved: usize,
+    /// Bytes of the trashed files
+    pub freed: u64,
+    /// Trashed files by extension, most bytes first
+    pub categories: Vec<Tally>,
+    /// Trashed files by parent directory, most bytes first
+    pub directories: Vec<Tally>,
+}
+
+impl Digest {
+    /// Summarizes the decisions in `logs` made between `since` and `until`
+    pub fn build(logs: &[SessionLog], since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        let range = since.timestamp()..=until.timestamp();
+        let mut digest = Self {
+            since,
+            until,
+            sessions: 0,
+            kept: 0,
+            trashed: 0,
+            moved: 0,
+            freed: 0,
+            categories: Vec::new(),
+            directories: Vec::new(),
+        };
+        let mut categories: HashMap<String, Tally> = HashMap::new();
+        let mut directories: HashMap<String, Tally> = HashMap::new();
+
+        for log in logs.iter().filter(|log| !log.is_dry_run()) {
+            let applied = applied_paths(&log.records);
+            let decisions: Vec<_> = net_decisions(&log.records)
+                .into_iter()
+                .filter(|record| range.contains(&record.time))
+                .filter(|record| {
+                    record.action != JournalAction::Trash || applied.contains(record.path.as_path())
+                })
+                .collect();
+            if decisions.is_empty() {
+                continue;
+            }
+            digest.sessions += 1;
+
+            for record in decisions {
+                match record.action {
+                    JournalAction::Keep => digest.kept += 1,
+                    JournalAction::Move => digest.moved += 1,
+                    JournalAction::Trash => {
+                        digest.trashed += 1;
+                        digest.freed += record.size;
+                        add(&mut categories, category(&record.path), record.size);
+                        let parent = record.path.parent().unwrap_or(Path::new(""));
+                        add(&mut directories, parent.display().to_string(), record.size);
+                    }
+                    JournalAction::Skip | JournalAction::Undo | JournalAction::Applied => {}
+                }
+            }
+        }
+
+        digest.categories = top(categories);
+        digest.directories = top(directories);
+        digest
+    }
+
+    pub fn reviewed(&self) -> usize {
+        self.kept + self.trashed + self.moved
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "fswp digest: {} to {}",
+            self.since.format("%Y-%m-%d"),
+            self.until.format("%Y-%m-%d")
+        )
+    }
+
+    fn summary(&self) -> [(&'static str, String); 3] {
+        [
+            ("Sessions", self.sessions.to_string()),
+            (
+                "Files reviewed",
+                format!(
+                    "{} ({} kept, {} trashed, {} moved)",
+                    self.reviewed(),
+                    self.kept,
+                    self.trashed,
+                    self.moved
+                ),
+            ),
+            ("Space freed", format_file_size(self.freed)),
+        ]
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n\n", self.title());
+        for (label, value) in self.summary() {
+            let _ = writeln!(out, "- **{}:** {}", label, value);
+        }
+        if self.reviewed() == 0 {
+            out.push_str("\nNo decisions were recorded in this period.\n");
+            return out;
+        }
+        for (heading, column, tallies) in self.tables() {
+            let _ = write!(
+                out,
+                "\n## {}\n\n| {} | Files trashed | Size |\n|---|---:|---:|\n",
+                heading, column
+            );
+            for tally in tallies {
+                let _ = writeln!(
+                    out,
+                    "| {} | {} | {} |",
+                    tally.label.replace('|', "\\|"),
+                    tally.files,
+                    format_file_size(tally.bytes)
+                );
+            }
+        }
+        out
+    }
+
+    pub fn to_html(&self) -> String {
+        let title = escape_html(&self.title());
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+             <style>body {{ font-family: sans-serif; max-width: 50em; margin: 2em auto; }} \
+             table {{ border-collapse: collapse; }} \
+             th, td {{ padding: 0.25em 0.75em; border-bottom: 1px solid #ddd; text-align: left; }}</style>\n\
+             </head>\n<body>\n<h1>{title}</h1>\n<ul>\n"
+        );
+        for (label, value) in self.summary() {
+            let _ = writeln!(
+                out,
+                "<li><strong>{}:</strong> {}</li>",
+                label,
+                escape_html(&value)
+            );
+        }
+        out.push_str("</ul>\n");
+        if self.reviewed() == 0 {
+            out.push_str("<p>No decisions were recorded in this period.</p>\n");
+        } else {
+            for (heading, column, tallies) in self.tables() {
+                let _ = writeln!(
+                    out,
+                    "<h2>{}</h2>\n<table>\n<tr><th>{}</th><th>Files trashed</th><th>Size</th></tr>",
+                    heading, column
+                );
+                for tally in tallies {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td>{}</td><td>{}</td><td>{}</td></tr>",
+                        escape_html(&tally.label),
+                        tally.files,
+                        format_file_size(tally.bytes)
+                    );
+                }
+                out.push_str("</table>\n");
+            }
+        }
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    fn tables(&self) -> [(&'static str, &'static str, &[Tally]); 2] {
+        [
+            ("Top junk categories", "Extension", &self.categories),
+            (
+                "Directories most in need of attention",
+                "Directory",
+                &self.directories,
+            ),
+        ]
+    }
+}
+
+/// Reads every journal in `dir`; a missing directory has no journals
+pub fn load_all(dir: &Path) -> Result<Vec<SessionLog>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+        .collect();
+    paths.sort();
+    paths.iter().map(|path| journal::load(path)).collect()
+}
+
+/// Each file's last decision in a session, with undone decisions dropped
+pub(crate) fn net_decisions(records: &[JournalRecord]) -> Vec<&JournalRecord> {
+    let mut latest: HashMap<&Path, &JournalRecord> = HashMap::new();
+    for record in records {
+        match record.action {
+            JournalAction::Undo => {
+                latest.remove(record.path.as_path());
+            }
+            JournalAction::Applied => {}
+            _ => {
+                latest.insert(&record.path, record);
+            }
+        }
+    }
+    latest.into_values().collect()
+}
+
+/// Paths whose trashed file reached the system trash
+fn applied_paths(records: &[JournalRecord]) -> HashSet<&Path> {
+    records
+        .iter()
+        .filter(|record| record.action == JournalAction::Applied)
+        .map(|record| record.path.as_path())
+        .collect()
+}
+
+/// Lowercase extension with its dot, e.g. ".log"
+fn category(path: &Path) -> String {
+    path.extension()
+        .map(|ext| format!(".{}", ext.to_string_lossy().to_lowercase()))
+        .unwrap_or_else(|| "(no extension)".to_string())
+}
+
+fn add(tallies: &mut HashMap<String, Tally>, label: String, bytes: u64) {
+    let tally = tallies.entry(label.clone()).or_insert(Tally {
+        label,
+        files: 0,
+        bytes: 0,
+    });
+    tally.files += 1;
+    tally.bytes += bytes;
+}
+
+/// The largest tallies by bytes, then file count, then label
+fn top(tallies: HashMap<String, Tally>) -> Vec<Tally> {
+    let mut tallies: Vec<Tally> = tallies.into_values().collect();
+    tallies.sort_by(|a, b| {
+        b.bytes
+            .cmp(&a.bytes)
+            .then(b.files.cmp(&a.files))
+            .then(a.label.cmp(&b.label))
+    });
+    tallies.truncate(TOP_COUNT);
+    tallies
+}
+
+pub(crate) fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{Decision, DecisionListener, FileEntry, FileType};
+    use crate::journal::{Journal, SessionInfo};
+    use chrono::{Duration, TimeZone};
+    use tempfile::TempDir;
+
+    fn record(time: i64, action: JournalAction, path: &str, size: u64) -> JournalRecord {
+        JournalRecord {
+            time,
+            action,
+            path: PathBuf::from(path),
+            size,
+            destination: None,
+            prev: None,
+        }
+    }
+
+    fn log(records: Vec<JournalRecord>) -> SessionLog {
+        SessionLog {
+            info: None,
+            records,
+        }
+    }
+
+    fn week() -> (DateTime<Utc>, DateTime<Utc>) {
+        let until = Utc.with_ymd_and_hms(2024, 1, 8, 0, 0, 0).unwrap();
+        (until - Duration::days(7), until)
+    }
+
+    #[test]
+    fn test_digest_nets_out_undos_and_old_decisions() {
+        let (since, until) = week();
+        let t = since.timestamp() + 60;
+        let logs = [
+            log(vec![
+                record(t, JournalAction::Trash, "/dl/setup.dmg", 500),
+                record(t, JournalAction::Trash, "/dl/a.log", 10),
+                record(t, JournalAction::Trash, "/dl/notes.txt", 40),
+                record(t + 1, JournalAction::Undo, "/dl/notes.txt", 40),
+                record(t + 2, JournalAction::Keep, "/dl/notes.txt", 40),
+                // Its apply was cancelled, so it never left
+                record(t + 2, JournalAction::Trash, "/dl/kept.zip", 800),
+                record(t + 3, JournalAction::Applied, "/dl/setup.dmg", 500),
+                record(t + 3, JournalAction::Applied, "/dl/a.log", 10),
+            ]),
+            log(vec![
+                record(t, JournalAction::Trash, "/var/b.LOG", 30),
+                record(t, JournalAction::Move, "/dl/photo.jpg", 99),
+                record(t + 1, JournalAction::Applied, "/var/b.LOG", 30),
+            ]),
+            // A dry run decided nothing for real
+            SessionLog {
+                info: Some(SessionInfo {
+                    dry_run: true,
+                    ..SessionInfo::current(None)
+                }),
+                records: vec![record(t, JournalAction::Keep, "/dl/rehearsal.txt", 1)],
+            },
+            // Before the period, so neither the session nor its decisions count
+            log(vec![record(
+                since.timestamp() - 1,
+                JournalAction::Trash,
+                "/old/big.iso",
+                9_000,
+            )]),
+        ];
+
+        let digest = Digest::build(&logs, since, until);
+        assert_eq!(digest.sessions, 2);
+        assert_eq!((digest.kept, digest.trashed, digest.moved), (1, 3, 1));
+        assert_eq!(digest.reviewed(), 5);
+        assert_eq!(digest.freed, 540);
+        assert_eq!(
+            digest.categories,
+            vec![
+                Tally {
+                    label: ".dmg".to_string(),
+                    files: 1,
+                    bytes: 500
+                },
+                Tally {
+                    label: ".log".to_string(),
+                    files: 2,
+                    bytes: 40
+                },
+            ]
+        );
+        assert_eq!(digest.directories[0].label, "/dl");
+        assert_eq!(digest.directories[0].bytes, 510);
+        assert_eq!(digest.directories[1].label, "/var");
+    }
+
+    #[test]
+    fn test_digest_renders_markdown_and_html() {
+        let (since, until) = week();
+        let t = since.timestamp() + 60;
+        let logs = [log(vec![
+            record(t, JournalAction::Trash, "/a|b/<x>.tmp", 2048),
+            record(t, JournalAction::Applied, "/a|b/<x>.tmp", 2048),
+        ])];
+        let digest = Digest::build(&logs, since, until);
+
+        let markdown = digest.to_markdown();
+        assert!(markdown.starts_with("# fswp digest: 2024-01-01 to 2024-01-08\n"));
+        assert!(markdown.contains("- **Files reviewed:** 1 (0 kept, 1 trashed, 0 moved)"));
+        assert!(markdown.contains("- **Space freed:** 2.0 KB"));
+        assert!(markdown.contains("| .tmp | 1 | 2.0 KB |"));
+        assert!(markdown.contains("| /a\\|b | 1 | 2.0 KB |"));
+
+        let html = digest.to_html();
+        assert!(html.contains("<h1>fswp digest: 2024-01-01 to 2024-01-08</h1>"));
+        assert!(html.contains("<td>/a|b</td>"));
+        assert!(!html.contains("<x>"));
+
+        let empty = Digest::build(&[], since, until);
+        assert!(empty
+            .to_markdown()
+            .contains("No decisions were recorded in this period."));
+    }
+
+    #[test]
+    fn test_load_all_reads_journals_in_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = FileEntry {
+            path: PathBuf::from("/tmp/junk.bin"),
+            name: "junk.bin".to_string(),
+            size: 7,
+            modified_date: Utc::now(),
+            file_type: FileType::Binary,
+            disk: Default::default(),
+        };
+        let info = SessionInfo::current(None);
+        let mut journal = Journal::create_at(&temp_dir.path().join("1.jsonl"), &info).unwrap();
+        journal.on_decision(0, &file, &Decision::Trash);
+        journal.on_applied(0, &file);
+        fs::write(temp_dir.path().join("notes.txt"), "not a journal").unwrap();
+
+        let logs = load_all(temp_dir.path()).unwrap();
+        assert_eq!(logs.len(), 1);
+        let digest = Digest::build(&logs, Utc::now() - Duration::days(1), Utc::now());
+        assert_eq!(digest.trashed, 1);
+        assert_eq!(digest.freed, 7);
+
+        assert!(load_all(&temp_dir.path().join("missing"))
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,127 @@
+//! Module for measuring directory items in the background
+//!
+//! With `--dirs`, a subdirectory's size is everything under it, which can take a
+//! while for something like a huge `node_modules`. Discovery lists directories
+//! without measuring them; a worker thread then walks them one at a time in queue
+//! order, reporting its counts every so often so the header can show
+//! "calculating… 1.2 GB so far". Each directory is measured once per session and
+//! the result is kept in `AppState::directories`.
+
+use crate::domain::DirectoryStats;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often a directory being walked reports its counts so far
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// (file index, counts so far, whether the walk is finished)
+pub type SizeUpdate = (usize, DirectoryStats, bool);
+
+/// Directories being measured on a worker thread
+#[derive(Debug)]
+pub struct DirSizer {
+    cancel: Arc<AtomicBool>,
+    updates: Receiver<SizeUpdate>,
+    remaining: usize,
+}
+
+impl DirSizer {
+    /// Starts measuring `dirs` (file index, path), in the given order
+    pub fn start(dirs: Vec<(usize, PathBuf)>) -> Self {
+        let remaining = dirs.len();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, updates) = mpsc::channel();
+        let stop = Arc::clone(&cancel);
+        thread::spawn(move || {
+            for (index, path) in dirs {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                let mut reported = Instant::now();
+                // An unreadable directory counts as empty rather than pending forever
+                let stats = DirectoryStats::walk(&path, |stats| {
+                    if reported.elapsed() >= PROGRESS_INTERVAL {
+                        reported = Instant::now();
+                        let _ = sender.send((index, *stats, false));
+                    }
+                    !stop.load(Ordering::Relaxed)
+                })
+                .unwrap_or_default();
+                let _ = sender.send((index, stats, true));
+            }
+        });
+
+        Self {
+            cancel,
+            updates,
+            remaining,
+        }
+    }
+
+    /// Updates since the last poll, oldest first
+    pub fn poll(&mut self) -> Vec<SizeUpdate> {
+        let updates: Vec<SizeUpdate> = self.updates.try_iter().collect();
+        let finished = updates.iter().filter(|(_, _, done)| *done).count();
+        self.remaining = self.remaining.saturating_sub(finished);
+        updates
+    }
+
+    /// Whether every directory has been measured (and polled)
+    pub fn is_finished(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+impl Drop for DirSizer {
+    /// The worker stops mid-walk once nobody is waiting for its counts
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dir_sizer_measures_each_directory() {
+        let temp_dir = TempDir::new().unwrap();
+        let small = temp_dir.path().join("small");
+        let nested = temp_dir.path().join("nested");
+        fs::create_dir(&small).unwrap();
+        fs::write(small.join("a"), b"12").unwrap();
+        fs::create_dir_all(nested.join("deep")).unwrap();
+        fs::write(nested.join("deep/b"), b"345").unwrap();
+        fs::write(nested.join("c"), b"6").unwrap();
+        let missing = temp_dir.path().join("missing");
+
+        let mut sizer = DirSizer::start(vec![(3, small), (5, nested), (7, missing)]);
+        let mut finished = Vec::new();
+        let started = Instant::now();
+        while !sizer.is_finished() {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            finished.extend(sizer.poll().into_iter().filter(|(_, _, done)| *done));
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        let sizes: Vec<(usize, u64, u64)> = finished
+            .iter()
+            .map(|(index, stats, _)| (*index, stats.bytes, stats.entries()))
+            .collect();
+        assert_eq!(sizes, [(3, 2, 1), (5, 4, 3), (7, 0, 0)]);
+        assert!(sizer.poll().is_empty());
+    }
+
+    #[test]
+    fn test_dir_sizer_with_nothing_to_measure() {
+        let mut sizer = DirSizer::start(Vec::new());
+        assert!(sizer.is_finished());
+        assert!(sizer.poll().is_empty());
+    }
+}
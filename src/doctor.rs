@@ -0,0 +1,495 @@
+//! Module for `fswp doctor`, a health check of the environment
+//!
+//! Most "previews don't work" reports come down to the terminal (no 24-bit
+//! color, mouse or clipboard passthrough in tmux), a missing Pdfium library, an
+//! unusable system trash or a config file that no longer parses. The doctor looks
+//! at each of these and says what to do about the ones that need attention.
+//!
+//! Image previews are drawn with Unicode half blocks in 24-bit color, so no
+//! terminal graphics protocol is needed; one that's detected is only reported.
+
+use crate::config::UserConfig;
+use crate::filesystem::StorageKind;
+use crate::trash_listing;
+use std::env;
+use std::ffi::OsStr;
+use std::fmt::Write;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How a check came out
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    /// Works, but something is degraded or worth knowing
+    Warn,
+    /// fswp won't work properly until this is fixed
+    Fail,
+}
+
+/// One line of the report
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    pub name: &'static str,
+    pub status: Status,
+    pub detail: String,
+    /// What to do about a warning or failure
+    pub fix: Option<String>,
+}
+
+impl Check {
+    fn ok(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Ok,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn warn(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Warn,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>, fix: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: Status::Fail,
+            detail: detail.into(),
+            fix: Some(fix.into()),
+        }
+    }
+}
+
+/// Runs every check against the current process's environment
+pub fn run() -> Vec<Check> {
+    let var = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+    let path = env::var_os("PATH").unwrap_or_default();
+    let mut checks = terminal_checks(std::io::IsTerminal::is_terminal(&std::io::stdin()), var);
+    checks.push(trash_check());
+    checks.push(staging_check());
+    checks.extend(config_checks(
+        UserConfig::config_path().as_deref(),
+        env::vars(),
+    ));
+    checks.push(pdf_check());
+    checks.push(opener_check(var, &path));
+    checks.push(storage_check(
+        crate::filesystem::detect_storage(Path::new(".")),
+        UserConfig::load()
+            .ok()
+            .and_then(|config| config.limits.storage),
+    ));
+    checks
+}
+
+/// Terminal capabilities, read from `TERM`, `COLORTERM` and friends through `var`
+pub fn terminal_checks(is_tty: bool, var: impl Fn(&str) -> Option<String>) -> Vec<Check> {
+    let term = var("TERM").unwrap_or_default();
+    let tmux = var("TMUX").is_some();
+    let mut checks = Vec::new();
+
+    checks.push(if !is_tty {
+        Check::fail(
+            "terminal",
+            "input is not a terminal",
+            "run fswp from an interactive terminal, not a script or pipe",
+        )
+    } else if term.is_empty() || term == "dumb" {
+        Check::fail(
+            "terminal",
+            format!("TERM is {:?}", term),
+            "set TERM to your terminal's type, e.g. TERM=xterm-256color",
+        )
+    } else {
+        Check::ok("terminal", format!("interactive ({})", term))
+    });
+
+    let colorterm = var("COLORTERM").unwrap_or_default();
+    checks.push(if matches!(colorterm.as_str(), "truecolor" | "24bit") {
+        Check::ok("colors", "24-bit color")
+    } else if term.contains("256color") {
+        Check::warn(
+            "colors",
+            "256 colors only as far as COLORTERM tells: image previews may look off",
+            "if your terminal does 24-bit color, export COLORTERM=truecolor",
+        )
+    } else {
+        Check::warn(
+            "colors",
+            "no 24-bit color detected: image previews may be garbled",
+            "use a terminal with 24-bit color and export COLORTERM=truecolor",
+        )
+    });
+
+    let protocol = if var("KITTY_WINDOW_ID").is_some() || term == "xterm-kitty" {
+        Some("kitty")
+    } else {
+        match var("TERM_PROGRAM").as_deref() {
+            Some("iTerm.app") => Some("iTerm2"),
+            Some("WezTerm") => Some("WezTerm"),
+            _ => None,
+        }
+    };
+    checks.push(Check::ok(
+        "graphics",
+        match protocol {
+            Some(name) => format!(
+                "{} graphics available; not needed, previews use half blocks",
+                name
+            ),
+            None => "not needed, previews use half blocks".to_string(),
+        },
+    ));
+
+    checks.push(if term == "linux" {
+        Check::warn(
+            "mouse",
+            "the Linux console doesn't report the mouse: the progress bar can't be clicked",
+            "use a terminal emulator for mouse support; every action has a key",
+        )
+    } else if tmux {
+        Check::warn(
+            "mouse",
+            "inside tmux, clicks only arrive with tmux's mouse mode on",
+            "add `set -g mouse on` to ~/.tmux.conf",
+        )
+    } else {
+        Check::ok("mouse", "click the progress bar to jump")
+    });
+
+    checks.push(if tmux {
+        Check::warn(
+            "clipboard",
+            "inside tmux, copying paths (k / t on the summary) needs clipboard passthrough",
+            "add `set -g set-clipboard on` to ~/.tmux.conf",
+        )
+    } else {
+        Check::ok(
+            "clipboard",
+            "copied with OSC 52, ignored by terminals without it",
+        )
+    });
+    checks
+}
+
+fn trash_check() -> Check {
+    match trash_listing::list() {
+        Ok(listing) => Check::ok(
+            "system trash",
+            format!("{} items in the trash", listing.files.len()),
+        ),
+        Err(e) if e.kind() == io::ErrorKind::Unsupported => {
+            Check::ok("system trash", "the Finder's trash")
+        }
+        Err(e) => Check::fail(
+            "system trash",
+            format!("can't read the trash: {}", e),
+            "check that your trash folder (e.g. ~/.local/share/Trash) exists and is writable",
+        ),
+    }
+}
+
+/// Trashed files wait in the temp directory until the session is applied
+fn staging_check() -> Check {
+    let dir = env::temp_dir().join(format!("fswp-doctor-{}", std::process::id()));
+    match fs::create_dir_all(&dir).and_then(|_| fs::remove_dir(&dir)) {
+        Ok(()) => Check::ok("staging", env::temp_dir().display().to_string()),
+        Err(e) => Check::fail(
+            "staging",
+            format!("can't write to {}: {}", env::temp_dir().display(), e),
+            "point TMPDIR at a writable directory on the same disk as your files",
+        ),
+    }
+}
+
+/// Whether the config at `path` loads and its settings (with the `FILE_TINDER_*`
+/// variables in `vars` applied) are valid
+pub fn config_checks(
+    path: Option<&Path>,
+    vars: impl IntoIterator<Item = (String, String)>,
+) -> Vec<Check> {
+    let Some(path) = path else {
+        return vec![Check::warn(
+            "config",
+            "no config directory",
+            "set HOME (or XDG_CONFIG_HOME) so settings can be saved",
+        )];
+    };
+    let fix = format!("fix or remove {}", path.display());
+    let mut config = match UserConfig::load_from(path) {
+        Ok(config) => config,
+        Err(e) => return vec![Check::fail("config", e.to_string(), fix)],
+    };
+    let environment = config.apply_env_overrides(vars).err().map(|e| {
+        Check::warn(
+            "environment",
+            e.to_string(),
+            "fix or unset the FILE_TINDER_* variables named above",
+        )
+    });
+
+    let mut problems = Vec::new();
+    if let Err(e) = config.resource_limits() {
+        problems.push(e.to_string());
+    }
+    if let Err(e) = config.retention_rules() {
+        problems.push(e.to_string());
+    }
+    for name in config.preset_names() {
+        if let Err(e) = config.preset(name) {
+            problems.push(format!("preset '{}': {}", name, e));
+        }
+    }
+    let config = if !problems.is_empty() {
+        Check::fail("config", problems.join("; "), fix)
+    } else if path.exists() {
+        Check::ok("config", path.display().to_string())
+    } else {
+        Check::ok(
+            "config",
+            format!("{} not created yet, using defaults", path.display()),
+        )
+    };
+    std::iter::once(config).chain(environment).collect()
+}
+
+/// What the current directory is stored on, and so how many files are read at
+/// once; `configured` is `limits.storage`, which wins over `detected`
+pub fn storage_check(detected: Option<StorageKind>, configured: Option<StorageKind>) -> Check {
+    let (storage, source) = match (configured, detected) {
+        (Some(storage), _) => (storage, "from limits.storage"),
+        (None, Some(storage)) => (storage, "detected"),
+        (None, None) => {
+            return Check::ok(
+                "storage",
+                "not detected: up to 4 files read at once (set limits.storage to tune)",
+            )
+        }
+    };
+    let workers = storage.workers();
+    Check::ok(
+        "storage",
+        format!(
+            "{} ({}): {} file{} read at once",
+            storage,
+            source,
+            workers,
+            if workers == 1 { "" } else { "s" }
+        ),
+    )
+}
+
+fn pdf_check() -> Check {
+    if crate::preview::is_pdfium_available() {
+        Check::ok("PDF previews", "Pdfium library found")
+    } else {
+        Check::warn(
+            "PDF previews",
+            "Pdfium library not found: PDFs get no preview",
+            "install libpdfium system-wide or put it in ./lib next to fswp",
+        )
+    }
+}
+
+/// What `o` opens files with: `$VISUAL` / `$EDITOR`, else the desktop's opener
+fn opener_check(var: impl Fn(&str) -> Option<String>, path: &OsStr) -> Check {
+    if let Some(editor) = var("VISUAL").or_else(|| var("EDITOR")) {
+        let program = editor.split_whitespace().next().unwrap_or_default();
+        return if find_program(program, path).is_some() {
+            Check::ok("opener", format!("editor {}", editor))
+        } else {
+            Check::warn(
+                "opener",
+                format!("editor {:?} is not on PATH", program),
+                "point EDITOR at an installed editor, or unset it to use the default app",
+            )
+        };
+    }
+    if !cfg!(unix) || cfg!(target_os = "macos") {
+        return Check::ok("opener", "the default application");
+    }
+    match find_program("xdg-open", path) {
+        Some(_) => Check::ok("opener", "xdg-open (the default application)"),
+        None => Check::warn(
+            "opener",
+            "neither EDITOR nor xdg-open is available: `o` can't open files",
+            "install xdg-utils or set EDITOR",
+        ),
+    }
+}
+
+/// Where `program` is found: itself when it contains a path separator, else the
+/// first match in the `path` list (with `.exe` on Windows)
+pub fn find_program(program: &str, path: &OsStr) -> Option<PathBuf> {
+    if program.is_empty() {
+        return None;
+    }
+    let candidate = Path::new(program);
+    if candidate.components().count() > 1 {
+        return candidate.is_file().then(|| candidate.to_path_buf());
+    }
+    env::split_paths(path).find_map(|dir| {
+        let exe = dir.join(program);
+        if exe.is_file() {
+            return Some(exe);
+        }
+        let exe = dir.join(format!("{}.exe", program));
+        (cfg!(windows) && exe.is_file()).then_some(exe)
+    })
+}
+
+/// The report printed by `fswp doctor`
+pub fn render(checks: &[Check]) -> String {
+    let width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for check in checks {
+        let mark = match check.status {
+            Status::Ok => "✓",
+            Status::Warn => "!",
+            Status::Fail => "✗",
+        };
+        let _ = writeln!(
+            out,
+            "{} {:<width$}  {}",
+            mark,
+            check.name,
+            check.detail,
+            width = width
+        );
+        if let Some(ref fix) = check.fix {
+            let _ = writeln!(out, "  {:<width$}  → {}", "", fix, width = width);
+        }
+    }
+    let count = |status| checks.iter().filter(|c| c.status == status).count();
+    let (warnings, failures) = (count(Status::Warn), count(Status::Fail));
+    out.push('\n');
+    if warnings + failures == 0 {
+        out.push_str("Everything looks fine.\n");
+    } else {
+        let _ = writeln!(
+            out,
+            "{} warning{}, {} problem{}",
+            warnings,
+            if warnings == 1 { "" } else { "s" },
+            failures,
+            if failures == 1 { "" } else { "s" }
+        );
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    fn terminal(is_tty: bool, vars: &[(&str, &str)]) -> HashMap<&'static str, Status> {
+        let vars: HashMap<String, String> = vars
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        terminal_checks(is_tty, |name| vars.get(name).cloned())
+            .into_iter()
+            .map(|check| (check.name, check.status))
+            .collect()
+    }
+
+    #[test]
+    fn test_terminal_checks() {
+        let good = terminal(
+            true,
+            &[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")],
+        );
+        assert!(good.values().all(|&status| status == Status::Ok));
+
+        let piped = terminal(false, &[("TERM", "xterm-256color")]);
+        assert_eq!(piped["terminal"], Status::Fail);
+        assert_eq!(piped["colors"], Status::Warn);
+
+        let tmux = terminal(
+            true,
+            &[
+                ("TERM", "tmux-256color"),
+                ("TMUX", "/tmp/tmux-1000/default,1,0"),
+            ],
+        );
+        assert_eq!(tmux["mouse"], Status::Warn);
+        assert_eq!(tmux["clipboard"], Status::Warn);
+
+        let console = terminal(true, &[("TERM", "linux")]);
+        assert_eq!(console["mouse"], Status::Warn);
+        assert_eq!(
+            terminal(true, &[("TERM", "dumb")])["terminal"],
+            Status::Fail
+        );
+    }
+
+    #[test]
+    fn test_storage_check() {
+        let check = storage_check(Some(StorageKind::Ssd), Some(StorageKind::Hdd));
+        assert_eq!(
+            check.detail,
+            "HDD (from limits.storage): 1 file read at once"
+        );
+        let check = storage_check(Some(StorageKind::Network), None);
+        assert_eq!(check.detail, "network (detected): 4 files read at once");
+        assert!(storage_check(None, None).detail.starts_with("not detected"));
+    }
+
+    #[test]
+    fn test_config_checks() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.json");
+        let checks = config_checks(Some(&path), Vec::new());
+        assert_eq!(checks[0].status, Status::Ok);
+
+        fs::write(
+            &path,
+            r#"{"limits": {"max_open_files": 0}, "presets": {"big": {"filter": "size>"}}}"#,
+        )
+        .unwrap();
+        let checks = config_checks(
+            Some(&path),
+            [("FILE_TINDER_AUTO_ADVANCE".to_string(), "maybe".to_string())],
+        );
+        assert_eq!(checks[0].status, Status::Fail);
+        assert!(checks[0].detail.contains("preset 'big'"));
+        assert_eq!(checks[1].name, "environment");
+
+        fs::write(&path, "{ not json").unwrap();
+        let checks = config_checks(Some(&path), Vec::new());
+        assert_eq!(checks.len(), 1);
+        assert!(checks[0].fix.as_deref().unwrap().contains("config.json"));
+    }
+
+    #[test]
+    fn test_find_program_and_report() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("vim"), "").unwrap();
+        let path = env::join_paths([dir.path()]).unwrap();
+        assert_eq!(find_program("vim", &path), Some(dir.path().join("vim")));
+        assert_eq!(find_program("emacs", &path), None);
+        assert_eq!(find_program("", &path), None);
+
+        let report = render(&[
+            Check::ok("colors", "24-bit color"),
+            Check::fail("config", "bad", "fix it"),
+        ]);
+        assert_eq!(
+            report,
+            "✓ colors  24-bit color\n✗ config  bad\n          → fix it\n\n0 warnings, 1 problem\n"
+        );
+    }
+}
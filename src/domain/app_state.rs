@@ -1,23 +1,134 @@
-use super::{Decision, FileEntry};
+use super::{
+    compare_files, is_sidecar_of, Decision, DecisionEngine, DefaultDecision, DirectorySize,
+    DirectoryStats, FileEntry, FileType, NamePattern, QueueFilter, SessionGoal, SortBy,
+};
+use crate::cloud::CloudProvider;
+use crate::permissions::FileAccess;
+use crate::sensitive::{self, Sensitivity};
+use crate::usage::UsageInfo;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How long a notice stays in the header
+const NOTICE_DURATION: Duration = Duration::from_secs(4);
+
+/// Which pass of the review the session is in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReviewPhase {
+    /// Metadata-only pass: keep, trash or mark unsure without loading previews
+    Triage,
+    /// Full previews (the only pass unless triage mode is enabled)
+    Detail,
+    /// Back over the files skipped earlier, offered from the summary
+    Skipped,
+}
+
+/// A temporary subset of the queue (e.g. content search results) and the full
+/// queue to go back to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Narrowing {
+    /// What the subset is, shown in the header (e.g. `"invoice 2021"`)
+    pub label: String,
+    full_queue: Vec<usize>,
+}
 
 #[derive(Debug)]
 pub struct AppState {
-    pub files: Vec<FileEntry>,
+    /// The same list the engine holds, not a copy
+    pub files: Arc<Vec<FileEntry>>,
+    /// Review order as indices into `files`; `current_index` is a position in this queue
+    pub queue: Vec<usize>,
     pub current_index: usize,
-    pub decisions_stack: Vec<(usize, Decision)>,
+    /// Owner of the session's decisions; everything decision-related is queried from it
+    pub engine: DecisionEngine,
+    /// Files the user has looked at, tracked separately from decisions
+    pub seen: HashSet<usize>,
+    /// Whether keep/trash moves on to the next file automatically
+    pub auto_advance: bool,
+    /// Whether the header shows what's left to review (`queue_stats` in the config)
+    pub show_queue_stats: bool,
+    /// Quick-move targets listed in the footer and help (`move_targets` in the config)
+    pub move_targets: Vec<(u8, PathBuf)>,
+    pub phase: ReviewPhase,
+    /// Files marked unsure during triage, revisited in the detail pass
+    pub unsure: HashSet<usize>,
+    pub goal: Option<SessionGoal>,
+    /// Set once the goal-reached prompt has been shown, so it only appears once
+    pub goal_prompted: bool,
+    /// Last-used info by file index, filled in as files are shown
+    pub usage: HashMap<usize, UsageInfo>,
+    /// Ownership and trash-ability by file index, checked at startup
+    pub access: HashMap<usize, FileAccess>,
+    /// Filesystem type when the session runs in degraded mode on a network mount
+    pub degraded_fs: Option<String>,
+    /// Sync service of the scanned directory; trashing there reaches other devices
+    pub cloud_sync: Option<CloudProvider>,
+    /// What a background worker is still doing, e.g. "comparing copies 3/40"
+    pub background_work: Option<String>,
+    /// Per-type defaults from the config, shown as suggestions
+    pub default_decisions: HashMap<FileType, DefaultDecision>,
+    /// Byte-identical download copies (`name (1).ext`): copy index → original index
+    pub copies: HashMap<usize, usize>,
+    /// Why a file was suggested for trash, by file index (the easy-win rule it matched)
+    pub reasons: HashMap<usize, String>,
+    /// Set while `queue` only shows part of the files
+    pub narrowing: Option<Narrowing>,
+    /// Short-lived message shown in the header (e.g. after a config reload)
+    pub notice: Option<(String, Instant)>,
+    /// Measured sizes of directory items by file index, filled in in the background
+    pub directories: HashMap<usize, DirectorySize>,
+    /// Sensitive content found in previews, by file index, filled in as files are shown
+    pub sensitive: HashMap<usize, Sensitivity>,
+    /// Files whose redacted preview lines are shown anyway (`R`)
+    pub revealed: HashSet<usize>,
 }
 
 impl AppState {
     pub fn new(files: Vec<FileEntry>) -> Self {
+        let queue = (0..files.len()).collect();
+        let files = Arc::new(files);
         Self {
+            engine: DecisionEngine::with_files(Arc::clone(&files)),
             files,
+            queue,
             current_index: 0,
-            decisions_stack: Vec::new(),
+            seen: HashSet::new(),
+            auto_advance: true,
+            show_queue_stats: false,
+            move_targets: Vec::new(),
+            phase: ReviewPhase::Detail,
+            unsure: HashSet::new(),
+            goal: None,
+            goal_prompted: false,
+            usage: HashMap::new(),
+            access: HashMap::new(),
+            degraded_fs: None,
+            cloud_sync: None,
+            background_work: None,
+            default_decisions: HashMap::new(),
+            copies: HashMap::new(),
+            reasons: HashMap::new(),
+            narrowing: None,
+            notice: None,
+            directories: HashMap::new(),
+            sensitive: HashMap::new(),
+            revealed: HashSet::new(),
+        }
+    }
+
+    /// Creates a state that starts with a metadata-only triage pass
+    pub fn new_triage(files: Vec<FileEntry>) -> Self {
+        Self {
+            phase: ReviewPhase::Triage,
+            ..Self::new(files)
         }
     }
 
     pub fn next(&mut self) {
-        if self.current_index < self.files.len().saturating_sub(1) {
+        if self.current_index < self.queue.len().saturating_sub(1) {
             self.current_index += 1;
         }
     }
@@ -28,16 +139,626 @@ impl AppState {
         }
     }
 
+    /// Jumps to a queue position, clamped to the last file
+    pub fn goto(&mut self, position: usize) {
+        self.current_index = position.min(self.queue.len().saturating_sub(1));
+    }
+
+    /// Index into `files` of the file at the current queue position
+    pub fn current_file_index(&self) -> Option<usize> {
+        self.queue.get(self.current_index).copied()
+    }
+
     pub fn current_file(&self) -> Option<&FileEntry> {
-        self.files.get(self.current_index)
+        self.current_file_index().and_then(|i| self.files.get(i))
+    }
+
+    /// Decisions so far as (file index, decision), oldest first
+    pub fn decisions(&self) -> &[(usize, Decision)] {
+        &self.engine.decisions
+    }
+
+    /// Decides on the current file through the engine.
+    ///
+    /// An already decided file is refused (e.g. a second key press with
+    /// auto-advance off); undo first to change it.
+    pub fn record_decision(&mut self, decision: Decision) -> io::Result<()> {
+        let index = self
+            .current_file_index()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No file to decide on"))?;
+        if self.is_decided(index) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Already decided; undo to change it",
+            ));
+        }
+        self.engine.record_decision(index, decision)
+    }
+
+    /// Changes the current file's earlier decision (see `DecisionEngine::change_decision`).
+    ///
+    /// Returns the replaced decision and how the projected freed space moved, in bytes.
+    pub fn change_decision(&mut self, decision: Decision) -> io::Result<(Decision, i64)> {
+        let index = self
+            .current_file_index()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No file to decide on"))?;
+        self.change_decision_of(index, decision)
+    }
+
+    /// Changes the earlier decision on the file at `index`, like `change_decision`
+    pub fn change_decision_of(
+        &mut self,
+        index: usize,
+        decision: Decision,
+    ) -> io::Result<(Decision, i64)> {
+        let before = self.freed_bytes();
+        let old = self.engine.change_decision(index, decision)?;
+        Ok((old, self.freed_bytes() as i64 - before as i64))
+    }
+
+    /// The latest decision on the file at `index`, if any
+    pub fn decision_of(&self, index: usize) -> Option<&Decision> {
+        self.decisions()
+            .iter()
+            .rev()
+            .find(|(i, _)| *i == index)
+            .map(|(_, decision)| decision)
+    }
+
+    /// Original paths of the files whose current decision is `decision`, in queue order
+    pub fn decided_paths(&self, decision: &Decision) -> Vec<&Path> {
+        self.full_queue()
+            .iter()
+            .filter(|&&index| self.decision_of(index) == Some(decision))
+            .map(|&index| self.files[index].path.as_path())
+            .collect()
+    }
+
+    /// Files decided as trash that are still staged, in queue order
+    pub fn staged_trash(&self) -> Vec<usize> {
+        self.full_queue()
+            .iter()
+            .copied()
+            .filter(|&index| {
+                self.decision_of(index) == Some(&Decision::Trash) && !self.engine.is_applied(index)
+            })
+            .collect()
+    }
+
+    /// Records several decisions as one undoable group (see `DecisionEngine::record_batch`)
+    pub fn record_batch(&mut self, entries: &[(usize, Decision)]) -> io::Result<u64> {
+        self.engine.record_batch(entries)
+    }
+
+    /// Makes the last `count` decisions undo as one batch
+    pub fn group_recent(&mut self, count: usize) -> u64 {
+        self.engine.group_last(count)
+    }
+
+    /// Decides every undecided file in the queue as one undoable batch; returns how many.
+    ///
+    /// Bulk trash leaves out the files that always need a yes (`asks_before_trash`).
+    pub fn decide_remaining(&mut self, decision: Decision) -> io::Result<usize> {
+        let undecided: Vec<usize> = self
+            .queue
+            .iter()
+            .copied()
+            .filter(|&i| !self.is_decided(i))
+            .collect();
+        self.decide_files(&undecided, decision)
+    }
+
+    /// Decides the given files as one undoable batch; returns how many.
+    ///
+    /// Like `decide_remaining`, trash leaves out files that always need a yes.
+    pub fn decide_files(&mut self, indices: &[usize], decision: Decision) -> io::Result<usize> {
+        let entries: Vec<(usize, Decision)> = indices
+            .iter()
+            .copied()
+            .filter(|&i| decision != Decision::Trash || !self.asks_before_trash(i))
+            .map(|i| (i, decision.clone()))
+            .collect();
+        if !entries.is_empty() {
+            self.record_batch(&entries)?;
+        }
+        Ok(entries.len())
+    }
+
+    /// Keeps `keep` and trashes the other `files` as one undoable batch; returns
+    /// how many were trashed.
+    ///
+    /// Like `decide_files`, trash leaves out files that always need a yes.
+    pub fn keep_one_of(&mut self, keep: usize, files: &[usize]) -> io::Result<usize> {
+        let mut entries = vec![(keep, Decision::Keep)];
+        entries.extend(
+            files
+                .iter()
+                .copied()
+                .filter(|&i| i != keep && !self.asks_before_trash(i))
+                .map(|i| (i, Decision::Trash)),
+        );
+        self.record_batch(&entries)?;
+        Ok(entries.len() - 1)
+    }
+
+    /// Moves the undecided file at `index` into `dir` over the file of the same name
+    /// already there. That file joins the session and is trashed in the same
+    /// undoable batch, so undo puts it back and apply, the backup manifest and the
+    /// journal all record it.
+    pub fn move_replacing(&mut self, index: usize, dir: PathBuf) -> io::Result<()> {
+        if self.is_decided(index) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Already decided; undo to change it",
+            ));
+        }
+        let existing = FileEntry::from_path(&self.engine.move_destination(index, &dir))?;
+        let displaced = self.files.len();
+        self.admit_files(vec![existing]);
+        self.record_batch(&[(displaced, Decision::Trash), (index, Decision::Move(dir))])?;
+        Ok(())
+    }
+
+    /// The current file's name pattern and the undecided queue files matching it,
+    /// current file first; `None` when its name has nothing to generalize
+    pub fn files_like_current(&self) -> Option<(NamePattern, Vec<usize>)> {
+        let current = self.current_file_index()?;
+        let pattern = NamePattern::generalize(&self.files[current].name)?;
+        let mut matches = vec![current];
+        matches.extend(self.queue.iter().copied().filter(|&i| {
+            i != current && !self.is_decided(i) && pattern.matches(&self.files[i].name)
+        }));
+        Some((pattern, matches))
     }
 
-    pub fn record_decision(&mut self, decision: Decision) {
-        self.decisions_stack.push((self.current_index, decision));
+    /// Records directory measurements (file index, counts, finished); a finished
+    /// directory's total becomes its size, for the engine's space accounting too
+    pub fn update_directory_sizes(
+        &mut self,
+        updates: impl IntoIterator<Item = (usize, DirectoryStats, bool)>,
+    ) {
+        let mut finished = Vec::new();
+        for (index, stats, done) in updates {
+            self.directories
+                .insert(index, DirectorySize { stats, done });
+            if done {
+                finished.push((index, stats.bytes));
+            }
+        }
+        if finished.is_empty() {
+            return;
+        }
+        for &(index, bytes) in &finished {
+            self.engine.note_resize(index, bytes);
+        }
+        // With the engine's handle let go, the list is changed in place, not copied
+        self.engine.files = Arc::default();
+        let files = Arc::make_mut(&mut self.files);
+        for (index, bytes) in finished {
+            if let Some(file) = files.get_mut(index) {
+                file.size = bytes;
+            }
+        }
+        self.engine.files = Arc::clone(&self.files);
+    }
+
+    /// Undecided files in the session that are sidecars of the current file (see
+    /// `is_sidecar_of`), including ones the filter bar or a search has hidden
+    pub fn sidecars_of_current(&self) -> Vec<usize> {
+        let Some(current) = self.current_file_index() else {
+            return Vec::new();
+        };
+        let file = &self.files[current];
+        self.full_queue()
+            .iter()
+            .copied()
+            .filter(|&i| i != current && !self.is_decided(i) && is_sidecar_of(&self.files[i], file))
+            .collect()
+    }
+
+    /// Undoes the last decision, or the whole batch it belongs to.
+    ///
+    /// Returns the undone (file index, decision) pairs, most recent first.
+    pub fn undo(&mut self) -> io::Result<Vec<(usize, Decision)>> {
+        let before = self.engine.decisions.clone();
+        self.engine.undo_group()?;
+        let undone: Vec<(usize, Decision)> = before[self.engine.decisions.len()..]
+            .iter()
+            .rev()
+            .cloned()
+            .collect();
+        // A triage decision undone during the detail pass rejoins the narrowed queue
+        for (index, _) in &undone {
+            if !self.queue.contains(index) {
+                self.queue.push(*index);
+            }
+        }
+        Ok(undone)
+    }
+
+    /// Moves on after a decision, unless auto-advance is turned off
+    pub fn advance_after_decision(&mut self) {
+        if self.auto_advance {
+            self.next();
+        }
+    }
+
+    /// Flips auto-advance and returns the new setting
+    pub fn toggle_auto_advance(&mut self) -> bool {
+        self.auto_advance = !self.auto_advance;
+        self.auto_advance
+    }
+
+    /// Marks the current file as seen without deciding on it
+    pub fn mark_seen(&mut self) {
+        if let Some(index) = self.current_file_index() {
+            self.seen.insert(index);
+        }
+    }
+
+    /// Whether the file at `index` has been seen
+    pub fn is_seen(&self, index: usize) -> bool {
+        self.seen.contains(&index)
+    }
+
+    /// Toggles the unsure mark on the current file (triage pass)
+    pub fn toggle_unsure(&mut self) {
+        if let Some(index) = self.current_file_index() {
+            if !self.unsure.remove(&index) {
+                self.unsure.insert(index);
+            }
+        }
     }
 
-    pub fn undo(&mut self) -> Option<(usize, Decision)> {
-        self.decisions_stack.pop()
+    /// Whether the file at `index` was marked unsure
+    pub fn is_unsure(&self, index: usize) -> bool {
+        self.unsure.contains(&index)
+    }
+
+    /// Whether every file in the triage queue has been decided or marked unsure
+    pub fn triage_complete(&self) -> bool {
+        self.phase == ReviewPhase::Triage
+            && self
+                .full_queue()
+                .iter()
+                .all(|&i| self.is_decided(i) || self.is_unsure(i))
+    }
+
+    /// Narrows the queue to undecided unsure files and switches to the detail pass.
+    ///
+    /// Returns false (and stays in triage) when there is nothing left to look at.
+    pub fn start_detail_pass(&mut self) -> bool {
+        let remaining: Vec<usize> = self
+            .full_queue()
+            .iter()
+            .copied()
+            .filter(|&i| self.is_unsure(i) && !self.is_decided(i))
+            .collect();
+        if remaining.is_empty() {
+            return false;
+        }
+        self.queue = remaining;
+        self.narrowing = None;
+        self.current_index = 0;
+        self.phase = ReviewPhase::Detail;
+        true
+    }
+
+    /// Files currently decided as skipped, in queue order
+    pub fn skipped_files(&self) -> Vec<usize> {
+        self.full_queue()
+            .iter()
+            .copied()
+            .filter(|&i| self.decision_of(i) == Some(&Decision::Skip))
+            .collect()
+    }
+
+    /// Narrows the queue to the skipped files and switches to the skipped pass;
+    /// false if nothing was skipped
+    pub fn start_skipped_pass(&mut self) -> bool {
+        let skipped = self.skipped_files();
+        if skipped.is_empty() {
+            return false;
+        }
+        self.queue = skipped;
+        self.narrowing = None;
+        self.current_index = 0;
+        self.phase = ReviewPhase::Skipped;
+        true
+    }
+
+    /// Space actually freed by the files decided as trash so far
+    pub fn freed_bytes(&self) -> u64 {
+        self.engine.freed_bytes()
+    }
+
+    /// Fraction of the session goal reached, if a goal is set
+    pub fn goal_progress(&self) -> Option<f64> {
+        self.goal
+            .map(|goal| goal.progress(self.decided_count(), self.freed_bytes()))
+    }
+
+    pub fn goal_met(&self) -> bool {
+        self.goal
+            .is_some_and(|goal| goal.is_met(self.decided_count(), self.freed_bytes()))
+    }
+
+    /// Returns true the first time the goal is met, so the prompt is shown once
+    pub fn take_goal_reached(&mut self) -> bool {
+        if self.goal_prompted || !self.goal_met() {
+            return false;
+        }
+        self.goal_prompted = true;
+        true
+    }
+
+    /// Why the current file looks sensitive: what its preview showed, else its name
+    pub fn current_sensitivity(&self) -> Option<Sensitivity> {
+        self.sensitivity_of(self.current_file_index()?)
+    }
+
+    /// Why file `index` looks sensitive, like `current_sensitivity`
+    pub fn sensitivity_of(&self, index: usize) -> Option<Sensitivity> {
+        self.sensitive
+            .get(&index)
+            .copied()
+            .or_else(|| sensitive::in_name(&self.files[index].name))
+    }
+
+    /// Whether trashing file `index` always needs a yes: its type is configured as
+    /// `always-ask`, or it looks sensitive. Bulk trash leaves these files out.
+    pub fn asks_before_trash(&self, index: usize) -> bool {
+        self.default_decisions.get(&self.files[index].file_type)
+            == Some(&DefaultDecision::AlwaysAsk)
+            || self.sensitivity_of(index).is_some()
+    }
+
+    /// Records what the preview lines of file `index` give away, if anything
+    pub fn note_preview_lines(&mut self, index: usize, lines: &[String]) {
+        if self.sensitive.contains_key(&index) {
+            return;
+        }
+        if let Some(found) = sensitive::in_lines(lines) {
+            self.sensitive.insert(index, found);
+        }
+    }
+
+    /// Shows or hides the current file's redacted preview lines again
+    pub fn toggle_reveal(&mut self) {
+        if let Some(index) = self.current_file_index() {
+            if !self.revealed.remove(&index) {
+                self.revealed.insert(index);
+            }
+        }
+    }
+
+    /// Whether the current file's sensitive preview lines are shown as they are
+    pub fn current_revealed(&self) -> bool {
+        self.current_file_index()
+            .is_some_and(|index| self.revealed.contains(&index))
+    }
+
+    /// Last-used info for the current file, if a usage source knew about it
+    pub fn current_usage(&self) -> Option<&UsageInfo> {
+        self.current_file_index().and_then(|i| self.usage.get(&i))
+    }
+
+    /// Ownership and trash-ability of the current file, if checked
+    pub fn current_access(&self) -> Option<&FileAccess> {
+        self.current_file_index().and_then(|i| self.access.get(&i))
+    }
+
+    /// The original the current file is an identical copy of, unless the original
+    /// is being trashed too (then this copy may be the one worth keeping)
+    pub fn current_original(&self) -> Option<&FileEntry> {
+        let original = *self.copies.get(&self.current_file_index()?)?;
+        let trashed = self.decision_of(original) == Some(&Decision::Trash);
+        (!trashed).then(|| &self.files[original])
+    }
+
+    /// Why the current file was suggested for trash, if a rule picked it
+    pub fn current_reason(&self) -> Option<&str> {
+        self.current_file_index()
+            .and_then(|i| self.reasons.get(&i))
+            .map(String::as_str)
+    }
+
+    /// Configured default for the current file's type, if any
+    pub fn current_default_decision(&self) -> Option<DefaultDecision> {
+        let file = self.current_file()?;
+        self.default_decisions.get(&file.file_type).copied()
+    }
+
+    /// Trash decisions still staged, i.e. not yet taken by background apply
+    pub fn pending_trash_count(&self) -> usize {
+        self.engine.pending_trash_count()
+    }
+
+    /// Whether the current file is a cloud placeholder, whose size isn't on this disk
+    pub fn current_is_placeholder(&self) -> bool {
+        self.cloud_sync.is_some() && self.current_file().is_some_and(FileEntry::is_online_only)
+    }
+
+    /// Shows `text` in the header for a few seconds
+    pub fn show_notice(&mut self, text: impl Into<String>) {
+        self.notice = Some((text.into(), Instant::now()));
+    }
+
+    /// The notice to display, if one was shown recently
+    pub fn active_notice(&self) -> Option<&str> {
+        self.notice
+            .as_ref()
+            .filter(|(_, shown)| shown.elapsed() < NOTICE_DURATION)
+            .map(|(text, _)| text.as_str())
+    }
+
+    /// Whether the file at `index` has a decision on the undo stack
+    pub fn is_decided(&self, index: usize) -> bool {
+        self.engine.is_decided(index)
+    }
+
+    /// Number of distinct files decided so far (re-decisions count once)
+    pub fn decided_count(&self) -> usize {
+        self.engine.decided_count()
+    }
+
+    /// Whether every file has a decision
+    pub fn all_decided(&self) -> bool {
+        self.decided_count() >= self.files.len()
+    }
+
+    /// The whole queue, including files a narrowing hides
+    pub fn full_queue(&self) -> &[usize] {
+        match self.narrowing {
+            Some(ref narrowing) => &narrowing.full_queue,
+            None => &self.queue,
+        }
+    }
+
+    /// Shows only the files in `keep`, in queue order; returns how many.
+    ///
+    /// Narrowing again starts from the full queue. The cursor stays on the current
+    /// file when it's kept. Decisions are untouched, so `clear_narrowing` brings
+    /// every file back as it was.
+    pub fn narrow_queue(&mut self, label: impl Into<String>, keep: &HashSet<usize>) -> usize {
+        let current = self.current_file_index();
+        let full_queue = match self.narrowing.take() {
+            Some(narrowing) => narrowing.full_queue,
+            None => self.queue.clone(),
+        };
+        self.queue = full_queue
+            .iter()
+            .copied()
+            .filter(|i| keep.contains(i))
+            .collect();
+        self.narrowing = Some(Narrowing {
+            label: label.into(),
+            full_queue,
+        });
+        self.current_index = current
+            .and_then(|index| self.queue.iter().position(|&i| i == index))
+            .unwrap_or(0);
+        self.queue.len()
+    }
+
+    /// Narrows the queue to the files matching a filter-bar expression (an empty
+    /// one shows every file again); returns how many are shown
+    pub fn filter_queue(&mut self, text: &str) -> crate::error::Result<usize> {
+        let filter = QueueFilter::parse(text)?;
+        if filter.is_empty() {
+            self.clear_narrowing();
+            return Ok(self.queue.len());
+        }
+        let keep: HashSet<usize> = self
+            .full_queue()
+            .iter()
+            .copied()
+            .filter(|&i| filter.matches(&self.files[i]))
+            .collect();
+        Ok(self.narrow_queue(format!("filter: {}", text.trim()), &keep))
+    }
+
+    /// Adds files to the session, at the end of the queue (behind any narrowing);
+    /// returns how many
+    pub fn admit_files(&mut self, new_files: Vec<FileEntry>) -> usize {
+        let start = self.files.len();
+        let count = new_files.len();
+        let files: Arc<Vec<FileEntry>> =
+            Arc::new(self.files.iter().cloned().chain(new_files).collect());
+        self.engine.admit(Arc::clone(&files));
+        self.files = files;
+        let queue = match self.narrowing {
+            Some(ref mut narrowing) => &mut narrowing.full_queue,
+            None => &mut self.queue,
+        };
+        queue.extend(start..start + count);
+        count
+    }
+
+    /// How many files of the full queue the narrowing hides
+    pub fn narrowed_out(&self) -> usize {
+        self.full_queue().len() - self.queue.len()
+    }
+
+    /// Brings back the full queue, keeping the cursor on the current file;
+    /// false when the queue wasn't narrowed
+    pub fn clear_narrowing(&mut self) -> bool {
+        let Some(narrowing) = self.narrowing.take() else {
+            return false;
+        };
+        let current = self.current_file_index();
+        self.queue = narrowing.full_queue;
+        self.current_index = current
+            .and_then(|index| self.queue.iter().position(|&i| i == index))
+            .unwrap_or(0);
+        true
+    }
+
+    /// Moves the current file to the end of the queue ("decide later").
+    ///
+    /// The cursor stays at the same position, which now shows the following file.
+    pub fn send_to_back(&mut self) {
+        if self.current_index + 1 >= self.queue.len() {
+            return;
+        }
+        let index = self.queue.remove(self.current_index);
+        self.queue.push(index);
+    }
+
+    /// Moves decided files after the undecided ones, each keeping its order, and
+    /// puts the cursor on the first file
+    pub fn send_decided_to_back(&mut self) {
+        let engine = &self.engine;
+        self.queue.sort_by_key(|&index| engine.is_decided(index));
+        self.current_index = 0;
+    }
+
+    /// Re-sorts the queue (ascending), keeping the current file under the cursor
+    pub fn sort_queue(&mut self, sort_by: SortBy) {
+        let current = self.current_file_index();
+        let files = &self.files;
+        self.queue
+            .sort_by(|&a, &b| compare_files(&files[a], &files[b], sort_by));
+        if let Some(index) = current {
+            self.current_index = self.queue.iter().position(|&i| i == index).unwrap_or(0);
+        }
+    }
+
+    /// Re-sorts only the undecided files, which take the queue positions undecided
+    /// files held before; decided files stay where they are. The cursor goes to
+    /// the first undecided file.
+    pub fn sort_undecided(&mut self, sort_by: SortBy, reverse: bool) {
+        let slots: Vec<usize> = (0..self.queue.len())
+            .filter(|&position| !self.is_decided(self.queue[position]))
+            .collect();
+        let mut undecided: Vec<usize> =
+            slots.iter().map(|&position| self.queue[position]).collect();
+        let files = &self.files;
+        undecided.sort_by(|&a, &b| compare_files(&files[a], &files[b], sort_by));
+        if reverse {
+            undecided.reverse();
+        }
+        for (&position, index) in slots.iter().zip(undecided) {
+            self.queue[position] = index;
+        }
+        if let Some(&first) = slots.first() {
+            self.current_index = first;
+        }
+    }
+
+    /// Moves the current file to the first undecided position so it comes up next.
+    ///
+    /// Files it jumps over keep their relative order; the cursor follows the pinned file.
+    pub fn pin_to_front(&mut self) {
+        let Some(target) = self.queue.iter().position(|&i| !self.is_decided(i)) else {
+            return;
+        };
+        if target >= self.current_index {
+            return;
+        }
+        let index = self.queue.remove(self.current_index);
+        self.queue.insert(target, index);
+        self.current_index = target;
     }
 }
 
@@ -55,9 +776,23 @@ mod tests {
             size: 0,
             modified_date: Utc::now(),
             file_type: FileType::Text,
+            disk: Default::default(),
         }
     }
 
+    /// Test files don't exist on disk, so nothing is staged
+    fn test_state(files: Vec<FileEntry>) -> AppState {
+        let mut state = AppState::new(files);
+        state.engine.set_dry_run(true);
+        state
+    }
+
+    fn triage_state(files: Vec<FileEntry>) -> AppState {
+        let mut state = AppState::new_triage(files);
+        state.engine.set_dry_run(true);
+        state
+    }
+
     #[test]
     fn test_app_state_new() {
         let files = vec![
@@ -65,17 +800,20 @@ mod tests {
             create_test_entry("file2.txt"),
         ];
 
-        let state = AppState::new(files.clone());
+        let state = test_state(files.clone());
 
         assert_eq!(state.files.len(), 2);
+        assert_eq!(state.queue, vec![0, 1]);
         assert_eq!(state.current_index, 0);
-        assert_eq!(state.decisions_stack.len(), 0);
+        assert!(state.decisions().is_empty());
+        // One file list for the state and its engine
+        assert!(Arc::ptr_eq(&state.files, &state.engine.files));
     }
 
     #[test]
     fn test_app_state_current_file() {
         let files = vec![create_test_entry("file1.txt")];
-        let state = AppState::new(files);
+        let state = test_state(files);
 
         let current = state.current_file();
         assert!(current.is_some());
@@ -84,7 +822,7 @@ mod tests {
 
     #[test]
     fn test_app_state_current_file_empty() {
-        let state = AppState::new(vec![]);
+        let state = test_state(vec![]);
         assert!(state.current_file().is_none());
     }
 
@@ -95,7 +833,7 @@ mod tests {
             create_test_entry("file2.txt"),
             create_test_entry("file3.txt"),
         ];
-        let mut state = AppState::new(files);
+        let mut state = test_state(files);
 
         state.next();
         assert_eq!(state.current_index, 1);
@@ -113,7 +851,7 @@ mod tests {
             create_test_entry("file1.txt"),
             create_test_entry("file2.txt"),
         ];
-        let mut state = AppState::new(files);
+        let mut state = test_state(files);
 
         state.current_index = 1;
         state.previous();
@@ -129,12 +867,11 @@ mod tests {
             create_test_entry("file1.txt"),
             create_test_entry("file2.txt"),
         ];
-        let mut state = AppState::new(files);
+        let mut state = test_state(files);
 
-        state.record_decision(Decision::Keep);
+        state.record_decision(Decision::Keep).unwrap();
 
-        assert_eq!(state.decisions_stack.len(), 1);
-        assert_eq!(state.decisions_stack[0], (0, Decision::Keep));
+        assert_eq!(state.decisions(), &[(0, Decision::Keep)]);
     }
 
     #[test]
@@ -143,24 +880,546 @@ mod tests {
             create_test_entry("file1.txt"),
             create_test_entry("file2.txt"),
         ];
-        let mut state = AppState::new(files);
+        let mut state = test_state(files);
 
-        state.record_decision(Decision::Keep);
+        state.record_decision(Decision::Keep).unwrap();
         state.current_index = 1;
-        state.record_decision(Decision::Trash);
+        state.record_decision(Decision::Trash).unwrap();
+
+        let undone = state.undo().unwrap();
+        assert_eq!(undone, vec![(1, Decision::Trash)]);
+        assert_eq!(state.decisions().len(), 1);
+    }
 
-        let undone = state.undo();
-        assert!(undone.is_some());
-        assert_eq!(undone.unwrap(), (1, Decision::Trash));
-        assert_eq!(state.decisions_stack.len(), 1);
+    fn queued_names(state: &AppState) -> Vec<&str> {
+        state
+            .queue
+            .iter()
+            .map(|&i| state.files[i].name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_app_state_send_to_back() {
+        let files = vec![
+            create_test_entry("a.txt"),
+            create_test_entry("b.txt"),
+            create_test_entry("c.txt"),
+        ];
+        let mut state = test_state(files);
+
+        state.send_to_back();
+
+        assert_eq!(queued_names(&state), vec!["b.txt", "c.txt", "a.txt"]);
+        assert_eq!(state.current_index, 0);
+        assert_eq!(state.current_file().unwrap().name, "b.txt");
+    }
+
+    #[test]
+    fn test_app_state_send_to_back_last_file_is_noop() {
+        let files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+        let mut state = test_state(files);
+        state.next();
+
+        state.send_to_back();
+
+        assert_eq!(queued_names(&state), vec!["a.txt", "b.txt"]);
+        assert_eq!(state.current_file().unwrap().name, "b.txt");
+    }
+
+    #[test]
+    fn test_app_state_pin_to_front_skips_decided_files() {
+        let files = vec![
+            create_test_entry("a.txt"),
+            create_test_entry("b.txt"),
+            create_test_entry("c.txt"),
+            create_test_entry("d.txt"),
+        ];
+        let mut state = test_state(files);
+
+        state.record_decision(Decision::Keep).unwrap();
+        state.next();
+        state.next();
+        state.next();
+        state.pin_to_front();
+
+        assert_eq!(
+            queued_names(&state),
+            vec!["a.txt", "d.txt", "b.txt", "c.txt"]
+        );
+        assert_eq!(state.current_index, 1);
+        assert_eq!(state.current_file().unwrap().name, "d.txt");
+    }
+
+    #[test]
+    fn test_app_state_send_decided_to_back() {
+        let files = vec![
+            create_test_entry("a.txt"),
+            create_test_entry("b.txt"),
+            create_test_entry("c.txt"),
+            create_test_entry("d.txt"),
+        ];
+        let mut state = test_state(files);
+        state.decide_files(&[0, 2], Decision::Trash).unwrap();
+        state.goto(3);
+
+        state.send_decided_to_back();
+
+        assert_eq!(
+            queued_names(&state),
+            vec!["b.txt", "d.txt", "a.txt", "c.txt"]
+        );
+        assert_eq!(state.current_file().unwrap().name, "b.txt");
+    }
+
+    #[test]
+    fn test_app_state_decisions_use_file_index_after_reorder() {
+        let files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+        let mut state = test_state(files);
+
+        state.send_to_back();
+        state.record_decision(Decision::Trash).unwrap();
+
+        // b.txt is shown first but is still file index 1
+        assert_eq!(state.current_file_index(), Some(1));
+        assert_eq!(state.decisions()[0], (1, Decision::Trash));
+    }
+
+    #[test]
+    fn test_app_state_auto_advance_toggle() {
+        let files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+        let mut state = test_state(files);
+        assert!(state.auto_advance);
+
+        assert!(!state.toggle_auto_advance());
+        state.record_decision(Decision::Keep).unwrap();
+        state.advance_after_decision();
+        assert_eq!(state.current_index, 0);
+
+        assert!(state.toggle_auto_advance());
+        state.advance_after_decision();
+        assert_eq!(state.current_index, 1);
+    }
+
+    #[test]
+    fn test_app_state_seen_is_separate_from_decisions() {
+        let files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+        let mut state = test_state(files);
+
+        state.mark_seen();
+        state.next();
+
+        assert!(state.is_seen(0));
+        assert!(!state.is_decided(0));
+        assert!(!state.is_seen(1));
+        assert!(state.decisions().is_empty());
+
+        state.record_decision(Decision::Trash).unwrap();
+        assert!(state.is_decided(1));
+        assert!(!state.is_seen(1));
+    }
+
+    #[test]
+    fn test_app_state_triage_then_detail_pass() {
+        let files = vec![
+            create_test_entry("a.txt"),
+            create_test_entry("b.txt"),
+            create_test_entry("c.txt"),
+        ];
+        let mut state = triage_state(files);
+        assert_eq!(state.phase, ReviewPhase::Triage);
+
+        state.record_decision(Decision::Keep).unwrap();
+        state.next();
+        state.toggle_unsure();
+        state.next();
+        assert!(!state.triage_complete());
+        state.record_decision(Decision::Trash).unwrap();
+        assert!(state.triage_complete());
+
+        assert!(state.start_detail_pass());
+        assert_eq!(state.phase, ReviewPhase::Detail);
+        assert_eq!(queued_names(&state), vec!["b.txt"]);
+        assert_eq!(state.current_file().unwrap().name, "b.txt");
+
+        // The detail pass only walks the narrowed queue
+        state.next();
+        assert_eq!(state.current_index, 0);
+
+        // Undoing a triage decision brings that file back for review
+        state.undo().unwrap();
+        assert_eq!(queued_names(&state), vec!["b.txt", "c.txt"]);
+    }
+
+    #[test]
+    fn test_app_state_detail_pass_needs_unsure_files() {
+        let files = vec![create_test_entry("a.txt")];
+        let mut state = triage_state(files);
+        state.record_decision(Decision::Keep).unwrap();
+
+        assert!(state.triage_complete());
+        assert!(!state.start_detail_pass());
+        assert_eq!(state.phase, ReviewPhase::Triage);
+    }
+
+    #[test]
+    fn test_app_state_toggle_unsure() {
+        let mut state = triage_state(vec![create_test_entry("a.txt")]);
+        state.toggle_unsure();
+        assert!(state.is_unsure(0));
+        state.toggle_unsure();
+        assert!(!state.is_unsure(0));
+    }
+
+    #[test]
+    fn test_app_state_free_bytes_goal() {
+        let mut files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+        files[0].size = 600;
+        files[1].size = 600;
+        let mut state = test_state(files);
+        state.goal = Some(SessionGoal::FreeBytes(1000));
+
+        state.record_decision(Decision::Trash).unwrap();
+        state.next();
+        assert_eq!(state.freed_bytes(), 600);
+        assert_eq!(state.goal_progress(), Some(0.6));
+        assert!(!state.take_goal_reached());
+
+        state.record_decision(Decision::Trash).unwrap();
+        assert!(state.goal_met());
+        assert!(state.take_goal_reached());
+        // Only prompted once
+        assert!(!state.take_goal_reached());
+    }
+
+    #[test]
+    fn test_app_state_review_files_goal_ignores_keeps_for_bytes() {
+        let mut state = test_state(vec![create_test_entry("a.txt")]);
+        state.goal = Some(SessionGoal::ReviewFiles(1));
+        state.record_decision(Decision::Keep).unwrap();
+
+        assert_eq!(state.freed_bytes(), 0);
+        assert!(state.goal_met());
+    }
+
+    #[test]
+    fn test_app_state_no_goal() {
+        let state = test_state(vec![create_test_entry("a.txt")]);
+        assert_eq!(state.goal_progress(), None);
+        assert!(!state.goal_met());
+    }
+
+    #[test]
+    fn test_app_state_current_usage() {
+        let files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+        let mut state = test_state(files);
+        state.usage.insert(1, UsageInfo::never_opened());
+
+        assert_eq!(state.current_usage(), None);
+        state.next();
+        assert!(state.current_usage().unwrap().is_never_opened());
+    }
+
+    #[test]
+    fn test_app_state_current_default_decision() {
+        let mut image = create_test_entry("photo.png");
+        image.file_type = FileType::Image;
+        let mut state = test_state(vec![create_test_entry("a.txt"), image]);
+        state
+            .default_decisions
+            .insert(FileType::Image, DefaultDecision::SuggestTrash);
+
+        assert_eq!(state.current_default_decision(), None);
+        state.next();
+        assert_eq!(
+            state.current_default_decision(),
+            Some(DefaultDecision::SuggestTrash)
+        );
+    }
+
+    #[test]
+    fn test_app_state_record_batch() {
+        let files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+        let mut state = test_state(files);
+        state
+            .record_batch(&[(0, Decision::Trash), (1, Decision::Trash)])
+            .unwrap();
+
+        assert!(state.is_decided(0));
+        assert!(state.is_decided(1));
+        // The batch undoes as a unit
+        assert_eq!(
+            state.undo().unwrap(),
+            vec![(1, Decision::Trash), (0, Decision::Trash)]
+        );
+        assert_eq!(state.decided_count(), 0);
+    }
+
+    #[test]
+    fn test_app_state_pending_trash_count() {
+        let files = vec![
+            create_test_entry("a.txt"),
+            create_test_entry("b.txt"),
+            create_test_entry("c.txt"),
+        ];
+        let mut state = test_state(files);
+        state
+            .record_batch(&[
+                (0, Decision::Trash),
+                (1, Decision::Keep),
+                (2, Decision::Trash),
+            ])
+            .unwrap();
+
+        assert_eq!(state.pending_trash_count(), 2);
+        assert_eq!(state.staged_trash(), [0, 2]);
+
+        // Flipped by index, wherever the cursor is
+        state.change_decision_of(2, Decision::Keep).unwrap();
+        assert_eq!(state.staged_trash(), [0]);
+        assert_eq!(state.decision_of(2), Some(&Decision::Keep));
+    }
+
+    #[test]
+    fn test_app_state_notice_expires() {
+        let mut state = test_state(vec![create_test_entry("a.txt")]);
+        assert_eq!(state.active_notice(), None);
+
+        state.show_notice("config reloaded");
+        assert_eq!(state.active_notice(), Some("config reloaded"));
+
+        state.notice = Some(("old".to_string(), Instant::now() - NOTICE_DURATION));
+        assert_eq!(state.active_notice(), None);
+    }
+
+    #[test]
+    fn test_app_state_rejects_re_decision() {
+        let files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+        let mut state = test_state(files);
+        state.record_decision(Decision::Keep).unwrap();
+        assert!(state.record_decision(Decision::Trash).is_err());
+        assert_eq!(state.decisions(), &[(0, Decision::Keep)]);
+        assert_eq!(state.decided_count(), 1);
+        assert!(!state.all_decided());
+
+        state.undo().unwrap();
+        assert!(!state.is_decided(0));
+        assert_eq!(state.decided_count(), 0);
+
+        state
+            .record_batch(&[(0, Decision::Trash), (1, Decision::Keep)])
+            .unwrap();
+        assert!(state.all_decided());
+    }
+
+    #[test]
+    fn test_app_state_change_decision_reports_freed_delta() {
+        let mut big = create_test_entry("big.iso");
+        big.size = 4096;
+        big.disk.allocated = Some(4096);
+        let mut state = test_state(vec![big, create_test_entry("b.txt")]);
+        state.record_decision(Decision::Trash).unwrap();
+        state.next();
+        state.record_decision(Decision::Keep).unwrap();
+        state.previous();
+
+        let (old, delta) = state.change_decision(Decision::Keep).unwrap();
+        assert_eq!((old, delta), (Decision::Trash, -4096));
+        assert_eq!(state.freed_bytes(), 0);
+        // Changed in place: the stack keeps its order
+        assert_eq!(
+            state.decisions(),
+            &[(0, Decision::Keep), (1, Decision::Keep)]
+        );
+        assert_eq!(state.engine.get_statistics().kept, 2);
+
+        assert_eq!(state.change_decision(Decision::Trash).unwrap().1, 4096);
+    }
+
+    #[test]
+    fn test_app_state_goto_clamps() {
+        let files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+        let mut state = test_state(files);
+        state.goto(usize::MAX);
+        assert_eq!(state.current_index, 1);
+        state.goto(0);
+        assert_eq!(state.current_index, 0);
+    }
+
+    #[test]
+    fn test_app_state_sort_queue_keeps_current_file() {
+        let mut files = vec![
+            create_test_entry("c.txt"),
+            create_test_entry("a.txt"),
+            create_test_entry("b.txt"),
+        ];
+        files[0].size = 1;
+        files[1].size = 3;
+        files[2].size = 2;
+        let mut state = test_state(files);
+        state.next();
+
+        state.sort_queue(SortBy::Name);
+        assert_eq!(queued_names(&state), vec!["a.txt", "b.txt", "c.txt"]);
+        assert_eq!(state.current_file().unwrap().name, "a.txt");
+
+        state.sort_queue(SortBy::Size);
+        assert_eq!(queued_names(&state), vec!["c.txt", "b.txt", "a.txt"]);
+        assert_eq!(state.current_index, 2);
+    }
+
+    #[test]
+    fn test_app_state_sort_undecided_leaves_decided_in_place() {
+        let mut files: Vec<FileEntry> = ["a.txt", "b.txt", "c.txt", "d.txt"]
+            .into_iter()
+            .map(create_test_entry)
+            .collect();
+        for (file, size) in files.iter_mut().zip([1, 3, 2, 4]) {
+            file.size = size;
+        }
+        let mut state = test_state(files);
+        state.next();
+        state.record_decision(Decision::Keep).unwrap();
+
+        state.sort_undecided(SortBy::Size, true);
+        assert_eq!(
+            queued_names(&state),
+            vec!["d.txt", "b.txt", "c.txt", "a.txt"]
+        );
+        assert_eq!(state.current_index, 0);
+
+        state.sort_undecided(SortBy::Random, false);
+        assert_eq!(queued_names(&state)[1], "b.txt");
+        let mut names = queued_names(&state);
+        names.sort();
+        assert_eq!(names, vec!["a.txt", "b.txt", "c.txt", "d.txt"]);
+    }
+
+    #[test]
+    fn test_app_state_narrow_and_clear_queue() {
+        let files = ["a.txt", "b.txt", "c.txt", "d.txt"]
+            .into_iter()
+            .map(create_test_entry)
+            .collect();
+        let mut state = test_state(files);
+        state.goto(2);
+
+        // The current file stays under the cursor when it's kept
+        assert_eq!(state.narrow_queue("search: 'x'", &HashSet::from([1, 2])), 2);
+        assert_eq!(queued_names(&state), vec!["b.txt", "c.txt"]);
+        assert_eq!(state.current_file().unwrap().name, "c.txt");
+        assert_eq!(state.full_queue(), &[0, 1, 2, 3]);
+
+        // Narrowing again starts from the full queue
+        assert_eq!(state.narrow_queue("search: 'y'", &HashSet::from([0, 3])), 2);
+        assert_eq!(queued_names(&state), vec!["a.txt", "d.txt"]);
+        assert_eq!(state.current_index, 0);
+
+        state.next();
+        state.record_decision(Decision::Trash).unwrap();
+        assert!(state.clear_narrowing());
+        assert_eq!(state.queue, vec![0, 1, 2, 3]);
+        assert_eq!(state.current_file().unwrap().name, "d.txt");
+        assert!(state.is_decided(3));
+        assert!(!state.clear_narrowing());
+    }
+
+    #[test]
+    fn test_app_state_decide_remaining() {
+        let mut image = create_test_entry("photo.png");
+        image.file_type = FileType::Image;
+        let files = vec![
+            create_test_entry("a.txt"),
+            create_test_entry("b.txt"),
+            image,
+        ];
+        let mut state = test_state(files);
+        state
+            .default_decisions
+            .insert(FileType::Image, DefaultDecision::AlwaysAsk);
+        state.record_decision(Decision::Keep).unwrap();
+
+        // Already decided files and always-ask types are left alone
+        assert_eq!(state.decide_remaining(Decision::Trash).unwrap(), 1);
+        assert_eq!(state.decisions()[1], (1, Decision::Trash));
+        assert!(!state.is_decided(2));
+
+        // The batch undoes as one step
+        state.undo().unwrap();
+        assert_eq!(state.decided_count(), 1);
+
+        assert_eq!(state.decide_remaining(Decision::Keep).unwrap(), 2);
+        assert!(state.all_decided());
+    }
+
+    #[test]
+    fn test_app_state_bulk_trash_leaves_out_sensitive_files() {
+        let files = vec![
+            create_test_entry("a.txt"),
+            create_test_entry(".env"),
+            create_test_entry("notes.txt"),
+            create_test_entry("b.txt"),
+        ];
+        let mut state = test_state(files);
+        // What the preview of notes.txt turned up
+        state.sensitive.insert(2, Sensitivity::Credentials);
+
+        assert_eq!(state.decide_files(&[0, 1, 2], Decision::Trash).unwrap(), 1);
+        assert!(!state.is_decided(1) && !state.is_decided(2));
+        state.undo().unwrap();
+
+        assert_eq!(state.keep_one_of(3, &[0, 1, 2, 3]).unwrap(), 1);
+        assert_eq!(state.decision_of(0), Some(&Decision::Trash));
+        assert!(!state.is_decided(1) && !state.is_decided(2));
+    }
+
+    #[test]
+    fn test_app_state_files_like_current() {
+        let files = vec![
+            create_test_entry("IMG_0001.jpg"),
+            create_test_entry("notes.txt"),
+            create_test_entry("IMG_0002.jpg"),
+            create_test_entry("IMG_0003.jpg"),
+        ];
+        let mut state = test_state(files);
+        state.current_index = 2;
+        state.record_decision(Decision::Keep).unwrap();
+
+        // The current file leads; decided files drop out
+        state.current_index = 0;
+        let (pattern, matches) = state.files_like_current().unwrap();
+        assert_eq!(pattern.to_string(), "IMG_####.jpg");
+        assert_eq!(matches, vec![0, 3]);
+
+        state.current_index = 1;
+        assert!(state.files_like_current().is_none());
+    }
+
+    #[test]
+    fn test_app_state_current_original() {
+        let files = vec![
+            create_test_entry("report.pdf"),
+            create_test_entry("report (1).pdf"),
+        ];
+        let mut state = test_state(files);
+        state.copies.insert(1, 0);
+
+        assert!(state.current_original().is_none());
+        state.current_index = 1;
+        assert_eq!(state.current_original().unwrap().name, "report.pdf");
+
+        // Once the original is being trashed, the copy isn't redundant any more
+        state.current_index = 0;
+        state.record_decision(Decision::Trash).unwrap();
+        state.current_index = 1;
+        assert!(state.current_original().is_none());
     }
 
     #[test]
     fn test_app_state_undo_empty() {
         let files = vec![create_test_entry("file1.txt")];
-        let mut state = AppState::new(files);
+        let mut state = test_state(files);
 
-        let undone = state.undo();
-        assert!(undone.is_none());
+        assert!(state.undo().is_err());
     }
 }
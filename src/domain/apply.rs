@@ -0,0 +1,144 @@
+use super::Anomaly;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// What happened to one file during the apply phase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ApplyOutcome {
+    /// Moved to the system trash
+    Trashed,
+    /// Moving to the trash failed; the file is back at (or still in) its original location
+    Failed(String),
+    /// Apply was cancelled before this file; it was put back where it was
+    Restored,
+}
+
+/// Progress through moving the session's trashed files to the system trash.
+///
+/// Created by `DecisionEngine::start_apply` and advanced one file at a time with
+/// `apply_next`, so a UI can redraw (and offer cancel) between files.
+#[derive(Debug, Clone)]
+pub struct ApplyJob {
+    pending: VecDeque<usize>,
+    /// Number of files the job started with
+    pub total: usize,
+    /// Per-file results, as (file index, outcome), in the order they were processed
+    pub results: Vec<(usize, ApplyOutcome)>,
+    /// Bytes moved to the trash so far
+    pub bytes_done: u64,
+    /// Set once the job was cancelled
+    pub cancelled: bool,
+    /// Anomalies found by the verification pass, once it has run
+    pub issues: Option<Vec<(usize, Anomaly)>>,
+    started: Instant,
+}
+
+impl ApplyJob {
+    pub fn new(indices: Vec<usize>) -> Self {
+        Self {
+            total: indices.len(),
+            pending: indices.into(),
+            results: Vec::new(),
+            bytes_done: 0,
+            cancelled: false,
+            issues: None,
+            started: Instant::now(),
+        }
+    }
+
+    /// Starts a job whose first results were already produced elsewhere (background apply)
+    pub fn with_results(indices: Vec<usize>, results: Vec<(usize, ApplyOutcome)>) -> Self {
+        Self {
+            total: indices.len() + results.len(),
+            results,
+            ..Self::new(indices)
+        }
+    }
+
+    /// File index that will be processed next
+    pub fn current(&self) -> Option<usize> {
+        self.pending.front().copied()
+    }
+
+    pub(crate) fn take_next(&mut self) -> Option<usize> {
+        self.pending.pop_front()
+    }
+
+    pub(crate) fn take_pending(&mut self) -> Vec<usize> {
+        self.pending.drain(..).collect()
+    }
+
+    pub fn done(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Fraction of files processed (0.0..=1.0)
+    pub fn progress(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        self.done() as f64 / self.total as f64
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    /// Average bytes per second since the job started
+    pub fn bytes_per_second(&self) -> f64 {
+        let secs = self.elapsed().as_secs_f64();
+        if secs <= 0.0 {
+            return 0.0;
+        }
+        self.bytes_done as f64 / secs
+    }
+
+    /// Number of results with the given kind of outcome
+    pub fn count(&self, matches: impl Fn(&ApplyOutcome) -> bool) -> usize {
+        self.results
+            .iter()
+            .filter(|(_, outcome)| matches(outcome))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_job_progress() {
+        let mut job = ApplyJob::new(vec![3, 5]);
+        assert_eq!(job.current(), Some(3));
+        assert_eq!(job.progress(), 0.0);
+
+        let index = job.take_next().unwrap();
+        job.results.push((index, ApplyOutcome::Trashed));
+        assert_eq!(job.progress(), 0.5);
+        assert_eq!(job.current(), Some(5));
+        assert!(!job.is_finished());
+
+        assert_eq!(job.take_pending(), vec![5]);
+        assert!(job.is_finished());
+        assert_eq!(job.count(|o| *o == ApplyOutcome::Trashed), 1);
+    }
+
+    #[test]
+    fn test_apply_job_with_results() {
+        let job = ApplyJob::with_results(vec![2], vec![(1, ApplyOutcome::Trashed)]);
+        assert_eq!(job.total, 2);
+        assert_eq!(job.done(), 1);
+        assert_eq!(job.current(), Some(2));
+    }
+
+    #[test]
+    fn test_apply_job_empty_is_finished() {
+        let job = ApplyJob::new(Vec::new());
+        assert!(job.is_finished());
+        assert_eq!(job.progress(), 1.0);
+    }
+}
@@ -0,0 +1,296 @@
+//! Compact storage for file lists too big to hold as `FileEntry`s
+//!
+//! A filtered scan of a big tree leaves out far more files than it keeps:
+//! `--type image` over 500k files keeps a few thousand, and the rest are held only
+//! so a filter can be turned off mid-session (`LeftOut`). `CompactFiles` holds
+//! them in under a third of the space. Each parent directory is stored once and
+//! referred to by a `u32` (`PathInterner`), the name is kept without its path, the
+//! size fits a `SizeBucket` and the modified time is whole seconds. Disk usage
+//! (allocated blocks, hard links, shared extents) isn't kept at all; it's read
+//! from the disk, with the exact size, when a file is admitted back (`entry()`).
+
+use super::{DiskUsage, FileEntry, FileType};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Sizes from here up are kept in whole KiB
+const EXACT_LIMIT: u64 = 1 << 31;
+
+/// Each distinct path once, numbered in the order first seen
+#[derive(Debug, Clone, Default)]
+pub struct PathInterner {
+    ids: HashMap<Arc<Path>, u32>,
+    paths: Vec<Arc<Path>>,
+}
+
+impl PathInterner {
+    /// The number of `path`, adding it if it's new
+    pub fn intern(&mut self, path: &Path) -> u32 {
+        if let Some(&id) = self.ids.get(path) {
+            return id;
+        }
+        let id = self.paths.len() as u32;
+        let path: Arc<Path> = path.into();
+        self.ids.insert(Arc::clone(&path), id);
+        self.paths.push(path);
+        id
+    }
+
+    pub fn get(&self, id: u32) -> &Path {
+        &self.paths[id as usize]
+    }
+
+    /// Distinct paths held
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+}
+
+/// A size in 32 bits: exact under 2 GiB, rounded up to whole KiB above (up to
+/// 2 TiB), which is as close as the size filters need
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeBucket(u32);
+
+impl SizeBucket {
+    pub fn new(bytes: u64) -> Self {
+        if bytes < EXACT_LIMIT {
+            Self(bytes as u32)
+        } else {
+            let kib = bytes.div_ceil(1024).min(u64::from(u32::MAX >> 1));
+            Self(1 << 31 | kib as u32)
+        }
+    }
+
+    pub fn bytes(self) -> u64 {
+        if self.0 & 1 << 31 == 0 {
+            u64::from(self.0)
+        } else {
+            u64::from(self.0 & !(1 << 31)) * 1024
+        }
+    }
+}
+
+/// One file of a `CompactFiles`
+#[derive(Debug, Clone)]
+pub struct CompactEntry {
+    parent: u32,
+    /// The name as stored on disk
+    name: Box<OsStr>,
+    pub size: SizeBucket,
+    /// Unix time, seconds
+    modified: i64,
+    pub file_type: FileType,
+}
+
+impl CompactEntry {
+    pub fn name(&self) -> &OsStr {
+        &self.name
+    }
+
+    /// Whether the name starts with a dot
+    pub fn is_hidden(&self) -> bool {
+        self.name.as_encoded_bytes().starts_with(b".")
+    }
+
+    pub fn extension(&self) -> Option<&OsStr> {
+        Path::new(&*self.name).extension()
+    }
+}
+
+/// A file list in compact form, in the order files were pushed
+#[derive(Debug, Clone, Default)]
+pub struct CompactFiles {
+    parents: PathInterner,
+    entries: Vec<CompactEntry>,
+}
+
+impl CompactFiles {
+    pub fn push(&mut self, file: FileEntry) {
+        let parent = self
+            .parents
+            .intern(file.path.parent().unwrap_or(Path::new("")));
+        self.entries.push(CompactEntry {
+            parent,
+            name: file.os_name().into(),
+            size: SizeBucket::new(file.size),
+            modified: file.modified_date.timestamp(),
+            file_type: file.file_type,
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &CompactEntry> {
+        self.entries.iter()
+    }
+
+    /// Distinct parent directories held
+    pub fn parent_count(&self) -> usize {
+        self.parents.len()
+    }
+
+    pub fn path(&self, entry: &CompactEntry) -> PathBuf {
+        self.parents.get(entry.parent).join(&*entry.name)
+    }
+
+    /// `entry` as a full `FileEntry`: read from the disk again, or rebuilt from
+    /// what's kept (without disk usage) when it can't be read any more
+    pub fn entry(&self, entry: &CompactEntry) -> FileEntry {
+        let path = self.path(entry);
+        FileEntry::from_path(&path).unwrap_or_else(|_| FileEntry {
+            name: entry.name.to_string_lossy().into_owned(),
+            size: entry.size.bytes(),
+            modified_date: DateTime::<Utc>::from_timestamp(entry.modified, 0).unwrap_or_default(),
+            file_type: entry.file_type.clone(),
+            disk: DiskUsage::default(),
+            path,
+        })
+    }
+
+    /// Takes out the files `take` picks, as full entries (see `entry()`)
+    pub fn take_where(&mut self, take: impl Fn(&CompactEntry) -> bool) -> Vec<FileEntry> {
+        let (taken, kept): (Vec<_>, Vec<_>) = std::mem::take(&mut self.entries)
+            .into_iter()
+            .partition(take);
+        self.entries = kept;
+        taken.iter().map(|entry| self.entry(entry)).collect()
+    }
+}
+
+impl FromIterator<FileEntry> for CompactFiles {
+    fn from_iter<I: IntoIterator<Item = FileEntry>>(files: I) -> Self {
+        let mut compact = Self::default();
+        for file in files {
+            compact.push(file);
+        }
+        compact
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::mem::size_of;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compact_files_intern_parents_and_shrink_entries() {
+        let dir = PathBuf::from("/home/someone/Downloads/photos/2024/holiday");
+        let files: CompactFiles = (0..1000)
+            .map(|i| FileEntry {
+                path: dir.join(format!("IMG_{:04}.jpg", i)),
+                name: format!("IMG_{:04}.jpg", i),
+                size: 3_000_000,
+                modified_date: Utc::now(),
+                file_type: FileType::Image,
+                disk: DiskUsage::default(),
+            })
+            .collect();
+        assert_eq!(files.len(), 1000);
+        assert_eq!(files.parent_count(), 1);
+        let first = files.iter().next().unwrap();
+        assert_eq!(files.path(first), dir.join("IMG_0000.jpg"));
+
+        // Inline, a third of a FileEntry or less, with no path on the heap
+        assert!(
+            size_of::<CompactEntry>() * 3 <= size_of::<FileEntry>(),
+            "{} vs {}",
+            size_of::<CompactEntry>(),
+            size_of::<FileEntry>()
+        );
+    }
+
+    /// Bytes `files` take as `FileEntry`s: inline, plus each path and name on the heap
+    fn file_entry_bytes(files: &[FileEntry]) -> usize {
+        files
+            .iter()
+            .map(|file| size_of::<FileEntry>() + file.path.as_os_str().len() + file.name.len())
+            .sum()
+    }
+
+    /// Bytes a `CompactFiles` takes: each entry inline with its name, and each
+    /// parent once, with its place in the interner's map and list
+    fn compact_bytes(files: &CompactFiles) -> usize {
+        let entries: usize = files
+            .iter()
+            .map(|entry| size_of::<CompactEntry>() + entry.name.len())
+            .sum();
+        let parents: usize = files
+            .parents
+            .paths
+            .iter()
+            .map(|path| path.as_os_str().len() + 2 * size_of::<Arc<Path>>() + size_of::<u32>())
+            .sum();
+        entries + parents
+    }
+
+    #[test]
+    fn test_compact_files_hold_a_big_scan_in_a_third_of_the_space() {
+        // Shaped like a Downloads tree: 50k files over 250 directories
+        let files: Vec<FileEntry> = (0..50_000)
+            .map(|i| {
+                let dir = PathBuf::from(format!("/home/someone/Downloads/folder {}", i % 250));
+                let name = format!("IMG_{:05}.jpg", i);
+                FileEntry {
+                    path: dir.join(&name),
+                    name,
+                    size: 3_000_000,
+                    modified_date: Utc::now(),
+                    file_type: FileType::Image,
+                    disk: DiskUsage::default(),
+                }
+            })
+            .collect();
+        let full = file_entry_bytes(&files);
+        let compact = compact_bytes(&files.into_iter().collect());
+        assert!(compact * 3 <= full, "{} vs {}", compact, full);
+    }
+
+    #[test]
+    fn test_size_bucket() {
+        for bytes in [0, 1, 4096, EXACT_LIMIT - 1] {
+            assert_eq!(SizeBucket::new(bytes).bytes(), bytes);
+        }
+        let big = 5 * 1024 * 1024 * 1024 + 1;
+        assert_eq!(SizeBucket::new(big).bytes(), big.div_ceil(1024) * 1024);
+        assert!(SizeBucket::new(big).bytes() - big < 1024);
+    }
+
+    #[test]
+    fn test_take_where_reads_entries_from_disk() {
+        let temp_dir = TempDir::new().unwrap();
+        let kept = temp_dir.path().join("kept.log");
+        let gone = temp_dir.path().join(".gone.txt");
+        fs::write(&kept, "log line").unwrap();
+        fs::write(&gone, "soon deleted").unwrap();
+        let mut files: CompactFiles = [&kept, &gone]
+            .into_iter()
+            .map(|path| FileEntry::from_path(path).unwrap())
+            .collect();
+        fs::write(&kept, "log line, and another").unwrap();
+        fs::remove_file(&gone).unwrap();
+
+        let taken = files.take_where(|entry| entry.is_hidden());
+        assert_eq!(taken.len(), 1);
+        assert_eq!(taken[0].name, ".gone.txt");
+        assert_eq!(taken[0].size, 12, "what was kept when it can't be read");
+
+        let taken = files.take_where(|entry| entry.extension() == Some(OsStr::new("log")));
+        assert_eq!(taken[0].size, 21, "read from the disk again");
+        assert!(files.is_empty());
+    }
+}
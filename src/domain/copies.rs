@@ -0,0 +1,293 @@
+use super::pattern::original_name;
+use super::FileEntry;
+use crate::hashing::HashAlgorithm;
+use crate::scan_index::HashCache;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Bytes compared per read when checking two files for identical contents
+const COMPARE_CHUNK: usize = 64 * 1024;
+
+/// Finds browser and Finder style copies that are byte-identical to their original.
+///
+/// A copy is `name (N).ext`, `name copy.ext` or `name copy N.ext` sitting next to
+/// `name.ext` in the scanned files. Only pairs of equal size are read, and a copy
+/// whose contents differ (or can't be read) isn't reported. Online-only
+/// placeholders are never read, since that downloads them, and on a network mount
+/// (`degraded`) nothing is. Returns copy index → original index.
+///
+/// Pairs are compared on `limits::workers()` threads, one at a time on a spinning disk.
+pub fn find_redundant_copies(files: &[FileEntry], degraded: bool) -> HashMap<usize, usize> {
+    let pairs = candidate_pairs(files, degraded);
+    let same = |a: &Path, b: &Path| same_contents(a, b);
+    compare_pairs(
+        files,
+        &pairs,
+        same,
+        &AtomicUsize::new(0),
+        &AtomicBool::new(false),
+    )
+}
+
+/// Copies being compared with their originals on a worker thread, so a folder of
+/// big downloads doesn't hold up the start of the review
+#[derive(Debug)]
+pub struct CopyFinder {
+    cancel: Arc<AtomicBool>,
+    compared: Arc<AtomicUsize>,
+    total: usize,
+    result: Receiver<HashMap<usize, usize>>,
+}
+
+impl CopyFinder {
+    /// Starts looking for redundant copies among `files` (see `find_redundant_copies`).
+    /// With a scan index in `hashes`, pairs are compared by their `algorithm` hashes
+    /// instead, so files hashed on an earlier run aren't read again.
+    pub fn start(
+        files: Arc<Vec<FileEntry>>,
+        degraded: bool,
+        hashes: HashCache,
+        algorithm: HashAlgorithm,
+    ) -> Self {
+        let pairs = candidate_pairs(&files, degraded);
+        let total = pairs.len();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let compared = Arc::new(AtomicUsize::new(0));
+        let (sender, result) = mpsc::channel();
+        let (stop, count) = (Arc::clone(&cancel), Arc::clone(&compared));
+        thread::spawn(move || {
+            let copies = if hashes.is_enabled() {
+                let same = |a: &Path, b: &Path| -> io::Result<bool> {
+                    Ok(hashes.hash_file(a, algorithm)? == hashes.hash_file(b, algorithm)?)
+                };
+                let copies = compare_pairs(&files, &pairs, same, &count, &stop);
+                let _ = hashes.save();
+                copies
+            } else {
+                let same = |a: &Path, b: &Path| same_contents(a, b);
+                compare_pairs(&files, &pairs, same, &count, &stop)
+            };
+            let _ = sender.send(copies);
+        });
+
+        Self {
+            cancel,
+            compared,
+            total,
+            result,
+        }
+    }
+
+    /// (pairs compared, pairs to compare)
+    pub fn progress(&self) -> (usize, usize) {
+        (
+            self.compared.load(Ordering::Relaxed).min(self.total),
+            self.total,
+        )
+    }
+
+    /// Copy index → original index, once every pair has been compared
+    pub fn poll(&self) -> Option<HashMap<usize, usize>> {
+        self.result.try_recv().ok()
+    }
+}
+
+impl Drop for CopyFinder {
+    /// Workers stop after their current pair once nobody is waiting for results
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// (copy, original) pairs worth reading: named like a copy and its original, of
+/// the same size, neither a placeholder; none on a network mount
+fn candidate_pairs(files: &[FileEntry], degraded: bool) -> Vec<(usize, usize)> {
+    if degraded {
+        return Vec::new();
+    }
+    let by_path: HashMap<&Path, usize> = files
+        .iter()
+        .enumerate()
+        .map(|(i, file)| (file.path.as_path(), i))
+        .collect();
+
+    files
+        .iter()
+        .enumerate()
+        .filter(|(_, file)| !file.is_online_only())
+        .filter_map(|(index, file)| {
+            let original = original_path(&file.path, &file.name)?;
+            let &original_index = by_path.get(original.as_path())?;
+            let same_size = files[original_index].size == file.size;
+            (same_size && !files[original_index].is_online_only())
+                .then_some((index, original_index))
+        })
+        .collect()
+}
+
+/// The pairs whose files have the same bytes by `same`, counting each into
+/// `compared`; stops early once `cancel` is set
+fn compare_pairs(
+    files: &[FileEntry],
+    pairs: &[(usize, usize)],
+    same: impl Fn(&Path, &Path) -> io::Result<bool> + Sync,
+    compared: &AtomicUsize,
+    cancel: &AtomicBool,
+) -> HashMap<usize, usize> {
+    let next = AtomicUsize::new(0);
+    let copies = Mutex::new(HashMap::new());
+    thread::scope(|scope| {
+        for _ in 0..crate::limits::workers().min(pairs.len()) {
+            scope.spawn(|| {
+                while let Some(&(index, original)) = pairs.get(next.fetch_add(1, Ordering::Relaxed))
+                {
+                    if cancel.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    if same(&files[index].path, &files[original].path).unwrap_or(false) {
+                        if let Ok(mut copies) = copies.lock() {
+                            copies.insert(index, original);
+                        }
+                    }
+                    compared.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    copies.into_inner().unwrap_or_default()
+}
+
+/// Where the original of a copy named `name` would be
+fn original_path(path: &Path, name: &str) -> Option<PathBuf> {
+    let original = original_name(name)?;
+    Some(path.with_file_name(original))
+}
+
+/// Whether two files have the same bytes, compared a chunk at a time
+pub fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    let _permit = crate::limits::open_files(2);
+    let mut a = File::open(a)?;
+    let mut b = File::open(b)?;
+    let mut buf_a = vec![0; COMPARE_CHUNK];
+    let mut buf_b = vec![0; COMPARE_CHUNK];
+    loop {
+        crate::limits::throttle(2 * COMPARE_CHUNK as u64);
+        let read = read_full(&mut a, &mut buf_a)?;
+        if read_full(&mut b, &mut buf_b)? != read || buf_a[..read] != buf_b[..read] {
+            return Ok(false);
+        }
+        if read == 0 {
+            return Ok(true);
+        }
+    }
+}
+
+/// Fills `buf` as far as the file allows; short only at end of file
+fn read_full(file: &mut File, buf: &mut [u8]) -> io::Result<usize> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match file.read(&mut buf[filled..])? {
+            0 => break,
+            n => filled += n,
+        }
+    }
+    Ok(filled)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn entries(dir: &TempDir, files: &[(&str, &str)]) -> Vec<FileEntry> {
+        files
+            .iter()
+            .map(|(name, contents)| {
+                let path = dir.path().join(name);
+                fs::write(&path, contents).unwrap();
+                FileEntry::from_path(&path).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_find_redundant_copies() {
+        let dir = TempDir::new().unwrap();
+        let files = entries(
+            &dir,
+            &[
+                ("report.pdf", "quarterly numbers"),
+                ("report (1).pdf", "quarterly numbers"),
+                ("report copy.pdf", "quarterly numbers"),
+                // Same size, different bytes
+                ("report (2).pdf", "quarterly NUMBERS"),
+                // No original alongside
+                ("invoice (1).pdf", "quarterly numbers"),
+            ],
+        );
+
+        let copies = find_redundant_copies(&files, false);
+        assert_eq!(copies.len(), 2);
+        assert_eq!(copies.get(&1), Some(&0));
+        assert_eq!(copies.get(&2), Some(&0));
+
+        // Nothing is read on a network mount
+        assert!(find_redundant_copies(&files, true).is_empty());
+    }
+
+    #[test]
+    fn test_copy_finder_skips_placeholders() {
+        let dir = TempDir::new().unwrap();
+        let mut files = entries(
+            &dir,
+            &[
+                ("song.mp3", "same tune"),
+                ("song (1).mp3", "same tune"),
+                ("clip.mov", "same film"),
+                ("clip (1).mov", "same film"),
+            ],
+        );
+        // Reading an online-only placeholder would download it
+        files[3].disk.allocated = Some(0);
+
+        let finder = CopyFinder::start(
+            Arc::new(files),
+            false,
+            HashCache::default(),
+            HashAlgorithm::default(),
+        );
+        let started = std::time::Instant::now();
+        let copies = loop {
+            if let Some(copies) = finder.poll() {
+                break copies;
+            }
+            assert!(started.elapsed() < std::time::Duration::from_secs(10));
+            thread::sleep(std::time::Duration::from_millis(5));
+        };
+        assert_eq!(copies, HashMap::from([(1, 0)]));
+        assert_eq!(finder.progress(), (1, 1));
+    }
+
+    #[test]
+    fn test_same_contents_across_chunks() {
+        let dir = TempDir::new().unwrap();
+        let a = dir.path().join("a");
+        let b = dir.path().join("b");
+        let mut data = vec![7u8; COMPARE_CHUNK * 2 + 10];
+        fs::write(&a, &data).unwrap();
+        fs::write(&b, &data).unwrap();
+        assert!(same_contents(&a, &b).unwrap());
+
+        // A difference past the first chunk still counts
+        *data.last_mut().unwrap() = 8;
+        fs::write(&b, &data).unwrap();
+        assert!(!same_contents(&a, &b).unwrap());
+    }
+}
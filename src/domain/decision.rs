@@ -1,7 +1,28 @@
+use super::FileType;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Decision {
     Keep,
     Trash,
+    /// Move the file into this directory (a quick-move target)
+    Move(PathBuf),
+    /// Deferred on purpose: nothing happens to the file, and the summary offers
+    /// to review the skipped files again
+    Skip,
+}
+
+/// Per-file-type default from the config (`"default_decision": {"Image": "suggest-trash"}`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DefaultDecision {
+    /// Show keep as the suggested decision
+    SuggestKeep,
+    /// Show trash as the suggested decision
+    SuggestTrash,
+    /// Always confirm trash, even with `--yes` or in dry-run mode
+    AlwaysAsk,
 }
 
 #[derive(Debug, Clone)]
@@ -9,4 +30,13 @@ pub struct DecisionStatistics {
     pub total_files: usize,
     pub kept: usize,
     pub trashed: usize,
+    pub moved: usize,
+    /// Files explicitly skipped for later
+    pub skipped: usize,
+    /// Files without a decision
+    pub undecided: usize,
+    /// Space actually freed by the trashed files (allocated blocks, hard links counted once)
+    pub freed_bytes: u64,
+    /// Space freed per file type, omitting types with nothing freed
+    pub freed_by_type: Vec<(FileType, u64)>,
 }
@@ -1,19 +1,69 @@
-use super::{Decision, DecisionStatistics, FileEntry};
+use super::{
+    reclaimable_bytes, verify_apply, ApplyJob, ApplyOutcome, Decision, DecisionListener,
+    DecisionStatistics, FileEntry, FileType, QueueTally,
+};
+use crate::error::FileTinderError;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fmt;
 use std::fs;
 use std::io;
-use std::path::PathBuf;
-use std::time::SystemTime;
-
-#[derive(Debug)]
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Owns the session's decisions: the ordered undo stack, the staged files behind
+/// trash decisions, and their apply status. `AppState` queries it rather than
+/// keeping its own copy.
+///
+/// Moves happen right away rather than being staged: the file already sits where
+/// the user asked, and undo moves it back.
 pub struct DecisionEngine {
-    pub files: Vec<FileEntry>,
+    /// Shared with `AppState`, so the file list is held once however large the queue
+    pub files: Arc<Vec<FileEntry>>,
     pub decisions: Vec<(usize, Decision)>,
+    /// When each entry in `decisions` was made (or last changed), for the undo grace period
+    decided_at: Vec<Instant>,
+    /// Number of entries in `decisions` per file, so re-decided files count once
+    decided: HashMap<usize, usize>,
+    /// Files with no entry in `decisions`, kept in step with `decided`
+    undecided: QueueTally,
+    /// Batches recorded together, as (group id, positions in `decisions`)
+    groups: Vec<(u64, Range<usize>)>,
+    next_group_id: u64,
+    /// Files already taken out of staging by background apply, in order
+    applied: Vec<(usize, ApplyOutcome)>,
+    /// When background apply started trashing each file that reached the trash
+    trashed_at: HashMap<usize, SystemTime>,
+    /// Names files are moved under instead of their own, after a name clash
+    move_names: HashMap<usize, OsString>,
     staging_dir: PathBuf,
     dry_run: bool,
+    listeners: Vec<Box<dyn DecisionListener>>,
+}
+
+impl fmt::Debug for DecisionEngine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DecisionEngine")
+            .field("files", &self.files)
+            .field("decisions", &self.decisions)
+            .field("groups", &self.groups)
+            .field("applied", &self.applied)
+            .field("staging_dir", &self.staging_dir)
+            .field("dry_run", &self.dry_run)
+            .field("listeners", &self.listeners.len())
+            .finish()
+    }
 }
 
 impl DecisionEngine {
     pub fn new(files: Vec<FileEntry>) -> Self {
+        Self::with_files(files.into())
+    }
+
+    /// An engine over a file list shared with its owner (see `AppState::new`)
+    pub fn with_files(files: Arc<Vec<FileEntry>>) -> Self {
         use std::time::UNIX_EPOCH;
 
         let timestamp = SystemTime::now()
@@ -24,14 +74,38 @@ impl DecisionEngine {
             std::env::temp_dir().join(format!("fswp-{}-{}", std::process::id(), timestamp));
         fs::create_dir_all(&staging_dir).ok();
 
+        let undecided = QueueTally::new(&files);
         Self {
             files,
             decisions: Vec::new(),
+            decided_at: Vec::new(),
+            decided: HashMap::new(),
+            undecided,
+            groups: Vec::new(),
+            next_group_id: 0,
+            applied: Vec::new(),
+            trashed_at: HashMap::new(),
+            move_names: HashMap::new(),
             staging_dir,
             dry_run: false,
+            listeners: Vec::new(),
         }
     }
 
+    /// Takes over `files`, the current list with more files after it; the new ones
+    /// start undecided
+    pub fn admit(&mut self, files: Arc<Vec<FileEntry>>) {
+        for file in &files[self.files.len()..] {
+            self.undecided.add(file);
+        }
+        self.files = files;
+    }
+
+    /// Registers a listener notified of every decision, undo and apply step
+    pub fn add_listener(&mut self, listener: Box<dyn DecisionListener>) {
+        self.listeners.push(listener);
+    }
+
     pub fn set_dry_run(&mut self, dry_run: bool) {
         self.dry_run = dry_run;
     }
@@ -41,6 +115,14 @@ impl DecisionEngine {
     }
 
     pub fn record_decision(&mut self, index: usize, decision: Decision) -> io::Result<()> {
+        self.stage_decision(index, decision.clone())?;
+        for listener in &mut self.listeners {
+            listener.on_decision(index, &self.files[index], &decision);
+        }
+        Ok(())
+    }
+
+    fn stage_decision(&mut self, index: usize, decision: Decision) -> io::Result<()> {
         if index >= self.files.len() {
             return Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -48,20 +130,26 @@ impl DecisionEngine {
             ));
         }
 
-        let file_entry = &self.files[index];
-        let original_path = &file_entry.path;
+        if !self.dry_run {
+            self.carry_out(index, &decision)?;
+        }
+
+        self.decisions.push((index, decision));
+        self.decided_at.push(Instant::now());
+        let count = self.decided.entry(index).or_insert(0);
+        if *count == 0 {
+            self.undecided.remove(&self.files[index]);
+        }
+        *count += 1;
+        Ok(())
+    }
 
+    /// Does what a decision means on disk: stages a trashed file, moves a moved one
+    fn carry_out(&self, index: usize, decision: &Decision) -> io::Result<()> {
+        let original_path = &self.files[index].path;
         match decision {
-            Decision::Keep => {
-                self.decisions.push((index, decision));
-                Ok(())
-            }
+            Decision::Keep | Decision::Skip => Ok(()),
             Decision::Trash => {
-                if self.dry_run {
-                    self.decisions.push((index, decision));
-                    return Ok(());
-                }
-
                 if !original_path.exists() {
                     return Err(io::Error::new(
                         io::ErrorKind::NotFound,
@@ -71,77 +159,583 @@ impl DecisionEngine {
 
                 let staged_path = self.get_staged_path(index);
                 fs::create_dir_all(staged_path.parent().unwrap())?;
-                fs::rename(original_path, &staged_path)?;
-
-                self.decisions.push((index, decision));
-                Ok(())
+                fs::rename(original_path, &staged_path)
+            }
+            Decision::Move(dir) => {
+                let destination = self.move_destination(index, dir);
+                if destination.exists() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::AlreadyExists,
+                        format!("{:?} already exists", destination),
+                    ));
+                }
+                fs::create_dir_all(dir)?;
+                move_file(original_path, &destination)
             }
         }
     }
 
-    pub fn undo(&mut self) -> io::Result<()> {
-        let (index, decision) = self
-            .decisions
-            .pop()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No decisions to undo"))?;
-
-        if self.dry_run {
-            return Ok(());
-        }
-
+    /// Puts a file back where it was before `carry_out`
+    fn take_back(&self, index: usize, decision: &Decision) -> io::Result<()> {
         match decision {
-            Decision::Keep => Ok(()),
+            Decision::Keep | Decision::Skip => Ok(()),
+            Decision::Move(dir) => {
+                let destination = self.move_destination(index, dir);
+                if !destination.exists() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!("Moved file not found: {:?}", destination),
+                    ));
+                }
+                move_file(&destination, &self.files[index].path)
+            }
             Decision::Trash => {
-                let file_entry = &self.files[index];
-                let original_path = &file_entry.path;
                 let staged_path = self.get_staged_path(index);
-
                 if !staged_path.exists() {
                     return Err(io::Error::new(
                         io::ErrorKind::NotFound,
                         format!("Staged file not found: {:?}", staged_path),
                     ));
                 }
+                fs::rename(&staged_path, &self.files[index].path)
+            }
+        }
+    }
+
+    /// Changes the decision on an already decided file in place: it keeps its
+    /// spot in the undo stack and its batch. Returns the decision it replaced.
+    ///
+    /// If the new decision can't be carried out, the old one is put back.
+    pub fn change_decision(&mut self, index: usize, decision: Decision) -> io::Result<Decision> {
+        let position = self
+            .decisions
+            .iter()
+            .rposition(|(i, _)| *i == index)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Not decided yet"))?;
+        if self.is_applied(index) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "Already moved to the system trash; restore it from there",
+            ));
+        }
+        let old = self.decisions[position].1.clone();
+        if old == decision {
+            return Ok(old);
+        }
+
+        if !self.dry_run {
+            self.take_back(index, &old)?;
+            if let Err(e) = self.carry_out(index, &decision) {
+                self.carry_out(index, &old).ok();
+                return Err(e);
+            }
+        }
+
+        self.decisions[position].1 = decision.clone();
+        // A changed decision gets a fresh grace period
+        self.decided_at[position] = Instant::now();
+        for listener in &mut self.listeners {
+            listener.on_change(index, &self.files[index], &old, &decision);
+        }
+        Ok(old)
+    }
+
+    /// Whether the file at `index` has a decision on the undo stack
+    pub fn is_decided(&self, index: usize) -> bool {
+        self.decided.contains_key(&index)
+    }
+
+    /// Count, size and types of the files without a decision
+    pub fn undecided(&self) -> &QueueTally {
+        &self.undecided
+    }
+
+    /// Takes a newly measured size of the file at `index` into the undecided
+    /// tally; call before the entry in `files` is resized
+    pub fn note_resize(&mut self, index: usize, bytes: u64) {
+        if !self.is_decided(index) {
+            if let Some(file) = self.files.get(index) {
+                self.undecided.resize(file, bytes);
+            }
+        }
+    }
+
+    /// Number of distinct files decided so far
+    pub fn decided_count(&self) -> usize {
+        self.decided.len()
+    }
+
+    /// Space actually freed by the files decided as trash so far
+    pub fn freed_bytes(&self) -> u64 {
+        reclaimable_bytes(self.trashed_files())
+    }
+
+    /// Trash decisions still staged, i.e. not yet taken by background apply
+    pub fn pending_trash_count(&self) -> usize {
+        self.decisions
+            .iter()
+            .filter(|(i, decision)| *decision == Decision::Trash && !self.is_applied(*i))
+            .count()
+    }
+
+    fn trashed_files(&self) -> impl Iterator<Item = &FileEntry> {
+        self.decisions
+            .iter()
+            .filter(|(_, decision)| *decision == Decision::Trash)
+            .filter_map(|(index, _)| self.files.get(*index))
+    }
+
+    /// Records several decisions as one group that undoes as a unit.
+    ///
+    /// All-or-nothing: if any decision fails, the ones already recorded are undone.
+    pub fn record_batch(&mut self, entries: &[(usize, Decision)]) -> io::Result<u64> {
+        let start = self.decisions.len();
+        for (index, decision) in entries {
+            if let Err(e) = self.record_decision(*index, decision.clone()) {
+                while self.decisions.len() > start {
+                    self.undo().ok();
+                }
+                return Err(e);
+            }
+        }
+
+        let id = self.next_group_id;
+        self.next_group_id += 1;
+        if self.decisions.len() > start {
+            self.groups.push((id, start..self.decisions.len()));
+        }
+        Ok(id)
+    }
+
+    /// Makes the last `count` decisions one group that undoes as a unit, taking in
+    /// any batch that overlaps them; returns the group's id
+    pub fn group_last(&mut self, count: usize) -> u64 {
+        let mut start = self.decisions.len().saturating_sub(count);
+        while let Some((_, range)) = self.groups.last() {
+            if range.end <= start {
+                break;
+            }
+            start = start.min(range.start);
+            self.groups.pop();
+        }
+        let id = self.next_group_id;
+        self.next_group_id += 1;
+        if start < self.decisions.len() {
+            self.groups.push((id, start..self.decisions.len()));
+        }
+        id
+    }
+
+    /// Group id of the decision at `position` in `decisions`, if it was part of a batch
+    pub fn group_of(&self, position: usize) -> Option<u64> {
+        self.groups
+            .iter()
+            .find(|(_, range)| range.contains(&position))
+            .map(|(id, _)| *id)
+    }
+
+    /// Undoes the last decision, or the whole batch it belongs to.
+    ///
+    /// Returns how many decisions were undone; a failure partway through a batch
+    /// stops early, leaving the rest of the batch in place.
+    pub fn undo_group(&mut self) -> io::Result<usize> {
+        let count = match self.groups.last() {
+            Some((_, range)) if range.end == self.decisions.len() => range.len(),
+            _ => 1,
+        };
+
+        self.undo()?;
+        let mut undone = 1;
+        while undone < count && self.undo().is_ok() {
+            undone += 1;
+        }
+        Ok(undone)
+    }
+
+    pub fn undo(&mut self) -> io::Result<()> {
+        let (index, decision) = self.restore_last()?;
+        for listener in &mut self.listeners {
+            listener.on_undo(index, &self.files[index], &decision);
+        }
+        Ok(())
+    }
+
+    /// Pops the last decision and puts a staged file back; returns what was undone
+    fn restore_last(&mut self) -> io::Result<(usize, Decision)> {
+        // Background-applied files are in the system trash now, so they can only be restored from there
+        if let Some(&(index, _)) = self.decisions.last() {
+            if self.is_applied(index) {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Already moved to the system trash; restore it from there",
+                ));
+            }
+        }
+
+        let (index, decision) = self
+            .decisions
+            .pop()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "No decisions to undo"))?;
+        self.decided_at.pop();
+
+        if let Some(count) = self.decided.get_mut(&index) {
+            *count -= 1;
+            if *count == 0 {
+                self.decided.remove(&index);
+                self.undecided.add(&self.files[index]);
+            }
+        }
 
-                fs::rename(&staged_path, original_path)?;
-                Ok(())
+        // Shrink (or drop) the batch this decision belonged to
+        if let Some((_, range)) = self.groups.last_mut() {
+            range.end = range.end.min(self.decisions.len());
+            if range.start >= range.end {
+                self.groups.pop();
             }
         }
+
+        if !self.dry_run {
+            self.take_back(index, &decision)?;
+        }
+        Ok((index, decision))
     }
 
     pub fn get_statistics(&self) -> DecisionStatistics {
         let mut kept = 0;
         let mut trashed = 0;
+        let mut moved = 0;
+        let mut skipped = 0;
 
         for (_, decision) in &self.decisions {
             match decision {
                 Decision::Keep => kept += 1,
                 Decision::Trash => trashed += 1,
+                Decision::Move(_) => moved += 1,
+                Decision::Skip => skipped += 1,
             }
         }
 
+        let freed_by_type = FileType::ALL
+            .into_iter()
+            .map(|file_type| {
+                let freed = reclaimable_bytes(
+                    self.trashed_files()
+                        .filter(|file| file.file_type == file_type),
+                );
+                (file_type, freed)
+            })
+            .filter(|(_, freed)| *freed > 0)
+            .collect();
+
         DecisionStatistics {
             total_files: self.files.len(),
             kept,
             trashed,
+            moved,
+            skipped,
+            undecided: self.files.len().saturating_sub(self.decided_count()),
+            freed_bytes: self.freed_bytes(),
+            freed_by_type,
         }
     }
 
-    pub fn commit_trash_decisions(&self) -> io::Result<()> {
-        for (index, decision) in &self.decisions {
-            if *decision == Decision::Trash {
-                let staged_path = self.get_staged_path(*index);
-                if staged_path.exists() {
-                    trash::delete(&staged_path).map_err(|e| io::Error::other(e.to_string()))?;
-                }
+    /// Moves every staged file to the system trash in one go.
+    ///
+    /// Files that fail don't stop the rest; every failure is listed in the error.
+    pub fn commit_trash_decisions(&mut self) -> crate::Result<()> {
+        let mut job = self.start_apply();
+        while self.apply_next(&mut job) {}
+        let failures: Vec<String> = job
+            .results
+            .into_iter()
+            .filter_map(|(index, outcome)| match outcome {
+                ApplyOutcome::Failed(e) => Some(format!("{}: {}", self.files[index].name, e)),
+                _ => None,
+            })
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(FileTinderError::TrashError(failures.join("; ")))
+        }
+    }
+
+    /// Starts moving the session's trashed files to the system trash (nothing in dry-run mode).
+    ///
+    /// Files already handled by background apply are included as finished results.
+    pub fn start_apply(&self) -> ApplyJob {
+        if self.dry_run {
+            return ApplyJob::new(Vec::new());
+        }
+        ApplyJob::with_results(
+            self.decisions
+                .iter()
+                .filter(|(_, decision)| *decision == Decision::Trash)
+                .map(|(index, _)| *index)
+                .filter(|index| !self.is_applied(*index))
+                .collect(),
+            self.applied.clone(),
+        )
+    }
+
+    /// Re-checks original locations once `job` is finished and stores any anomalies in it
+    pub fn verify(&self, job: &mut ApplyJob) {
+        job.issues = Some(verify_apply(&self.files, &self.decisions, &job.results));
+    }
+
+    /// Whether background apply has already taken this file out of staging
+    pub fn is_applied(&self, index: usize) -> bool {
+        self.applied.iter().any(|(i, _)| *i == index)
+    }
+
+    /// When background apply sent `index` to the system trash (no earlier than the
+    /// trash's own record of it); `None` when it hasn't
+    pub fn trashed_at(&self, index: usize) -> Option<SystemTime> {
+        self.trashed_at.get(&index).copied()
+    }
+
+    /// Files background apply has taken out of staging, with how it went
+    pub fn applied(&self) -> &[(usize, ApplyOutcome)] {
+        &self.applied
+    }
+
+    /// Number of files background apply has taken out of staging (restore-only now)
+    pub fn applied_count(&self) -> usize {
+        self.applied.len()
+    }
+
+    /// Whether `apply_in_background` has anything to do
+    pub fn has_background_work(&self, keep_recent: usize) -> bool {
+        let eligible = self.decisions.len().saturating_sub(keep_recent);
+        self.next_background_index(|position| position < eligible)
+            .is_some()
+    }
+
+    /// Trashes the oldest staged file, leaving the `keep_recent` latest decisions
+    /// staged so they can still be undone. Returns the file index and outcome.
+    pub fn apply_in_background(&mut self, keep_recent: usize) -> Option<(usize, ApplyOutcome)> {
+        let eligible = self.decisions.len().saturating_sub(keep_recent);
+        let index = self.next_background_index(|position| position < eligible)?;
+        Some(self.apply_staged(index))
+    }
+
+    /// Whether `apply_expired` has anything to do
+    pub fn has_expired_work(&self, grace: Duration) -> bool {
+        self.next_background_index(|position| self.decided_at[position].elapsed() >= grace)
+            .is_some()
+    }
+
+    /// Trashes the oldest staged file decided more than `grace` ago (apply on
+    /// decide); younger ones stay staged so they can still be undone
+    pub fn apply_expired(&mut self, grace: Duration) -> Option<(usize, ApplyOutcome)> {
+        let index =
+            self.next_background_index(|position| self.decided_at[position].elapsed() >= grace)?;
+        Some(self.apply_staged(index))
+    }
+
+    /// Staged trash decisions still inside `grace`, oldest first, as (file index,
+    /// time left before they're applied)
+    pub fn within_grace(&self, grace: Duration) -> Vec<(usize, Duration)> {
+        if self.dry_run {
+            return Vec::new();
+        }
+        self.decisions
+            .iter()
+            .zip(&self.decided_at)
+            .filter(|((index, decision), _)| {
+                *decision == Decision::Trash && !self.is_applied(*index)
+            })
+            .filter_map(|((index, _), at)| Some((*index, grace.checked_sub(at.elapsed())?)))
+            .collect()
+    }
+
+    /// The first staged trash decision whose position in `decisions` is `eligible`
+    fn next_background_index(&self, eligible: impl Fn(usize) -> bool) -> Option<usize> {
+        if self.dry_run {
+            return None;
+        }
+        self.decisions
+            .iter()
+            .enumerate()
+            .filter(|(position, (_, decision))| *decision == Decision::Trash && eligible(*position))
+            .map(|(_, (index, _))| *index)
+            .find(|index| !self.is_applied(*index))
+    }
+
+    fn apply_staged(&mut self, index: usize) -> (usize, ApplyOutcome) {
+        let started = SystemTime::now();
+        let outcome = match self.trash_staged(index) {
+            Ok(()) => {
+                self.notify_applied(index);
+                ApplyOutcome::Trashed
+            }
+            Err(e) => ApplyOutcome::Failed(e.to_string()),
+        };
+        self.record_applied(index, outcome.clone(), started);
+        (index, outcome)
+    }
+
+    /// Notes that background apply took `index` out of staging with `outcome`,
+    /// having started at `started`
+    pub(crate) fn record_applied(
+        &mut self,
+        index: usize,
+        outcome: ApplyOutcome,
+        started: SystemTime,
+    ) {
+        if outcome == ApplyOutcome::Trashed {
+            self.trashed_at.insert(index, started);
+        }
+        self.applied.push((index, outcome));
+    }
+
+    /// Trashes the next file in `job`; returns false once there is nothing left
+    pub fn apply_next(&mut self, job: &mut ApplyJob) -> bool {
+        let Some(index) = job.take_next() else {
+            return false;
+        };
+        let outcome = match self.trash_staged(index) {
+            Ok(()) => {
+                job.bytes_done += self.files[index].size;
+                self.notify_applied(index);
+                ApplyOutcome::Trashed
             }
+            Err(e) => ApplyOutcome::Failed(e.to_string()),
+        };
+        job.results.push((index, outcome));
+        for listener in &mut self.listeners {
+            listener.on_apply_progress(job.done(), job.total, &self.files[index]);
         }
-        Ok(())
+        true
+    }
+
+    fn notify_applied(&mut self, index: usize) {
+        for listener in &mut self.listeners {
+            listener.on_applied(index, &self.files[index]);
+        }
+    }
+
+    /// Stops `job` between files, putting every file it hadn't reached back in place
+    pub fn cancel_apply(&mut self, job: &mut ApplyJob) {
+        job.cancelled = true;
+        for index in job.take_pending() {
+            let staged_path = self.get_staged_path(index);
+            let outcome = match fs::rename(&staged_path, &self.files[index].path) {
+                Ok(()) => ApplyOutcome::Restored,
+                Err(e) => {
+                    ApplyOutcome::Failed(format!("could not restore from {:?}: {}", staged_path, e))
+                }
+            };
+            job.results.push((index, outcome));
+        }
+    }
+
+    /// Moves a staged file to the system trash.
+    ///
+    /// The file goes back to its original path first, so the trash records (and
+    /// restores to) the real location rather than the staging directory.
+    fn trash_staged(&self, index: usize) -> io::Result<()> {
+        let staged_path = self.get_staged_path(index);
+        if !staged_path.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("Staged file not found: {:?}", staged_path),
+            ));
+        }
+
+        let original_path = &self.files[index].path;
+        let target = if !original_path.exists() && fs::rename(&staged_path, original_path).is_ok() {
+            original_path.clone()
+        } else {
+            staged_path
+        };
+        trash::delete(&target).map_err(|e| io::Error::other(e.to_string()))
     }
 
-    fn get_staged_path(&self, index: usize) -> PathBuf {
+    pub(crate) fn get_staged_path(&self, index: usize) -> PathBuf {
         self.staging_dir.join(format!("file_{}", index))
     }
+
+    /// Where a move into `dir` puts the file at `index`
+    pub fn move_destination(&self, index: usize, dir: &Path) -> PathBuf {
+        match self.move_names.get(&index) {
+            Some(name) => dir.join(name),
+            None => dir.join(self.files[index].os_name()),
+        }
+    }
+
+    /// Moves the file at `index` under `name` from now on, to sidestep a name
+    /// already taken in the target directory
+    pub fn rename_on_move(&mut self, index: usize, name: OsString) {
+        self.move_names.insert(index, name);
+    }
+
+    /// The first of `name (1).ext`, `name (2).ext`, ... not taken in `dir`
+    pub fn free_move_name(&self, index: usize, dir: &Path) -> OsString {
+        let path = &self.files[index].path;
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let extension = path
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        (1..)
+            .map(|n| OsString::from(format!("{} ({}){}", stem, n, extension)))
+            .find(|name| !dir.join(name).exists())
+            .unwrap_or_default()
+    }
+
+    /// Where the file at `index` is on disk after `decision` was carried out
+    /// (its staged copy, its move destination, or in place)
+    pub fn location(&self, index: usize, decision: &Decision) -> PathBuf {
+        match decision {
+            Decision::Trash if !self.dry_run => self.get_staged_path(index),
+            Decision::Move(dir) if !self.dry_run => self.move_destination(index, dir),
+            _ => self.files[index].path.clone(),
+        }
+    }
+}
+
+/// Renames `from` to `to`, copying and removing instead across filesystems
+fn move_file(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices && from.is_dir() => {
+            copy_dir(from, to)?;
+            fs::remove_dir_all(from)
+        }
+        Err(e) if e.kind() == io::ErrorKind::CrossesDevices => {
+            fs::copy(from, to)?;
+            fs::remove_file(from)
+        }
+        result => result,
+    }
+}
+
+/// Copies a directory tree; symlinks are recreated rather than followed
+fn copy_dir(from: &Path, to: &Path) -> io::Result<()> {
+    fs::create_dir(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        let target = to.join(entry.file_name());
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            copy_dir(&entry.path(), &target)?;
+        } else if file_type.is_symlink() {
+            copy_symlink(&entry.path(), &target)?;
+        } else {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+#[cfg(not(unix))]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    fs::copy(from, to).map(|_| ())
 }
 
 impl Drop for DecisionEngine {
@@ -170,6 +764,7 @@ mod tests {
             size: 0,
             modified_date: Utc::now(),
             file_type: FileType::Text,
+            disk: Default::default(),
         }
     }
 
@@ -259,6 +854,147 @@ mod tests {
         assert!(file_path.exists());
     }
 
+    #[test]
+    fn test_decision_engine_move_and_undo() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("photo.jpg");
+        fs::write(&file_path, b"content").unwrap();
+        let archive = temp_dir.path().join("Archive");
+
+        let entry = create_test_entry_with_path(file_path.clone());
+        let mut engine = DecisionEngine::new(vec![entry]);
+
+        // The target directory is created on first use
+        engine
+            .record_decision(0, Decision::Move(archive.clone()))
+            .unwrap();
+        assert!(!file_path.exists());
+        assert!(archive.join("photo.jpg").exists());
+        assert_eq!(engine.get_statistics().moved, 1);
+        // Moves aren't trashed when applying
+        assert!(engine.start_apply().is_finished());
+
+        engine.undo().unwrap();
+        assert!(file_path.exists());
+        assert!(!archive.join("photo.jpg").exists());
+    }
+
+    #[test]
+    fn test_decision_engine_trash_directory_and_undo() {
+        let temp_dir = TempDir::new().unwrap();
+        let project = temp_dir.path().join("old-project");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("src/main.rs"), b"fn main() {}").unwrap();
+
+        let mut engine = DecisionEngine::new(vec![FileEntry::from_dir(&project).unwrap()]);
+        engine.record_decision(0, Decision::Trash).unwrap();
+        assert!(!project.exists());
+        assert!(engine.get_staged_path(0).join("src/main.rs").exists());
+
+        engine.undo().unwrap();
+        assert!(project.join("src/main.rs").exists());
+    }
+
+    #[test]
+    fn test_copy_dir_copies_the_tree() {
+        let temp_dir = TempDir::new().unwrap();
+        let from = temp_dir.path().join("from");
+        fs::create_dir_all(from.join("a/b")).unwrap();
+        fs::write(from.join("top.txt"), b"1").unwrap();
+        fs::write(from.join("a/b/deep.txt"), b"2").unwrap();
+
+        let to = temp_dir.path().join("to");
+        copy_dir(&from, &to).unwrap();
+        assert_eq!(fs::read(to.join("top.txt")).unwrap(), b"1");
+        assert_eq!(fs::read(to.join("a/b/deep.txt")).unwrap(), b"2");
+        // Refuses to merge into an existing directory
+        assert!(copy_dir(&from, &to).is_err());
+    }
+
+    #[test]
+    fn test_decision_engine_change_decision() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("photo.jpg");
+        fs::write(&file_path, b"content").unwrap();
+        let archive = temp_dir.path().join("Archive");
+        fs::create_dir(&archive).unwrap();
+        fs::write(archive.join("photo.jpg"), b"taken").unwrap();
+
+        let entry = create_test_entry_with_path(file_path.clone());
+        let mut engine = DecisionEngine::new(vec![entry]);
+        engine.record_decision(0, Decision::Trash).unwrap();
+        assert!(!file_path.exists());
+
+        // Trash → keep puts the staged file back
+        assert_eq!(
+            engine.change_decision(0, Decision::Keep).unwrap(),
+            Decision::Trash
+        );
+        assert!(file_path.exists());
+        assert_eq!(engine.decisions, vec![(0, Decision::Keep)]);
+
+        // A move that can't happen leaves the earlier decision in place
+        engine.change_decision(0, Decision::Trash).unwrap();
+        let err = engine
+            .change_decision(0, Decision::Move(archive.clone()))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(engine.decisions, vec![(0, Decision::Trash)]);
+        assert!(!file_path.exists());
+
+        // Undo still reverts whatever the decision is now
+        engine.undo().unwrap();
+        assert!(file_path.exists());
+        assert!(engine.change_decision(0, Decision::Keep).is_err());
+    }
+
+    #[test]
+    fn test_decision_engine_move_refuses_to_overwrite() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("photo.jpg");
+        fs::write(&file_path, b"new").unwrap();
+        let archive = temp_dir.path().join("Archive");
+        fs::create_dir(&archive).unwrap();
+        fs::write(archive.join("photo.jpg"), b"old").unwrap();
+
+        let entry = create_test_entry_with_path(file_path.clone());
+        let mut engine = DecisionEngine::new(vec![entry]);
+
+        let err = engine
+            .record_decision(0, Decision::Move(archive.clone()))
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(file_path.exists());
+        assert_eq!(fs::read(archive.join("photo.jpg")).unwrap(), b"old");
+        assert!(engine.decisions.is_empty());
+    }
+
+    #[test]
+    fn test_decision_engine_move_under_free_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("photo.jpg");
+        fs::write(&file_path, b"new").unwrap();
+        let archive = temp_dir.path().join("Archive");
+        fs::create_dir(&archive).unwrap();
+        fs::write(archive.join("photo.jpg"), b"old").unwrap();
+        fs::write(archive.join("photo (1).jpg"), b"older").unwrap();
+
+        let mut engine = DecisionEngine::new(vec![create_test_entry_with_path(file_path.clone())]);
+        let name = engine.free_move_name(0, &archive);
+        assert_eq!(name, "photo (2).jpg");
+        engine.rename_on_move(0, name);
+        engine
+            .record_decision(0, Decision::Move(archive.clone()))
+            .unwrap();
+        assert_eq!(fs::read(archive.join("photo (2).jpg")).unwrap(), b"new");
+        assert_eq!(fs::read(archive.join("photo.jpg")).unwrap(), b"old");
+
+        // Undo brings it back under its own name
+        engine.undo().unwrap();
+        assert_eq!(fs::read(&file_path).unwrap(), b"new");
+        assert!(!archive.join("photo (2).jpg").exists());
+    }
+
     #[test]
     fn test_decision_engine_undo_empty() {
         let temp_dir = TempDir::new().unwrap();
@@ -339,6 +1075,328 @@ mod tests {
         assert_eq!(engine.decisions.len(), 0);
     }
 
+    #[test]
+    fn test_decision_engine_undo_group_reverts_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths: Vec<_> = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, b"content").unwrap();
+                path
+            })
+            .collect();
+        let files = paths
+            .iter()
+            .cloned()
+            .map(create_test_entry_with_path)
+            .collect();
+
+        let mut engine = DecisionEngine::new(files);
+        engine.record_decision(0, Decision::Keep).unwrap();
+        let id = engine
+            .record_batch(&[(1, Decision::Trash), (2, Decision::Trash)])
+            .unwrap();
+        assert_eq!(engine.group_of(0), None);
+        assert_eq!(engine.group_of(1), Some(id));
+        assert_eq!(engine.group_of(2), Some(id));
+
+        // The whole batch comes back in one step
+        assert_eq!(engine.undo_group().unwrap(), 2);
+        assert!(paths[1].exists());
+        assert!(paths[2].exists());
+        assert_eq!(engine.decisions.len(), 1);
+
+        // Ungrouped decisions undo one at a time
+        assert_eq!(engine.undo_group().unwrap(), 1);
+        assert!(engine.decisions.is_empty());
+    }
+
+    #[test]
+    fn test_decision_engine_group_last_takes_in_overlapping_batches() {
+        let files = ["a.txt", "b.txt", "c.txt", "d.txt"]
+            .iter()
+            .map(|name| create_test_entry_with_path(PathBuf::from(name)))
+            .collect();
+        let mut engine = DecisionEngine::new(files);
+        engine.set_dry_run(true);
+        engine.record_decision(0, Decision::Keep).unwrap();
+        engine
+            .record_batch(&[(1, Decision::Trash), (2, Decision::Trash)])
+            .unwrap();
+        engine.record_decision(3, Decision::Keep).unwrap();
+
+        // The last two reach into the batch, so all three group together
+        let id = engine.group_last(2);
+        assert_eq!(engine.group_of(0), None);
+        assert_eq!(engine.group_of(1), Some(id));
+        assert_eq!(engine.group_of(3), Some(id));
+        assert_eq!(engine.undo_group().unwrap(), 3);
+        assert_eq!(engine.decisions.len(), 1);
+    }
+
+    #[test]
+    fn test_decision_engine_record_batch_is_all_or_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let existing = temp_dir.path().join("exists.txt");
+        fs::write(&existing, b"content").unwrap();
+        let files = vec![
+            create_test_entry_with_path(existing.clone()),
+            create_test_entry_with_path(temp_dir.path().join("missing.txt")),
+        ];
+
+        let mut engine = DecisionEngine::new(files);
+        let result = engine.record_batch(&[(0, Decision::Trash), (1, Decision::Trash)]);
+
+        assert!(result.is_err());
+        assert!(engine.decisions.is_empty());
+        assert!(existing.exists());
+    }
+
+    #[test]
+    fn test_decision_engine_single_undo_shrinks_batch() {
+        let temp_dir = TempDir::new().unwrap();
+        let files = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(|name| create_test_entry_with_path(temp_dir.path().join(name)))
+            .collect();
+
+        let mut engine = DecisionEngine::new(files);
+        engine
+            .record_batch(&[
+                (0, Decision::Keep),
+                (1, Decision::Keep),
+                (2, Decision::Keep),
+            ])
+            .unwrap();
+        engine.undo().unwrap();
+
+        assert_eq!(engine.undo_group().unwrap(), 2);
+        assert!(engine.decisions.is_empty());
+    }
+
+    #[derive(Clone, Default)]
+    struct Recorder(std::rc::Rc<std::cell::RefCell<Vec<String>>>);
+
+    impl DecisionListener for Recorder {
+        fn on_decision(&mut self, index: usize, _file: &FileEntry, decision: &Decision) {
+            self.0
+                .borrow_mut()
+                .push(format!("decide {} {:?}", index, decision));
+        }
+
+        fn on_undo(&mut self, index: usize, _file: &FileEntry, decision: &Decision) {
+            self.0
+                .borrow_mut()
+                .push(format!("undo {} {:?}", index, decision));
+        }
+
+        fn on_apply_progress(&mut self, done: usize, total: usize, file: &FileEntry) {
+            self.0
+                .borrow_mut()
+                .push(format!("apply {}/{} {}", done, total, file.name));
+        }
+    }
+
+    #[test]
+    fn test_decision_engine_notifies_listeners() {
+        let temp_dir = TempDir::new().unwrap();
+        let files = ["a.txt", "b.txt"]
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, b"content").unwrap();
+                create_test_entry_with_path(path)
+            })
+            .collect();
+
+        let recorder = Recorder::default();
+        let mut engine = DecisionEngine::new(files);
+        engine.set_dry_run(true);
+        engine.add_listener(Box::new(recorder.clone()));
+
+        engine.record_decision(0, Decision::Keep).unwrap();
+        engine.record_decision(1, Decision::Trash).unwrap();
+        engine.undo().unwrap();
+        // Failed decisions and undos are not reported
+        assert!(engine.record_decision(5, Decision::Keep).is_err());
+
+        assert_eq!(
+            *recorder.0.borrow(),
+            vec!["decide 0 Keep", "decide 1 Trash", "undo 1 Trash"]
+        );
+    }
+
+    #[test]
+    fn test_decision_engine_cancel_apply_restores_pending() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths: Vec<_> = ["a.txt", "b.txt"]
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, b"content").unwrap();
+                path
+            })
+            .collect();
+        let files = paths
+            .iter()
+            .cloned()
+            .map(create_test_entry_with_path)
+            .collect();
+
+        let mut engine = DecisionEngine::new(files);
+        engine.record_decision(0, Decision::Trash).unwrap();
+        engine.record_decision(1, Decision::Trash).unwrap();
+
+        let mut job = engine.start_apply();
+        assert_eq!(job.total, 2);
+        engine.cancel_apply(&mut job);
+
+        assert!(job.cancelled);
+        assert!(job.is_finished());
+        assert_eq!(
+            job.results,
+            vec![(0, ApplyOutcome::Restored), (1, ApplyOutcome::Restored)]
+        );
+        assert!(paths[0].exists());
+        assert!(paths[1].exists());
+    }
+
+    #[test]
+    fn test_commit_trash_decisions_reports_every_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let paths: Vec<PathBuf> = ["a.txt", "b.txt"]
+            .iter()
+            .map(|name| temp_dir.path().join(name))
+            .collect();
+        for path in &paths {
+            fs::write(path, b"content").unwrap();
+        }
+        let mut engine = DecisionEngine::new(
+            paths
+                .iter()
+                .cloned()
+                .map(create_test_entry_with_path)
+                .collect(),
+        );
+        engine.record_decision(0, Decision::Trash).unwrap();
+        engine.record_decision(1, Decision::Trash).unwrap();
+        fs::remove_file(engine.get_staged_path(0)).unwrap();
+        fs::remove_file(engine.get_staged_path(1)).unwrap();
+
+        let err = engine.commit_trash_decisions().unwrap_err();
+        let FileTinderError::TrashError(message) = err else {
+            panic!("expected a trash error, got {:?}", err);
+        };
+        assert!(message.starts_with("a.txt: "));
+        assert!(message.contains("; b.txt: "));
+    }
+
+    #[test]
+    fn test_decision_engine_apply_missing_staged_file_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("a.txt");
+        fs::write(&path, b"content").unwrap();
+
+        let mut engine = DecisionEngine::new(vec![create_test_entry_with_path(path)]);
+        engine.record_decision(0, Decision::Trash).unwrap();
+        fs::remove_file(engine.get_staged_path(0)).unwrap();
+
+        let mut job = engine.start_apply();
+        assert!(engine.apply_next(&mut job));
+        assert!(matches!(job.results[0], (0, ApplyOutcome::Failed(_))));
+        assert!(!engine.apply_next(&mut job));
+    }
+
+    #[test]
+    fn test_decision_engine_dry_run_applies_nothing() {
+        let mut engine = DecisionEngine::new(vec![create_test_entry_with_path(PathBuf::from(
+            "/nonexistent/a.txt",
+        ))]);
+        engine.set_dry_run(true);
+        engine.record_decision(0, Decision::Trash).unwrap();
+
+        assert!(engine.start_apply().is_finished());
+    }
+
+    #[test]
+    fn test_decision_engine_apply_expired_waits_for_grace() {
+        let temp_dir = TempDir::new().unwrap();
+        let files = ["a.txt", "b.txt"]
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, b"content").unwrap();
+                create_test_entry_with_path(path)
+            })
+            .collect();
+
+        let mut engine = DecisionEngine::new(files);
+        engine.record_decision(0, Decision::Trash).unwrap();
+        engine.record_decision(1, Decision::Keep).unwrap();
+
+        // Inside the grace period nothing is applied and the toast has the file
+        let hour = Duration::from_secs(3600);
+        assert!(!engine.has_expired_work(hour));
+        assert!(engine.apply_expired(hour).is_none());
+        let pending = engine.within_grace(hour);
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].0, 0);
+        assert!(pending[0].1 > Duration::from_secs(3500));
+
+        // Once it's over, the trash decision goes (keeps never do)
+        assert!(engine.has_expired_work(Duration::ZERO));
+        assert!(engine.within_grace(Duration::ZERO).is_empty());
+        // Simulate the trash move failing so the test doesn't touch the real trash
+        fs::remove_file(engine.get_staged_path(0)).unwrap();
+        let (index, _) = engine.apply_expired(Duration::ZERO).unwrap();
+        assert_eq!(index, 0);
+        assert!(engine.is_applied(0));
+        assert!(!engine.has_expired_work(Duration::ZERO));
+        assert!(engine.within_grace(hour).is_empty());
+    }
+
+    #[test]
+    fn test_decision_engine_background_apply_keeps_recent_undoable() {
+        let temp_dir = TempDir::new().unwrap();
+        let files = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, b"content").unwrap();
+                create_test_entry_with_path(path)
+            })
+            .collect();
+
+        let mut engine = DecisionEngine::new(files);
+        engine.record_decision(0, Decision::Trash).unwrap();
+        engine.record_decision(1, Decision::Keep).unwrap();
+        engine.record_decision(2, Decision::Trash).unwrap();
+
+        // With the two latest decisions held back, only file 0 is eligible
+        assert!(engine.has_background_work(2));
+        // Simulate the trash move failing so the test doesn't touch the real trash
+        fs::remove_file(engine.get_staged_path(0)).unwrap();
+        let (index, outcome) = engine.apply_in_background(2).unwrap();
+        assert_eq!(index, 0);
+        assert!(matches!(outcome, ApplyOutcome::Failed(_)));
+        assert!(engine.is_applied(0));
+        assert_eq!(engine.pending_trash_count(), 1);
+        assert!(!engine.has_background_work(2));
+
+        // Recent decisions still undo normally
+        engine.undo().unwrap();
+        engine.undo().unwrap();
+        // The applied one is restore-only
+        assert!(engine.undo().is_err());
+        assert_eq!(engine.decisions.len(), 1);
+
+        // The final apply reports it without trying it again
+        let job = engine.start_apply();
+        assert!(job.is_finished());
+        assert_eq!(job.total, 1);
+    }
+
     #[test]
     fn test_decision_engine_trash_nonexistent_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -383,6 +1441,57 @@ mod tests {
         assert_eq!(stats.total_files, 4);
         assert_eq!(stats.kept, 2);
         assert_eq!(stats.trashed, 2);
+        assert_eq!(stats.undecided, 0);
+
+        engine.undo().unwrap();
+        assert_eq!(engine.get_statistics().undecided, 1);
+
+        // A skip is a decision of its own, counted apart from undecided files
+        engine.record_decision(3, Decision::Skip).unwrap();
+        let stats = engine.get_statistics();
+        assert_eq!((stats.kept, stats.skipped, stats.undecided), (1, 1, 0));
+    }
+
+    #[test]
+    fn test_decision_engine_statistics_freed_bytes() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut files = Vec::new();
+        for name in ["a.bin", "b.bin"] {
+            let path = temp_dir.path().join(name);
+            fs::write(&path, b"x").unwrap();
+            let mut entry = create_test_entry_with_path(path);
+            entry.size = 1000;
+            files.push(entry);
+        }
+
+        let mut engine = DecisionEngine::new(files);
+        engine.set_dry_run(true);
+        engine.record_decision(0, Decision::Trash).unwrap();
+        engine.record_decision(1, Decision::Keep).unwrap();
+
+        let stats = engine.get_statistics();
+        assert_eq!(stats.freed_bytes, 1000);
+        assert_eq!(stats.freed_by_type, vec![(FileType::Text, 1000)]);
+    }
+
+    #[test]
+    fn test_decision_engine_decided_count_ignores_re_decisions() {
+        let files = vec![
+            create_test_entry_with_path(PathBuf::from("/tmp/a.txt")),
+            create_test_entry_with_path(PathBuf::from("/tmp/b.txt")),
+        ];
+        let mut engine = DecisionEngine::new(files);
+        engine.set_dry_run(true);
+        engine.record_decision(0, Decision::Keep).unwrap();
+        engine.record_decision(0, Decision::Trash).unwrap();
+        assert_eq!(engine.decided_count(), 1);
+
+        // The file stays decided until its last entry is undone
+        engine.undo().unwrap();
+        assert!(engine.is_decided(0));
+        engine.undo().unwrap();
+        assert!(!engine.is_decided(0));
+        assert_eq!(engine.decided_count(), 0);
     }
 
     #[test]
@@ -0,0 +1,333 @@
+use super::{reclaimable_bytes, FileEntry, FileType};
+use crate::hashing::HashAlgorithm;
+use crate::scan_index::HashCache;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Bytes from the start of each file compared before anything is read in full on
+/// a network mount
+const SAMPLE_BYTES: u64 = 64 * 1024;
+
+/// Files with byte-identical contents, wherever they are and whatever they're called
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateGroup {
+    /// Indices into the scanned files, oldest first: the one most likely the original
+    pub files: Vec<usize>,
+    /// Size of each copy
+    pub size: u64,
+}
+
+impl DuplicateGroup {
+    /// Bytes freed by keeping just the first (oldest) of the files, as
+    /// `reclaimable_bytes` counts them: blocks shared with other hard links or
+    /// copy-on-write clones stay
+    pub fn wasted_bytes(&self, files: &[FileEntry]) -> u64 {
+        reclaimable_bytes(self.files.iter().skip(1).map(|&index| &files[index]))
+    }
+}
+
+/// Groups the scanned files that have the same contents.
+///
+/// Only files sharing their size with another are read, and each is hashed with
+/// `algorithm` on `limits::workers()` threads. On a network mount (`degraded`)
+/// their first 64 KiB are hashed first, and only files whose samples match
+/// another's are read in full. Hard links to the same data count once, since
+/// trashing a link frees nothing. Empty files, directory items, online-only
+/// placeholders and files that can't be read are left out. Groups come biggest
+/// waste first.
+pub fn find_duplicates(
+    files: &[FileEntry],
+    algorithm: HashAlgorithm,
+    degraded: bool,
+) -> Vec<DuplicateGroup> {
+    let progress = Progress::default();
+    group_duplicates(files, algorithm, degraded, &HashCache::default(), &progress)
+}
+
+/// Files hashed so far and files to hash, shared with a worker
+#[derive(Debug, Default)]
+struct Progress {
+    hashed: AtomicUsize,
+    total: AtomicUsize,
+    cancel: AtomicBool,
+}
+
+/// Duplicates being looked for on worker threads, so hashing a big folder doesn't
+/// hold up the start of the review
+#[derive(Debug)]
+pub struct DuplicateFinder {
+    progress: Arc<Progress>,
+    result: Receiver<Vec<DuplicateGroup>>,
+}
+
+impl DuplicateFinder {
+    /// Starts grouping `files` by contents (see `find_duplicates`), reusing the
+    /// hashes in `hashes` and saving the ones it adds
+    pub fn start(
+        files: Arc<Vec<FileEntry>>,
+        algorithm: HashAlgorithm,
+        degraded: bool,
+        hashes: HashCache,
+    ) -> Self {
+        let progress = Arc::new(Progress::default());
+        let (sender, result) = mpsc::channel();
+        let shared = Arc::clone(&progress);
+        thread::spawn(move || {
+            let groups = group_duplicates(&files, algorithm, degraded, &hashes, &shared);
+            let _ = hashes.save();
+            let _ = sender.send(groups);
+        });
+        Self { progress, result }
+    }
+
+    /// (files hashed, files to hash); on a network mount the full reads are added
+    /// once the samples are in
+    pub fn progress(&self) -> (usize, usize) {
+        let total = self.progress.total.load(Ordering::Relaxed);
+        (
+            self.progress.hashed.load(Ordering::Relaxed).min(total),
+            total,
+        )
+    }
+
+    /// The groups, once every file has been hashed
+    pub fn poll(&self) -> Option<Vec<DuplicateGroup>> {
+        self.result.try_recv().ok()
+    }
+}
+
+impl Drop for DuplicateFinder {
+    /// Workers stop after their current file once nobody is waiting for groups
+    fn drop(&mut self) {
+        self.progress.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+fn group_duplicates(
+    files: &[FileEntry],
+    algorithm: HashAlgorithm,
+    degraded: bool,
+    hashes: &HashCache,
+    progress: &Progress,
+) -> Vec<DuplicateGroup> {
+    let mut by_size: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut inodes = HashSet::new();
+    for (index, file) in files.iter().enumerate() {
+        if file.size == 0 || file.file_type == FileType::Directory || file.is_online_only() {
+            continue;
+        }
+        // The first link to an inode stands for all of them
+        if file.disk.id.is_some_and(|id| !inodes.insert(id)) {
+            continue;
+        }
+        by_size.entry(file.size).or_default().push(index);
+    }
+    let mut candidates: Vec<usize> = by_size
+        .into_values()
+        .filter(|same_size| same_size.len() > 1)
+        .flatten()
+        .collect();
+    progress.total.store(candidates.len(), Ordering::Relaxed);
+
+    if degraded {
+        let samples = hash_each(files, &candidates, progress, |path| {
+            sample_hash(path, algorithm)
+        });
+        candidates = samples
+            .into_values()
+            .filter(|same_sample| same_sample.len() > 1)
+            .flatten()
+            .collect();
+        progress
+            .total
+            .fetch_add(candidates.len(), Ordering::Relaxed);
+    }
+    let hashes = hash_each(files, &candidates, progress, |path| {
+        hashes.hash_file(path, algorithm)
+    });
+    if progress.cancel.load(Ordering::Relaxed) {
+        return Vec::new();
+    }
+
+    let mut groups: Vec<DuplicateGroup> = hashes
+        .into_iter()
+        .filter(|(_, same)| same.len() > 1)
+        .map(|((size, _), mut same)| {
+            same.sort_by(|&a, &b| {
+                (files[a].modified_date, &files[a].path)
+                    .cmp(&(files[b].modified_date, &files[b].path))
+            });
+            DuplicateGroup { files: same, size }
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        b.wasted_bytes(files)
+            .cmp(&a.wasted_bytes(files))
+            .then_with(|| files[a.files[0]].path.cmp(&files[b.files[0]].path))
+    });
+    groups
+}
+
+/// The `candidates` by (size, hash), hashed on `limits::workers()` threads and
+/// counted into `progress`; files that can't be read are left out
+fn hash_each(
+    files: &[FileEntry],
+    candidates: &[usize],
+    progress: &Progress,
+    hash: impl Fn(&Path) -> io::Result<String> + Sync,
+) -> HashMap<(u64, String), Vec<usize>> {
+    let next = AtomicUsize::new(0);
+    let hashes = Mutex::new(HashMap::<(u64, String), Vec<usize>>::new());
+    thread::scope(|scope| {
+        for _ in 0..crate::limits::workers().min(candidates.len()) {
+            scope.spawn(|| {
+                while let Some(&index) = candidates.get(next.fetch_add(1, Ordering::Relaxed)) {
+                    if progress.cancel.load(Ordering::Relaxed) {
+                        break;
+                    }
+                    let file = &files[index];
+                    if let Ok(hash) = hash(&file.path) {
+                        if let Ok(mut hashes) = hashes.lock() {
+                            hashes.entry((file.size, hash)).or_default().push(index);
+                        }
+                    }
+                    progress.hashed.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    hashes.into_inner().unwrap_or_default()
+}
+
+/// Hash of the file's first `SAMPLE_BYTES`
+fn sample_hash(path: &Path, algorithm: HashAlgorithm) -> io::Result<String> {
+    let _permit = crate::limits::open_files(1);
+    crate::limits::throttle(SAMPLE_BYTES);
+    let mut sample = Vec::new();
+    File::open(path)?
+        .take(SAMPLE_BYTES)
+        .read_to_end(&mut sample)?;
+    Ok(algorithm.hash_bytes(&sample))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_find_duplicates_groups_identical_contents() {
+        let dir = TempDir::new().unwrap();
+        fs::create_dir(dir.path().join("backup")).unwrap();
+        let contents = [
+            ("photo.jpg", "same picture bytes"),
+            ("backup/IMG_0001.jpg", "same picture bytes"),
+            ("other.jpg", "diff picture bytes"),
+            ("notes.txt", "todo"),
+            ("todo.md", "todo"),
+            ("todo copy.md", "todo"),
+            ("empty-a", ""),
+            ("empty-b", ""),
+            ("alone.txt", "nothing else is this long"),
+        ];
+        let files: Vec<FileEntry> = contents
+            .iter()
+            .map(|(name, contents)| {
+                let path = dir.path().join(name);
+                fs::write(&path, contents).unwrap();
+                FileEntry::from_path(&path).unwrap()
+            })
+            .collect();
+
+        let groups = find_duplicates(&files, HashAlgorithm::default(), false);
+        assert_eq!(groups.len(), 2);
+        assert!(groups[0].wasted_bytes(&files) >= groups[1].wasted_bytes(&files));
+        let mut found: Vec<Vec<usize>> = groups
+            .iter()
+            .map(|group| {
+                let mut same = group.files.clone();
+                same.sort();
+                same
+            })
+            .collect();
+        found.sort();
+        assert_eq!(found, [vec![0, 1], vec![3, 4, 5]]);
+        let pictures = groups.iter().find(|group| group.size == 18).unwrap();
+        assert_eq!(
+            pictures.wasted_bytes(&files),
+            reclaimable_bytes(pictures.files[1..].iter().map(|&index| &files[index]))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_duplicates_counts_hard_links_once() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("a.txt"), "same contents").unwrap();
+        fs::hard_link(dir.path().join("a.txt"), dir.path().join("a-link.txt")).unwrap();
+        let entry = |name: &str| FileEntry::from_path(&dir.path().join(name)).unwrap();
+
+        // Two links to one file free nothing
+        let links = [entry("a.txt"), entry("a-link.txt")];
+        assert!(find_duplicates(&links, HashAlgorithm::default(), false).is_empty());
+
+        // With a real copy, one link stands for the file
+        fs::write(dir.path().join("b.txt"), "same contents").unwrap();
+        let files = [entry("a.txt"), entry("a-link.txt"), entry("b.txt")];
+        let groups = find_duplicates(&files, HashAlgorithm::default(), false);
+        assert_eq!(groups.len(), 1);
+        let mut found = groups[0].files.clone();
+        found.sort();
+        assert_eq!(found, [0, 2]);
+    }
+
+    #[test]
+    fn test_duplicate_finder_samples_first_on_network_mounts() {
+        let dir = TempDir::new().unwrap();
+        let same = vec![1u8; SAMPLE_BYTES as usize + 10];
+        let mut differs_late = same.clone();
+        *differs_late.last_mut().unwrap() = 2;
+        let differs_early = vec![2u8; same.len()];
+        let files: Vec<FileEntry> = [
+            ("a.bin", &same),
+            ("b.bin", &same),
+            ("c.bin", &differs_late),
+            ("d.bin", &differs_early),
+        ]
+        .iter()
+        .map(|(name, contents)| {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            FileEntry::from_path(&path).unwrap()
+        })
+        .collect();
+
+        let finder = DuplicateFinder::start(
+            Arc::new(files),
+            HashAlgorithm::default(),
+            true,
+            HashCache::default(),
+        );
+        let started = std::time::Instant::now();
+        let groups = loop {
+            if let Some(groups) = finder.poll() {
+                break groups;
+            }
+            assert!(started.elapsed() < std::time::Duration::from_secs(10));
+            thread::sleep(std::time::Duration::from_millis(5));
+        };
+        assert_eq!(groups.len(), 1);
+        let mut found = groups[0].files.clone();
+        found.sort();
+        assert_eq!(found, [0, 1]);
+        // Four samples, then only the three that matched are read in full
+        assert_eq!(finder.progress(), (7, 7));
+    }
+}
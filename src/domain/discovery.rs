@@ -1,36 +1,196 @@
-use super::{FileEntry, FileType};
+use super::{CompactEntry, CompactFiles, FileEntry, FileType};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::ffi::OsStr;
 use std::fs;
 use std::io;
 use std::path::Path;
+use std::sync::OnceLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum SortBy {
     Date,
     Name,
     Size,
     Type,
+    /// Shuffled, one order per run
+    Random,
 }
 
 #[derive(Debug, Clone)]
 pub struct DiscoveryOptions {
     pub file_types: Option<Vec<FileType>>,
+    /// Extensions to list, lowercase and without the dot; with `file_types` too,
+    /// a file matching either is listed
+    pub extensions: Option<Vec<String>>,
     pub show_hidden: bool,
     pub min_size: Option<u64>,
     pub max_size: Option<u64>,
     pub sort_by: SortBy,
     pub reverse: bool,
+    /// List subdirectories as items too (unmeasured; see `FileEntry::from_dir`);
+    /// type and size filters don't apply to them
+    pub include_dirs: bool,
+    /// Also scan subdirectories, all the way down (symlinked directories aren't followed)
+    pub recursive: bool,
 }
 
 impl Default for DiscoveryOptions {
     fn default() -> Self {
         DiscoveryOptions {
             file_types: None,
+            extensions: None,
             show_hidden: false,
             min_size: None,
             max_size: None,
             sort_by: SortBy::Date,
             reverse: false,
+            include_dirs: false,
+            recursive: false,
+        }
+    }
+}
+
+/// A scan option that leaves files out of the queue, in the order they're checked
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScanFilter {
+    /// Names starting with a dot, unless `--hidden`
+    Hidden,
+    /// `--type` and `--ext`, which a file passes by matching either
+    Type,
+    MinSize,
+    MaxSize,
+}
+
+impl DiscoveryOptions {
+    /// The scan filters these options turn on
+    pub fn active_filters(&self) -> Vec<ScanFilter> {
+        let mut filters = Vec::new();
+        if !self.show_hidden {
+            filters.push(ScanFilter::Hidden);
+        }
+        if self.file_types.is_some() || self.extensions.is_some() {
+            filters.push(ScanFilter::Type);
+        }
+        if self.min_size.is_some() {
+            filters.push(ScanFilter::MinSize);
+        }
+        if self.max_size.is_some() {
+            filters.push(ScanFilter::MaxSize);
+        }
+        filters
+    }
+
+    /// The first scan filter, other than those in `off`, that leaves `file` out.
+    /// Type and size filters don't apply to directory items.
+    pub fn excluded_by(&self, file: &FileEntry, off: &[ScanFilter]) -> Option<ScanFilter> {
+        self.excluded(
+            file.name.starts_with('.'),
+            &file.file_type,
+            file.path.extension(),
+            file.size,
+            off,
+        )
+    }
+
+    /// `excluded_by` for a file held compactly
+    pub fn excluded_compact(&self, file: &CompactEntry, off: &[ScanFilter]) -> Option<ScanFilter> {
+        self.excluded(
+            file.is_hidden(),
+            &file.file_type,
+            file.extension(),
+            file.size.bytes(),
+            off,
+        )
+    }
+
+    fn excluded(
+        &self,
+        hidden: bool,
+        file_type: &FileType,
+        extension: Option<&OsStr>,
+        size: u64,
+        off: &[ScanFilter],
+    ) -> Option<ScanFilter> {
+        let on = |filter| !off.contains(&filter);
+        if !self.show_hidden && on(ScanFilter::Hidden) && hidden {
+            return Some(ScanFilter::Hidden);
+        }
+        if *file_type == FileType::Directory {
+            return None;
+        }
+        if on(ScanFilter::Type) && (self.file_types.is_some() || self.extensions.is_some()) {
+            let by_type = self
+                .file_types
+                .as_ref()
+                .is_some_and(|types| types.contains(file_type));
+            let by_extension = self.extensions.as_ref().is_some_and(|extensions| {
+                extension
+                    .is_some_and(|ext| extensions.contains(&ext.to_string_lossy().to_lowercase()))
+            });
+            if !by_type && !by_extension {
+                return Some(ScanFilter::Type);
+            }
         }
+        if on(ScanFilter::MinSize) && self.min_size.is_some_and(|min| size < min) {
+            return Some(ScanFilter::MinSize);
+        }
+        if on(ScanFilter::MaxSize) && self.max_size.is_some_and(|max| size > max) {
+            return Some(ScanFilter::MaxSize);
+        }
+        None
+    }
+}
+
+/// The files a scan's filters left out, kept so a filter can be turned off
+/// mid-session (see `AppState::admit_files`). They can outnumber the reviewed
+/// files many times over, so they're held compactly.
+#[derive(Debug, Clone, Default)]
+pub struct LeftOut {
+    pub options: DiscoveryOptions,
+    /// Filters turned off since the scan
+    pub off: Vec<ScanFilter>,
+    pub files: CompactFiles,
+}
+
+impl LeftOut {
+    pub fn new(options: DiscoveryOptions, files: Vec<FileEntry>) -> Self {
+        Self {
+            options,
+            off: Vec::new(),
+            files: files.into_iter().collect(),
+        }
+    }
+
+    /// Each scan filter still on, with how many files it leaves out (a file
+    /// counts for the first filter it fails)
+    pub fn counts(&self) -> Vec<(ScanFilter, usize)> {
+        self.options
+            .active_filters()
+            .into_iter()
+            .filter(|filter| !self.off.contains(filter))
+            .map(|filter| {
+                let count = self
+                    .files
+                    .iter()
+                    .filter(|file| self.options.excluded_compact(file, &self.off) == Some(filter))
+                    .count();
+                (filter, count)
+            })
+            .collect()
+    }
+
+    /// Turns `filter` off, handing back the files no filter leaves out any more,
+    /// read from the disk again
+    pub fn turn_off(&mut self, filter: ScanFilter) -> Vec<FileEntry> {
+        if !self.off.contains(&filter) {
+            self.off.push(filter);
+        }
+        let (options, off) = (&self.options, &self.off);
+        self.files
+            .take_where(|file| options.excluded_compact(file, off).is_none())
     }
 }
 
@@ -42,49 +202,90 @@ pub fn discover_files_with_options(
     dir_path: &Path,
     options: &DiscoveryOptions,
 ) -> io::Result<Vec<FileEntry>> {
-    let entries = fs::read_dir(dir_path)?;
-    let mut files = Vec::new();
-
-    for entry_result in entries {
-        let entry = entry_result?;
-        let path = entry.path();
+    discover_files_with(dir_path, options, FileEntry::from_path)
+}
 
-        if path.is_dir() {
-            continue;
-        }
+/// Like `discover_files_with_options`, building each entry with `load` (e.g. through
+/// the scan index, which skips work for files unchanged since the last run)
+pub fn discover_files_with(
+    dir_path: &Path,
+    options: &DiscoveryOptions,
+    load: impl FnMut(&Path) -> io::Result<FileEntry>,
+) -> io::Result<Vec<FileEntry>> {
+    discover_files_excluding(dir_path, options, load).map(|(files, _)| files)
+}
 
-        if !options.show_hidden {
-            if let Some(filename) = path.file_name().and_then(|n| n.to_str()) {
-                if filename.starts_with('.') {
-                    continue;
+/// Like `discover_files_with`, also returning the files the type, size and
+/// hidden-file filters left out (unsorted). Hidden directories are neither
+/// listed nor looked into.
+pub fn discover_files_excluding(
+    dir_path: &Path,
+    options: &DiscoveryOptions,
+    mut load: impl FnMut(&Path) -> io::Result<FileEntry>,
+) -> io::Result<(Vec<FileEntry>, Vec<FileEntry>)> {
+    let mut files = Vec::new();
+    let mut left_out = Vec::new();
+    let mut pending = vec![fs::read_dir(dir_path)?];
+
+    while let Some(entries) = pending.pop() {
+        for entry_result in entries {
+            let entry = entry_result?;
+            let path = entry.path();
+
+            if !options.show_hidden && entry.file_name().as_encoded_bytes().starts_with(b".") {
+                if entry.file_type().is_ok_and(|t| t.is_file()) {
+                    if let Ok(file_entry) = load(&path) {
+                        left_out.push(file_entry);
+                    }
                 }
+                continue;
+            }
+            // The note left by `--leave-summary` isn't something to review
+            if entry.file_name() == crate::park::SUMMARY_FILE {
+                continue;
             }
-        }
 
-        if let Ok(file_entry) = FileEntry::from_path(&path) {
-            if let Some(ref type_filters) = options.file_types {
-                if !type_filters.contains(&file_entry.file_type) {
+            let loaded = if path.is_dir() {
+                // Symlinks to directories are never items or descended into
+                if !entry.file_type().is_ok_and(|t| t.is_dir()) {
                     continue;
                 }
-            }
-
-            if let Some(min_size) = options.min_size {
-                if file_entry.size < min_size {
+                if options.recursive {
+                    // An unreadable subdirectory is skipped rather than failing the scan
+                    if let Ok(entries) = fs::read_dir(&path) {
+                        pending.push(entries);
+                    }
                     continue;
                 }
-            }
-
-            if let Some(max_size) = options.max_size {
-                if file_entry.size > max_size {
+                if !options.include_dirs {
                     continue;
                 }
+                FileEntry::from_dir(&path)
+            } else {
+                load(&path)
+            };
+
+            if let Ok(file_entry) = loaded {
+                match options.excluded_by(&file_entry, &[]) {
+                    Some(_) => left_out.push(file_entry),
+                    None => files.push(file_entry),
+                }
             }
-
-            files.push(file_entry);
         }
     }
 
-    files.sort_by(|a, b| match options.sort_by {
+    files.sort_by(|a, b| compare_files(a, b, options.sort_by));
+
+    if options.reverse {
+        files.reverse();
+    }
+
+    Ok((files, left_out))
+}
+
+/// Ascending order of two files by `sort_by`
+pub fn compare_files(a: &FileEntry, b: &FileEntry, sort_by: SortBy) -> Ordering {
+    match sort_by {
         SortBy::Date => a.modified_date.cmp(&b.modified_date),
         SortBy::Name => a.name.cmp(&b.name),
         SortBy::Size => a.size.cmp(&b.size),
@@ -93,13 +294,19 @@ pub fn discover_files_with_options(
             let b_type = format!("{:?}", b.file_type);
             a_type.cmp(&b_type)
         }
-    });
-
-    if options.reverse {
-        files.reverse();
+        SortBy::Random => shuffle_key(a).cmp(&shuffle_key(b)),
     }
+}
 
-    Ok(files)
+/// Where a file falls in the random order: a hash of its path, seeded once per run
+fn shuffle_key(file: &FileEntry) -> u64 {
+    static SEED: OnceLock<u64> = OnceLock::new();
+    let seed = *SEED.get_or_init(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64)
+    });
+    xxhash_rust::xxh3::xxh3_64_with_seed(file.path.as_os_str().as_encoded_bytes(), seed)
 }
 
 #[cfg(test)]
@@ -192,6 +399,25 @@ mod tests {
         assert_eq!(files[1].name, "file2.txt");
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_discover_files_with_non_utf8_names() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let visible = temp_dir.path().join(OsStr::from_bytes(b"r\xe9sum\xe9.txt"));
+        let hidden = temp_dir.path().join(OsStr::from_bytes(b".h\xe9dden"));
+        fs::write(&visible, "content").unwrap();
+        fs::write(&hidden, "content").unwrap();
+
+        let files = discover_files(temp_dir.path()).unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert!(files[0].has_lossy_name());
+        assert_eq!(files[0].path, visible);
+    }
+
     #[test]
     fn test_discover_with_file_type_filter() {
         let temp_dir = TempDir::new().unwrap();
@@ -211,6 +437,89 @@ mod tests {
         assert_eq!(files[0].name, "file.txt");
     }
 
+    #[test]
+    fn test_discover_with_extension_filter() {
+        let temp_dir = TempDir::new().unwrap();
+        for name in ["report.PDF", "letter.docx", "notes.txt", "photo.png"] {
+            fs::write(temp_dir.path().join(name), "content").unwrap();
+        }
+
+        let options = DiscoveryOptions {
+            extensions: Some(vec!["pdf".to_string(), "docx".to_string()]),
+            sort_by: SortBy::Name,
+            ..Default::default()
+        };
+        let files = discover_files_with_options(temp_dir.path(), &options).unwrap();
+        let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["letter.docx", "report.PDF"]);
+
+        // Along with --type, either one lets a file through
+        let options = DiscoveryOptions {
+            file_types: Some(vec![FileType::Image]),
+            ..options
+        };
+        let files = discover_files_with_options(temp_dir.path(), &options).unwrap();
+        assert_eq!(files.len(), 3);
+    }
+
+    #[test]
+    fn test_discover_with_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("notes.txt"), "content").unwrap();
+        fs::create_dir_all(temp_dir.path().join("project/src")).unwrap();
+        fs::write(temp_dir.path().join("project/src/lib.rs"), "12345").unwrap();
+        fs::write(temp_dir.path().join("project/README"), "123").unwrap();
+        fs::create_dir(temp_dir.path().join(".git")).unwrap();
+
+        let files = discover_files(temp_dir.path()).unwrap();
+        assert_eq!(files.len(), 1);
+
+        // Type and size filters only narrow the files; directories are measured later
+        let options = DiscoveryOptions {
+            include_dirs: true,
+            file_types: Some(vec![FileType::Image]),
+            min_size: Some(1),
+            sort_by: SortBy::Name,
+            ..Default::default()
+        };
+        let files = discover_files_with_options(temp_dir.path(), &options).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "project");
+        assert_eq!(files[0].file_type, FileType::Directory);
+        assert_eq!(files[0].size, 0);
+    }
+
+    #[test]
+    fn test_discover_recursive() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("top.txt"), "content").unwrap();
+        fs::create_dir_all(temp_dir.path().join("a/b")).unwrap();
+        fs::write(temp_dir.path().join("a/middle.txt"), "content").unwrap();
+        fs::write(temp_dir.path().join("a/b/deep.png"), "content").unwrap();
+        fs::create_dir(temp_dir.path().join(".cache")).unwrap();
+        fs::write(temp_dir.path().join(".cache/hidden.txt"), "content").unwrap();
+
+        let options = DiscoveryOptions {
+            recursive: true,
+            sort_by: SortBy::Name,
+            ..Default::default()
+        };
+        let files = discover_files_with_options(temp_dir.path(), &options).unwrap();
+        let names: Vec<&str> = files.iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, ["deep.png", "middle.txt", "top.txt"]);
+        assert_eq!(files[0].path, temp_dir.path().join("a/b/deep.png"));
+
+        // Filters apply at every level
+        let options = DiscoveryOptions {
+            file_types: Some(vec![FileType::Text]),
+            show_hidden: true,
+            ..options
+        };
+        let files = discover_files_with_options(temp_dir.path(), &options).unwrap();
+        assert_eq!(files.len(), 3);
+        assert!(files.iter().all(|f| f.file_type == FileType::Text));
+    }
+
     #[test]
     fn test_discover_with_size_filter() {
         let temp_dir = TempDir::new().unwrap();
@@ -230,6 +539,38 @@ mod tests {
         assert_eq!(files[0].name, "large.txt");
     }
 
+    #[test]
+    fn test_discover_keeps_left_out_files_for_turning_off() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("large.txt"), "large content here").unwrap();
+        fs::write(temp_dir.path().join("small.txt"), "small").unwrap();
+        fs::write(temp_dir.path().join(".hidden.txt"), "large hidden content").unwrap();
+        fs::create_dir(temp_dir.path().join(".git")).unwrap();
+        fs::write(temp_dir.path().join(".git").join("config"), "x").unwrap();
+
+        let options = DiscoveryOptions {
+            min_size: Some(10),
+            ..Default::default()
+        };
+        let (files, left_out) =
+            discover_files_excluding(temp_dir.path(), &options, FileEntry::from_path).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(left_out.len(), 2);
+
+        let mut left_out = LeftOut::new(options, left_out);
+        assert_eq!(
+            left_out.counts(),
+            [(ScanFilter::Hidden, 1), (ScanFilter::MinSize, 1)]
+        );
+        let admitted = left_out.turn_off(ScanFilter::MinSize);
+        assert_eq!(admitted.len(), 1);
+        assert_eq!(admitted[0].name, "small.txt");
+        assert_eq!(left_out.counts(), [(ScanFilter::Hidden, 1)]);
+        let admitted = left_out.turn_off(ScanFilter::Hidden);
+        assert_eq!(admitted[0].name, ".hidden.txt");
+        assert!(left_out.files.is_empty());
+    }
+
     #[test]
     fn test_discover_with_show_hidden() {
         let temp_dir = TempDir::new().unwrap();
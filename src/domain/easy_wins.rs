@@ -0,0 +1,353 @@
+use super::pattern::original_name;
+use super::{FileEntry, FileType};
+use crate::usage::UsageInfo;
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Names operating systems and apps leave behind that nobody opens
+const JUNK_NAMES: &[&str] = &[".DS_Store", "Thumbs.db", "ehthumbs.db", "desktop.ini"];
+
+/// Extensions of interrupted downloads and temporary files
+const JUNK_EXTENSIONS: &[&str] = &["tmp", "temp", "crdownload", "part", "partial", "download"];
+
+/// Extensions of installers and disk images that are only needed once
+const INSTALLER_EXTENSIONS: &[&str] = &["dmg", "pkg", "msi", "deb", "rpm", "appimage"];
+
+/// How old an installer has to be before it's suggested (about six months)
+const INSTALLER_AGE_DAYS: i64 = 180;
+
+/// Why a group of files is suggested for trash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EasyWinKind {
+    /// OS droppings (`.DS_Store`, `Thumbs.db`), Office lock files and leftover
+    /// partial downloads
+    Junk,
+    /// Zero-byte files
+    Empty,
+    /// Byte-identical `name (1).ext` style copies whose original is also scanned
+    Duplicate,
+    /// Installers and disk images untouched for six months
+    OldInstaller,
+}
+
+impl fmt::Display for EasyWinKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EasyWinKind::Junk => "Junk and temporary files",
+            EasyWinKind::Empty => "Empty files",
+            EasyWinKind::Duplicate => "Identical copies",
+            EasyWinKind::OldInstaller => "Old installers",
+        })
+    }
+}
+
+impl EasyWinKind {
+    /// Name of the rule, as quoted in explanations
+    pub fn rule_name(&self) -> &'static str {
+        match self {
+            EasyWinKind::Junk => "junk",
+            EasyWinKind::Empty => "empty files",
+            EasyWinKind::Duplicate => "identical copies",
+            EasyWinKind::OldInstaller => "old installers",
+        }
+    }
+
+    /// What about `file` made it match, e.g. "a .dmg untouched for 400 days (over 180),
+    /// never opened"; `usage` is its last-used info, when a usage source has it
+    pub fn criterion(
+        &self,
+        file: &FileEntry,
+        usage: Option<&UsageInfo>,
+        now: DateTime<Utc>,
+    ) -> String {
+        let criterion = self.file_criterion(file, now);
+        // Junk and empty files go on their name or size alone; nobody opens them
+        if matches!(self, EasyWinKind::Junk | EasyWinKind::Empty) {
+            return criterion;
+        }
+        match usage {
+            Some(usage) if usage.is_never_opened() => format!("{}, never opened", criterion),
+            Some(UsageInfo {
+                last_used: Some(last_used),
+                ..
+            }) => format!(
+                "{}, last opened {} days ago",
+                criterion,
+                (now - *last_used).num_days()
+            ),
+            _ => criterion,
+        }
+    }
+
+    fn file_criterion(&self, file: &FileEntry, now: DateTime<Utc>) -> String {
+        match self {
+            EasyWinKind::Junk if file.name.starts_with("~$") => "an Office lock file".to_string(),
+            EasyWinKind::Junk if file.name.starts_with("._") => {
+                "macOS metadata from a foreign disk".to_string()
+            }
+            EasyWinKind::Junk => match extension(&file.name) {
+                Some(ext) if JUNK_EXTENSIONS.contains(&ext.as_str()) => {
+                    format!("a .{} temporary or partial download", ext)
+                }
+                _ => format!("{} is left behind by the system", file.name),
+            },
+            EasyWinKind::Empty => "0 bytes".to_string(),
+            EasyWinKind::Duplicate => match original_name(&file.name) {
+                Some(original) => format!("byte-identical to {}", original),
+                None => "byte-identical to its original".to_string(),
+            },
+            EasyWinKind::OldInstaller => format!(
+                "a .{} untouched for {} days (over {})",
+                extension(&file.name).unwrap_or_default(),
+                (now - file.modified_date).num_days(),
+                INSTALLER_AGE_DAYS
+            ),
+        }
+    }
+
+    /// Why `file` was suggested, e.g. "matched rule 'old installers': a .dmg untouched for 400 days (over 180)"
+    pub fn explain(
+        &self,
+        file: &FileEntry,
+        usage: Option<&UsageInfo>,
+        now: DateTime<Utc>,
+    ) -> String {
+        format!(
+            "matched rule '{}': {}",
+            self.rule_name(),
+            self.criterion(file, usage, now)
+        )
+    }
+
+    /// Whether a file suggested under this kind still is once its last-used info
+    /// is known: an installer opened within the last six months isn't old
+    pub fn still_applies(&self, usage: Option<&UsageInfo>, now: DateTime<Utc>) -> bool {
+        match self {
+            EasyWinKind::OldInstaller => usage
+                .and_then(|usage| usage.last_used)
+                .is_none_or(|last_used| now - last_used > Duration::days(INSTALLER_AGE_DAYS)),
+            _ => true,
+        }
+    }
+}
+
+/// A group of files that can be trashed wholesale before the review starts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EasyWin {
+    pub kind: EasyWinKind,
+    /// Indices into the session's files
+    pub files: Vec<usize>,
+    /// Whether the group will be trashed when the suggestions are accepted
+    pub accepted: bool,
+}
+
+/// Groups the files that are safe bets for trash, most certain kind first.
+///
+/// Each file lands in at most one group; directory items are never suggested.
+/// `copies` maps a redundant copy to its original (`find_redundant_copies`).
+/// Groups start accepted and empty ones are left out.
+pub fn find_easy_wins(
+    files: &[FileEntry],
+    copies: &HashMap<usize, usize>,
+    now: DateTime<Utc>,
+) -> Vec<EasyWin> {
+    let kinds = [
+        EasyWinKind::Junk,
+        EasyWinKind::Empty,
+        EasyWinKind::Duplicate,
+        EasyWinKind::OldInstaller,
+    ];
+    let mut groups: Vec<EasyWin> = kinds
+        .into_iter()
+        .map(|kind| EasyWin {
+            kind,
+            files: Vec::new(),
+            accepted: true,
+        })
+        .collect();
+    for (index, file) in files.iter().enumerate() {
+        if file.file_type == FileType::Directory {
+            continue;
+        }
+        let kind = if is_junk(&file.name) {
+            EasyWinKind::Junk
+        } else if file.size == 0 {
+            EasyWinKind::Empty
+        } else if copies.contains_key(&index) {
+            EasyWinKind::Duplicate
+        } else if is_installer(&file.name)
+            && now - file.modified_date > Duration::days(INSTALLER_AGE_DAYS)
+        {
+            EasyWinKind::OldInstaller
+        } else {
+            continue;
+        };
+        if let Some(group) = groups.iter_mut().find(|group| group.kind == kind) {
+            group.files.push(index);
+        }
+    }
+    groups.retain(|group| !group.files.is_empty());
+    groups
+}
+
+fn is_junk(name: &str) -> bool {
+    // `~$report.docx` is an Office lock file, `._photo.jpg` macOS metadata on a
+    // foreign filesystem
+    JUNK_NAMES
+        .iter()
+        .any(|junk| name.eq_ignore_ascii_case(junk))
+        || name.starts_with("~$")
+        || name.starts_with("._")
+        || extension(name).is_some_and(|ext| JUNK_EXTENSIONS.contains(&ext.as_str()))
+}
+
+fn is_installer(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    match extension(name) {
+        Some(ext) if INSTALLER_EXTENSIONS.contains(&ext.as_str()) => true,
+        // Plenty of .exe files are programs, not installers
+        Some(ext) if ext == "exe" => lower.contains("setup") || lower.contains("install"),
+        _ => false,
+    }
+}
+
+fn extension(name: &str) -> Option<String> {
+    let (stem, ext) = name.rsplit_once('.')?;
+    (!stem.is_empty()).then(|| ext.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(name: &str, size: u64, days_old: i64) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from("/d").join(name),
+            name: name.to_string(),
+            size,
+            modified_date: Utc::now() - Duration::days(days_old),
+            file_type: FileType::from_extension(name.rsplit('.').next().unwrap_or("")),
+            disk: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_find_easy_wins_groups_each_file_once() {
+        let mut folder = entry("build", 0, 0);
+        folder.file_type = FileType::Directory;
+        let files = vec![
+            entry(".DS_Store", 0, 0),
+            entry("~$budget.xlsx", 162, 3),
+            entry("video.mp4.crdownload", 5000, 1),
+            entry("notes.txt", 0, 1),
+            entry("photo (1).jpg", 400, 1),
+            entry("photo.jpg", 400, 1),
+            entry("Slack-4.2.dmg", 9000, 400),
+            entry("Zoom-5.1.dmg", 9000, 30),
+            entry("SetupTool.exe", 9000, 400),
+            entry("game.exe", 9000, 400),
+            folder,
+        ];
+        let copies = HashMap::from([(4, 5)]);
+
+        let wins = find_easy_wins(&files, &copies, Utc::now());
+        let groups: Vec<(EasyWinKind, Vec<usize>)> = wins
+            .into_iter()
+            .map(|win| {
+                assert!(win.accepted);
+                (win.kind, win.files)
+            })
+            .collect();
+        assert_eq!(
+            groups,
+            [
+                // The empty .DS_Store counts as junk, not as empty
+                (EasyWinKind::Junk, vec![0, 1, 2]),
+                (EasyWinKind::Empty, vec![3]),
+                (EasyWinKind::Duplicate, vec![4]),
+                (EasyWinKind::OldInstaller, vec![6, 8]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_easy_win_explanations() {
+        let cases = [
+            (
+                EasyWinKind::Junk,
+                entry(".DS_Store", 0, 0),
+                "matched rule 'junk': .DS_Store is left behind by the system",
+            ),
+            (
+                EasyWinKind::Junk,
+                entry("~$budget.xlsx", 162, 0),
+                "matched rule 'junk': an Office lock file",
+            ),
+            (
+                EasyWinKind::Junk,
+                entry("video.mp4.crdownload", 9, 0),
+                "matched rule 'junk': a .crdownload temporary or partial download",
+            ),
+            (
+                EasyWinKind::Empty,
+                entry("notes.txt", 0, 0),
+                "matched rule 'empty files': 0 bytes",
+            ),
+            (
+                EasyWinKind::Duplicate,
+                entry("photo (1).jpg", 400, 0),
+                "matched rule 'identical copies': byte-identical to photo.jpg",
+            ),
+            (
+                EasyWinKind::OldInstaller,
+                entry("Slack-4.2.dmg", 9000, 400),
+                "matched rule 'old installers': a .dmg untouched for 400 days (over 180)",
+            ),
+        ];
+        let now = Utc::now();
+        for (kind, file, expected) in cases {
+            assert_eq!(kind.explain(&file, None, now), expected);
+        }
+    }
+
+    #[test]
+    fn test_easy_wins_use_last_opened() {
+        let installer = entry("Slack-4.2.dmg", 9000, 400);
+        let now = Utc::now();
+        let never = UsageInfo::never_opened();
+        let opened = |days_ago: i64| UsageInfo {
+            last_used: Some(now - Duration::days(days_ago)),
+            open_count: 2,
+        };
+        let kind = EasyWinKind::OldInstaller;
+
+        assert_eq!(
+            kind.explain(&installer, Some(&never), now),
+            "matched rule 'old installers': a .dmg untouched for 400 days (over 180), never opened"
+        );
+        assert_eq!(
+            kind.criterion(&installer, Some(&opened(300)), now),
+            "a .dmg untouched for 400 days (over 180), last opened 300 days ago"
+        );
+        // Nobody opens a .DS_Store, so saying so adds nothing
+        assert_eq!(
+            EasyWinKind::Junk.criterion(&entry(".DS_Store", 0, 0), Some(&never), now),
+            ".DS_Store is left behind by the system"
+        );
+
+        assert!(kind.still_applies(None, now));
+        assert!(kind.still_applies(Some(&never), now));
+        assert!(kind.still_applies(Some(&opened(300)), now));
+        // Modified long ago, but still in use
+        assert!(!kind.still_applies(Some(&opened(3)), now));
+        assert!(EasyWinKind::Duplicate.still_applies(Some(&opened(3)), now));
+    }
+
+    #[test]
+    fn test_find_easy_wins_leaves_out_empty_groups() {
+        let files = vec![entry("report.pdf", 100, 1000)];
+        assert!(find_easy_wins(&files, &HashMap::new(), Utc::now()).is_empty());
+    }
+}
@@ -1,33 +1,183 @@
 use super::FileType;
 use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::ffi::OsStr;
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
+/// Unicode bidirectional control characters (embeddings, overrides, isolates and marks)
+///
+/// These change the visual order of the text around them and are a known trick for
+/// disguising extensions, e.g. `invoice\u{202E}fdp.exe` displays as `invoiceexe.pdf`.
+pub const BIDI_CONTROL_CHARS: [char; 12] = [
+    '\u{061C}', '\u{200E}', '\u{200F}', '\u{202A}', '\u{202B}', '\u{202C}', '\u{202D}', '\u{202E}',
+    '\u{2066}', '\u{2067}', '\u{2068}', '\u{2069}',
+];
+
+/// Returns true if the text contains any bidirectional control character
+pub fn contains_bidi_control(text: &str) -> bool {
+    text.chars().any(|c| BIDI_CONTROL_CHARS.contains(&c))
+}
+
+/// On-disk footprint of a file, which can differ from its apparent size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// Bytes actually allocated (None when the platform can't tell; `size` is used instead)
+    pub allocated: Option<u64>,
+    /// Number of hard links to the underlying data
+    pub links: u64,
+    /// (device, inode) of the underlying data, used to group hard links
+    pub id: Option<(u64, u64)>,
+    /// Bytes in extents shared with other files (copy-on-write clones on btrfs/XFS)
+    pub shared: u64,
+}
+
+impl Default for DiskUsage {
+    fn default() -> Self {
+        Self {
+            allocated: None,
+            links: 1,
+            id: None,
+            shared: 0,
+        }
+    }
+}
+
+impl DiskUsage {
+    #[cfg(unix)]
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        use std::os::unix::fs::MetadataExt;
+
+        Self {
+            // st_blocks is always in 512-byte units, whatever the filesystem block size
+            allocated: Some(metadata.blocks() * 512),
+            links: metadata.nlink(),
+            id: Some((metadata.dev(), metadata.ino())),
+            shared: 0,
+        }
+    }
+
+    #[cfg(windows)]
+    fn from_metadata(metadata: &fs::Metadata) -> Self {
+        use std::os::windows::fs::MetadataExt;
+
+        // Cloud placeholders (OneDrive "online-only", iCloud) keep their data
+        // elsewhere until opened: FILE_ATTRIBUTE_OFFLINE, RECALL_ON_OPEN and
+        // RECALL_ON_DATA_ACCESS
+        const NOT_ON_DISK: u32 = 0x1000 | 0x4_0000 | 0x40_0000;
+        Self {
+            allocated: (metadata.file_attributes() & NOT_ON_DISK != 0).then_some(0),
+            ..Self::default()
+        }
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn from_metadata(_metadata: &fs::Metadata) -> Self {
+        Self::default()
+    }
+}
+
+/// What a directory holds, counted recursively without following symlinks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DirectoryStats {
+    pub files: u64,
+    pub directories: u64,
+    /// Total size of the files
+    pub bytes: u64,
+}
+
+impl DirectoryStats {
+    /// Walks `path`; subdirectories that can't be read are skipped
+    pub fn of(path: &Path) -> io::Result<Self> {
+        Self::walk(path, |_| true)
+    }
+
+    /// Like `of`, calling `on_entry` with the counts so far after every entry; the
+    /// walk stops early (with partial counts) once it returns false
+    pub fn walk(path: &Path, mut on_entry: impl FnMut(&Self) -> bool) -> io::Result<Self> {
+        let mut stats = Self::default();
+        let mut pending = vec![fs::read_dir(path)?];
+        while let Some(entries) = pending.pop() {
+            for entry in entries.flatten() {
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                if file_type.is_dir() {
+                    stats.directories += 1;
+                    if let Ok(entries) = fs::read_dir(entry.path()) {
+                        pending.push(entries);
+                    }
+                } else {
+                    stats.files += 1;
+                    stats.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+                }
+                if !on_entry(&stats) {
+                    return Ok(stats);
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Files and subdirectories together
+    pub fn entries(&self) -> u64 {
+        self.files + self.directories
+    }
+}
+
+/// A directory item's size, as far as it has been measured
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DirectorySize {
+    pub stats: DirectoryStats,
+    /// False while the walk is still going and `stats` are partial
+    pub done: bool,
+}
+
 #[derive(Debug, Clone)]
 pub struct FileEntry {
     pub path: PathBuf,
+    /// Display name, lossily converted when the real name is not valid UTF-8
     pub name: String,
     pub size: u64,
     pub modified_date: DateTime<Utc>,
     pub file_type: FileType,
+    pub disk: DiskUsage,
 }
 
 impl FileEntry {
     pub fn from_path(path: &Path) -> io::Result<Self> {
         let metadata = fs::metadata(path)?;
+        let shared = crate::filesystem::shared_bytes(path).unwrap_or(0);
+        Self::with_metadata(path, &metadata, shared)
+    }
+
+    /// `from_path` without asking which extents are shared, for network mounts:
+    /// the lookup costs a round trip per file and remote files aren't local clones
+    pub fn from_path_unshared(path: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        Self::with_metadata(path, &metadata, 0)
+    }
+
+    /// Builds an entry from already fetched metadata and shared-extent bytes
+    /// (e.g. remembered by the scan index instead of asking the filesystem again)
+    pub fn with_metadata(path: &Path, metadata: &fs::Metadata, shared: u64) -> io::Result<Self> {
         let modified = metadata.modified()?;
         let modified_date: DateTime<Utc> = modified.into();
 
-        let name = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
+        let os_name = path.file_name().unwrap_or_default();
+        let name = if os_name.is_empty() {
+            "unknown".to_string()
+        } else {
+            os_name.to_string_lossy().into_owned()
+        };
 
-        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy())
+            .unwrap_or_default();
 
-        let file_type = FileType::from_extension(extension);
+        let file_type = FileType::from_extension(&extension);
 
         Ok(FileEntry {
             path: path.to_path_buf(),
@@ -35,8 +185,101 @@ impl FileEntry {
             size: metadata.len(),
             modified_date,
             file_type,
+            disk: DiskUsage {
+                shared,
+                ..DiskUsage::from_metadata(metadata)
+            },
+        })
+    }
+
+    /// An entry for a whole directory. Its size is left at 0: measuring everything
+    /// in it can take long, so that happens in the background (see `DirSizer`)
+    pub fn from_dir(path: &Path) -> io::Result<Self> {
+        let metadata = fs::metadata(path)?;
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "unknown".to_string());
+        Ok(FileEntry {
+            path: path.to_path_buf(),
+            name,
+            size: 0,
+            modified_date: metadata.modified()?.into(),
+            file_type: FileType::Directory,
+            disk: DiskUsage::default(),
         })
     }
+
+    /// Original file name as stored on disk, taken from `path` rather than kept twice
+    pub fn os_name(&self) -> &OsStr {
+        self.path.file_name().unwrap_or_default()
+    }
+
+    /// Returns true if the on-disk name is not valid UTF-8 and `name` is a lossy copy
+    pub fn has_lossy_name(&self) -> bool {
+        self.os_name().to_str().is_none()
+    }
+
+    /// Bytes the file occupies on disk (less than `size` for sparse files)
+    pub fn allocated_size(&self) -> u64 {
+        self.disk.allocated.unwrap_or(self.size)
+    }
+
+    /// Allocated bytes not shared with a copy-on-write clone; only these are freed
+    /// for sure when the file goes
+    pub fn exclusive_size(&self) -> u64 {
+        self.allocated_size().saturating_sub(self.disk.shared)
+    }
+
+    /// Returns true if fewer bytes are allocated than the file's apparent size
+    pub fn is_sparse(&self) -> bool {
+        self.allocated_size() < self.size
+    }
+
+    /// Returns true if none of the file's data is on this disk: an online-only
+    /// placeholder in a cloud-synced folder (or, elsewhere, an entirely sparse file)
+    pub fn is_online_only(&self) -> bool {
+        self.size > 0 && self.disk.allocated == Some(0)
+    }
+
+    /// Returns true if other hard links share this file's data
+    pub fn has_other_links(&self) -> bool {
+        self.disk.links > 1
+    }
+
+    /// Returns true if the name uses bidi control characters that can disguise it
+    pub fn has_bidi_control(&self) -> bool {
+        contains_bidi_control(&self.name)
+    }
+}
+
+/// Space actually freed by removing all of `files`.
+///
+/// Counts allocated blocks rather than apparent size, and only counts hard-linked
+/// data once every link to it is among `files`; removing one link frees nothing.
+/// Extents shared with copy-on-write clones are left out, since the clone keeps them.
+pub fn reclaimable_bytes<'a>(files: impl IntoIterator<Item = &'a FileEntry>) -> u64 {
+    let mut linked: HashMap<(u64, u64), (u64, u64, u64)> = HashMap::new();
+    let mut total = 0;
+    for file in files {
+        match file.disk.id {
+            Some(id) if file.has_other_links() => {
+                let entry = linked
+                    .entry(id)
+                    .or_insert((0, file.disk.links, file.exclusive_size()));
+                entry.0 += 1;
+            }
+            // Without an id we can't tell whether the other links go too
+            None if file.has_other_links() => {}
+            _ => total += file.exclusive_size(),
+        }
+    }
+    total
+        + linked
+            .values()
+            .filter(|(removed, links, _)| removed >= links)
+            .map(|(_, _, allocated)| allocated)
+            .sum::<u64>()
 }
 
 #[cfg(test)]
@@ -45,6 +288,40 @@ mod tests {
     use std::fs;
     use tempfile::NamedTempFile;
 
+    #[test]
+    fn test_directory_stats_counts_recursively() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("a/b")).unwrap();
+        fs::write(temp_dir.path().join("one.txt"), b"12").unwrap();
+        fs::write(temp_dir.path().join("a/b/two.txt"), b"345").unwrap();
+
+        let stats = DirectoryStats::of(temp_dir.path()).unwrap();
+        assert_eq!(
+            stats,
+            DirectoryStats {
+                files: 2,
+                directories: 2,
+                bytes: 5
+            }
+        );
+        assert_eq!(stats.entries(), 4);
+
+        let entry = FileEntry::from_dir(temp_dir.path()).unwrap();
+        assert_eq!(entry.file_type, FileType::Directory);
+        assert_eq!(entry.size, 0);
+        assert!(DirectoryStats::of(&temp_dir.path().join("missing")).is_err());
+
+        // Stopping early leaves partial counts
+        let mut calls = 0;
+        let partial = DirectoryStats::walk(temp_dir.path(), |_| {
+            calls += 1;
+            calls < 2
+        })
+        .unwrap();
+        assert_eq!(calls, 2);
+        assert_eq!(partial.entries(), 2);
+    }
+
     #[test]
     fn test_file_entry_from_path() {
         let temp_file = NamedTempFile::new().unwrap();
@@ -74,6 +351,180 @@ mod tests {
         fs::remove_file(&txt_path).ok();
     }
 
+    #[test]
+    fn test_file_entry_from_path_unshared() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), b"test content").unwrap();
+
+        let entry = FileEntry::from_path_unshared(temp_file.path()).unwrap();
+
+        assert_eq!(entry.size, 12);
+        assert_eq!(entry.disk.shared, 0);
+    }
+
+    fn entry_with_disk(size: u64, disk: DiskUsage) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from("f"),
+            name: "f".to_string(),
+            size,
+            modified_date: Utc::now(),
+            file_type: FileType::Binary,
+            disk,
+        }
+    }
+
+    #[test]
+    fn test_online_only_placeholder_frees_nothing() {
+        let placeholder = entry_with_disk(
+            5_000_000,
+            DiskUsage {
+                allocated: Some(0),
+                ..DiskUsage::default()
+            },
+        );
+        let empty = entry_with_disk(
+            0,
+            DiskUsage {
+                allocated: Some(0),
+                ..DiskUsage::default()
+            },
+        );
+        assert!(placeholder.is_online_only());
+        assert!(!empty.is_online_only());
+        assert!(!entry_with_disk(100, DiskUsage::default()).is_online_only());
+        assert_eq!(reclaimable_bytes([&placeholder]), 0);
+    }
+
+    #[test]
+    fn test_reclaimable_bytes_uses_allocated_size() {
+        let sparse = entry_with_disk(
+            1_000_000,
+            DiskUsage {
+                allocated: Some(4096),
+                ..DiskUsage::default()
+            },
+        );
+        let plain = entry_with_disk(100, DiskUsage::default());
+
+        assert!(sparse.is_sparse());
+        assert!(!plain.is_sparse());
+        assert_eq!(reclaimable_bytes([&sparse, &plain]), 4096 + 100);
+    }
+
+    #[test]
+    fn test_reclaimable_bytes_needs_every_hard_link() {
+        let linked = DiskUsage {
+            allocated: Some(8192),
+            links: 2,
+            id: Some((1, 42)),
+            shared: 0,
+        };
+        let a = entry_with_disk(8000, linked);
+        let b = entry_with_disk(8000, linked);
+
+        assert!(a.has_other_links());
+        assert_eq!(reclaimable_bytes([&a]), 0);
+        assert_eq!(reclaimable_bytes([&a, &b]), 8192);
+    }
+
+    #[test]
+    fn test_reclaimable_bytes_skips_shared_clone_extents() {
+        let clone = entry_with_disk(
+            10_000,
+            DiskUsage {
+                allocated: Some(12_288),
+                shared: 8_192,
+                ..DiskUsage::default()
+            },
+        );
+        assert_eq!(clone.exclusive_size(), 4_096);
+        assert_eq!(reclaimable_bytes([&clone]), 4_096);
+    }
+
+    #[test]
+    fn test_reclaimable_bytes_unknown_links_count_nothing() {
+        let unknown = entry_with_disk(
+            500,
+            DiskUsage {
+                links: 3,
+                ..DiskUsage::default()
+            },
+        );
+        assert_eq!(reclaimable_bytes([&unknown]), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_entry_from_path_reads_links_and_blocks() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.bin");
+        let file = fs::File::create(&path).unwrap();
+        // A hole with no data written: most filesystems allocate nothing for it
+        file.set_len(10 * 1024 * 1024).unwrap();
+        fs::hard_link(&path, temp_dir.path().join("link.bin")).unwrap();
+
+        let entry = FileEntry::from_path(&path).unwrap();
+
+        assert_eq!(entry.disk.links, 2);
+        assert!(entry.disk.id.is_some());
+        assert!(entry.allocated_size() < entry.size);
+        assert!(entry.is_sparse());
+    }
+
+    #[test]
+    fn test_contains_bidi_control() {
+        assert!(contains_bidi_control("invoice\u{202E}fdp.exe"));
+        assert!(contains_bidi_control("\u{2067}report.txt"));
+        assert!(!contains_bidi_control("plain-name.txt"));
+        // Genuine right-to-left text is not a control character
+        assert!(!contains_bidi_control("تقرير.pdf"));
+        assert!(!contains_bidi_control("דוח.pdf"));
+    }
+
+    #[test]
+    fn test_file_entry_has_bidi_control() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("invoice\u{202E}fdp.exe");
+        fs::write(&path, b"MZ").unwrap();
+
+        let entry = FileEntry::from_path(&path).unwrap();
+
+        assert!(entry.has_bidi_control());
+        assert_eq!(entry.file_type, FileType::Binary);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_entry_non_utf8_name() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let raw_name = OsStr::from_bytes(b"caf\xe9-menu.txt");
+        let path = temp_dir.path().join(raw_name);
+        fs::write(&path, b"hello").unwrap();
+
+        let entry = FileEntry::from_path(&path).unwrap();
+
+        assert!(entry.has_lossy_name());
+        assert_eq!(entry.os_name(), raw_name);
+        assert_eq!(entry.name, "caf\u{FFFD}-menu.txt");
+        assert_eq!(entry.file_type, FileType::Text);
+        assert_eq!(entry.path, path);
+    }
+
+    #[test]
+    fn test_file_entry_utf8_name_is_not_lossy() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("café.txt");
+        fs::write(&path, b"hello").unwrap();
+
+        let entry = FileEntry::from_path(&path).unwrap();
+
+        assert!(!entry.has_lossy_name());
+        assert_eq!(entry.name, "café.txt");
+    }
+
     #[test]
     fn test_file_entry_nonexistent_file() {
         let result = FileEntry::from_path(Path::new("/nonexistent/file.txt"));
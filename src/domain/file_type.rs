@@ -1,12 +1,29 @@
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub enum FileType {
     Text,
     Image,
     Pdf,
+    /// zip, tar, tar.gz, gzip and 7z files, previewed as a listing of their entries
+    Archive,
+    /// mp3, flac, ogg and m4a files, previewed as their tags and stream details
+    Audio,
     Binary,
+    /// A whole subdirectory, reviewed as one item (`--dirs`)
+    Directory,
 }
 
 impl FileType {
+    /// Every file type, in display order
+    pub const ALL: [FileType; 7] = [
+        FileType::Text,
+        FileType::Image,
+        FileType::Pdf,
+        FileType::Archive,
+        FileType::Audio,
+        FileType::Binary,
+        FileType::Directory,
+    ];
+
     pub fn from_extension(ext: &str) -> Self {
         let ext = ext.to_lowercase();
         match ext.as_str() {
@@ -15,16 +32,37 @@ impl FileType {
             | "toml" | "xml" | "html" | "css" | "sh" | "bash" | "c" | "cpp" | "h" | "hpp"
             | "java" | "go" | "rb" | "php" | "swift" | "kt" | "cs" | "sql" => FileType::Text,
 
-            // Image files
-            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "ico" => FileType::Image,
+            // Image files, including camera RAW and HEIC photos
+            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "ico" | "heic" | "heif"
+            | "cr2" | "nef" | "arw" | "dng" => FileType::Image,
 
             // PDF files
             "pdf" => FileType::Pdf,
 
+            // Archives
+            "zip" | "tar" | "gz" | "tgz" | "7z" => FileType::Archive,
+
+            // Audio
+            "mp3" | "flac" | "ogg" | "m4a" => FileType::Audio,
+
             // Everything else is binary
             _ => FileType::Binary,
         }
     }
+
+    /// Parses a type name as written in the config file ("Image", "pdf", ...)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "text" => Some(FileType::Text),
+            "image" => Some(FileType::Image),
+            "pdf" => Some(FileType::Pdf),
+            "archive" => Some(FileType::Archive),
+            "audio" => Some(FileType::Audio),
+            "binary" => Some(FileType::Binary),
+            "directory" => Some(FileType::Directory),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -47,6 +85,8 @@ mod tests {
         assert_eq!(FileType::from_extension("jpeg"), FileType::Image);
         assert_eq!(FileType::from_extension("gif"), FileType::Image);
         assert_eq!(FileType::from_extension("webp"), FileType::Image);
+        assert_eq!(FileType::from_extension("heic"), FileType::Image);
+        assert_eq!(FileType::from_extension("NEF"), FileType::Image);
     }
 
     #[test]
@@ -54,6 +94,24 @@ mod tests {
         assert_eq!(FileType::from_extension("pdf"), FileType::Pdf);
     }
 
+    #[test]
+    fn test_file_type_from_extension_archive() {
+        assert_eq!(FileType::from_extension("zip"), FileType::Archive);
+        assert_eq!(FileType::from_extension("gz"), FileType::Archive);
+        assert_eq!(FileType::from_extension("TGZ"), FileType::Archive);
+        assert_eq!(FileType::from_extension("7z"), FileType::Archive);
+        // ZIP-based documents keep their own type
+        assert_eq!(FileType::from_extension("docx"), FileType::Binary);
+    }
+
+    #[test]
+    fn test_file_type_from_extension_audio() {
+        assert_eq!(FileType::from_extension("mp3"), FileType::Audio);
+        assert_eq!(FileType::from_extension("FLAC"), FileType::Audio);
+        assert_eq!(FileType::from_extension("ogg"), FileType::Audio);
+        assert_eq!(FileType::from_extension("m4a"), FileType::Audio);
+    }
+
     #[test]
     fn test_file_type_from_extension_binary() {
         assert_eq!(FileType::from_extension("exe"), FileType::Binary);
@@ -68,4 +126,13 @@ mod tests {
         assert_eq!(FileType::from_extension("TXT"), FileType::Text);
         assert_eq!(FileType::from_extension("PDF"), FileType::Pdf);
     }
+
+    #[test]
+    fn test_file_type_from_name() {
+        assert_eq!(FileType::from_name("Image"), Some(FileType::Image));
+        assert_eq!(FileType::from_name("pdf"), Some(FileType::Pdf));
+        assert_eq!(FileType::from_name("Archive"), Some(FileType::Archive));
+        assert_eq!(FileType::from_name("audio"), Some(FileType::Audio));
+        assert_eq!(FileType::from_name("Video"), None);
+    }
 }
@@ -0,0 +1,213 @@
+use super::{FileEntry, FileType};
+use crate::cli::Args;
+use crate::error::{FileTinderError, Result};
+use chrono::{DateTime, Duration, Utc};
+
+/// One space-separated part of a filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Term {
+    /// Case-insensitive substring of the name
+    Name(String),
+    Type(FileType),
+    /// Extension without the dot, lowercase
+    Extension(String),
+    /// Larger than this many bytes
+    Larger(u64),
+    /// Smaller than this many bytes
+    Smaller(u64),
+    /// Last modified before this time
+    ModifiedBefore(DateTime<Utc>),
+    /// Last modified after this time
+    ModifiedAfter(DateTime<Utc>),
+    Not(Box<Term>),
+}
+
+impl Term {
+    fn parse(text: &str) -> Result<Self> {
+        if let Some(rest) = text.strip_prefix('!') {
+            if rest.is_empty() {
+                return Err(invalid("'!' needs a term after it"));
+            }
+            return Ok(Term::Not(Box::new(Term::parse(rest)?)));
+        }
+        if let Some(name) = text.strip_prefix("type:") {
+            return FileType::from_name(name)
+                .map(Term::Type)
+                .ok_or_else(|| invalid(format!("unknown type '{}'", name)));
+        }
+        if let Some(extension) = text.strip_prefix("ext:") {
+            let extension = extension.trim_start_matches('.');
+            if extension.is_empty() {
+                return Err(invalid("'ext:' needs an extension"));
+            }
+            return Ok(Term::Extension(extension.to_lowercase()));
+        }
+        if let Some(size) = text.strip_prefix("size>") {
+            return parse_size(size).map(Term::Larger);
+        }
+        if let Some(size) = text.strip_prefix("size<") {
+            return parse_size(size).map(Term::Smaller);
+        }
+        if let Some(age) = text.strip_prefix("age>") {
+            return parse_age(age).map(|age| Term::ModifiedBefore(Utc::now() - age));
+        }
+        if let Some(age) = text.strip_prefix("age<") {
+            return parse_age(age).map(|age| Term::ModifiedAfter(Utc::now() - age));
+        }
+        Ok(Term::Name(text.to_lowercase()))
+    }
+
+    fn matches(&self, file: &FileEntry) -> bool {
+        match self {
+            Term::Name(text) => file.name.to_lowercase().contains(text.as_str()),
+            Term::Type(file_type) => file.file_type == *file_type,
+            Term::Extension(extension) => file
+                .path
+                .extension()
+                .is_some_and(|ext| ext.to_string_lossy().to_lowercase() == *extension),
+            Term::Larger(bytes) => file.size > *bytes,
+            Term::Smaller(bytes) => file.size < *bytes,
+            Term::ModifiedBefore(time) => file.modified_date < *time,
+            Term::ModifiedAfter(time) => file.modified_date > *time,
+            Term::Not(term) => !term.matches(file),
+        }
+    }
+}
+
+/// A filter typed into the filter bar, e.g. `type:image size>5MB !screenshot`.
+///
+/// Every term has to match: plain words match anywhere in the name
+/// (case-insensitive), `type:` takes a file type, `ext:` an extension,
+/// `size>` / `size<` a size like `parse_size` accepts, `age>` / `age<` an age
+/// in days, weeks, months or years (`30d`, `6m`, `1y`), and `!` negates a term.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct QueueFilter {
+    terms: Vec<Term>,
+}
+
+impl QueueFilter {
+    pub fn parse(text: &str) -> Result<Self> {
+        let terms = text
+            .split_whitespace()
+            .map(Term::parse)
+            .collect::<Result<_>>()?;
+        Ok(Self { terms })
+    }
+
+    /// Whether the filter has no terms (and so matches everything)
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, file: &FileEntry) -> bool {
+        self.terms.iter().all(|term| term.matches(file))
+    }
+}
+
+fn parse_size(text: &str) -> Result<u64> {
+    Args::parse_size(text).ok_or_else(|| invalid(format!("'{}' is not a size (e.g. 5MB)", text)))
+}
+
+/// Parses `30d`, `2w`, `6m` or `1y` (months are 30 days, years 365)
+pub fn parse_age(text: &str) -> Result<Duration> {
+    let days = text
+        .len()
+        .checked_sub(1)
+        .and_then(|split| {
+            let count: i64 = text.get(..split)?.parse().ok()?;
+            let unit = match text.get(split..)? {
+                "d" => 1,
+                "w" => 7,
+                "m" => 30,
+                "y" => 365,
+                _ => return None,
+            };
+            count.checked_mul(unit)
+        })
+        .ok_or_else(|| invalid(format!("'{}' is not an age (e.g. 30d, 6m, 1y)", text)))?;
+    Duration::try_days(days).ok_or_else(|| invalid(format!("'{}' is too long ago", text)))
+}
+
+fn invalid(reason: impl Into<String>) -> FileTinderError {
+    FileTinderError::InvalidFilter(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use std::path::PathBuf;
+
+    fn entry(name: &str, size: u64) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(name),
+            name: name.to_string(),
+            size,
+            modified_date: Utc::now() - Duration::days(size as i64 % 1000),
+            file_type: FileType::from_extension(name.rsplit('.').next().unwrap_or("")),
+            disk: Default::default(),
+        }
+    }
+
+    fn matching<'a>(filter: &str, files: &'a [FileEntry]) -> Vec<&'a str> {
+        let filter = QueueFilter::parse(filter).unwrap();
+        files
+            .iter()
+            .filter(|file| filter.matches(file))
+            .map(|file| file.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_queue_filter_terms() {
+        let files = [
+            entry("Screenshot 1.png", 2_000_000),
+            entry("holiday.JPG", 8_000_000),
+            entry("notes.txt", 300),
+            entry("backup.zip", 900_000_000),
+        ];
+
+        assert_eq!(matching("", &files).len(), 4);
+        assert_eq!(matching("screenshot", &files), vec!["Screenshot 1.png"]);
+        assert_eq!(
+            matching("type:image", &files),
+            vec!["Screenshot 1.png", "holiday.JPG"]
+        );
+        assert_eq!(matching("ext:jpg", &files), vec!["holiday.JPG"]);
+        assert_eq!(
+            matching("size>1MB size<100MB", &files),
+            vec!["Screenshot 1.png", "holiday.JPG"]
+        );
+        assert_eq!(
+            matching("type:image !screenshot", &files),
+            vec!["holiday.JPG"]
+        );
+        // Ages: 0, 0, 300 and 0 days, from the sizes above
+        assert_eq!(matching("age>6m", &files), vec!["notes.txt"]);
+        assert_eq!(matching("age<1w", &files).len(), 3);
+    }
+
+    #[test]
+    fn test_queue_filter_rejects_bad_terms() {
+        assert!(QueueFilter::parse("").unwrap().is_empty());
+        for text in [
+            "type:movie",
+            "size>",
+            "size<lots",
+            "ext:",
+            "!",
+            "age>",
+            "age>5",
+            "age<3x",
+        ] {
+            assert!(
+                matches!(
+                    QueueFilter::parse(text),
+                    Err(FileTinderError::InvalidFilter(_))
+                ),
+                "{}",
+                text
+            );
+        }
+    }
+}
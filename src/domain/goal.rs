@@ -0,0 +1,56 @@
+/// A target for the session, shown as a second progress gauge
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionGoal {
+    /// Free at least this many bytes by trashing files
+    FreeBytes(u64),
+    /// Decide on at least this many files
+    ReviewFiles(usize),
+}
+
+impl SessionGoal {
+    /// Fraction of the goal reached (0.0..=1.0) given decisions so far
+    pub fn progress(&self, reviewed: usize, freed_bytes: u64) -> f64 {
+        let (done, target) = match *self {
+            SessionGoal::FreeBytes(target) => (freed_bytes as f64, target as f64),
+            SessionGoal::ReviewFiles(target) => (reviewed as f64, target as f64),
+        };
+        if target <= 0.0 {
+            return 1.0;
+        }
+        (done / target).min(1.0)
+    }
+
+    pub fn is_met(&self, reviewed: usize, freed_bytes: u64) -> bool {
+        match *self {
+            SessionGoal::FreeBytes(target) => freed_bytes >= target,
+            SessionGoal::ReviewFiles(target) => reviewed >= target,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_free_bytes_goal() {
+        let goal = SessionGoal::FreeBytes(1000);
+        assert_eq!(goal.progress(50, 250), 0.25);
+        assert!(!goal.is_met(50, 999));
+        assert!(goal.is_met(0, 1000));
+    }
+
+    #[test]
+    fn test_review_files_goal() {
+        let goal = SessionGoal::ReviewFiles(4);
+        assert_eq!(goal.progress(1, u64::MAX), 0.25);
+        assert!(!goal.is_met(3, u64::MAX));
+        assert!(goal.is_met(4, 0));
+    }
+
+    #[test]
+    fn test_goal_progress_is_capped() {
+        assert_eq!(SessionGoal::ReviewFiles(2).progress(5, 0), 1.0);
+        assert_eq!(SessionGoal::FreeBytes(0).progress(0, 0), 1.0);
+    }
+}
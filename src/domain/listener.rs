@@ -0,0 +1,25 @@
+use super::{Decision, FileEntry};
+
+/// Observer for `DecisionEngine` events, registered with `add_listener`.
+///
+/// Every method has a no-op default, so listeners only implement what they need.
+pub trait DecisionListener {
+    /// A decision was recorded (and, for trash, the file staged)
+    fn on_decision(&mut self, _index: usize, _file: &FileEntry, _decision: &Decision) {}
+
+    /// A decision was undone (and, for trash, the file restored)
+    fn on_undo(&mut self, _index: usize, _file: &FileEntry, _decision: &Decision) {}
+
+    /// A decided file was re-decided from `old` to `new` (files re-staged or moved
+    /// accordingly). Defaults to an undo of `old` followed by a decision of `new`.
+    fn on_change(&mut self, index: usize, file: &FileEntry, old: &Decision, new: &Decision) {
+        self.on_undo(index, file, old);
+        self.on_decision(index, file, new);
+    }
+
+    /// `done` of `total` trashed files have been moved to the system trash
+    fn on_apply_progress(&mut self, _done: usize, _total: usize, _file: &FileEntry) {}
+
+    /// A trashed file reached the system trash, by apply or background apply
+    fn on_applied(&mut self, _index: usize, _file: &FileEntry) {}
+}
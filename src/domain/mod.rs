@@ -1,15 +1,46 @@
 // Module declarations
 pub mod app_state;
+pub mod apply;
+pub mod compact;
+pub mod copies;
 pub mod decision;
 pub mod decision_engine;
+pub mod dedup;
 pub mod discovery;
+pub mod easy_wins;
 pub mod file_entry;
 pub mod file_type;
+pub mod filter;
+pub mod goal;
+pub mod listener;
+pub mod names;
+pub mod overview;
+pub mod pattern;
+pub mod sidecar;
+pub mod verify;
 
 // Re-exports for convenience
-pub use app_state::AppState;
-pub use decision::{Decision, DecisionStatistics};
+pub use app_state::{AppState, Narrowing, ReviewPhase};
+pub use apply::{ApplyJob, ApplyOutcome};
+pub use compact::{CompactEntry, CompactFiles, PathInterner, SizeBucket};
+pub use copies::{find_redundant_copies, CopyFinder};
+pub use decision::{Decision, DecisionStatistics, DefaultDecision};
 pub use decision_engine::DecisionEngine;
-pub use discovery::{discover_files, discover_files_with_options, DiscoveryOptions, SortBy};
-pub use file_entry::FileEntry;
+pub use dedup::{find_duplicates, DuplicateFinder, DuplicateGroup};
+pub use discovery::{
+    compare_files, discover_files, discover_files_excluding, discover_files_with,
+    discover_files_with_options, DiscoveryOptions, LeftOut, ScanFilter, SortBy,
+};
+pub use easy_wins::{find_easy_wins, EasyWin, EasyWinKind};
+pub use file_entry::{
+    contains_bidi_control, reclaimable_bytes, DirectorySize, DirectoryStats, DiskUsage, FileEntry,
+};
 pub use file_type::FileType;
+pub use filter::{parse_age, QueueFilter};
+pub use goal::SessionGoal;
+pub use listener::DecisionListener;
+pub use names::{find_renames, NameIssue, RenameSuggestion};
+pub use overview::{QueueTally, ScanOverview, Tally};
+pub use pattern::{original_name, NamePattern};
+pub use sidecar::is_sidecar_of;
+pub use verify::{verify_apply, Anomaly};
@@ -0,0 +1,198 @@
+use super::FileEntry;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+/// Device names Windows won't create a file under, whatever the extension
+const RESERVED_WINDOWS_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Something about a file name that breaks on other systems or in sync tools
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NameIssue {
+    /// Starts with a space
+    LeadingSpace,
+    /// Ends with a space or a dot, which Windows strips
+    TrailingSpaceOrDot,
+    /// `CON`, `NUL`, `COM1` and the like
+    ReservedOnWindows,
+    /// Not in Unicode NFC form (macOS writes decomposed accents), so the same
+    /// name can exist twice and compare unequal
+    Denormalized,
+}
+
+impl fmt::Display for NameIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            NameIssue::LeadingSpace => "leading space",
+            NameIssue::TrailingSpaceOrDot => "trailing space or dot",
+            NameIssue::ReservedOnWindows => "reserved on Windows",
+            NameIssue::Denormalized => "mixed Unicode forms",
+        })
+    }
+}
+
+/// A kept file whose name could be fixed when the session is applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenameSuggestion {
+    /// Index into the session's files
+    pub index: usize,
+    pub new_name: String,
+    pub issues: Vec<NameIssue>,
+    /// Whether the rename will be done
+    pub accepted: bool,
+}
+
+/// What's wrong with `name`, if anything
+pub fn name_issues(name: &str) -> Vec<NameIssue> {
+    let mut issues = Vec::new();
+    if name.starts_with(' ') {
+        issues.push(NameIssue::LeadingSpace);
+    }
+    if name.ends_with([' ', '.']) {
+        issues.push(NameIssue::TrailingSpaceOrDot);
+    }
+    if is_reserved(name.trim()) {
+        issues.push(NameIssue::ReservedOnWindows);
+    }
+    if !is_nfc(name) {
+        issues.push(NameIssue::Denormalized);
+    }
+    issues
+}
+
+/// `name` in NFC without surrounding spaces or trailing dots, with `_` after a
+/// reserved stem (`con.txt` → `con_.txt`)
+pub fn normalize_name(name: &str) -> String {
+    let name: String = name.nfc().collect();
+    let name = name.trim_start_matches(' ').trim_end_matches([' ', '.']);
+    if !is_reserved(name) {
+        return name.to_string();
+    }
+    match name.split_once('.') {
+        Some((stem, rest)) => format!("{}_.{}", stem, rest),
+        None => format!("{}_", name),
+    }
+}
+
+fn is_reserved(name: &str) -> bool {
+    let stem = name.split('.').next().unwrap_or_default();
+    RESERVED_WINDOWS_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+}
+
+/// Renames worth offering for the files at `indices`, in that order (accepted).
+///
+/// Names that aren't valid UTF-8 are left out, as are names that would
+/// normalize to nothing.
+pub fn find_renames(
+    files: &[FileEntry],
+    indices: impl IntoIterator<Item = usize>,
+) -> Vec<RenameSuggestion> {
+    indices
+        .into_iter()
+        .filter_map(|index| {
+            let file = files.get(index)?;
+            if file.has_lossy_name() {
+                return None;
+            }
+            let issues = name_issues(&file.name);
+            let new_name = normalize_name(&file.name);
+            (!issues.is_empty() && !new_name.is_empty() && new_name != file.name).then_some(
+                RenameSuggestion {
+                    index,
+                    new_name,
+                    issues,
+                    accepted: true,
+                },
+            )
+        })
+        .collect()
+}
+
+/// Renames the file at `path` to `new_name` in the same directory, refusing to
+/// replace an existing file; returns the new path
+pub fn rename_file(path: &Path, new_name: &str) -> io::Result<PathBuf> {
+    let target = path.with_file_name(new_name);
+    if target.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{:?} already exists", target),
+        ));
+    }
+    fs::rename(path, &target)?;
+    Ok(target)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_name_issues_and_normalized_names() {
+        let cases = [
+            ("report.pdf", vec![], "report.pdf"),
+            (
+                "notes.txt ",
+                vec![NameIssue::TrailingSpaceOrDot],
+                "notes.txt",
+            ),
+            (
+                " draft.",
+                vec![NameIssue::LeadingSpace, NameIssue::TrailingSpaceOrDot],
+                "draft",
+            ),
+            ("con.txt", vec![NameIssue::ReservedOnWindows], "con_.txt"),
+            ("LPT1", vec![NameIssue::ReservedOnWindows], "LPT1_"),
+            ("console.log", vec![], "console.log"),
+            (
+                "Cafe\u{301}.md",
+                vec![NameIssue::Denormalized],
+                "Caf\u{e9}.md",
+            ),
+        ];
+        for (name, issues, normalized) in cases {
+            assert_eq!(name_issues(name), issues, "{:?}", name);
+            assert_eq!(normalize_name(name), normalized, "{:?}", name);
+        }
+    }
+
+    #[test]
+    fn test_find_renames_and_rename_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let names = ["fine.txt", "spaced.txt ", "...", "nul.txt"];
+        let files: Vec<FileEntry> = names
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, name).unwrap();
+                FileEntry::from_path(&path).unwrap()
+            })
+            .collect();
+
+        // Only the listed files are looked at, and "..." would become nothing
+        let renames = find_renames(&files, [0, 1, 2]);
+        assert_eq!(
+            renames,
+            [RenameSuggestion {
+                index: 1,
+                new_name: "spaced.txt".to_string(),
+                issues: vec![NameIssue::TrailingSpaceOrDot],
+                accepted: true,
+            }]
+        );
+
+        let renamed = rename_file(&files[1].path, "spaced.txt").unwrap();
+        assert_eq!(fs::read_to_string(renamed).unwrap(), "spaced.txt ");
+        // Never over an existing file
+        let err = rename_file(&files[3].path, "fine.txt").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert!(files[3].path.exists());
+    }
+}
@@ -0,0 +1,285 @@
+use super::{FileEntry, FileType};
+use chrono::{DateTime, Utc};
+
+/// Age bands of the histogram as (label, files younger than this many days)
+const AGE_BANDS: [(&str, i64); 7] = [
+    ("<1w", 7),
+    ("<1m", 30),
+    ("<6m", 182),
+    ("<1y", 365),
+    ("<2y", 730),
+    ("<5y", 1826),
+    ("older", i64::MAX),
+];
+
+/// Number of files and their combined size
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub count: usize,
+    pub bytes: u64,
+}
+
+impl Tally {
+    fn add(&mut self, file: &FileEntry) {
+        self.count += 1;
+        self.bytes += file.size;
+    }
+
+    fn remove(&mut self, file: &FileEntry) {
+        self.count = self.count.saturating_sub(1);
+        self.bytes = self.bytes.saturating_sub(file.size);
+    }
+}
+
+/// What's left to review, by type: updated file by file as decisions are made and
+/// undone rather than recounted
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueueTally {
+    pub total: Tally,
+    by_type: Vec<(FileType, Tally)>,
+}
+
+impl QueueTally {
+    pub fn new(files: &[FileEntry]) -> Self {
+        let mut tally = Self {
+            total: Tally::default(),
+            by_type: FileType::ALL
+                .into_iter()
+                .map(|file_type| (file_type, Tally::default()))
+                .collect(),
+        };
+        for file in files {
+            tally.add(file);
+        }
+        tally
+    }
+
+    /// A file went back to being undecided
+    pub fn add(&mut self, file: &FileEntry) {
+        self.total.add(file);
+        if let Some(tally) = self.tally_mut(&file.file_type) {
+            tally.add(file);
+        }
+    }
+
+    /// A file was decided
+    pub fn remove(&mut self, file: &FileEntry) {
+        self.total.remove(file);
+        if let Some(tally) = self.tally_mut(&file.file_type) {
+            tally.remove(file);
+        }
+    }
+
+    /// An undecided file's size changed to `bytes` (a directory finished measuring)
+    pub fn resize(&mut self, file: &FileEntry, bytes: u64) {
+        self.remove(file);
+        self.add(&FileEntry {
+            size: bytes,
+            ..file.clone()
+        });
+    }
+
+    /// Types with files left, most files first
+    pub fn by_type(&self) -> Vec<(FileType, Tally)> {
+        let mut by_type: Vec<_> = self
+            .by_type
+            .iter()
+            .filter(|(_, tally)| tally.count > 0)
+            .cloned()
+            .collect();
+        by_type.sort_by_key(|(_, tally)| std::cmp::Reverse(tally.count));
+        by_type
+    }
+
+    fn tally_mut(&mut self, file_type: &FileType) -> Option<&mut Tally> {
+        self.by_type
+            .iter_mut()
+            .find(|(t, _)| t == file_type)
+            .map(|(_, tally)| tally)
+    }
+}
+
+/// What a scan turned up: files by age and by type, to see where the clutter is
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanOverview {
+    /// Every age band youngest first, empty ones included so the histogram keeps its shape
+    pub by_age: Vec<(&'static str, Tally)>,
+    /// File types with at least one file, largest total size first
+    pub by_type: Vec<(FileType, Tally)>,
+}
+
+impl ScanOverview {
+    /// Tallies `files` by the age of their last modification at `now`
+    pub fn new(files: &[FileEntry], now: DateTime<Utc>) -> Self {
+        let mut by_age: Vec<(&'static str, Tally)> = AGE_BANDS
+            .iter()
+            .map(|(label, _)| (*label, Tally::default()))
+            .collect();
+        let mut by_type: Vec<(FileType, Tally)> = FileType::ALL
+            .into_iter()
+            .map(|file_type| (file_type, Tally::default()))
+            .collect();
+
+        for file in files {
+            let days = (now - file.modified_date).num_days();
+            let band = AGE_BANDS
+                .iter()
+                .position(|(_, limit)| days < *limit)
+                .unwrap_or(AGE_BANDS.len() - 1);
+            by_age[band].1.add(file);
+            if let Some((_, tally)) = by_type.iter_mut().find(|(t, _)| *t == file.file_type) {
+                tally.add(file);
+            }
+        }
+
+        by_type.retain(|(_, tally)| tally.count > 0);
+        by_type.sort_by_key(|(_, tally)| std::cmp::Reverse(tally.bytes));
+        Self { by_age, by_type }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use std::path::PathBuf;
+
+    fn entry(name: &str, size: u64, days_old: i64, file_type: FileType) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from("/d").join(name),
+            name: name.to_string(),
+            size,
+            modified_date: Utc::now() - Duration::days(days_old),
+            file_type,
+            disk: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_overview_tallies_by_age_and_type() {
+        let files = vec![
+            entry("shot1.png", 300, 1, FileType::Image),
+            entry("shot2.png", 300, 2, FileType::Image),
+            entry("notes.txt", 10, 40, FileType::Text),
+            entry("old.zip", 5000, 800, FileType::Binary),
+            entry("ancient.zip", 7000, 4000, FileType::Binary),
+        ];
+        let overview = ScanOverview::new(&files, Utc::now());
+
+        let ages: Vec<(&str, usize)> = overview
+            .by_age
+            .iter()
+            .map(|(label, tally)| (*label, tally.count))
+            .collect();
+        assert_eq!(
+            ages,
+            [
+                ("<1w", 2),
+                ("<1m", 0),
+                ("<6m", 1),
+                ("<1y", 0),
+                ("<2y", 0),
+                ("<5y", 1),
+                ("older", 1)
+            ]
+        );
+
+        // Biggest first, types without files left out
+        assert_eq!(
+            overview.by_type,
+            [
+                (
+                    FileType::Binary,
+                    Tally {
+                        count: 2,
+                        bytes: 12000
+                    }
+                ),
+                (
+                    FileType::Image,
+                    Tally {
+                        count: 2,
+                        bytes: 600
+                    }
+                ),
+                (
+                    FileType::Text,
+                    Tally {
+                        count: 1,
+                        bytes: 10
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overview_of_no_files() {
+        let overview = ScanOverview::new(&[], Utc::now());
+        assert_eq!(overview.by_age.len(), 7);
+        assert!(overview.by_age.iter().all(|(_, tally)| tally.count == 0));
+        assert!(overview.by_type.is_empty());
+    }
+
+    #[test]
+    fn test_queue_tally_follows_decisions() {
+        let files = vec![
+            entry("a.png", 300, 1, FileType::Image),
+            entry("b.png", 200, 1, FileType::Image),
+            entry("c.pdf", 50, 1, FileType::Pdf),
+        ];
+        let mut tally = QueueTally::new(&files);
+        assert_eq!(
+            tally.total,
+            Tally {
+                count: 3,
+                bytes: 550
+            }
+        );
+
+        tally.remove(&files[0]);
+        tally.resize(&files[2], 80);
+        assert_eq!(
+            tally.total,
+            Tally {
+                count: 2,
+                bytes: 280
+            }
+        );
+        assert_eq!(
+            tally.by_type(),
+            [
+                (
+                    FileType::Image,
+                    Tally {
+                        count: 1,
+                        bytes: 200
+                    }
+                ),
+                (
+                    FileType::Pdf,
+                    Tally {
+                        count: 1,
+                        bytes: 80
+                    }
+                ),
+            ]
+        );
+
+        // Types without files left drop out; undone decisions come back
+        tally.remove(&files[1]);
+        assert_eq!(tally.by_type().len(), 1);
+        tally.add(&files[0]);
+        tally.add(&files[1]);
+        assert_eq!(
+            tally.by_type()[0],
+            (
+                FileType::Image,
+                Tally {
+                    count: 2,
+                    bytes: 500
+                }
+            )
+        );
+    }
+}
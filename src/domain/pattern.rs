@@ -0,0 +1,179 @@
+use std::fmt;
+
+/// A piece of a file stem
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Text(String),
+    /// A run of this many digits
+    Digits(usize),
+}
+
+/// A file name generalized for "select all like this".
+///
+/// Digit runs match any digits of the same length (`IMG_####.jpg`), and browser
+/// or Finder copy suffixes — `name (1).ext`, `name copy.ext`, `name copy 2.ext` —
+/// are ignored when the name had one (`report (*).pdf` also matches `report.pdf`).
+/// Extensions compare case-insensitively.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamePattern {
+    parts: Vec<Part>,
+    copies: bool,
+    extension: Option<String>,
+}
+
+impl NamePattern {
+    /// The pattern for `name`, or `None` when nothing in it varies (no digits, no copy suffix)
+    pub fn generalize(name: &str) -> Option<Self> {
+        let (stem, extension) = split_extension(name);
+        let (stem, copies) = match strip_copy_suffix(stem) {
+            Some(base) => (base, true),
+            None => (stem, false),
+        };
+        let parts = parts(stem);
+        if !copies && !parts.iter().any(|part| matches!(part, Part::Digits(_))) {
+            return None;
+        }
+        Some(Self {
+            parts,
+            copies,
+            extension: extension.map(str::to_lowercase),
+        })
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        let (stem, extension) = split_extension(name);
+        if extension.map(str::to_lowercase) != self.extension {
+            return false;
+        }
+        let stem = if self.copies {
+            strip_copy_suffix(stem).unwrap_or(stem)
+        } else {
+            stem
+        };
+        parts(stem) == self.parts
+    }
+}
+
+impl fmt::Display for NamePattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => f.write_str(text)?,
+                Part::Digits(len) => f.write_str(&"#".repeat(*len))?,
+            }
+        }
+        if self.copies {
+            f.write_str(" (*)")?;
+        }
+        if let Some(ref extension) = self.extension {
+            write!(f, ".{}", extension)?;
+        }
+        Ok(())
+    }
+}
+
+/// The name without its copy suffix (`report (2).pdf` → `report.pdf`), if it has one
+pub fn original_name(name: &str) -> Option<String> {
+    let (stem, extension) = split_extension(name);
+    let base = strip_copy_suffix(stem)?;
+    Some(match extension {
+        Some(extension) => format!("{}.{}", base, extension),
+        None => base.to_string(),
+    })
+}
+
+/// Splits off the extension; dotfiles like `.bashrc` have none
+fn split_extension(name: &str) -> (&str, Option<&str>) {
+    match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, Some(extension)),
+        _ => (name, None),
+    }
+}
+
+/// The stem without a copy suffix, if it has one: ` (N)`, ` copy` or ` copy N`
+fn strip_copy_suffix(stem: &str) -> Option<&str> {
+    let is_number = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if let Some(inner) = stem.strip_suffix(')') {
+        if let Some((base, number)) = inner.rsplit_once(" (") {
+            if is_number(number) && !base.is_empty() {
+                return Some(base);
+            }
+        }
+    }
+    if let Some(base) = stem.strip_suffix(" copy") {
+        return (!base.is_empty()).then_some(base);
+    }
+    let (rest, number) = stem.rsplit_once(' ')?;
+    let base = rest.strip_suffix(" copy")?;
+    (is_number(number) && !base.is_empty()).then_some(base)
+}
+
+fn parts(stem: &str) -> Vec<Part> {
+    let mut parts: Vec<Part> = Vec::new();
+    for c in stem.chars() {
+        match (c.is_ascii_digit(), parts.last_mut()) {
+            (true, Some(Part::Digits(len))) => *len += 1,
+            (true, _) => parts.push(Part::Digits(1)),
+            (false, Some(Part::Text(text))) => text.push(c),
+            (false, _) => parts.push(Part::Text(c.to_string())),
+        }
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(name: &str) -> NamePattern {
+        NamePattern::generalize(name).unwrap()
+    }
+
+    #[test]
+    fn test_name_pattern_digit_runs() {
+        let camera = pattern("IMG_1234.jpg");
+        assert_eq!(camera.to_string(), "IMG_####.jpg");
+        assert!(camera.matches("IMG_0001.JPG"));
+        assert!(!camera.matches("IMG_12345.jpg"));
+        assert!(!camera.matches("IMG_1234.png"));
+        assert!(!camera.matches("DSC_1234.jpg"));
+
+        let dated = pattern("Screenshot 2024-01-05 at 10.31.22.png");
+        assert_eq!(dated.to_string(), "Screenshot ####-##-## at ##.##.##.png");
+        assert!(dated.matches("Screenshot 2023-12-31 at 23.59.59.png"));
+    }
+
+    #[test]
+    fn test_name_pattern_copy_suffixes() {
+        let copies = pattern("report (2).pdf");
+        assert_eq!(copies.to_string(), "report (*).pdf");
+        assert!(copies.matches("report.pdf"));
+        assert!(copies.matches("report (13).pdf"));
+        assert!(copies.matches("report copy.pdf"));
+        assert!(copies.matches("report copy 3.pdf"));
+        assert!(!copies.matches("report final.pdf"));
+
+        assert_eq!(pattern("notes copy 2.txt").to_string(), "notes (*).txt");
+    }
+
+    #[test]
+    fn test_original_name() {
+        assert_eq!(
+            original_name("report (2).pdf").as_deref(),
+            Some("report.pdf")
+        );
+        assert_eq!(
+            original_name("notes copy 3.txt").as_deref(),
+            Some("notes.txt")
+        );
+        assert_eq!(original_name("Makefile copy").as_deref(), Some("Makefile"));
+        assert_eq!(original_name("report.pdf"), None);
+    }
+
+    #[test]
+    fn test_name_pattern_needs_something_to_generalize() {
+        assert_eq!(NamePattern::generalize("report.pdf"), None);
+        assert_eq!(NamePattern::generalize("copy.txt"), None);
+        assert_eq!(pattern(".env2").to_string(), ".env#");
+    }
+}
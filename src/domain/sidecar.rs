@@ -0,0 +1,76 @@
+use super::FileEntry;
+
+/// Extensions of files that only describe another file with the same stem: photo
+/// edits (`.xmp`, `.aae`), video thumbnails and proxies (`.thm`, `.lrv`), subtitles
+/// and GPS tracks
+const SIDECAR_EXTENSIONS: &[&str] = &[
+    "xmp", "aae", "thm", "lrv", "srt", "vtt", "ass", "ssa", "sub", "gpx",
+];
+
+/// Whether `file` is a sidecar kind of file
+pub fn is_sidecar(file: &FileEntry) -> bool {
+    file.path.extension().is_some_and(|ext| {
+        let ext = ext.to_string_lossy().to_lowercase();
+        SIDECAR_EXTENSIONS.contains(&ext.as_str())
+    })
+}
+
+/// Whether `sidecar` belongs to `file`: it sits in the same directory and is named
+/// after it, either by stem (`IMG_1.xmp` for `IMG_1.jpg`) or by full name
+/// (`IMG_1.jpg.xmp`). Names compare case-insensitively, and a sidecar has none of
+/// its own.
+pub fn is_sidecar_of(sidecar: &FileEntry, file: &FileEntry) -> bool {
+    if !is_sidecar(sidecar) || is_sidecar(file) || sidecar.path.parent() != file.path.parent() {
+        return false;
+    }
+    let (Some(name), Some(stem)) = (sidecar.path.file_stem(), file.path.file_stem()) else {
+        return false;
+    };
+    let name = name.to_string_lossy().to_lowercase();
+    name == stem.to_string_lossy().to_lowercase() || name == file.name.to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::FileType;
+    use chrono::Utc;
+    use std::path::PathBuf;
+
+    fn entry(path: &str) -> FileEntry {
+        let path = PathBuf::from(path);
+        FileEntry {
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            path,
+            size: 0,
+            modified_date: Utc::now(),
+            file_type: FileType::Binary,
+            disk: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_sidecar_pairs() {
+        let photo = entry("/pics/IMG_1.JPG");
+        assert!(is_sidecar_of(&entry("/pics/IMG_1.xmp"), &photo));
+        assert!(is_sidecar_of(&entry("/pics/img_1.XMP"), &photo));
+        assert!(is_sidecar_of(&entry("/pics/IMG_1.JPG.xmp"), &photo));
+        assert!(is_sidecar_of(
+            &entry("/clips/GX01.srt"),
+            &entry("/clips/GX01.mp4")
+        ));
+        assert!(is_sidecar_of(
+            &entry("/clips/GX01.gpx"),
+            &entry("/clips/GX01.mp4")
+        ));
+
+        // Other directories, other stems, non-sidecars and sidecars of sidecars
+        assert!(!is_sidecar_of(&entry("/other/IMG_1.xmp"), &photo));
+        assert!(!is_sidecar_of(&entry("/pics/IMG_10.xmp"), &photo));
+        assert!(!is_sidecar_of(&entry("/pics/IMG_1.png"), &photo));
+        assert!(!is_sidecar_of(
+            &entry("/clips/GX01.srt"),
+            &entry("/clips/GX01.vtt")
+        ));
+    }
+}
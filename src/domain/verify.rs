@@ -0,0 +1,134 @@
+use super::{ApplyOutcome, Decision, FileEntry};
+use chrono::{DateTime, Utc};
+use std::fs;
+
+/// Something unexpected found when re-checking files after apply
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Anomaly {
+    /// A trashed file exists at its original path again (recreated by another process?)
+    TrashedStillPresent,
+    /// A kept file (or one put back after a cancel) is missing
+    KeptMissing,
+    /// A kept file changed size or modification time during the session
+    KeptModified,
+}
+
+impl Anomaly {
+    /// Short explanation shown in the apply results
+    pub fn description(&self) -> &'static str {
+        match self {
+            Anomaly::TrashedStillPresent => "trashed, but a file exists at its path again",
+            Anomaly::KeptMissing => "kept, but no longer at its path",
+            Anomaly::KeptModified => "kept, but changed during the session",
+        }
+    }
+}
+
+/// Re-checks the original locations after apply.
+///
+/// Trashed files must be gone; kept files (and files put back after a cancel or a
+/// failed move) must still be there, with the size and modification time seen at
+/// discovery. Returns (file index, anomaly) pairs.
+pub fn verify_apply(
+    files: &[FileEntry],
+    decisions: &[(usize, Decision)],
+    results: &[(usize, ApplyOutcome)],
+) -> Vec<(usize, Anomaly)> {
+    let mut anomalies = Vec::new();
+
+    for (index, outcome) in results {
+        let Some(file) = files.get(*index) else {
+            continue;
+        };
+        let present = fs::symlink_metadata(&file.path).is_ok();
+        match outcome {
+            ApplyOutcome::Trashed if present => {
+                anomalies.push((*index, Anomaly::TrashedStillPresent));
+            }
+            ApplyOutcome::Restored if !present => {
+                anomalies.push((*index, Anomaly::KeptMissing));
+            }
+            _ => {}
+        }
+    }
+
+    for (index, decision) in decisions {
+        if *decision != Decision::Keep {
+            continue;
+        }
+        let Some(file) = files.get(*index) else {
+            continue;
+        };
+        match fs::metadata(&file.path) {
+            Err(_) => anomalies.push((*index, Anomaly::KeptMissing)),
+            Ok(metadata) => {
+                let modified: Option<DateTime<Utc>> = metadata.modified().ok().map(Into::into);
+                if metadata.len() != file.size || modified != Some(file.modified_date) {
+                    anomalies.push((*index, Anomaly::KeptModified));
+                }
+            }
+        }
+    }
+
+    anomalies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_verify_apply_clean_session() {
+        let dir = TempDir::new().unwrap();
+        let kept = dir.path().join("kept.txt");
+        fs::write(&kept, b"keep me").unwrap();
+        let entry = FileEntry::from_path(&kept).unwrap();
+        let mut gone = entry.clone();
+        gone.path = dir.path().join("gone.txt");
+        let files = vec![entry, gone];
+
+        let anomalies = verify_apply(
+            &files,
+            &[(0, Decision::Keep), (1, Decision::Trash)],
+            &[(1, ApplyOutcome::Trashed)],
+        );
+        assert!(anomalies.is_empty());
+    }
+
+    #[test]
+    fn test_verify_apply_reports_anomalies() {
+        let dir = TempDir::new().unwrap();
+        let kept = dir.path().join("kept.txt");
+        let recreated = dir.path().join("recreated.txt");
+        let missing = dir.path().join("missing.txt");
+        for path in [&kept, &recreated, &missing] {
+            fs::write(path, b"data").unwrap();
+        }
+        let files: Vec<FileEntry> = [&kept, &recreated, &missing]
+            .iter()
+            .map(|path| FileEntry::from_path(path).unwrap())
+            .collect();
+
+        fs::write(&kept, b"edited while reviewing").unwrap();
+        fs::remove_file(&missing).unwrap();
+
+        let anomalies = verify_apply(
+            &files,
+            &[
+                (0, Decision::Keep),
+                (1, Decision::Trash),
+                (2, Decision::Keep),
+            ],
+            &[(1, ApplyOutcome::Trashed)],
+        );
+        assert_eq!(
+            anomalies,
+            vec![
+                (1, Anomaly::TrashedStillPresent),
+                (0, Anomaly::KeptModified),
+                (2, Anomaly::KeptMissing),
+            ]
+        );
+    }
+}
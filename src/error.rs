@@ -28,6 +28,22 @@ pub enum FileTinderError {
 
     #[error("Failed to open file: {0}")]
     OpenFileError(String),
+
+    #[error("Failed to run shell: {0}")]
+    ShellError(String),
+
+    #[error("Invalid filter: {0}")]
+    InvalidFilter(String),
+
+    #[error(
+        "{what} uses schema version {version}, but this fswp reads up to version {newest}; \
+         a newer fswp wrote it"
+    )]
+    UnsupportedSchema {
+        what: &'static str,
+        version: u32,
+        newest: u32,
+    },
 }
 
 pub type Result<T> = std::result::Result<T, FileTinderError>;
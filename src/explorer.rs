@@ -0,0 +1,323 @@
+//! Module for the size explorer (`--explore`)
+//!
+//! Before deciding what goes, it helps to know where the space went. The
+//! explorer lists the subdirectories of one directory, biggest first, measured on
+//! a worker thread like `--dirs` items (`DirSizer`), next to a row for the files
+//! directly in it. Going into a subdirectory lists its own; going back stops at
+//! the directory fswp was started on. From any row a review can start: of the
+//! whole subtree for a subdirectory, of just those files for the loose-files row.
+//! Sizes measured once are kept while exploring, except for a subtree that was
+//! just reviewed (and the directories above it), which is measured again.
+
+use crate::dir_size::{DirSizer, SizeUpdate};
+use crate::domain::{DirectorySize, DirectoryStats};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// One row of the explorer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplorerRow {
+    pub path: PathBuf,
+    pub name: String,
+    pub size: DirectorySize,
+    /// The files directly in the directory rather than a subdirectory
+    pub loose: bool,
+}
+
+/// The size explorer's state
+#[derive(Debug)]
+pub struct Explorer {
+    /// The directory exploring started from; going back stops here
+    pub root: PathBuf,
+    /// The directory listed
+    pub current: PathBuf,
+    /// Subdirectories and the loose-files row, biggest first
+    pub rows: Vec<ExplorerRow>,
+    pub cursor: usize,
+    pub show_hidden: bool,
+    /// Shown under the list until the next key
+    pub notice: Option<String>,
+    /// Finished measurements, by directory
+    measured: HashMap<PathBuf, DirectoryStats>,
+    sizer: Option<DirSizer>,
+    /// Paths of the directories `sizer` measures, by its index
+    measuring: Vec<PathBuf>,
+}
+
+impl Explorer {
+    /// Lists `root` and starts measuring its subdirectories
+    pub fn new(root: &Path, show_hidden: bool) -> io::Result<Self> {
+        let mut explorer = Self {
+            root: root.to_path_buf(),
+            current: root.to_path_buf(),
+            rows: Vec::new(),
+            cursor: 0,
+            show_hidden,
+            notice: None,
+            measured: HashMap::new(),
+            sizer: None,
+            measuring: Vec::new(),
+        };
+        explorer.open(root.to_path_buf())?;
+        Ok(explorer)
+    }
+
+    /// Lists `dir`: a row per subdirectory (symlinks aren't followed) and one for
+    /// its files, measuring the subdirectories not measured before
+    fn open(&mut self, dir: PathBuf) -> io::Result<()> {
+        let mut rows = Vec::new();
+        let mut loose = DirectoryStats::default();
+        for entry in fs::read_dir(&dir)?.flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !self.show_hidden && name.starts_with('.') {
+                continue;
+            }
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                let path = entry.path();
+                let size = match self.measured.get(&path) {
+                    Some(&stats) => DirectorySize { stats, done: true },
+                    None => DirectorySize {
+                        stats: DirectoryStats::default(),
+                        done: false,
+                    },
+                };
+                rows.push(ExplorerRow {
+                    path,
+                    name,
+                    size,
+                    loose: false,
+                });
+            } else {
+                loose.files += 1;
+                loose.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+            }
+        }
+        if loose.files > 0 {
+            rows.push(ExplorerRow {
+                path: dir.clone(),
+                name: "(files here)".to_string(),
+                size: DirectorySize {
+                    stats: loose,
+                    done: true,
+                },
+                loose: true,
+            });
+        }
+
+        self.measuring = rows
+            .iter()
+            .filter(|row| !row.size.done)
+            .map(|row| row.path.clone())
+            .collect();
+        // Dropping the previous sizer stops its walk
+        self.sizer = (!self.measuring.is_empty())
+            .then(|| DirSizer::start(self.measuring.iter().cloned().enumerate().collect()));
+        self.current = dir;
+        self.rows = rows;
+        self.cursor = 0;
+        self.sort();
+        Ok(())
+    }
+
+    /// Takes in the sizes measured since the last poll; false when nothing changed
+    pub fn poll(&mut self) -> bool {
+        let Some(ref mut sizer) = self.sizer else {
+            return false;
+        };
+        let updates: Vec<SizeUpdate> = sizer.poll();
+        if sizer.is_finished() {
+            self.sizer = None;
+        }
+        for &(index, stats, done) in &updates {
+            let Some(path) = self.measuring.get(index) else {
+                continue;
+            };
+            if done {
+                self.measured.insert(path.clone(), stats);
+            }
+            if let Some(row) = self
+                .rows
+                .iter_mut()
+                .find(|row| &row.path == path && !row.loose)
+            {
+                row.size = DirectorySize { stats, done };
+            }
+        }
+        if !updates.is_empty() {
+            self.sort();
+        }
+        !updates.is_empty()
+    }
+
+    /// Whether some subdirectory is still being measured
+    pub fn is_measuring(&self) -> bool {
+        self.sizer.is_some()
+    }
+
+    /// Biggest first, then by name, keeping the cursor on the same row
+    fn sort(&mut self) {
+        let selected = self.selected().map(|row| (row.path.clone(), row.loose));
+        self.rows.sort_by(|a, b| {
+            b.size
+                .stats
+                .bytes
+                .cmp(&a.size.stats.bytes)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        if let Some((path, loose)) = selected {
+            self.cursor = self
+                .rows
+                .iter()
+                .position(|row| row.path == path && row.loose == loose)
+                .unwrap_or(0);
+        }
+    }
+
+    pub fn selected(&self) -> Option<&ExplorerRow> {
+        self.rows.get(self.cursor)
+    }
+
+    /// Bytes in every row together, so far
+    pub fn total(&self) -> DirectoryStats {
+        self.rows
+            .iter()
+            .fold(DirectoryStats::default(), |total, row| DirectoryStats {
+                files: total.files + row.size.stats.files,
+                directories: total.directories + row.size.stats.directories + u64::from(!row.loose),
+                bytes: total.bytes + row.size.stats.bytes,
+            })
+    }
+
+    pub fn next(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.rows.len().saturating_sub(1));
+    }
+
+    pub fn previous(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// Goes into the selected subdirectory
+    pub fn enter(&mut self) -> io::Result<()> {
+        match self.selected() {
+            Some(row) if !row.loose => {
+                let path = row.path.clone();
+                self.open(path)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Goes back to the parent directory, with the cursor on the one just left;
+    /// false at the directory exploring started from
+    pub fn back(&mut self) -> io::Result<bool> {
+        if self.current == self.root {
+            return Ok(false);
+        }
+        let Some(parent) = self.current.parent().map(Path::to_path_buf) else {
+            return Ok(false);
+        };
+        let left = self.current.clone();
+        self.open(parent)?;
+        if let Some(position) = self.rows.iter().position(|row| row.path == left) {
+            self.cursor = position;
+        }
+        Ok(true)
+    }
+
+    /// What a review of the selected row covers: the directory, and whether its
+    /// subdirectories are included (not for the loose-files row)
+    pub fn review_target(&self) -> Option<(PathBuf, bool)> {
+        self.selected().map(|row| (row.path.clone(), !row.loose))
+    }
+
+    /// Forgets the sizes a review of `reviewed` may have changed (it, what's under
+    /// it and the directories above it) and lists the current directory again
+    pub fn refresh_after_review(&mut self, reviewed: &Path) -> io::Result<()> {
+        self.measured
+            .retain(|path, _| !path.starts_with(reviewed) && !reviewed.starts_with(path));
+        let selected = self.selected().map(|row| (row.path.clone(), row.loose));
+        self.open(self.current.clone())?;
+        if let Some((path, loose)) = selected {
+            if let Some(position) = self
+                .rows
+                .iter()
+                .position(|row| row.path == path && row.loose == loose)
+            {
+                self.cursor = position;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    fn measure(explorer: &mut Explorer) {
+        let started = Instant::now();
+        while explorer.is_measuring() {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            explorer.poll();
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn names(explorer: &Explorer) -> Vec<&str> {
+        explorer.rows.iter().map(|row| row.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_explorer_lists_biggest_first_and_navigates() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("videos/old")).unwrap();
+        fs::write(root.join("videos/old/a.mp4"), vec![0; 5000]).unwrap();
+        fs::write(root.join("videos/b.mp4"), vec![0; 1000]).unwrap();
+        fs::create_dir(root.join("docs")).unwrap();
+        fs::write(root.join("docs/notes.txt"), vec![0; 300]).unwrap();
+        fs::create_dir(root.join(".cache")).unwrap();
+        fs::write(root.join(".cache/blob"), vec![0; 9000]).unwrap();
+        fs::write(root.join("loose.iso"), vec![0; 2000]).unwrap();
+
+        let mut explorer = Explorer::new(root, false).unwrap();
+        measure(&mut explorer);
+        assert_eq!(names(&explorer), ["videos", "(files here)", "docs"]);
+        assert_eq!(explorer.rows[0].size.stats.bytes, 6000);
+        assert_eq!(explorer.total().bytes, 8300);
+        assert_eq!(explorer.review_target(), Some((root.join("videos"), true)));
+        explorer.next();
+        assert_eq!(explorer.review_target(), Some((root.to_path_buf(), false)));
+
+        // Into videos and back: the cursor lands on the directory left
+        explorer.previous();
+        explorer.enter().unwrap();
+        measure(&mut explorer);
+        assert_eq!(explorer.current, root.join("videos"));
+        assert_eq!(names(&explorer), ["old", "(files here)"]);
+        assert!(explorer.back().unwrap());
+        assert_eq!(explorer.selected().unwrap().name, "videos");
+        assert!(!explorer.is_measuring(), "sizes measured before are kept");
+        assert!(!explorer.back().unwrap());
+
+        // After a review of videos/old took a.mp4, videos is measured again
+        fs::remove_file(root.join("videos/old/a.mp4")).unwrap();
+        explorer
+            .refresh_after_review(&root.join("videos/old"))
+            .unwrap();
+        measure(&mut explorer);
+        assert_eq!(names(&explorer), ["(files here)", "videos", "docs"]);
+        assert_eq!(explorer.selected().unwrap().name, "videos");
+
+        let with_hidden = Explorer::new(root, true).unwrap();
+        assert!(with_hidden.rows.iter().any(|row| row.name == ".cache"));
+    }
+}
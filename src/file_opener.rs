@@ -3,6 +3,7 @@
 use crate::error::{FileTinderError, Result};
 use std::env;
 use std::path::Path;
+use std::process::Command;
 
 /// Opens a file in the user's preferred editor or default application.
 ///
@@ -33,6 +34,33 @@ pub fn open_file<P: AsRef<Path>>(path: P) -> Result<()> {
     Ok(())
 }
 
+/// Returns the shell to launch for `!`: `$SHELL`, falling back to the platform default
+pub fn shell_command() -> String {
+    env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.trim().is_empty())
+        .unwrap_or_else(|| {
+            if cfg!(windows) {
+                "cmd".to_string()
+            } else {
+                "/bin/sh".to_string()
+            }
+        })
+}
+
+/// Runs an interactive subshell with `dir` as its working directory.
+///
+/// This function blocks until the shell exits. A non-zero exit status is not
+/// treated as an error, since it usually just reflects the last command run.
+pub fn run_shell<P: AsRef<Path>>(dir: P) -> Result<()> {
+    let shell = shell_command();
+    Command::new(&shell)
+        .current_dir(dir.as_ref())
+        .status()
+        .map_err(|e| FileTinderError::ShellError(format!("{}: {}", shell, e)))?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -54,4 +82,15 @@ mod tests {
         // Just verify path exists (actual opening depends on environment)
         assert!(path.exists());
     }
+
+    #[test]
+    fn test_run_shell_with_nonexistent_directory() {
+        let result = run_shell("/nonexistent/directory/12345");
+        assert!(matches!(result, Err(FileTinderError::ShellError(_))));
+    }
+
+    #[test]
+    fn test_shell_command_not_empty() {
+        assert!(!shell_command().is_empty());
+    }
 }
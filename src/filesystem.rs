@@ -0,0 +1,386 @@
+//! Module for detecting network and FUSE filesystems
+//!
+//! Sessions over NFS/SMB/sshfs mounts pay a round trip for every read, so the app
+//! switches to a degraded mode there: smaller preview reads and longer timeouts.
+//!
+//! Also detects copy-on-write clones, whose shared extents aren't freed by trashing one copy,
+//! and whether a local disk spins, which decides how many files are read at once.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// Filesystem types that are remote (or FUSE-backed and usually remote)
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb",
+    "smbfs",
+    "smb2",
+    "smb3",
+    "afpfs",
+    "afs",
+    "webdav",
+    "davfs",
+    "ncpfs",
+    "9p",
+    "ceph",
+    "glusterfs",
+    "sshfs",
+    "fuse",
+    "macfuse",
+    "osxfuse",
+];
+
+/// Describes the filesystem a directory lives on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilesystemInfo {
+    /// Filesystem type as reported by the OS (e.g. "nfs4", "fuse.sshfs")
+    pub fs_type: String,
+}
+
+impl FilesystemInfo {
+    /// Whether reads go over the network (or through a FUSE daemon)
+    pub fn is_network(&self) -> bool {
+        is_network_fs_type(&self.fs_type)
+    }
+}
+
+/// What a directory is stored on, as far as reading many files goes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageKind {
+    /// Solid state: reads in parallel finish sooner
+    Ssd,
+    /// Spinning disk: parallel reads make the head seek back and forth
+    Hdd,
+    /// A network or FUSE mount: reads wait on round trips, so a few overlap
+    Network,
+}
+
+impl StorageKind {
+    /// Files to read at once on this storage
+    pub fn workers(&self) -> usize {
+        match self {
+            StorageKind::Ssd => std::thread::available_parallelism().map_or(1, |n| n.get().min(8)),
+            StorageKind::Hdd => 1,
+            StorageKind::Network => 4,
+        }
+    }
+}
+
+impl fmt::Display for StorageKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StorageKind::Ssd => "SSD",
+            StorageKind::Hdd => "HDD",
+            StorageKind::Network => "network",
+        })
+    }
+}
+
+/// Detects what `path` is stored on, or None when it can't be told (e.g. btrfs
+/// and ZFS, whose files don't map to one block device, or anything but Linux
+/// for local disks)
+pub fn detect_storage(path: &Path) -> Option<StorageKind> {
+    if detect(path).is_some_and(|fs| fs.is_network()) {
+        return Some(StorageKind::Network);
+    }
+    rotational(path).map(|spins| {
+        if spins {
+            StorageKind::Hdd
+        } else {
+            StorageKind::Ssd
+        }
+    })
+}
+
+/// Whether the block device holding `path` spins, from `/sys/dev/block/<major>:<minor>`
+#[cfg(target_os = "linux")]
+fn rotational(path: &Path) -> Option<bool> {
+    use std::os::unix::fs::MetadataExt;
+
+    let dev = std::fs::metadata(path).ok()?.dev();
+    let block =
+        Path::new("/sys/dev/block").join(format!("{}:{}", libc::major(dev), libc::minor(dev)));
+    rotational_in(&block.canonicalize().ok()?)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn rotational(_path: &Path) -> Option<bool> {
+    None
+}
+
+/// Reads `queue/rotational` of a sysfs block device, or of the disk a partition
+/// belongs to
+#[cfg(any(target_os = "linux", test))]
+fn rotational_in(block: &Path) -> Option<bool> {
+    [Some(block), block.parent()]
+        .into_iter()
+        .flatten()
+        .find_map(|dir| std::fs::read_to_string(dir.join("queue/rotational")).ok())
+        .map(|flag| flag.trim() == "1")
+}
+
+/// Returns true for NFS/SMB/FUSE-style filesystem type names.
+///
+/// `fuseblk` is excluded since it backs local disks (e.g. ntfs-3g).
+pub fn is_network_fs_type(fs_type: &str) -> bool {
+    let fs_type = fs_type.to_lowercase();
+    if fs_type == "fuseblk" {
+        return false;
+    }
+    // FUSE mounts are reported as "fuse.<daemon>" on Linux
+    let base = fs_type.split('.').next().unwrap_or(&fs_type);
+    NETWORK_FS_TYPES.contains(&base) || NETWORK_FS_TYPES.contains(&fs_type.as_str())
+}
+
+/// Detects the filesystem `path` lives on, or None when it can't be determined
+#[cfg(target_os = "linux")]
+pub fn detect(path: &Path) -> Option<FilesystemInfo> {
+    let path = path.canonicalize().ok()?;
+    let mounts = std::fs::read_to_string("/proc/self/mounts").ok()?;
+    fs_type_for_path(&mounts, &path).map(|fs_type| FilesystemInfo { fs_type })
+}
+
+/// Detects the filesystem `path` lives on, or None when it can't be determined
+#[cfg(target_os = "macos")]
+pub fn detect(path: &Path) -> Option<FilesystemInfo> {
+    use std::ffi::{CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+    // SAFETY: statfs is plain old data and only written by the call below
+    let mut stats: libc::statfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statfs(c_path.as_ptr(), &mut stats) } != 0 {
+        return None;
+    }
+    // SAFETY: f_fstypename is a NUL-terminated array filled in by statfs
+    let name = unsafe { CStr::from_ptr(stats.f_fstypename.as_ptr()) };
+    Some(FilesystemInfo {
+        fs_type: name.to_string_lossy().into_owned(),
+    })
+}
+
+/// Detects the filesystem `path` lives on, or None when it can't be determined
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+pub fn detect(_path: &Path) -> Option<FilesystemInfo> {
+    None
+}
+
+/// Bytes of `path` stored in extents shared with other files (copy-on-write clones).
+///
+/// Uses the FIEMAP ioctl, which reports shared extents on btrfs, XFS (reflink) and
+/// bcachefs. Returns None when the filesystem doesn't support it.
+#[cfg(target_os = "linux")]
+pub fn shared_bytes(path: &Path) -> Option<u64> {
+    use std::os::unix::io::AsRawFd;
+
+    const FS_IOC_FIEMAP: libc::c_ulong = 0xC020_660B;
+    const FIEMAP_FLAG_SYNC: u32 = 0x1;
+    const FIEMAP_EXTENT_LAST: u32 = 0x1;
+    const FIEMAP_EXTENT_SHARED: u32 = 0x2000;
+    const BATCH: usize = 64;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Default)]
+    struct FiemapExtent {
+        fe_logical: u64,
+        fe_physical: u64,
+        fe_length: u64,
+        fe_reserved64: [u64; 2],
+        fe_flags: u32,
+        fe_reserved: [u32; 3],
+    }
+
+    #[repr(C)]
+    struct Fiemap {
+        fm_start: u64,
+        fm_length: u64,
+        fm_flags: u32,
+        fm_mapped_extents: u32,
+        fm_extent_count: u32,
+        fm_reserved: u32,
+        fm_extents: [FiemapExtent; BATCH],
+    }
+
+    let file = std::fs::File::open(path).ok()?;
+    let mut shared = 0;
+    let mut start = 0;
+    loop {
+        let mut map = Fiemap {
+            fm_start: start,
+            fm_length: u64::MAX - start,
+            fm_flags: FIEMAP_FLAG_SYNC,
+            fm_mapped_extents: 0,
+            fm_extent_count: BATCH as u32,
+            fm_reserved: 0,
+            fm_extents: [FiemapExtent::default(); BATCH],
+        };
+        // SAFETY: map is a correctly laid out fiemap with room for BATCH extents
+        let rc = unsafe { libc::ioctl(file.as_raw_fd(), FS_IOC_FIEMAP as _, &mut map) };
+        if rc != 0 {
+            return None;
+        }
+        let mapped = (map.fm_mapped_extents as usize).min(BATCH);
+        if mapped == 0 {
+            return Some(shared);
+        }
+        for extent in &map.fm_extents[..mapped] {
+            if extent.fe_flags & FIEMAP_EXTENT_SHARED != 0 {
+                shared += extent.fe_length;
+            }
+            if extent.fe_flags & FIEMAP_EXTENT_LAST != 0 {
+                return Some(shared);
+            }
+        }
+        let last = map.fm_extents[mapped - 1];
+        start = last.fe_logical + last.fe_length;
+    }
+}
+
+/// Bytes of `path` stored in extents shared with other files (copy-on-write clones).
+///
+/// Not available on this platform (APFS has no public API for clone extents).
+#[cfg(not(target_os = "linux"))]
+pub fn shared_bytes(_path: &Path) -> Option<u64> {
+    None
+}
+
+/// Finds the type of the most specific mount containing `path` in a mounts table
+#[cfg(any(target_os = "linux", test))]
+fn fs_type_for_path(mounts: &str, path: &Path) -> Option<String> {
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let _device = fields.next()?;
+            let mount_point = unescape_mount_field(fields.next()?);
+            let fs_type = fields.next()?;
+            Some((mount_point, fs_type))
+        })
+        .filter(|(mount_point, _)| path.starts_with(mount_point))
+        .max_by_key(|(mount_point, _)| mount_point.len())
+        .map(|(_, fs_type)| fs_type.to_string())
+}
+
+/// Decodes the octal escapes (`\040` for space, etc.) used in /proc/mounts
+#[cfg(any(target_os = "linux", test))]
+fn unescape_mount_field(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'\\' && i + 3 < bytes.len() {
+            let digits = std::str::from_utf8(&bytes[i + 1..i + 4]).unwrap_or("");
+            if let Ok(value) = u8::from_str_radix(digits, 8) {
+                out.push(value);
+                i += 4;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MOUNTS: &str = "\
+/dev/sda1 / ext4 rw,relatime 0 0
+server:/export /mnt/share nfs4 rw,relatime 0 0
+me@host:/home /mnt/share/ssh fuse.sshfs rw,nosuid 0 0
+/dev/sdb1 /media/usb\\040drive fuseblk rw 0 0
+";
+
+    #[test]
+    fn test_is_network_fs_type() {
+        assert!(is_network_fs_type("nfs4"));
+        assert!(is_network_fs_type("cifs"));
+        assert!(is_network_fs_type("fuse.sshfs"));
+        assert!(is_network_fs_type("smbfs"));
+        assert!(!is_network_fs_type("fuseblk"));
+        assert!(!is_network_fs_type("ext4"));
+        assert!(!is_network_fs_type("apfs"));
+    }
+
+    #[test]
+    fn test_fs_type_for_path_picks_longest_mount() {
+        assert_eq!(
+            fs_type_for_path(MOUNTS, Path::new("/mnt/share/ssh/docs")),
+            Some("fuse.sshfs".to_string())
+        );
+        assert_eq!(
+            fs_type_for_path(MOUNTS, Path::new("/mnt/share/photos")),
+            Some("nfs4".to_string())
+        );
+        assert_eq!(
+            fs_type_for_path(MOUNTS, Path::new("/home/me")),
+            Some("ext4".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fs_type_for_path_unescapes_mount_points() {
+        assert_eq!(
+            fs_type_for_path(MOUNTS, Path::new("/media/usb drive/file")),
+            Some("fuseblk".to_string())
+        );
+    }
+
+    #[test]
+    fn test_fs_type_for_path_matches_whole_components() {
+        // "/mnt/shared" is not inside "/mnt/share"
+        assert_eq!(
+            fs_type_for_path(MOUNTS, Path::new("/mnt/shared")),
+            Some("ext4".to_string())
+        );
+    }
+
+    #[test]
+    fn test_shared_bytes_plain_file_shares_nothing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("plain.txt");
+        std::fs::write(&path, vec![b'x'; 64 * 1024]).unwrap();
+
+        // Filesystems without FIEMAP (e.g. tmpfs) return None; a fresh file never shares
+        if let Some(shared) = shared_bytes(&path) {
+            assert_eq!(shared, 0);
+        }
+    }
+
+    #[test]
+    fn test_shared_bytes_missing_file() {
+        assert_eq!(shared_bytes(Path::new("/nonexistent/file")), None);
+    }
+
+    #[test]
+    fn test_detect_current_dir_does_not_panic() {
+        let _ = detect(Path::new("."));
+        let _ = detect_storage(Path::new("."));
+    }
+
+    #[test]
+    fn test_rotational_in_disk_or_its_partition() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let disk = dir.path().join("sda");
+        let partition = disk.join("sda1");
+        std::fs::create_dir_all(disk.join("queue")).unwrap();
+        std::fs::create_dir_all(&partition).unwrap();
+        std::fs::write(disk.join("queue/rotational"), "1\n").unwrap();
+
+        assert_eq!(rotational_in(&disk), Some(true));
+        assert_eq!(rotational_in(&partition), Some(true));
+
+        let nvme = dir.path().join("nvme0n1");
+        std::fs::create_dir_all(nvme.join("queue")).unwrap();
+        std::fs::write(nvme.join("queue/rotational"), "0\n").unwrap();
+        assert_eq!(rotational_in(&nvme), Some(false));
+        assert_eq!(rotational_in(&dir.path().join("missing")), None);
+        assert_eq!(StorageKind::Hdd.workers(), 1);
+    }
+}
@@ -0,0 +1,167 @@
+//! Module for content hashes
+//!
+//! Hashes are written where a later check has to recompute them: the links of a
+//! `--audit-chain` journal and the contents of trashed files in an exported
+//! report. Each place records which algorithm it used, so changing
+//! `hash_algorithm` in the config never breaks checking older journals. BLAKE3 is
+//! the default; XXH3-128 is faster but not cryptographic, and SHA-256 is there
+//! for audits that call for it.
+
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+/// Bytes hashed per read of a file
+const HASH_CHUNK: usize = 64 * 1024;
+
+/// Which hash function to use
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    #[default]
+    Blake3,
+    /// XXH3 with a 128-bit result; fast, but easy to collide on purpose
+    Xxhash128,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HashAlgorithm::Blake3 => "blake3",
+            HashAlgorithm::Xxhash128 => "xxhash128",
+            HashAlgorithm::Sha256 => "sha256",
+        }
+    }
+
+    /// Lowercase hex hash of `data`
+    pub fn hash_bytes(&self, data: &[u8]) -> String {
+        let mut hasher = Hasher::new(*self);
+        hasher.update(data);
+        hasher.finish()
+    }
+
+    /// Lowercase hex hash of the file at `path`, read a chunk at a time at the
+    /// configured read rate
+    pub fn hash_file(&self, path: &Path) -> io::Result<String> {
+        let _permit = crate::limits::open_files(1);
+        let mut file = File::open(path)?;
+        let mut hasher = Hasher::new(*self);
+        let mut buf = vec![0; HASH_CHUNK];
+        loop {
+            crate::limits::throttle(HASH_CHUNK as u64);
+            let read = file.read(&mut buf)?;
+            if read == 0 {
+                return Ok(hasher.finish());
+            }
+            hasher.update(&buf[..read]);
+        }
+    }
+}
+
+impl fmt::Display for HashAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A hash being computed over data fed in pieces
+enum Hasher {
+    Blake3(Box<blake3::Hasher>),
+    Xxhash128(Box<xxhash_rust::xxh3::Xxh3>),
+    Sha256(Box<sha2::Sha256>),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::default()),
+            HashAlgorithm::Xxhash128 => Hasher::Xxhash128(Box::default()),
+            HashAlgorithm::Sha256 => Hasher::Sha256(Box::default()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            Hasher::Xxhash128(hasher) => hasher.update(data),
+            Hasher::Sha256(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finish(self) -> String {
+        match self {
+            Hasher::Blake3(hasher) => hasher.finalize().to_hex().to_string(),
+            Hasher::Xxhash128(hasher) => format!("{:032x}", hasher.digest128()),
+            Hasher::Sha256(hasher) => format!("{:x}", hasher.finalize()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_known_vectors() {
+        let cases = [
+            (
+                HashAlgorithm::Sha256,
+                &b""[..],
+                "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            ),
+            (
+                HashAlgorithm::Sha256,
+                b"abc",
+                "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            ),
+            (
+                HashAlgorithm::Sha256,
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+                "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+            ),
+            (
+                HashAlgorithm::Blake3,
+                b"",
+                "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            ),
+            (
+                HashAlgorithm::Xxhash128,
+                b"",
+                "99aa06d3014798d86001c324468d497f",
+            ),
+        ];
+        for (algorithm, data, expected) in cases {
+            assert_eq!(algorithm.hash_bytes(data), expected, "{}", algorithm);
+        }
+    }
+
+    #[test]
+    fn test_hash_file_matches_hash_bytes_across_chunks() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("big.bin");
+        let data: Vec<u8> = (0..HASH_CHUNK * 2 + 100).map(|i| (i % 251) as u8).collect();
+        std::fs::write(&path, &data).unwrap();
+
+        for algorithm in [
+            HashAlgorithm::Blake3,
+            HashAlgorithm::Xxhash128,
+            HashAlgorithm::Sha256,
+        ] {
+            assert_eq!(
+                algorithm.hash_file(&path).unwrap(),
+                algorithm.hash_bytes(&data)
+            );
+        }
+        assert_eq!(
+            serde_json::from_value::<HashAlgorithm>("xxhash128".into()).unwrap(),
+            HashAlgorithm::Xxhash128
+        );
+    }
+}
@@ -0,0 +1,534 @@
+//! Module for the per-session decision journal
+//!
+//! Every keep, trash and undo is appended as one JSON line to
+//! `<data dir>/fswp/sessions/<session>.jsonl` as it happens, so a session can be
+//! audited (or replayed with `fswp replay <session>`) after the fact. A trashed
+//! file gets a second, `applied` line once it really reaches the system trash;
+//! one whose apply was cancelled or failed never does.
+//!
+//! The first line is a session header recording who ran the session, where, with
+//! which version, and an optional `--annotate` label (e.g. a ticket number).
+//!
+//! With `--audit-chain` every record also carries the hash of the line before
+//! it, so editing or removing a line breaks the chain; `fswp audit <session>`
+//! checks it with `verify_chain`. The header names the hash algorithm (the
+//! config's `hash_algorithm`), and journals from before it did are SHA-256.
+//! Lines cut off the end of the file can't be detected this way.
+//!
+//! The header records the journal's `schema_version` (see `schema`); journals
+//! without one are version 0, and `load` refuses one from a newer fswp.
+
+use crate::domain::{Decision, DecisionListener, FileEntry};
+use crate::error::{FileTinderError, Result};
+use crate::hashing::HashAlgorithm;
+use crate::schema::{self, JOURNAL_VERSION};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+/// What happened to a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JournalAction {
+    Keep,
+    Trash,
+    /// Moved into a quick-move target; the record's `destination` says where
+    Move,
+    /// Skipped for later
+    Skip,
+    /// The file's previous decision was undone
+    Undo,
+    /// The file of a trash decision reached the system trash
+    Applied,
+}
+
+/// One line of the journal
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalRecord {
+    /// Unix timestamp (seconds)
+    pub time: i64,
+    pub action: JournalAction,
+    pub path: PathBuf,
+    pub size: u64,
+    /// Target directory of a move
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination: Option<PathBuf>,
+    /// Hex hash of the previous line, in a chained journal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prev: Option<String>,
+}
+
+/// Who ran a session and where, written as the journal's first line
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub user: String,
+    pub host: String,
+    pub version: String,
+    /// Free-form label from `--annotate`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<String>,
+    /// Unix timestamp (seconds)
+    pub started: i64,
+    /// Records carry the previous line's hash (`--audit-chain`)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub chained: bool,
+    /// Algorithm of a chained journal's hashes; SHA-256 when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<HashAlgorithm>,
+    /// A `--dry-run` session: its trash decisions never reached the trash
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub dry_run: bool,
+}
+
+impl SessionInfo {
+    /// Describes the current process
+    pub fn current(annotation: Option<String>) -> Self {
+        Self {
+            user: crate::permissions::current_user(),
+            host: hostname(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            annotation,
+            started: Utc::now().timestamp(),
+            chained: false,
+            hash: None,
+            dry_run: false,
+        }
+    }
+
+    /// Algorithm linking the records when the journal is chained
+    pub fn chain_algorithm(&self) -> HashAlgorithm {
+        self.hash.unwrap_or(HashAlgorithm::Sha256)
+    }
+
+    /// One-line attribution, e.g. "alice@build-01 · fswp 0.1.0 · ticket-1234"
+    pub fn describe(&self) -> String {
+        let mut text = format!("{}@{} · fswp {}", self.user, self.host, self.version);
+        if let Some(ref annotation) = self.annotation {
+            text.push_str(" · ");
+            text.push_str(annotation);
+        }
+        text
+    }
+}
+
+/// Journal header line, kept distinct from records by its `session` key
+#[derive(Serialize, Deserialize)]
+struct Header {
+    /// `schema::JOURNAL_VERSION` when written; 0 for headers from before it was
+    #[serde(default)]
+    schema_version: u32,
+    session: SessionInfo,
+}
+
+/// A journal read back from disk
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionLog {
+    /// None for journals written before headers were recorded
+    pub info: Option<SessionInfo>,
+    pub records: Vec<JournalRecord>,
+}
+
+impl SessionLog {
+    /// Whether the session was a `--dry-run`, whose trash decisions trashed nothing
+    pub fn is_dry_run(&self) -> bool {
+        self.info.as_ref().is_some_and(|info| info.dry_run)
+    }
+}
+
+/// Appends decision records to a session journal; attach with `DecisionEngine::add_listener`
+#[derive(Debug)]
+pub struct Journal {
+    path: PathBuf,
+    file: File,
+    /// Hash of the last line written, when the journal is chained
+    last_hash: Option<String>,
+    algorithm: HashAlgorithm,
+}
+
+impl Journal {
+    /// Creates a new journal in the sessions directory, named after the current time
+    pub fn create(info: &SessionInfo) -> Result<Self> {
+        let dir = sessions_dir().ok_or_else(|| {
+            FileTinderError::ConfigError("Could not determine data directory".to_string())
+        })?;
+        fs::create_dir_all(&dir)?;
+        let name = Utc::now().format("%Y%m%d-%H%M%S").to_string();
+        Self::create_at(&dir.join(format!("{}.jsonl", name)), info)
+    }
+
+    /// Creates (or appends to) a journal at `path`, starting with a session header.
+    /// A chained header starts a new chain, even when appending.
+    pub fn create_at(path: &Path, info: &SessionInfo) -> Result<Self> {
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        let header = serde_json::to_string(&Header {
+            schema_version: JOURNAL_VERSION,
+            session: info.clone(),
+        })
+        .map_err(|e| FileTinderError::ConfigError(e.to_string()))?;
+        writeln!(file, "{}", header)?;
+        let algorithm = info.chain_algorithm();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            last_hash: info
+                .chained
+                .then(|| algorithm.hash_bytes(header.as_bytes())),
+            algorithm,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn append(&mut self, action: JournalAction, file: &FileEntry, destination: Option<&Path>) {
+        let record = JournalRecord {
+            time: Utc::now().timestamp(),
+            action,
+            path: file.path.clone(),
+            size: file.size,
+            destination: destination.map(Path::to_path_buf),
+            prev: self.last_hash.clone(),
+        };
+        // Journaling must never interrupt a session, so write errors are dropped
+        if let Ok(line) = serde_json::to_string(&record) {
+            let _ = writeln!(self.file, "{}", line);
+            if self.last_hash.is_some() {
+                self.last_hash = Some(self.algorithm.hash_bytes(line.as_bytes()));
+            }
+        }
+    }
+}
+
+impl DecisionListener for Journal {
+    fn on_decision(&mut self, _index: usize, file: &FileEntry, decision: &Decision) {
+        let (action, destination) = match decision {
+            Decision::Keep => (JournalAction::Keep, None),
+            Decision::Trash => (JournalAction::Trash, None),
+            Decision::Move(dir) => (JournalAction::Move, Some(dir.as_path())),
+            Decision::Skip => (JournalAction::Skip, None),
+        };
+        self.append(action, file, destination);
+    }
+
+    fn on_undo(&mut self, _index: usize, file: &FileEntry, _decision: &Decision) {
+        self.append(JournalAction::Undo, file, None);
+    }
+
+    fn on_applied(&mut self, _index: usize, file: &FileEntry) {
+        self.append(JournalAction::Applied, file, None);
+    }
+}
+
+/// Directory holding session journals (`<data dir>/fswp/sessions`)
+pub fn sessions_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| crate::profile::scoped(dir.join("fswp")).join("sessions"))
+}
+
+/// Finds a journal by path, or by session name in the sessions directory
+pub fn resolve_session(session: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(session);
+    if path.is_file() {
+        return Some(path);
+    }
+    let dir = sessions_dir()?;
+    [format!("{}.jsonl", session), session.to_string()]
+        .into_iter()
+        .map(|name| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Reads a journal's header and records, skipping lines that don't parse; refuses
+/// a journal written by a newer fswp
+pub fn load(path: &Path) -> Result<SessionLog> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut log = SessionLog::default();
+    for line in reader.lines() {
+        let line = line?;
+        if let Ok(record) = serde_json::from_str(&line) {
+            log.records.push(record);
+        } else if let Ok(header) = serde_json::from_str::<Header>(&line) {
+            schema::check("Session journal", header.schema_version, JOURNAL_VERSION)?;
+            log.info.get_or_insert(header.session);
+        }
+    }
+    Ok(log)
+}
+
+/// Outcome of checking a journal's hash chain
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChainCheck {
+    /// The journal wasn't written with `--audit-chain`
+    Unchained,
+    /// Every record links to the line before it
+    Intact {
+        records: usize,
+        algorithm: HashAlgorithm,
+    },
+    /// This line (1-based) doesn't parse or doesn't match the line before it
+    Broken { line: usize },
+}
+
+/// Checks that each record of a chained journal carries the hash of the line
+/// before it. A header starts the chain again (journals can be appended to).
+pub fn verify_chain(path: &Path) -> Result<ChainCheck> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut last_hash: Option<String> = None;
+    let mut chained = false;
+    let mut algorithm = HashAlgorithm::Sha256;
+    let mut records = 0;
+    for (number, line) in reader.lines().enumerate() {
+        let line = line?;
+        let header = serde_json::from_str::<Header>(&line).ok();
+        // A header is hashed with the algorithm it names, as it was written
+        if let Some(ref header) = header {
+            algorithm = header.session.chain_algorithm();
+        }
+        let hash = algorithm.hash_bytes(line.as_bytes());
+        if let Some(header) = header {
+            if number > 0 && chained != header.session.chained {
+                return Ok(ChainCheck::Broken { line: number + 1 });
+            }
+            chained = header.session.chained;
+        } else if !chained {
+            // A plain journal has nothing to check
+            if number == 0 {
+                return Ok(ChainCheck::Unchained);
+            }
+        } else {
+            match serde_json::from_str::<JournalRecord>(&line) {
+                Ok(record) if record.prev.is_some() && record.prev == last_hash => records += 1,
+                _ => return Ok(ChainCheck::Broken { line: number + 1 }),
+            }
+        }
+        last_hash = Some(hash);
+    }
+    Ok(if chained {
+        ChainCheck::Intact { records, algorithm }
+    } else {
+        ChainCheck::Unchained
+    })
+}
+
+/// Name of this machine
+#[cfg(unix)]
+fn hostname() -> String {
+    let mut buf = [0 as libc::c_char; 256];
+    // SAFETY: buf is writable for its whole length; gethostname NUL-terminates on success
+    if unsafe { libc::gethostname(buf.as_mut_ptr(), buf.len() - 1) } != 0 {
+        return "unknown".to_string();
+    }
+    // SAFETY: the last byte is never written, so the string is NUL-terminated
+    let name = unsafe { std::ffi::CStr::from_ptr(buf.as_ptr()) };
+    name.to_string_lossy().into_owned()
+}
+
+/// Name of this machine
+#[cfg(not(unix))]
+fn hostname() -> String {
+    std::env::var("COMPUTERNAME").unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::DecisionEngine;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_journal_records_decisions_and_undo() {
+        let dir = TempDir::new().unwrap();
+        let file_path = dir.path().join("a.txt");
+        fs::write(&file_path, b"content").unwrap();
+        let journal_path = dir.path().join("session.jsonl");
+
+        let mut engine = DecisionEngine::new(vec![FileEntry::from_path(&file_path).unwrap()]);
+        engine.set_dry_run(true);
+        let info = SessionInfo::current(Some("ticket-1234".to_string()));
+        engine.add_listener(Box::new(Journal::create_at(&journal_path, &info).unwrap()));
+        engine.record_decision(0, Decision::Trash).unwrap();
+        engine.undo().unwrap();
+        engine.record_decision(0, Decision::Keep).unwrap();
+        engine.undo().unwrap();
+        engine
+            .record_decision(0, Decision::Move(PathBuf::from("/archive")))
+            .unwrap();
+
+        let log = load(&journal_path).unwrap();
+        assert_eq!(log.info, Some(info));
+        let records = log.records;
+        let actions: Vec<_> = records.iter().map(|r| r.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                JournalAction::Trash,
+                JournalAction::Undo,
+                JournalAction::Keep,
+                JournalAction::Undo,
+                JournalAction::Move
+            ]
+        );
+        assert_eq!(records[0].path, file_path);
+        assert_eq!(records[0].size, 7);
+        assert_eq!(records[0].destination, None);
+        assert_eq!(records[4].destination, Some(PathBuf::from("/archive")));
+    }
+
+    #[test]
+    fn test_load_skips_malformed_lines() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.jsonl");
+        fs::write(
+            &path,
+            "{\"time\":1,\"action\":\"keep\",\"path\":\"/a\",\"size\":3}\nnot json\n",
+        )
+        .unwrap();
+
+        let log = load(&path).unwrap();
+        assert_eq!(log.records.len(), 1);
+        assert_eq!(log.info, None);
+    }
+
+    #[test]
+    fn test_load_checks_schema_version() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let info = SessionInfo::current(None);
+        Journal::create_at(&path, &info).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text.starts_with("{\"schema_version\":1,\"session\":"));
+
+        // Headers from before versions were recorded still load
+        fs::write(&path, text.replace("\"schema_version\":1,", "")).unwrap();
+        assert_eq!(load(&path).unwrap().info, Some(info));
+
+        fs::write(
+            &path,
+            text.replace("\"schema_version\":1", "\"schema_version\":2"),
+        )
+        .unwrap();
+        assert!(matches!(
+            load(&path),
+            Err(FileTinderError::UnsupportedSchema { version: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_session_info_describe() {
+        let info = SessionInfo {
+            user: "alice".to_string(),
+            host: "build-01".to_string(),
+            version: "0.1.0".to_string(),
+            annotation: Some("ticket-1234".to_string()),
+            started: 0,
+            chained: false,
+            hash: None,
+            dry_run: false,
+        };
+        assert_eq!(info.describe(), "alice@build-01 · fswp 0.1.0 · ticket-1234");
+        assert!(!SessionInfo::current(None).host.is_empty());
+    }
+
+    #[test]
+    fn test_chained_journal_detects_edits() {
+        let dir = TempDir::new().unwrap();
+        let file_path = dir.path().join("a.txt");
+        fs::write(&file_path, b"content").unwrap();
+        let journal_path = dir.path().join("session.jsonl");
+
+        let mut engine = DecisionEngine::new(vec![FileEntry::from_path(&file_path).unwrap()]);
+        engine.set_dry_run(true);
+        let info = SessionInfo {
+            chained: true,
+            ..SessionInfo::current(None)
+        };
+        engine.add_listener(Box::new(Journal::create_at(&journal_path, &info).unwrap()));
+        engine.record_decision(0, Decision::Trash).unwrap();
+        engine.undo().unwrap();
+        engine.record_decision(0, Decision::Keep).unwrap();
+
+        assert_eq!(
+            verify_chain(&journal_path).unwrap(),
+            ChainCheck::Intact {
+                records: 3,
+                algorithm: HashAlgorithm::Sha256
+            }
+        );
+        assert_eq!(load(&journal_path).unwrap().records.len(), 3);
+
+        // Rewriting the trash as a keep breaks the link from the undo after it
+        let original = fs::read_to_string(&journal_path).unwrap();
+        let edited = original.replacen("\"trash\"", "\"keep\"", 1);
+        fs::write(&journal_path, &edited).unwrap();
+        assert_eq!(
+            verify_chain(&journal_path).unwrap(),
+            ChainCheck::Broken { line: 3 }
+        );
+
+        // So does dropping a line
+        let lines: Vec<&str> = original.lines().collect();
+        fs::write(&journal_path, format!("{}\n{}\n", lines[0], lines[2])).unwrap();
+        assert_eq!(
+            verify_chain(&journal_path).unwrap(),
+            ChainCheck::Broken { line: 2 }
+        );
+    }
+
+    #[test]
+    fn test_chained_journal_checked_with_its_own_algorithm() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let info = SessionInfo {
+            chained: true,
+            hash: Some(HashAlgorithm::Xxhash128),
+            ..SessionInfo::current(None)
+        };
+        let mut journal = Journal::create_at(&path, &info).unwrap();
+        let file = FileEntry::from_path(&path).unwrap();
+        journal.on_decision(0, &file, &Decision::Keep);
+        journal.on_decision(0, &file, &Decision::Trash);
+
+        let text = fs::read_to_string(&path).unwrap();
+        assert!(text
+            .lines()
+            .next()
+            .unwrap()
+            .contains("\"hash\":\"xxhash128\""));
+        // 128-bit links
+        assert_eq!(
+            load(&path).unwrap().records[0].prev.as_ref().unwrap().len(),
+            32
+        );
+        assert_eq!(
+            verify_chain(&path).unwrap(),
+            ChainCheck::Intact {
+                records: 2,
+                algorithm: HashAlgorithm::Xxhash128
+            }
+        );
+    }
+
+    #[test]
+    fn test_verify_chain_of_plain_journal() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let mut journal = Journal::create_at(&path, &SessionInfo::current(None)).unwrap();
+        let file = FileEntry::from_path(&path).unwrap();
+        journal.on_decision(0, &file, &Decision::Keep);
+
+        assert_eq!(verify_chain(&path).unwrap(), ChainCheck::Unchained);
+        assert!(!fs::read_to_string(&path).unwrap().contains("prev"));
+    }
+
+    #[test]
+    fn test_resolve_session_by_path() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("session.jsonl");
+        fs::write(&path, "").unwrap();
+
+        assert_eq!(resolve_session(path.to_str().unwrap()), Some(path));
+        assert_eq!(resolve_session("/nonexistent/session"), None);
+    }
+}
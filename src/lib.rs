@@ -3,20 +3,53 @@
 //! This crate provides the core functionality for the Fswp application,
 //! enabling programmatic file review and organization workflows.
 
+// CI lints the library and binary only; these two style lints are left to the tests
+#![cfg_attr(test, allow(clippy::bool_assert_comparison, clippy::len_zero))]
+
+pub mod archive;
 pub mod async_preview;
+pub mod audio;
 pub mod cli;
+pub mod clipboard;
+pub mod cloud;
 pub mod config;
+pub mod content_search;
+pub mod diagnostics;
+pub mod digest;
+pub mod dir_size;
+pub mod doctor;
 pub mod domain;
 pub mod error;
+pub mod explorer;
 pub mod file_opener;
+pub mod filesystem;
+pub mod hashing;
+pub mod journal;
+pub mod limits;
+pub mod manifest;
+pub mod park;
+pub mod permissions;
+pub mod photo;
 pub mod preview;
+pub mod profile;
+pub mod progress;
+pub mod report;
+pub mod retention;
+pub mod rules;
+pub mod scan_index;
+pub mod schema;
+pub mod sensitive;
+pub mod setup;
+pub mod trash_listing;
 pub mod tui;
+pub mod usage;
 
 // Re-export primary types for convenience
 pub use config::UserConfig;
 pub use domain::{
     discover_files, discover_files_with_options, AppState, Decision, DecisionEngine,
-    DecisionStatistics, DiscoveryOptions, FileEntry, FileType, SortBy,
+    DecisionListener, DecisionStatistics, DefaultDecision, DiscoveryOptions, FileEntry, FileType,
+    ReviewPhase, SessionGoal, SortBy,
 };
 pub use error::{FileTinderError, Result};
-pub use file_opener::open_file;
+pub use file_opener::{open_file, run_shell};
@@ -0,0 +1,223 @@
+//! Module for capping the resources background work may use
+//!
+//! Previews, content search and copy detection read files on worker threads. On
+//! a busy server they can be made polite with the `limits` section of the config:
+//! how many files the workers hold open at once, how much memory a decoded
+//! preview image may take, and how many bytes per second previews, copy
+//! detection and content search may read (so triaging a network mount doesn't
+//! saturate the link). Nothing is capped unless configured.
+//!
+//! How many files content search and copy detection read at once follows the
+//! storage the directory is on (detected, or `limits.storage`): one at a time
+//! on a spinning disk, a few on a network mount, one per core on an SSD.
+//!
+//! The limits are set once at startup, before any worker starts.
+
+use crate::filesystem::StorageKind;
+use std::sync::{Condvar, Mutex, MutexGuard, OnceLock, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+static LIMITER: OnceLock<Limiter> = OnceLock::new();
+
+/// Files read at once when the storage is unknown
+const DEFAULT_WORKERS: usize = 4;
+
+/// Caps on what the worker subsystems may use; `None` means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResourceLimits {
+    /// Files open at once across all workers
+    pub max_open_files: Option<usize>,
+    /// Decoded size of a preview image (width × height × 4 bytes)
+    pub max_image_bytes: Option<u64>,
+    /// Bytes read per second by previews, copy detection and content search
+    pub max_read_rate: Option<u64>,
+    /// What the scanned directory is stored on, which sets `workers()`
+    pub storage: Option<StorageKind>,
+}
+
+/// Applies `limits` for the rest of the process; only the first call takes effect
+pub fn set(limits: ResourceLimits) {
+    let _ = LIMITER.set(Limiter::new(limits));
+}
+
+/// The limits in effect
+pub fn current() -> ResourceLimits {
+    LIMITER
+        .get()
+        .map(|limiter| limiter.limits)
+        .unwrap_or_default()
+}
+
+/// Waits until `files` more files may be opened; they count as open until the
+/// permit is dropped
+pub fn open_files(files: usize) -> FilePermit<'static> {
+    match LIMITER.get() {
+        Some(limiter) => limiter.open_files(files),
+        None => FilePermit {
+            limiter: None,
+            files: 0,
+        },
+    }
+}
+
+/// Waits as long as reading `bytes` more takes at the configured read rate
+pub fn throttle(bytes: u64) {
+    if let Some(limiter) = LIMITER.get() {
+        limiter.throttle(bytes);
+    }
+}
+
+/// Files content search and copy detection read at once
+pub fn workers() -> usize {
+    current().storage.map_or_else(
+        || thread::available_parallelism().map_or(1, |n| n.get().min(DEFAULT_WORKERS)),
+        |storage| storage.workers(),
+    )
+}
+
+/// Whether a `width` × `height` image may be decoded for a preview
+pub fn image_fits(width: u32, height: u32) -> bool {
+    current()
+        .max_image_bytes
+        .is_none_or(|max| u64::from(width) * u64::from(height) * 4 <= max)
+}
+
+/// Enforces a set of limits: a counting semaphore for open files and a shared
+/// schedule for reads
+#[derive(Debug)]
+struct Limiter {
+    limits: ResourceLimits,
+    open: Mutex<usize>,
+    closed: Condvar,
+    /// When the next read may start without going over the read rate
+    next_read: Mutex<Instant>,
+}
+
+impl Limiter {
+    fn new(limits: ResourceLimits) -> Self {
+        Self {
+            limits,
+            open: Mutex::new(0),
+            closed: Condvar::new(),
+            next_read: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn open_files(&self, files: usize) -> FilePermit<'_> {
+        let Some(max) = self.limits.max_open_files else {
+            return FilePermit {
+                limiter: None,
+                files: 0,
+            };
+        };
+        // A caller needing more files than allowed at all gets them all, alone
+        let files = files.min(max.max(1));
+        let mut open = lock(&self.open);
+        while *open + files > max.max(1) {
+            open = self
+                .closed
+                .wait(open)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        *open += files;
+        FilePermit {
+            limiter: Some(self),
+            files,
+        }
+    }
+
+    fn throttle(&self, bytes: u64) {
+        let Some(rate) = self.limits.max_read_rate.filter(|&rate| rate > 0) else {
+            return;
+        };
+        let cost = Duration::from_secs_f64(bytes as f64 / rate as f64);
+        let wait = {
+            let mut next = lock(&self.next_read);
+            let now = Instant::now();
+            let start = (*next).max(now);
+            *next = start + cost;
+            start - now
+        };
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+}
+
+/// Files counted as open until dropped
+#[derive(Debug)]
+pub struct FilePermit<'a> {
+    limiter: Option<&'a Limiter>,
+    files: usize,
+}
+
+impl Drop for FilePermit<'_> {
+    fn drop(&mut self) {
+        if let Some(limiter) = self.limiter {
+            *lock(&limiter.open) -= self.files;
+            limiter.closed.notify_all();
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_open_files_never_exceeds_limit() {
+        let limiter = Arc::new(Limiter::new(ResourceLimits {
+            max_open_files: Some(2),
+            ..Default::default()
+        }));
+        let open = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let workers: Vec<_> = (0..6)
+            .map(|_| {
+                let (limiter, open, peak) = (limiter.clone(), open.clone(), peak.clone());
+                thread::spawn(move || {
+                    let _permit = limiter.open_files(1);
+                    let now = open.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(10));
+                    open.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(peak.load(Ordering::SeqCst), 2);
+        assert_eq!(*lock(&limiter.open), 0);
+
+        // Asking for more than the limit takes the whole allowance instead of hanging
+        let permit = limiter.open_files(5);
+        assert_eq!(permit.files, 2);
+    }
+
+    #[test]
+    fn test_throttle_spreads_reads_over_time() {
+        let limiter = Limiter::new(ResourceLimits {
+            max_read_rate: Some(1000),
+            ..Default::default()
+        });
+        let started = Instant::now();
+        // The first read starts right away, the next two wait 50ms each
+        for _ in 0..3 {
+            limiter.throttle(50);
+        }
+        assert!(started.elapsed() >= Duration::from_millis(100));
+
+        let unlimited = Limiter::new(ResourceLimits::default());
+        let started = Instant::now();
+        unlimited.throttle(u64::MAX);
+        assert!(started.elapsed() < Duration::from_millis(50));
+    }
+}
@@ -1,27 +1,94 @@
 use fswp::async_preview::SyncPreviewManager;
-use fswp::cli::{AppConfig, Args, SortOrder};
-use fswp::config::UserConfig;
+use fswp::cli::{AppConfig, Args, Command, DigestFormat, DigestPeriod, RulesCommand, SortOrder};
+use fswp::clipboard;
+use fswp::config::{ConfigWatcher, UserConfig};
+use fswp::digest::{self, Digest};
+use fswp::doctor::{self, Status};
 use fswp::domain::{
-    discover_files_with_options, AppState, Decision, DecisionEngine, DiscoveryOptions, SortBy,
+    discover_files_excluding, find_easy_wins, AppState, CopyFinder, DiscoveryOptions,
+    DuplicateFinder, FileEntry, LeftOut, SortBy,
 };
-use fswp::open_file;
+use fswp::explorer::Explorer;
+use fswp::journal::{self, ChainCheck, Journal, SessionInfo};
+use fswp::manifest::{self, Manifest, ManifestWriter, RestoreStep};
+use fswp::park;
+use fswp::permissions::AccessChecker;
+use fswp::preview::{self, PreviewOptions};
+use fswp::progress::{self, SavedProgress};
+use fswp::report;
+use fswp::retention::{self, EmptyTrash};
+use fswp::rules::{self, RuleSet};
+use fswp::scan_index::{HashCache, ScanIndex};
+use fswp::setup::SetupWizard;
+use fswp::trash_listing::{self, TrashListing};
+use fswp::tui::glyphs;
+use fswp::tui::session::{run_session, TerminalEvents};
 use fswp::tui::{
-    handle_confirm_input, handle_key_event, render_confirm_trash_overlay, render_help_overlay,
-    render_summary, render_welcome_overlay, render_with_preview, KeyAction, ViewState,
+    format_file_size, handle_key_event, render_empty_trash, render_explorer, render_replay, App,
+    Effect, KeyAction, ViewState,
 };
+use fswp::usage::UsageLookup;
+use fswp::{open_file, run_shell};
 
+use chrono::{Duration, Local, Utc};
 use crossterm::{
-    event::{self, Event},
+    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::{backend::CrosstermBackend, Terminal};
-use std::{io, time::Duration};
+use std::io::{self, IsTerminal, Write};
+use std::path::Path;
+use std::sync::Arc;
 
 fn main() -> io::Result<()> {
     // Parse command line arguments
     let args = Args::parse_args();
 
+    // Every config, journal and report path depends on the profile
+    if let Some(ref name) = args.profile {
+        if let Err(e) = fswp::profile::set(name) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    if let Some(Command::Replay { ref session }) = args.command {
+        return run_replay(session);
+    }
+    if let Some(Command::Rules {
+        command: RulesCommand::Test { ref directory },
+    }) = args.command
+    {
+        return run_rules_test(directory);
+    }
+    if let Some(Command::Audit { ref session }) = args.command {
+        return run_audit(session);
+    }
+    if let Some(Command::Doctor) = args.command {
+        return run_doctor();
+    }
+    if let Some(Command::Purge { yes }) = args.command {
+        return run_purge(yes);
+    }
+    if let Some(Command::EmptyTrash) = args.command {
+        return run_empty_trash();
+    }
+    if let Some(Command::Restore { ref manifest, yes }) = args.command {
+        return run_restore(manifest.as_deref(), yes);
+    }
+    if let Some(Command::Preview { ref file, reveal }) = args.command {
+        return run_preview(file, reveal);
+    }
+    if let Some(Command::Digest {
+        period,
+        format,
+        ref output,
+    }) = args.command
+    {
+        return run_digest(period, format, output.as_deref());
+    }
+
     // Validate arguments
     if let Err(e) = args.validate() {
         eprintln!("Error: {}", e);
@@ -30,16 +97,40 @@ fn main() -> io::Result<()> {
 
     // Convert to config
     let config: AppConfig = args.into();
+    if config.explore {
+        return run_explorer(&config);
+    }
 
     // Run the app
     run_app_with_config(&config)
 }
 
+/// Prints what the config's rules would do with each file in `directory`
+fn run_rules_test(directory: &Path) -> io::Result<()> {
+    let rules = match UserConfig::load().and_then(|config| RuleSet::from_config(&config)) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let options = DiscoveryOptions {
+        sort_by: SortBy::Name,
+        ..DiscoveryOptions::default()
+    };
+    let files = fswp::domain::discover_files_with_options(directory, &options)?;
+    let mut usage = UsageLookup::new();
+    let verdicts = rules::test(&files, &rules, |file| usage.lookup(&file.path), Utc::now());
+    print!("{}", rules::format_verdicts(&verdicts));
+    Ok(())
+}
+
 /// Runs the TUI application with configuration
 pub fn run_app_with_config(config: &AppConfig) -> io::Result<()> {
     // Convert config to discovery options
     let discovery_options = DiscoveryOptions {
         file_types: config.file_type_filters.clone(),
+        extensions: config.extensions.clone(),
         show_hidden: config.show_hidden,
         min_size: config.min_size,
         max_size: config.max_size,
@@ -48,24 +139,82 @@ pub fn run_app_with_config(config: &AppConfig) -> io::Result<()> {
             SortOrder::Name => SortBy::Name,
             SortOrder::Size => SortBy::Size,
             SortOrder::Type => SortBy::Type,
+            SortOrder::Random => SortBy::Random,
         },
         reverse: config.reverse,
+        include_dirs: config.include_dirs,
+        recursive: config.recursive,
     };
 
-    // Discover files with options
-    let files = discover_files_with_options(&config.directory, &discovery_options)?;
+    // Network mounts get smaller preview reads, longer timeouts and no
+    // shared-extent lookups
+    let network_fs = fswp::filesystem::detect(&config.directory).filter(|fs| fs.is_network());
+
+    // Discover files, reusing the last scan's results for files that haven't changed;
+    // the ones the filters leave out are kept for the filter inspector. The index
+    // only saves shared-extent lookups, which network mounts skip altogether, and
+    // content hashes, which the copy finder and duplicate pass add to it.
+    let mut hash_cache = HashCache::default();
+    let (files, left_out) = if network_fs.is_some() {
+        discover_files_excluding(
+            &config.directory,
+            &discovery_options,
+            FileEntry::from_path_unshared,
+        )?
+    } else if config.index_cache {
+        let mut index = ScanIndex::load(&config.directory);
+        let found = discover_files_excluding(&config.directory, &discovery_options, |path| {
+            index.entry(path)
+        })?;
+        if let Err(e) = index.save() {
+            eprintln!("Warning: Failed to save scan index: {}", e);
+        }
+        hash_cache = HashCache::new(index);
+        found
+    } else {
+        discover_files_excluding(&config.directory, &discovery_options, FileEntry::from_path)?
+    };
 
     if files.is_empty() {
         println!(
             "No files found in directory: {}",
             config.directory.display()
         );
-        if config.file_type_filters.is_some() {
-            println!("(File type filters are active - try without filters)");
+        if config.file_type_filters.is_some() || config.extensions.is_some() {
+            println!("(File type or extension filters are active - try without filters)");
         }
         return Ok(());
     }
 
+    // Check up front which files can't be trashed, rather than failing mid-session
+    let mut access_checker = AccessChecker::new();
+    let mut access: Vec<_> = files
+        .iter()
+        .map(|f| access_checker.inspect(&f.path))
+        .collect();
+    let blocked = access.iter().filter(|a| !a.can_delete()).count();
+    let mut files = files;
+    if blocked > 0 && !config.dry_run {
+        println!(
+            "{} of {} files can't be moved to trash by the current user (e.g. no write permission on the parent directory).",
+            blocked,
+            files.len()
+        );
+        if ask_yes_no("   Skip them?", config.skip_confirm)? {
+            let (kept_files, kept_access): (Vec<_>, Vec<_>) = files
+                .into_iter()
+                .zip(access)
+                .filter(|(_, a)| a.can_delete())
+                .unzip();
+            files = kept_files;
+            access = kept_access;
+            if files.is_empty() {
+                println!("No files left to review.");
+                return Ok(());
+            }
+        }
+    }
+
     // Print dry-run notice
     if config.dry_run {
         println!("[DRY RUN] No files will be moved to trash");
@@ -75,57 +224,790 @@ pub fn run_app_with_config(config: &AppConfig) -> io::Result<()> {
         std::io::stdin().read_line(&mut input)?;
     }
 
-    // Initialize state
-    let mut app_state = AppState::new(files.clone());
-    let mut decision_engine = DecisionEngine::new(files);
-    decision_engine.set_dry_run(config.dry_run);
-    let mut preview_manager = SyncPreviewManager::new();
-
     // Load user configuration
     let mut user_config = UserConfig::load().unwrap_or_else(|e| {
         eprintln!("Warning: Failed to load user config: {}", e);
         UserConfig::default()
     });
+    if let Err(e) = user_config.apply_env_overrides(std::env::vars()) {
+        eprintln!("Warning: {}", e);
+    }
+    fswp::tui::input::set_layout(user_config.key_layout);
+    glyphs::set_glyphs(user_config.glyphs);
+    // Caps for the workers below, so a busy machine isn't swamped, and as many
+    // parallel reads as the storage handles well
+    match user_config.resource_limits() {
+        Ok(mut limits) => {
+            limits.storage = limits
+                .storage
+                .or_else(|| fswp::filesystem::detect_storage(&config.directory));
+            fswp::limits::set(limits)
+        }
+        Err(e) => eprintln!("Warning: {}", e),
+    }
+    // A missing or broken preset is an error before the terminal is taken over
+    if let Some(ref name) = config.preset {
+        if let Err(e) = user_config.preset(name) {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+
+    // Initialize state
+    let mut app_state = if config.triage {
+        AppState::new_triage(files)
+    } else {
+        AppState::new(files)
+    };
+    app_state.auto_advance = user_config.auto_advance;
+    app_state.show_queue_stats = user_config.queue_stats;
+    app_state.move_targets = user_config.move_target_list();
+    app_state.goal = config.goal;
+    app_state.default_decisions = user_config.default_decisions();
+    app_state.access = access.into_iter().enumerate().collect();
+    app_state.engine.set_dry_run(config.dry_run);
+    // Journal every decision so the session can be audited or replayed later;
+    // attached before resuming, so resumed decisions are journaled like new ones
+    let info = SessionInfo {
+        chained: config.audit_chain,
+        hash: config.audit_chain.then_some(user_config.hash_algorithm),
+        dry_run: config.dry_run,
+        ..SessionInfo::current(config.annotation.clone())
+    };
+    match Journal::create(&info) {
+        Ok(journal) => app_state.engine.add_listener(Box::new(journal)),
+        Err(e) => eprintln!("Warning: Failed to create session journal: {}", e),
+    }
+    // Offer to pick up an unfinished review of this directory (dry runs neither
+    // resume nor save, so a rehearsal never turns into real decisions)
+    let directory = config
+        .directory
+        .canonicalize()
+        .unwrap_or_else(|_| config.directory.clone());
+    let progress_path = progress::progress_path(&directory).filter(|_| !config.dry_run);
+    if let Some(saved) = progress_path
+        .as_deref()
+        .and_then(|path| progress::load(path, &directory))
+    {
+        let resumable = saved.resumable(&app_state);
+        if resumable > 0 {
+            let question = format!(
+                "Resume where you left off ({} of {} files decided)?",
+                resumable,
+                app_state.files.len()
+            );
+            if ask_yes_no(&question, config.skip_confirm)? {
+                if let Err(e) = saved.restore(&mut app_state) {
+                    eprintln!("Warning: Failed to restore some decisions: {}", e);
+                }
+            }
+        }
+    }
+    let mut preview_manager = if network_fs.is_some() {
+        SyncPreviewManager::with_options(PreviewOptions::degraded())
+    } else {
+        SyncPreviewManager::new()
+    };
+    app_state.degraded_fs = network_fs.map(|fs| fs.fs_type);
+    // Trashing in a synced folder deletes on every device, and placeholders free nothing
+    app_state.cloud_sync = fswp::cloud::detect(&config.directory);
+    if let Some(provider) = app_state.cloud_sync {
+        let placeholders = app_state
+            .files
+            .iter()
+            .filter(|file| file.is_online_only())
+            .count();
+        let mut warning = format!(
+            "{} folder: trashed files are deleted on your other devices too",
+            provider
+        );
+        if placeholders > 0 {
+            warning.push_str(&format!(
+                "; {} online-only files free no space here",
+                placeholders
+            ));
+        }
+        eprintln!("Warning: {}", warning);
+        app_state.show_notice(warning);
+    }
 
-    // Setup terminal
+    // Setup terminal; mouse capture lets a click on the progress bar jump there
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
+    let mut app = App::new(app_state, user_config);
+    app.skip_confirm = config.skip_confirm;
+    app.verify = config.verify;
+    app.background_apply = config.background_apply;
+    app.apply_on_decide = config.apply_on_decide;
+    app.normalize_names = config.normalize_names;
+    app.sort = (discovery_options.sort_by, discovery_options.reverse);
+    app.left_out = LeftOut::new(discovery_options, left_out);
+    app.config_watcher = ConfigWatcher::new();
+    if config.include_dirs {
+        app.start_sizing();
+    }
+    // Copies are compared in the background; with --easy-wins they join the
+    // suggestions once they're found
+    app.copy_finder = Some(CopyFinder::start(
+        Arc::clone(&app.state.files),
+        app.state.degraded_fs.is_some(),
+        hash_cache.clone(),
+        app.user_config.hash_algorithm,
+    ));
+    if config.easy_wins {
+        app.easy_wins = find_easy_wins(&app.state.files, &app.state.copies, Utc::now());
+        app.easy_wins_pending = true;
+    }
+    // Hashing reads every file that shares its size with another, so it's done in
+    // the background too; the groups come up once they're all found
+    if config.duplicates {
+        app.duplicate_finder = Some(DuplicateFinder::start(
+            Arc::clone(&app.state.files),
+            app.user_config.hash_algorithm,
+            app.state.degraded_fs.is_some(),
+            hash_cache,
+        ));
+    }
+    if let Some(ref name) = config.preset {
+        if let Err(e) = app.apply_preset(name) {
+            app.state.show_notice(format!("preset not applied: {}", e));
+        }
+    }
+    // Show welcome on first launch or if --welcome flag is set
+    let should_show_welcome = config.show_welcome || !app.user_config.welcome_shown;
+    // First launch: ask a few setup questions before the welcome screen
+    app.setup = UserConfig::is_first_run().then(SetupWizard::new);
+    app.view = if app.setup.is_some() {
+        ViewState::Setup
+    } else if should_show_welcome {
+        ViewState::Welcome
+    } else {
+        app.review_view()
+    };
+
     // Main loop
-    let result = run_loop(
+    let result = run_session(
         &mut terminal,
-        &mut app_state,
-        &mut decision_engine,
+        &mut app,
         &mut preview_manager,
-        config,
-        &mut user_config,
+        &mut TerminalEvents,
+        |terminal, app, preview_manager, effect| {
+            handle_effect(terminal, app, preview_manager, config, effect)
+        },
     );
 
     // Restore terminal
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
     terminal.show_cursor()?;
 
+    // Save an unfinished review for next time; a finished one has nothing to resume
+    if let Some(ref path) = progress_path {
+        let saved = SavedProgress::capture(&app.state, &directory);
+        let stored = if app.state.all_decided() || saved.decisions.is_empty() {
+            progress::clear(path)
+        } else {
+            saved.save(path)
+        };
+        if let Err(e) = stored {
+            eprintln!("Warning: Failed to save progress: {}", e);
+        }
+    }
+
+    match app.manifest {
+        Some(Ok(ref path)) => println!(
+            "Backup manifest written to {} ('fswp restore' puts the files back)",
+            path.display()
+        ),
+        Some(Err(ref e)) => eprintln!("Warning: Failed to write the backup manifest: {}", e),
+        None => {}
+    }
+
+    // Note what the cleanup took, for whoever browses the directory later
+    if config.leave_summary && !config.dry_run {
+        let results = match app.apply_job {
+            Some(ref job) => job.results.as_slice(),
+            None => app.state.engine.applied(),
+        };
+        let removals = park::removals(&app.state, &config.directory, results);
+        match park::append(&config.directory, &removals, Local::now()) {
+            Ok(Some(path)) => println!("Summary written to {}", path.display()),
+            Ok(None) => {}
+            Err(e) => eprintln!("Warning: Failed to write the directory summary: {}", e),
+        }
+    }
+
     // Print summary after exit
+    for (path, renamed) in &app.renamed {
+        match renamed {
+            Ok(name) if config.dry_run => {
+                println!("[DRY RUN] Would rename {} to {:?}", path.display(), name)
+            }
+            Ok(name) => println!("Renamed {} to {:?}", path.display(), name),
+            Err(e) => eprintln!("Could not rename {}: {}", path.display(), e),
+        }
+    }
     if config.dry_run {
-        let stats = decision_engine.get_statistics();
+        let stats = app.state.engine.get_statistics();
         println!("\n[DRY RUN] Complete");
         println!("   Would have kept: {} files", stats.kept);
         println!("   Would have trashed: {} files", stats.trashed);
+        println!(
+            "   Would have freed: {}",
+            format_file_size(stats.freed_bytes)
+        );
+    }
+
+    result
+}
+
+/// Asks a question answered with Enter for yes before the terminal is taken
+/// over. With `-y`, or with no terminal on stdin to answer from, the yes is taken
+/// without waiting.
+fn ask_yes_no(question: &str, skip_confirm: bool) -> io::Result<bool> {
+    if skip_confirm || !io::stdin().is_terminal() {
+        println!("{} [Y/n] Y", question);
+        return Ok(true);
+    }
+    print!("{} [Y/n] ", question);
+    io::Write::flush(&mut io::stdout())?;
+    let mut input = String::new();
+    io::stdin().read_line(&mut input)?;
+    Ok(!input.trim().eq_ignore_ascii_case("n"))
+}
+
+/// Steps through a recorded session journal in a read-only view
+fn run_replay(session: &str) -> io::Result<()> {
+    let Some(path) = journal::resolve_session(session) else {
+        eprintln!("Error: Session not found: {}", session);
+        std::process::exit(1);
+    };
+    let log = journal::load(&path).map_err(|e| io::Error::other(e.to_string()))?;
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| session.to_string());
+
+    let mut user_config = UserConfig::load().unwrap_or_default();
+    let _ = user_config.apply_env_overrides(std::env::vars());
+    glyphs::set_glyphs(user_config.glyphs);
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let mut position = 0;
+    let result = loop {
+        if let Err(e) = terminal.draw(|frame| {
+            render_replay(frame, &name, &log, position);
+            glyphs::apply(frame.buffer_mut());
+        }) {
+            break Err(e);
+        }
+        match event::read() {
+            Ok(Event::Key(key)) => match handle_key_event(key) {
+                KeyAction::Quit => break Ok(()),
+                KeyAction::Next | KeyAction::Keep => {
+                    position = (position + 1).min(log.records.len().saturating_sub(1));
+                }
+                KeyAction::Previous | KeyAction::Trash => position = position.saturating_sub(1),
+                _ => {}
+            },
+            Ok(_) => {}
+            Err(e) => break Err(e),
+        }
+    };
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+/// The size explorer (`--explore`): moves through the directory's subdirectories
+/// biggest first and runs a review of whichever one is picked, coming back after
+fn run_explorer(config: &AppConfig) -> io::Result<()> {
+    let mut explorer = Explorer::new(&config.directory, config.show_hidden)?;
+
+    let mut user_config = UserConfig::load().unwrap_or_default();
+    let _ = user_config.apply_env_overrides(std::env::vars());
+    glyphs::set_glyphs(user_config.glyphs);
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let result = loop {
+        explorer.poll();
+        if let Err(e) = terminal.draw(|frame| {
+            render_explorer(frame, &explorer);
+            glyphs::apply(frame.buffer_mut());
+        }) {
+            break Err(e);
+        }
+        // Wake up now and then to show sizes as they're measured
+        match event::poll(std::time::Duration::from_millis(100)) {
+            Ok(false) => continue,
+            Ok(true) => {}
+            Err(e) => break Err(e),
+        }
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e),
+        };
+        explorer.notice = None;
+        let moved = match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => {
+                explorer.next();
+                Ok(())
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                explorer.previous();
+                Ok(())
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => explorer.enter(),
+            KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => explorer.back().map(|_| ()),
+            KeyCode::Char('r') => {
+                let Some((directory, subtree)) = explorer.review_target() else {
+                    continue;
+                };
+                let size = explorer.selected().map(|row| row.size);
+                if size.is_some_and(|size| {
+                    size.done && size.stats.files == 0 && size.stats.directories == 0
+                }) {
+                    explorer.notice = Some(format!("Nothing to review in {}", directory.display()));
+                    continue;
+                }
+
+                // The review takes over the terminal, then hands it back
+                disable_raw_mode()?;
+                execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+                let review = AppConfig {
+                    directory: directory.clone(),
+                    // `--dirs` reviews the subdirectory's own items, folders included
+                    recursive: subtree && !config.include_dirs,
+                    explore: false,
+                    ..config.clone()
+                };
+                let reviewed = run_app_with_config(&review);
+                enable_raw_mode()?;
+                execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+                terminal.clear()?;
+                if let Err(e) = reviewed {
+                    explorer.notice = Some(format!("Review failed: {}", e));
+                }
+                explorer.refresh_after_review(&directory)
+            }
+            _ => Ok(()),
+        };
+        if let Err(e) = moved {
+            explorer.notice = Some(format!("Can't list that directory: {}", e));
+        }
+    };
+
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    result
+}
+
+fn run_audit(session: &str) -> io::Result<()> {
+    let Some(path) = journal::resolve_session(session) else {
+        eprintln!("Error: Session not found: {}", session);
+        std::process::exit(1);
+    };
+    match journal::verify_chain(&path).map_err(|e| io::Error::other(e.to_string()))? {
+        ChainCheck::Intact { records, algorithm } => {
+            println!(
+                "{}: chain intact ({} records, {})",
+                path.display(),
+                records,
+                algorithm
+            );
+            Ok(())
+        }
+        ChainCheck::Unchained => {
+            eprintln!(
+                "{}: not written with --audit-chain, nothing to check",
+                path.display()
+            );
+            std::process::exit(2);
+        }
+        ChainCheck::Broken { line } => {
+            eprintln!("{}: chain broken at line {}", path.display(), line);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn run_purge(yes: bool) -> io::Result<()> {
+    let loaded = UserConfig::load().and_then(|config| {
+        let rules = config.retention_rules()?;
+        Ok((config.retention, rules, config.hash_algorithm))
+    });
+    let (configured, rules, algorithm) = match loaded {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if rules.is_empty() {
+        println!("No retention rules configured (see \"retention\" in the config file)");
+        return Ok(());
+    }
+    let logs = match journal::sessions_dir() {
+        Some(dir) => digest::load_all(&dir).map_err(|e| io::Error::other(e.to_string()))?,
+        None => Vec::new(),
+    };
+    let ours = retention::journaled_trash(&logs);
+
+    let listing = list_system_trash();
+    let now = Utc::now();
+    // Journals record no hashes, so the item's size has to match
+    let expired = retention::expired(
+        &listing.files,
+        &rules,
+        &ours,
+        |position, record| listing.holds(position, record.size, None, algorithm),
+        now,
+    );
+    if expired.is_empty() {
+        println!("Nothing in the trash is past its retention period");
+        return Ok(());
+    }
+
+    let mut bytes = 0;
+    for &(position, rule) in &expired {
+        let file = &listing.files[position];
+        bytes += retention::record_of(file, &ours).map_or(0, |record| record.size);
+        println!(
+            "  {} ({})",
+            file.path.display(),
+            retention::explain(&configured[rule], file, now)
+        );
+    }
+    if !yes {
+        println!(
+            "{} files ({}) are past their retention period. Run 'fswp purge --yes' to delete them permanently.",
+            expired.len(),
+            format_file_size(bytes)
+        );
+        return Ok(());
     }
+    listing.purge(expired.iter().map(|&(position, _)| position))?;
+    println!(
+        "Deleted {} files ({}) from the trash",
+        expired.len(),
+        format_file_size(bytes)
+    );
+    Ok(())
+}
+
+/// Lists what session journals recorded as trashed and is still in the system
+/// trash, and deletes the files picked for good
+fn run_empty_trash() -> io::Result<()> {
+    let logs = match journal::sessions_dir() {
+        Some(dir) => digest::load_all(&dir).map_err(|e| io::Error::other(e.to_string()))?,
+        None => Vec::new(),
+    };
+    let ours = retention::journaled_trash(&logs);
+    let mut user_config = UserConfig::load().unwrap_or_default();
+    let _ = user_config.apply_env_overrides(std::env::vars());
+    let algorithm = user_config.hash_algorithm;
+    let now = Utc::now();
+    let mut listing = list_system_trash();
+    // Only items that are still the file fswp trashed, with their real sizes
+    let rows = |listing: &TrashListing| {
+        EmptyTrash::new(
+            &listing.files,
+            &ours,
+            |position, record| listing.holds(position, record.size, None, algorithm),
+            |position| listing.size(position),
+            now,
+        )
+    };
+    let mut list = rows(&listing);
+    if list.rows.is_empty() {
+        println!("Nothing fswp trashed is still in the trash");
+        return Ok(());
+    }
+
+    glyphs::set_glyphs(user_config.glyphs);
+
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout))?;
+
+    let mut freed = (0, 0);
+    let result = loop {
+        if let Err(e) = terminal.draw(|frame| {
+            render_empty_trash(frame, &list, now);
+            glyphs::apply(frame.buffer_mut());
+        }) {
+            break Err(e);
+        }
+        let key = match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => key,
+            Ok(_) => continue,
+            Err(e) => break Err(e),
+        };
+        list.notice = None;
+        if list.confirming {
+            list.confirming = false;
+            if key.code != KeyCode::Char('y') {
+                continue;
+            }
+            let (files, bytes) = list.selection();
+            let notice = match listing.purge(list.selected_positions()) {
+                Ok(()) => {
+                    freed = (freed.0 + files, freed.1 + bytes);
+                    format!(
+                        "Deleted {} files ({}) from the trash",
+                        files,
+                        format_file_size(bytes)
+                    )
+                }
+                Err(e) => format!("Couldn't empty the trash: {}", e),
+            };
+            // Whatever went, list what the trash holds now
+            match trash_listing::list() {
+                Ok(listed) => listing = listed,
+                Err(e) => break Err(e),
+            }
+            list = rows(&listing);
+            list.notice = Some(notice);
+            if list.rows.is_empty() {
+                break Ok(());
+            }
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => break Ok(()),
+            KeyCode::Down | KeyCode::Char('j') => list.next(),
+            KeyCode::Up | KeyCode::Char('k') => list.previous(),
+            KeyCode::Char(' ') => list.toggle(),
+            KeyCode::Char('g') => list.toggle_group(),
+            KeyCode::Char('a') => list.toggle_all(),
+            KeyCode::Char('d') | KeyCode::Enter => {
+                if list.selection().0 == 0 {
+                    list.notice = Some("Select files with Space, g or a first".to_string());
+                } else {
+                    list.confirming = true;
+                }
+            }
+            _ => {}
+        }
+    };
 
+    disable_raw_mode()?;
+    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    terminal.show_cursor()?;
+    if freed.0 > 0 {
+        println!(
+            "Deleted {} files ({}) from the trash",
+            freed.0,
+            format_file_size(freed.1)
+        );
+    }
     result
 }
 
+/// What the system trash holds; exits when it can't be listed (on macOS)
+fn list_system_trash() -> TrashListing {
+    match trash_listing::list() {
+        Ok(listing) => listing,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Lists what a backup manifest says a session took and, with `yes`, puts it back:
+/// trashed files out of the system trash, moved files from where they went
+fn run_restore(path: Option<&Path>, yes: bool) -> io::Result<()> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => match manifest::manifests_dir().and_then(|dir| manifest::latest(&dir)) {
+            Some(path) => path,
+            None => {
+                println!("No backup manifests yet; one is written each time a session applies");
+                return Ok(());
+            }
+        },
+    };
+    let manifest = Manifest::load(&path)
+        .map_err(|e| io::Error::other(format!("{}: {}", path.display(), e)))?;
+    let listing = list_system_trash();
+    let plan = manifest.plan(
+        |path| path.symlink_metadata().is_ok(),
+        &listing.files,
+        |position, entry| {
+            listing.holds(
+                position,
+                entry.size,
+                entry.hash.as_deref(),
+                manifest.hash_algorithm,
+            )
+        },
+    );
+
+    println!(
+        "{}: {} files from {}, written {}",
+        path.display(),
+        manifest.entries.len(),
+        manifest.directory.display(),
+        manifest
+            .written_at()
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+    );
+    let mut pending = 0;
+    for (entry, step) in manifest.entries.iter().zip(&plan) {
+        let status = match step {
+            RestoreStep::InPlace => "already in place".to_string(),
+            RestoreStep::MoveBack(from) => format!("moved to {}", from.display()),
+            RestoreStep::FromTrash(_) => "in the trash".to_string(),
+            RestoreStep::Missing => "not found where it went".to_string(),
+        };
+        if matches!(step, RestoreStep::MoveBack(_) | RestoreStep::FromTrash(_)) {
+            pending += 1;
+        }
+        println!("  {} ({})", entry.path.display(), status);
+    }
+    if pending == 0 {
+        println!("Nothing to restore");
+        return Ok(());
+    }
+    if !yes {
+        println!(
+            "{} files can be put back. Run 'fswp restore --yes' to restore them.",
+            pending
+        );
+        return Ok(());
+    }
+
+    let mut restored = Vec::new();
+    for (entry, step) in manifest.entries.iter().zip(&plan) {
+        let result = match step {
+            RestoreStep::MoveBack(from) => std::fs::rename(from, &entry.path),
+            RestoreStep::FromTrash(position) => listing.restore(*position),
+            RestoreStep::InPlace | RestoreStep::Missing => continue,
+        };
+        match result {
+            Ok(()) => restored.push(entry),
+            Err(e) => eprintln!("Could not restore {}: {}", entry.path.display(), e),
+        }
+    }
+    for entry in &restored {
+        if entry.unchanged(manifest.hash_algorithm) == Some(false) {
+            eprintln!(
+                "Warning: {} differs from the manifest's {} hash",
+                entry.path.display(),
+                manifest.hash_algorithm
+            );
+        }
+    }
+    println!("Restored {} of {} files", restored.len(), pending);
+    Ok(())
+}
+
+/// Prints the environment health check; exits with 1 when something needs fixing
+fn run_doctor() -> io::Result<()> {
+    let checks = doctor::run();
+    print!("{}", doctor::render(&checks));
+    if checks.iter().any(|check| check.status == Status::Fail) {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Prints the preview the review would show for `path`: text as it is, images as
+/// colored half blocks (plain when stdout isn't a terminal)
+fn run_preview(path: &Path, reveal: bool) -> io::Result<()> {
+    let user_config = UserConfig::load().unwrap_or_default();
+    match user_config.resource_limits() {
+        Ok(limits) => fswp::limits::set(limits),
+        Err(e) => eprintln!("Warning: {}", e),
+    }
+    let file = if path.is_dir() {
+        FileEntry::from_dir(path)
+    } else {
+        FileEntry::from_path(path)
+    };
+    let file = match file {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Error: {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    };
+    // Network mounts get the same smaller reads as in the review
+    let options = if fswp::filesystem::detect(path).is_some_and(|fs| fs.is_network()) {
+        PreviewOptions::degraded()
+    } else {
+        PreviewOptions::default()
+    };
+    let content = match preview::generate_preview_with_options(&file, &options) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!(
+                "Error: preview of {} failed: {}",
+                path.display(),
+                fswp::diagnostics::error_chain(&e)
+            );
+            std::process::exit(1);
+        }
+    };
+    let color = io::stdout().is_terminal();
+    let mut out = io::stdout().lock();
+    for line in fswp::tui::preview_to_ansi(&content, !reveal, color) {
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+fn run_digest(period: DigestPeriod, format: DigestFormat, output: Option<&Path>) -> io::Result<()> {
+    let logs = match journal::sessions_dir() {
+        Some(dir) => digest::load_all(&dir).map_err(|e| io::Error::other(e.to_string()))?,
+        None => Vec::new(),
+    };
+    let until = Utc::now();
+    let digest = Digest::build(&logs, until - Duration::days(period.days()), until);
+    let text = match format {
+        DigestFormat::Markdown => digest.to_markdown(),
+        DigestFormat::Html => digest.to_html(),
+    };
+    match output {
+        Some(path) => std::fs::write(path, text),
+        None => io::stdout().write_all(text.as_bytes()),
+    }
+}
+
 /// Suspends the TUI terminal to allow external programs to run
 fn suspend_terminal<B: ratatui::backend::Backend + std::io::Write>(
     terminal: &mut Terminal<B>,
 ) -> io::Result<()> {
     disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
     terminal.show_cursor()?;
     Ok(())
 }
@@ -135,219 +1017,156 @@ fn resume_terminal<B: ratatui::backend::Backend + std::io::Write>(
     terminal: &mut Terminal<B>,
 ) -> io::Result<()> {
     enable_raw_mode()?;
-    execute!(terminal.backend_mut(), EnterAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
     terminal.hide_cursor()?;
     terminal.clear()?;
     Ok(())
 }
 
-/// Main application loop
-fn run_loop<B: ratatui::backend::Backend + std::io::Write>(
+/// Stops the process with SIGTSTP, as the shell would for Ctrl+Z outside raw mode.
+///
+/// The terminal is restored first; execution continues here after `fg` sends SIGCONT.
+#[cfg(unix)]
+fn suspend_process() {
+    // SAFETY: raise() has no memory-safety preconditions; SIGTSTP uses its default
+    // disposition here, which stops the process until it receives SIGCONT.
+    unsafe {
+        libc::raise(libc::SIGTSTP);
+    }
+}
+
+/// Job control is not available on this platform, so suspending is a no-op
+#[cfg(not(unix))]
+fn suspend_process() {}
+
+/// Carries out the effects `run_session` leaves to the binary: the real terminal and the config file
+fn handle_effect<B: ratatui::backend::Backend + std::io::Write>(
     terminal: &mut Terminal<B>,
-    app_state: &mut AppState,
-    decision_engine: &mut DecisionEngine,
+    app: &mut App,
     preview_manager: &mut SyncPreviewManager,
     config: &AppConfig,
-    user_config: &mut UserConfig,
+    effect: Effect,
 ) -> io::Result<()> {
-    // Show welcome on first launch or if --welcome flag is set
-    let should_show_welcome = config.show_welcome || !user_config.welcome_shown;
-    let mut view_state = if should_show_welcome {
-        ViewState::Welcome
-    } else {
-        ViewState::Browsing
-    };
+    match effect {
+        Effect::SaveConfig(stored) => {
+            if let Err(e) = stored.save() {
+                app.state.show_notice(format!("setup not saved: {}", e));
+            }
+        }
+        Effect::MarkWelcomeShown => {
+            // Re-read the file so environment overrides aren't written into it
+            let saved = UserConfig::load().and_then(|mut stored| {
+                stored.welcome_shown = true;
+                stored.save()
+            });
+            if let Err(e) = saved {
+                eprintln!("Warning: Failed to save user config: {}", e);
+            }
+        }
+        Effect::Open(path) => {
+            // Suspend terminal before opening external program
+            if let Err(e) = suspend_terminal(terminal) {
+                eprintln!("Failed to suspend terminal: {}", e);
+                return Ok(());
+            }
 
-    loop {
-        // Render based on current view state
-        terminal.draw(|frame| {
-            render_with_preview(frame, app_state, preview_manager);
-
-            // Render overlays
-            match view_state {
-                ViewState::Help => render_help_overlay(frame),
-                ViewState::Summary => {
-                    let stats = decision_engine.get_statistics();
-                    render_summary(frame, &stats);
-                }
-                ViewState::ConfirmTrash => {
-                    if let Some(file) = app_state.current_file() {
-                        render_confirm_trash_overlay(frame, file);
-                    }
-                }
-                ViewState::Welcome => render_welcome_overlay(frame),
-                ViewState::Browsing => {}
+            // Open the file (blocking call)
+            let open_result = open_file(&path);
+
+            // Resume terminal after external program exits
+            if let Err(e) = resume_terminal(terminal) {
+                eprintln!("Failed to resume terminal: {}", e);
+                return Err(e);
             }
-        })?;
 
-        // Handle input
-        if event::poll(Duration::from_millis(100))? {
-            if let Event::Key(key) = event::read()? {
-                // Handle overlay-specific input
-                match view_state {
-                    ViewState::Help => {
-                        // Any key closes help (or toggle with ?)
-                        let action = handle_key_event(key);
-                        if matches!(action, KeyAction::Help | KeyAction::Quit | KeyAction::None) {
-                            view_state = ViewState::Browsing;
-                        }
-                        continue;
-                    }
-                    ViewState::Summary => {
-                        // Any key exits from summary
-                        break;
-                    }
-                    ViewState::ConfirmTrash => {
-                        let action = handle_confirm_input(key);
-                        match action {
-                            KeyAction::ConfirmTrash => {
-                                // Execute trash decision
-                                if decision_engine
-                                    .record_decision(app_state.current_index, Decision::Trash)
-                                    .is_ok()
-                                {
-                                    app_state.record_decision(Decision::Trash);
-                                    app_state.next();
-                                    preview_manager.reset();
-
-                                    if is_all_files_processed(app_state, decision_engine) {
-                                        view_state = ViewState::Summary;
-                                    } else {
-                                        view_state = ViewState::Browsing;
-                                    }
-                                } else {
-                                    view_state = ViewState::Browsing;
-                                }
-                            }
-                            KeyAction::CancelTrash => {
-                                view_state = ViewState::Browsing;
-                            }
-                            _ => {}
-                        }
-                        continue;
-                    }
-                    ViewState::Welcome => {
-                        // Any key dismisses welcome and starts browsing
-                        view_state = ViewState::Browsing;
-
-                        // Mark welcome as shown and persist
-                        user_config.welcome_shown = true;
-                        if let Err(e) = user_config.save() {
-                            eprintln!("Warning: Failed to save user config: {}", e);
-                        }
-                        continue;
-                    }
-                    ViewState::Browsing => {}
-                }
+            // Handle any errors from opening the file
+            if let Err(e) = open_result {
+                eprintln!("Failed to open file: {}", e);
+            }
+        }
+        Effect::Suspend => {
+            suspend_terminal(terminal)?;
+            suspend_process();
+            // Back from `fg`: re-enter raw mode and redraw from scratch
+            resume_terminal(terminal)?;
+        }
+        Effect::Shell => {
+            if let Err(e) = suspend_terminal(terminal) {
+                eprintln!("Failed to suspend terminal: {}", e);
+                return Ok(());
+            }
+
+            println!(
+                "fswp: starting a shell in {}. Type 'exit' to return to your session.",
+                config.directory.display()
+            );
+            // Reported before the TUI is back, where it would be drawn over
+            if let Err(e) = run_shell(&config.directory) {
+                eprintln!("{}", e);
+            }
+
+            resume_terminal(terminal)?;
 
-                let action = handle_key_event(key);
-
-                match action {
-                    KeyAction::Quit => {
-                        // Show summary before quitting if any decisions were made
-                        let stats = decision_engine.get_statistics();
-                        if stats.kept > 0 || stats.trashed > 0 {
-                            view_state = ViewState::Summary;
-                        } else {
-                            break;
-                        }
-                    }
-                    KeyAction::Keep => {
-                        if decision_engine
-                            .record_decision(app_state.current_index, Decision::Keep)
-                            .is_ok()
-                        {
-                            app_state.record_decision(Decision::Keep);
-                            app_state.next();
-                            preview_manager.reset();
-
-                            // Check if we've processed all files
-                            if is_all_files_processed(app_state, decision_engine) {
-                                view_state = ViewState::Summary;
-                            }
-                        }
-                    }
-                    KeyAction::Trash => {
-                        // Skip confirmation if flag set or dry-run mode
-                        if config.skip_confirm || decision_engine.is_dry_run() {
-                            // Execute trash immediately
-                            if decision_engine
-                                .record_decision(app_state.current_index, Decision::Trash)
-                                .is_ok()
-                            {
-                                app_state.record_decision(Decision::Trash);
-                                app_state.next();
-                                preview_manager.reset();
-
-                                // Check if we've processed all files
-                                if is_all_files_processed(app_state, decision_engine) {
-                                    view_state = ViewState::Summary;
-                                }
-                            }
-                        } else {
-                            // Show confirmation dialog
-                            view_state = ViewState::ConfirmTrash;
-                        }
-                    }
-                    KeyAction::Next => {
-                        app_state.next();
-                        preview_manager.reset();
-                    }
-                    KeyAction::Previous => {
-                        app_state.previous();
-                        preview_manager.reset();
-                    }
-                    KeyAction::Undo => {
-                        if decision_engine.undo().is_ok() {
-                            app_state.undo();
-                            preview_manager.reset();
-                            // Return to browsing if we were in summary
-                            if view_state == ViewState::Summary {
-                                view_state = ViewState::Browsing;
-                            }
-                        }
-                    }
-                    KeyAction::Help => {
-                        view_state = ViewState::Help;
-                    }
-                    KeyAction::Open => {
-                        if let Some(file) = app_state.current_file() {
-                            // Suspend terminal before opening external program
-                            if let Err(e) = suspend_terminal(terminal) {
-                                eprintln!("Failed to suspend terminal: {}", e);
-                                continue;
-                            }
-
-                            // Open the file (blocking call)
-                            let open_result = open_file(&file.path);
-
-                            // Resume terminal after external program exits
-                            if let Err(e) = resume_terminal(terminal) {
-                                eprintln!("Failed to resume terminal: {}", e);
-                                return Err(e);
-                            }
-
-                            // Handle any errors from opening the file
-                            if let Err(e) = open_result {
-                                eprintln!("Failed to open file: {}", e);
-                            }
-                        }
-                    }
-                    KeyAction::ConfirmTrash | KeyAction::CancelTrash => {
-                        // These actions are only handled in ConfirmTrash state
-                        // Ignore them here
-                    }
-                    KeyAction::None => {}
+            // Files may have been edited in the shell, so reload previews
+            preview_manager.clear_cache();
+        }
+        Effect::ExportReport => match report::save(
+            &app.state,
+            &config.directory,
+            app.user_config.hash_algorithm,
+        ) {
+            Ok(path) => app
+                .state
+                .show_notice(format!("report saved to {}", path.display())),
+            Err(e) => app.state.show_notice(format!("report not saved: {}", e)),
+        },
+        Effect::WriteManifest => {
+            app.manifest_writer = Some(ManifestWriter::start(
+                &app.state,
+                &config.directory,
+                app.user_config.hash_algorithm,
+                Utc::now(),
+                manifest::manifests_dir(),
+            ));
+        }
+        Effect::SaveDiagnostics => {
+            if let Some(ref failure) = app.preview_failure {
+                match failure.save() {
+                    Ok(path) => app
+                        .state
+                        .show_notice(format!("diagnostics saved to {}", path.display())),
+                    Err(e) => app
+                        .state
+                        .show_notice(format!("diagnostics not saved: {}", e)),
                 }
             }
         }
+        Effect::CopyToClipboard(text) => {
+            if let Err(e) = clipboard::copy(terminal.backend_mut(), &text) {
+                app.state.show_notice(format!("not copied: {}", e));
+            }
+        }
+        Effect::SaveMacro(steps) => {
+            // Re-read the file so environment overrides aren't written into it
+            let saved = UserConfig::load().and_then(|mut stored| {
+                stored.macro_steps = steps;
+                stored.save()
+            });
+            if let Err(e) = saved {
+                app.state.show_notice(format!("macro not saved: {}", e));
+            }
+        }
+        // Handled inside the session loop
+        Effect::None
+        | Effect::Exit
+        | Effect::ResetPreview
+        | Effect::AdjustImage(_)
+        | Effect::ScrollPreview(_)
+        | Effect::ShowPreviewFailure => {}
     }
-
     Ok(())
 }
-
-/// Checks if all files have been processed
-fn is_all_files_processed(app_state: &AppState, decision_engine: &DecisionEngine) -> bool {
-    let stats = decision_engine.get_statistics();
-    stats.kept + stats.trashed >= app_state.files.len()
-}
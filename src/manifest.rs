@@ -0,0 +1,477 @@
+//! Module for the backup manifest written before applying
+//!
+//! Just before a session sends its staged files to the system trash, it writes
+//! `<data dir>/fswp/manifests/<YYYYMMDD-HHMMSS>.json`: every file the session
+//! took out of the directory, with its size, a hash of its contents (the config's
+//! `hash_algorithm`) and where it went — the system trash, or the directory it was
+//! moved to. This happens on every apply, whether or not a report was exported,
+//! so `fswp restore` can always put the last session's files back and say whether
+//! what came back is what went. Dry runs write none. The file records its
+//! `schema_version` (see `schema`).
+//!
+//! Files background apply or apply on decide sent to the trash before the
+//! manifest was written are in it too, each with when it went, and a session that
+//! only moved files writes one as well.
+//!
+//! Hashing a large session takes a while, so `ManifestWriter` does it on a worker
+//! thread while the apply screen counts the files; nothing goes until it's saved.
+
+use crate::domain::{AppState, Decision};
+use crate::hashing::HashAlgorithm;
+use crate::retention::TrashedFile;
+use crate::schema::{self, MANIFEST_VERSION};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Arc;
+use std::thread;
+
+/// What a session's apply is about to take out of a directory
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// `schema::MANIFEST_VERSION` when written; 0 for manifests from before it was
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Unix timestamp (seconds)
+    pub written: i64,
+    /// The reviewed directory
+    pub directory: PathBuf,
+    pub hash_algorithm: HashAlgorithm,
+    pub entries: Vec<ManifestEntry>,
+}
+
+/// A file the session trashed or moved
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Where the file was before the session
+    pub path: PathBuf,
+    pub size: u64,
+    /// Hash of the contents; `None` when they couldn't be read (a directory, or a
+    /// file background apply had already trashed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Where it was moved; `None` when it goes to the system trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub moved_to: Option<PathBuf>,
+    /// When it was sent to the trash, for a file that went before the manifest
+    /// was written (Unix timestamp, seconds)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trashed: Option<i64>,
+}
+
+/// What `fswp restore` does for one entry
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RestoreStep {
+    /// Something is at the original path already, so the entry is left alone
+    InPlace,
+    /// Move it back from where it was moved
+    MoveBack(PathBuf),
+    /// Restore this item (a position in the trash listing)
+    FromTrash(usize),
+    /// Neither where it was moved nor in the trash
+    Missing,
+}
+
+impl Manifest {
+    /// Whether `state` has trashed or moved anything for a manifest to cover
+    pub fn is_needed(state: &AppState) -> bool {
+        (0..state.files.len()).any(|index| {
+            matches!(
+                state.decision_of(index),
+                Some(Decision::Trash | Decision::Move(_))
+            )
+        })
+    }
+
+    /// The files `state` has trashed or moved, in scan order, each hashed where
+    /// its decision left it
+    pub fn build(
+        state: &AppState,
+        directory: &Path,
+        algorithm: HashAlgorithm,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let (mut manifest, locations) = Self::unhashed(state, directory, algorithm, now);
+        manifest.hash_entries(&locations, || true);
+        manifest
+    }
+
+    /// What `build` makes, before any hashing, and where each entry's contents
+    /// are to be hashed from
+    fn unhashed(
+        state: &AppState,
+        directory: &Path,
+        algorithm: HashAlgorithm,
+        now: DateTime<Utc>,
+    ) -> (Self, Vec<PathBuf>) {
+        let (entries, locations) = (0..state.files.len())
+            .filter_map(|index| {
+                let decision = state.decision_of(index)?;
+                let moved_to = match decision {
+                    Decision::Trash => None,
+                    Decision::Move(dir) => Some(state.engine.move_destination(index, dir)),
+                    Decision::Keep | Decision::Skip => return None,
+                };
+                let file = &state.files[index];
+                let trashed = state
+                    .engine
+                    .trashed_at(index)
+                    .map(|at| DateTime::<Utc>::from(at).timestamp());
+                let entry = ManifestEntry {
+                    path: file.path.clone(),
+                    size: file.size,
+                    hash: None,
+                    moved_to,
+                    trashed,
+                };
+                Some((entry, state.engine.location(index, decision)))
+            })
+            .unzip();
+        let manifest = Self {
+            schema_version: MANIFEST_VERSION,
+            written: now.timestamp(),
+            directory: directory.to_path_buf(),
+            hash_algorithm: algorithm,
+            entries,
+        };
+        (manifest, locations)
+    }
+
+    /// Hashes each entry's contents at its location, in order; stops once
+    /// `hashed`, called after each, returns false
+    fn hash_entries(&mut self, locations: &[PathBuf], mut hashed: impl FnMut() -> bool) {
+        for (entry, location) in self.entries.iter_mut().zip(locations) {
+            entry.hash = self.hash_algorithm.hash_file(location).ok();
+            if !hashed() {
+                return;
+            }
+        }
+    }
+
+    /// Writes the manifest into `dir`, named after when it was written
+    pub fn save_to(&self, dir: &Path) -> io::Result<PathBuf> {
+        fs::create_dir_all(dir)?;
+        let name = self.written_at().format("%Y%m%d-%H%M%S");
+        let path = dir.join(format!("{}.json", name));
+        let data = serde_json::to_vec_pretty(self).map_err(io::Error::other)?;
+        fs::write(&path, data)?;
+        Ok(path)
+    }
+
+    pub fn written_at(&self) -> DateTime<Utc> {
+        DateTime::from_timestamp(self.written, 0).unwrap_or_default()
+    }
+
+    /// Reads the manifest at `path`, upgraded to the current schema; refuses one
+    /// written by a newer fswp
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let invalid = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+        let data = fs::read(path)?;
+        let value: Value = serde_json::from_slice(&data).map_err(invalid)?;
+        schema::check(
+            "Backup manifest",
+            schema::version_of(&value),
+            MANIFEST_VERSION,
+        )
+        .map_err(io::Error::other)?;
+        let mut manifest: Self = serde_json::from_value(value).map_err(invalid)?;
+        // Version 0 has the same fields as version 1
+        manifest.schema_version = MANIFEST_VERSION;
+        Ok(manifest)
+    }
+
+    /// What to do for each entry, in order, given what is on disk and in the
+    /// trash. A trashed entry comes back from the latest trashing of its path
+    /// since it was sent to the trash (when the manifest was written, unless it
+    /// went earlier) whose item `holds` it (the recorded size and hash; see
+    /// `TrashListing::holds`), so a later file trashed from the same path is
+    /// never restored in its place.
+    pub fn plan(
+        &self,
+        exists: impl Fn(&Path) -> bool,
+        trashed: &[TrashedFile],
+        holds: impl Fn(usize, &ManifestEntry) -> bool,
+    ) -> Vec<RestoreStep> {
+        let written = self.written_at();
+        self.entries
+            .iter()
+            .map(|entry| {
+                if exists(&entry.path) {
+                    return RestoreStep::InPlace;
+                }
+                match entry.moved_to {
+                    Some(ref destination) if exists(destination) => {
+                        RestoreStep::MoveBack(destination.clone())
+                    }
+                    Some(_) => RestoreStep::Missing,
+                    None => {
+                        let since = entry
+                            .trashed
+                            .and_then(|time| DateTime::from_timestamp(time, 0))
+                            .unwrap_or(written);
+                        let mut candidates: Vec<usize> = (0..trashed.len())
+                            .filter(|&position| {
+                                let item = &trashed[position];
+                                item.path == entry.path && item.deleted >= since
+                            })
+                            .collect();
+                        candidates
+                            .sort_by_key(|&position| std::cmp::Reverse(trashed[position].deleted));
+                        candidates
+                            .into_iter()
+                            .find(|&position| holds(position, entry))
+                            .map_or(RestoreStep::Missing, RestoreStep::FromTrash)
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+impl ManifestEntry {
+    /// Whether the file now at its original path has the contents recorded;
+    /// `None` when there is no hash to compare or the file can't be read
+    pub fn unchanged(&self, algorithm: HashAlgorithm) -> Option<bool> {
+        let expected = self.hash.as_ref()?;
+        let actual = algorithm.hash_file(&self.path).ok()?;
+        Some(&actual == expected)
+    }
+}
+
+/// A manifest being hashed and saved on a worker thread
+#[derive(Debug)]
+pub struct ManifestWriter {
+    cancel: Arc<AtomicBool>,
+    hashed: Arc<AtomicUsize>,
+    total: usize,
+    result: Receiver<Result<PathBuf, String>>,
+}
+
+impl ManifestWriter {
+    /// Starts hashing what `state` has trashed or moved (see `Manifest::build`),
+    /// then saves the manifest into `dir`
+    pub fn start(
+        state: &AppState,
+        directory: &Path,
+        algorithm: HashAlgorithm,
+        now: DateTime<Utc>,
+        dir: Option<PathBuf>,
+    ) -> Self {
+        let (mut manifest, locations) = Manifest::unhashed(state, directory, algorithm, now);
+        let total = locations.len();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let hashed = Arc::new(AtomicUsize::new(0));
+        let (sender, result) = mpsc::channel();
+        let (stop, count) = (Arc::clone(&cancel), Arc::clone(&hashed));
+        thread::spawn(move || {
+            manifest.hash_entries(&locations, || {
+                count.fetch_add(1, Ordering::Relaxed);
+                !stop.load(Ordering::Relaxed)
+            });
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            let saved = match dir {
+                Some(dir) => manifest.save_to(&dir).map_err(|e| e.to_string()),
+                None => Err("Could not determine data directory".to_string()),
+            };
+            let _ = sender.send(saved);
+        });
+
+        Self {
+            cancel,
+            hashed,
+            total,
+            result,
+        }
+    }
+
+    /// (files hashed, files to hash)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.hashed.load(Ordering::Relaxed), self.total)
+    }
+
+    /// Where the manifest was written, or why it wasn't, once the worker is done
+    pub fn poll(&self) -> Option<Result<PathBuf, String>> {
+        match self.result.try_recv() {
+            Ok(saved) => Some(saved),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err("the manifest writer stopped".to_string())),
+        }
+    }
+}
+
+impl Drop for ManifestWriter {
+    /// A cancelled apply with nothing gone yet needs no manifest, so the worker
+    /// stops between files
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Directory holding backup manifests (`<data dir>/fswp/manifests`)
+pub fn manifests_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| crate::profile::scoped(dir.join("fswp")).join("manifests"))
+}
+
+/// The most recently written manifest in `dir`
+pub fn latest(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .max()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::FileEntry;
+    use chrono::TimeZone;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_manifest_round_trip_and_restore_plan() {
+        let temp_dir = TempDir::new().unwrap();
+        let files: Vec<FileEntry> = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, name).unwrap();
+                FileEntry::from_path(&path).unwrap()
+            })
+            .collect();
+        let mut state = AppState::new(files);
+        state.engine.set_dry_run(true);
+        let archive = PathBuf::from("/archive");
+        state
+            .record_batch(&[
+                (0, Decision::Trash),
+                (1, Decision::Move(archive.clone())),
+                (2, Decision::Keep),
+            ])
+            .unwrap();
+
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap();
+        let manifest = Manifest::build(&state, temp_dir.path(), HashAlgorithm::Blake3, now);
+        assert_eq!(manifest.entries.len(), 2);
+        assert_eq!(
+            manifest.entries[0].hash.as_deref(),
+            Some(HashAlgorithm::Blake3.hash_bytes(b"a.txt").as_str())
+        );
+        assert_eq!(manifest.entries[1].moved_to, Some(archive.join("b.txt")));
+        assert_eq!(
+            manifest.entries[0].unchanged(HashAlgorithm::Blake3),
+            Some(true)
+        );
+
+        let dir = temp_dir.path().join("manifests");
+        let saved = manifest.save_to(&dir).unwrap();
+        assert_eq!(saved, dir.join("20240501-093000.json"));
+        assert_eq!(Manifest::load(&saved).unwrap(), manifest);
+        fs::write(dir.join("20230101-000000.json"), "{}").unwrap();
+        assert_eq!(latest(&dir), Some(saved.clone()));
+
+        // A manifest from before versions were recorded reads as the current one;
+        // one from a newer fswp is refused
+        let text = fs::read_to_string(&saved).unwrap();
+        assert!(text.contains("\"schema_version\": 1"));
+        let older = dir.join("older.json");
+        fs::write(&older, text.replace("\"schema_version\": 1,", "")).unwrap();
+        assert_eq!(Manifest::load(&older).unwrap(), manifest);
+        let newer = dir.join("newer.json");
+        fs::write(
+            &newer,
+            text.replace("\"schema_version\": 1", "\"schema_version\": 9"),
+        )
+        .unwrap();
+        assert!(Manifest::load(&newer)
+            .unwrap_err()
+            .to_string()
+            .contains("schema version 9"));
+
+        let trashed = [
+            TrashedFile {
+                path: manifest.entries[0].path.clone(),
+                deleted: now - chrono::Duration::days(30),
+            },
+            TrashedFile {
+                path: manifest.entries[0].path.clone(),
+                deleted: now,
+            },
+            TrashedFile {
+                path: manifest.entries[0].path.clone(),
+                deleted: now + chrono::Duration::hours(1),
+            },
+        ];
+        // The latest trashing is another file that took the same name, and the
+        // earliest went before the manifest was written
+        let holds = |position: usize, _: &ManifestEntry| position != 2;
+        let moved = archive.join("b.txt");
+        let plan = manifest.plan(|path| path == moved, &trashed, holds);
+        assert_eq!(
+            plan,
+            [RestoreStep::FromTrash(1), RestoreStep::MoveBack(moved)]
+        );
+        assert_eq!(
+            manifest.plan(|_| false, &trashed, |position, _| position == 0)[0],
+            RestoreStep::Missing
+        );
+        assert_eq!(
+            manifest.plan(|_| false, &[], holds),
+            [RestoreStep::Missing, RestoreStep::Missing]
+        );
+        let original = manifest.entries[0].path.clone();
+        assert_eq!(
+            manifest.plan(|path| path == original, &[], holds)[0],
+            RestoreStep::InPlace
+        );
+    }
+
+    #[test]
+    fn test_manifest_writer_hashes_and_saves_in_background() {
+        let temp_dir = TempDir::new().unwrap();
+        let files: Vec<FileEntry> = ["a.txt", "b.txt"]
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, name).unwrap();
+                FileEntry::from_path(&path).unwrap()
+            })
+            .collect();
+        let mut state = AppState::new(files);
+        state.engine.set_dry_run(true);
+        state
+            .record_batch(&[(0, Decision::Trash), (1, Decision::Trash)])
+            .unwrap();
+
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap();
+        let dir = temp_dir.path().join("manifests");
+        let writer = ManifestWriter::start(
+            &state,
+            temp_dir.path(),
+            HashAlgorithm::Blake3,
+            now,
+            Some(dir.clone()),
+        );
+        let started = std::time::Instant::now();
+        let saved = loop {
+            if let Some(saved) = writer.poll() {
+                break saved.unwrap();
+            }
+            assert!(started.elapsed() < std::time::Duration::from_secs(10));
+            thread::sleep(std::time::Duration::from_millis(5));
+        };
+        assert_eq!(writer.progress(), (2, 2));
+        assert_eq!(saved, dir.join("20240501-093000.json"));
+        assert_eq!(
+            Manifest::load(&saved).unwrap(),
+            Manifest::build(&state, temp_dir.path(), HashAlgorithm::Blake3, now)
+        );
+    }
+}
@@ -0,0 +1,177 @@
+//! Module for the summary left in a reviewed directory
+//!
+//! With `--leave-summary`, a session that took files out of the directory appends
+//! a section to `FILE_TINDER_SUMMARY.md` there: when the cleanup happened and
+//! which files went to the trash or were moved elsewhere. Whoever browses the
+//! folder later can see that a cleanup happened and what it took. Earlier
+//! sections are kept, so the file reads as a log of cleanups; discovery never
+//! lists it for review.
+
+use crate::domain::{AppState, ApplyOutcome, Decision};
+use crate::tui::format_file_size;
+use chrono::{DateTime, Local, Utc};
+use std::fmt::Write as _;
+use std::fs::OpenOptions;
+use std::io::{self, Write as _};
+use std::path::{Path, PathBuf};
+
+/// Name of the summary file in the reviewed directory
+pub const SUMMARY_FILE: &str = "FILE_TINDER_SUMMARY.md";
+
+/// A file a session took out of the directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Removal {
+    /// Path relative to the reviewed directory
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: DateTime<Utc>,
+    /// Where it was moved; `None` when it went to the system trash
+    pub moved_to: Option<PathBuf>,
+}
+
+/// The files moved to the system trash (per `results`) or moved elsewhere, in
+/// scan order
+pub fn removals(
+    state: &AppState,
+    directory: &Path,
+    results: &[(usize, ApplyOutcome)],
+) -> Vec<Removal> {
+    (0..state.files.len())
+        .filter_map(|index| {
+            let moved_to = match state.decision_of(index)? {
+                Decision::Trash
+                    if results
+                        .iter()
+                        .any(|(i, outcome)| *i == index && *outcome == ApplyOutcome::Trashed) =>
+                {
+                    None
+                }
+                Decision::Move(dir) => Some(dir.clone()),
+                _ => return None,
+            };
+            let file = &state.files[index];
+            Some(Removal {
+                path: file
+                    .path
+                    .strip_prefix(directory)
+                    .unwrap_or(&file.path)
+                    .to_path_buf(),
+                size: file.size,
+                modified: file.modified_date,
+                moved_to,
+            })
+        })
+        .collect()
+}
+
+/// One cleanup's section of the summary, dated `now`
+pub fn to_markdown(removals: &[Removal], now: DateTime<Local>) -> String {
+    let trashed = removals.iter().filter(|r| r.moved_to.is_none()).count();
+    let mut out = format!(
+        "## {}\n\n{} trashed, {} moved elsewhere, {} in all.\n\n\
+         | File | Size | Last modified | Went to |\n|---|---:|---|---|\n",
+        now.format("%Y-%m-%d %H:%M"),
+        trashed,
+        removals.len() - trashed,
+        format_file_size(removals.iter().map(|r| r.size).sum())
+    );
+    for removal in removals {
+        let destination = match removal.moved_to {
+            Some(ref dir) => cell(&dir.display().to_string()),
+            None => "Trash".to_string(),
+        };
+        let _ = writeln!(
+            out,
+            "| {} | {} | {} | {} |",
+            cell(&removal.path.display().to_string()),
+            format_file_size(removal.size),
+            removal.modified.format("%Y-%m-%d"),
+            destination
+        );
+    }
+    out
+}
+
+/// Appends the section for `removals` to the directory's summary file, starting
+/// the file if there is none; returns its path, or `None` when nothing was removed
+pub fn append(
+    directory: &Path,
+    removals: &[Removal],
+    now: DateTime<Local>,
+) -> io::Result<Option<PathBuf>> {
+    if removals.is_empty() {
+        return Ok(None);
+    }
+    let path = directory.join(SUMMARY_FILE);
+    let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+    let mut text = if file.metadata()?.len() == 0 {
+        "# Cleanups with fswp\n\nFiles reviewed with fswp and taken out of this folder.\n"
+            .to_string()
+    } else {
+        String::new()
+    };
+    text.push('\n');
+    text.push_str(&to_markdown(removals, now));
+    file.write_all(text.as_bytes())?;
+    Ok(Some(path))
+}
+
+/// Text for a Markdown table cell, with pipes escaped
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::FileEntry;
+    use chrono::TimeZone;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_removals_and_summary_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let files: Vec<FileEntry> = ["old|draft.txt", "photo.jpg", "keep.txt", "failed.txt"]
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                fs::write(&path, "1234").unwrap();
+                FileEntry::from_path(&path).unwrap()
+            })
+            .collect();
+        let mut state = AppState::new(files);
+        state.engine.set_dry_run(true);
+        let archive = PathBuf::from("/archive");
+        state
+            .record_batch(&[
+                (0, Decision::Trash),
+                (1, Decision::Move(archive.clone())),
+                (2, Decision::Keep),
+                (3, Decision::Trash),
+            ])
+            .unwrap();
+        let results = [
+            (0, ApplyOutcome::Trashed),
+            (3, ApplyOutcome::Failed("busy".to_string())),
+        ];
+
+        let removals = removals(&state, temp_dir.path(), &results);
+        let paths: Vec<&Path> = removals.iter().map(|r| r.path.as_path()).collect();
+        assert_eq!(paths, [Path::new("old|draft.txt"), Path::new("photo.jpg")]);
+        assert_eq!(removals[1].moved_to, Some(archive));
+
+        let now = Local.with_ymd_and_hms(2024, 5, 1, 9, 30, 0).unwrap();
+        let path = append(temp_dir.path(), &removals, now).unwrap().unwrap();
+        append(temp_dir.path(), &removals[..1], now).unwrap();
+        let text = fs::read_to_string(path).unwrap();
+        assert!(text.starts_with("# Cleanups with fswp\n"));
+        assert_eq!(text.matches("# Cleanups").count(), 1);
+        assert_eq!(text.matches("## 2024-05-01 09:30").count(), 2);
+        assert!(text.contains("1 trashed, 1 moved elsewhere, 8 B in all."));
+        assert!(text.contains("| old\\|draft.txt | 4 B |"));
+        assert!(text.contains("| /archive |"));
+
+        assert_eq!(append(temp_dir.path(), &[], now).unwrap(), None);
+    }
+}
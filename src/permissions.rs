@@ -0,0 +1,256 @@
+//! Module for file ownership and "can this actually be trashed" checks
+//!
+//! Moving a file to the trash removes its directory entry, which needs write
+//! permission on the parent directory, not on the file itself. These checks run
+//! up front so the user finds out before deciding, not when the move fails.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Sticky bit in `st_mode` (S_ISVTX, whose libc type differs across platforms)
+#[cfg(unix)]
+const STICKY_BIT: u32 = 0o1000;
+
+/// Owner and group names of a file (numeric ids when a name can't be resolved)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ownership {
+    pub owner: String,
+    pub group: String,
+}
+
+/// Why the current user can't move a file to the trash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeleteBlocker {
+    /// No write (or search) permission on the parent directory
+    ParentNotWritable,
+    /// The parent directory is sticky (like /tmp) and the file belongs to someone else
+    StickyNotOwner,
+}
+
+impl DeleteBlocker {
+    /// Short explanation shown in the header and confirmation dialog
+    pub fn reason(&self) -> &'static str {
+        match self {
+            DeleteBlocker::ParentNotWritable => "no write permission on parent",
+            DeleteBlocker::StickyNotOwner => "sticky directory, owned by another user",
+        }
+    }
+}
+
+/// Ownership and deletability of a single file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileAccess {
+    pub ownership: Option<Ownership>,
+    pub blocker: Option<DeleteBlocker>,
+}
+
+impl FileAccess {
+    pub fn can_delete(&self) -> bool {
+        self.blocker.is_none()
+    }
+}
+
+/// Inspects many files, caching per-directory and per-id lookups
+#[derive(Debug, Default)]
+pub struct AccessChecker {
+    parent_writable: HashMap<PathBuf, bool>,
+    #[cfg_attr(not(unix), allow(dead_code))]
+    names: HashMap<(bool, u32), String>,
+}
+
+impl AccessChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Looks up ownership and whether the current user can trash `path`
+    #[cfg(unix)]
+    pub fn inspect(&mut self, path: &Path) -> FileAccess {
+        use std::os::unix::fs::MetadataExt;
+
+        let Ok(metadata) = std::fs::symlink_metadata(path) else {
+            return FileAccess::default();
+        };
+        let ownership = Some(Ownership {
+            owner: self.name(false, metadata.uid()),
+            group: self.name(true, metadata.gid()),
+        });
+
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        let writable = *self
+            .parent_writable
+            .entry(parent.clone())
+            .or_insert_with(|| unix::can_write_dir(&parent));
+
+        let blocker = if !writable {
+            Some(DeleteBlocker::ParentNotWritable)
+        } else {
+            let euid = unix::effective_uid();
+            let sticky_blocked = std::fs::metadata(&parent).is_ok_and(|dir| {
+                dir.mode() & STICKY_BIT != 0
+                    && euid != 0
+                    && euid != metadata.uid()
+                    && euid != dir.uid()
+            });
+            sticky_blocked.then_some(DeleteBlocker::StickyNotOwner)
+        };
+
+        FileAccess { ownership, blocker }
+    }
+
+    #[cfg(not(unix))]
+    pub fn inspect(&mut self, path: &Path) -> FileAccess {
+        // Without POSIX ownership, a read-only parent is the only thing we can check
+        let parent = path.parent().unwrap_or(Path::new("."));
+        let writable = *self
+            .parent_writable
+            .entry(parent.to_path_buf())
+            .or_insert_with(|| {
+                std::fs::metadata(parent).map_or(true, |m| !m.permissions().readonly())
+            });
+        FileAccess {
+            ownership: None,
+            blocker: (!writable).then_some(DeleteBlocker::ParentNotWritable),
+        }
+    }
+
+    /// Resolves a user (`group == false`) or group id to a name, caching the result
+    #[cfg(unix)]
+    fn name(&mut self, group: bool, id: u32) -> String {
+        self.names
+            .entry((group, id))
+            .or_insert_with(|| {
+                let name = if group {
+                    unix::group_name(id)
+                } else {
+                    unix::user_name(id)
+                };
+                name.unwrap_or_else(|| id.to_string())
+            })
+            .clone()
+    }
+}
+
+/// Name of the user running the program
+#[cfg(unix)]
+pub fn current_user() -> String {
+    let uid = unix::effective_uid();
+    unix::user_name(uid).unwrap_or_else(|| uid.to_string())
+}
+
+/// Name of the user running the program
+#[cfg(not(unix))]
+pub fn current_user() -> String {
+    std::env::var("USERNAME").unwrap_or_else(|_| "unknown".to_string())
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::ffi::{CStr, CString};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::Path;
+
+    pub fn effective_uid() -> u32 {
+        // SAFETY: geteuid has no preconditions and cannot fail
+        unsafe { libc::geteuid() }
+    }
+
+    /// Whether entries can be removed from `dir` (needs write and search permission)
+    pub fn can_write_dir(dir: &Path) -> bool {
+        let Ok(c_path) = CString::new(dir.as_os_str().as_bytes()) else {
+            return false;
+        };
+        // SAFETY: c_path is a valid NUL-terminated string for the duration of the call
+        unsafe { libc::access(c_path.as_ptr(), libc::W_OK | libc::X_OK) == 0 }
+    }
+
+    pub fn user_name(uid: u32) -> Option<String> {
+        let mut buf = vec![0 as libc::c_char; 4096];
+        // SAFETY: passwd is plain old data; getpwuid_r only writes into pwd and buf
+        let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let rc =
+            unsafe { libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+        if rc != 0 || result.is_null() {
+            return None;
+        }
+        // SAFETY: on success pw_name points to a NUL-terminated string inside buf
+        let name = unsafe { CStr::from_ptr(pwd.pw_name) };
+        Some(name.to_string_lossy().into_owned())
+    }
+
+    pub fn group_name(gid: u32) -> Option<String> {
+        let mut buf = vec![0 as libc::c_char; 4096];
+        // SAFETY: group is plain old data; getgrgid_r only writes into grp and buf
+        let mut grp: libc::group = unsafe { std::mem::zeroed() };
+        let mut result = std::ptr::null_mut();
+        let rc =
+            unsafe { libc::getgrgid_r(gid, &mut grp, buf.as_mut_ptr(), buf.len(), &mut result) };
+        if rc != 0 || result.is_null() {
+            return None;
+        }
+        // SAFETY: on success gr_name points to a NUL-terminated string inside buf
+        let name = unsafe { CStr::from_ptr(grp.gr_name) };
+        Some(name.to_string_lossy().into_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_inspect_own_file_is_deletable() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("mine.txt");
+        fs::write(&path, b"data").unwrap();
+
+        let access = AccessChecker::new().inspect(&path);
+        assert!(access.can_delete());
+        #[cfg(unix)]
+        assert!(access.ownership.is_some());
+    }
+
+    #[test]
+    fn test_inspect_missing_file() {
+        let access = AccessChecker::new().inspect(Path::new("/nonexistent/file.txt"));
+        assert_eq!(access.ownership, None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_inspect_read_only_parent_is_blocked() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("locked.txt");
+        fs::write(&path, b"data").unwrap();
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o555)).unwrap();
+
+        let access = AccessChecker::new().inspect(&path);
+        fs::set_permissions(dir.path(), fs::Permissions::from_mode(0o755)).unwrap();
+
+        // root ignores directory permissions, so only assert when not running as root
+        if unix::effective_uid() != 0 {
+            assert_eq!(access.blocker, Some(DeleteBlocker::ParentNotWritable));
+        }
+    }
+
+    #[test]
+    fn test_current_user_is_not_empty() {
+        assert!(!current_user().is_empty());
+    }
+
+    #[test]
+    fn test_delete_blocker_reason() {
+        assert_eq!(
+            DeleteBlocker::ParentNotWritable.reason(),
+            "no write permission on parent"
+        );
+    }
+}
@@ -0,0 +1,973 @@
+//! Camera RAW and HEIC/HEIF photos, and the EXIF details of any photo
+//!
+//! The `image` crate decodes neither format. RAW files (`.cr2`, `.nef`, `.arw`,
+//! `.dng`) are TIFF containers that also carry a JPEG rendered by the camera, which
+//! is previewed instead of the sensor data. HEIC images are HEVC-coded, so only
+//! their dimensions and EXIF details are read. JPEG, PNG and WebP images are
+//! decoded by `image`; only their EXIF block is read here.
+//!
+//! EXIF blocks are parsed by `kamadak-exif`. A RAW file's IFDs are walked here
+//! instead, since the previews hang off SubIFDs that crate doesn't follow.
+//!
+//! RAW and HEIC files run to tens of megabytes, so they're never read whole: a
+//! RAW file's IFDs and a HEIC file's `meta` box are read a few KiB at a time as
+//! they're walked, then just the embedded JPEG or the EXIF item they point to.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{self, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::rc::Rc;
+
+use exif::{In, Tag, Value};
+
+/// Most IFDs visited in one file, so corrupt offsets can't loop forever
+const MAX_IFDS: usize = 32;
+/// Most values read from a single IFD entry
+const MAX_VALUES: usize = 64;
+/// Bytes read from the start of an image to find its EXIF block; a JPEG's APP1
+/// segment, which holds it, is at most 64 KiB
+const EXIF_HEAD_BYTES: u64 = 128 * 1024;
+/// Bytes read from the start of an embedded JPEG to check it can be decoded
+/// before the rest of it is read
+const JPEG_HEAD_BYTES: usize = 256 * 1024;
+/// Largest HEIC `meta` box read; phones write a few KiB
+const MAX_META_BYTES: usize = 1024 * 1024;
+/// Bytes a file is read in while its structures are walked
+const BLOCK_BYTES: usize = 4096;
+
+/// Photo formats that need more than `image::open` to preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhotoFormat {
+    /// Camera RAW in a TIFF container, previewed through its embedded JPEG
+    Raw,
+    /// HEIC / HEIF, shown as metadata only
+    Heif,
+}
+
+impl PhotoFormat {
+    pub fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
+            "cr2" | "nef" | "arw" | "dng" => Some(PhotoFormat::Raw),
+            "heic" | "heif" => Some(PhotoFormat::Heif),
+            _ => None,
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(Self::from_extension)
+    }
+}
+
+/// Details read from a photo's EXIF or container metadata
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PhotoInfo {
+    /// Full image size, when the file records it
+    pub dimensions: Option<(u32, u32)>,
+    pub make: Option<String>,
+    pub model: Option<String>,
+    /// When the photo was taken, as written in EXIF ("2024:06:01 12:30:00")
+    pub taken: Option<String>,
+    /// EXIF orientation (1-8)
+    pub orientation: Option<u16>,
+    /// Whether the photo records where it was taken (a GPS latitude)
+    pub gps: bool,
+}
+
+impl PhotoInfo {
+    /// Make and model, without repeating the make when the model already starts with it
+    pub fn camera(&self) -> Option<String> {
+        match (&self.make, &self.model) {
+            (Some(make), Some(model)) if model.starts_with(make.as_str()) => Some(model.clone()),
+            (Some(make), Some(model)) => Some(format!("{} {}", make, model)),
+            (make, model) => make.clone().or_else(|| model.clone()),
+        }
+    }
+}
+
+/// A RAW file's metadata and the largest embedded JPEG that can be decoded
+#[derive(Debug)]
+pub struct RawPhoto {
+    pub info: PhotoInfo,
+    pub preview: Option<Vec<u8>>,
+}
+
+/// Reads a TIFF-based RAW file; `None` when `data` isn't a TIFF container
+pub fn parse_raw(data: &[u8]) -> Option<RawPhoto> {
+    raw_from(&data, None)
+}
+
+/// Reads a TIFF-based RAW file's IFDs and the largest decodable JPEG of at most
+/// `max_preview` bytes it embeds, and nothing else of it; `None` when the file
+/// isn't a TIFF container
+pub fn read_raw(path: &Path, max_preview: Option<u64>) -> io::Result<Option<RawPhoto>> {
+    let _permit = crate::limits::open_files(1);
+    let file = FileSource::open(path)?;
+    Ok(raw_from(&file, max_preview))
+}
+
+fn raw_from(source: &dyn Source, max_preview: Option<u64>) -> Option<RawPhoto> {
+    let tiff = Tiff::new(source)?;
+    let scan = scan_tiff(&tiff);
+    let mut candidates = scan.previews;
+    candidates.retain(|&(_, len)| max_preview.is_none_or(|max| len as u64 <= max));
+    candidates.sort_by_key(|&(_, len)| std::cmp::Reverse(len));
+    candidates.dedup();
+    // Each is checked from its first bytes, so only the one previewed is read whole
+    let preview = candidates.into_iter().find_map(|(offset, len)| {
+        let head = source.bytes(offset, len.min(JPEG_HEAD_BYTES))?;
+        if !is_decodable_jpeg(&head) {
+            return None;
+        }
+        Some(source.bytes(offset, len)?.into_owned())
+    });
+    Some(RawPhoto {
+        info: scan.info,
+        preview,
+    })
+}
+
+/// Reads the dimensions and EXIF details of a HEIC / HEIF file
+pub fn parse_heif(data: &[u8]) -> Option<PhotoInfo> {
+    heif_from(&data)
+}
+
+/// Reads a HEIC / HEIF file's dimensions and EXIF details from its `meta` box and
+/// the EXIF item it points to; `None` when the file isn't one
+pub fn read_heif(path: &Path) -> io::Result<Option<PhotoInfo>> {
+    let _permit = crate::limits::open_files(1);
+    let file = FileSource::open(path)?;
+    Ok(heif_from(&file))
+}
+
+fn heif_from(source: &dyn Source) -> Option<PhotoInfo> {
+    let mut top = top_boxes(source);
+    if &top.next()?.0 != b"ftyp" {
+        return None;
+    }
+    let meta = top
+        .find(|(kind, _, _)| kind == b"meta")
+        .filter(|&(_, _, len)| len <= MAX_META_BYTES)
+        .and_then(|(_, offset, len)| source.bytes(offset, len));
+    // meta is a full box: version and flags come before its children
+    let meta = meta.as_deref().and_then(|meta| meta.get(4..));
+
+    // The EXIF item is found through the same `meta` box, so a file whose box is
+    // too big to read is left at that
+    let mut info = meta
+        .and_then(|_| {
+            let mut reader = BufReader::new(SourceReader { source, at: 0 });
+            exif_info(exif::Reader::new().read_from_container(&mut reader))
+        })
+        .unwrap_or_default();
+    let ispe = meta
+        .and_then(|meta| find_box(meta, b"iprp"))
+        .and_then(|iprp| find_box(iprp, b"ipco"))
+        .into_iter()
+        .flat_map(boxes)
+        .filter(|(kind, _)| kind == b"ispe")
+        .filter_map(|(_, body)| Some((be_u32(body, 4)?, be_u32(body, 8)?)))
+        // Tiles have their own sizes; the primary image is the largest
+        .max_by_key(|&(width, height)| u64::from(width) * u64::from(height));
+    info.dimensions = ispe.or(info.dimensions);
+    Some(info)
+}
+
+/// Reads the EXIF details near the start of a JPEG, PNG or WebP image; `None`
+/// when it has none or can't be read
+pub fn read_exif(path: &Path) -> Option<PhotoInfo> {
+    let mut head = Vec::new();
+    File::open(path)
+        .ok()?
+        .take(EXIF_HEAD_BYTES)
+        .read_to_end(&mut head)
+        .ok()?;
+    parse_exif(&head)
+}
+
+/// The EXIF details of a JPEG (APP1 segment), PNG (`eXIf` chunk) or WebP (`EXIF`
+/// chunk); `None` for images without EXIF and formats `kamadak-exif` can't read
+pub fn parse_exif(data: &[u8]) -> Option<PhotoInfo> {
+    exif_info(exif::Reader::new().read_from_container(&mut Cursor::new(data)))
+}
+
+/// The details in parsed EXIF fields; fields that failed to parse are skipped
+/// rather than losing the rest
+fn exif_info(exif: Result<exif::Exif, exif::Error>) -> Option<PhotoInfo> {
+    let exif = match exif {
+        Ok(exif) => exif,
+        Err(exif::Error::PartialResult(partial)) => partial.into_inner().0,
+        Err(_) => return None,
+    };
+    // The EXIF and GPS IFDs' fields belong to the primary image, along with IFD0's
+    let field = |tag: Tag| exif.get_field(tag, In::PRIMARY);
+    let number = |tag: Tag| field(tag).and_then(|field| field.value.get_uint(0));
+    let text = |tag: Tag| match &field(tag)?.value {
+        Value::Ascii(lines) => {
+            let text = String::from_utf8_lossy(lines.first()?);
+            let text = text.trim_end_matches('\0').trim();
+            (!text.is_empty()).then(|| text.to_string())
+        }
+        _ => None,
+    };
+    let size = |width: Tag, height: Tag| Some((number(width)?, number(height)?));
+    let dimensions = [
+        size(Tag::ImageWidth, Tag::ImageLength),
+        size(Tag::PixelXDimension, Tag::PixelYDimension),
+    ]
+    .into_iter()
+    .flatten()
+    .max_by_key(|&(width, height)| u64::from(width) * u64::from(height));
+    Some(PhotoInfo {
+        dimensions,
+        make: text(Tag::Make),
+        model: text(Tag::Model),
+        taken: text(Tag::DateTimeOriginal).or_else(|| text(Tag::DateTime)),
+        orientation: number(Tag::Orientation).map(|orientation| orientation as u16),
+        gps: field(Tag::GPSLatitude).is_some(),
+    })
+}
+
+/// Whether `data` is a JPEG the `image` crate can decode: baseline or progressive,
+/// not the lossless JPEG some RAW formats store sensor data in
+fn is_decodable_jpeg(data: &[u8]) -> bool {
+    if !data.starts_with(&[0xFF, 0xD8]) {
+        return false;
+    }
+    let mut at = 2;
+    while let (Some(0xFF), Some(&marker)) = (data.get(at), data.get(at + 1)) {
+        match marker {
+            0xC0..=0xC2 => return true,
+            // Fill byte before the marker
+            0xFF => {
+                at += 1;
+                continue;
+            }
+            // Any other frame type, or image data before a frame header
+            0xC3 | 0xC5..=0xC7 | 0xC9..=0xCB | 0xCD..=0xCF | 0xD9 | 0xDA => return false,
+            _ => {}
+        }
+        let Some(length) = be_u16(data, at + 2) else {
+            return false;
+        };
+        at += 2 + usize::from(length);
+    }
+    false
+}
+
+/// One 12-byte IFD entry; the value (or its offset) starts at `value_at`
+struct IfdEntry {
+    tag: u16,
+    kind: u16,
+    count: u32,
+    value_at: usize,
+}
+
+/// Where TIFF and box structures are read from: a buffer, or a file
+trait Source {
+    fn len(&self) -> usize;
+
+    /// `len` bytes at `offset`; `None` when they run past the end or can't be read
+    fn bytes(&self, offset: usize, len: usize) -> Option<Cow<'_, [u8]>>;
+}
+
+impl Source for &[u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn bytes(&self, offset: usize, len: usize) -> Option<Cow<'_, [u8]>> {
+        self.get(offset..offset.checked_add(len)?)
+            .map(Cow::Borrowed)
+    }
+}
+
+/// A `Source` as a stream, for `exif::Reader`
+struct SourceReader<'a> {
+    source: &'a dyn Source,
+    at: u64,
+}
+
+impl Read for SourceReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let at = usize::try_from(self.at).map_err(io::Error::other)?;
+        let len = buf.len().min(self.source.len().saturating_sub(at));
+        if len == 0 {
+            return Ok(0);
+        }
+        let bytes = self
+            .source
+            .bytes(at, len)
+            .ok_or_else(|| io::Error::from(io::ErrorKind::UnexpectedEof))?;
+        buf[..len].copy_from_slice(&bytes);
+        self.at += len as u64;
+        Ok(len)
+    }
+}
+
+impl Seek for SourceReader<'_> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let at = match pos {
+            SeekFrom::Start(at) => Some(at),
+            SeekFrom::End(delta) => (self.source.len() as u64).checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.at.checked_add_signed(delta),
+        };
+        self.at = at.ok_or_else(|| io::Error::from(io::ErrorKind::InvalidInput))?;
+        Ok(self.at)
+    }
+}
+
+/// A file read a block at a time as its structures are walked, at the configured
+/// read rate; ranges bigger than a few blocks are read directly
+struct FileSource {
+    file: RefCell<File>,
+    len: usize,
+    blocks: RefCell<HashMap<usize, Rc<Vec<u8>>>>,
+}
+
+impl FileSource {
+    fn open(path: &Path) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = usize::try_from(file.metadata()?.len()).map_err(io::Error::other)?;
+        Ok(Self {
+            file: RefCell::new(file),
+            len,
+            blocks: RefCell::new(HashMap::new()),
+        })
+    }
+
+    fn read(&self, offset: usize, len: usize) -> Option<Vec<u8>> {
+        crate::limits::throttle(len as u64);
+        let mut file = self.file.borrow_mut();
+        file.seek(SeekFrom::Start(offset as u64)).ok()?;
+        let mut buf = vec![0; len];
+        file.read_exact(&mut buf).ok()?;
+        Some(buf)
+    }
+
+    fn block(&self, number: usize) -> Option<Rc<Vec<u8>>> {
+        if let Some(block) = self.blocks.borrow().get(&number) {
+            return Some(Rc::clone(block));
+        }
+        let offset = number * BLOCK_BYTES;
+        let block = Rc::new(self.read(offset, BLOCK_BYTES.min(self.len.checked_sub(offset)?))?);
+        self.blocks.borrow_mut().insert(number, Rc::clone(&block));
+        Some(block)
+    }
+}
+
+impl Source for FileSource {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn bytes(&self, offset: usize, len: usize) -> Option<Cow<'_, [u8]>> {
+        let end = offset.checked_add(len)?;
+        if end > self.len {
+            return None;
+        }
+        if len > 4 * BLOCK_BYTES {
+            return self.read(offset, len).map(Cow::Owned);
+        }
+        let mut out = Vec::with_capacity(len);
+        let mut at = offset;
+        while at < end {
+            let block = self.block(at / BLOCK_BYTES)?;
+            let start = at % BLOCK_BYTES;
+            let take = (end - at).min(block.len() - start);
+            out.extend_from_slice(&block[start..start + take]);
+            at += take;
+        }
+        Some(Cow::Owned(out))
+    }
+}
+
+/// A TIFF structure with its byte order
+struct Tiff<'a> {
+    data: &'a dyn Source,
+    little_endian: bool,
+}
+
+impl<'a> Tiff<'a> {
+    fn new(data: &'a dyn Source) -> Option<Self> {
+        let little_endian = match data.bytes(0, 4)?.as_ref() {
+            b"II*\0" => true,
+            b"MM\0*" => false,
+            _ => return None,
+        };
+        Some(Self {
+            data,
+            little_endian,
+        })
+    }
+
+    fn u16_at(&self, offset: usize) -> Option<u16> {
+        let bytes = self.data.bytes(offset, 2)?.as_ref().try_into().ok()?;
+        Some(if self.little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    }
+
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        let bytes = self.data.bytes(offset, 4)?.as_ref().try_into().ok()?;
+        Some(if self.little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    }
+
+    /// Entries of the IFD at `offset`, and the offset of the next IFD in its chain
+    fn ifd(&self, offset: usize) -> Option<(Vec<IfdEntry>, Option<usize>)> {
+        let count = usize::from(self.u16_at(offset)?);
+        let entries = (0..count)
+            .filter_map(|i| {
+                let at = offset + 2 + i * 12;
+                Some(IfdEntry {
+                    tag: self.u16_at(at)?,
+                    kind: self.u16_at(at + 2)?,
+                    count: self.u32_at(at + 4)?,
+                    value_at: at + 8,
+                })
+            })
+            .collect();
+        let next = self
+            .u32_at(offset + 2 + count * 12)
+            .filter(|&next| next != 0)
+            .map(|next| next as usize);
+        Some((entries, next))
+    }
+
+    /// Values of a SHORT, LONG or IFD-pointer entry
+    fn values(&self, entry: &IfdEntry) -> Vec<u32> {
+        let size = match entry.kind {
+            3 => 2,
+            4 | 13 => 4,
+            _ => return Vec::new(),
+        };
+        let count = (entry.count as usize).min(MAX_VALUES);
+        let start = if size * count <= 4 {
+            entry.value_at
+        } else {
+            match self.u32_at(entry.value_at) {
+                Some(offset) => offset as usize,
+                None => return Vec::new(),
+            }
+        };
+        (0..count)
+            .map_while(|i| match size {
+                2 => self.u16_at(start + i * 2).map(u32::from),
+                _ => self.u32_at(start + i * 4),
+            })
+            .collect()
+    }
+
+    fn value(&self, entry: &IfdEntry) -> Option<u32> {
+        self.values(entry).first().copied()
+    }
+
+    /// Text of an ASCII entry, trimmed; `None` when empty
+    fn ascii(&self, entry: &IfdEntry) -> Option<String> {
+        if entry.kind != 2 {
+            return None;
+        }
+        let count = entry.count as usize;
+        let start = if count <= 4 {
+            entry.value_at
+        } else {
+            self.u32_at(entry.value_at)? as usize
+        };
+        let bytes = self.data.bytes(start, count)?;
+        let text = String::from_utf8_lossy(&bytes);
+        let text = text.trim_end_matches('\0').trim();
+        (!text.is_empty()).then(|| text.to_string())
+    }
+}
+
+/// What a walk over every IFD of a TIFF found
+#[derive(Debug, Default)]
+struct TiffScan {
+    info: PhotoInfo,
+    /// (offset, length) of each embedded JPEG candidate
+    previews: Vec<(usize, usize)>,
+}
+
+/// Visits IFD0, its chain, SubIFDs and the EXIF IFD, collecting metadata and JPEG candidates
+fn scan_tiff(tiff: &Tiff) -> TiffScan {
+    let mut scan = TiffScan::default();
+    let mut largest: Option<(u32, u32)> = None;
+    let mut modified = None;
+    let mut consider = |size: (Option<u32>, Option<u32>)| {
+        if let (Some(width), Some(height)) = size {
+            let area = |(w, h): (u32, u32)| u64::from(w) * u64::from(h);
+            if largest.is_none_or(|l| area(l) < area((width, height))) {
+                largest = Some((width, height));
+            }
+        }
+    };
+
+    let mut pending: Vec<usize> = tiff
+        .u32_at(4)
+        .map(|first| first as usize)
+        .into_iter()
+        .collect();
+    let mut visited = HashSet::new();
+    while let Some(offset) = pending.pop() {
+        if visited.len() >= MAX_IFDS || !visited.insert(offset) {
+            continue;
+        }
+        let Some((entries, next)) = tiff.ifd(offset) else {
+            continue;
+        };
+        pending.extend(next);
+
+        let (mut width, mut height, mut pixel_width, mut pixel_height) = (None, None, None, None);
+        let mut compression = None;
+        let (mut strip_offsets, mut strip_lengths) = (Vec::new(), Vec::new());
+        let (mut jpeg_offset, mut jpeg_length) = (None, None);
+        for entry in &entries {
+            match entry.tag {
+                0x0100 => width = tiff.value(entry),
+                0x0101 => height = tiff.value(entry),
+                0x0103 => compression = tiff.value(entry),
+                0x010F if scan.info.make.is_none() => scan.info.make = tiff.ascii(entry),
+                0x0110 if scan.info.model.is_none() => scan.info.model = tiff.ascii(entry),
+                0x0111 => strip_offsets = tiff.values(entry),
+                // IFD0 comes first, and its orientation is the one for the photo
+                0x0112 if scan.info.orientation.is_none() => {
+                    scan.info.orientation = tiff.value(entry).map(|o| o as u16)
+                }
+                0x0117 => strip_lengths = tiff.values(entry),
+                0x0132 if modified.is_none() => modified = tiff.ascii(entry),
+                0x0201 => jpeg_offset = tiff.value(entry),
+                0x0202 => jpeg_length = tiff.value(entry),
+                0x9003 if scan.info.taken.is_none() => scan.info.taken = tiff.ascii(entry),
+                0xA002 => pixel_width = tiff.value(entry),
+                0xA003 => pixel_height = tiff.value(entry),
+                // SubIFDs and the EXIF IFD
+                0x014A | 0x8769 => {
+                    pending.extend(tiff.values(entry).into_iter().map(|o| o as usize))
+                }
+                // The GPS IFD numbers its tags apart from the others, so it's only
+                // checked for a latitude rather than walked
+                0x8825 => {
+                    scan.info.gps |= tiff
+                        .value(entry)
+                        .and_then(|offset| tiff.ifd(offset as usize))
+                        .is_some_and(|(gps, _)| gps.iter().any(|entry| entry.tag == 0x0002))
+                }
+                _ => {}
+            }
+        }
+
+        consider((width, height));
+        consider((pixel_width, pixel_height));
+        if let (Some(offset), Some(length)) = (jpeg_offset, jpeg_length) {
+            scan.previews.push((offset as usize, length as usize));
+        }
+        // A single-strip JPEG-compressed image (6: old-style, 7: JPEG)
+        if matches!(compression, Some(6 | 7)) && strip_offsets.len() == 1 {
+            if let Some(&length) = strip_lengths.first() {
+                scan.previews
+                    .push((strip_offsets[0] as usize, length as usize));
+            }
+        }
+    }
+
+    scan.info.dimensions = largest;
+    scan.info.taken = scan.info.taken.take().or(modified);
+    scan
+}
+
+/// Iterates the ISO-BMFF boxes in `data` as (type, body)
+fn boxes(data: &[u8]) -> impl Iterator<Item = ([u8; 4], &[u8])> + '_ {
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        let kind: [u8; 4] = rest.get(4..8)?.try_into().ok()?;
+        let (header, size) = match be_u32(rest, 0)? {
+            0 => (8, rest.len() as u64),
+            1 => (16, be_u64(rest, 8)?),
+            size => (8, u64::from(size)),
+        };
+        let size = usize::try_from(size).ok()?;
+        let body = rest.get(header..size)?;
+        rest = &rest[size..];
+        Some((kind, body))
+    })
+}
+
+/// The top-level boxes of a file as (type, body offset, body length), read a
+/// header at a time
+fn top_boxes(source: &dyn Source) -> impl Iterator<Item = ([u8; 4], usize, usize)> + '_ {
+    let mut at = 0;
+    std::iter::from_fn(move || {
+        let header = source.bytes(at, 16.min(source.len().checked_sub(at)?))?;
+        let kind: [u8; 4] = header.get(4..8)?.try_into().ok()?;
+        let (header_len, size) = match be_u32(&header, 0)? {
+            0 => (8, (source.len() - at) as u64),
+            1 => (16, be_u64(&header, 8)?),
+            size => (8, u64::from(size)),
+        };
+        let size = usize::try_from(size).ok()?;
+        let body = at + header_len;
+        let end = at.checked_add(size)?;
+        if size < header_len || end > source.len() {
+            return None;
+        }
+        at = end;
+        Some((kind, body, size - header_len))
+    })
+}
+
+/// Body of the first box of `kind` directly inside `data`
+fn find_box<'a>(data: &'a [u8], kind: &[u8; 4]) -> Option<&'a [u8]> {
+    boxes(data)
+        .find(|(found, _)| found == kind)
+        .map(|(_, body)| body)
+}
+
+fn be_u16(data: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(
+        data.get(offset..offset.checked_add(2)?)?.try_into().ok()?,
+    ))
+}
+
+fn be_u32(data: &[u8], offset: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(
+        data.get(offset..offset.checked_add(4)?)?.try_into().ok()?,
+    ))
+}
+
+fn be_u64(data: &[u8], offset: usize) -> Option<u64> {
+    Some(u64::from_be_bytes(
+        data.get(offset..offset.checked_add(8)?)?.try_into().ok()?,
+    ))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use image::{ImageFormat, RgbImage};
+    use std::fs;
+    use std::io::Cursor;
+
+    /// A small baseline JPEG
+    pub(crate) fn jpeg(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbImage::from_pixel(width, height, image::Rgb([200, 40, 40]))
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Jpeg)
+            .unwrap();
+        bytes
+    }
+
+    /// IFD entries as (tag, kind, count, value-or-offset)
+    type Entry = (u16, u16, u32, u32);
+
+    fn ifd(entries: &[Entry], next: u32) -> Vec<u8> {
+        let mut out = (entries.len() as u16).to_le_bytes().to_vec();
+        for &(tag, kind, count, value) in entries {
+            out.extend(tag.to_le_bytes());
+            out.extend(kind.to_le_bytes());
+            out.extend(count.to_le_bytes());
+            if kind == 3 && count == 1 {
+                out.extend((value as u16).to_le_bytes());
+                out.extend([0, 0]);
+            } else {
+                out.extend(value.to_le_bytes());
+            }
+        }
+        out.extend(next.to_le_bytes());
+        out
+    }
+
+    /// A little-endian RAW laid out like a NEF: IFD0 holds the camera details, a
+    /// SubIFD the full-size JPEG preview, and the EXIF IFD the capture date
+    pub(crate) fn raw_file(preview: &[u8]) -> Vec<u8> {
+        const IFD0: u32 = 8;
+        const SUB_IFD: u32 = 200;
+        const EXIF_IFD: u32 = 300;
+        const TEXT: u32 = 400;
+        const JPEG: u32 = 512;
+        let mut data = vec![0u8; JPEG as usize];
+        data[..8].copy_from_slice(b"II*\0\x08\0\0\0");
+        let write = |data: &mut Vec<u8>, at: u32, bytes: &[u8]| {
+            data[at as usize..at as usize + bytes.len()].copy_from_slice(bytes);
+        };
+        let ifd0 = ifd(
+            &[
+                (0x0100, 4, 1, 160),
+                (0x0101, 4, 1, 120),
+                (0x010F, 2, 6, TEXT),
+                (0x0110, 2, 10, TEXT + 8),
+                (0x0112, 3, 1, 6),
+                (0x014A, 4, 1, SUB_IFD),
+                (0x8769, 4, 1, EXIF_IFD),
+            ],
+            0,
+        );
+        write(&mut data, IFD0, &ifd0);
+        let sub = ifd(
+            &[
+                (0x0100, 4, 1, 6048),
+                (0x0101, 4, 1, 4024),
+                (0x0201, 4, 1, JPEG),
+                (0x0202, 4, 1, preview.len() as u32),
+            ],
+            0,
+        );
+        write(&mut data, SUB_IFD, &sub);
+        let exif = ifd(&[(0x9003, 2, 20, TEXT + 24)], 0);
+        write(&mut data, EXIF_IFD, &exif);
+        write(&mut data, TEXT, b"NIKON\0");
+        write(&mut data, TEXT + 8, b"NIKON Z6\0\0");
+        write(&mut data, TEXT + 24, b"2024:06:01 12:30:00\0");
+        data.extend(preview);
+        data
+    }
+
+    /// A phone-style EXIF block: camera in IFD0, capture date in the EXIF IFD and
+    /// a latitude in the GPS IFD
+    fn exif_block() -> Vec<u8> {
+        const EXIF_IFD: u32 = 80;
+        const GPS_IFD: u32 = 100;
+        const TEXT: u32 = 120;
+        let mut data = vec![0u8; TEXT as usize + 72];
+        data[..8].copy_from_slice(b"II*\0\x08\0\0\0");
+        let write = |data: &mut Vec<u8>, at: u32, bytes: &[u8]| {
+            data[at as usize..at as usize + bytes.len()].copy_from_slice(bytes);
+        };
+        let ifd0 = ifd(
+            &[
+                (0x010F, 2, 6, TEXT),
+                (0x0110, 2, 10, TEXT + 8),
+                (0x8769, 4, 1, EXIF_IFD),
+                (0x8825, 4, 1, GPS_IFD),
+            ],
+            0,
+        );
+        write(&mut data, 8, &ifd0);
+        write(&mut data, EXIF_IFD, &ifd(&[(0x9003, 2, 20, TEXT + 24)], 0));
+        write(&mut data, GPS_IFD, &ifd(&[(0x0002, 5, 3, TEXT + 48)], 0));
+        write(&mut data, TEXT, b"Apple\0");
+        write(&mut data, TEXT + 8, b"iPhone 6\0\0");
+        write(&mut data, TEXT + 24, b"2014:08:17 18:04:51\0");
+        data
+    }
+
+    /// `jpeg(width, height)` with `exif_block()` in an APP1 segment after SOI
+    pub(crate) fn jpeg_with_exif(width: u32, height: u32) -> Vec<u8> {
+        let mut body = b"Exif\0\0".to_vec();
+        body.extend(exif_block());
+        let mut data = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        data.extend(((body.len() + 2) as u16).to_be_bytes());
+        data.extend(body);
+        data.extend(&jpeg(width, height)[2..]);
+        data
+    }
+
+    fn heif_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut out = ((body.len() + 8) as u32).to_be_bytes().to_vec();
+        out.extend(kind);
+        out.extend(body);
+        out
+    }
+
+    fn ispe(width: u32, height: u32) -> Vec<u8> {
+        let mut body = vec![0; 4];
+        body.extend(width.to_be_bytes());
+        body.extend(height.to_be_bytes());
+        heif_box(b"ispe", &body)
+    }
+
+    #[test]
+    fn test_photo_format_from_path() {
+        assert_eq!(
+            PhotoFormat::from_path(Path::new("a/IMG_1.CR2")),
+            Some(PhotoFormat::Raw)
+        );
+        assert_eq!(
+            PhotoFormat::from_path(Path::new("IMG_2.heic")),
+            Some(PhotoFormat::Heif)
+        );
+        assert_eq!(PhotoFormat::from_path(Path::new("photo.jpg")), None);
+    }
+
+    #[test]
+    fn test_parse_raw_finds_preview_and_exif() {
+        let preview = jpeg(32, 24);
+        let data = raw_file(&preview);
+        let raw = parse_raw(&data).unwrap();
+
+        assert_eq!(raw.preview.as_deref(), Some(preview.as_slice()));
+        assert_eq!(raw.info.dimensions, Some((6048, 4024)));
+        assert_eq!(raw.info.camera().as_deref(), Some("NIKON Z6"));
+        assert_eq!(raw.info.taken.as_deref(), Some("2024:06:01 12:30:00"));
+        assert_eq!(raw.info.orientation, Some(6));
+
+        assert!(parse_raw(b"not a tiff").is_none());
+    }
+
+    #[test]
+    fn test_parse_raw_skips_undecodable_jpeg() {
+        // A lossless JPEG header (SOF3), as used for sensor data
+        let lossless = [0xFF, 0xD8, 0xFF, 0xC3, 0x00, 0x02];
+        let raw_data = raw_file(&lossless);
+        let raw = parse_raw(&raw_data).unwrap();
+        assert!(raw.preview.is_none());
+        assert_eq!(raw.info.dimensions, Some((6048, 4024)));
+    }
+
+    #[test]
+    fn test_parse_raw_survives_ifd_loops() {
+        let mut data = b"II*\0\x08\0\0\0".to_vec();
+        // IFD0 whose next pointer is itself
+        data.extend(ifd(&[(0x0100, 4, 1, 10)], 8));
+        assert!(parse_raw(&data).unwrap().preview.is_none());
+    }
+
+    #[test]
+    fn test_read_raw_keeps_to_the_preview_limit() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("DSC_0001.NEF");
+        let preview = jpeg(32, 24);
+        fs::write(&path, raw_file(&preview)).unwrap();
+
+        let raw = read_raw(&path, None).unwrap().unwrap();
+        assert_eq!(raw.preview.as_deref(), Some(preview.as_slice()));
+        assert_eq!(raw.info.camera().as_deref(), Some("NIKON Z6"));
+
+        // Too big for the limit: the details still come through
+        let raw = read_raw(&path, Some(preview.len() as u64 - 1))
+            .unwrap()
+            .unwrap();
+        assert!(raw.preview.is_none());
+        assert_eq!(raw.info.dimensions, Some((6048, 4024)));
+    }
+
+    /// A HEIC file whose `meta` box lists an EXIF item in `iinf` and points `iloc`
+    /// at it, `gap` bytes past the end of the boxes before it
+    fn heif_file(ipco: &[u8], gap: usize) -> Vec<u8> {
+        let mut infe = vec![2, 0, 0, 0];
+        infe.extend(7u16.to_be_bytes());
+        infe.extend([0, 0]);
+        infe.extend(b"Exif");
+        let mut iinf = vec![0; 4];
+        iinf.extend(1u16.to_be_bytes());
+        iinf.extend(heif_box(b"infe", &infe));
+
+        let mut exif = b"\0\0\0\x06Exif\0\0II*\0\x08\0\0\0".to_vec();
+        exif.extend(ifd(&[(0x010F, 2, 6, 26)], 0));
+        exif.extend(b"Apple\0");
+
+        // Version 0, 4-byte offsets and lengths, no base offset, one item; the
+        // offset is filled in once the boxes before it are laid out
+        let mut iloc = vec![0, 0, 0, 0, 0x44, 0x00];
+        iloc.extend(1u16.to_be_bytes());
+        iloc.extend(7u16.to_be_bytes());
+        iloc.extend([0, 0]);
+        iloc.extend(1u16.to_be_bytes());
+        let offset_at = iloc.len();
+        iloc.extend(0u32.to_be_bytes());
+        iloc.extend((exif.len() as u32).to_be_bytes());
+
+        let mut meta = vec![0; 4];
+        meta.extend(heif_box(b"iinf", &iinf));
+        meta.extend(heif_box(b"iloc", &iloc));
+        meta.extend(heif_box(b"iprp", &heif_box(b"ipco", ipco)));
+
+        let ftyp = heif_box(b"ftyp", b"heic\0\0\0\0mif1heic");
+        // ftyp, meta, a free box of `gap` bytes, then the mdat header
+        let at = ftyp.len() + meta.len() + 8 + gap + 8 + 8;
+        let iloc_at = 4 + heif_box(b"iinf", &iinf).len() + 8 + offset_at;
+        meta[iloc_at..iloc_at + 4].copy_from_slice(&(at as u32).to_be_bytes());
+
+        let mut data = ftyp;
+        data.extend(heif_box(b"meta", &meta));
+        data.extend(heif_box(b"free", &vec![0; gap]));
+        data.extend(heif_box(b"mdat", &exif));
+        data
+    }
+
+    #[test]
+    fn test_parse_heif_reads_ispe_and_exif() {
+        let mut ipco = ispe(512, 512);
+        ipco.extend(ispe(4032, 3024));
+        let data = heif_file(&ipco, 0);
+
+        let info = parse_heif(&data).unwrap();
+        assert_eq!(info.dimensions, Some((4032, 3024)));
+        assert_eq!(info.make.as_deref(), Some("Apple"));
+
+        assert!(parse_heif(b"\0\0\0\x08free").is_none());
+    }
+
+    #[test]
+    fn test_read_heif_follows_iloc_to_the_exif_item() {
+        // Well past the blocks read for the boxes before it
+        let data = heif_file(&ispe(4032, 3024), EXIF_HEAD_BYTES as usize);
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("IMG_0001.HEIC");
+        fs::write(&path, &data).unwrap();
+
+        let info = read_heif(&path).unwrap().unwrap();
+        assert_eq!(info.dimensions, Some((4032, 3024)));
+        assert_eq!(info.make.as_deref(), Some("Apple"));
+    }
+
+    #[test]
+    fn test_parse_exif_reads_jpeg_png_and_webp() {
+        let info = parse_exif(&jpeg_with_exif(8, 8)).unwrap();
+        assert_eq!(info.camera().as_deref(), Some("Apple iPhone 6"));
+        assert_eq!(info.taken.as_deref(), Some("2014:08:17 18:04:51"));
+        assert!(info.gps);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        for (kind, body) in [(&b"IHDR"[..], vec![0; 13]), (b"eXIf", exif_block())] {
+            png.extend((body.len() as u32).to_be_bytes());
+            png.extend(kind);
+            png.extend(&body);
+            png.extend([0; 4]);
+        }
+        assert_eq!(parse_exif(&png), Some(info.clone()));
+
+        let mut chunk = b"EXIF".to_vec();
+        chunk.extend((exif_block().len() as u32).to_le_bytes());
+        chunk.extend(exif_block());
+        let mut webp = b"RIFF".to_vec();
+        webp.extend(((chunk.len() + 4) as u32).to_le_bytes());
+        webp.extend(b"WEBP");
+        webp.extend(chunk);
+        assert_eq!(parse_exif(&webp), Some(info));
+
+        // A screenshot: a plain JPEG without EXIF
+        assert_eq!(parse_exif(&jpeg(8, 8)), None);
+        assert_eq!(parse_exif(b"GIF89a"), None);
+    }
+
+    #[test]
+    fn test_photo_info_camera() {
+        let info = |make: Option<&str>, model: Option<&str>| PhotoInfo {
+            make: make.map(str::to_string),
+            model: model.map(str::to_string),
+            ..Default::default()
+        };
+        assert_eq!(
+            info(Some("Canon"), Some("Canon EOS R5"))
+                .camera()
+                .as_deref(),
+            Some("Canon EOS R5")
+        );
+        assert_eq!(
+            info(Some("SONY"), Some("ILCE-7M3")).camera().as_deref(),
+            Some("SONY ILCE-7M3")
+        );
+        assert_eq!(info(None, None).camera(), None);
+    }
+}
@@ -1,24 +1,74 @@
 // Preview module for generating file previews with syntax highlighting, images, and PDFs
 #![allow(dead_code)]
 
+use crate::archive;
+use crate::audio;
 use crate::domain::FileEntry;
-use image::{DynamicImage, GenericImageView, Pixel};
+use crate::photo::{self, PhotoFormat, PhotoInfo};
+use crate::tui::format_file_size;
+use image::codecs::gif::GifDecoder;
+use image::codecs::webp::WebPDecoder;
+use image::metadata::Orientation;
+use image::{AnimationDecoder, DynamicImage, GenericImageView, ImageFormat, ImageReader, Pixel};
 use pdfium_render::prelude::*;
 use ratatui::{
     style::{Color, Modifier, Style},
     text::{Line, Span},
 };
-use std::fs;
-use std::io;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Cursor};
 use std::path::Path;
+use std::time::Duration;
 use syntect::easy::HighlightLines;
 use syntect::highlighting::ThemeSet;
 use syntect::parsing::SyntaxSet;
 
-const MAX_PREVIEW_LINES: usize = 50;
-const MAX_IMAGE_WIDTH: u32 = 160;
+/// Lines read for a preview; the pane scrolls through what doesn't fit
+const MAX_PREVIEW_LINES: usize = 300;
+pub const MAX_IMAGE_WIDTH: u32 = 160;
 /// Height is halved because we render 2 pixels per terminal row using half-blocks
-const MAX_IMAGE_HEIGHT: u32 = 100;
+pub const MAX_IMAGE_HEIGHT: u32 = 100;
+/// Scale change per zoom in/out step
+const ZOOM_STEP: f64 = 1.5;
+/// Largest magnification (preview pixels per image pixel)
+const MAX_IMAGE_SCALE: f64 = 16.0;
+/// Frames counted before an animation is reported as "N+ frames"
+const MAX_ANIMATION_FRAMES: usize = 500;
+
+/// Limits applied while generating previews
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PreviewOptions {
+    pub max_lines: usize,
+    /// Cap on bytes read from text files (None reads the whole file)
+    pub max_text_bytes: Option<u64>,
+    /// Images and PDFs larger than this are not decoded (None means no limit)
+    pub max_media_bytes: Option<u64>,
+    /// How long to wait for a preview before showing a timeout error
+    pub timeout: Duration,
+}
+
+impl Default for PreviewOptions {
+    fn default() -> Self {
+        Self {
+            max_lines: MAX_PREVIEW_LINES,
+            max_text_bytes: None,
+            max_media_bytes: None,
+            timeout: Duration::from_secs(5),
+        }
+    }
+}
+
+impl PreviewOptions {
+    /// Options for slow (network) filesystems: small reads and a patient timeout
+    pub fn degraded() -> Self {
+        Self {
+            max_lines: 20,
+            max_text_bytes: Some(64 * 1024),
+            max_media_bytes: Some(4 * 1024 * 1024),
+            timeout: Duration::from_secs(30),
+        }
+    }
+}
 
 /// Represents preview content that can be either plain text or styled image lines
 #[derive(Debug, Clone)]
@@ -38,22 +88,62 @@ pub fn detect_syntax_from_extension(extension: &str) -> Option<String> {
         .map(|syntax| syntax.name.clone())
 }
 
-/// Reads the first N lines of a text file
+/// Reads the first N lines of a text file, stopping once they're read
 pub fn read_file_lines(path: &Path, max_lines: usize) -> io::Result<Vec<String>> {
-    let content = fs::read_to_string(path)?;
-    let lines: Vec<String> = content
+    use std::io::BufRead;
+
+    let lines = io::BufReader::new(File::open(path)?)
         .lines()
         .take(max_lines)
-        .map(|s| s.to_string())
-        .collect();
-
+        .collect::<io::Result<Vec<String>>>()?;
+    crate::limits::throttle(lines.iter().map(|line| line.len() as u64 + 1).sum());
     Ok(lines)
 }
 
+/// Reads the first N lines of a text file, reading at most `max_bytes` from disk
+pub fn read_file_head(path: &Path, max_lines: usize, max_bytes: u64) -> io::Result<Vec<String>> {
+    use std::io::Read;
+
+    let mut buf = Vec::new();
+    fs::File::open(path)?
+        .take(max_bytes)
+        .read_to_end(&mut buf)?;
+    crate::limits::throttle(buf.len() as u64);
+
+    // A multi-byte character cut off by the byte limit is not an encoding error
+    let content = match String::from_utf8(buf) {
+        Ok(content) => content,
+        Err(e) if e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?
+        }
+        Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e)),
+    };
+
+    Ok(content
+        .lines()
+        .take(max_lines)
+        .map(|s| s.to_string())
+        .collect())
+}
+
 /// Generates a syntax-highlighted preview for a text file
 pub fn generate_text_preview(file_entry: &FileEntry) -> io::Result<Vec<String>> {
+    generate_text_preview_with_options(file_entry, &PreviewOptions::default())
+}
+
+/// Generates a text preview within the given read limits
+pub fn generate_text_preview_with_options(
+    file_entry: &FileEntry,
+    options: &PreviewOptions,
+) -> io::Result<Vec<String>> {
     // Read file content with line limit
-    let lines = read_file_lines(&file_entry.path, MAX_PREVIEW_LINES)?;
+    let lines = match options.max_text_bytes {
+        Some(max_bytes) => read_file_head(&file_entry.path, options.max_lines, max_bytes)?,
+        None => read_file_lines(&file_entry.path, options.max_lines)?,
+    };
 
     // Try to detect syntax from extension
     let extension = file_entry
@@ -99,9 +189,162 @@ pub fn generate_text_preview(file_entry: &FileEntry) -> io::Result<Vec<String>>
 
 /// Loads an image from a file path
 pub fn load_image(path: &Path) -> io::Result<DynamicImage> {
+    if crate::limits::current().max_image_bytes.is_some() {
+        if let Ok(dimensions) = image::image_dimensions(path) {
+            check_image_memory(dimensions)?;
+        }
+    }
     image::open(path).map_err(|e| io::Error::other(format!("Image loading error: {}", e)))
 }
 
+/// Decodes the JPEG a camera embedded in a RAW file, turned upright, with the file's
+/// metadata. Only the file's IFDs and that JPEG are read, and with `max_bytes` the
+/// largest JPEG within it is used.
+pub fn load_raw_preview(
+    path: &Path,
+    max_bytes: Option<u64>,
+) -> io::Result<(DynamicImage, PhotoInfo)> {
+    let raw = photo::read_raw(path, max_bytes)?
+        .ok_or_else(|| io::Error::other("Not a TIFF-based RAW file"))?;
+    let jpeg = raw.preview.ok_or_else(|| match max_bytes {
+        Some(_) => io::Error::other("No embedded JPEG preview small enough for degraded mode"),
+        None => io::Error::other("No embedded JPEG preview found"),
+    })?;
+    if let Ok(dimensions) =
+        ImageReader::with_format(Cursor::new(&jpeg), ImageFormat::Jpeg).into_dimensions()
+    {
+        check_image_memory(dimensions)?;
+    }
+    let mut img = image::load_from_memory_with_format(&jpeg, ImageFormat::Jpeg)
+        .map_err(|e| io::Error::other(format!("Image loading error: {}", e)))?;
+    if let Some(orientation) = raw
+        .info
+        .orientation
+        .and_then(|o| Orientation::from_exif(o as u8))
+    {
+        img.apply_orientation(orientation);
+    }
+    Ok((img, raw.info))
+}
+
+/// Refuses to decode an image bigger than the `max_image_memory` limit allows
+fn check_image_memory((width, height): (u32, u32)) -> io::Result<()> {
+    if crate::limits::image_fits(width, height) {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "Image too large to preview ({}x{} is over the max_image_memory limit)",
+            width, height
+        )))
+    }
+}
+
+/// Frame count and total duration of an animated image
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AnimationInfo {
+    pub frames: usize,
+    pub duration: Duration,
+    /// Counting stopped at `MAX_ANIMATION_FRAMES`
+    pub truncated: bool,
+}
+
+impl AnimationInfo {
+    /// e.g. "24 frames · 2.4 s"
+    pub fn label(&self) -> String {
+        let more = if self.truncated { "+" } else { "" };
+        format!(
+            "{}{} frames · {:.1} s{}",
+            self.frames,
+            more,
+            self.duration.as_secs_f64(),
+            more
+        )
+    }
+}
+
+/// Counts the frames of an animated GIF or WebP; `None` for still images.
+///
+/// Each frame is decoded to read its delay, so counting stops at `MAX_ANIMATION_FRAMES`.
+pub fn read_animation(path: &Path) -> Option<AnimationInfo> {
+    let ext = path.extension()?.to_str()?.to_lowercase();
+    let reader = BufReader::new(File::open(path).ok()?);
+    let frames = match ext.as_str() {
+        "gif" => GifDecoder::new(reader).ok()?.into_frames(),
+        "webp" => {
+            let decoder = WebPDecoder::new(reader).ok()?;
+            if !decoder.has_animation() {
+                return None;
+            }
+            decoder.into_frames()
+        }
+        _ => return None,
+    };
+
+    let mut info = AnimationInfo {
+        frames: 0,
+        duration: Duration::ZERO,
+        truncated: false,
+    };
+    for frame in frames {
+        if info.frames == MAX_ANIMATION_FRAMES {
+            info.truncated = true;
+            break;
+        }
+        // A damaged frame ends the count; the first frame is still previewed
+        let Ok(frame) = frame else {
+            break;
+        };
+        info.frames += 1;
+        info.duration += Duration::from(frame.delay());
+    }
+    (info.frames > 1).then_some(info)
+}
+
+/// What's known about an image besides its pixels
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImageDetails {
+    /// Camera metadata of RAW and HEIC photos, and the EXIF details of other images
+    pub photo: Option<PhotoInfo>,
+    /// Length of animated GIF / WebP images
+    pub animation: Option<AnimationInfo>,
+}
+
+/// Loads any previewable image: the first frame of animations, the embedded
+/// JPEG of RAW files, and whatever details the file carries
+pub fn load_preview_image(path: &Path) -> io::Result<(DynamicImage, ImageDetails)> {
+    load_preview_image_within(path, None)
+}
+
+/// `load_preview_image`, using a RAW file's largest embedded JPEG of at most
+/// `max_raw_preview` bytes
+fn load_preview_image_within(
+    path: &Path,
+    max_raw_preview: Option<u64>,
+) -> io::Result<(DynamicImage, ImageDetails)> {
+    match PhotoFormat::from_path(path) {
+        Some(PhotoFormat::Raw) => load_raw_preview(path, max_raw_preview).map(|(img, info)| {
+            let details = ImageDetails {
+                photo: Some(info),
+                ..Default::default()
+            };
+            (img, details)
+        }),
+        Some(PhotoFormat::Heif) => Err(io::Error::other("HEIC images can't be decoded")),
+        None => {
+            let img = load_image(path)?;
+            let details = ImageDetails {
+                // The decoded size is the one to show, whatever EXIF says
+                photo: photo::read_exif(path).map(|info| PhotoInfo {
+                    dimensions: None,
+                    ..info
+                }),
+                animation: read_animation(path),
+            };
+            Ok((img, details))
+        }
+    }
+}
+
 /// Calculates new dimensions to fit image within max width and height while preserving aspect ratio
 pub fn calculate_resize_dimensions(
     original_width: u32,
@@ -177,45 +420,336 @@ pub fn image_to_halfblock_lines(img: &DynamicImage, width: u32, height: u32) ->
     lines
 }
 
+/// How an image preview is scaled before any zoom steps
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ImageFit {
+    /// The whole image, never upscaled
+    #[default]
+    Fit,
+    /// The preview area filled, cropping what overflows
+    Fill,
+    /// One image pixel per preview pixel
+    Actual,
+}
+
+impl ImageFit {
+    /// Next mode in the fit → fill → 100% cycle
+    pub fn next(self) -> Self {
+        match self {
+            ImageFit::Fit => ImageFit::Fill,
+            ImageFit::Fill => ImageFit::Actual,
+            ImageFit::Actual => ImageFit::Fit,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ImageFit::Fit => "fit",
+            ImageFit::Fill => "fill",
+            ImageFit::Actual => "100%",
+        }
+    }
+}
+
+/// A zoom or pan request for the image preview
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageCommand {
+    ZoomIn,
+    ZoomOut,
+    /// Cycle fit / fill / 100%, dropping any zoom steps
+    CycleFit,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+}
+
+/// A scroll request for the preview pane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrollCommand {
+    /// A pane's height, keeping the last row in view
+    PageDown,
+    PageUp,
+    HalfPageDown,
+    HalfPageUp,
+}
+
+/// How far the current preview is scrolled, and the pane it was last drawn in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PreviewScroll {
+    /// First row shown
+    pub offset: u16,
+    /// Rows the pane showed when last drawn
+    pub page: u16,
+    /// Rows the preview took when last drawn, wrapped to the pane's width
+    pub rows: u16,
+}
+
+impl PreviewScroll {
+    /// Records the pane's height and the preview's wrapped rows, keeping the
+    /// offset within them
+    pub fn set_viewport(&mut self, page: u16, rows: u16) {
+        self.page = page;
+        self.rows = rows;
+        self.offset = self.offset.min(self.max_offset());
+    }
+
+    /// Whether part of the preview is out of view
+    pub fn overflows(&self) -> bool {
+        self.rows > self.page
+    }
+
+    pub fn apply(&mut self, command: ScrollCommand) {
+        let page = self.page.saturating_sub(1).max(1);
+        let half = (self.page / 2).max(1);
+        let offset = match command {
+            ScrollCommand::PageDown => self.offset.saturating_add(page),
+            ScrollCommand::PageUp => self.offset.saturating_sub(page),
+            ScrollCommand::HalfPageDown => self.offset.saturating_add(half),
+            ScrollCommand::HalfPageUp => self.offset.saturating_sub(half),
+        };
+        self.offset = offset.min(self.max_offset());
+    }
+
+    fn max_offset(&self) -> u16 {
+        self.rows.saturating_sub(self.page)
+    }
+}
+
+/// Zoom and pan applied to an image preview
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct ImageView {
+    pub fit: ImageFit,
+    /// Zoom steps on top of `fit`; each one scales by `ZOOM_STEP`
+    pub zoom_steps: i32,
+    /// Center of the visible region in image pixels (None is the image center)
+    pub center: Option<(f64, f64)>,
+}
+
+impl ImageView {
+    /// Preview pixels per image pixel for an image of `size` shown within `max`
+    pub fn scale(&self, size: (u32, u32), max: (u32, u32)) -> f64 {
+        let width_ratio = max.0 as f64 / size.0.max(1) as f64;
+        let height_ratio = max.1 as f64 / size.1.max(1) as f64;
+        let fit = width_ratio.min(height_ratio).min(1.0);
+        let base = match self.fit {
+            ImageFit::Fit => fit,
+            ImageFit::Fill => width_ratio.max(height_ratio),
+            ImageFit::Actual => 1.0,
+        };
+        // Zooming out never goes below showing the whole image
+        (base * ZOOM_STEP.powi(self.zoom_steps)).clamp(fit, MAX_IMAGE_SCALE.max(fit))
+    }
+
+    /// Visible part of the image as (x, y, width, height) in image pixels
+    pub fn region(&self, size: (u32, u32), max: (u32, u32)) -> (u32, u32, u32, u32) {
+        let scale = self.scale(size, max);
+        let width = ((max.0 as f64 / scale) as u32).clamp(1, size.0.max(1));
+        let height = ((max.1 as f64 / scale) as u32).clamp(1, size.1.max(1));
+        let (center_x, center_y) = self
+            .center
+            .unwrap_or((size.0 as f64 / 2.0, size.1 as f64 / 2.0));
+        let x = (center_x - width as f64 / 2.0).clamp(0.0, size.0.saturating_sub(width) as f64);
+        let y = (center_y - height as f64 / 2.0).clamp(0.0, size.1.saturating_sub(height) as f64);
+        (x as u32, y as u32, width, height)
+    }
+
+    /// Applies a zoom or pan to an image of `size` shown within `max`.
+    ///
+    /// Pans move a quarter of the visible region. The center is kept where the
+    /// region can actually reach, so panning back responds immediately.
+    pub fn apply(&mut self, command: ImageCommand, size: (u32, u32), max: (u32, u32)) {
+        let before = self.scale(size, max);
+        let (x, y, width, height) = self.region(size, max);
+        let mut center = (
+            x as f64 + width as f64 / 2.0,
+            y as f64 + height as f64 / 2.0,
+        );
+        match command {
+            ImageCommand::ZoomIn | ImageCommand::ZoomOut => {
+                let step = if command == ImageCommand::ZoomIn {
+                    1
+                } else {
+                    -1
+                };
+                self.zoom_steps += step;
+                // Undo steps that hit a scale limit so they don't pile up
+                if self.scale(size, max) == before {
+                    self.zoom_steps -= step;
+                }
+            }
+            ImageCommand::CycleFit => {
+                self.fit = self.fit.next();
+                self.zoom_steps = 0;
+            }
+            ImageCommand::PanLeft => center.0 -= width as f64 / 4.0,
+            ImageCommand::PanRight => center.0 += width as f64 / 4.0,
+            ImageCommand::PanUp => center.1 -= height as f64 / 4.0,
+            ImageCommand::PanDown => center.1 += height as f64 / 4.0,
+        }
+        self.center = Some(center);
+        let (x, y, width, height) = self.region(size, max);
+        self.center = Some((
+            x as f64 + width as f64 / 2.0,
+            y as f64 + height as f64 / 2.0,
+        ));
+    }
+
+    /// Whether the view differs from the plain fitted preview
+    pub fn is_zoomed(&self) -> bool {
+        self.fit != ImageFit::Fit || self.zoom_steps != 0
+    }
+}
+
 /// Generates an image preview using half-block character rendering for true color display
 pub fn generate_image_preview(file_entry: &FileEntry) -> io::Result<PreviewContent> {
-    let img = load_image(&file_entry.path)?;
-    let (original_width, original_height) = img.dimensions();
+    generate_image_preview_within(file_entry, None)
+}
 
-    // Calculate resize dimensions - note that the height will be halved in rendering
-    // because we use 2 pixels per terminal row
-    let (new_width, new_height) = calculate_resize_dimensions(
-        original_width,
-        original_height,
-        MAX_IMAGE_WIDTH,
-        MAX_IMAGE_HEIGHT,
-    );
+/// `generate_image_preview`, with `load_preview_image_within`'s limit on RAW files
+fn generate_image_preview_within(
+    file_entry: &FileEntry,
+    max_raw_preview: Option<u64>,
+) -> io::Result<PreviewContent> {
+    if PhotoFormat::from_path(&file_entry.path) == Some(PhotoFormat::Heif) {
+        return generate_heif_preview(file_entry);
+    }
+    let (img, details) = load_preview_image_within(&file_entry.path, max_raw_preview)?;
+    Ok(render_image_preview(
+        file_entry,
+        &img,
+        &details,
+        &ImageView::default(),
+    ))
+}
 
-    // Create header lines with image info
+/// HEIC images are HEVC-coded and can't be decoded, so only their metadata is shown
+fn generate_heif_preview(file_entry: &FileEntry) -> io::Result<PreviewContent> {
+    let info = photo::read_heif(&file_entry.path)?.unwrap_or_default();
+    let dimensions = info.dimensions;
+    let details = ImageDetails {
+        photo: Some(info),
+        ..Default::default()
+    };
+    let mut lines = image_header(file_entry, dimensions, &details);
+    lines.extend([
+        Line::from(""),
+        Line::from("[HEIC decoding isn't built in]"),
+        Line::from(""),
+        Line::from("Press 'o' to open it in the default application."),
+    ]);
+    Ok(PreviewContent::Styled(lines))
+}
+
+/// Name, dimensions and size lines, plus camera details and animation length
+fn image_header(
+    file_entry: &FileEntry,
+    dimensions: Option<(u32, u32)>,
+    details: &ImageDetails,
+) -> Vec<Line<'static>> {
     let header_style = Style::default().add_modifier(Modifier::BOLD);
     let info_style = Style::default().fg(Color::Gray);
+    let dimensions = match dimensions {
+        Some((width, height)) => format!("Dimensions: {}×{} px", width, height),
+        None => "Dimensions: unknown".to_string(),
+    };
 
-    let mut lines: Vec<Line<'static>> = vec![
+    let mut lines = vec![
         Line::from(vec![
             Span::styled("Image: ", header_style),
             Span::styled(file_entry.name.clone(), Style::default().fg(Color::Cyan)),
         ]),
         Line::from(vec![
-            Span::styled(
-                format!("Dimensions: {}×{} px", original_width, original_height),
-                info_style,
-            ),
+            Span::styled(dimensions, info_style),
             Span::raw("  "),
             Span::styled(format!("Size: {} bytes", file_entry.size), info_style),
         ]),
-        Line::from(""),
     ];
+    if let Some(ref photo) = details.photo {
+        let details: Vec<String> = [
+            photo.camera().map(|camera| format!("Camera: {}", camera)),
+            photo
+                .taken
+                .as_ref()
+                .map(|taken| format!("Taken: {}", taken)),
+            photo.gps.then(|| "GPS: recorded".to_string()),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if !details.is_empty() {
+            lines.push(Line::from(Span::styled(details.join("  "), info_style)));
+        }
+    }
+    if let Some(animation) = details.animation {
+        lines.push(Line::from(Span::styled(
+            format!("Animation: {} (first frame shown)", animation.label()),
+            info_style,
+        )));
+    }
+    lines
+}
+
+/// Renders the part of `img` selected by `view`, with the image info header.
+///
+/// For RAW files `img` is the embedded preview, and `details.photo` supplies the
+/// full dimensions; for other photos it has just the EXIF details.
+pub fn render_image_preview(
+    file_entry: &FileEntry,
+    img: &DynamicImage,
+    details: &ImageDetails,
+    view: &ImageView,
+) -> PreviewContent {
+    let (original_width, original_height) = img.dimensions();
+    let size = (original_width, original_height);
+    let max = (MAX_IMAGE_WIDTH, MAX_IMAGE_HEIGHT);
+
+    // Output dimensions - note that the height will be halved in rendering
+    // because we use 2 pixels per terminal row
+    let scale = view.scale(size, max);
+    let (x, y, width, height) = view.region(size, max);
+    let new_width = ((width as f64 * scale) as u32).clamp(1, MAX_IMAGE_WIDTH);
+    let new_height = ((height as f64 * scale) as u32).clamp(1, MAX_IMAGE_HEIGHT);
+
+    // Create header lines with image info
+    let info_style = Style::default().fg(Color::Gray);
+    let photo = details.photo.as_ref();
+    let dimensions = photo.and_then(|photo| photo.dimensions).unwrap_or(size);
+    let mut lines = image_header(file_entry, Some(dimensions), details);
+    if PhotoFormat::from_path(&file_entry.path) == Some(PhotoFormat::Raw) {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "Preview: embedded {}×{} JPEG",
+                original_width, original_height
+            ),
+            info_style,
+        )));
+    }
+    if view.is_zoomed() {
+        lines.push(Line::from(Span::styled(
+            format!(
+                "Zoom: {} · {:.0}% · showing {}×{} at ({}, {})",
+                view.fit.label(),
+                scale * 100.0,
+                width,
+                height,
+                x,
+                y
+            ),
+            info_style,
+        )));
+    }
+    lines.push(Line::from(""));
 
     // Generate half-block image lines
-    let image_lines = image_to_halfblock_lines(&img, new_width, new_height);
+    if original_width == 0 || original_height == 0 {
+        return PreviewContent::Styled(lines);
+    }
+    let visible = img.crop_imm(x, y, width, height);
+    let image_lines = image_to_halfblock_lines(&visible, new_width, new_height);
     lines.extend(image_lines);
 
-    Ok(PreviewContent::Styled(lines))
+    PreviewContent::Styled(lines)
 }
 
 /// Attempts to create a Pdfium instance using explicit binding (no panic)
@@ -424,19 +958,200 @@ pub fn generate_pdf_preview(file_entry: &FileEntry) -> io::Result<PreviewContent
 
 /// Generates a preview for any file type
 pub fn generate_preview(file_entry: &FileEntry) -> io::Result<PreviewContent> {
+    generate_preview_with_options(file_entry, &PreviewOptions::default())
+}
+
+/// Generates a preview for any file type within the given limits
+pub fn generate_preview_with_options(
+    file_entry: &FileEntry,
+    options: &PreviewOptions,
+) -> io::Result<PreviewContent> {
     use crate::domain::FileType;
 
+    // RAW and HEIC files are read a structure at a time and paced as they're read,
+    // so a RAW's embedded JPEG is held to the media limit rather than the file
+    let photo = file_entry.file_type == FileType::Image
+        && PhotoFormat::from_path(&file_entry.path).is_some();
+    let is_media = matches!(file_entry.file_type, FileType::Image | FileType::Pdf) && !photo;
+    if is_media
+        && options
+            .max_media_bytes
+            .is_some_and(|max| file_entry.size > max)
+    {
+        return Ok(PreviewContent::Text(vec![
+            format!("{:?}: {}", file_entry.file_type, file_entry.name),
+            format!("Size: {} bytes", file_entry.size),
+            String::new(),
+            "[Preview skipped: file is too large to load in degraded mode]".to_string(),
+            String::new(),
+            "Press 'o' to open it in the default application.".to_string(),
+        ]));
+    }
+
+    // Media is decoded from the whole file, so its reads are paced up front
+    if is_media {
+        crate::limits::throttle(file_entry.size);
+    }
+
     match file_entry.file_type {
-        FileType::Text => generate_text_preview(file_entry).map(PreviewContent::Text),
-        FileType::Binary => Ok(PreviewContent::Text(vec![
+        FileType::Text => {
+            generate_text_preview_with_options(file_entry, options).map(PreviewContent::Text)
+        }
+        FileType::Archive | FileType::Binary
+            if archive::archive_kind(&file_entry.path).is_some() =>
+        {
+            generate_archive_preview(file_entry, options.max_lines)
+        }
+        FileType::Archive | FileType::Binary => Ok(PreviewContent::Text(vec![
             format!("Binary file: {}", file_entry.name),
             format!("Size: {} bytes", file_entry.size),
             String::new(),
             "[Binary content not displayed]".to_string(),
         ])),
-        FileType::Image => generate_image_preview(file_entry),
+        FileType::Image => generate_image_preview_within(file_entry, options.max_media_bytes),
         FileType::Pdf => generate_pdf_preview(file_entry),
+        FileType::Audio => Ok(generate_audio_preview(file_entry)),
+        FileType::Directory => generate_directory_preview(file_entry, options.max_lines),
+    }
+}
+
+/// The archive's entries and how much it compresses them, without decompressing
+/// more than listing needs; anything that can't be listed falls back to the
+/// binary summary
+fn generate_archive_preview(
+    file_entry: &FileEntry,
+    max_lines: usize,
+) -> io::Result<PreviewContent> {
+    let listing = match archive::list_archive(&file_entry.path, max_lines) {
+        Ok(listing) => listing,
+        Err(e) => {
+            return Ok(PreviewContent::Text(vec![
+                format!("Binary file: {}", file_entry.name),
+                format!("Size: {} bytes", file_entry.size),
+                String::new(),
+                format!("[Archive not listed: {}]", e),
+            ]))
+        }
+    };
+    let entries = match listing.total_entries {
+        Some(total) => format!("{} entries", total),
+        None => format!("{}+ entries", listing.entries.len()),
+    };
+    let mut lines = vec![
+        format!("{}: {}", listing.kind.label(), file_entry.name),
+        format!(
+            "{} (read {} of {})",
+            entries,
+            format_file_size(listing.bytes_read.min(file_entry.size)),
+            format_file_size(file_entry.size)
+        ),
+    ];
+    match listing.unpacked_size {
+        Some(_) if listing.kind == archive::ArchiveKind::Tar => {
+            lines.push("Not compressed".to_string())
+        }
+        Some(unpacked) if unpacked > 0 => lines.push(format!(
+            "{} unpacked, compressed to {}%",
+            format_file_size(unpacked),
+            file_entry.size * 100 / unpacked
+        )),
+        _ => {}
+    }
+    lines.push(String::new());
+    lines.extend(
+        listing
+            .entries
+            .iter()
+            .map(|entry| format!("{:>10}  {}", format_file_size(entry.size), entry.name)),
+    );
+    match listing.total_entries {
+        Some(total) if total > listing.entries.len() => {
+            lines.push(format!("… and {} more", total - listing.entries.len()))
+        }
+        None => lines.push("… and more (listing stopped partway)".to_string()),
+        _ => {}
+    }
+    Ok(PreviewContent::Text(lines))
+}
+
+/// The audio's tags, length, codec and cover art; a file whose headers can't be
+/// read gets the binary summary with the reason
+fn generate_audio_preview(file_entry: &FileEntry) -> PreviewContent {
+    let info = match audio::read_audio_info(&file_entry.path) {
+        Ok(info) => info,
+        Err(e) => {
+            return PreviewContent::Text(vec![
+                format!("Binary file: {}", file_entry.name),
+                format!("Size: {} bytes", file_entry.size),
+                String::new(),
+                format!("[Audio not read: {}]", e),
+            ])
+        }
+    };
+    let field = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    let art = match info.pictures {
+        0 => "none".to_string(),
+        1 => "embedded".to_string(),
+        n => format!("embedded ({} pictures)", n),
+    };
+    PreviewContent::Text(vec![
+        format!("Audio: {}", file_entry.name),
+        info.summary(),
+        format!("Size: {}", format_file_size(file_entry.size)),
+        String::new(),
+        format!("Title:     {}", field(&info.title)),
+        format!("Artist:    {}", field(&info.artist)),
+        format!("Album:     {}", field(&info.album)),
+        format!("Cover art: {}", art),
+    ])
+}
+
+/// The top of a directory's tree, at most `max_lines` entries of it. Only what's
+/// shown is read; the totals come from the background measurement (`DirSizer`)
+fn generate_directory_preview(
+    file_entry: &FileEntry,
+    max_lines: usize,
+) -> io::Result<PreviewContent> {
+    fs::read_dir(&file_entry.path)?;
+    let mut lines = vec![format!("Directory: {}", file_entry.name), String::new()];
+    // The limit counts tree lines, not the heading
+    let limit = max_lines + lines.len();
+    if !tree_lines(&file_entry.path, "", limit, &mut lines) {
+        lines.push("…".to_string());
     }
+    Ok(PreviewContent::Text(lines))
+}
+
+/// Appends `dir`'s entries, sorted by name and indented like `tree`, while `out`
+/// holds fewer than `limit` lines; false if some didn't fit
+fn tree_lines(dir: &Path, prefix: &str, limit: usize, out: &mut Vec<String>) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return true;
+    };
+    let mut entries: Vec<_> = entries.flatten().collect();
+    entries.sort_by_key(|entry| entry.file_name());
+    let count = entries.len();
+    for (i, entry) in entries.into_iter().enumerate() {
+        if out.len() >= limit {
+            return false;
+        }
+        let last = i + 1 == count;
+        let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
+        out.push(format!(
+            "{}{}{}{}",
+            prefix,
+            if last { "└── " } else { "├── " },
+            entry.file_name().to_string_lossy(),
+            if is_dir { "/" } else { "" }
+        ));
+        if is_dir {
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            if !tree_lines(&entry.path(), &prefix, limit, out) {
+                return false;
+            }
+        }
+    }
+    true
 }
 
 #[cfg(test)]
@@ -469,6 +1184,18 @@ mod tests {
         assert_eq!(syntax.unwrap(), "JavaScript");
     }
 
+    #[test]
+    fn test_read_file_lines_stops_after_the_lines_read() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut content = b"line 1\nline 2\n".to_vec();
+        content.extend([0xff, 0xfe, b'\n']);
+        fs::write(temp_file.path(), content).unwrap();
+
+        // The invalid UTF-8 further down is never read
+        let lines = read_file_lines(temp_file.path(), 2).unwrap();
+        assert_eq!(lines, ["line 1", "line 2"]);
+    }
+
     #[test]
     fn test_detect_syntax_from_extension_unknown() {
         let syntax = detect_syntax_from_extension("xyz123");
@@ -528,6 +1255,7 @@ mod tests {
             size: rust_code.len() as u64,
             modified_date: Utc::now(),
             file_type: FileType::Text,
+            disk: Default::default(),
         };
 
         // Copy to the expected path
@@ -546,9 +1274,9 @@ mod tests {
     fn test_generate_text_preview_respects_line_limit() {
         let temp_file = NamedTempFile::new().unwrap();
 
-        // Generate 100 lines
+        // Generate more lines than the limit
         let mut content = String::new();
-        for i in 1..=100 {
+        for i in 1..=MAX_PREVIEW_LINES + 50 {
             content.push_str(&format!("line {}\n", i));
         }
         fs::write(temp_file.path(), &content).unwrap();
@@ -559,6 +1287,7 @@ mod tests {
             size: content.len() as u64,
             modified_date: Utc::now(),
             file_type: FileType::Text,
+            disk: Default::default(),
         };
 
         let preview = generate_text_preview(&file_entry).unwrap();
@@ -567,6 +1296,65 @@ mod tests {
         assert_eq!(preview[49], "line 50");
     }
 
+    #[test]
+    fn test_read_file_head_stops_at_byte_limit() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), "first line\nsecond line\nthird line\n").unwrap();
+
+        let lines = read_file_head(temp_file.path(), 10, 14).unwrap();
+        assert_eq!(lines, vec!["first line", "sec"]);
+    }
+
+    #[test]
+    fn test_read_file_head_tolerates_cut_multibyte_char() {
+        let temp_file = NamedTempFile::new().unwrap();
+        fs::write(temp_file.path(), "añb").unwrap();
+
+        // "ñ" is two bytes; cutting inside it drops the partial character
+        let lines = read_file_head(temp_file.path(), 10, 2).unwrap();
+        assert_eq!(lines, vec!["a"]);
+    }
+
+    #[test]
+    fn test_generate_preview_degraded_skips_large_media() {
+        let entry = FileEntry {
+            path: PathBuf::from("/nonexistent/huge.png"),
+            name: "huge.png".to_string(),
+            size: 100 * 1024 * 1024,
+            modified_date: Utc::now(),
+            file_type: FileType::Image,
+            disk: Default::default(),
+        };
+
+        match generate_preview_with_options(&entry, &PreviewOptions::degraded()).unwrap() {
+            PreviewContent::Text(lines) => {
+                assert!(lines.iter().any(|l| l.contains("Preview skipped")));
+            }
+            _ => panic!("Expected Text content"),
+        }
+    }
+
+    #[test]
+    fn test_generate_preview_degraded_limits_lines() {
+        let temp_file = NamedTempFile::new().unwrap();
+        let content: String = (0..100).map(|i| format!("line {}\n", i)).collect();
+        fs::write(temp_file.path(), content).unwrap();
+        let entry = FileEntry {
+            path: temp_file.path().to_path_buf(),
+            name: "many.txt".to_string(),
+            size: 100,
+            modified_date: Utc::now(),
+            file_type: FileType::Text,
+            disk: Default::default(),
+        };
+
+        let options = PreviewOptions::degraded();
+        match generate_preview_with_options(&entry, &options).unwrap() {
+            PreviewContent::Text(lines) => assert_eq!(lines.len(), options.max_lines),
+            _ => panic!("Expected Text content"),
+        }
+    }
+
     #[test]
     fn test_generate_preview_binary() {
         let file_entry = FileEntry {
@@ -575,6 +1363,7 @@ mod tests {
             size: 1024,
             modified_date: Utc::now(),
             file_type: FileType::Binary,
+            disk: Default::default(),
         };
 
         let preview = generate_preview(&file_entry).unwrap();
@@ -588,6 +1377,64 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_generate_preview_audio_tags() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("download (3).mp3");
+        fs::write(&path, crate::audio::tests::mp3(400)).unwrap();
+        let file_entry = FileEntry::from_path(&path).unwrap();
+        assert_eq!(file_entry.file_type, FileType::Audio);
+
+        let PreviewContent::Text(lines) = generate_preview(&file_entry).unwrap() else {
+            panic!("audio previews are text");
+        };
+        assert_eq!(lines[0], "Audio: download (3).mp3");
+        assert!(lines[1].starts_with("MP3 · 0:10 · "));
+        assert!(lines.contains(&"Artist:    The Tide".to_string()));
+        assert!(lines.contains(&"Cover art: embedded".to_string()));
+
+        fs::write(&path, b"not really audio").unwrap();
+        let file_entry = FileEntry::from_path(&path).unwrap();
+        let PreviewContent::Text(lines) = generate_preview(&file_entry).unwrap() else {
+            panic!("audio previews are text");
+        };
+        assert!(lines[3].starts_with("[Audio not read: "));
+    }
+
+    #[test]
+    fn test_generate_preview_directory_tree() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project");
+        fs::create_dir_all(project.join("src")).unwrap();
+        fs::write(project.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(project.join("Cargo.toml"), "[package]").unwrap();
+        let entry = FileEntry::from_dir(&project).unwrap();
+
+        let PreviewContent::Text(lines) = generate_preview(&entry).unwrap() else {
+            panic!("Expected Text preview for a directory");
+        };
+        assert_eq!(
+            lines,
+            [
+                "Directory: project",
+                "",
+                "├── Cargo.toml",
+                "└── src/",
+                "    └── main.rs",
+            ]
+        );
+
+        let options = PreviewOptions {
+            max_lines: 2,
+            ..Default::default()
+        };
+        let PreviewContent::Text(lines) = generate_preview_with_options(&entry, &options).unwrap()
+        else {
+            panic!("Expected Text preview for a directory");
+        };
+        assert_eq!(lines[2..], ["├── Cargo.toml", "└── src/", "…"]);
+    }
+
     #[test]
     fn test_generate_preview_pdf_dispatches_correctly() {
         // Test that generate_preview correctly dispatches PDF files to generate_pdf_preview
@@ -598,6 +1445,7 @@ mod tests {
             size: 4096,
             modified_date: Utc::now(),
             file_type: FileType::Pdf,
+            disk: Default::default(),
         };
 
         let preview = generate_preview(&file_entry).unwrap();
@@ -721,6 +1569,277 @@ mod tests {
         assert_eq!(loaded.dimensions(), (10, 10));
     }
 
+    #[test]
+    fn test_preview_scroll_stays_within_preview() {
+        let mut scroll = PreviewScroll::default();
+        // Nothing drawn yet: nowhere to go
+        scroll.apply(ScrollCommand::PageDown);
+        assert_eq!(scroll.offset, 0);
+
+        scroll.set_viewport(20, 50);
+        assert!(scroll.overflows());
+        scroll.apply(ScrollCommand::PageDown);
+        assert_eq!(scroll.offset, 19);
+        scroll.apply(ScrollCommand::HalfPageDown);
+        assert_eq!(scroll.offset, 29);
+        scroll.apply(ScrollCommand::PageDown);
+        assert_eq!(scroll.offset, 30);
+        scroll.apply(ScrollCommand::HalfPageUp);
+        assert_eq!(scroll.offset, 20);
+        scroll.apply(ScrollCommand::PageUp);
+        scroll.apply(ScrollCommand::PageUp);
+        assert_eq!(scroll.offset, 0);
+
+        // A taller pane pulls the offset back so the end stays in view
+        scroll.set_viewport(20, 50);
+        scroll.apply(ScrollCommand::PageDown);
+        scroll.set_viewport(45, 50);
+        assert_eq!(scroll.offset, 5);
+        scroll.set_viewport(60, 50);
+        assert!(!scroll.overflows());
+        assert_eq!(scroll.offset, 0);
+    }
+
+    #[test]
+    fn test_image_view_fit_fill_actual() {
+        let size = (1600, 400);
+        let max = (160, 100);
+        let mut view = ImageView::default();
+        assert_eq!(view.scale(size, max), 0.1);
+        assert_eq!(view.region(size, max), (0, 0, 1600, 400));
+
+        view.apply(ImageCommand::CycleFit, size, max);
+        assert_eq!(view.fit, ImageFit::Fill);
+        assert_eq!(view.scale(size, max), 0.25);
+        // Filling the height crops the sides, centered
+        assert_eq!(view.region(size, max), (480, 0, 640, 400));
+
+        view.apply(ImageCommand::CycleFit, size, max);
+        assert_eq!(view.scale(size, max), 1.0);
+        assert_eq!(view.region(size, max), (720, 150, 160, 100));
+
+        view.apply(ImageCommand::CycleFit, size, max);
+        assert!(!view.is_zoomed());
+    }
+
+    #[test]
+    fn test_image_view_zoom_is_clamped() {
+        let size = (1600, 400);
+        let max = (160, 100);
+        let mut view = ImageView::default();
+
+        // Already showing the whole image
+        view.apply(ImageCommand::ZoomOut, size, max);
+        assert_eq!(view.zoom_steps, 0);
+
+        for _ in 0..50 {
+            view.apply(ImageCommand::ZoomIn, size, max);
+        }
+        assert_eq!(view.scale(size, max), MAX_IMAGE_SCALE);
+        let steps = view.zoom_steps;
+        view.apply(ImageCommand::ZoomOut, size, max);
+        assert_eq!(view.zoom_steps, steps - 1);
+        assert!(view.scale(size, max) < MAX_IMAGE_SCALE);
+    }
+
+    #[test]
+    fn test_image_view_pan_stays_inside_image() {
+        let size = (1600, 400);
+        let max = (160, 100);
+        let mut view = ImageView {
+            fit: ImageFit::Actual,
+            ..Default::default()
+        };
+
+        view.apply(ImageCommand::PanRight, size, max);
+        assert_eq!(view.region(size, max), (760, 150, 160, 100));
+
+        for _ in 0..100 {
+            view.apply(ImageCommand::PanUp, size, max);
+            view.apply(ImageCommand::PanLeft, size, max);
+        }
+        assert_eq!(view.region(size, max), (0, 0, 160, 100));
+
+        // One pan back moves immediately
+        view.apply(ImageCommand::PanDown, size, max);
+        assert_eq!(view.region(size, max), (0, 25, 160, 100));
+    }
+
+    #[test]
+    fn test_render_image_preview_zoomed() {
+        let img = DynamicImage::new_rgb8(1600, 400);
+        let entry = FileEntry {
+            path: PathBuf::from("wide.png"),
+            name: "wide.png".to_string(),
+            size: 0,
+            modified_date: Utc::now(),
+            file_type: FileType::Image,
+            disk: Default::default(),
+        };
+
+        // Fitted: 160×40 px, i.e. 20 rows after the 3 header lines
+        let PreviewContent::Styled(lines) = render_image_preview(
+            &entry,
+            &img,
+            &ImageDetails::default(),
+            &ImageView::default(),
+        ) else {
+            panic!("image previews are styled");
+        };
+        assert_eq!(lines.len(), 3 + 20);
+
+        let view = ImageView {
+            fit: ImageFit::Actual,
+            ..Default::default()
+        };
+        let PreviewContent::Styled(lines) =
+            render_image_preview(&entry, &img, &ImageDetails::default(), &view)
+        else {
+            panic!("image previews are styled");
+        };
+        assert_eq!(
+            lines[2].to_string(),
+            "Zoom: 100% · 100% · showing 160×100 at (720, 150)"
+        );
+        assert_eq!(lines.len(), 4 + 50);
+        assert_eq!(lines[4].spans.len(), 160);
+    }
+
+    fn photo_entry(path: &Path) -> FileEntry {
+        FileEntry {
+            path: path.to_path_buf(),
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            size: fs::metadata(path).unwrap().len(),
+            modified_date: Utc::now(),
+            file_type: FileType::Image,
+            disk: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_generate_image_preview_raw_uses_embedded_jpeg() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("DSC_0001.NEF");
+        fs::write(&path, photo::tests::raw_file(&photo::tests::jpeg(32, 24))).unwrap();
+
+        // Orientation 6: the landscape preview is turned upright
+        let (img, info) = load_raw_preview(&path, None).unwrap();
+        assert_eq!(img.dimensions(), (24, 32));
+        assert_eq!(info.dimensions, Some((6048, 4024)));
+
+        let PreviewContent::Styled(lines) = generate_image_preview(&photo_entry(&path)).unwrap()
+        else {
+            panic!("image previews are styled");
+        };
+        assert!(lines[1].to_string().starts_with("Dimensions: 6048×4024 px"));
+        assert_eq!(
+            lines[2].to_string(),
+            "Camera: NIKON Z6  Taken: 2024:06:01 12:30:00"
+        );
+        assert_eq!(lines[3].to_string(), "Preview: embedded 24×32 JPEG");
+        assert!(lines.len() > 5);
+    }
+
+    #[test]
+    fn test_generate_image_preview_shows_jpeg_exif() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("IMG_0042.JPG");
+        fs::write(&path, photo::tests::jpeg_with_exif(40, 30)).unwrap();
+
+        let PreviewContent::Styled(lines) = generate_image_preview(&photo_entry(&path)).unwrap()
+        else {
+            panic!("image previews are styled");
+        };
+        assert!(lines[1].to_string().starts_with("Dimensions: 40×30 px"));
+        assert_eq!(
+            lines[2].to_string(),
+            "Camera: Apple iPhone 6  Taken: 2014:08:17 18:04:51  GPS: recorded"
+        );
+        assert_eq!(lines[3].to_string(), "");
+    }
+
+    /// An animated GIF with one 100 ms frame per color
+    fn animated_gif(path: &Path, colors: &[[u8; 4]]) {
+        use image::codecs::gif::GifEncoder;
+        use image::{Delay, Frame, RgbaImage};
+
+        let frames = colors.iter().map(|&color| {
+            Frame::from_parts(
+                RgbaImage::from_pixel(8, 8, image::Rgba(color)),
+                0,
+                0,
+                Delay::from_numer_denom_ms(100, 1),
+            )
+        });
+        GifEncoder::new(File::create(path).unwrap())
+            .encode_frames(frames)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_read_animation_counts_frames() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let animated = temp_dir.path().join("meme.gif");
+        animated_gif(
+            &animated,
+            &[[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 255]],
+        );
+        let info = read_animation(&animated).unwrap();
+        assert_eq!(info.frames, 3);
+        assert_eq!(info.duration, Duration::from_millis(300));
+        assert_eq!(info.label(), "3 frames · 0.3 s");
+
+        let still = temp_dir.path().join("still.gif");
+        animated_gif(&still, &[[255, 0, 0, 255]]);
+        assert!(read_animation(&still).is_none());
+
+        let truncated = AnimationInfo {
+            frames: MAX_ANIMATION_FRAMES,
+            duration: Duration::from_secs(20),
+            truncated: true,
+        };
+        assert_eq!(truncated.label(), "500+ frames · 20.0 s+");
+    }
+
+    #[test]
+    fn test_generate_image_preview_animated_gif() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("meme.gif");
+        animated_gif(&path, &[[255, 0, 0, 255], [0, 0, 255, 255]]);
+
+        let PreviewContent::Styled(lines) = generate_image_preview(&photo_entry(&path)).unwrap()
+        else {
+            panic!("image previews are styled");
+        };
+        assert_eq!(
+            lines[2].to_string(),
+            "Animation: 2 frames · 0.2 s (first frame shown)"
+        );
+        // The first (red) frame is the one rendered
+        let Some(Color::Rgb(r, _, b)) = lines[4].spans[0].style.fg else {
+            panic!("image cells are true color");
+        };
+        assert!(r > 200 && b < 50);
+    }
+
+    #[test]
+    fn test_generate_image_preview_heic_shows_metadata() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("IMG_0001.HEIC");
+        // Not a valid container: still previewed, just without details
+        fs::write(&path, b"not really heic").unwrap();
+
+        let PreviewContent::Styled(lines) = generate_image_preview(&photo_entry(&path)).unwrap()
+        else {
+            panic!("image previews are styled");
+        };
+        assert!(lines[1].to_string().starts_with("Dimensions: unknown"));
+        assert!(lines
+            .iter()
+            .any(|line| line.to_string().contains("HEIC decoding isn't built in")));
+        assert!(load_preview_image(&path).is_err());
+    }
+
     #[test]
     fn test_load_image_nonexistent() {
         let result = load_image(Path::new("/nonexistent/image.png"));
@@ -748,6 +1867,7 @@ mod tests {
             size: fs::metadata(&image_path).unwrap().len(),
             modified_date: Utc::now(),
             file_type: FileType::Image,
+            disk: Default::default(),
         };
 
         let preview = generate_image_preview(&file_entry).unwrap();
@@ -787,6 +1907,7 @@ mod tests {
             size: fs::metadata(&image_path).unwrap().len(),
             modified_date: Utc::now(),
             file_type: FileType::Image,
+            disk: Default::default(),
         };
 
         let preview = generate_preview(&file_entry).unwrap();
@@ -884,6 +2005,7 @@ mod tests {
             size: fs::metadata(&pdf_path).unwrap().len(),
             modified_date: Utc::now(),
             file_type: FileType::Pdf,
+            disk: Default::default(),
         };
 
         let preview = generate_pdf_preview(&file_entry).unwrap();
@@ -909,6 +2031,7 @@ mod tests {
             size: 0,
             modified_date: Utc::now(),
             file_type: FileType::Pdf,
+            disk: Default::default(),
         };
 
         let preview = generate_pdf_preview(&file_entry).unwrap();
@@ -945,6 +2068,7 @@ mod tests {
             size: fs::metadata(&pdf_path).unwrap().len(),
             modified_date: Utc::now(),
             file_type: FileType::Pdf,
+            disk: Default::default(),
         };
 
         let preview = generate_pdf_preview(&file_entry).unwrap();
@@ -992,6 +2116,7 @@ mod tests {
             size: fs::metadata(&pdf_path).unwrap().len(),
             modified_date: Utc::now(),
             file_type: FileType::Pdf,
+            disk: Default::default(),
         };
 
         let preview = generate_preview(&file_entry).unwrap();
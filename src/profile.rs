@@ -0,0 +1,85 @@
+//! Module for named profiles
+//!
+//! `--profile NAME` gives each person on a shared machine (or work vs personal
+//! use) their own config, presets, retention rules, session journals, saved
+//! progress and reports: everything fswp remembers lives under `fswp/profiles/<NAME>` in the
+//! config and data directories instead of directly under `fswp`. The scan index
+//! is only a cache of file metadata, so it stays shared.
+//!
+//! The profile is set once at startup, before anything reads a path.
+
+use crate::error::{FileTinderError, Result};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static PROFILE: OnceLock<String> = OnceLock::new();
+
+/// Selects the profile for the rest of the process; only the first call takes effect
+pub fn set(name: &str) -> Result<()> {
+    validate(name)?;
+    let _ = PROFILE.set(name.to_string());
+    Ok(())
+}
+
+/// The selected profile, if any
+pub fn current() -> Option<&'static str> {
+    PROFILE.get().map(String::as_str)
+}
+
+/// `base` (an `fswp` directory) narrowed to the selected profile
+pub fn scoped(base: PathBuf) -> PathBuf {
+    scoped_to(base, current())
+}
+
+fn scoped_to(base: PathBuf, profile: Option<&str>) -> PathBuf {
+    match profile {
+        Some(name) => base.join("profiles").join(name),
+        None => base,
+    }
+}
+
+/// Profile names become directory names, so only plain ones are allowed
+fn validate(name: &str) -> Result<()> {
+    let plain = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if plain && Path::new(name).file_name().is_some() {
+        Ok(())
+    } else {
+        Err(FileTinderError::ConfigError(format!(
+            "Invalid profile name '{}' (use letters, digits, '-', '_' or '.')",
+            name
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_names() {
+        for name in ["work", "kid-laptop", "alice_2", "v1.2"] {
+            assert!(validate(name).is_ok(), "{}", name);
+        }
+        for name in ["", ".", "..", ".hidden", "a/b", "../etc", "with space"] {
+            assert!(
+                matches!(validate(name), Err(FileTinderError::ConfigError(_))),
+                "{}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn test_scoped_paths() {
+        let base = PathBuf::from("/home/me/.config/fswp");
+        assert_eq!(scoped_to(base.clone(), None), base);
+        assert_eq!(
+            scoped_to(base, Some("work")),
+            PathBuf::from("/home/me/.config/fswp/profiles/work")
+        );
+    }
+}
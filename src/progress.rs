@@ -0,0 +1,257 @@
+//! Module for saving and resuming an unfinished review
+//!
+//! When a session ends with files still undecided, the decisions made so far
+//! and the file being looked at are saved to
+//! `<data dir>/fswp/progress/<directory hash>.json`. The next run in the same
+//! directory offers to pick up from there; a finished session removes the file.
+//!
+//! Files are remembered by path rather than queue position, so a rescan that
+//! finds new files or sorts differently still resumes correctly. Decisions on
+//! files that are gone by then (trashed when the session was applied, or moved)
+//! are dropped, which is why moves aren't saved at all.
+
+use crate::domain::{AppState, Decision};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Bumped whenever the stored fields change meaning; older progress is ignored
+const PROGRESS_VERSION: u32 = 1;
+
+/// A decision worth carrying over to the next run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SavedDecision {
+    Keep,
+    Trash,
+    Skip,
+}
+
+/// How far a review of one directory got
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedProgress {
+    version: u32,
+    directory: PathBuf,
+    /// The file on screen when the session ended
+    pub current: Option<PathBuf>,
+    /// Each decided file's latest decision, oldest first
+    pub decisions: Vec<(PathBuf, SavedDecision)>,
+}
+
+impl SavedProgress {
+    /// The decisions in `state` and its current file, for a review of `directory`
+    pub fn capture(state: &AppState, directory: &Path) -> Self {
+        // Only a file's latest entry counts
+        let mut seen = HashSet::new();
+        let mut decisions: Vec<(PathBuf, SavedDecision)> = state
+            .decisions()
+            .iter()
+            .rev()
+            .filter(|(index, _)| seen.insert(*index))
+            .filter_map(|(index, decision)| {
+                let saved = match decision {
+                    Decision::Keep => SavedDecision::Keep,
+                    Decision::Trash => SavedDecision::Trash,
+                    Decision::Skip => SavedDecision::Skip,
+                    Decision::Move(_) => return None,
+                };
+                Some((state.files[*index].path.clone(), saved))
+            })
+            .collect();
+        decisions.reverse();
+        Self {
+            version: PROGRESS_VERSION,
+            directory: directory.to_path_buf(),
+            current: state.current_file().map(|file| file.path.clone()),
+            decisions,
+        }
+    }
+
+    /// Saved decisions about files in `state`
+    pub fn resumable(&self, state: &AppState) -> usize {
+        let paths: HashSet<&Path> = state.files.iter().map(|file| file.path.as_path()).collect();
+        self.decisions
+            .iter()
+            .filter(|(path, _)| paths.contains(path.as_path()))
+            .count()
+    }
+
+    /// Records the saved decisions on the matching files of `state`, in their
+    /// original order so undo walks back through them, and returns to the saved
+    /// current file (or the first undecided one); returns how many were restored
+    pub fn restore(&self, state: &mut AppState) -> io::Result<usize> {
+        let indices: HashMap<PathBuf, usize> = state
+            .files
+            .iter()
+            .enumerate()
+            .map(|(index, file)| (file.path.clone(), index))
+            .collect();
+        let mut restored = 0;
+        for (path, saved) in &self.decisions {
+            let Some(&index) = indices.get(path) else {
+                continue;
+            };
+            let decision = match saved {
+                SavedDecision::Keep => Decision::Keep,
+                SavedDecision::Trash => Decision::Trash,
+                SavedDecision::Skip => Decision::Skip,
+            };
+            state.engine.record_decision(index, decision)?;
+            restored += 1;
+        }
+
+        let current = self.current.as_ref().and_then(|path| indices.get(path));
+        let position = state
+            .queue
+            .iter()
+            .position(|index| Some(index) == current)
+            .or_else(|| {
+                state
+                    .queue
+                    .iter()
+                    .position(|&index| !state.is_decided(index))
+            });
+        if let Some(position) = position {
+            state.goto(position);
+        }
+        Ok(restored)
+    }
+
+    /// Writes the progress to `path`, replacing what was there
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let data = serde_json::to_vec(self).map_err(io::Error::other)?;
+        // Write then rename, so an interrupted save never leaves half a file
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, data)?;
+        fs::rename(&partial, path)
+    }
+}
+
+/// The progress saved at `path` for `directory`; unreadable, outdated or
+/// foreign progress counts as none
+pub fn load(path: &Path, directory: &Path) -> Option<SavedProgress> {
+    let data = fs::read(path).ok()?;
+    serde_json::from_slice::<SavedProgress>(&data)
+        .ok()
+        .filter(|saved| saved.version == PROGRESS_VERSION && saved.directory == directory)
+}
+
+/// Removes the progress at `path`, if any
+pub fn clear(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Where the progress for `directory` (canonical) is kept
+pub fn progress_path(directory: &Path) -> Option<PathBuf> {
+    let dir =
+        dirs::data_dir().map(|dir| crate::profile::scoped(dir.join("fswp")).join("progress"))?;
+    Some(dir.join(format!(
+        "{:016x}.json",
+        crate::scan_index::fnv1a(directory.as_os_str().as_encoded_bytes())
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::FileEntry;
+    use tempfile::TempDir;
+
+    fn scan(dir: &Path, names: &[&str]) -> AppState {
+        let files = names
+            .iter()
+            .map(|name| {
+                let path = dir.join(name);
+                if !path.exists() {
+                    fs::write(&path, name).unwrap();
+                }
+                FileEntry::from_path(&path).unwrap()
+            })
+            .collect();
+        let mut state = AppState::new(files);
+        state.engine.set_dry_run(true);
+        state
+    }
+
+    #[test]
+    fn test_progress_round_trip_and_resume() {
+        let dir = TempDir::new().unwrap();
+        let data = TempDir::new().unwrap();
+        let path = data.path().join("progress.json");
+
+        let mut state = scan(dir.path(), &["a.txt", "b.txt", "c.txt", "d.txt"]);
+        state.engine.record_decision(1, Decision::Trash).unwrap();
+        state.engine.record_decision(0, Decision::Keep).unwrap();
+        state.goto(2);
+        SavedProgress::capture(&state, dir.path())
+            .save(&path)
+            .unwrap();
+
+        // The next scan sorts differently and b.txt is gone
+        fs::remove_file(dir.path().join("b.txt")).unwrap();
+        let saved = load(&path, dir.path()).unwrap();
+        let mut state = scan(dir.path(), &["d.txt", "c.txt", "a.txt"]);
+        assert_eq!(saved.resumable(&state), 1);
+        assert_eq!(saved.restore(&mut state).unwrap(), 1);
+        assert_eq!(state.decisions(), &[(2, Decision::Keep)]);
+        assert_eq!(state.current_file().unwrap().name, "c.txt");
+
+        // Another directory's progress, or garbage, is no progress
+        assert!(load(&path, data.path()).is_none());
+        fs::write(&path, "not json").unwrap();
+        assert!(load(&path, dir.path()).is_none());
+        clear(&path).unwrap();
+        clear(&path).unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_resume_without_current_file_goes_to_first_undecided() {
+        let dir = TempDir::new().unwrap();
+        let mut state = scan(dir.path(), &["a.txt", "b.txt", "c.txt"]);
+        state.engine.record_decision(0, Decision::Keep).unwrap();
+        state.engine.record_decision(0, Decision::Trash).unwrap();
+        let mut saved = SavedProgress::capture(&state, dir.path());
+        // Only the latest decision on a file is kept
+        assert_eq!(
+            saved.decisions,
+            [(dir.path().join("a.txt"), SavedDecision::Trash)]
+        );
+
+        saved.current = None;
+        let mut state = scan(dir.path(), &["a.txt", "b.txt", "c.txt"]);
+        saved.restore(&mut state).unwrap();
+        assert_eq!(state.current_file().unwrap().name, "b.txt");
+    }
+
+    #[test]
+    fn test_resumed_decisions_reach_the_session_journal() {
+        use crate::journal::{self, Journal, JournalAction, SessionInfo};
+
+        let dir = TempDir::new().unwrap();
+        let mut state = scan(dir.path(), &["a.txt", "b.txt"]);
+        state.engine.record_decision(1, Decision::Trash).unwrap();
+        let saved = SavedProgress::capture(&state, dir.path());
+
+        // The journal is attached before resuming, as the session does
+        let journal_path = dir.path().join("session.jsonl");
+        let journal = Journal::create_at(&journal_path, &SessionInfo::current(None)).unwrap();
+        let mut state = scan(dir.path(), &["a.txt", "b.txt"]);
+        state.engine.add_listener(Box::new(journal));
+        saved.restore(&mut state).unwrap();
+        drop(state);
+
+        let log = journal::load(&journal_path).unwrap();
+        assert_eq!(log.records.len(), 1);
+        assert_eq!(log.records[0].action, JournalAction::Trash);
+        assert_eq!(log.records[0].path, dir.path().join("b.txt"));
+    }
+}
@@ -0,0 +1,283 @@
+//! Module for shareable HTML reports of a session's decisions
+//!
+//! Pressing `E` while reviewing writes the decisions so far to
+//! `<data dir>/fswp/reports/<YYYYMMDD-HHMMSS>.html`: one standalone page listing
+//! what is about to be trashed, moved and kept, with small thumbnails of images
+//! embedded and links to the kept files, so it can be passed around for sign-off
+//! before anything is applied. Each file to trash is listed with a hash of its
+//! contents (the config's `hash_algorithm`, named in the report), so it can be
+//! checked later that what was signed off is what went.
+
+use crate::digest::escape_html;
+use crate::domain::{AppState, Decision, FileEntry, FileType};
+use crate::error::{FileTinderError, Result};
+use crate::hashing::HashAlgorithm;
+use crate::preview::load_preview_image;
+use crate::tui::format_file_size;
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use chrono::Utc;
+use image::{ImageFormat, ImageReader};
+use std::fmt::Write;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// Longest side of an embedded thumbnail, in pixels
+const THUMBNAIL_SIZE: u32 = 96;
+
+/// Directory holding exported reports (`<data dir>/fswp/reports`)
+pub fn reports_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| crate::profile::scoped(dir.join("fswp")).join("reports"))
+}
+
+/// Writes the report to the reports directory, named after the current time
+pub fn save(state: &AppState, directory: &Path, algorithm: HashAlgorithm) -> Result<PathBuf> {
+    let dir = reports_dir().ok_or_else(|| {
+        FileTinderError::ConfigError("Could not determine data directory".to_string())
+    })?;
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{}.html", Utc::now().format("%Y%m%d-%H%M%S")));
+    fs::write(&path, to_html(state, directory, algorithm))?;
+    Ok(path)
+}
+
+/// The session's decisions as a standalone HTML page, grouped trash / move / keep / skip,
+/// files to trash hashed with `algorithm`
+pub fn to_html(state: &AppState, directory: &Path, algorithm: HashAlgorithm) -> String {
+    let title = escape_html(&format!("fswp review of {}", directory.display()));
+    let mut out = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n\
+         <style>body {{ font-family: sans-serif; max-width: 60em; margin: 2em auto; }} \
+         table {{ border-collapse: collapse; width: 100%; }} \
+         th, td {{ padding: 0.25em 0.75em; border-bottom: 1px solid #ddd; text-align: left; vertical-align: middle; }} \
+         td.thumb {{ width: {size}px; }} img {{ display: block; }}</style>\n\
+         </head>\n<body>\n<h1>{title}</h1>\n<p>Exported {time}, {pending} of {total} files still undecided. \
+         Files to trash are listed with their {algorithm} hash.</p>\n",
+        size = THUMBNAIL_SIZE,
+        time = Utc::now().format("%Y-%m-%d %H:%M UTC"),
+        pending = state.files.len() - state.decided_count(),
+        total = state.files.len(),
+        algorithm = algorithm,
+    );
+
+    for (section, heading) in ["To trash", "To move", "Kept", "Skipped"]
+        .into_iter()
+        .enumerate()
+    {
+        let files: Vec<(usize, &FileEntry, &Decision)> = state
+            .decisions()
+            .iter()
+            .filter(|(_, decision)| section_of(decision) == section)
+            .filter_map(|(index, decision)| Some((*index, state.files.get(*index)?, decision)))
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+        let bytes: u64 = files.iter().map(|(_, file, _)| file.size).sum();
+        let _ = writeln!(
+            out,
+            "<h2>{} ({} files, {})</h2>\n<table>\n\
+             <tr><th></th><th>Name</th><th>Size</th><th>Modified</th><th>Directory</th></tr>",
+            heading,
+            files.len(),
+            format_file_size(bytes)
+        );
+        for (index, file, decision) in files {
+            let location = state.engine.location(index, decision);
+            let hash = match decision {
+                Decision::Trash => algorithm.hash_file(&location).ok(),
+                _ => None,
+            };
+            write_row(
+                &mut out,
+                file,
+                decision,
+                &location,
+                state.reasons.get(&index).map(String::as_str),
+                hash.as_deref(),
+            );
+        }
+        out.push_str("</table>\n");
+    }
+
+    if state.decisions().is_empty() {
+        out.push_str("<p>No decisions yet.</p>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Position of a decision's section in the report
+fn section_of(decision: &Decision) -> usize {
+    match decision {
+        Decision::Trash => 0,
+        Decision::Move(_) => 1,
+        Decision::Keep => 2,
+        Decision::Skip => 3,
+    }
+}
+
+/// One file's row; `location` is where the file is now (trashed files are staged
+/// until the session is applied), `reason` why a rule suggested it, `hash` its
+/// contents' hash
+fn write_row(
+    out: &mut String,
+    file: &FileEntry,
+    decision: &Decision,
+    location: &Path,
+    reason: Option<&str>,
+    hash: Option<&str>,
+) {
+    let thumb = thumbnail(file, location)
+        .map(|uri| format!("<img src=\"{}\" alt=\"\">", uri))
+        .unwrap_or_default();
+    let name = escape_html(&file.name);
+    // Kept and moved files can be opened from the report
+    let name = match decision {
+        Decision::Keep | Decision::Skip => {
+            format!("<a href=\"{}\">{}</a>", file_url(location), name)
+        }
+        Decision::Move(dir) => format!(
+            "<a href=\"{}\">{}</a> → {}",
+            file_url(location),
+            name,
+            escape_html(&dir.display().to_string())
+        ),
+        Decision::Trash => name,
+    };
+    let name = match reason {
+        Some(reason) => format!("{}<br><small>{}</small>", name, escape_html(reason)),
+        None => name,
+    };
+    let name = match hash {
+        Some(hash) => format!("{}<br><small><code>{}</code></small>", name, hash),
+        None => name,
+    };
+    let parent = file.path.parent().unwrap_or(Path::new(""));
+    let _ = writeln!(
+        out,
+        "<tr><td class=\"thumb\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+        thumb,
+        name,
+        format_file_size(file.size),
+        file.modified_date.format("%Y-%m-%d"),
+        escape_html(&parent.display().to_string())
+    );
+}
+
+/// A small JPEG of an image file (read from `path`) as a data URI; None for
+/// other files or on a decode error
+fn thumbnail(file: &FileEntry, path: &Path) -> Option<String> {
+    if file.file_type != FileType::Image {
+        return None;
+    }
+    let img = match load_preview_image(path) {
+        Ok((img, _)) => img,
+        // Staged copies are named without their extension, so sniff the format
+        Err(_) => ImageReader::open(path)
+            .ok()?
+            .with_guessed_format()
+            .ok()?
+            .decode()
+            .ok()?,
+    };
+    let img = img.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgb8();
+    let mut jpeg = Vec::new();
+    img.write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)
+        .ok()?;
+    Some(format!("data:image/jpeg;base64,{}", STANDARD.encode(jpeg)))
+}
+
+/// `file://` URL of a path, percent-encoding everything but unreserved characters and `/`
+fn file_url(path: &Path) -> String {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut url = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                url.push(byte as char)
+            }
+            _ => {
+                let _ = write!(url, "%{:02X}", byte);
+            }
+        }
+    }
+    url
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{DynamicImage, RgbImage};
+    use tempfile::TempDir;
+
+    fn entry(path: PathBuf, file_type: FileType) -> FileEntry {
+        FileEntry {
+            name: path.file_name().unwrap().to_string_lossy().into_owned(),
+            size: 2048,
+            modified_date: Utc::now(),
+            file_type,
+            disk: Default::default(),
+            path,
+        }
+    }
+
+    #[test]
+    fn test_report_groups_decisions_with_thumbnails_and_links() {
+        let temp_dir = TempDir::new().unwrap();
+        let photo = temp_dir.path().join("holiday.png");
+        DynamicImage::ImageRgb8(RgbImage::new(400, 300))
+            .save(&photo)
+            .unwrap();
+        let files = vec![
+            entry(photo, FileType::Image),
+            entry(temp_dir.path().join("a&b <notes>.txt"), FileType::Text),
+            entry(temp_dir.path().join("later.txt"), FileType::Text),
+        ];
+        let mut state = AppState::new(files);
+        state.record_decision(Decision::Trash).unwrap();
+        state.next();
+        state.record_decision(Decision::Keep).unwrap();
+        state
+            .reasons
+            .insert(0, "matched rule 'junk': a <test>".to_string());
+
+        let html = to_html(&state, temp_dir.path(), HashAlgorithm::Sha256);
+        assert!(html.contains("1 of 3 files still undecided"));
+        let trash = html.find("<h2>To trash (1 files, 2.0 KB)</h2>").unwrap();
+        let kept = html.find("<h2>Kept (1 files, 2.0 KB)</h2>").unwrap();
+        assert!(trash < kept);
+        assert!(!html.contains("To move"));
+
+        // The trashed image is embedded; the kept text file is linked and escaped
+        assert_eq!(html.matches("data:image/jpeg;base64,").count(), 1);
+        assert!(html
+            .contains("holiday.png<br><small>matched rule &#39;junk&#39;: a &lt;test&gt;</small>"));
+        assert!(html.contains("%20%3Cnotes%3E.txt\">a&amp;b &lt;notes&gt;.txt</a>"));
+        assert!(!html.contains("later.txt"));
+
+        // Only the trashed file is hashed, from where it's staged
+        let staged = state.engine.location(0, &Decision::Trash);
+        let hash = HashAlgorithm::Sha256.hash_file(&staged).unwrap();
+        assert!(html.contains(&format!("<code>{}</code>", hash)));
+        assert_eq!(html.matches("<code>").count(), 1);
+        assert!(html.contains("listed with their sha256 hash"));
+    }
+
+    #[test]
+    fn test_report_without_decisions() {
+        let state = AppState::new(vec![entry(PathBuf::from("/x/a.txt"), FileType::Text)]);
+        let html = to_html(&state, Path::new("/x"), HashAlgorithm::default());
+        assert!(html.contains("<h1>fswp review of /x</h1>"));
+        assert!(html.contains("No decisions yet."));
+    }
+
+    #[test]
+    fn test_file_url_encodes_reserved_characters() {
+        assert_eq!(
+            file_url(Path::new("/no/such dir/100%#1.txt")),
+            "file:///no/such%20dir/100%25%231.txt"
+        );
+    }
+}
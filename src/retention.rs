@@ -0,0 +1,571 @@
+//! Module for trash retention
+//!
+//! `fswp purge` permanently deletes files fswp moved to the system trash once
+//! they've been there longer than the first `retention` rule in the config that
+//! matches them (e.g. images 90 days, logs 7 days). Only files a session journal
+//! recorded as trashed are considered, so anything else in the trash is left alone:
+//! an item counts as fswp's when a session that wasn't a dry run recorded its file
+//! reaching the trash (an `Applied` line) about when the item got there, and the
+//! item still has the size the journal recorded. A decision whose apply was
+//! cancelled or failed has no `Applied` line, so a file it left on disk and that
+//! was trashed by hand later is never taken for fswp's.
+//!
+//! `fswp empty-trash` lists the same files, whatever their age, grouped by how long
+//! they've been in the trash and biggest first, so they can be picked by hand and
+//! deleted for good.
+
+use crate::config::RetentionRule;
+use crate::digest::net_decisions;
+use crate::domain::{FileEntry, FileType, QueueFilter};
+use crate::journal::{JournalAction, SessionLog};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Most seconds between a journal's `Applied` line and the trash's own deletion
+/// time for the same file, allowing for the two clocks' rounding
+const APPLIED_WITHIN_SECONDS: i64 = 60;
+
+/// An item in the system trash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashedFile {
+    /// Where the file was before it was trashed
+    pub path: PathBuf,
+    /// When it was trashed
+    pub deleted: DateTime<Utc>,
+}
+
+/// A file a session journal recorded reaching the system trash
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrashRecord {
+    /// Size of the file when it was decided
+    pub size: u64,
+    /// When it reached the trash (its `Applied` line)
+    pub time: DateTime<Utc>,
+}
+
+/// Trash decisions session journals recorded (and not undone) whose file then
+/// reached the trash, by path, oldest first; dry-run sessions trashed nothing and
+/// are left out
+pub fn journaled_trash(logs: &[SessionLog]) -> HashMap<PathBuf, Vec<TrashRecord>> {
+    let mut ours: HashMap<PathBuf, Vec<TrashRecord>> = HashMap::new();
+    for log in logs.iter().filter(|log| !log.is_dry_run()) {
+        let mut applied: HashMap<&Path, Vec<i64>> = HashMap::new();
+        for record in &log.records {
+            if record.action == JournalAction::Applied {
+                applied.entry(&record.path).or_default().push(record.time);
+            }
+        }
+        for record in net_decisions(&log.records)
+            .into_iter()
+            .filter(|record| record.action == JournalAction::Trash)
+        {
+            // The first time the file reached the trash after it was decided
+            let Some(&time) = applied
+                .get(record.path.as_path())
+                .and_then(|times| times.iter().find(|&&time| time >= record.time))
+            else {
+                continue;
+            };
+            ours.entry(record.path.clone())
+                .or_default()
+                .push(TrashRecord {
+                    size: record.size,
+                    time: DateTime::from_timestamp(time, 0).unwrap_or_default(),
+                });
+        }
+    }
+    for records in ours.values_mut() {
+        records.sort_by_key(|record| record.time);
+    }
+    ours
+}
+
+/// The journaled record `item` came from: the latest at its path whose file
+/// reached the trash about when the item did. None when fswp didn't trash it.
+pub fn record_of<'a>(
+    item: &TrashedFile,
+    ours: &'a HashMap<PathBuf, Vec<TrashRecord>>,
+) -> Option<&'a TrashRecord> {
+    ours.get(&item.path)?
+        .iter()
+        .rev()
+        .find(|record| (item.deleted - record.time).num_seconds().abs() <= APPLIED_WITHIN_SECONDS)
+}
+
+/// Positions in `trashed` of the files fswp trashed whose retention is over, each
+/// with the index of the rule that expired it. An item counts only when `holds`
+/// says it is the recorded file (see `TrashListing::holds`).
+///
+/// Rules are matched in order against the file as it was (name, type, journaled
+/// size; its date is when it was trashed), and the first match decides.
+pub fn expired(
+    trashed: &[TrashedFile],
+    rules: &[(QueueFilter, Duration)],
+    ours: &HashMap<PathBuf, Vec<TrashRecord>>,
+    holds: impl Fn(usize, &TrashRecord) -> bool,
+    now: DateTime<Utc>,
+) -> Vec<(usize, usize)> {
+    trashed
+        .iter()
+        .enumerate()
+        .filter_map(|(position, item)| {
+            let record = record_of(item, ours).filter(|record| holds(position, record))?;
+            let entry = entry_for(item, record.size);
+            let rule = rules
+                .iter()
+                .position(|(filter, _)| filter.matches(&entry))?;
+            (now - item.deleted > rules[rule].1).then_some((position, rule))
+        })
+        .collect()
+}
+
+/// Why `item` is due for purging, e.g. "matched rule 'type:image': in the trash 100 days (keep 90d)"
+pub fn explain(rule: &RetentionRule, item: &TrashedFile, now: DateTime<Utc>) -> String {
+    format!(
+        "matched rule '{}': in the trash {} days (keep {})",
+        rule.filter,
+        (now - item.deleted).num_days(),
+        rule.keep
+    )
+}
+
+/// How long an item has been in the trash; `fswp empty-trash` groups by it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TrashAge {
+    Older,
+    Quarter,
+    Month,
+    Week,
+}
+
+impl TrashAge {
+    pub fn of(age: Duration) -> Self {
+        match age.num_days() {
+            ..=7 => TrashAge::Week,
+            8..=30 => TrashAge::Month,
+            31..=90 => TrashAge::Quarter,
+            _ => TrashAge::Older,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            TrashAge::Week => "This week",
+            TrashAge::Month => "This month",
+            TrashAge::Quarter => "1 to 3 months ago",
+            TrashAge::Older => "Over 3 months ago",
+        }
+    }
+}
+
+/// A file fswp trashed that is still in the trash
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrashRow {
+    /// Position in the trash listing
+    pub position: usize,
+    pub path: PathBuf,
+    /// The item's size in the trash, or the journaled one where the trash can't
+    /// tell (a directory)
+    pub size: u64,
+    pub deleted: DateTime<Utc>,
+    pub age: TrashAge,
+    pub selected: bool,
+}
+
+/// The list `fswp empty-trash` reviews: what fswp trashed and is still in the
+/// trash, oldest group first and biggest first within a group, nothing selected
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmptyTrash {
+    pub rows: Vec<TrashRow>,
+    pub cursor: usize,
+    /// Asking whether to delete the selection
+    pub confirming: bool,
+    /// Shown in place of the key hint until the next key
+    pub notice: Option<String>,
+}
+
+impl EmptyTrash {
+    /// The items of `trashed` a session journal recorded (`ours`, matched like
+    /// `expired`, `holds` included), each with its `size` in the trash, as of `now`
+    pub fn new(
+        trashed: &[TrashedFile],
+        ours: &HashMap<PathBuf, Vec<TrashRecord>>,
+        holds: impl Fn(usize, &TrashRecord) -> bool,
+        size: impl Fn(usize) -> Option<u64>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let mut rows: Vec<TrashRow> = trashed
+            .iter()
+            .enumerate()
+            .filter_map(|(position, item)| {
+                let record = record_of(item, ours).filter(|record| holds(position, record))?;
+                Some(TrashRow {
+                    position,
+                    path: item.path.clone(),
+                    size: size(position).unwrap_or(record.size),
+                    deleted: item.deleted,
+                    age: TrashAge::of(now - item.deleted),
+                    selected: false,
+                })
+            })
+            .collect();
+        rows.sort_by(|a, b| {
+            a.age
+                .cmp(&b.age)
+                .then(b.size.cmp(&a.size))
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        Self {
+            rows,
+            ..Self::default()
+        }
+    }
+
+    pub fn next(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.rows.len().saturating_sub(1));
+    }
+
+    pub fn previous(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    /// Selects or unselects the row under the cursor
+    pub fn toggle(&mut self) {
+        if let Some(row) = self.rows.get_mut(self.cursor) {
+            row.selected = !row.selected;
+        }
+    }
+
+    /// Selects every row in the cursor's group, or unselects them when all are
+    pub fn toggle_group(&mut self) {
+        let Some(age) = self.rows.get(self.cursor).map(|row| row.age) else {
+            return;
+        };
+        Self::toggle_where(&mut self.rows, |row| row.age == age);
+    }
+
+    /// Selects every row, or none when all are
+    pub fn toggle_all(&mut self) {
+        Self::toggle_where(&mut self.rows, |_| true);
+    }
+
+    fn toggle_where(rows: &mut [TrashRow], matches: impl Fn(&TrashRow) -> bool) {
+        let select = rows
+            .iter()
+            .filter(|row| matches(row))
+            .any(|row| !row.selected);
+        for row in rows.iter_mut().filter(|row| matches(row)) {
+            row.selected = select;
+        }
+    }
+
+    /// Each group in order with its rows' count and bytes, and how many are selected
+    pub fn groups(&self) -> Vec<(TrashAge, usize, u64, usize)> {
+        let mut groups: Vec<(TrashAge, usize, u64, usize)> = Vec::new();
+        for row in &self.rows {
+            match groups.last_mut() {
+                Some(group) if group.0 == row.age => {
+                    group.1 += 1;
+                    group.2 += row.size;
+                    group.3 += usize::from(row.selected);
+                }
+                _ => groups.push((row.age, 1, row.size, usize::from(row.selected))),
+            }
+        }
+        groups
+    }
+
+    /// Count and bytes of the selected rows
+    pub fn selection(&self) -> (usize, u64) {
+        self.rows
+            .iter()
+            .filter(|row| row.selected)
+            .fold((0, 0), |(files, bytes), row| (files + 1, bytes + row.size))
+    }
+
+    /// Positions in the trash listing of the selected rows
+    pub fn selected_positions(&self) -> Vec<usize> {
+        self.rows
+            .iter()
+            .filter(|row| row.selected)
+            .map(|row| row.position)
+            .collect()
+    }
+
+    /// Drops the selected rows once they've been deleted
+    pub fn remove_selected(&mut self) {
+        self.rows.retain(|row| !row.selected);
+        self.cursor = self.cursor.min(self.rows.len().saturating_sub(1));
+    }
+}
+
+fn entry_for(item: &TrashedFile, size: u64) -> FileEntry {
+    let name = item
+        .path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = item
+        .path
+        .extension()
+        .map(|ext| ext.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    FileEntry {
+        path: item.path.clone(),
+        name,
+        size,
+        modified_date: item.deleted,
+        file_type: FileType::from_extension(&extension),
+        disk: Default::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::parse_age;
+    use crate::journal::JournalRecord;
+
+    fn record(action: JournalAction, path: &str) -> JournalRecord {
+        record_at(0, action, path)
+    }
+
+    fn record_at(time: i64, action: JournalAction, path: &str) -> JournalRecord {
+        JournalRecord {
+            time,
+            action,
+            path: PathBuf::from(path),
+            size: 100,
+            destination: None,
+            prev: None,
+        }
+    }
+
+    /// Records of fswp trashing each of `files` (path and journaled size) when the
+    /// trash says it was deleted
+    fn trashed_by_fswp<'a>(
+        trashed: &[TrashedFile],
+        files: impl IntoIterator<Item = (&'a str, u64)>,
+    ) -> HashMap<PathBuf, Vec<TrashRecord>> {
+        files
+            .into_iter()
+            .map(|(path, size)| {
+                let path = PathBuf::from(path);
+                let item = trashed.iter().find(|item| item.path == path).unwrap();
+                let time = item.deleted;
+                (path, vec![TrashRecord { size, time }])
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_journaled_trash_skips_undone_and_kept() {
+        let logs = [SessionLog {
+            info: None,
+            records: vec![
+                record(JournalAction::Trash, "/a.log"),
+                record_at(5, JournalAction::Applied, "/a.log"),
+                record(JournalAction::Trash, "/b.log"),
+                record(JournalAction::Undo, "/b.log"),
+                record(JournalAction::Keep, "/c.log"),
+            ],
+        }];
+        let ours = journaled_trash(&logs);
+        assert_eq!(ours.len(), 1);
+        assert_eq!(
+            ours.get(&PathBuf::from("/a.log")),
+            Some(&vec![TrashRecord {
+                size: 100,
+                time: DateTime::from_timestamp(5, 0).unwrap()
+            }])
+        );
+    }
+
+    #[test]
+    fn test_journaled_trash_needs_the_file_to_reach_the_trash() {
+        let logs = [SessionLog {
+            info: None,
+            records: vec![
+                // A cancelled or failed apply writes no Applied line
+                record(JournalAction::Trash, "/cancelled.log"),
+                // Applied before this decision, for an earlier one that was undone
+                record_at(10, JournalAction::Trash, "/again.log"),
+                record_at(11, JournalAction::Applied, "/again.log"),
+                record_at(20, JournalAction::Undo, "/again.log"),
+                record_at(30, JournalAction::Trash, "/again.log"),
+            ],
+        }];
+        assert!(journaled_trash(&logs).is_empty());
+
+        let ours = journaled_trash(&[SessionLog {
+            info: None,
+            records: vec![
+                record_at(0, JournalAction::Trash, "/late.log"),
+                record_at(3600, JournalAction::Applied, "/late.log"),
+            ],
+        }]);
+        let at = |seconds| TrashedFile {
+            path: PathBuf::from("/late.log"),
+            deleted: DateTime::from_timestamp(seconds, 0).unwrap(),
+        };
+        // Trashed when the journal says it was applied, not when it was decided
+        assert!(record_of(&at(3601), &ours).is_some());
+        assert!(record_of(&at(10), &ours).is_none());
+        assert!(record_of(&at(7200), &ours).is_none());
+    }
+
+    #[test]
+    fn test_journaled_trash_skips_dry_runs() {
+        let info = crate::journal::SessionInfo {
+            dry_run: true,
+            ..crate::journal::SessionInfo::current(None)
+        };
+        let logs = [SessionLog {
+            info: Some(info),
+            records: vec![record(JournalAction::Trash, "/a.log")],
+        }];
+        assert!(journaled_trash(&logs).is_empty());
+    }
+
+    #[test]
+    fn test_empty_trash_groups_and_selects() {
+        let now = Utc::now();
+        let days_ago = |path: &str, days| TrashedFile {
+            path: PathBuf::from(path),
+            deleted: now - Duration::days(days),
+        };
+        let trashed = [
+            days_ago("/a/small.log", 2),
+            days_ago("/a/big.iso", 3),
+            days_ago("/b/old.zip", 200),
+            days_ago("/b/month.png", 20),
+            days_ago("/not/ours.txt", 400),
+            days_ago("/a/by-hand.txt", 5),
+        ];
+        let mut ours = trashed_by_fswp(
+            &trashed,
+            [
+                ("/a/small.log", 10),
+                ("/a/big.iso", 5000),
+                ("/b/old.zip", 700),
+                ("/b/month.png", 300),
+            ],
+        );
+        // fswp trashed a file at this path, but not this one
+        ours.insert(
+            PathBuf::from("/a/by-hand.txt"),
+            vec![TrashRecord {
+                size: 1,
+                time: now - Duration::days(1),
+            }],
+        );
+
+        // The trash can't size position 2 (a directory, say), and position 3 has
+        // grown since it was decided; with `holds` turned down it isn't listed
+        let sizes = |position: usize| {
+            [Some(10), Some(5000), None, Some(320)]
+                .get(position)
+                .copied()?
+        };
+        let list = EmptyTrash::new(&trashed, &ours, |position, _| position != 3, sizes, now);
+        assert_eq!(list.rows.len(), 3);
+        assert!(list.rows.iter().all(|row| row.position != 3));
+
+        let mut list = EmptyTrash::new(&trashed, &ours, |_, _| true, sizes, now);
+        let order: Vec<usize> = list.rows.iter().map(|row| row.position).collect();
+        assert_eq!(order, [2, 3, 1, 0]);
+        assert_eq!(
+            list.groups(),
+            [
+                (TrashAge::Older, 1, 700, 0),
+                (TrashAge::Month, 1, 320, 0),
+                (TrashAge::Week, 2, 5010, 0)
+            ]
+        );
+
+        list.toggle();
+        list.next();
+        list.next();
+        list.toggle_group();
+        assert_eq!(list.selection(), (3, 5710));
+        assert_eq!(list.selected_positions(), [2, 1, 0]);
+        list.toggle_group();
+        assert_eq!(list.selection(), (1, 700));
+        list.toggle_all();
+        assert_eq!(list.selection(), (4, 6030));
+        list.toggle_all();
+        assert_eq!(list.selection(), (0, 0));
+
+        list.toggle();
+        list.remove_selected();
+        assert_eq!(list.rows.len(), 3);
+        assert_eq!(list.rows[list.cursor].path, PathBuf::from("/a/small.log"));
+    }
+
+    #[test]
+    fn test_expired_uses_first_matching_rule() {
+        let now = Utc::now();
+        let days_ago = |path: &str, days| TrashedFile {
+            path: PathBuf::from(path),
+            deleted: now - Duration::days(days),
+        };
+        let trashed = [
+            days_ago("/pics/old.png", 100),
+            days_ago("/pics/new.png", 30),
+            days_ago("/var/app.log", 8),
+            days_ago("/var/today.log", 0),
+            days_ago("/docs/report.pdf", 1000),
+            days_ago("/not/ours.log", 100),
+            days_ago("/var/by-hand.log", 30),
+        ];
+        let mut ours = trashed_by_fswp(
+            &trashed,
+            trashed[..5]
+                .iter()
+                .map(|item| (item.path.to_str().unwrap(), 100)),
+        );
+        // fswp's file reached the trash a day ago; this one went weeks before
+        ours.insert(
+            PathBuf::from("/var/by-hand.log"),
+            vec![TrashRecord {
+                size: 100,
+                time: now - Duration::days(1),
+            }],
+        );
+        let rules = [
+            (
+                QueueFilter::parse("type:image").unwrap(),
+                parse_age("90d").unwrap(),
+            ),
+            (
+                QueueFilter::parse("ext:log").unwrap(),
+                parse_age("1w").unwrap(),
+            ),
+            // Shadowed by the image rule above
+            (QueueFilter::parse("ext:png").unwrap(), Duration::zero()),
+        ];
+
+        // The PDF matches no rule and the last two logs aren't fswp's, so they stay
+        let any = |_: usize, _: &TrashRecord| true;
+        assert_eq!(
+            expired(&trashed, &rules, &ours, any, now),
+            vec![(0, 0), (2, 1)]
+        );
+        assert!(expired(&trashed, &[], &ours, any, now).is_empty());
+        // An item that isn't the recorded file (another size, say) stays too
+        assert_eq!(
+            expired(&trashed, &rules, &ours, |position, _| position != 0, now),
+            vec![(2, 1)]
+        );
+
+        let rule = RetentionRule {
+            filter: "type:image".to_string(),
+            keep: "90d".to_string(),
+        };
+        assert_eq!(
+            explain(&rule, &trashed[0], now),
+            "matched rule 'type:image': in the trash 100 days (keep 90d)"
+        );
+    }
+}
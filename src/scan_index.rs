@@ -0,0 +1,436 @@
+//! Module for the per-directory scan index
+//!
+//! The slow part of scanning a large directory is asking the filesystem which of
+//! each file's extents are shared copy-on-write clones (a FIEMAP ioctl per file on
+//! Linux). The index remembers what the last scan of a directory found, in
+//! `<cache dir>/fswp/index/<directory hash>.json`; on the next run a file whose
+//! size, modification time and inode are unchanged costs a single stat.
+//!
+//! Shared extents can change without the file changing (another file cloned from
+//! it), so space accounting may lag until the file is touched; `--no-index-cache`
+//! always scans in full.
+//!
+//! The index also keeps each file's content hashes, per algorithm, once the copy
+//! finder or the duplicate pass has read it (`HashCache`), so an unchanged file
+//! isn't read again on the next run.
+
+use crate::domain::FileEntry;
+use crate::hashing::HashAlgorithm;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Bumped whenever the stored fields change meaning; older indexes are ignored
+const INDEX_VERSION: u32 = 2;
+
+/// What the index remembers about one file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct IndexedFile {
+    /// Path relative to the scanned directory, so same-named files in different
+    /// subdirectories of a recursive scan stay apart
+    path: OsString,
+    size: u64,
+    /// Modification time in nanoseconds since the Unix epoch
+    modified: i64,
+    /// (device, inode), so a replaced file isn't mistaken for the old one
+    id: Option<(u64, u64)>,
+    /// Bytes in copy-on-write shared extents, the expensive part to find out
+    shared: u64,
+    /// Content hashes by algorithm, from whichever pass read the file
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    hashes: HashMap<HashAlgorithm, String>,
+}
+
+impl IndexedFile {
+    fn describe(path: OsString, entry: &FileEntry) -> Option<Self> {
+        Some(Self {
+            path,
+            size: entry.size,
+            modified: entry.modified_date.timestamp_nanos_opt()?,
+            id: entry.disk.id,
+            shared: entry.disk.shared,
+            hashes: HashMap::new(),
+        })
+    }
+
+    /// Whether `metadata` still describes the file this was recorded from
+    fn matches(&self, metadata: &fs::Metadata) -> bool {
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|time| DateTime::<Utc>::from(time).timestamp_nanos_opt());
+        self.size == metadata.len()
+            && modified == Some(self.modified)
+            && self.id == file_id(metadata)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct StoredIndex {
+    version: u32,
+    directory: PathBuf,
+    files: Vec<IndexedFile>,
+}
+
+/// The last scan of one directory, and what this scan found so far
+#[derive(Debug)]
+pub struct ScanIndex {
+    /// Where the index is read from and saved to
+    path: PathBuf,
+    directory: PathBuf,
+    /// The directory as scanned, which discovered paths start with (`directory`
+    /// is canonical)
+    root: PathBuf,
+    previous: HashMap<OsString, IndexedFile>,
+    current: HashMap<OsString, IndexedFile>,
+    hits: usize,
+    /// Hashes taken from the index instead of reading the file
+    hash_hits: usize,
+}
+
+impl ScanIndex {
+    /// The index for `directory` in the cache directory (empty if there is none yet)
+    pub fn load(directory: &Path) -> Self {
+        let canonical = directory
+            .canonicalize()
+            .unwrap_or_else(|_| directory.to_path_buf());
+        let path = index_dir()
+            .unwrap_or_else(|| std::env::temp_dir().join("fswp-index"))
+            .join(format!(
+                "{:016x}.json",
+                fnv1a(canonical.as_os_str().as_encoded_bytes())
+            ));
+        Self {
+            root: directory.to_path_buf(),
+            ..Self::load_from(path, &canonical)
+        }
+    }
+
+    /// The index stored at `path`; unreadable, outdated or foreign indexes start empty
+    pub fn load_from(path: PathBuf, directory: &Path) -> Self {
+        let previous = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice::<StoredIndex>(&data).ok())
+            .filter(|stored| stored.version == INDEX_VERSION && stored.directory == directory)
+            .map(|stored| {
+                stored
+                    .files
+                    .into_iter()
+                    .map(|file| (file.path.clone(), file))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            directory: directory.to_path_buf(),
+            root: directory.to_path_buf(),
+            previous,
+            current: HashMap::new(),
+            hits: 0,
+            hash_hits: 0,
+        }
+    }
+
+    /// What `path` is stored under: its path relative to the scanned directory
+    fn key(&self, path: &Path) -> OsString {
+        path.strip_prefix(&self.root)
+            .unwrap_or(path)
+            .as_os_str()
+            .to_os_string()
+    }
+
+    /// The entry for `path`, reusing the last scan's shared-extent count when the
+    /// file is unchanged (otherwise scanned like `FileEntry::from_path`)
+    pub fn entry(&mut self, path: &Path) -> io::Result<FileEntry> {
+        let metadata = fs::metadata(path)?;
+        let key = self.key(path);
+        let cached = self
+            .previous
+            .get(&key)
+            .filter(|file| file.matches(&metadata));
+        let entry = match cached {
+            Some(file) => {
+                self.hits += 1;
+                FileEntry::with_metadata(path, &metadata, file.shared)?
+            }
+            None => {
+                let shared = crate::filesystem::shared_bytes(path).unwrap_or(0);
+                FileEntry::with_metadata(path, &metadata, shared)?
+            }
+        };
+        if let Some(mut file) = IndexedFile::describe(key.clone(), &entry) {
+            // An unchanged file's hashes still hold
+            if let Some(cached) = cached {
+                file.hashes = cached.hashes.clone();
+            }
+            self.current.insert(key, file);
+        }
+        Ok(entry)
+    }
+
+    /// Files this scan took from the index instead of the filesystem
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Hashes taken from the index instead of reading the file
+    pub fn hash_hits(&self) -> usize {
+        self.hash_hits
+    }
+
+    /// Replaces the stored index with this scan's files (vanished files drop out)
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let stored = StoredIndex {
+            version: INDEX_VERSION,
+            directory: self.directory.clone(),
+            files: self.current.values().cloned().collect(),
+        };
+        let data = serde_json::to_vec(&stored).map_err(io::Error::other)?;
+        // Write then rename, so an interrupted save never leaves half an index
+        let partial = self.path.with_extension("json.partial");
+        fs::write(&partial, data)?;
+        fs::rename(&partial, &self.path)
+    }
+}
+
+/// The scan index, shared with the workers that hash files so they reuse the
+/// hashes it remembers and add the ones they compute. Without an index (the
+/// default, for `--no-index-cache` and network mounts) every file is read.
+#[derive(Debug, Clone, Default)]
+pub struct HashCache(Option<Arc<Mutex<ScanIndex>>>);
+
+impl HashCache {
+    /// Hashes kept in `index`, which has to have scanned the files being hashed
+    pub fn new(index: ScanIndex) -> Self {
+        Self(Some(Arc::new(Mutex::new(index))))
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    fn index(&self) -> Option<MutexGuard<'_, ScanIndex>> {
+        self.0.as_ref()?.lock().ok()
+    }
+
+    /// The `algorithm` hash of the file at `path`: from the index when the file is
+    /// unchanged since it was hashed, otherwise read (see `HashAlgorithm::hash_file`)
+    /// and remembered
+    pub fn hash_file(&self, path: &Path, algorithm: HashAlgorithm) -> io::Result<String> {
+        if !self.is_enabled() {
+            return algorithm.hash_file(path);
+        }
+        let metadata = fs::metadata(path)?;
+        if let Some(mut index) = self.index() {
+            let key = index.key(path);
+            let cached = index
+                .current
+                .get(&key)
+                .filter(|file| file.matches(&metadata))
+                .and_then(|file| file.hashes.get(&algorithm))
+                .cloned();
+            if let Some(hash) = cached {
+                index.hash_hits += 1;
+                return Ok(hash);
+            }
+        }
+        let hash = algorithm.hash_file(path)?;
+        // Only kept when the file didn't change while it was read
+        let after = fs::metadata(path)?;
+        if let Some(mut index) = self.index() {
+            let key = index.key(path);
+            if let Some(file) = index
+                .current
+                .get_mut(&key)
+                .filter(|file| file.matches(&metadata) && file.matches(&after))
+            {
+                file.hashes.insert(algorithm, hash.clone());
+            }
+        }
+        Ok(hash)
+    }
+
+    /// Saves the index with the hashes added since it was loaded
+    pub fn save(&self) -> io::Result<()> {
+        match self.index() {
+            Some(index) => index.save(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Directory holding scan indexes (`<cache dir>/fswp/index`)
+pub fn index_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("fswp").join("index"))
+}
+
+#[cfg(unix)]
+fn file_id(metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// FNV-1a, a hash that stays the same across builds (unlike `DefaultHasher`)
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{discover_files_with, DiscoveryOptions};
+    use tempfile::TempDir;
+
+    fn scan(index_path: &Path, dir: &Path) -> (Vec<FileEntry>, usize) {
+        scan_with(index_path, dir, &DiscoveryOptions::default())
+    }
+
+    fn scan_with(
+        index_path: &Path,
+        dir: &Path,
+        options: &DiscoveryOptions,
+    ) -> (Vec<FileEntry>, usize) {
+        let mut index = ScanIndex::load_from(index_path.to_path_buf(), dir);
+        let files = discover_files_with(dir, options, |path| index.entry(path)).unwrap();
+        index.save().unwrap();
+        (files, index.hits())
+    }
+
+    #[test]
+    fn test_scan_index_reuses_unchanged_files() {
+        let dir = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let index_path = cache.path().join("index.json");
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+        fs::write(dir.path().join("b.txt"), "beta").unwrap();
+
+        let (files, hits) = scan(&index_path, dir.path());
+        assert_eq!((files.len(), hits), (2, 0));
+
+        let (files, hits) = scan(&index_path, dir.path());
+        assert_eq!((files.len(), hits), (2, 2));
+
+        // A changed size is a miss; a new file is a miss; a removed one drops out
+        fs::write(dir.path().join("a.txt"), "alpha, longer").unwrap();
+        fs::write(dir.path().join("c.txt"), "gamma").unwrap();
+        fs::remove_file(dir.path().join("b.txt")).unwrap();
+        let (files, hits) = scan(&index_path, dir.path());
+        assert_eq!((files.len(), hits), (2, 0));
+        let a = files.iter().find(|f| f.name == "a.txt").unwrap();
+        assert_eq!(a.size, 13);
+
+        let index = ScanIndex::load_from(index_path, dir.path());
+        assert_eq!(index.previous.len(), 2);
+        assert!(!index.previous.contains_key(std::ffi::OsStr::new("b.txt")));
+    }
+
+    #[test]
+    fn test_scan_index_keeps_same_named_files_apart() {
+        let dir = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let index_path = cache.path().join("index.json");
+        for (sub, contents) in [("a", "alpha"), ("b", "beta, longer")] {
+            fs::create_dir(dir.path().join(sub)).unwrap();
+            fs::write(dir.path().join(sub).join("notes.txt"), contents).unwrap();
+        }
+        let options = DiscoveryOptions {
+            recursive: true,
+            ..Default::default()
+        };
+
+        let (files, hits) = scan_with(&index_path, dir.path(), &options);
+        assert_eq!((files.len(), hits), (2, 0));
+        let (files, hits) = scan_with(&index_path, dir.path(), &options);
+        assert_eq!((files.len(), hits), (2, 2));
+
+        let index = ScanIndex::load_from(index_path, dir.path());
+        assert!(index
+            .previous
+            .contains_key(Path::new("a").join("notes.txt").as_os_str()));
+    }
+
+    #[test]
+    fn test_scan_index_ignores_other_directories_and_garbage() {
+        let dir = TempDir::new().unwrap();
+        let other = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let index_path = cache.path().join("index.json");
+        fs::write(dir.path().join("a.txt"), "alpha").unwrap();
+
+        scan(&index_path, dir.path());
+        assert!(ScanIndex::load_from(index_path.clone(), other.path())
+            .previous
+            .is_empty());
+
+        fs::write(&index_path, "not json").unwrap();
+        assert!(ScanIndex::load_from(index_path, dir.path())
+            .previous
+            .is_empty());
+    }
+
+    #[test]
+    fn test_hash_cache_reuses_hashes_of_unchanged_files() {
+        let dir = TempDir::new().unwrap();
+        let cache = TempDir::new().unwrap();
+        let index_path = cache.path().join("index.json");
+        let a = dir.path().join("a.txt");
+        fs::write(&a, "alpha").unwrap();
+        let hashes = || {
+            let mut index = ScanIndex::load_from(index_path.clone(), dir.path());
+            discover_files_with(dir.path(), &DiscoveryOptions::default(), |path| {
+                index.entry(path)
+            })
+            .unwrap();
+            HashCache::new(index)
+        };
+        let hash_hits = |hashes: &HashCache| hashes.index().unwrap().hash_hits();
+        let blake3 = HashAlgorithm::Blake3;
+
+        let first = hashes();
+        let hash = first.hash_file(&a, blake3).unwrap();
+        assert_eq!(hash, blake3.hash_file(&a).unwrap());
+        assert_eq!(hash_hits(&first), 0);
+        first.save().unwrap();
+
+        // Kept per algorithm
+        let second = hashes();
+        assert_eq!(second.hash_file(&a, blake3).unwrap(), hash);
+        assert_eq!(hash_hits(&second), 1);
+        second.hash_file(&a, HashAlgorithm::Sha256).unwrap();
+        assert_eq!(hash_hits(&second), 1);
+        second.save().unwrap();
+
+        // A changed file is read again
+        fs::write(&a, "alpha, longer").unwrap();
+        let third = hashes();
+        assert_ne!(third.hash_file(&a, blake3).unwrap(), hash);
+        assert_eq!(hash_hits(&third), 0);
+
+        // Without an index every file is read
+        assert_eq!(
+            HashCache::default().hash_file(&a, blake3).unwrap(),
+            blake3.hash_file(&a).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fnv1a_is_stable() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+}
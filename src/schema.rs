@@ -0,0 +1,63 @@
+//! Module for the versions of the JSON files fswp writes for later reading
+//!
+//! The backup manifest (what an apply took out and where it went) and the session
+//! journal (a header line, then one event per keep, trash, move, skip, undo or
+//! applied trash) are read back by `fswp restore`, `replay`, `digest` and `audit`,
+//! and are meant to be read by other tools too. Each records a `schema_version`:
+//! the manifest at its top level, the journal in its header line. The version goes
+//! up when a field is removed, renamed or changes meaning; a new optional field or
+//! journal event leaves it alone, so readers should ignore keys and events they
+//! don't know.
+//!
+//! Files written before versions were recorded have none and read as version 0,
+//! which has the same fields as version 1. A file with a version newer than this
+//! build knows is refused rather than half-read.
+
+use crate::error::{FileTinderError, Result};
+use serde_json::Value;
+
+/// Schema of backup manifests (`manifest::Manifest`)
+pub const MANIFEST_VERSION: u32 = 1;
+/// Schema of session journals (the `SessionInfo` header and `JournalRecord` lines)
+pub const JOURNAL_VERSION: u32 = 1;
+
+/// The `schema_version` of a JSON object; 0 when it has none
+pub fn version_of(value: &Value) -> u32 {
+    value
+        .get("schema_version")
+        .and_then(Value::as_u64)
+        .map_or(0, |version| version.try_into().unwrap_or(u32::MAX))
+}
+
+/// Refuses a `what` file at `version` when it's newer than `newest`
+pub fn check(what: &'static str, version: u32, newest: u32) -> Result<()> {
+    if version > newest {
+        return Err(FileTinderError::UnsupportedSchema {
+            what,
+            version,
+            newest,
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_version_of_and_check() {
+        assert_eq!(version_of(&json!({"schema_version": 1})), 1);
+        assert_eq!(version_of(&json!({"entries": []})), 0);
+        assert_eq!(version_of(&json!({"schema_version": "1"})), 0);
+
+        assert!(check("Backup manifest", 0, MANIFEST_VERSION).is_ok());
+        assert!(check("Backup manifest", 1, MANIFEST_VERSION).is_ok());
+        assert_eq!(
+            check("Backup manifest", 2, 1).unwrap_err().to_string(),
+            "Backup manifest uses schema version 2, but this fswp reads up to version 1; \
+             a newer fswp wrote it"
+        );
+    }
+}
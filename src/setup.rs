@@ -0,0 +1,174 @@
+//! First-run setup wizard
+//!
+//! Shown when no config file exists yet: a few multiple-choice questions whose
+//! answers are written to the config before the first session starts.
+
+use crate::config::UserConfig;
+use crate::domain::{DefaultDecision, FileType};
+use crate::tui::input::KeyLayout;
+
+/// One question of the wizard; the first option matches the config default
+#[derive(Debug)]
+pub struct SetupStep {
+    pub question: &'static str,
+    /// (label, description) pairs
+    pub options: &'static [(&'static str, &'static str)],
+}
+
+pub const SETUP_STEPS: [SetupStep; 4] = [
+    SetupStep {
+        question: "After you keep or trash a file",
+        options: &[
+            ("Move to the next file", "Fastest for long sessions"),
+            ("Stay on the file", "Press ↓ to move on yourself"),
+        ],
+    },
+    SetupStep {
+        question: "When you browse past a file with the arrow keys",
+        options: &[
+            ("Mark it as seen", "Shows a [seen] badge when you come back"),
+            ("Leave it unmarked", "Only decisions are tracked"),
+        ],
+    },
+    SetupStep {
+        question: "Safety level",
+        options: &[
+            ("Standard", "Confirm each trash, unless started with --yes"),
+            (
+                "Careful",
+                "Always confirm trash, even with --yes or --dry-run",
+            ),
+        ],
+    },
+    SetupStep {
+        question: "Keyboard layout",
+        options: &[
+            (
+                "Keys as printed",
+                "k keeps, t trashes, j / i move, on any keyboard",
+            ),
+            (
+                "AZERTY, QWERTY positions",
+                "Same spots as on QWERTY; digits without Shift",
+            ),
+            ("Dvorak, QWERTY positions", "Same spots as on QWERTY"),
+        ],
+    },
+];
+
+/// Progress through the setup questions
+#[derive(Debug, Clone, Default)]
+pub struct SetupWizard {
+    /// Chosen option per answered step
+    pub choices: Vec<usize>,
+    /// Highlighted option of the current step
+    pub selected: usize,
+}
+
+impl SetupWizard {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Index of the question being asked
+    pub fn step(&self) -> usize {
+        self.choices.len()
+    }
+
+    pub fn current(&self) -> Option<&'static SetupStep> {
+        SETUP_STEPS.get(self.step())
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current().is_none()
+    }
+
+    pub fn select_next(&mut self) {
+        if let Some(step) = self.current() {
+            self.selected = (self.selected + 1).min(step.options.len() - 1);
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    /// Answers the current question with the highlighted option
+    pub fn confirm(&mut self) {
+        if !self.is_finished() {
+            self.choices.push(self.selected);
+            self.selected = 0;
+        }
+    }
+
+    /// Answers the remaining questions with their defaults
+    pub fn skip(&mut self) {
+        self.choices.resize(SETUP_STEPS.len(), 0);
+        self.selected = 0;
+    }
+
+    /// Writes the answers into `config`
+    pub fn apply(&self, config: &mut UserConfig) {
+        let choice = |step: usize| self.choices.get(step).copied().unwrap_or(0);
+        config.auto_advance = choice(0) == 0;
+        config.mark_seen_on_browse = choice(1) == 0;
+        if choice(2) == 1 {
+            for file_type in FileType::ALL {
+                config
+                    .default_decision
+                    .insert(format!("{:?}", file_type), DefaultDecision::AlwaysAsk);
+            }
+        }
+        config.key_layout = match choice(3) {
+            1 => KeyLayout::Azerty,
+            2 => KeyLayout::Dvorak,
+            _ => KeyLayout::Letters,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_setup_wizard_answers_apply_to_config() {
+        let mut wizard = SetupWizard::new();
+        wizard.select_next();
+        wizard.select_next(); // clamped to the last option
+        wizard.confirm();
+        wizard.confirm();
+        wizard.select_next();
+        wizard.confirm();
+        wizard.select_next();
+        wizard.select_next();
+        wizard.confirm();
+        assert!(wizard.is_finished());
+
+        let mut config = UserConfig::default();
+        wizard.apply(&mut config);
+        assert!(!config.auto_advance);
+        assert!(config.mark_seen_on_browse);
+        assert_eq!(
+            config.default_decisions().get(&FileType::Image),
+            Some(&DefaultDecision::AlwaysAsk)
+        );
+        assert_eq!(config.key_layout, KeyLayout::Dvorak);
+    }
+
+    #[test]
+    fn test_setup_wizard_skip_keeps_defaults() {
+        let mut wizard = SetupWizard::new();
+        wizard.select_next();
+        wizard.confirm();
+        wizard.skip();
+        assert!(wizard.is_finished());
+
+        let mut config = UserConfig::default();
+        wizard.apply(&mut config);
+        assert!(!config.auto_advance);
+        assert!(config.mark_seen_on_browse);
+        assert!(config.default_decision.is_empty());
+        assert_eq!(config.key_layout, KeyLayout::Letters);
+    }
+}
@@ -0,0 +1,181 @@
+//! Module for listing the system trash
+//!
+//! `fswp purge`, `fswp empty-trash` and `fswp restore` all start from what's in
+//! the system trash, and the doctor checks it can be read. The trash crate lists,
+//! purges and restores trashed items on Windows and freedesktop systems only: on
+//! macOS the Finder keeps no record of where a file came from, so `list()` fails
+//! there with `io::ErrorKind::Unsupported`.
+
+use crate::hashing::HashAlgorithm;
+use crate::retention::TrashedFile;
+use chrono::DateTime;
+use std::io;
+
+/// What the system trash held when it was listed
+#[derive(Debug, Default)]
+pub struct TrashListing {
+    items: Vec<trash::TrashItem>,
+    /// Each item's original path and when it was trashed, in listing order
+    pub files: Vec<TrashedFile>,
+}
+
+impl TrashListing {
+    /// Deletes the items at `positions` (in `files`) for good
+    pub fn purge(&self, positions: impl IntoIterator<Item = usize>) -> io::Result<()> {
+        os::purge_all(
+            positions
+                .into_iter()
+                .map(|p| self.items[p].clone())
+                .collect(),
+        )
+    }
+
+    /// Whether the item at `position` (in `files`) is the file recorded with `size`
+    /// and, when one was recorded, `hash`: the same size and, where the trash lets
+    /// its contents be read in place, the same hash. What the platform can't tell
+    /// about an item doesn't count against it.
+    pub fn holds(
+        &self,
+        position: usize,
+        size: u64,
+        hash: Option<&str>,
+        algorithm: HashAlgorithm,
+    ) -> bool {
+        let item = &self.items[position];
+        if os::size(item).is_some_and(|actual| actual != size) {
+            return false;
+        }
+        match (os::contents(item), hash) {
+            (Some(path), Some(hash)) => algorithm
+                .hash_file(&path)
+                .map_or(true, |actual| actual == hash),
+            _ => true,
+        }
+    }
+
+    /// The item at `position`'s size in bytes; `None` for a directory, or where
+    /// the platform can't tell
+    pub fn size(&self, position: usize) -> Option<u64> {
+        os::size(&self.items[position])
+    }
+
+    /// Puts the item at `position` (in `files`) back where it was trashed from
+    pub fn restore(&self, position: usize) -> io::Result<()> {
+        os::restore_all(vec![self.items[position].clone()])
+    }
+}
+
+/// Lists the system trash
+pub fn list() -> io::Result<TrashListing> {
+    let items = os::list()?;
+    let files = items
+        .iter()
+        .map(|item| TrashedFile {
+            path: item.original_path(),
+            deleted: DateTime::from_timestamp(item.time_deleted, 0).unwrap_or_default(),
+        })
+        .collect();
+    Ok(TrashListing { items, files })
+}
+
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+mod os {
+    use std::fs;
+    use std::io;
+    use std::path::{Path, PathBuf};
+    use trash::{os_limited, TrashItem};
+
+    fn other(e: trash::Error) -> io::Error {
+        io::Error::other(e.to_string())
+    }
+
+    pub fn list() -> io::Result<Vec<TrashItem>> {
+        os_limited::list().map_err(other)
+    }
+
+    pub fn purge_all(items: Vec<TrashItem>) -> io::Result<()> {
+        os_limited::purge_all(items).map_err(other)
+    }
+
+    pub fn restore_all(items: Vec<TrashItem>) -> io::Result<()> {
+        os_limited::restore_all(items).map_err(other)
+    }
+
+    /// Where a freedesktop trash keeps the item: under `files`, named after its
+    /// `.trashinfo` file (the item's id). The Windows recycle bin has no such path.
+    pub fn contents(item: &TrashItem) -> Option<PathBuf> {
+        if cfg!(windows) {
+            return None;
+        }
+        let info = Path::new(&item.id);
+        Some(
+            info.parent()?
+                .parent()?
+                .join("files")
+                .join(info.file_stem()?),
+        )
+    }
+
+    /// A trashed file's size in bytes; `None` for a directory
+    pub fn size(item: &TrashItem) -> Option<u64> {
+        match contents(item) {
+            Some(path) => fs::symlink_metadata(path)
+                .ok()
+                .filter(|metadata| metadata.is_file())
+                .map(|metadata| metadata.len()),
+            None => os_limited::metadata(item).ok()?.size.size(),
+        }
+    }
+}
+
+/// The system trash can't be listed on macOS, so nothing is ever purged or
+/// restored from it either
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+mod os {
+    use std::io;
+    use std::path::PathBuf;
+    use trash::TrashItem;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the system trash can't be listed on this platform",
+        )
+    }
+
+    pub fn list() -> io::Result<Vec<TrashItem>> {
+        Err(unsupported())
+    }
+
+    pub fn purge_all(_items: Vec<TrashItem>) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn restore_all(_items: Vec<TrashItem>) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn contents(_item: &TrashItem) -> Option<PathBuf> {
+        None
+    }
+
+    pub fn size(_item: &TrashItem) -> Option<u64> {
+        None
+    }
+}
@@ -0,0 +1,3377 @@
+// Session model and reducer: every key becomes a `KeyAction`, applied by `App::update`
+use super::help::HelpState;
+use super::helpers::format_file_size;
+use super::input::{
+    handle_confirm_input, handle_conflict_input, handle_key_event, ConflictChoice, KeyAction,
+};
+use super::{ViewState, SELECTION_SAMPLE};
+use crate::config::{ConfigWatcher, UserConfig};
+use crate::content_search::ContentSearch;
+use crate::diagnostics::PreviewFailure;
+use crate::dir_size::DirSizer;
+use crate::domain::{
+    find_easy_wins, find_renames, reclaimable_bytes, AppState, ApplyJob, CopyFinder, Decision,
+    DefaultDecision, DirectoryStats, DiscoveryOptions, DuplicateFinder, DuplicateGroup, EasyWin,
+    EasyWinKind, FileEntry, FileType, LeftOut, NamePattern, RenameSuggestion, ReviewPhase,
+    ScanFilter, ScanOverview, SortBy,
+};
+use crate::manifest::{Manifest, ManifestWriter};
+use crate::preview::{ImageCommand, ScrollCommand};
+use crate::setup::SetupWizard;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+use ratatui::layout::Rect;
+use std::collections::HashSet;
+use std::io;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// The orders `O` steps through: sort key, reversed, and how the notice names it
+const SORT_ORDERS: [(SortBy, bool, &str); 6] = [
+    (SortBy::Date, false, "oldest first"),
+    (SortBy::Date, true, "newest first"),
+    (SortBy::Size, true, "largest first"),
+    (SortBy::Name, false, "by name"),
+    (SortBy::Type, false, "by type"),
+    (SortBy::Random, false, "shuffled"),
+];
+
+/// Decisions closer together than this count toward a rapid burst
+const RAPID_GAP: Duration = Duration::from_millis(200);
+/// Decisions in one burst before the session stops to ask about them
+const RAPID_COUNT: usize = 8;
+
+/// Work `update` leaves to the event loop because it needs the terminal or the disk
+#[derive(Debug)]
+pub enum Effect {
+    None,
+    /// Leave the main loop
+    Exit,
+    /// The current file changed; reload its preview
+    ResetPreview,
+    /// Open the file in an external application
+    Open(PathBuf),
+    /// Suspend the process (job control)
+    Suspend,
+    /// Drop to a subshell in the scanned directory
+    Shell,
+    /// Write the decisions so far to an HTML report
+    ExportReport,
+    /// Open the details of the current file's preview, if it failed
+    ShowPreviewFailure,
+    /// Write `App.preview_failure` to a diagnostics file
+    SaveDiagnostics,
+    /// Put this text on the clipboard (through the terminal)
+    CopyToClipboard(String),
+    /// Store a newly recorded macro in the config file
+    SaveMacro(Vec<String>),
+    /// Write the setup wizard's answers to the config file
+    SaveConfig(Box<UserConfig>),
+    /// Persist that the welcome screen has been seen
+    MarkWelcomeShown,
+    /// Record what the apply is about to take before it starts (see `manifest`)
+    WriteManifest,
+    /// Zoom or pan the current image's preview
+    AdjustImage(ImageCommand),
+    /// Scroll the preview pane
+    ScrollPreview(ScrollCommand),
+}
+
+/// A move held up because the target directory already has a file of that name
+#[derive(Debug, Clone)]
+pub struct MoveConflict {
+    /// The file being moved
+    pub index: usize,
+    /// Where it was going
+    pub dir: PathBuf,
+    /// The file in the way and whether both have the same bytes, once compared
+    pub compared: Option<(FileEntry, bool)>,
+}
+
+/// Decisions made in quick succession, each within `RAPID_GAP` of the one before
+#[derive(Debug, Clone)]
+pub struct RapidBurst {
+    /// Position on the undo stack of the burst's first decision
+    pub start: usize,
+    /// When the latest decision came
+    pub last: Instant,
+    /// The screen the last decision would have led to, while the prompt is open
+    pub after: Option<ViewState>,
+    /// The user said the burst was deliberate; don't ask again until it ends
+    pub confirmed: bool,
+}
+
+/// What Enter does on a row of the filter inspector
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InspectorAction {
+    /// Step to the next sort order, as `O` does
+    CycleSort,
+    /// Turn a scan filter off, adding the files it left out to the queue
+    TurnOff(ScanFilter),
+    /// Bring back the full queue
+    ClearNarrowing,
+    /// Stop suggesting the config's default decision for a type, for this session
+    DropRule(FileType),
+}
+
+/// One line of the filter inspector: what's active, its effect, and what Enter does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InspectorRow {
+    pub label: String,
+    pub detail: String,
+    /// `None` for rows that can't be changed mid-session
+    pub action: Option<InspectorAction>,
+}
+
+/// The pause screen's state: the passphrase being typed, and what it's for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PauseScreen {
+    /// A new session passphrase is being chosen (`H` before one is set)
+    pub setting: bool,
+    /// What's been typed, shown masked
+    pub input: String,
+    /// The last passphrase entered didn't match
+    pub wrong: bool,
+}
+
+/// Everything the main loop renders and updates, apart from the terminal itself
+#[derive(Debug)]
+pub struct App {
+    pub state: AppState,
+    pub view: ViewState,
+    pub user_config: UserConfig,
+    pub help: HelpState,
+    /// Present while the first-run questions are being asked
+    pub setup: Option<SetupWizard>,
+    /// Set when the session ends and trashed files are being moved to the system trash
+    pub apply_job: Option<ApplyJob>,
+    /// Trash without confirmation (`--yes`)
+    pub skip_confirm: bool,
+    /// Re-check original paths after apply (`--verify`)
+    pub verify: bool,
+    /// Trash older decisions while browsing (`--background-apply`)
+    pub background_apply: bool,
+    /// Trash each decision once its undo grace period is over (`--apply-on-decide`)
+    pub apply_on_decide: bool,
+    /// Picks up edits to the config file so settings can be tweaked without quitting
+    pub config_watcher: Option<ConfigWatcher>,
+    /// The pattern and matching files while "select all like this" is open
+    pub selection: Option<(NamePattern, Vec<usize>)>,
+    /// What's typed into the content search prompt
+    pub search_input: String,
+    /// The content search running in the background, if any
+    pub search: Option<ContentSearch>,
+    /// Directory items being measured in the background, if any
+    pub sizer: Option<DirSizer>,
+    /// The filter bar's expression, applied to the queue on every edit
+    pub filter_input: String,
+    /// Why the filter bar's expression doesn't parse; the last one that did stays applied
+    pub filter_error: Option<String>,
+    /// The config preset last applied (Tab in the filter bar moves on from it)
+    pub preset: Option<String>,
+    /// Focus mode: only the file name, preview and swipe hints are drawn (`Z`)
+    pub focus: bool,
+    /// The queue list is shown beside the preview (`L`)
+    pub queue_panel: bool,
+    /// File whose preview stays beside the current one for comparison (`P`)
+    pub pinned: Option<usize>,
+    /// Order of the queue and whether it's reversed; `O` steps through `SORT_ORDERS`
+    pub sort: (SortBy, bool),
+    /// The burst of decisions in progress, if the latest came quickly after another
+    pub rapid: Option<RapidBurst>,
+    /// A macro is being replayed: its decisions are quick by design, not a stuck key
+    replaying: bool,
+    /// Steps of the macro being recorded, while `M` is recording
+    pub recording: Option<Vec<String>>,
+    /// Sidecars offered along with the current file in the trash confirmation
+    pub sidecars: Vec<usize>,
+    /// Bulk trash suggestions still to be answered (`--easy-wins`)
+    pub easy_wins: Vec<EasyWin>,
+    /// The highlighted group on the easy wins screen
+    pub easy_wins_cursor: usize,
+    /// Whether identical copies are still to join the easy wins once the copy
+    /// finder is done
+    pub easy_wins_pending: bool,
+    /// Byte-identical copies being looked for in the background
+    pub copy_finder: Option<CopyFinder>,
+    /// Groups of identical files still to be answered, current one first (`--duplicates`)
+    pub duplicates: Vec<DuplicateGroup>,
+    /// The file to keep in the current duplicate group
+    pub duplicates_cursor: usize,
+    /// Duplicates being looked for in the background (`--duplicates`)
+    pub duplicate_finder: Option<DuplicateFinder>,
+    /// Details of the failed preview, while they're shown
+    pub preview_failure: Option<PreviewFailure>,
+    /// Tallies shown on the scan overview, while it's open
+    pub overview: Option<ScanOverview>,
+    /// The move waiting for an answer on the conflict prompt
+    pub move_conflict: Option<MoveConflict>,
+    /// Offer to fix kept files' names before applying (`--normalize-names`)
+    pub normalize_names: bool,
+    /// Files decided as trash, while the review screen before applying is open
+    pub review: Vec<usize>,
+    /// The highlighted file on the review screen
+    pub review_cursor: usize,
+    /// Renames offered for kept files, while the rename screen is open
+    pub renames: Vec<RenameSuggestion>,
+    /// The highlighted rename
+    pub renames_cursor: usize,
+    /// Renames carried out (or, in a dry run, that would have been): original
+    /// path and the new name or why it failed
+    pub renamed: Vec<(PathBuf, Result<String, String>)>,
+    /// Where the backup manifest was written before applying, or why it wasn't
+    pub manifest: Option<Result<PathBuf, String>>,
+    /// The backup manifest being hashed; the apply waits until it's saved
+    pub manifest_writer: Option<ManifestWriter>,
+    /// The files the scan's filters left out, and the filters turned off since
+    pub left_out: LeftOut,
+    /// The highlighted row of the filter inspector (`I`)
+    pub inspector_cursor: usize,
+    /// Present while the screen is paused
+    pub pause: Option<PauseScreen>,
+    /// Needed to resume from a pause once set with `H`; kept in memory for this
+    /// session only
+    pub passphrase: Option<String>,
+}
+
+impl App {
+    pub fn new(state: AppState, user_config: UserConfig) -> Self {
+        Self {
+            state,
+            view: ViewState::Browsing,
+            user_config,
+            help: HelpState::new(),
+            setup: None,
+            apply_job: None,
+            skip_confirm: false,
+            verify: false,
+            background_apply: false,
+            apply_on_decide: false,
+            config_watcher: None,
+            selection: None,
+            search_input: String::new(),
+            search: None,
+            sizer: None,
+            filter_input: String::new(),
+            filter_error: None,
+            preset: None,
+            focus: false,
+            queue_panel: false,
+            pinned: None,
+            sort: (SortBy::Date, false),
+            rapid: None,
+            replaying: false,
+            recording: None,
+            sidecars: Vec::new(),
+            easy_wins: Vec::new(),
+            easy_wins_cursor: 0,
+            easy_wins_pending: false,
+            copy_finder: None,
+            duplicates: Vec::new(),
+            duplicates_cursor: 0,
+            duplicate_finder: None,
+            preview_failure: None,
+            overview: None,
+            move_conflict: None,
+            normalize_names: false,
+            review: Vec::new(),
+            review_cursor: 0,
+            renames: Vec::new(),
+            renames_cursor: 0,
+            renamed: Vec::new(),
+            manifest: None,
+            manifest_writer: None,
+            left_out: LeftOut::default(),
+            inspector_cursor: 0,
+            pause: None,
+            passphrase: None,
+        }
+    }
+
+    /// Translates a key press into a command for the current view
+    pub fn action_for_key(&self, key: KeyEvent) -> KeyAction {
+        match self.view {
+            ViewState::Help => KeyAction::HelpInput(key),
+            ViewState::Search => KeyAction::SearchInput(key),
+            ViewState::Filter => KeyAction::FilterInput(key),
+            ViewState::Paused => KeyAction::PauseInput(key),
+            ViewState::ConfirmTrash | ViewState::GoalReached | ViewState::RapidDecisions => {
+                handle_confirm_input(key)
+            }
+            ViewState::MoveConflict => handle_conflict_input(key),
+            ViewState::Setup
+            | ViewState::EasyWins
+            | ViewState::Duplicates
+            | ViewState::Review
+            | ViewState::Renames
+            | ViewState::Inspector
+                if key.code == KeyCode::Enter =>
+            {
+                KeyAction::Select
+            }
+            ViewState::Setup
+            | ViewState::Browsing
+            | ViewState::SelectSimilar
+            | ViewState::EasyWins
+            | ViewState::Duplicates
+            | ViewState::Review
+            | ViewState::Renames
+            | ViewState::Inspector => handle_key_event(key),
+            ViewState::Applying => match handle_key_event(key) {
+                KeyAction::Quit => KeyAction::Quit,
+                _ => KeyAction::Continue,
+            },
+            ViewState::Overview => KeyAction::Continue,
+            ViewState::PreviewError => match key.code {
+                KeyCode::Char('b') => KeyAction::SaveDiagnostics,
+                _ => KeyAction::Continue,
+            },
+            // s reviews the skipped files, k / t copy the kept / trashed paths;
+            // anything else applies
+            ViewState::Summary => match key.code {
+                KeyCode::Char('s') => KeyAction::Skip,
+                KeyCode::Char('k') => KeyAction::CopyPaths(Decision::Keep),
+                KeyCode::Char('t') => KeyAction::CopyPaths(Decision::Trash),
+                _ => KeyAction::Continue,
+            },
+            ViewState::Welcome => KeyAction::Continue,
+        }
+    }
+
+    /// Translates a mouse event on a frame of `area` into a command: a left click on
+    /// the header's progress bar jumps to that part of the queue
+    pub fn action_for_click(&self, mouse: MouseEvent, area: Rect) -> KeyAction {
+        // Focus mode has no progress bar to click
+        if self.view != ViewState::Browsing
+            || self.focus
+            || mouse.kind != MouseEventKind::Down(MouseButton::Left)
+        {
+            return KeyAction::None;
+        }
+        super::queue_position_at(area, &self.state, mouse.column, mouse.row)
+            .map_or(KeyAction::None, KeyAction::Goto)
+    }
+
+    /// Applies a command to the session and returns what the event loop still has to do
+    pub fn update(&mut self, action: KeyAction) -> Effect {
+        match self.view {
+            ViewState::Help => {
+                // Typing searches the bindings; ? or Esc closes
+                if let KeyAction::HelpInput(key) = action {
+                    if self.help.handle_key(key) {
+                        self.view = ViewState::Browsing;
+                    }
+                }
+                Effect::None
+            }
+            ViewState::Summary => match action {
+                KeyAction::CopyPaths(decision) => self.copy_paths(&decision),
+                KeyAction::Skip if self.state.start_skipped_pass() => {
+                    self.view = ViewState::Browsing;
+                    Effect::ResetPreview
+                }
+                // Nothing skipped: s applies like any other key
+                _ => self.offer_review(),
+            },
+            ViewState::Applying => match self.apply_job {
+                // Cancel stops between files and puts the rest back
+                Some(ref mut job) if !job.is_finished() => {
+                    if action == KeyAction::Quit {
+                        self.state.engine.cancel_apply(job);
+                        // Files trashed in the background or moved still need theirs
+                        let went_earlier = (0..self.state.files.len()).any(|index| {
+                            self.state.engine.trashed_at(index).is_some()
+                                || matches!(self.state.decision_of(index), Some(Decision::Move(_)))
+                        });
+                        if !went_earlier {
+                            self.manifest_writer = None;
+                        }
+                    }
+                    Effect::None
+                }
+                // The files are gone already, so their manifest is waited for
+                _ if self.manifest_writer.is_some() => Effect::None,
+                // Any key exits once the results are shown
+                _ => Effect::Exit,
+            },
+            ViewState::ConfirmTrash => match action {
+                KeyAction::ConfirmTrash | KeyAction::TrashAlone => {
+                    let sidecars = std::mem::take(&mut self.sidecars);
+                    let effect = if action == KeyAction::ConfirmTrash && !sidecars.is_empty() {
+                        self.trash_with_sidecars(&sidecars)
+                    } else {
+                        self.decide(Decision::Trash)
+                    };
+                    if matches!(effect, Effect::None) {
+                        self.view = ViewState::Browsing;
+                    }
+                    effect
+                }
+                KeyAction::CancelTrash => {
+                    self.sidecars.clear();
+                    // A cancelled trash isn't part of the macro being recorded
+                    if let Some(steps) = self.recording.as_mut() {
+                        steps.pop();
+                    }
+                    self.view = ViewState::Browsing;
+                    Effect::None
+                }
+                _ => Effect::None,
+            },
+            ViewState::GoalReached => {
+                // Yes applies the session and stops; no keeps reviewing
+                match action {
+                    KeyAction::ConfirmTrash => self.view = ViewState::Summary,
+                    KeyAction::CancelTrash => self.view = ViewState::Browsing,
+                    _ => {}
+                }
+                Effect::None
+            }
+            ViewState::RapidDecisions => self.update_rapid(action),
+            ViewState::Setup => self.update_setup(action),
+            ViewState::SelectSimilar => self.update_selection(action),
+            ViewState::Search => self.update_search(action),
+            ViewState::Filter => self.update_filter(action),
+            ViewState::EasyWins => self.update_easy_wins(action),
+            ViewState::Duplicates => self.update_duplicates(action),
+            ViewState::MoveConflict => self.update_move_conflict(action),
+            ViewState::Review => self.update_review(action),
+            ViewState::Renames => self.update_renames(action),
+            ViewState::Inspector => self.update_inspector(action),
+            ViewState::Paused => self.update_paused(action),
+            ViewState::Overview => {
+                // Any key goes back to reviewing
+                self.overview = None;
+                self.view = ViewState::Browsing;
+                Effect::None
+            }
+            ViewState::PreviewError => match action {
+                KeyAction::SaveDiagnostics => Effect::SaveDiagnostics,
+                _ => {
+                    self.preview_failure = None;
+                    self.view = ViewState::Browsing;
+                    Effect::None
+                }
+            },
+            ViewState::Welcome => {
+                // Any key dismisses welcome and starts reviewing
+                self.view = self.review_view();
+                self.user_config.welcome_shown = true;
+                Effect::MarkWelcomeShown
+            }
+            ViewState::Browsing => {
+                if let (Some(steps), Some(step)) = (self.recording.as_mut(), action.macro_step()) {
+                    steps.push(step);
+                }
+                self.update_browsing(action)
+            }
+        }
+    }
+
+    fn update_browsing(&mut self, action: KeyAction) -> Effect {
+        match action {
+            KeyAction::Quit => {
+                // Show summary before quitting if any decisions were made
+                let stats = self.state.engine.get_statistics();
+                if stats.kept > 0 || stats.trashed > 0 || stats.skipped > 0 {
+                    self.view = ViewState::Summary;
+                    Effect::None
+                } else {
+                    Effect::Exit
+                }
+            }
+            KeyAction::Keep => self.decide(Decision::Keep),
+            KeyAction::Skip => self.skip(),
+            KeyAction::MoveTo(slot) => match self.user_config.move_target(slot) {
+                Some(dir) => self.decide(Decision::Move(dir)),
+                None => {
+                    self.state
+                        .show_notice(format!("no move target {} in the config", slot));
+                    Effect::None
+                }
+            },
+            KeyAction::Trash => {
+                // Sidecars are offered along with an undecided file so they
+                // aren't left orphaned; that always needs an answer
+                let undecided = self
+                    .state
+                    .current_file_index()
+                    .is_some_and(|index| !self.state.is_decided(index));
+                self.sidecars = if undecided {
+                    self.state.sidecars_of_current()
+                } else {
+                    Vec::new()
+                };
+                // Skip confirmation if flag set or dry-run mode, unless the
+                // file's type is configured to always ask or the file looks sensitive
+                let always_ask = self.state.current_default_decision()
+                    == Some(DefaultDecision::AlwaysAsk)
+                    || self.state.current_sensitivity().is_some();
+                if self.sidecars.is_empty()
+                    && (self.skip_confirm || self.state.engine.is_dry_run())
+                    && !always_ask
+                {
+                    self.decide(Decision::Trash)
+                } else {
+                    self.view = ViewState::ConfirmTrash;
+                    Effect::None
+                }
+            }
+            KeyAction::BulkDecide(decision) => match self.state.decide_remaining(decision) {
+                Ok(count) if count > 0 => {
+                    self.view = self.next_view_after_decision();
+                    Effect::ResetPreview
+                }
+                _ => Effect::None,
+            },
+            KeyAction::SelectSimilar => match self.state.files_like_current() {
+                Some((pattern, matches)) if matches.len() > 1 => {
+                    self.selection = Some((pattern, matches));
+                    self.view = ViewState::SelectSimilar;
+                    Effect::None
+                }
+                Some((pattern, _)) => {
+                    self.state
+                        .show_notice(format!("no other undecided files like {}", pattern));
+                    Effect::None
+                }
+                None => {
+                    self.state
+                        .show_notice("nothing in this name to match on (no digits or copy suffix)");
+                    Effect::None
+                }
+            },
+            KeyAction::Search => {
+                self.search_input.clear();
+                self.view = ViewState::Search;
+                Effect::None
+            }
+            KeyAction::Filter => {
+                self.view = ViewState::Filter;
+                Effect::None
+            }
+            KeyAction::Unsure => {
+                if self.state.phase == ReviewPhase::Triage {
+                    self.state.toggle_unsure();
+                    self.state.advance_after_decision();
+                    self.view = self.next_view_after_decision();
+                }
+                Effect::None
+            }
+            KeyAction::Next => self.browse(AppState::next),
+            KeyAction::Previous => self.browse(AppState::previous),
+            KeyAction::Goto(position) => self.browse(|state| state.goto(position)),
+            KeyAction::SetSort(sort_by) => {
+                self.state.sort_queue(sort_by);
+                self.sort = (sort_by, false);
+                Effect::None
+            }
+            KeyAction::CycleSort => self.cycle_sort(),
+            KeyAction::Inspector => {
+                self.inspector_cursor = 0;
+                self.view = ViewState::Inspector;
+                Effect::None
+            }
+            KeyAction::ToggleAutoAdvance => {
+                self.state.toggle_auto_advance();
+                Effect::None
+            }
+            KeyAction::ToggleFocus => {
+                self.focus = !self.focus;
+                Effect::None
+            }
+            KeyAction::ToggleQueuePanel => {
+                self.queue_panel = !self.queue_panel;
+                Effect::None
+            }
+            KeyAction::PinPreview => {
+                if self.pinned.take().is_some() {
+                    self.state.show_notice("unpinned the preview");
+                } else if let Some(index) = self.state.current_file_index() {
+                    self.pinned = Some(index);
+                    self.state.show_notice(format!(
+                        "pinned {}: its preview stays on the left (P unpins)",
+                        self.state.files[index].name
+                    ));
+                }
+                Effect::None
+            }
+            KeyAction::RecordMacro => self.toggle_recording(),
+            KeyAction::PlayMacro => self.play_macro(),
+            KeyAction::SendToBack => {
+                self.state.send_to_back();
+                Effect::ResetPreview
+            }
+            KeyAction::PinToFront => {
+                self.state.pin_to_front();
+                Effect::ResetPreview
+            }
+            KeyAction::Undo => {
+                // Batches undo as a single step
+                match self.state.undo() {
+                    Ok(_) => Effect::ResetPreview,
+                    Err(_) => Effect::None,
+                }
+            }
+            KeyAction::Help => {
+                self.help = HelpState::new();
+                self.view = ViewState::Help;
+                Effect::None
+            }
+            KeyAction::Open => match self.state.current_file() {
+                Some(file) => Effect::Open(file.path.clone()),
+                None => Effect::None,
+            },
+            KeyAction::Suspend => Effect::Suspend,
+            KeyAction::Shell => Effect::Shell,
+            KeyAction::RevealSensitive => {
+                self.state.toggle_reveal();
+                Effect::None
+            }
+            KeyAction::Pause => self.pause(false),
+            KeyAction::Lock => self.pause(true),
+            KeyAction::ExportReport => Effect::ExportReport,
+            KeyAction::Overview => {
+                self.overview = Some(ScanOverview::new(&self.state.files, chrono::Utc::now()));
+                self.view = ViewState::Overview;
+                Effect::None
+            }
+            KeyAction::PreviewDetails => Effect::ShowPreviewFailure,
+            // Triage shows no previews, and other file types have nothing to zoom
+            KeyAction::Image(command) => {
+                let is_image = self
+                    .state
+                    .current_file()
+                    .is_some_and(|file| file.file_type == FileType::Image);
+                if is_image && self.state.phase != ReviewPhase::Triage {
+                    Effect::AdjustImage(command)
+                } else {
+                    Effect::None
+                }
+            }
+            // Triage shows no preview to scroll
+            KeyAction::ScrollPreview(_) if self.state.phase == ReviewPhase::Triage => Effect::None,
+            KeyAction::ScrollPreview(command) => Effect::ScrollPreview(command),
+            // Only meaningful in other views
+            KeyAction::ConfirmTrash
+            | KeyAction::TrashAlone
+            | KeyAction::CancelTrash
+            | KeyAction::Select
+            | KeyAction::Continue
+            | KeyAction::CopyPaths(_)
+            | KeyAction::SaveDiagnostics
+            | KeyAction::ResolveConflict(_)
+            | KeyAction::HelpInput(_)
+            | KeyAction::SearchInput(_)
+            | KeyAction::FilterInput(_)
+            | KeyAction::PauseInput(_)
+            | KeyAction::None => Effect::None,
+        }
+    }
+
+    /// Starts recording a macro, or stops and keeps what was recorded
+    fn toggle_recording(&mut self) -> Effect {
+        let Some(steps) = self.recording.take() else {
+            self.recording = Some(Vec::new());
+            self.state
+                .show_notice("recording macro: press M again to stop");
+            return Effect::None;
+        };
+        if steps.is_empty() {
+            self.state
+                .show_notice("nothing recorded; the macro is unchanged");
+            return Effect::None;
+        }
+        self.state
+            .show_notice(format!("recorded macro: {} (@ replays)", steps.join(", ")));
+        self.user_config.macro_steps = steps.clone();
+        Effect::SaveMacro(steps)
+    }
+
+    /// Runs the recorded macro's steps as if their keys were pressed, stopping early
+    /// if one leaves the browsing view (a trash confirmation or the summary)
+    fn play_macro(&mut self) -> Effect {
+        if self.recording.is_some() {
+            self.state
+                .show_notice("can't replay while recording; press M to stop");
+            return Effect::None;
+        }
+        let steps = &self.user_config.macro_steps;
+        if steps.is_empty() {
+            self.state
+                .show_notice("no macro recorded; press M to record one");
+            return Effect::None;
+        }
+        let actions: Option<Vec<KeyAction>> = steps
+            .iter()
+            .map(|step| KeyAction::from_macro_step(step))
+            .collect();
+        let Some(actions) = actions else {
+            let bad = steps
+                .iter()
+                .find(|step| KeyAction::from_macro_step(step).is_none())
+                .cloned()
+                .unwrap_or_default();
+            self.state
+                .show_notice(format!("unknown macro step '{}' in the config", bad));
+            return Effect::None;
+        };
+
+        let total = actions.len();
+        let mut effect = Effect::None;
+        self.replaying = true;
+        for (done, action) in actions.into_iter().enumerate() {
+            if self.view != ViewState::Browsing {
+                self.state
+                    .show_notice(format!("macro stopped after {} of {} steps", done, total));
+                break;
+            }
+            if let Effect::ResetPreview = self.update_browsing(action) {
+                effect = Effect::ResetPreview;
+            }
+        }
+        self.replaying = false;
+        effect
+    }
+
+    fn update_setup(&mut self, action: KeyAction) -> Effect {
+        let Some(ref mut wizard) = self.setup else {
+            self.view = ViewState::Welcome;
+            return Effect::None;
+        };
+        match action {
+            KeyAction::Next => wizard.select_next(),
+            KeyAction::Previous => wizard.select_previous(),
+            // Esc/q accepts the defaults for the remaining questions
+            KeyAction::Quit => wizard.skip(),
+            KeyAction::Select => wizard.confirm(),
+            _ => {}
+        }
+        if !wizard.is_finished() {
+            return Effect::None;
+        }
+
+        // Save only the answers; environment overrides stay out of the file
+        let mut stored = UserConfig::default();
+        wizard.apply(&mut stored);
+        let mut answers = UserConfig::default();
+        wizard.apply(&mut answers);
+        self.apply_config(answers);
+        self.view = ViewState::Welcome;
+        Effect::SaveConfig(Box::new(stored))
+    }
+
+    /// Copies the original paths of the files decided `decision`, one per line
+    fn copy_paths(&mut self, decision: &Decision) -> Effect {
+        let paths: Vec<String> = self
+            .state
+            .decided_paths(decision)
+            .iter()
+            .map(|path| path.to_string_lossy().into_owned())
+            .collect();
+        let label = match decision {
+            Decision::Keep => "kept",
+            Decision::Trash => "trashed",
+            Decision::Move(_) => "moved",
+            Decision::Skip => "skipped",
+        };
+        if paths.is_empty() {
+            self.state
+                .show_notice(format!("no {} files to copy", label));
+            return Effect::None;
+        }
+        self.state.show_notice(format!(
+            "copied {} {} paths to the clipboard",
+            paths.len(),
+            label
+        ));
+        Effect::CopyToClipboard(paths.join("\n"))
+    }
+
+    /// The view the review starts in: the easy wins screen while it has
+    /// suggestions, then the duplicate groups
+    pub fn review_view(&self) -> ViewState {
+        if !self.easy_wins.is_empty() {
+            ViewState::EasyWins
+        } else if !self.duplicates.is_empty() {
+            ViewState::Duplicates
+        } else {
+            ViewState::Browsing
+        }
+    }
+
+    /// Drops decided files from the duplicate groups, and groups with fewer than
+    /// two files left
+    pub fn prune_duplicates(&mut self) {
+        let state = &self.state;
+        for group in &mut self.duplicates {
+            group.files.retain(|&i| !state.is_decided(i));
+        }
+        self.duplicates.retain(|group| group.files.len() > 1);
+        self.duplicates_cursor = 0;
+    }
+
+    /// Enter keeps the highlighted file of the current group and trashes the rest
+    /// as one undoable batch; → leaves the group for the review, q/Esc skips them all
+    fn update_duplicates(&mut self, action: KeyAction) -> Effect {
+        let Some(group) = self.duplicates.first() else {
+            self.view = ViewState::Browsing;
+            return Effect::None;
+        };
+        match action {
+            KeyAction::Next => {
+                self.duplicates_cursor = (self.duplicates_cursor + 1).min(group.files.len() - 1);
+                return Effect::None;
+            }
+            KeyAction::Previous => {
+                self.duplicates_cursor = self.duplicates_cursor.saturating_sub(1);
+                return Effect::None;
+            }
+            KeyAction::Keep => {
+                self.duplicates.remove(0);
+            }
+            KeyAction::Quit => self.duplicates.clear(),
+            KeyAction::Select => {
+                let group = self.duplicates.remove(0);
+                let keep = group.files[self.duplicates_cursor.min(group.files.len() - 1)];
+                let reason = format!("same contents as {}", self.state.files[keep].name);
+                let freed = reclaimable_bytes(
+                    group
+                        .files
+                        .iter()
+                        .filter(|&&i| i != keep)
+                        .map(|&i| &self.state.files[i]),
+                );
+                match self.state.keep_one_of(keep, &group.files) {
+                    Ok(count) => {
+                        for &i in group.files.iter().filter(|&&i| i != keep) {
+                            if self.state.decision_of(i) == Some(&Decision::Trash) {
+                                self.state.reasons.insert(i, reason.clone());
+                            }
+                        }
+                        self.state.show_notice(format!(
+                            "kept {}, trashed {} duplicates ({})",
+                            self.state.files[keep].name,
+                            count,
+                            format_file_size(freed)
+                        ));
+                    }
+                    Err(e) => self.state.show_notice(format!("nothing trashed: {}", e)),
+                }
+            }
+            _ => return Effect::None,
+        }
+        self.prune_duplicates();
+        if self.duplicates.is_empty() {
+            // Only what's left to review comes up
+            self.state.send_decided_to_back();
+            self.view = ViewState::Browsing;
+            return Effect::ResetPreview;
+        }
+        Effect::None
+    }
+
+    /// ← marks the highlighted group for trash and → leaves it for the review;
+    /// Enter trashes the marked groups as one undoable batch, q/Esc skips them all
+    fn update_easy_wins(&mut self, action: KeyAction) -> Effect {
+        match action {
+            KeyAction::Next => {
+                self.easy_wins_cursor = (self.easy_wins_cursor + 1).min(self.easy_wins.len() - 1);
+            }
+            KeyAction::Previous => self.easy_wins_cursor = self.easy_wins_cursor.saturating_sub(1),
+            KeyAction::Trash | KeyAction::Keep => {
+                if let Some(win) = self.easy_wins.get_mut(self.easy_wins_cursor) {
+                    win.accepted = action == KeyAction::Trash;
+                }
+            }
+            KeyAction::Quit => {
+                self.easy_wins.clear();
+                self.prune_duplicates();
+                self.view = self.review_view();
+            }
+            KeyAction::Select => {
+                let wins = std::mem::take(&mut self.easy_wins);
+                let files: Vec<usize> = wins
+                    .iter()
+                    .filter(|win| win.accepted)
+                    .flat_map(|win| win.files.iter().copied())
+                    .collect();
+                let now = chrono::Utc::now();
+                for win in wins.iter().filter(|win| win.accepted) {
+                    for &i in &win.files {
+                        let reason =
+                            win.kind
+                                .explain(&self.state.files[i], self.state.usage.get(&i), now);
+                        self.state.reasons.insert(i, reason);
+                    }
+                }
+                let decided = self.state.decide_files(&files, Decision::Trash);
+                self.prune_duplicates();
+                self.view = self.review_view();
+                match decided {
+                    Ok(0) => {}
+                    Ok(count) => {
+                        // `always-ask` types and sensitive files aren't trashed in bulk
+                        let bytes: u64 = files
+                            .iter()
+                            .filter(|&&i| self.state.is_decided(i))
+                            .map(|&i| self.state.files[i].size)
+                            .sum();
+                        // Only what's left to review comes up
+                        self.state.send_decided_to_back();
+                        self.state.show_notice(format!(
+                            "trashed {} easy wins ({})",
+                            count,
+                            format_file_size(bytes)
+                        ));
+                        return Effect::ResetPreview;
+                    }
+                    Err(e) => self.state.show_notice(format!("nothing trashed: {}", e)),
+                }
+            }
+            _ => {}
+        }
+        Effect::None
+    }
+
+    /// Keep, trash or move every selected file as one undoable batch; q/Esc backs out
+    fn update_selection(&mut self, action: KeyAction) -> Effect {
+        let decision = match action {
+            KeyAction::Keep => Decision::Keep,
+            KeyAction::Trash => Decision::Trash,
+            KeyAction::MoveTo(slot) => match self.user_config.move_target(slot) {
+                Some(dir) => Decision::Move(dir),
+                None => {
+                    self.state
+                        .show_notice(format!("no move target {} in the config", slot));
+                    return Effect::None;
+                }
+            },
+            KeyAction::Quit => {
+                self.selection = None;
+                self.view = ViewState::Browsing;
+                return Effect::None;
+            }
+            _ => return Effect::None,
+        };
+        self.view = ViewState::Browsing;
+        let Some((pattern, matches)) = self.selection.take() else {
+            return Effect::None;
+        };
+        match self.state.decide_files(&matches, decision) {
+            Ok(0) => Effect::None,
+            Ok(count) => {
+                self.state
+                    .show_notice(format!("decided {} files like {}", count, pattern));
+                self.state.advance_after_decision();
+                self.view = self.next_view_after_decision();
+                Effect::ResetPreview
+            }
+            Err(e) => {
+                self.state.show_notice(format!("nothing decided: {}", e));
+                Effect::None
+            }
+        }
+    }
+
+    /// Edits the search prompt; Enter searches (or shows every file again when
+    /// empty), Esc backs out and stops a running search
+    fn update_search(&mut self, action: KeyAction) -> Effect {
+        let KeyAction::SearchInput(key) = action else {
+            return Effect::None;
+        };
+        if key.code == KeyCode::Esc {
+            self.search = None;
+            self.view = ViewState::Browsing;
+            return Effect::None;
+        }
+        if self.search.is_some() {
+            return Effect::None;
+        }
+        match key.code {
+            KeyCode::Enter if self.search_input.is_empty() => {
+                self.view = ViewState::Browsing;
+                if self.state.clear_narrowing() {
+                    self.state.show_notice("showing every file again");
+                    return Effect::ResetPreview;
+                }
+            }
+            KeyCode::Enter => {
+                // Only text is worth grepping; PDFs and binaries would just match noise
+                let files = &self.state.files;
+                let candidates = self
+                    .state
+                    .full_queue()
+                    .iter()
+                    .filter(|&&i| files[i].file_type == FileType::Text)
+                    .map(|&i| (i, files[i].path.clone()))
+                    .collect();
+                self.search = Some(ContentSearch::start(&self.search_input, candidates));
+            }
+            KeyCode::Backspace => {
+                self.search_input.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.search_input.push(c);
+            }
+            _ => {}
+        }
+        Effect::None
+    }
+
+    /// Collects results from a running content search and narrows the queue once it
+    /// finishes; true when the queue changed
+    pub fn poll_search(&mut self) -> bool {
+        let Some(ref mut search) = self.search else {
+            return false;
+        };
+        if !search.poll() {
+            return false;
+        }
+        let search = self.search.take().expect("checked above");
+        self.view = ViewState::Browsing;
+        if search.matches().is_empty() {
+            self.state
+                .show_notice(format!("no text files mention '{}'", search.query()));
+            return false;
+        }
+        // The search replaces whatever the filter bar had narrowed to
+        self.filter_input.clear();
+        self.filter_error = None;
+        let matches: HashSet<usize> = search.matches().iter().copied().collect();
+        let count = self
+            .state
+            .narrow_queue(format!("search: '{}'", search.query()), &matches);
+        self.state.show_notice(format!(
+            "{} files mention '{}' (/ then Enter shows all)",
+            count,
+            search.query()
+        ));
+        true
+    }
+
+    /// Edits the filter bar, narrowing the queue after every key. Enter goes back
+    /// to browsing with the filter still applied; Esc clears it and shows every file
+    fn update_filter(&mut self, action: KeyAction) -> Effect {
+        let KeyAction::FilterInput(key) = action else {
+            return Effect::None;
+        };
+        match key.code {
+            KeyCode::Enter => {
+                self.view = ViewState::Browsing;
+                return Effect::None;
+            }
+            KeyCode::Esc => {
+                self.view = ViewState::Browsing;
+                self.filter_input.clear();
+                self.filter_error = None;
+                self.preset = None;
+                if self.state.clear_narrowing() {
+                    return Effect::ResetPreview;
+                }
+                return Effect::None;
+            }
+            KeyCode::Tab => return self.next_preset(),
+            KeyCode::Backspace => {
+                self.filter_input.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.filter_input.push(c);
+            }
+            _ => return Effect::None,
+        }
+
+        self.preset = None;
+        let before = self.state.current_file_index();
+        match self.state.filter_queue(&self.filter_input) {
+            Ok(_) => self.filter_error = None,
+            Err(e) => self.filter_error = Some(e.to_string()),
+        }
+        if self.state.current_file_index() != before {
+            Effect::ResetPreview
+        } else {
+            Effect::None
+        }
+    }
+
+    /// Applies a saved preset from the config: its sort order, then its filter in
+    /// the filter bar; returns how many files it shows
+    pub fn apply_preset(&mut self, name: &str) -> crate::error::Result<usize> {
+        let preset = self.user_config.preset(name)?.clone();
+        self.state.clear_narrowing();
+        if let Some(sort_by) = preset.sort {
+            self.state.sort_queue(sort_by);
+            self.sort = (sort_by, false);
+        }
+        let count = self.state.filter_queue(&preset.filter)?;
+        self.filter_input = preset.filter;
+        self.filter_error = None;
+        self.preset = Some(name.to_string());
+        Ok(count)
+    }
+
+    /// Tab in the filter bar: applies the preset after the last one, alphabetically
+    fn next_preset(&mut self) -> Effect {
+        let names = self.user_config.preset_names();
+        let Some(&first) = names.first() else {
+            self.state.show_notice("no presets in the config");
+            return Effect::None;
+        };
+        let name = self
+            .preset
+            .as_deref()
+            .and_then(|current| names.iter().find(|&&name| name > current))
+            .copied()
+            .unwrap_or(first)
+            .to_string();
+        match self.apply_preset(&name) {
+            Ok(count) => self
+                .state
+                .show_notice(format!("preset '{}': {} files", name, count)),
+            Err(e) => {
+                // Skipped next time round, like one that applied
+                self.filter_error = Some(e.to_string());
+                self.preset = Some(name);
+            }
+        }
+        Effect::ResetPreview
+    }
+
+    /// Whether the filter bar is on screen: while editing it, and while browsing a
+    /// queue it narrowed
+    pub fn filter_active(&self) -> bool {
+        match self.view {
+            ViewState::Filter => true,
+            ViewState::Browsing => {
+                !self.filter_input.trim().is_empty() && self.state.narrowing.is_some()
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether a content search is still running
+    pub fn is_searching(&self) -> bool {
+        self.search.is_some()
+    }
+
+    /// Starts measuring the session's directory items in the background, in
+    /// queue order so the first ones reviewed are measured first
+    pub fn start_sizing(&mut self) {
+        let dirs: Vec<(usize, PathBuf)> = self
+            .state
+            .queue
+            .iter()
+            .filter(|&&index| self.state.files[index].file_type == FileType::Directory)
+            .map(|&index| (index, self.state.files[index].path.clone()))
+            .collect();
+        if dirs.is_empty() {
+            return;
+        }
+        self.state.update_directory_sizes(
+            dirs.iter()
+                .map(|(index, _)| (*index, DirectoryStats::default(), false)),
+        );
+        self.sizer = Some(DirSizer::start(dirs));
+    }
+
+    /// Collects measurements from the background sizer, dropping it once every
+    /// directory is measured
+    pub fn poll_sizes(&mut self) {
+        let Some(ref mut sizer) = self.sizer else {
+            return;
+        };
+        let updates = sizer.poll();
+        let finished = sizer.is_finished();
+        self.state.update_directory_sizes(updates);
+        if finished {
+            self.sizer = None;
+        }
+    }
+
+    pub fn is_sizing(&self) -> bool {
+        self.sizer.is_some()
+    }
+
+    /// Files on the easy wins screen whose last use is worth knowing: every old
+    /// installer, since one still in use drops out, and the ones shown of the
+    /// group under the cursor
+    pub fn easy_wins_usage_wanted(&self) -> Vec<usize> {
+        if self.view != ViewState::EasyWins {
+            return Vec::new();
+        }
+        let installers = self
+            .easy_wins
+            .iter()
+            .filter(|win| win.kind == EasyWinKind::OldInstaller)
+            .flat_map(|win| win.files.iter().copied());
+        let shown = self
+            .easy_wins
+            .get(self.easy_wins_cursor)
+            .into_iter()
+            .flat_map(|win| win.files.iter().take(SELECTION_SAMPLE).copied());
+        installers.chain(shown).collect()
+    }
+
+    /// Drops the suggestions last-used info has since shown to be in use, and the
+    /// groups that leaves empty
+    pub fn prune_easy_wins_by_usage(&mut self) {
+        let now = chrono::Utc::now();
+        let usage = &self.state.usage;
+        for win in &mut self.easy_wins {
+            let kind = win.kind;
+            win.files.retain(|i| kind.still_applies(usage.get(i), now));
+        }
+        self.easy_wins.retain(|win| !win.files.is_empty());
+        self.easy_wins_cursor = self
+            .easy_wins_cursor
+            .min(self.easy_wins.len().saturating_sub(1));
+        if self.view == ViewState::EasyWins && self.easy_wins.is_empty() {
+            self.view = self.review_view();
+        }
+    }
+
+    /// Takes the copy finder's results once it's done. While easy wins are
+    /// pending the copies join them, opening the easy wins screen from browsing;
+    /// under any other screen the results wait.
+    pub fn poll_copies(&mut self) {
+        if !matches!(self.view, ViewState::Browsing | ViewState::EasyWins) {
+            return;
+        }
+        let Some(copies) = self.copy_finder.as_ref().and_then(CopyFinder::poll) else {
+            self.note_background_work();
+            return;
+        };
+        self.copy_finder = None;
+        self.note_background_work();
+        self.state.copies = copies;
+        if !std::mem::take(&mut self.easy_wins_pending) {
+            return;
+        }
+        let found = find_easy_wins(&self.state.files, &self.state.copies, chrono::Utc::now())
+            .into_iter()
+            .find(|win| win.kind == EasyWinKind::Duplicate);
+        let Some(mut win) = found else {
+            return;
+        };
+        // A copy already suggested under another kind stays there
+        let suggested: HashSet<usize> = self
+            .easy_wins
+            .iter()
+            .flat_map(|win| win.files.iter().copied())
+            .collect();
+        win.files
+            .retain(|i| !self.state.is_decided(*i) && !suggested.contains(i));
+        if win.files.is_empty() {
+            return;
+        }
+        // Kinds stay most certain first: copies go before old installers
+        let position = self
+            .easy_wins
+            .iter()
+            .position(|win| win.kind == EasyWinKind::OldInstaller)
+            .unwrap_or(self.easy_wins.len());
+        if self.view == ViewState::EasyWins && position <= self.easy_wins_cursor {
+            self.easy_wins_cursor += 1;
+        }
+        self.easy_wins.insert(position, win);
+        if self.view == ViewState::Browsing {
+            self.easy_wins_cursor = 0;
+            self.view = ViewState::EasyWins;
+        }
+    }
+
+    /// Takes the duplicate groups once they're all found and, from browsing,
+    /// opens the duplicates screen on the ones still undecided; under any other
+    /// screen the groups wait
+    pub fn poll_duplicates(&mut self) {
+        if self.view != ViewState::Browsing {
+            return;
+        }
+        let Some(groups) = self
+            .duplicate_finder
+            .as_ref()
+            .and_then(DuplicateFinder::poll)
+        else {
+            self.note_background_work();
+            return;
+        };
+        self.duplicate_finder = None;
+        self.note_background_work();
+        self.duplicates = groups;
+        self.prune_duplicates();
+        if self.duplicates.is_empty() {
+            self.state.show_notice("no duplicates found");
+        } else {
+            self.view = ViewState::Duplicates;
+        }
+    }
+
+    /// Whether a startup worker is still going through the files
+    pub fn is_finding(&self) -> bool {
+        self.copy_finder.is_some() || self.duplicate_finder.is_some()
+    }
+
+    /// Puts what the startup workers are doing in the header
+    fn note_background_work(&mut self) {
+        let copies = self.copy_finder.as_ref().map(|finder| {
+            let (done, total) = finder.progress();
+            format!("comparing copies {}/{}", done, total)
+        });
+        let duplicates = self.duplicate_finder.as_ref().map(|finder| {
+            let (done, total) = finder.progress();
+            format!("looking for duplicates {}/{}", done, total)
+        });
+        let work: Vec<String> = copies.into_iter().chain(duplicates).collect();
+        self.state.background_work = (!work.is_empty()).then(|| work.join(" · "));
+    }
+
+    /// Collects the backup manifest once the writer has saved it; true while the
+    /// apply still has to wait for it
+    pub fn poll_manifest(&mut self) -> bool {
+        let Some(ref writer) = self.manifest_writer else {
+            return false;
+        };
+        match writer.poll() {
+            Some(saved) => {
+                self.manifest = Some(saved);
+                self.manifest_writer = None;
+                false
+            }
+            None => true,
+        }
+    }
+
+    /// Records a decision on the current file and moves on
+    fn decide(&mut self, decision: Decision) -> Effect {
+        if let Some(current) = self.state.current_file_index() {
+            match self.state.decision_of(current) {
+                // A second press with auto-advance off changes nothing
+                Some(earlier) if *earlier == decision => return Effect::None,
+                Some(_) => return self.redecide(decision),
+                None => {}
+            }
+        }
+        let result = self.state.record_decision(decision.clone());
+        // Moves can fail for reasons worth showing (name taken, target not writable)
+        if let Decision::Move(dir) = decision {
+            match result {
+                Ok(()) => self
+                    .state
+                    .show_notice(format!("moved to {}", dir.display())),
+                Err(ref e) if self.open_move_conflict(&dir, e) => {}
+                Err(ref e) => self.state.show_notice(format!("can't move: {}", e)),
+            }
+        }
+        if result.is_err() {
+            return Effect::None;
+        }
+        self.after_decision()
+    }
+
+    /// Moves on from a newly recorded decision
+    fn after_decision(&mut self) -> Effect {
+        self.state.advance_after_decision();
+        self.view = self.next_view_after_decision();
+        self.note_decision_at(Instant::now());
+        Effect::ResetPreview
+    }
+
+    /// Extends or starts the burst of quick decisions with the one just recorded
+    /// at `now`; at `RAPID_COUNT` the burst becomes one undo group and the
+    /// session asks whether it was meant. A macro's decisions end any burst
+    /// instead of counting toward one.
+    fn note_decision_at(&mut self, now: Instant) {
+        if self.replaying {
+            self.rapid = None;
+            return;
+        }
+        let len = self.state.engine.decisions.len();
+        let mut burst = match self.rapid.take() {
+            Some(burst) if burst.start < len && now.duration_since(burst.last) <= RAPID_GAP => {
+                RapidBurst { last: now, ..burst }
+            }
+            _ => RapidBurst {
+                start: len.saturating_sub(1),
+                last: now,
+                after: None,
+                confirmed: false,
+            },
+        };
+        if !burst.confirmed && len - burst.start >= RAPID_COUNT {
+            self.state.group_recent(len - burst.start);
+            burst.after = Some(std::mem::replace(&mut self.view, ViewState::RapidDecisions));
+        }
+        self.rapid = Some(burst);
+    }
+
+    /// Decisions in the current burst
+    pub fn rapid_count(&self) -> usize {
+        self.rapid.as_ref().map_or(0, |burst| {
+            self.state
+                .engine
+                .decisions
+                .len()
+                .saturating_sub(burst.start)
+        })
+    }
+
+    /// Yes undoes the whole burst and goes back to its first file; no carries on.
+    /// Other keys are ignored, so a key that's still stuck does nothing.
+    fn update_rapid(&mut self, action: KeyAction) -> Effect {
+        match action {
+            KeyAction::ConfirmTrash => {
+                let undone = self.state.undo().unwrap_or_default();
+                self.rapid = None;
+                self.view = ViewState::Browsing;
+                if let Some(position) = undone
+                    .last()
+                    .and_then(|(index, _)| self.state.queue.iter().position(|i| i == index))
+                {
+                    self.state.goto(position);
+                }
+                self.state
+                    .show_notice(format!("undid {} rapid decisions", undone.len()));
+                Effect::ResetPreview
+            }
+            KeyAction::CancelTrash => {
+                let after = self.rapid.as_mut().and_then(|burst| {
+                    burst.confirmed = true;
+                    burst.after.take()
+                });
+                self.view = after.unwrap_or(ViewState::Browsing);
+                Effect::None
+            }
+            _ => Effect::None,
+        }
+    }
+
+    /// Skips the current file for the end-of-session pass. In that pass a file
+    /// that's still skipped is just passed over, and the last one ends the pass.
+    fn skip(&mut self) -> Effect {
+        let current = self.state.current_file_index();
+        if current.is_some_and(|index| self.state.decision_of(index) == Some(&Decision::Skip)) {
+            if self.state.current_index + 1 >= self.state.queue.len() {
+                self.view = ViewState::Summary;
+                return Effect::None;
+            }
+            self.state.next();
+            return Effect::ResetPreview;
+        }
+        self.decide(Decision::Skip)
+    }
+
+    /// Trashes the current file and its sidecars as one undoable batch
+    fn trash_with_sidecars(&mut self, sidecars: &[usize]) -> Effect {
+        let Some(current) = self.state.current_file_index() else {
+            return Effect::None;
+        };
+        let entries: Vec<(usize, Decision)> = std::iter::once(current)
+            .chain(sidecars.iter().copied())
+            .map(|index| (index, Decision::Trash))
+            .collect();
+        if let Err(e) = self.state.record_batch(&entries) {
+            self.state.show_notice(format!("nothing trashed: {}", e));
+            return Effect::None;
+        }
+        self.state.show_notice(format!(
+            "trashed {} with {} sidecar{}",
+            self.state.files[current].name,
+            sidecars.len(),
+            if sidecars.len() == 1 { "" } else { "s" }
+        ));
+        self.state.advance_after_decision();
+        self.view = self.next_view_after_decision();
+        Effect::ResetPreview
+    }
+
+    /// Changes the current file's earlier decision and shows what that does to the
+    /// projected freed space; the header gauges read the same totals
+    fn redecide(&mut self, decision: Decision) -> Effect {
+        match self.state.change_decision(decision.clone()) {
+            Ok((old, delta)) => {
+                let change = match delta {
+                    0 => "freed space unchanged".to_string(),
+                    d if d > 0 => format!("+{} freed", format_file_size(d.unsigned_abs())),
+                    d => format!("−{} freed", format_file_size(d.unsigned_abs())),
+                };
+                self.state.show_notice(format!(
+                    "{} → {}: {} (now {})",
+                    decision_label(&old),
+                    decision_label(&decision),
+                    change,
+                    format_file_size(self.state.freed_bytes())
+                ));
+                self.state.advance_after_decision();
+                self.view = self.next_view_after_decision();
+                Effect::ResetPreview
+            }
+            Err(e) => {
+                match decision {
+                    Decision::Move(ref dir) if self.open_move_conflict(dir, &e) => {}
+                    _ => self
+                        .state
+                        .show_notice(format!("can't change decision: {}", e)),
+                }
+                Effect::None
+            }
+        }
+    }
+
+    /// Asks what to do when a move into `dir` failed because the name is taken;
+    /// false for any other error
+    fn open_move_conflict(&mut self, dir: &std::path::Path, error: &io::Error) -> bool {
+        let Some(index) = self.state.current_file_index() else {
+            return false;
+        };
+        if error.kind() != io::ErrorKind::AlreadyExists {
+            return false;
+        }
+        self.move_conflict = Some(MoveConflict {
+            index,
+            dir: dir.to_path_buf(),
+            compared: None,
+        });
+        self.view = ViewState::MoveConflict;
+        true
+    }
+
+    /// Opens the details of the current file's failed preview, or says there are
+    /// none when the preview didn't fail
+    pub fn show_preview_failure(&mut self, failure: Option<PreviewFailure>) {
+        match failure {
+            Some(failure) => {
+                self.preview_failure = Some(failure);
+                self.view = ViewState::PreviewError;
+            }
+            None => self
+                .state
+                .show_notice("this preview didn't fail; no details to show"),
+        }
+    }
+
+    /// Blanks the screen; `lock` asks for a session passphrase first if there isn't one
+    fn pause(&mut self, lock: bool) -> Effect {
+        self.pause = Some(PauseScreen {
+            setting: lock && self.passphrase.is_none(),
+            ..PauseScreen::default()
+        });
+        self.view = ViewState::Paused;
+        Effect::None
+    }
+
+    /// Any key resumes, unless a passphrase is set: then it has to be typed and
+    /// confirmed with Enter (Esc clears what's typed). While choosing a passphrase,
+    /// Enter sets it (an empty one just pauses) and Esc resumes.
+    fn update_paused(&mut self, action: KeyAction) -> Effect {
+        let (KeyAction::PauseInput(key), Some(pause)) = (action, self.pause.as_mut()) else {
+            return Effect::None;
+        };
+        if !pause.setting && self.passphrase.is_none() {
+            self.pause = None;
+            self.view = ViewState::Browsing;
+            return Effect::None;
+        }
+        match key.code {
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                pause.input.push(c);
+                pause.wrong = false;
+            }
+            KeyCode::Backspace => {
+                pause.input.pop();
+            }
+            KeyCode::Esc if pause.setting => {
+                self.pause = None;
+                self.view = ViewState::Browsing;
+            }
+            KeyCode::Esc => pause.input.clear(),
+            KeyCode::Enter if pause.setting => {
+                let input = std::mem::take(&mut pause.input);
+                pause.setting = false;
+                self.passphrase = (!input.is_empty()).then_some(input);
+            }
+            KeyCode::Enter => {
+                if self.passphrase.as_deref() == Some(pause.input.as_str()) {
+                    self.pause = None;
+                    self.view = ViewState::Browsing;
+                } else {
+                    pause.input.clear();
+                    pause.wrong = true;
+                }
+            }
+            _ => {}
+        }
+        Effect::None
+    }
+
+    /// r moves under a free name, o trashes the file in the way first, c compares
+    /// the two, s / Esc leaves the file as it was
+    fn update_move_conflict(&mut self, action: KeyAction) -> Effect {
+        let KeyAction::ResolveConflict(choice) = action else {
+            return Effect::None;
+        };
+        let Some(mut conflict) = self.move_conflict.take() else {
+            self.view = ViewState::Browsing;
+            return Effect::None;
+        };
+        let engine = &mut self.state.engine;
+        match choice {
+            ConflictChoice::Compare => {
+                let existing = engine.move_destination(conflict.index, &conflict.dir);
+                let ours = &self.state.files[conflict.index].path;
+                match FileEntry::from_path(&existing) {
+                    Ok(entry) => {
+                        let identical = entry.size == self.state.files[conflict.index].size
+                            && crate::domain::copies::same_contents(ours, &existing)
+                                .unwrap_or(false);
+                        conflict.compared = Some((entry, identical));
+                    }
+                    Err(e) => self.state.show_notice(format!("can't compare: {}", e)),
+                }
+                self.move_conflict = Some(conflict);
+                return Effect::None;
+            }
+            ConflictChoice::Skip => {
+                self.view = ViewState::Browsing;
+                self.state.show_notice("move skipped");
+                return Effect::None;
+            }
+            ConflictChoice::Rename => {
+                let name = engine.free_move_name(conflict.index, &conflict.dir);
+                engine.rename_on_move(conflict.index, name);
+            }
+            ConflictChoice::Overwrite => {
+                self.view = ViewState::Browsing;
+                let dir = conflict.dir.display().to_string();
+                if let Err(e) = self.state.move_replacing(conflict.index, conflict.dir) {
+                    self.state
+                        .show_notice(format!("can't replace the existing file: {}", e));
+                    return Effect::None;
+                }
+                self.state
+                    .show_notice(format!("moved to {}, trashing the file it replaced", dir));
+                return self.after_decision();
+            }
+        }
+        self.view = ViewState::Browsing;
+        self.decide(Decision::Move(conflict.dir))
+    }
+
+    /// Moves the cursor, marking the file left behind as seen if configured
+    fn browse(&mut self, step: impl FnOnce(&mut AppState)) -> Effect {
+        if self.user_config.mark_seen_on_browse {
+            self.state.mark_seen();
+        }
+        step(&mut self.state);
+        Effect::ResetPreview
+    }
+
+    /// Lists the files still to be trashed so any can be kept before applying;
+    /// with none, goes straight on
+    fn offer_review(&mut self) -> Effect {
+        self.review = self.state.staged_trash();
+        if self.review.is_empty() {
+            return self.offer_renames();
+        }
+        self.review_cursor = 0;
+        self.view = ViewState::Review;
+        Effect::None
+    }
+
+    /// → keeps the highlighted file and ← trashes it again; Enter applies the
+    /// session, q/Esc goes back to the summary
+    fn update_review(&mut self, action: KeyAction) -> Effect {
+        match action {
+            KeyAction::Next => {
+                self.review_cursor = (self.review_cursor + 1).min(self.review.len() - 1);
+            }
+            KeyAction::Previous => self.review_cursor = self.review_cursor.saturating_sub(1),
+            KeyAction::Trash | KeyAction::Keep => {
+                let decision = if action == KeyAction::Trash {
+                    Decision::Trash
+                } else {
+                    Decision::Keep
+                };
+                if let Some(&index) = self.review.get(self.review_cursor) {
+                    if let Err(e) = self.state.change_decision_of(index, decision) {
+                        self.state
+                            .show_notice(format!("can't change decision: {}", e));
+                    }
+                }
+            }
+            KeyAction::Quit => {
+                self.review.clear();
+                self.view = ViewState::Summary;
+            }
+            KeyAction::Select => {
+                self.review.clear();
+                return self.offer_renames();
+            }
+            _ => {}
+        }
+        Effect::None
+    }
+
+    /// Sorts the files left by the next of `SORT_ORDERS`
+    fn cycle_sort(&mut self) -> Effect {
+        let next = SORT_ORDERS
+            .iter()
+            .position(|&(sort_by, reverse, _)| (sort_by, reverse) == self.sort)
+            .map_or(0, |at| (at + 1) % SORT_ORDERS.len());
+        let (sort_by, reverse, label) = SORT_ORDERS[next];
+        self.state.sort_undecided(sort_by, reverse);
+        self.sort = (sort_by, reverse);
+        self.state.show_notice(format!("Files left: {}", label));
+        Effect::ResetPreview
+    }
+
+    /// The filter inspector's rows: the sort, then everything that keeps files
+    /// out of the queue or shapes the suggestions, with how many files each hides
+    pub fn inspector_rows(&self) -> Vec<InspectorRow> {
+        let sort = SORT_ORDERS
+            .iter()
+            .find(|&&(sort_by, reverse, _)| (sort_by, reverse) == self.sort)
+            .map_or_else(
+                || format!("{:?}", self.sort.0).to_lowercase(),
+                |&(_, _, label)| label.to_string(),
+            );
+        let mut rows = vec![InspectorRow {
+            label: "sort".to_string(),
+            detail: sort,
+            action: Some(InspectorAction::CycleSort),
+        }];
+        let options = &self.left_out.options;
+        for (filter, count) in self.left_out.counts() {
+            rows.push(InspectorRow {
+                label: scan_filter_label(options, filter),
+                detail: match count {
+                    1 => "leaves out 1 file".to_string(),
+                    count => format!("leaves out {} files", count),
+                },
+                action: Some(InspectorAction::TurnOff(filter)),
+            });
+        }
+        for &filter in &self.left_out.off {
+            rows.push(InspectorRow {
+                label: scan_filter_label(options, filter),
+                detail: "off".to_string(),
+                action: None,
+            });
+        }
+        if !options.include_dirs && !options.recursive {
+            rows.push(InspectorRow {
+                label: "subdirectories".to_string(),
+                detail: "not listed; restart with --include-dirs or --recursive".to_string(),
+                action: None,
+            });
+        }
+        if let Some(ref narrowing) = self.state.narrowing {
+            rows.push(InspectorRow {
+                label: narrowing.label.clone(),
+                detail: match self.state.narrowed_out() {
+                    1 => "hides 1 file".to_string(),
+                    hidden => format!("hides {} files", hidden),
+                },
+                action: Some(InspectorAction::ClearNarrowing),
+            });
+        }
+        match self.state.phase {
+            ReviewPhase::Triage => rows.push(InspectorRow {
+                label: "triage pass".to_string(),
+                detail: "names only; the unsure files come back with previews".to_string(),
+                action: None,
+            }),
+            ReviewPhase::Skipped => rows.push(InspectorRow {
+                label: "skipped pass".to_string(),
+                detail: "only the files skipped earlier".to_string(),
+                action: None,
+            }),
+            ReviewPhase::Detail => {}
+        }
+        let mut rules: Vec<_> = self.state.default_decisions.iter().collect();
+        rules.sort_by_key(|(file_type, _)| format!("{:?}", file_type));
+        for (file_type, decision) in rules {
+            let suggestion = match decision {
+                DefaultDecision::SuggestKeep => "suggest keep",
+                DefaultDecision::SuggestTrash => "suggest trash",
+                DefaultDecision::AlwaysAsk => "always ask before trashing",
+            };
+            rows.push(InspectorRow {
+                label: format!("default for {:?}", file_type).to_lowercase(),
+                detail: suggestion.to_string(),
+                action: Some(InspectorAction::DropRule(file_type.clone())),
+            });
+        }
+        rows
+    }
+
+    /// ↑↓ choose a row and Enter turns it off (or, for the sort, steps it); any
+    /// other key goes back to reviewing
+    fn update_inspector(&mut self, action: KeyAction) -> Effect {
+        let rows = self.inspector_rows();
+        let effect = match action {
+            KeyAction::Next => {
+                self.inspector_cursor = (self.inspector_cursor + 1).min(rows.len() - 1);
+                Effect::None
+            }
+            KeyAction::Previous => {
+                self.inspector_cursor = self.inspector_cursor.saturating_sub(1);
+                Effect::None
+            }
+            KeyAction::Select => {
+                let chosen = rows
+                    .get(self.inspector_cursor)
+                    .and_then(|row| row.action.clone());
+                match chosen {
+                    Some(InspectorAction::CycleSort) => self.cycle_sort(),
+                    Some(InspectorAction::TurnOff(filter)) => {
+                        let label = scan_filter_label(&self.left_out.options, filter);
+                        let files = self.left_out.turn_off(filter);
+                        let added = self.state.admit_files(files);
+                        self.state
+                            .show_notice(format!("{} off: added {} files", label, added));
+                        Effect::None
+                    }
+                    Some(InspectorAction::ClearNarrowing) => {
+                        self.state.clear_narrowing();
+                        self.filter_input.clear();
+                        self.filter_error = None;
+                        self.preset = None;
+                        Effect::ResetPreview
+                    }
+                    Some(InspectorAction::DropRule(file_type)) => {
+                        self.state.default_decisions.remove(&file_type);
+                        Effect::None
+                    }
+                    None => Effect::None,
+                }
+            }
+            _ => {
+                self.view = ViewState::Browsing;
+                return Effect::None;
+            }
+        };
+        let count = self.inspector_rows().len();
+        self.inspector_cursor = self.inspector_cursor.min(count.saturating_sub(1));
+        effect
+    }
+
+    /// With `--normalize-names`, kept files with awkward names are offered for
+    /// renaming before the session is applied
+    fn offer_renames(&mut self) -> Effect {
+        if std::mem::take(&mut self.normalize_names) {
+            let kept = self
+                .state
+                .decisions()
+                .iter()
+                .filter(|(_, decision)| *decision == Decision::Keep)
+                .map(|(index, _)| *index);
+            self.renames = find_renames(&self.state.files, kept);
+            if !self.renames.is_empty() {
+                self.renames_cursor = 0;
+                self.view = ViewState::Renames;
+                return Effect::None;
+            }
+        }
+        self.start_apply()
+    }
+
+    /// → marks the highlighted rename and ← drops it; Enter renames the marked
+    /// files and applies the session, q/Esc applies it without renaming
+    fn update_renames(&mut self, action: KeyAction) -> Effect {
+        match action {
+            KeyAction::Next => {
+                self.renames_cursor = (self.renames_cursor + 1).min(self.renames.len() - 1);
+            }
+            KeyAction::Previous => self.renames_cursor = self.renames_cursor.saturating_sub(1),
+            KeyAction::Trash | KeyAction::Keep => {
+                if let Some(rename) = self.renames.get_mut(self.renames_cursor) {
+                    rename.accepted = action == KeyAction::Keep;
+                }
+            }
+            KeyAction::Quit => {
+                self.renames.clear();
+                return self.start_apply();
+            }
+            KeyAction::Select => {
+                let dry_run = self.state.engine.is_dry_run();
+                for rename in std::mem::take(&mut self.renames) {
+                    if !rename.accepted {
+                        continue;
+                    }
+                    let path = self.state.files[rename.index].path.clone();
+                    let result = if dry_run {
+                        Ok(rename.new_name)
+                    } else {
+                        crate::domain::names::rename_file(&path, &rename.new_name)
+                            .map(|_| rename.new_name)
+                            .map_err(|e| e.to_string())
+                    };
+                    self.renamed.push((path, result));
+                }
+                return self.start_apply();
+            }
+            _ => {}
+        }
+        Effect::None
+    }
+
+    /// Any key on the summary applies the session's decisions, then exits; the
+    /// backup manifest is written before the first file goes. It's written even
+    /// with nothing left to trash, when background apply already sent the files
+    /// or the session only moved some.
+    fn start_apply(&mut self) -> Effect {
+        let mut job = self.state.engine.start_apply();
+        let dry_run = self.state.engine.is_dry_run();
+        let manifest = !dry_run && Manifest::is_needed(&self.state);
+        if job.is_finished() {
+            if self.verify && !dry_run {
+                self.state.engine.verify(&mut job);
+            } else if !manifest {
+                return Effect::Exit;
+            }
+        }
+        self.apply_job = Some(job);
+        self.view = ViewState::Applying;
+        if manifest {
+            Effect::WriteManifest
+        } else {
+            Effect::None
+        }
+    }
+
+    /// Picks the view after a decision: the summary once everything is decided,
+    /// the goal prompt when the session goal is first met, the detail pass once
+    /// triage has covered every file, otherwise keep browsing
+    fn next_view_after_decision(&mut self) -> ViewState {
+        // Skipped files count as decided, so the skipped pass ends once none is left
+        if self.state.phase == ReviewPhase::Skipped {
+            return if self.state.skipped_files().is_empty() {
+                ViewState::Summary
+            } else {
+                ViewState::Browsing
+            };
+        }
+        if self.state.all_decided() {
+            return ViewState::Summary;
+        }
+        if self.state.take_goal_reached() {
+            return ViewState::GoalReached;
+        }
+        if self.state.triage_complete() && !self.state.start_detail_pass() {
+            return ViewState::Summary;
+        }
+        ViewState::Browsing
+    }
+
+    /// Adopts a re-read or newly written config (environment overrides re-applied) and
+    /// updates the session state it feeds; returns the names of the settings that changed
+    pub fn apply_config(&mut self, mut new_config: UserConfig) -> Vec<&'static str> {
+        // Environment overrides still win over the file
+        let _ = new_config.apply_env_overrides(std::env::vars());
+        let changed = self.user_config.apply_reload(new_config);
+        if changed.contains(&"auto_advance") {
+            self.state.auto_advance = self.user_config.auto_advance;
+        }
+        if changed.contains(&"queue_stats") {
+            self.state.show_queue_stats = self.user_config.queue_stats;
+        }
+        if changed.contains(&"move_targets") {
+            self.state.move_targets = self.user_config.move_target_list();
+        }
+        if changed.contains(&"default_decision") {
+            self.state.default_decisions = self.user_config.default_decisions();
+        }
+        if changed.contains(&"key_layout") {
+            super::input::set_layout(self.user_config.key_layout);
+        }
+        if changed.contains(&"glyphs") {
+            super::glyphs::set_glyphs(self.user_config.glyphs);
+        }
+        changed
+    }
+}
+
+/// Short name of a decision for notices
+fn decision_label(decision: &Decision) -> &'static str {
+    match decision {
+        Decision::Keep => "keep",
+        Decision::Trash => "trash",
+        Decision::Move(_) => "move",
+        Decision::Skip => "skip",
+    }
+}
+
+/// A scan filter as the command line spelled it, e.g. "--type image --ext pdf"
+fn scan_filter_label(options: &DiscoveryOptions, filter: ScanFilter) -> String {
+    match filter {
+        ScanFilter::Hidden => "hidden files".to_string(),
+        ScanFilter::Type => {
+            let types =
+                options.file_types.iter().flatten().map(|file_type| {
+                    format!("--type {}", format!("{:?}", file_type).to_lowercase())
+                });
+            let extensions = options
+                .extensions
+                .iter()
+                .flatten()
+                .map(|ext| format!("--ext {}", ext));
+            types.chain(extensions).collect::<Vec<_>>().join(" ")
+        }
+        ScanFilter::MinSize => format!(
+            "--min-size {}",
+            format_file_size(options.min_size.unwrap_or(0))
+        ),
+        ScanFilter::MaxSize => format!(
+            "--max-size {}",
+            format_file_size(options.max_size.unwrap_or(0))
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::FilterPreset;
+    use crate::domain::{FileEntry, SortBy};
+    use crate::usage::UsageInfo;
+    use chrono::Utc;
+    use crossterm::event::KeyModifiers;
+    use std::collections::HashMap;
+
+    fn create_test_entry(name: &str) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(name),
+            name: name.to_string(),
+            size: 0,
+            modified_date: Utc::now(),
+            file_type: FileType::Text,
+            disk: Default::default(),
+        }
+    }
+
+    /// A session over files that don't exist on disk, so nothing is staged
+    fn test_app(names: &[&str]) -> App {
+        test_app_with(names.iter().map(|name| create_test_entry(name)).collect())
+    }
+
+    fn test_app_with(files: Vec<FileEntry>) -> App {
+        let mut state = AppState::new(files);
+        state.engine.set_dry_run(true);
+        App::new(state, UserConfig::default())
+    }
+
+    /// Presses `code` the way a terminal reports it, with Shift on capital letters
+    fn press(app: &mut App, code: KeyCode) -> Effect {
+        let modifiers = match code {
+            KeyCode::Char(c) if c.is_uppercase() => KeyModifiers::SHIFT,
+            _ => KeyModifiers::NONE,
+        };
+        let action = app.action_for_key(KeyEvent::new(code, modifiers));
+        app.update(action)
+    }
+
+    #[test]
+    fn test_app_inspector_turns_filters_off_live() {
+        let mut app = test_app(&["a.txt", "b.txt", "c.txt"]);
+        let options = DiscoveryOptions {
+            min_size: Some(10),
+            ..Default::default()
+        };
+        app.left_out = LeftOut::new(options, vec![create_test_entry("tiny.txt")]);
+        app.state.narrow_queue("filter: a", &HashSet::from([0]));
+
+        press(&mut app, KeyCode::Char('I'));
+        assert_eq!(app.view, ViewState::Inspector);
+        let rows = app.inspector_rows();
+        let labels: Vec<(&str, &str)> = rows
+            .iter()
+            .map(|row| (row.label.as_str(), row.detail.as_str()))
+            .collect();
+        assert_eq!(
+            labels,
+            [
+                ("sort", "oldest first"),
+                ("hidden files", "leaves out 0 files"),
+                ("--min-size 10 B", "leaves out 1 file"),
+                (
+                    "subdirectories",
+                    "not listed; restart with --include-dirs or --recursive"
+                ),
+                ("filter: a", "hides 2 files"),
+            ]
+        );
+
+        // Turning --min-size off adds the file it left out behind the narrowing
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state.files.len(), 4);
+        assert_eq!(app.state.files[3].name, "tiny.txt");
+        assert_eq!(app.state.full_queue(), [0, 1, 2, 3]);
+        assert_eq!(app.state.engine.get_statistics().total_files, 4);
+        assert_eq!(app.inspector_rows()[2].detail, "off");
+
+        // Clearing the narrowing brings the whole queue back
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Down);
+        assert!(matches!(
+            press(&mut app, KeyCode::Enter),
+            Effect::ResetPreview
+        ));
+        assert!(app.state.narrowing.is_none());
+        assert_eq!(app.state.queue.len(), 4);
+        assert_eq!(app.inspector_cursor, app.inspector_rows().len() - 1);
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.view, ViewState::Browsing);
+    }
+
+    #[test]
+    fn test_app_easy_wins_trash_marked_groups_as_one_batch() {
+        let mut app = test_app(&["a.txt", ".DS_Store", "b.txt", "empty.log", "c.tmp"]);
+        app.easy_wins = vec![
+            EasyWin {
+                kind: EasyWinKind::Junk,
+                files: vec![1, 4],
+                accepted: true,
+            },
+            EasyWin {
+                kind: EasyWinKind::Empty,
+                files: vec![3],
+                accepted: true,
+            },
+        ];
+        app.view = ViewState::Welcome;
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.view, ViewState::EasyWins);
+
+        // Leave the empty file for the review, then accept
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Right);
+        assert!(!app.easy_wins[1].accepted);
+        assert!(matches!(
+            press(&mut app, KeyCode::Enter),
+            Effect::ResetPreview
+        ));
+
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.easy_wins.is_empty());
+        assert_eq!(app.state.pending_trash_count(), 2);
+        assert_eq!(app.state.queue, vec![0, 2, 3, 1, 4]);
+        assert_eq!(app.state.current_file().unwrap().name, "a.txt");
+        // Trashed files remember the rule that picked them
+        assert_eq!(
+            app.state.reasons.get(&4).map(String::as_str),
+            Some("matched rule 'junk': a .tmp temporary or partial download")
+        );
+        assert!(!app.state.reasons.contains_key(&3));
+
+        // One undo takes the whole batch back
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.state.decided_count(), 0);
+    }
+
+    #[test]
+    fn test_app_easy_wins_skip() {
+        let mut app = test_app(&["a.txt", "b.tmp"]);
+        app.easy_wins = vec![EasyWin {
+            kind: EasyWinKind::Junk,
+            files: vec![1],
+            accepted: true,
+        }];
+        app.view = app.review_view();
+        assert_eq!(app.view, ViewState::EasyWins);
+
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(app.state.decided_count(), 0);
+        assert_eq!(app.review_view(), ViewState::Browsing);
+    }
+
+    #[test]
+    fn test_app_easy_wins_drop_installers_still_in_use() {
+        let mut app = test_app(&["Slack.dmg", "Zoom.dmg", "a.txt", "b.tmp"]);
+        app.easy_wins = vec![
+            EasyWin {
+                kind: EasyWinKind::Junk,
+                files: vec![3],
+                accepted: true,
+            },
+            EasyWin {
+                kind: EasyWinKind::OldInstaller,
+                files: vec![0, 1],
+                accepted: true,
+            },
+        ];
+        app.view = app.review_view();
+        // Every installer is asked about, then the junk shown under the cursor
+        assert_eq!(app.easy_wins_usage_wanted(), [0, 1, 3]);
+
+        app.state.usage.insert(0, UsageInfo::never_opened());
+        app.state.usage.insert(
+            1,
+            UsageInfo {
+                last_used: Some(Utc::now() - chrono::Duration::days(2)),
+                open_count: 4,
+            },
+        );
+        app.prune_easy_wins_by_usage();
+        assert_eq!(app.easy_wins[1].files, [0]);
+
+        app.state.usage.insert(
+            0,
+            UsageInfo {
+                last_used: Some(Utc::now()),
+                open_count: 1,
+            },
+        );
+        app.easy_wins.remove(0);
+        app.prune_easy_wins_by_usage();
+        assert!(app.easy_wins.is_empty());
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.easy_wins_usage_wanted().is_empty());
+    }
+
+    #[test]
+    fn test_app_copies_found_in_background_join_easy_wins() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let files = [
+            ("installer.dmg", "old"),
+            ("report.pdf", "numbers"),
+            ("report (1).pdf", "numbers"),
+        ]
+        .iter()
+        .map(|(name, contents)| {
+            let path = temp_dir.path().join(name);
+            std::fs::write(&path, contents).unwrap();
+            let mut file = FileEntry::from_path(&path).unwrap();
+            file.modified_date = Utc::now() - chrono::Duration::days(400);
+            file
+        })
+        .collect();
+        let mut app = test_app_with(files);
+        app.easy_wins = find_easy_wins(&app.state.files, &app.state.copies, Utc::now());
+        app.easy_wins_pending = true;
+        app.copy_finder = Some(CopyFinder::start(
+            std::sync::Arc::clone(&app.state.files),
+            false,
+            crate::scan_index::HashCache::default(),
+            app.user_config.hash_algorithm,
+        ));
+        app.view = app.review_view();
+        assert_eq!(app.view, ViewState::EasyWins);
+        assert_eq!(app.easy_wins.len(), 1);
+
+        let started = Instant::now();
+        while app.is_finding() {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            app.poll_copies();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert_eq!(app.state.copies.get(&2), Some(&1));
+        assert_eq!(app.state.background_work, None);
+        // The copies go before the old installer, and the cursor stays on it
+        let kinds: Vec<EasyWinKind> = app.easy_wins.iter().map(|win| win.kind).collect();
+        assert_eq!(kinds, [EasyWinKind::Duplicate, EasyWinKind::OldInstaller]);
+        assert_eq!(app.easy_wins[0].files, [2]);
+        assert_eq!(app.easy_wins_cursor, 1);
+    }
+
+    #[test]
+    fn test_app_duplicates_found_in_background_open_from_browsing() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let files = ["a.txt", "b.txt", "c.txt"]
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                std::fs::write(&path, "same").unwrap();
+                FileEntry::from_path(&path).unwrap()
+            })
+            .collect();
+        let mut app = test_app_with(files);
+        app.duplicate_finder = Some(DuplicateFinder::start(
+            std::sync::Arc::clone(&app.state.files),
+            app.user_config.hash_algorithm,
+            false,
+            crate::scan_index::HashCache::default(),
+        ));
+        press(&mut app, KeyCode::Char('k'));
+
+        // The groups wait while another screen is up
+        press(&mut app, KeyCode::Char('?'));
+        let started = Instant::now();
+        while started.elapsed() < Duration::from_millis(200) {
+            app.poll_duplicates();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(app.is_finding());
+
+        press(&mut app, KeyCode::Esc);
+        while app.is_finding() {
+            assert!(started.elapsed() < Duration::from_secs(10));
+            app.poll_duplicates();
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        // The kept file is left out of the group
+        assert_eq!(app.view, ViewState::Duplicates);
+        let mut group = app.duplicates[0].files.clone();
+        group.sort();
+        assert_eq!(group, [1, 2]);
+    }
+
+    #[test]
+    fn test_app_duplicates_keep_one_and_trash_the_rest() {
+        let mut app = test_app(&["a.jpg", "b.jpg", "c.jpg", "d.txt", "e.txt", "f.txt"]);
+        app.duplicates = vec![
+            DuplicateGroup {
+                files: vec![0, 1, 2],
+                size: 10,
+            },
+            DuplicateGroup {
+                files: vec![3, 4],
+                size: 5,
+            },
+            DuplicateGroup {
+                files: vec![5, 0],
+                size: 10,
+            },
+        ];
+        app.view = app.review_view();
+        assert_eq!(app.view, ViewState::Duplicates);
+
+        // Keep the second file of the first group
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.state.decision_of(1), Some(&Decision::Keep));
+        assert_eq!(app.state.decision_of(0), Some(&Decision::Trash));
+        assert_eq!(app.state.decision_of(2), Some(&Decision::Trash));
+        assert_eq!(app.state.reasons[&2], "same contents as b.jpg");
+
+        // The second group is left for the review; the third has one undecided
+        // file left, so there's nothing more to choose
+        assert_eq!(app.view, ViewState::Duplicates);
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.duplicates.is_empty());
+        assert_eq!(app.state.decision_of(3), None);
+
+        // One undo takes back the whole group
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.state.decided_count(), 0);
+    }
+
+    #[test]
+    fn test_app_summary_copies_decided_paths() {
+        let mut app = test_app(&["/d/a.txt", "/d/b.txt", "/d/c.txt", "/d/d.txt"]);
+        app.skip_confirm = true;
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Left);
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Char('q'));
+        assert_eq!(app.view, ViewState::Summary);
+
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('k')),
+            Effect::CopyToClipboard(text) if text == "/d/a.txt\n/d/c.txt"
+        ));
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('t')),
+            Effect::CopyToClipboard(text) if text == "/d/b.txt"
+        ));
+        assert_eq!(
+            app.state.active_notice(),
+            Some("copied 1 trashed paths to the clipboard")
+        );
+        assert_eq!(app.view, ViewState::Summary);
+
+        // Any other key reviews the trash, then applies (a dry run has nothing to apply)
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.view, ViewState::Review);
+        assert!(matches!(press(&mut app, KeyCode::Enter), Effect::Exit));
+    }
+
+    #[test]
+    fn test_app_keep_and_trash_through_keys() {
+        let mut app = test_app(&["a.txt", "b.txt"]);
+        assert!(matches!(
+            press(&mut app, KeyCode::Right),
+            Effect::ResetPreview
+        ));
+        assert_eq!(app.state.current_index, 1);
+
+        // Dry run trashes without asking; the last decision ends on the summary
+        press(&mut app, KeyCode::Left);
+        assert_eq!(
+            app.state.decisions(),
+            &[(0, Decision::Keep), (1, Decision::Trash)]
+        );
+        assert_eq!(app.view, ViewState::Summary);
+        press(&mut app, KeyCode::Char('x'));
+        assert_eq!(app.view, ViewState::Review);
+        assert!(matches!(press(&mut app, KeyCode::Enter), Effect::Exit));
+    }
+
+    #[test]
+    fn test_app_review_keeps_trashed_files_before_applying() {
+        let mut app = test_app(&["a.txt", "b.txt", "c.txt"]);
+        press(&mut app, KeyCode::Left);
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.view, ViewState::Summary);
+
+        // Only the trashed files are listed
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.view, ViewState::Review);
+        assert_eq!(app.review, [0, 2]);
+
+        // Keep the second one, change mind on the first and back
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.state.staged_trash(), [0]);
+        assert_eq!(app.state.decision_of(2), Some(&Decision::Keep));
+
+        // Esc goes back to the summary, and the review lists what's still trashed
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.view, ViewState::Summary);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.review, [0]);
+        assert!(matches!(press(&mut app, KeyCode::Enter), Effect::Exit));
+        let stats = app.state.engine.get_statistics();
+        assert_eq!((stats.kept, stats.trashed), (2, 1));
+    }
+
+    #[test]
+    fn test_app_apply_writes_manifest_before_trashing() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("old.txt");
+        std::fs::write(&path, b"old notes").unwrap();
+        let state = AppState::new(vec![FileEntry::from_path(&path).unwrap()]);
+        let mut app = App::new(state, UserConfig::default());
+        app.skip_confirm = true;
+
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.view, ViewState::Summary);
+        press(&mut app, KeyCode::Enter);
+        assert!(matches!(
+            press(&mut app, KeyCode::Enter),
+            Effect::WriteManifest
+        ));
+        assert_eq!(app.view, ViewState::Applying);
+        // Nothing has gone to the trash yet, and the staged copy is what gets hashed
+        let manifest = crate::manifest::Manifest::build(
+            &app.state,
+            temp_dir.path(),
+            app.user_config.hash_algorithm,
+            chrono::Utc::now(),
+        );
+        assert_eq!(manifest.entries.len(), 1);
+        assert_eq!(manifest.entries[0].path, path);
+        assert_eq!(
+            manifest.entries[0].hash,
+            Some(app.user_config.hash_algorithm.hash_bytes(b"old notes"))
+        );
+
+        let mut job = app.apply_job.take().unwrap();
+        app.state.engine.cancel_apply(&mut job);
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn test_app_background_applied_files_get_a_manifest_to_restore_from() {
+        use crate::domain::ApplyOutcome;
+        use crate::manifest::{Manifest, ManifestWriter, RestoreStep};
+        use crate::retention::TrashedFile;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("old.txt");
+        std::fs::write(&path, b"old notes").unwrap();
+        let state = AppState::new(vec![FileEntry::from_path(&path).unwrap()]);
+        let mut app = App::new(state, UserConfig::default());
+        app.skip_confirm = true;
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.view, ViewState::Summary);
+
+        // Background apply sent the file to the trash an hour ago (removing the
+        // staged copy stands in for the trash)
+        std::fs::remove_file(app.state.engine.get_staged_path(0)).unwrap();
+        let sent = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        app.state
+            .engine
+            .record_applied(0, ApplyOutcome::Trashed, sent);
+
+        // Nothing is left to trash (so nothing to review), but the manifest is
+        // still written
+        assert!(matches!(
+            press(&mut app, KeyCode::Enter),
+            Effect::WriteManifest
+        ));
+        assert_eq!(app.view, ViewState::Applying);
+        let dir = temp_dir.path().join("manifests");
+        app.manifest_writer = Some(ManifestWriter::start(
+            &app.state,
+            temp_dir.path(),
+            app.user_config.hash_algorithm,
+            chrono::Utc::now(),
+            Some(dir.clone()),
+        ));
+        // Keys wait for it rather than leaving without it
+        assert!(matches!(press(&mut app, KeyCode::Enter), Effect::None));
+        let started = std::time::Instant::now();
+        while app.poll_manifest() {
+            assert!(started.elapsed() < std::time::Duration::from_secs(10));
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        let saved = app.manifest.clone().unwrap().unwrap();
+        assert!(matches!(press(&mut app, KeyCode::Enter), Effect::Exit));
+
+        // Restore finds the item, though it reached the trash before the manifest
+        let manifest = Manifest::load(&saved).unwrap();
+        let deleted = chrono::DateTime::<chrono::Utc>::from(sent);
+        assert_eq!(manifest.entries[0].trashed, Some(deleted.timestamp()));
+        let trashed = [TrashedFile { path, deleted }];
+        assert_eq!(
+            manifest.plan(|_| false, &trashed, |_, _| true),
+            [RestoreStep::FromTrash(0)]
+        );
+    }
+
+    #[test]
+    fn test_app_moves_alone_get_a_manifest() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("report.pdf");
+        std::fs::write(&path, b"report").unwrap();
+        let archive = temp_dir.path().join("archive");
+        std::fs::create_dir(&archive).unwrap();
+        let state = AppState::new(vec![FileEntry::from_path(&path).unwrap()]);
+        let mut app = App::new(state, UserConfig::default());
+        app.skip_confirm = true;
+
+        app.decide(Decision::Move(archive.clone()));
+        assert!(archive.join("report.pdf").exists());
+        app.view = ViewState::Summary;
+        assert!(matches!(
+            press(&mut app, KeyCode::Enter),
+            Effect::WriteManifest
+        ));
+    }
+
+    #[test]
+    fn test_app_trash_confirmation() {
+        let mut app = test_app(&["a.txt", "b.txt"]);
+        app.state
+            .default_decisions
+            .insert(FileType::Text, DefaultDecision::AlwaysAsk);
+
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.view, ViewState::ConfirmTrash);
+        press(&mut app, KeyCode::Char('n'));
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.state.decisions().is_empty());
+
+        press(&mut app, KeyCode::Char('t'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(app.state.decisions(), &[(0, Decision::Trash)]);
+    }
+
+    #[test]
+    fn test_app_offers_sidecars_with_trash() {
+        let mut app = test_app(&[
+            "/p/IMG_1.jpg",
+            "/p/IMG_1.xmp",
+            "/p/IMG_2.jpg",
+            "/p/IMG_2.xmp",
+        ]);
+        app.state.auto_advance = false;
+
+        // Even in a dry run the sidecar makes trash ask
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.view, ViewState::ConfirmTrash);
+        assert_eq!(app.sidecars, [1]);
+        assert!(matches!(
+            press(&mut app, KeyCode::Enter),
+            Effect::ResetPreview
+        ));
+        assert_eq!(
+            app.state.decisions(),
+            &[(0, Decision::Trash), (1, Decision::Trash)]
+        );
+        assert_eq!(
+            app.state.active_notice(),
+            Some("trashed /p/IMG_1.jpg with 1 sidecar")
+        );
+
+        // One undo brings back both
+        press(&mut app, KeyCode::Char('u'));
+        assert!(app.state.decisions().is_empty());
+
+        // o trashes just the file
+        app.update(KeyAction::Goto(2));
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.sidecars, [3]);
+        press(&mut app, KeyCode::Char('o'));
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(app.state.decisions(), &[(2, Decision::Trash)]);
+        assert!(app.sidecars.is_empty());
+
+        // Trashing a sidecar itself asks nothing more
+        app.update(KeyAction::Goto(3));
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.state.is_decided(3));
+    }
+
+    #[test]
+    fn test_app_quit_without_decisions_exits() {
+        let mut app = test_app(&["a.txt"]);
+        assert!(matches!(press(&mut app, KeyCode::Char('q')), Effect::Exit));
+    }
+
+    #[test]
+    fn test_app_commands_with_payloads() {
+        let mut app = test_app(&["c.txt", "a.txt", "b.txt"]);
+        app.user_config.mark_seen_on_browse = true;
+
+        app.update(KeyAction::Goto(usize::MAX));
+        assert_eq!(app.state.current_file().unwrap().name, "b.txt");
+        assert!(app.state.is_seen(0));
+
+        app.update(KeyAction::SetSort(SortBy::Name));
+        assert_eq!(app.state.current_index, 1);
+        assert_eq!(app.sort, (SortBy::Name, false));
+
+        // O steps on from the current order: by name, then by type
+        press(&mut app, KeyCode::Char('O'));
+        assert_eq!(app.sort, (SortBy::Type, false));
+        assert_eq!(app.state.active_notice(), Some("Files left: by type"));
+
+        app.update(KeyAction::BulkDecide(Decision::Keep));
+        assert!(app.state.all_decided());
+        assert_eq!(app.view, ViewState::Summary);
+    }
+
+    #[test]
+    fn test_app_image_keys_only_apply_to_images() {
+        let mut image = create_test_entry("b.png");
+        image.file_type = FileType::Image;
+        let mut app = test_app_with(vec![create_test_entry("a.txt"), image]);
+        assert!(matches!(press(&mut app, KeyCode::Char('+')), Effect::None));
+
+        press(&mut app, KeyCode::Down);
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('z')),
+            Effect::AdjustImage(ImageCommand::CycleFit)
+        ));
+        let pan = app.action_for_key(KeyEvent::new(KeyCode::Left, KeyModifiers::SHIFT));
+        assert!(matches!(
+            app.update(pan),
+            Effect::AdjustImage(ImageCommand::PanLeft)
+        ));
+        // Shift+arrow pans instead of deciding
+        assert!(app.state.decisions().is_empty());
+    }
+
+    #[test]
+    fn test_app_scroll_keys_scroll_preview() {
+        let mut app = test_app(&["a.txt", "b.txt"]);
+        assert!(matches!(
+            press(&mut app, KeyCode::PageDown),
+            Effect::ScrollPreview(ScrollCommand::PageDown)
+        ));
+        let half = app.action_for_key(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL));
+        assert!(matches!(
+            app.update(half),
+            Effect::ScrollPreview(ScrollCommand::HalfPageDown)
+        ));
+        assert_eq!(app.state.current_index, 0);
+
+        app.state.phase = ReviewPhase::Triage;
+        assert!(matches!(press(&mut app, KeyCode::PageUp), Effect::None));
+    }
+
+    #[test]
+    fn test_app_quick_move_targets() {
+        let mut app = test_app(&["a.jpg", "b.jpg"]);
+        app.user_config
+            .move_targets
+            .insert("1".to_string(), "/archive".to_string());
+
+        // Unbound digits only explain themselves
+        assert!(matches!(press(&mut app, KeyCode::Char('2')), Effect::None));
+        assert_eq!(
+            app.state.active_notice(),
+            Some("no move target 2 in the config")
+        );
+
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('1')),
+            Effect::ResetPreview
+        ));
+        assert_eq!(
+            app.state.decisions(),
+            &[(0, Decision::Move(PathBuf::from("/archive")))]
+        );
+        assert_eq!(app.state.current_index, 1);
+        assert_eq!(app.state.active_notice(), Some("moved to /archive"));
+    }
+
+    #[test]
+    fn test_app_move_conflict_compare_then_rename() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let photo = temp_dir.path().join("photo.jpg");
+        std::fs::write(&photo, b"same").unwrap();
+        let archive = temp_dir.path().join("archive");
+        std::fs::create_dir(&archive).unwrap();
+        std::fs::write(archive.join("photo.jpg"), b"same").unwrap();
+        let other = temp_dir.path().join("other.txt");
+        std::fs::write(&other, b"").unwrap();
+
+        let state = AppState::new(vec![
+            FileEntry::from_path(&photo).unwrap(),
+            FileEntry::from_path(&other).unwrap(),
+        ]);
+        let mut app = App::new(state, UserConfig::default());
+        app.user_config
+            .move_targets
+            .insert("1".to_string(), archive.display().to_string());
+
+        press(&mut app, KeyCode::Char('1'));
+        assert_eq!(app.view, ViewState::MoveConflict);
+        assert!(app.state.decisions().is_empty());
+
+        press(&mut app, KeyCode::Char('c'));
+        let compared = app.move_conflict.as_ref().unwrap().compared.as_ref();
+        assert!(compared.is_some_and(|(existing, identical)| existing.size == 4 && *identical));
+
+        press(&mut app, KeyCode::Char('r'));
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.move_conflict.is_none());
+        assert_eq!(
+            app.state.decisions(),
+            &[(0, Decision::Move(archive.clone()))]
+        );
+        assert!(archive.join("photo (1).jpg").exists());
+        assert!(archive.join("photo.jpg").exists());
+        assert!(!photo.exists());
+    }
+
+    #[test]
+    fn test_app_move_conflict_overwrite_is_undoable() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let photo = temp_dir.path().join("photo.jpg");
+        std::fs::write(&photo, b"new").unwrap();
+        let archive = temp_dir.path().join("archive");
+        std::fs::create_dir(&archive).unwrap();
+        let existing = archive.join("photo.jpg");
+        std::fs::write(&existing, b"old").unwrap();
+
+        let state = AppState::new(vec![FileEntry::from_path(&photo).unwrap()]);
+        let mut app = App::new(state, UserConfig::default());
+        app.user_config
+            .move_targets
+            .insert("1".to_string(), archive.display().to_string());
+
+        press(&mut app, KeyCode::Char('1'));
+        press(&mut app, KeyCode::Char('o'));
+        assert_eq!(app.view, ViewState::Summary);
+        // The file it replaced is staged like any trashed file, not gone
+        assert_eq!(
+            app.state.decisions(),
+            &[(1, Decision::Trash), (0, Decision::Move(archive.clone()))]
+        );
+        assert_eq!(app.state.files[1].path, existing);
+        assert_eq!(app.state.staged_trash(), vec![1]);
+        assert_eq!(std::fs::read(&existing).unwrap(), b"new");
+
+        assert_eq!(app.state.undo().unwrap().len(), 2);
+        assert_eq!(std::fs::read(&photo).unwrap(), b"new");
+        assert_eq!(std::fs::read(&existing).unwrap(), b"old");
+    }
+
+    #[test]
+    fn test_app_move_conflict_skip() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let photo = temp_dir.path().join("photo.jpg");
+        std::fs::write(&photo, b"new").unwrap();
+        let archive = temp_dir.path().join("archive");
+        std::fs::create_dir(&archive).unwrap();
+        std::fs::write(archive.join("photo.jpg"), b"old").unwrap();
+
+        let state = AppState::new(vec![FileEntry::from_path(&photo).unwrap()]);
+        let mut app = App::new(state, UserConfig::default());
+        app.user_config
+            .move_targets
+            .insert("1".to_string(), archive.display().to_string());
+
+        press(&mut app, KeyCode::Char('1'));
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(app.state.active_notice(), Some("move skipped"));
+        assert!(app.state.decisions().is_empty());
+        assert!(photo.exists());
+    }
+
+    #[test]
+    fn test_app_sensitive_files_always_confirm_trash() {
+        let mut app = test_app(&["notes.txt", ".env", "list.txt"]);
+
+        // A dry run trashes without asking, unless the file looks sensitive
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.view, ViewState::Browsing);
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.view, ViewState::ConfirmTrash);
+        press(&mut app, KeyCode::Esc);
+
+        // Content found in the preview counts too
+        press(&mut app, KeyCode::Char('j'));
+        app.state
+            .note_preview_lines(2, &["SSN 123-45-6789".to_string()]);
+        press(&mut app, KeyCode::Char('t'));
+        assert_eq!(app.view, ViewState::ConfirmTrash);
+        press(&mut app, KeyCode::Esc);
+
+        assert!(!app.state.current_revealed());
+        press(&mut app, KeyCode::Char('R'));
+        assert!(app.state.current_revealed());
+    }
+
+    #[test]
+    fn test_app_pause_and_lock() {
+        let mut app = test_app(&["a.txt", "b.txt"]);
+
+        // Without a passphrase any key resumes, and isn't acted on
+        press(&mut app, KeyCode::Char('h'));
+        assert_eq!(app.view, ViewState::Paused);
+        press(&mut app, KeyCode::Char('k'));
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(app.state.engine.get_statistics().kept, 0);
+
+        // H asks for a passphrase first; from then on every pause needs it
+        press(&mut app, KeyCode::Char('H'));
+        assert!(app.pause.as_ref().is_some_and(|pause| pause.setting));
+        for c in "owl".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.passphrase.as_deref(), Some("owl"));
+        press(&mut app, KeyCode::Char('q'));
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.view, ViewState::Paused);
+        assert!(app.pause.as_ref().is_some_and(|pause| pause.wrong));
+        for c in "owl".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.view, ViewState::Browsing);
+
+        press(&mut app, KeyCode::Char('h'));
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.view, ViewState::Paused);
+    }
+
+    #[test]
+    fn test_app_renames_kept_files_before_applying() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let names = ["notes.txt ", "con.txt", "fine.txt"];
+        let files = names
+            .iter()
+            .map(|name| {
+                let path = temp_dir.path().join(name);
+                std::fs::write(&path, name).unwrap();
+                FileEntry::from_path(&path).unwrap()
+            })
+            .collect();
+        let mut app = App::new(AppState::new(files), UserConfig::default());
+        app.normalize_names = true;
+
+        for _ in names {
+            press(&mut app, KeyCode::Right);
+        }
+        assert_eq!(app.view, ViewState::Summary);
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.view, ViewState::Renames);
+        let offered: Vec<&str> = app.renames.iter().map(|r| r.new_name.as_str()).collect();
+        assert_eq!(offered, ["notes.txt", "con_.txt"]);
+
+        // Leave con.txt as it is
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Left);
+        assert!(matches!(press(&mut app, KeyCode::Enter), Effect::Exit));
+        assert_eq!(
+            app.renamed,
+            [(
+                temp_dir.path().join("notes.txt "),
+                Ok("notes.txt".to_string())
+            )]
+        );
+        assert!(temp_dir.path().join("notes.txt").exists());
+        assert!(temp_dir.path().join("con.txt").exists());
+    }
+
+    #[test]
+    fn test_app_skipped_files_are_offered_again_from_the_summary() {
+        let mut app = test_app(&["a.txt", "b.txt", "c.txt"]);
+        press(&mut app, KeyCode::Char('s'));
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.view, ViewState::Summary);
+        let stats = app.state.engine.get_statistics();
+        assert_eq!((stats.kept, stats.skipped, stats.undecided), (1, 2, 0));
+
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(app.state.phase, ReviewPhase::Skipped);
+        assert_eq!(app.state.queue, [0, 2]);
+
+        // Still unsure about a.txt: skipping it again just moves on
+        press(&mut app, KeyCode::Char('s'));
+        assert_eq!(app.state.current_file().unwrap().name, "c.txt");
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.state.decision_of(2), Some(&Decision::Trash));
+        assert_eq!(app.view, ViewState::Browsing);
+        // Deciding the last skipped file ends the pass
+        press(&mut app, KeyCode::Up);
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.view, ViewState::Summary);
+        assert_eq!(app.state.engine.get_statistics().skipped, 0);
+    }
+
+    #[test]
+    fn test_app_changing_a_decision_shows_freed_delta() {
+        let mut big = create_test_entry("big.iso");
+        big.size = 2048;
+        let mut app = test_app_with(vec![big, create_test_entry("b.txt")]);
+
+        press(&mut app, KeyCode::Left);
+        press(&mut app, KeyCode::Up);
+        // Same decision again is a no-op
+        assert!(matches!(press(&mut app, KeyCode::Left), Effect::None));
+
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.state.decisions(), &[(0, Decision::Keep)]);
+        assert_eq!(
+            app.state.active_notice(),
+            Some("trash → keep: −2.0 KB freed (now 0 B)")
+        );
+        assert_eq!(app.state.current_index, 1);
+    }
+
+    #[test]
+    fn test_app_select_all_like_this() {
+        let mut app = test_app(&["IMG_0001.jpg", "notes.txt", "IMG_0002.jpg", "IMG_0003.jpg"]);
+
+        press(&mut app, KeyCode::Char('*'));
+        assert_eq!(app.view, ViewState::SelectSimilar);
+        // Esc backs out without deciding anything
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.state.decisions().is_empty());
+
+        press(&mut app, KeyCode::Char('*'));
+        assert!(matches!(
+            press(&mut app, KeyCode::Left),
+            Effect::ResetPreview
+        ));
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(
+            app.state.decisions(),
+            &[
+                (0, Decision::Trash),
+                (2, Decision::Trash),
+                (3, Decision::Trash)
+            ]
+        );
+        assert_eq!(app.state.current_index, 1);
+
+        // One undo takes back the whole selection
+        press(&mut app, KeyCode::Char('u'));
+        assert!(app.state.decisions().is_empty());
+
+        // Names without digits or a copy suffix have nothing to match on
+        app.state.current_index = 1;
+        press(&mut app, KeyCode::Char('*'));
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.state.active_notice().is_some());
+    }
+
+    #[test]
+    fn test_app_content_search_narrows_the_queue() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let files = [
+            ("a.txt", "nothing"),
+            ("b.txt", "Invoice 2021"),
+            ("c.txt", "also nothing"),
+        ]
+        .into_iter()
+        .map(|(name, text)| {
+            let path = dir.path().join(name);
+            std::fs::write(&path, text).unwrap();
+            FileEntry::from_path(&path).unwrap()
+        })
+        .collect();
+        let mut app = test_app_with(files);
+
+        press(&mut app, KeyCode::Char('/'));
+        assert_eq!(app.view, ViewState::Search);
+        // Keys type into the prompt instead of deciding
+        for c in "invoice 20211".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(app.search_input, "invoice 2021");
+        assert!(app.state.decisions().is_empty());
+
+        press(&mut app, KeyCode::Enter);
+        assert!(app.is_searching());
+        let started = std::time::Instant::now();
+        while !app.poll_search() {
+            assert!(started.elapsed() < std::time::Duration::from_secs(10));
+            std::thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(app.state.queue, vec![1]);
+        assert_eq!(app.state.current_file().unwrap().name, "b.txt");
+
+        // An empty search shows every file again
+        press(&mut app, KeyCode::Char('/'));
+        assert!(matches!(
+            press(&mut app, KeyCode::Enter),
+            Effect::ResetPreview
+        ));
+        assert_eq!(app.state.queue, vec![0, 1, 2]);
+        assert_eq!(app.state.current_index, 1);
+    }
+
+    #[test]
+    fn test_app_filter_bar_narrows_live() {
+        let mut app = test_app(&["IMG_1.png", "notes.txt", "IMG_2.png", "todo.txt"]);
+        app.state.next();
+
+        press(&mut app, KeyCode::Char('F'));
+        assert_eq!(app.view, ViewState::Filter);
+        for c in "ext:".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        // "ext:" needs an extension yet; the last expression that parsed ("ext") stays applied
+        assert!(app.filter_error.is_some());
+        assert!(app.state.queue.is_empty());
+        for c in "txt".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        assert_eq!(app.filter_error, None);
+        assert_eq!(app.state.queue, vec![1, 3]);
+        assert_eq!(app.state.current_file().unwrap().name, "notes.txt");
+
+        // Enter keeps the filter; keys decide again, against the full list
+        press(&mut app, KeyCode::Enter);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.filter_active());
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.state.decisions(), &[(1, Decision::Keep)]);
+
+        // Esc in the bar clears it and brings back the rest of the queue
+        press(&mut app, KeyCode::Char('F'));
+        press(&mut app, KeyCode::Esc);
+        assert!(!app.filter_active());
+        assert_eq!(app.state.queue, vec![0, 1, 2, 3]);
+        assert!(app.state.is_decided(1));
+    }
+
+    #[test]
+    fn test_app_filter_presets() {
+        let mut files: Vec<FileEntry> = ["b.zip", "notes.txt", "a.zip", "c.zip"]
+            .into_iter()
+            .map(create_test_entry)
+            .collect();
+        files[0].size = 500;
+        files[2].size = 5_000;
+        files[3].size = 50;
+        let mut app = test_app_with(files);
+        app.user_config.presets = HashMap::from([
+            (
+                "archives".to_string(),
+                FilterPreset {
+                    filter: "ext:zip size>100".to_string(),
+                    sort: Some(SortBy::Size),
+                },
+            ),
+            (
+                "text".to_string(),
+                FilterPreset {
+                    filter: "ext:txt".to_string(),
+                    sort: None,
+                },
+            ),
+        ]);
+
+        assert_eq!(app.apply_preset("archives").unwrap(), 2);
+        assert_eq!(app.state.queue, vec![0, 2]);
+        assert_eq!(app.filter_input, "ext:zip size>100");
+        assert!(app.filter_active());
+        assert!(app.apply_preset("missing").is_err());
+
+        // Tab in the filter bar moves on to the next preset, then wraps around
+        press(&mut app, KeyCode::Char('F'));
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.preset.as_deref(), Some("text"));
+        assert_eq!(app.state.queue, vec![1]);
+        press(&mut app, KeyCode::Tab);
+        assert_eq!(app.preset.as_deref(), Some("archives"));
+
+        // Editing the expression leaves the preset behind
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(app.preset, None);
+    }
+
+    #[test]
+    fn test_app_focus_mode_toggles_and_ignores_clicks() {
+        let mut app = test_app(&["a.txt", "b.txt"]);
+        press(&mut app, KeyCode::Char('Z'));
+        assert!(app.focus);
+
+        // Deciding works as usual
+        press(&mut app, KeyCode::Right);
+        assert!(app.state.is_decided(0));
+
+        let click = MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column: 1,
+            row: 2,
+            modifiers: KeyModifiers::NONE,
+        };
+        assert_eq!(
+            app.action_for_click(click, Rect::new(0, 0, 80, 24)),
+            KeyAction::None
+        );
+
+        press(&mut app, KeyCode::Char('Z'));
+        assert!(!app.focus);
+    }
+
+    #[test]
+    fn test_app_overview_opens_and_any_key_closes() {
+        let mut app = test_app(&["a.txt", "b.txt"]);
+        press(&mut app, KeyCode::Char('D'));
+        assert_eq!(app.view, ViewState::Overview);
+        let overview = app.overview.as_ref().unwrap();
+        assert_eq!(overview.by_age[0].1.count, 2);
+
+        // Even a decision key just closes it
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.overview.is_none());
+        assert!(!app.state.is_decided(0));
+    }
+
+    #[test]
+    fn test_app_preview_failure_details() {
+        let mut app = test_app(&["a.png", "b.txt"]);
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('e')),
+            Effect::ShowPreviewFailure
+        ));
+        app.show_preview_failure(None);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.state.active_notice().is_some());
+
+        let file = app.state.current_file().unwrap().clone();
+        app.show_preview_failure(Some(PreviewFailure::collect(&file, "Image loading error")));
+        assert_eq!(app.view, ViewState::PreviewError);
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('b')),
+            Effect::SaveDiagnostics
+        ));
+        assert_eq!(app.view, ViewState::PreviewError);
+
+        // Anything else closes it without deciding
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.preview_failure.is_none());
+        assert!(!app.state.is_decided(0));
+    }
+
+    #[test]
+    fn test_app_rapid_decisions_prompt_to_undo() {
+        let names: Vec<String> = (0..20).map(|i| format!("{:02}.txt", i)).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let mut app = test_app(&names);
+
+        for _ in 0..RAPID_COUNT {
+            press(&mut app, KeyCode::Right);
+        }
+        assert_eq!(app.view, ViewState::RapidDecisions);
+        assert_eq!(app.rapid_count(), RAPID_COUNT);
+        // A key still held down does nothing while the prompt is open
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.state.decided_count(), RAPID_COUNT);
+
+        // Yes undoes the whole burst in one step and goes back to its first file
+        press(&mut app, KeyCode::Char('y'));
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(app.state.decided_count(), 0);
+        assert_eq!(app.state.current_index, 0);
+
+        // No carries on without asking again during the same burst, and the
+        // burst still undoes as one
+        for _ in 0..RAPID_COUNT {
+            press(&mut app, KeyCode::Left);
+        }
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.view, ViewState::Browsing);
+        press(&mut app, KeyCode::Left);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(app.state.decided_count(), RAPID_COUNT + 1);
+        press(&mut app, KeyCode::Char('u'));
+        press(&mut app, KeyCode::Char('u'));
+        assert_eq!(app.state.decided_count(), 0);
+    }
+
+    #[test]
+    fn test_app_paced_decisions_never_prompt() {
+        let names: Vec<String> = (0..20).map(|i| format!("{:02}.txt", i)).collect();
+        let names: Vec<&str> = names.iter().map(String::as_str).collect();
+        let mut app = test_app(&names);
+        for _ in 0..RAPID_COUNT * 2 {
+            if let Some(burst) = app.rapid.as_mut() {
+                burst.last -= RAPID_GAP * 2;
+            }
+            press(&mut app, KeyCode::Right);
+        }
+        assert_eq!(app.view, ViewState::Browsing);
+        assert_eq!(app.state.decided_count(), RAPID_COUNT * 2);
+    }
+
+    #[test]
+    fn test_app_pins_and_unpins_preview() {
+        let mut app = test_app(&["draft.txt", "final.txt"]);
+        press(&mut app, KeyCode::Char('P'));
+        assert_eq!(app.pinned, Some(0));
+
+        // The pin stays while moving on and deciding
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Right);
+        assert_eq!(app.pinned, Some(0));
+
+        press(&mut app, KeyCode::Char('P'));
+        assert_eq!(app.pinned, None);
+    }
+
+    #[test]
+    fn test_app_macro_replay_is_not_a_rapid_burst() {
+        let mut app = test_app(&["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"]);
+        app.user_config.macro_steps = vec!["keep".to_string(); 9];
+        press(&mut app, KeyCode::Char('@'));
+        assert_eq!(app.state.decided_count(), 9);
+        assert_eq!(app.view, ViewState::Browsing);
+        assert!(app.rapid.is_none());
+    }
+
+    #[test]
+    fn test_app_records_and_replays_macros() {
+        let mut app = test_app(&["a.jpg", "b.jpg", "c.jpg", "d.jpg"]);
+        app.state.auto_advance = false;
+        app.user_config
+            .move_targets
+            .insert("1".to_string(), "/archive".to_string());
+
+        press(&mut app, KeyCode::Char('@'));
+        assert_eq!(
+            app.state.active_notice(),
+            Some("no macro recorded; press M to record one")
+        );
+
+        // Only the steps a macro can hold are recorded
+        press(&mut app, KeyCode::Char('M'));
+        press(&mut app, KeyCode::Char('1'));
+        press(&mut app, KeyCode::Char('L'));
+        press(&mut app, KeyCode::Down);
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('M')),
+            Effect::SaveMacro(ref steps) if steps == &["move:1", "next"]
+        ));
+        assert_eq!(app.user_config.macro_steps, ["move:1", "next"]);
+        assert_eq!(app.state.current_index, 1);
+
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('@')),
+            Effect::ResetPreview
+        ));
+        assert!(matches!(
+            app.state.decision_of(1),
+            Some(Decision::Move(dir)) if dir == std::path::Path::new("/archive")
+        ));
+        assert_eq!(app.state.current_index, 2);
+
+        // A step that needs confirmation ends the replay there
+        app.state.engine.set_dry_run(false);
+        app.user_config.macro_steps = vec!["trash".to_string(), "next".to_string()];
+        press(&mut app, KeyCode::Char('@'));
+        assert_eq!(app.view, ViewState::ConfirmTrash);
+        assert_eq!(app.state.current_index, 2);
+        assert_eq!(
+            app.state.active_notice(),
+            Some("macro stopped after 1 of 2 steps")
+        );
+        press(&mut app, KeyCode::Char('n'));
+
+        app.user_config.macro_steps = vec!["keep".to_string(), "tag:old".to_string()];
+        assert!(matches!(press(&mut app, KeyCode::Char('@')), Effect::None));
+        assert_eq!(
+            app.state.active_notice(),
+            Some("unknown macro step 'tag:old' in the config")
+        );
+        assert!(!app.state.is_decided(2));
+    }
+
+    #[test]
+    fn test_app_recording_drops_cancelled_trash() {
+        let mut app = test_app(&["a.txt", "b.txt"]);
+        app.state.engine.set_dry_run(false);
+        press(&mut app, KeyCode::Char('M'));
+        press(&mut app, KeyCode::Char('t'));
+        press(&mut app, KeyCode::Char('n'));
+        press(&mut app, KeyCode::Char('k'));
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('M')),
+            Effect::SaveMacro(ref steps) if steps == &["keep"]
+        ));
+    }
+
+    #[test]
+    fn test_app_help_overlay_takes_typed_keys() {
+        let mut app = test_app(&["a.txt"]);
+        press(&mut app, KeyCode::Char('?'));
+        assert_eq!(app.view, ViewState::Help);
+
+        // q searches rather than quitting
+        press(&mut app, KeyCode::Char('q'));
+        assert_eq!(app.help.filter, "q");
+        press(&mut app, KeyCode::Esc);
+        press(&mut app, KeyCode::Esc);
+        assert_eq!(app.view, ViewState::Browsing);
+    }
+
+    #[test]
+    fn test_app_setup_then_welcome() {
+        let mut app = test_app(&["a.txt"]);
+        app.setup = Some(SetupWizard::new());
+        app.view = ViewState::Setup;
+
+        press(&mut app, KeyCode::Down);
+        assert!(matches!(press(&mut app, KeyCode::Enter), Effect::None));
+        let Effect::SaveConfig(stored) = press(&mut app, KeyCode::Esc) else {
+            panic!("finishing setup should save the answers");
+        };
+        assert!(!stored.auto_advance);
+        assert!(!app.state.auto_advance);
+        assert_eq!(app.view, ViewState::Welcome);
+
+        assert!(matches!(
+            press(&mut app, KeyCode::Char('x')),
+            Effect::MarkWelcomeShown
+        ));
+        assert_eq!(app.view, ViewState::Browsing);
+    }
+}
@@ -0,0 +1,144 @@
+//! Plain ASCII stand-ins for the symbols the TUI draws
+//!
+//! The Linux console, serial lines and terminals without a UTF-8 locale show
+//! symbols like `⏸`, `⚠` or `✓` as boxes, or as two columns that push the rest of
+//! the line over. In ASCII mode each finished frame is rewritten cell by cell just
+//! before it's drawn: borders become `+-|`, arrows `<>^v`, the spinner `|/-\`,
+//! emoji `*`, and so on. Every stand-in is one column wide, and the second column
+//! of a wide symbol is already blank, so nothing shifts. File names and previews
+//! keep their characters unless they are one of these symbols.
+
+use ratatui::buffer::Buffer;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether frames are rewritten to ASCII before they're drawn
+static ASCII: AtomicBool = AtomicBool::new(false);
+
+/// Terminals known to lack the symbols, by `TERM`
+const ASCII_TERMS: &[&str] = &["linux", "dumb", "vt100", "vt102", "vt220", "cons25"];
+
+/// Which symbols the TUI draws (`"glyphs"` in the config)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Glyphs {
+    /// ASCII on the terminals and locales listed in `detect`, Unicode elsewhere
+    #[default]
+    Auto,
+    Unicode,
+    Ascii,
+}
+
+/// Switches ASCII mode on or off for `glyphs`, detecting the terminal for `Auto`
+pub fn set_glyphs(glyphs: Glyphs) {
+    let ascii = match glyphs {
+        Glyphs::Auto => detect(std::env::var("TERM").ok().as_deref(), locale().as_deref()),
+        Glyphs::Unicode => false,
+        Glyphs::Ascii => true,
+    };
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// Whether frames are drawn in ASCII
+pub fn is_ascii() -> bool {
+    ASCII.load(Ordering::Relaxed)
+}
+
+/// Whether a terminal should get ASCII: a `TERM` without the symbols, or a
+/// locale that isn't UTF-8
+pub fn detect(term: Option<&str>, locale: Option<&str>) -> bool {
+    let plain_term = term.is_some_and(|term| ASCII_TERMS.contains(&term));
+    let plain_locale = locale.is_some_and(|locale| {
+        let locale = locale.to_lowercase();
+        !(locale.contains("utf-8") || locale.contains("utf8"))
+    });
+    plain_term || plain_locale
+}
+
+/// The locale text is shown in: the first of `LC_ALL`, `LC_CTYPE` and `LANG` set
+fn locale() -> Option<String> {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+/// Rewrites `buffer` to ASCII when ASCII mode is on; call last when drawing a frame
+pub fn apply(buffer: &mut Buffer) {
+    if is_ascii() {
+        to_ascii(buffer);
+    }
+}
+
+/// Replaces every symbol in `buffer` that has a stand-in
+pub fn to_ascii(buffer: &mut Buffer) {
+    for cell in buffer.content.iter_mut() {
+        if let Some(plain) = stand_in(cell.symbol()) {
+            cell.set_symbol(plain);
+        }
+    }
+}
+
+/// The one-column ASCII stand-in for `symbol`, if it needs one
+fn stand_in(symbol: &str) -> Option<&'static str> {
+    let mut chars = symbol.chars();
+    let c = chars.next()?;
+    if c.is_ascii() {
+        return None;
+    }
+    Some(match c {
+        '╭' | '╮' | '╰' | '╯' | '┌' | '┐' | '└' | '┘' | '├' | '┤' | '┬' | '┴' | '┼' => {
+            "+"
+        }
+        '─' | '━' | '═' => "-",
+        '│' | '┃' | '║' | '▏' => "|",
+        '→' | '›' | '▶' | '»' => ">",
+        '←' | '‹' | '«' => "<",
+        '↑' => "^",
+        '↓' => "v",
+        '↺' => "@",
+        '·' | '•' => ".",
+        '…' => "~",
+        '—' | '–' | '−' => "-",
+        '×' => "x",
+        '✓' => "+",
+        '✗' => "x",
+        '○' => "o",
+        '⚠' => "!",
+        '⏸' => "=",
+        '█' | '▉' | '▊' | '▋' | '▌' | '▍' | '▎' => "#",
+        '▒' | '░' | '▓' => ":",
+        '⠋' | '⠼' | '⠇' => "|",
+        '⠙' | '⠴' | '⠏' => "/",
+        '⠹' | '⠦' => "-",
+        '⠸' | '⠧' => "\\",
+        // Emoji and pictographs
+        '\u{1F000}'..='\u{1FAFF}' | '\u{2600}'..='\u{27BF}' => "*",
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    #[test]
+    fn test_detect() {
+        assert!(detect(Some("linux"), Some("en_US.UTF-8")));
+        assert!(detect(Some("xterm-256color"), Some("C")));
+        assert!(detect(None, Some("de_DE.ISO-8859-1")));
+        assert!(!detect(Some("xterm-256color"), Some("en_US.utf8")));
+        assert!(!detect(Some("xterm-kitty"), None));
+    }
+
+    #[test]
+    fn test_to_ascii_keeps_columns() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 12, 1));
+        buffer.set_string(0, 0, "╭→🎉✓ é ⠹", Style::default());
+        to_ascii(&mut buffer);
+        let line: String = buffer.content.iter().map(|cell| cell.symbol()).collect();
+        assert_eq!(line, "+>* + é -   ");
+    }
+}
@@ -0,0 +1,158 @@
+// Help overlay state: the keybinding list generated from the keymap, with search and scrolling
+use super::input::{key_label, keymap};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+/// Lines moved by PageUp / PageDown
+const HELP_PAGE: usize = 8;
+
+/// One row of the help overlay: every key bound to an action, and what it does
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelpEntry {
+    pub keys: String,
+    pub description: &'static str,
+}
+
+/// Help rows generated from the current keymap, one per action, in keymap order
+pub fn help_entries() -> Vec<HelpEntry> {
+    let mut entries: Vec<(HelpEntry, Vec<String>)> = Vec::new();
+    for binding in keymap() {
+        let label = key_label(binding.code, binding.modifiers);
+        let description = binding.action.description();
+        match entries
+            .iter_mut()
+            .find(|(e, _)| e.description == description)
+        {
+            Some((_, keys)) => keys.push(label),
+            None => entries.push((
+                HelpEntry {
+                    keys: String::new(),
+                    description,
+                },
+                vec![label],
+            )),
+        }
+    }
+    entries
+        .into_iter()
+        .map(|(mut entry, keys)| {
+            entry.keys = keys.join(" / ");
+            entry
+        })
+        .collect()
+}
+
+/// Search text and scroll position of the help overlay
+#[derive(Debug, Clone, Default)]
+pub struct HelpState {
+    /// Filter typed while the overlay is open (matches keys or descriptions)
+    pub filter: String,
+    /// First visible row
+    pub scroll: usize,
+}
+
+impl HelpState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rows matching the current filter (case-insensitive)
+    pub fn entries(&self) -> Vec<HelpEntry> {
+        let filter = self.filter.to_lowercase();
+        help_entries()
+            .into_iter()
+            .filter(|entry| {
+                filter.is_empty()
+                    || entry.description.to_lowercase().contains(&filter)
+                    || entry.keys.to_lowercase().contains(&filter)
+            })
+            .collect()
+    }
+
+    /// Handles a key press; returns true when the overlay should close.
+    ///
+    /// Typed characters edit the filter, arrows and PageUp/PageDown scroll, `?` closes,
+    /// and Esc clears the filter (or closes when it is already empty).
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match (key.code, key.modifiers) {
+            (KeyCode::Char('?'), _) => return true,
+            (KeyCode::Char('c'), KeyModifiers::CONTROL) => return true,
+            (KeyCode::Esc, _) if self.filter.is_empty() => return true,
+            (KeyCode::Esc, _) => self.set_filter(String::new()),
+            (KeyCode::Backspace, _) => {
+                let mut filter = self.filter.clone();
+                filter.pop();
+                self.set_filter(filter);
+            }
+            (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                let filter = format!("{}{}", self.filter, c);
+                self.set_filter(filter);
+            }
+            (KeyCode::Down, _) => self.scroll_by(1),
+            (KeyCode::Up, _) => self.scroll = self.scroll.saturating_sub(1),
+            (KeyCode::PageDown, _) => self.scroll_by(HELP_PAGE),
+            (KeyCode::PageUp, _) => self.scroll = self.scroll.saturating_sub(HELP_PAGE),
+            _ => {}
+        }
+        false
+    }
+
+    fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        self.scroll = 0;
+    }
+
+    fn scroll_by(&mut self, rows: usize) {
+        let last = self.entries().len().saturating_sub(1);
+        self.scroll = (self.scroll + rows).min(last);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(state: &mut HelpState, code: KeyCode) -> bool {
+        state.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    #[test]
+    fn test_help_entries_group_keys_by_action() {
+        let entries = help_entries();
+        assert_eq!(entries[0].keys, "→ / k");
+        assert_eq!(entries[0].description, "Keep file");
+        assert!(entries
+            .iter()
+            .any(|e| e.keys == "q / Esc / Ctrl+C" && e.description == "Quit"));
+    }
+
+    #[test]
+    fn test_help_filter_as_you_type() {
+        let mut state = HelpState::new();
+        for c in "trash".chars() {
+            assert!(!press(&mut state, KeyCode::Char(c)));
+        }
+        let entries = state.entries();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].description, "Trash file");
+
+        // Esc clears the filter first, then closes
+        assert!(!press(&mut state, KeyCode::Esc));
+        assert!(state.filter.is_empty());
+        assert!(press(&mut state, KeyCode::Esc));
+    }
+
+    #[test]
+    fn test_help_scroll_is_clamped() {
+        let mut state = HelpState::new();
+        press(&mut state, KeyCode::Up);
+        assert_eq!(state.scroll, 0);
+
+        for _ in 0..100 {
+            press(&mut state, KeyCode::PageDown);
+        }
+        assert_eq!(state.scroll, help_entries().len() - 1);
+
+        press(&mut state, KeyCode::Char('u'));
+        assert_eq!(state.scroll, 0);
+    }
+}
@@ -1,3 +1,7 @@
+use crate::domain::file_entry::BIDI_CONTROL_CHARS;
+use crate::domain::AppState;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
 /// Calculates progress percentage
 pub fn calculate_progress(current: usize, total: usize) -> f64 {
     if total == 0 {
@@ -24,6 +28,126 @@ pub fn format_file_size(size: u64) -> String {
     }
 }
 
+/// Size of the current item; a directory still being measured shows its total
+/// so far, a measured one how many entries it holds
+pub fn current_size_label(state: &AppState) -> String {
+    let Some(index) = state.current_file_index() else {
+        return String::new();
+    };
+    match state.directories.get(&index) {
+        Some(dir) if !dir.done => {
+            format!("calculating… {} so far", format_file_size(dir.stats.bytes))
+        }
+        Some(dir) => format!(
+            "{} in {} entries",
+            format_file_size(dir.stats.bytes),
+            dir.stats.entries()
+        ),
+        None if state.current_is_placeholder() => state
+            .current_file()
+            .map(|file| format!("{} online-only", format_file_size(file.size)))
+            .unwrap_or_default(),
+        None => state
+            .current_file()
+            .map(|file| format_file_size(file.size))
+            .unwrap_or_default(),
+    }
+}
+
+/// Returns the number of terminal columns a string occupies
+///
+/// Wide characters (CJK, most emoji) take two columns and combining marks
+/// take none, so this differs from `chars().count()` for many file names.
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// Truncates a name in the middle so that it fits in `max_width` columns.
+///
+/// The start of the name and its extension are preserved, e.g.
+/// `very-long-report-draft-final.pdf` becomes `very-long-…final.pdf` at 20 columns.
+pub fn truncate_middle(name: &str, max_width: usize) -> String {
+    if display_width(name) <= max_width {
+        return name.to_string();
+    }
+    if max_width <= 1 {
+        return "…".repeat(max_width);
+    }
+
+    // Keep at least the extension (including the dot) at the end
+    let ext_width = match name.rfind('.') {
+        Some(pos) if pos > 0 => display_width(&name[pos..]),
+        _ => 0,
+    };
+
+    let available = max_width - 1;
+    let tail_budget = ext_width.max(available / 2).min(available);
+
+    // Collect the tail from the end, never splitting a wide character
+    let mut tail_rev = Vec::new();
+    let mut tail_width = 0;
+    for c in name.chars().rev() {
+        let w = c.width().unwrap_or(0);
+        if tail_width + w > tail_budget {
+            break;
+        }
+        tail_width += w;
+        tail_rev.push(c);
+    }
+    // Drop combining marks whose base character did not fit
+    while tail_rev.last().is_some_and(|c| c.width().unwrap_or(0) == 0) {
+        tail_rev.pop();
+    }
+
+    let head_budget = available - tail_width;
+    let mut result = String::new();
+    let mut head_width = 0;
+    for c in name.chars() {
+        let w = c.width().unwrap_or(0);
+        if head_width + w > head_budget {
+            break;
+        }
+        head_width += w;
+        result.push(c);
+    }
+
+    result.push('…');
+    result.extend(tail_rev.iter().rev());
+    result
+}
+
+/// Escapes characters that could corrupt the terminal or disguise a name
+///
+/// Control characters (newlines, ANSI escapes, bells, C1 codes) are shown as
+/// `\n`, `\x1b` and so on, and bidi controls as `\u{202E}`, so names stay on
+/// one line and in their logical order.
+pub fn sanitize_for_display(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\x{:02x}", c as u32)),
+            c if BIDI_CONTROL_CHARS.contains(&c) => {
+                escaped.push_str(&format!("\\u{{{:04X}}}", c as u32))
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Sanitizes one line of preview text, expanding tabs instead of escaping them
+pub fn sanitize_preview_line(line: &str) -> String {
+    sanitize_for_display(&line.replace('\t', "    "))
+}
+
+/// Prepares a file name for display in `max_width` columns
+pub fn display_name(name: &str, max_width: usize) -> String {
+    truncate_middle(&sanitize_for_display(name), max_width)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -36,6 +160,39 @@ mod tests {
         assert_eq!(calculate_progress(0, 0), 0.0);
     }
 
+    #[test]
+    fn test_current_size_label() {
+        use crate::domain::{DirectoryStats, FileEntry, FileType};
+        let file = |size| FileEntry {
+            path: "/p/node_modules".into(),
+            name: "node_modules".to_string(),
+            size,
+            modified_date: chrono::Utc::now(),
+            file_type: FileType::Directory,
+            disk: Default::default(),
+        };
+        let mut state = AppState::new(vec![file(0)]);
+        assert_eq!(current_size_label(&state), "0 B");
+
+        let stats = DirectoryStats {
+            files: 3,
+            directories: 1,
+            bytes: 2048,
+        };
+        state.update_directory_sizes([(0, stats, false)]);
+        assert_eq!(current_size_label(&state), "calculating… 2.0 KB so far");
+        let list = state.files.as_ptr();
+        state.update_directory_sizes([(0, stats, true)]);
+        assert_eq!(current_size_label(&state), "2.0 KB in 4 entries");
+        assert_eq!(state.files[0].size, 2048);
+        assert_eq!(state.engine.files[0].size, 2048);
+        // Resized in place and still shared with the engine
+        assert_eq!(state.files.as_ptr(), list);
+        assert!(std::sync::Arc::ptr_eq(&state.files, &state.engine.files));
+
+        assert_eq!(current_size_label(&AppState::new(vec![])), "");
+    }
+
     #[test]
     fn test_format_file_size() {
         assert_eq!(format_file_size(0), "0 B");
@@ -45,4 +202,110 @@ mod tests {
         assert_eq!(format_file_size(1024 * 1024), "1.0 MB");
         assert_eq!(format_file_size(1024 * 1024 * 1024), "1.0 GB");
     }
+
+    #[test]
+    fn test_truncate_middle_short_name_unchanged() {
+        assert_eq!(truncate_middle("file.txt", 20), "file.txt");
+        assert_eq!(truncate_middle("file.txt", 8), "file.txt");
+    }
+
+    #[test]
+    fn test_truncate_middle_preserves_extension() {
+        let truncated = truncate_middle("very-long-report-draft-v2-final.pdf", 20);
+        assert_eq!(display_width(&truncated), 20);
+        assert!(truncated.starts_with("very-long"));
+        assert!(truncated.ends_with("final.pdf"));
+        assert!(truncated.contains('…'));
+    }
+
+    #[test]
+    fn test_truncate_middle_without_extension() {
+        let truncated = truncate_middle("abcdefghijklmnopqrstuvwxyz", 10);
+        assert_eq!(truncated, "abcde…wxyz");
+    }
+
+    #[test]
+    fn test_truncate_middle_tiny_widths() {
+        assert_eq!(truncate_middle("long-name.txt", 0), "");
+        assert_eq!(truncate_middle("long-name.txt", 1), "…");
+        assert_eq!(truncate_middle("long-name.txt", 5), "….txt");
+    }
+
+    #[test]
+    fn test_display_width_wide_and_combining() {
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("写真"), 4);
+        assert_eq!(display_width("🎉"), 2);
+        // "e" followed by a combining acute accent renders as a single column
+        assert_eq!(display_width("e\u{301}"), 1);
+    }
+
+    #[test]
+    fn test_truncate_middle_cjk_respects_columns() {
+        let name = "報告書_最終版_二〇二四年_確定稿.pdf";
+        let truncated = truncate_middle(name, 16);
+        assert!(display_width(&truncated) <= 16);
+        assert!(truncated.starts_with("報告"));
+        assert!(truncated.ends_with(".pdf"));
+    }
+
+    #[test]
+    fn test_truncate_middle_emoji_not_split() {
+        let name = "🎉🎉🎉🎉🎉🎉🎉🎉🎉🎉.png";
+        let truncated = truncate_middle(name, 11);
+        assert!(display_width(&truncated) <= 11);
+        assert!(truncated.ends_with(".png"));
+        assert!(truncated
+            .chars()
+            .all(|c| c == '🎉' || c == '…' || ".png".contains(c)));
+    }
+
+    #[test]
+    fn test_truncate_middle_keeps_combining_marks_with_base() {
+        let name = "cafe\u{301}-menu-cafe\u{301}-menu-cafe\u{301}.txt";
+        let truncated = truncate_middle(name, 12);
+        assert!(display_width(&truncated) <= 12);
+        // A combining mark never directly follows the ellipsis
+        let after_ellipsis = truncated.split('…').nth(1).unwrap();
+        assert!(!after_ellipsis.starts_with('\u{301}'));
+    }
+
+    #[test]
+    fn test_sanitize_escapes_bidi_controls() {
+        assert_eq!(
+            sanitize_for_display("invoice\u{202E}fdp.exe"),
+            "invoice\\u{202E}fdp.exe"
+        );
+        assert_eq!(sanitize_for_display("plain.txt"), "plain.txt");
+        // Right-to-left letters are left untouched
+        assert_eq!(sanitize_for_display("تقرير.pdf"), "تقرير.pdf");
+    }
+
+    #[test]
+    fn test_sanitize_escapes_hostile_names() {
+        assert_eq!(sanitize_for_display("evil\x1b[2J.txt"), "evil\\x1b[2J.txt");
+        assert_eq!(sanitize_for_display("two\nlines.txt"), "two\\nlines.txt");
+        assert_eq!(sanitize_for_display("bell\x07.txt"), "bell\\x07.txt");
+        assert_eq!(sanitize_for_display("del\x7f.txt"), "del\\x7f.txt");
+        assert_eq!(sanitize_for_display("csi\u{9b}.txt"), "csi\\x9b.txt");
+        assert!(!sanitize_for_display("\x1b]0;title\x07")
+            .chars()
+            .any(|c| c.is_control()));
+    }
+
+    #[test]
+    fn test_sanitize_preview_line_expands_tabs() {
+        assert_eq!(sanitize_preview_line("\tfn main() {}"), "    fn main() {}");
+        assert_eq!(
+            sanitize_preview_line("echo \x1b[31mred"),
+            "echo \\x1b[31mred"
+        );
+    }
+
+    #[test]
+    fn test_display_name_keeps_real_extension_visible() {
+        let shown = display_name("invoice\u{202E}fdp.exe", 40);
+        assert!(shown.ends_with("fdp.exe"));
+        assert!(!shown.contains('\u{202E}'));
+    }
 }
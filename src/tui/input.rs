@@ -1,6 +1,21 @@
+use crate::domain::{Decision, SortBy};
+use crate::preview::{ImageCommand, ScrollCommand};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::{Deserialize, Serialize};
+use std::sync::{OnceLock, PoisonError, RwLock};
 
-/// Represents the result of handling a key event
+/// Letter keys of a QWERTY keyboard, in the order of the layout tables below
+const QWERTY_LETTERS: &str = "qwertyuiopasdfghjklzxcvbnmQWERTYUIOPASDFGHJKLZXCVBNM";
+/// What the keys of `QWERTY_LETTERS` type on a French AZERTY keyboard
+const AZERTY_LETTERS: &str = "azertyuiopqsdfghjklwxcvbn,AZERTYUIOPQSDFGHJKLWXCVBN?";
+/// What the keys of `QWERTY_LETTERS` type on a Dvorak keyboard
+const DVORAK_LETTERS: &str = "',.pyfgcrlaoeuidhtn;qjkxbm\"<>PYFGCRLAOEUIDHTN:QJKXBM";
+/// The unshifted digit row of an AZERTY keyboard, 1 to 9
+const AZERTY_DIGITS: &str = "&é\"'(-è_ç";
+
+static LAYOUT: RwLock<KeyLayout> = RwLock::new(KeyLayout::Letters);
+
+/// A command for `App::update`, usually produced from a key event
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KeyAction {
     /// Quit the application
@@ -9,58 +24,598 @@ pub enum KeyAction {
     Keep,
     /// Mark current file to trash
     Trash,
+    /// Mark current file as unsure during triage
+    Unsure,
+    /// Skip the current file for now, to review it again at the end
+    Skip,
     /// Confirm trash action
     ConfirmTrash,
+    /// Trash only the current file, leaving its sidecars
+    TrashAlone,
     /// Cancel trash action
     CancelTrash,
     /// Move to next file
     Next,
     /// Move to previous file
     Previous,
+    /// Jump to a queue position (clamped to the last file)
+    Goto(usize),
+    /// Re-sort the remaining queue
+    SetSort(SortBy),
+    /// Step to the next order for the files still undecided
+    CycleSort,
+    /// Decide every undecided file in the queue at once, as one undoable batch
+    BulkDecide(Decision),
+    /// Toggle moving to the next file after a decision
+    ToggleAutoAdvance,
+    /// Toggle focus mode (only the file name, preview and swipe hints)
+    ToggleFocus,
+    /// Show or hide the queue list beside the preview
+    ToggleQueuePanel,
+    /// Open the filter inspector: what's hiding files, and switches to turn it off
+    Inspector,
+    /// Start recording a macro, or stop and save it
+    RecordMacro,
+    /// Replay the recorded macro
+    PlayMacro,
+    /// Push current file to the end of the queue
+    SendToBack,
+    /// Move current file to the front of the undecided queue
+    PinToFront,
+    /// Keep the current file's preview beside the others, or unpin it
+    PinPreview,
+    /// Select the undecided files whose names follow the current one's pattern
+    SelectSimilar,
+    /// Search the text files in the queue and narrow it to the matches
+    Search,
+    /// Open the filter bar, which narrows the queue as you type
+    Filter,
+    /// Write the decisions so far to an HTML report for sign-off
+    ExportReport,
+    /// Show the scanned files by age and by type
+    Overview,
+    /// Show everything known about the current file's failed preview
+    PreviewDetails,
+    /// Write the failed preview's details to a file for a bug report
+    SaveDiagnostics,
+    /// Copy the paths of the files with this decision to the clipboard (summary screen)
+    CopyPaths(Decision),
+    /// Answer to a move whose destination name is taken
+    ResolveConflict(ConflictChoice),
     /// Undo last decision
     Undo,
     /// Toggle help overlay
     Help,
     /// Open current file in editor/application
     Open,
+    /// Suspend the process (job control), restoring the terminal first
+    Suspend,
+    /// Drop to a subshell in the scanned directory
+    Shell,
+    /// Show or hide the lines redacted from the current file's preview
+    RevealSensitive,
+    /// Blank the screen until a key is pressed (or the session passphrase typed)
+    Pause,
+    /// Pause, asking for a session passphrase first if none is set yet
+    Lock,
+    /// Zoom or pan the image preview
+    Image(ImageCommand),
+    /// Scroll the preview pane
+    ScrollPreview(ScrollCommand),
+    /// Move the current file to the quick-move target with this digit (1-9)
+    MoveTo(u8),
+    /// Accept the highlighted option (setup wizard)
+    Select,
+    /// Dismiss the current screen (welcome, summary, apply results)
+    Continue,
+    /// A key typed into the help overlay's search
+    HelpInput(KeyEvent),
+    /// A key typed into the content search prompt
+    SearchInput(KeyEvent),
+    /// A key typed into the filter bar
+    FilterInput(KeyEvent),
+    /// A key pressed on the pause screen
+    PauseInput(KeyEvent),
     /// No action
     None,
 }
 
-/// Maps keyboard events to actions
-pub fn handle_key_event(key: KeyEvent) -> KeyAction {
-    match (key.code, key.modifiers) {
-        // Quit: q or Ctrl+C
-        (KeyCode::Char('q'), KeyModifiers::NONE) => KeyAction::Quit,
-        (KeyCode::Char('c'), KeyModifiers::CONTROL) => KeyAction::Quit,
-        (KeyCode::Esc, KeyModifiers::NONE) => KeyAction::Quit,
+/// What to do when a move finds a file of the same name in the target directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictChoice {
+    /// Move it under the first free `name (N).ext`
+    Rename,
+    /// Trash the existing file, in one undoable batch with the move
+    Overwrite,
+    /// Leave the file undecided
+    Skip,
+    /// Show both files side by side before choosing
+    Compare,
+}
+
+/// A key and the action it triggers while browsing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+    pub action: KeyAction,
+}
+
+const fn bind(code: KeyCode, modifiers: KeyModifiers, action: KeyAction) -> KeyBinding {
+    KeyBinding {
+        code,
+        modifiers,
+        action,
+    }
+}
 
-        // Keep: Right arrow or k
-        (KeyCode::Right, KeyModifiers::NONE) => KeyAction::Keep,
-        (KeyCode::Char('k'), KeyModifiers::NONE) => KeyAction::Keep,
+/// Browsing key bindings, in the order the help overlay lists them
+pub const KEYMAP: &[KeyBinding] = &[
+    // Keep: Right arrow or k
+    bind(KeyCode::Right, KeyModifiers::NONE, KeyAction::Keep),
+    bind(KeyCode::Char('k'), KeyModifiers::NONE, KeyAction::Keep),
+    // Trash: Left arrow or t
+    bind(KeyCode::Left, KeyModifiers::NONE, KeyAction::Trash),
+    bind(KeyCode::Char('t'), KeyModifiers::NONE, KeyAction::Trash),
+    // Navigation
+    bind(KeyCode::Down, KeyModifiers::NONE, KeyAction::Next),
+    bind(KeyCode::Char('j'), KeyModifiers::NONE, KeyAction::Next),
+    bind(KeyCode::Up, KeyModifiers::NONE, KeyAction::Previous),
+    bind(KeyCode::Char('i'), KeyModifiers::NONE, KeyAction::Previous),
+    bind(KeyCode::Home, KeyModifiers::NONE, KeyAction::Goto(0)),
+    bind(
+        KeyCode::End,
+        KeyModifiers::NONE,
+        KeyAction::Goto(usize::MAX),
+    ),
+    // Auto-advance toggle: a
+    bind(
+        KeyCode::Char('a'),
+        KeyModifiers::NONE,
+        KeyAction::ToggleAutoAdvance,
+    ),
+    // Focus mode: Z (z zooms images)
+    bind(
+        KeyCode::Char('Z'),
+        KeyModifiers::NONE,
+        KeyAction::ToggleFocus,
+    ),
+    // Queue panel: L
+    bind(
+        KeyCode::Char('L'),
+        KeyModifiers::NONE,
+        KeyAction::ToggleQueuePanel,
+    ),
+    // Filter inspector: I
+    bind(KeyCode::Char('I'), KeyModifiers::NONE, KeyAction::Inspector),
+    // Macros: M records, @ replays
+    bind(
+        KeyCode::Char('M'),
+        KeyModifiers::NONE,
+        KeyAction::RecordMacro,
+    ),
+    bind(KeyCode::Char('@'), KeyModifiers::NONE, KeyAction::PlayMacro),
+    // Queue reordering: b sends to back, p pins to front
+    bind(
+        KeyCode::Char('b'),
+        KeyModifiers::NONE,
+        KeyAction::SendToBack,
+    ),
+    bind(
+        KeyCode::Char('p'),
+        KeyModifiers::NONE,
+        KeyAction::PinToFront,
+    ),
+    // Pin the preview for comparison: P
+    bind(
+        KeyCode::Char('P'),
+        KeyModifiers::NONE,
+        KeyAction::PinPreview,
+    ),
+    // Select all like this: *
+    bind(
+        KeyCode::Char('*'),
+        KeyModifiers::NONE,
+        KeyAction::SelectSimilar,
+    ),
+    // Content search: /
+    bind(KeyCode::Char('/'), KeyModifiers::NONE, KeyAction::Search),
+    // Filter bar: f or F
+    bind(KeyCode::Char('f'), KeyModifiers::NONE, KeyAction::Filter),
+    bind(KeyCode::Char('F'), KeyModifiers::NONE, KeyAction::Filter),
+    // Unsure (triage): m
+    bind(KeyCode::Char('m'), KeyModifiers::NONE, KeyAction::Unsure),
+    // Skip for now: s
+    bind(KeyCode::Char('s'), KeyModifiers::NONE, KeyAction::Skip),
+    // Undo: u
+    bind(KeyCode::Char('u'), KeyModifiers::NONE, KeyAction::Undo),
+    // Open: o
+    bind(KeyCode::Char('o'), KeyModifiers::NONE, KeyAction::Open),
+    // Shell: !
+    bind(KeyCode::Char('!'), KeyModifiers::NONE, KeyAction::Shell),
+    // Reveal redacted preview lines: R
+    bind(
+        KeyCode::Char('R'),
+        KeyModifiers::NONE,
+        KeyAction::RevealSensitive,
+    ),
+    // Pause: h hides the screen, H locks it with a passphrase
+    bind(KeyCode::Char('h'), KeyModifiers::NONE, KeyAction::Pause),
+    bind(KeyCode::Char('H'), KeyModifiers::NONE, KeyAction::Lock),
+    // Export an HTML report: E
+    bind(
+        KeyCode::Char('E'),
+        KeyModifiers::NONE,
+        KeyAction::ExportReport,
+    ),
+    // Order of the files left: O
+    bind(KeyCode::Char('O'), KeyModifiers::NONE, KeyAction::CycleSort),
+    // Failed preview details: e
+    bind(
+        KeyCode::Char('e'),
+        KeyModifiers::NONE,
+        KeyAction::PreviewDetails,
+    ),
+    // Scan overview: D
+    bind(KeyCode::Char('D'), KeyModifiers::NONE, KeyAction::Overview),
+    // Quick-move targets from the config: 1-9
+    bind(KeyCode::Char('1'), KeyModifiers::NONE, KeyAction::MoveTo(1)),
+    bind(KeyCode::Char('2'), KeyModifiers::NONE, KeyAction::MoveTo(2)),
+    bind(KeyCode::Char('3'), KeyModifiers::NONE, KeyAction::MoveTo(3)),
+    bind(KeyCode::Char('4'), KeyModifiers::NONE, KeyAction::MoveTo(4)),
+    bind(KeyCode::Char('5'), KeyModifiers::NONE, KeyAction::MoveTo(5)),
+    bind(KeyCode::Char('6'), KeyModifiers::NONE, KeyAction::MoveTo(6)),
+    bind(KeyCode::Char('7'), KeyModifiers::NONE, KeyAction::MoveTo(7)),
+    bind(KeyCode::Char('8'), KeyModifiers::NONE, KeyAction::MoveTo(8)),
+    bind(KeyCode::Char('9'), KeyModifiers::NONE, KeyAction::MoveTo(9)),
+    // Image zoom: + / = in, - out, z cycles fit / fill / 100%
+    bind(
+        KeyCode::Char('+'),
+        KeyModifiers::NONE,
+        KeyAction::Image(ImageCommand::ZoomIn),
+    ),
+    bind(
+        KeyCode::Char('='),
+        KeyModifiers::NONE,
+        KeyAction::Image(ImageCommand::ZoomIn),
+    ),
+    bind(
+        KeyCode::Char('-'),
+        KeyModifiers::NONE,
+        KeyAction::Image(ImageCommand::ZoomOut),
+    ),
+    bind(
+        KeyCode::Char('z'),
+        KeyModifiers::NONE,
+        KeyAction::Image(ImageCommand::CycleFit),
+    ),
+    // Image pan: Shift+arrows
+    bind(
+        KeyCode::Left,
+        KeyModifiers::SHIFT,
+        KeyAction::Image(ImageCommand::PanLeft),
+    ),
+    bind(
+        KeyCode::Right,
+        KeyModifiers::SHIFT,
+        KeyAction::Image(ImageCommand::PanRight),
+    ),
+    bind(
+        KeyCode::Up,
+        KeyModifiers::SHIFT,
+        KeyAction::Image(ImageCommand::PanUp),
+    ),
+    bind(
+        KeyCode::Down,
+        KeyModifiers::SHIFT,
+        KeyAction::Image(ImageCommand::PanDown),
+    ),
+    // Preview scroll: PageUp / PageDown a page, Ctrl+U / Ctrl+D half a page
+    bind(
+        KeyCode::PageDown,
+        KeyModifiers::NONE,
+        KeyAction::ScrollPreview(ScrollCommand::PageDown),
+    ),
+    bind(
+        KeyCode::PageUp,
+        KeyModifiers::NONE,
+        KeyAction::ScrollPreview(ScrollCommand::PageUp),
+    ),
+    bind(
+        KeyCode::Char('d'),
+        KeyModifiers::CONTROL,
+        KeyAction::ScrollPreview(ScrollCommand::HalfPageDown),
+    ),
+    bind(
+        KeyCode::Char('u'),
+        KeyModifiers::CONTROL,
+        KeyAction::ScrollPreview(ScrollCommand::HalfPageUp),
+    ),
+    // Suspend: Ctrl+Z (raw mode swallows the terminal's own SIGTSTP)
+    bind(
+        KeyCode::Char('z'),
+        KeyModifiers::CONTROL,
+        KeyAction::Suspend,
+    ),
+    // Quit: q, Esc or Ctrl+C
+    bind(KeyCode::Char('q'), KeyModifiers::NONE, KeyAction::Quit),
+    bind(KeyCode::Esc, KeyModifiers::NONE, KeyAction::Quit),
+    bind(KeyCode::Char('c'), KeyModifiers::CONTROL, KeyAction::Quit),
+    // Help: ?
+    bind(KeyCode::Char('?'), KeyModifiers::NONE, KeyAction::Help),
+];
+
+/// Which keys the browsing bindings sit on (`"key_layout"` in the config)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyLayout {
+    /// Keys as printed, whatever the keyboard: `k` keeps, `t` trashes
+    #[default]
+    Letters,
+    /// The QWERTY positions on an AZERTY keyboard, plus the unshifted digit row
+    /// (`&`, `é`, `"`…) for the quick-move targets
+    Azerty,
+    /// The QWERTY positions on a Dvorak keyboard
+    Dvorak,
+}
 
-        // Trash: Left arrow or t
-        (KeyCode::Left, KeyModifiers::NONE) => KeyAction::Trash,
-        (KeyCode::Char('t'), KeyModifiers::NONE) => KeyAction::Trash,
+impl KeyLayout {
+    const ALL: [KeyLayout; 3] = [KeyLayout::Letters, KeyLayout::Azerty, KeyLayout::Dvorak];
+
+    /// What the key at the QWERTY position of `c` types on this layout, for letters
+    fn translate(self, c: char) -> Option<&'static str> {
+        let letters = match self {
+            KeyLayout::Letters => return None,
+            KeyLayout::Azerty => AZERTY_LETTERS,
+            KeyLayout::Dvorak => DVORAK_LETTERS,
+        };
+        let index = QWERTY_LETTERS.find(c)?;
+        Some(&letters[index..index + 1])
+    }
 
-        // Navigation
-        (KeyCode::Down, KeyModifiers::NONE) => KeyAction::Next,
-        (KeyCode::Up, KeyModifiers::NONE) => KeyAction::Previous,
-        (KeyCode::Char('j'), KeyModifiers::NONE) => KeyAction::Next,
-        (KeyCode::Char('i'), KeyModifiers::NONE) => KeyAction::Previous,
+    /// Another key for the binding of `c`, typed at the same position without Shift
+    fn alternative(self, c: char) -> Option<char> {
+        match (self, c.to_digit(10)) {
+            (KeyLayout::Azerty, Some(digit @ 1..=9)) => {
+                AZERTY_DIGITS.chars().nth(digit as usize - 1)
+            }
+            _ => None,
+        }
+    }
+}
 
-        // Undo: u or Ctrl+Z
-        (KeyCode::Char('u'), KeyModifiers::NONE) => KeyAction::Undo,
-        (KeyCode::Char('z'), KeyModifiers::CONTROL) => KeyAction::Undo,
+/// Uses `layout` for the browsing keys from now on
+pub fn set_layout(layout: KeyLayout) {
+    *LAYOUT.write().unwrap_or_else(PoisonError::into_inner) = layout;
+}
 
-        // Help: ?
-        (KeyCode::Char('?'), KeyModifiers::NONE) => KeyAction::Help,
+/// The layout the browsing keys follow
+pub fn layout() -> KeyLayout {
+    *LAYOUT.read().unwrap_or_else(PoisonError::into_inner)
+}
 
-        // Open: o
-        (KeyCode::Char('o'), KeyModifiers::NONE) => KeyAction::Open,
+/// The browsing bindings of the current layout
+pub fn keymap() -> &'static [KeyBinding] {
+    keymap_for(layout())
+}
 
-        _ => KeyAction::None,
+/// `KEYMAP` moved to `layout`'s positions, built once per layout.
+///
+/// Only unmodified letters move. A letter whose new key is already bound to
+/// something else (`M` would become AZERTY's `?`) keeps its printed key, and
+/// digit-row alternatives are left out where their key is taken.
+pub fn keymap_for(layout: KeyLayout) -> &'static [KeyBinding] {
+    static KEYMAPS: [OnceLock<Vec<KeyBinding>>; 3] = [const { OnceLock::new() }; 3];
+    let index = KeyLayout::ALL
+        .iter()
+        .position(|l| *l == layout)
+        .unwrap_or(0);
+    KEYMAPS[index].get_or_init(|| {
+        let key_of = |binding: &KeyBinding| match (binding.code, binding.modifiers) {
+            (KeyCode::Char(c), KeyModifiers::NONE) => layout
+                .translate(c)
+                .and_then(|key| key.chars().next())
+                .map_or(binding.code, KeyCode::Char),
+            _ => binding.code,
+        };
+        let moved: Vec<KeyCode> = KEYMAP.iter().map(key_of).collect();
+        let taken = |code: KeyCode, modifiers: KeyModifiers, except: usize| {
+            KEYMAP
+                .iter()
+                .zip(&moved)
+                .enumerate()
+                .any(|(i, (b, m))| i != except && *m == code && b.modifiers == modifiers)
+        };
+        let mut keymap = Vec::with_capacity(KEYMAP.len());
+        for (i, binding) in KEYMAP.iter().enumerate() {
+            let code = if taken(moved[i], binding.modifiers, i) {
+                binding.code
+            } else {
+                moved[i]
+            };
+            keymap.push(bind(code, binding.modifiers, binding.action.clone()));
+            let alternative = match (binding.code, binding.modifiers) {
+                (KeyCode::Char(c), KeyModifiers::NONE) => layout.alternative(c),
+                _ => None,
+            };
+            if let Some(c) = alternative.filter(|c| !taken(KeyCode::Char(*c), binding.modifiers, i))
+            {
+                keymap.push(bind(
+                    KeyCode::Char(c),
+                    binding.modifiers,
+                    binding.action.clone(),
+                ));
+            }
+        }
+        keymap
+    })
+}
+
+/// The key to show in a hint for the binding printed `c` on QWERTY
+pub fn key_hint(c: &'static str) -> &'static str {
+    c.chars()
+        .next()
+        .and_then(|first| layout().translate(first))
+        .unwrap_or(c)
+}
+
+impl KeyAction {
+    /// What the action does, as listed in the help overlay
+    pub fn description(&self) -> &'static str {
+        match self {
+            KeyAction::Quit => "Quit",
+            KeyAction::Keep => "Keep file",
+            KeyAction::Trash => "Trash file",
+            KeyAction::Unsure => "Unsure (triage)",
+            KeyAction::Skip => "Skip for now (reviewed at the end)",
+            KeyAction::ConfirmTrash => "Confirm trash",
+            KeyAction::TrashAlone => "Trash without sidecars",
+            KeyAction::CancelTrash => "Cancel trash",
+            KeyAction::Next => "Next file",
+            KeyAction::Previous => "Previous file",
+            KeyAction::Goto(0) => "First file",
+            KeyAction::Goto(usize::MAX) => "Last file",
+            KeyAction::Goto(_) => "Go to file",
+            KeyAction::SetSort(_) => "Sort queue",
+            KeyAction::CycleSort => "Change the order of the files left",
+            KeyAction::BulkDecide(Decision::Keep) => "Keep all remaining",
+            KeyAction::BulkDecide(Decision::Trash) => "Trash all remaining",
+            KeyAction::BulkDecide(Decision::Move(_)) => "Move all remaining",
+            KeyAction::BulkDecide(Decision::Skip) => "Skip all remaining",
+            KeyAction::ToggleAutoAdvance => "Toggle auto-advance",
+            KeyAction::ToggleFocus => "Focus mode (hide everything but the file)",
+            KeyAction::ToggleQueuePanel => "Show / hide the queue panel",
+            KeyAction::Inspector => "Show active filters and turn them off",
+            KeyAction::RecordMacro => "Record macro (again to stop)",
+            KeyAction::PlayMacro => "Replay macro",
+            KeyAction::SendToBack => "Decide later",
+            KeyAction::PinToFront => "Pin to come up next",
+            KeyAction::PinPreview => "Pin preview to compare (again to unpin)",
+            KeyAction::SelectSimilar => "Select all like this",
+            KeyAction::Search => "Search file contents",
+            KeyAction::Filter => "Filter queue (name, type:, ext:, size>)",
+            KeyAction::Undo => "Undo",
+            KeyAction::Help => "Toggle help",
+            KeyAction::Open => "Open file in editor",
+            KeyAction::Suspend => "Suspend",
+            KeyAction::Shell => "Shell in directory",
+            KeyAction::RevealSensitive => "Reveal / redact sensitive preview lines",
+            KeyAction::Pause => "Pause and hide the screen",
+            KeyAction::Lock => "Pause, locked with a session passphrase",
+            KeyAction::ExportReport => "Export HTML report",
+            KeyAction::PreviewDetails => "Details of a failed preview",
+            KeyAction::SaveDiagnostics => "Save preview diagnostics to a file",
+            KeyAction::Overview => "Scan overview (file ages and types)",
+            KeyAction::CopyPaths(Decision::Keep) => "Copy kept paths",
+            KeyAction::CopyPaths(Decision::Trash) => "Copy trashed paths",
+            KeyAction::CopyPaths(Decision::Move(_)) => "Copy moved paths",
+            KeyAction::CopyPaths(Decision::Skip) => "Copy skipped paths",
+            KeyAction::ResolveConflict(ConflictChoice::Rename) => "Move under a free name",
+            KeyAction::ResolveConflict(ConflictChoice::Overwrite) => "Replace the existing file",
+            KeyAction::ResolveConflict(ConflictChoice::Skip) => "Skip the move",
+            KeyAction::ResolveConflict(ConflictChoice::Compare) => "Compare both files",
+            KeyAction::Image(ImageCommand::ZoomIn) => "Zoom in (images)",
+            KeyAction::Image(ImageCommand::ZoomOut) => "Zoom out (images)",
+            KeyAction::Image(ImageCommand::CycleFit) => "Fit / fill / 100% (images)",
+            KeyAction::Image(_) => "Pan zoomed image",
+            KeyAction::ScrollPreview(ScrollCommand::PageDown) => "Scroll preview down a page",
+            KeyAction::ScrollPreview(ScrollCommand::PageUp) => "Scroll preview up a page",
+            KeyAction::ScrollPreview(ScrollCommand::HalfPageDown) => {
+                "Scroll preview down half a page"
+            }
+            KeyAction::ScrollPreview(ScrollCommand::HalfPageUp) => "Scroll preview up half a page",
+            KeyAction::MoveTo(_) => "Move to quick-move target",
+            KeyAction::Select => "Select",
+            KeyAction::Continue => "Continue",
+            KeyAction::HelpInput(_)
+            | KeyAction::SearchInput(_)
+            | KeyAction::FilterInput(_)
+            | KeyAction::PauseInput(_)
+            | KeyAction::None => "",
+        }
+    }
+
+    /// How the action is stored as a macro step, e.g. "keep" or "move:1"; None for
+    /// actions a macro can't contain
+    pub fn macro_step(&self) -> Option<String> {
+        let step = match self {
+            KeyAction::Keep => "keep",
+            KeyAction::Trash => "trash",
+            KeyAction::Unsure => "unsure",
+            KeyAction::Skip => "skip",
+            KeyAction::Next => "next",
+            KeyAction::Previous => "previous",
+            KeyAction::SendToBack => "back",
+            KeyAction::PinToFront => "pin",
+            KeyAction::Undo => "undo",
+            KeyAction::MoveTo(slot) => return Some(format!("move:{}", slot)),
+            _ => return None,
+        };
+        Some(step.to_string())
     }
+
+    /// Parses a macro step written by `macro_step`
+    pub fn from_macro_step(step: &str) -> Option<Self> {
+        let action = match step.trim() {
+            "keep" => KeyAction::Keep,
+            "trash" => KeyAction::Trash,
+            "unsure" => KeyAction::Unsure,
+            "skip" => KeyAction::Skip,
+            "next" => KeyAction::Next,
+            "previous" => KeyAction::Previous,
+            "back" => KeyAction::SendToBack,
+            "pin" => KeyAction::PinToFront,
+            "undo" => KeyAction::Undo,
+            other => {
+                let slot: u8 = other.strip_prefix("move:")?.parse().ok()?;
+                if !(1..=9).contains(&slot) {
+                    return None;
+                }
+                KeyAction::MoveTo(slot)
+            }
+        };
+        Some(action)
+    }
+}
+
+/// Display form of a key, e.g. "→", "Esc" or "Ctrl+Z"
+pub fn key_label(code: KeyCode, modifiers: KeyModifiers) -> String {
+    let key = match code {
+        KeyCode::Left => "←".to_string(),
+        KeyCode::Right => "→".to_string(),
+        KeyCode::Up => "↑".to_string(),
+        KeyCode::Down => "↓".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+            c.to_ascii_uppercase().to_string()
+        }
+        KeyCode::Char(c) => c.to_string(),
+        other => format!("{:?}", other),
+    };
+    if modifiers.contains(KeyModifiers::CONTROL) {
+        format!("Ctrl+{}", key)
+    } else if modifiers.contains(KeyModifiers::SHIFT) {
+        format!("Shift+{}", key)
+    } else {
+        key
+    }
+}
+
+/// Maps keyboard events to actions using the keymap of the current layout.
+///
+/// Shift is ignored on characters: terminals report `H` as `H` with Shift held,
+/// and the character alone already says which key it was.
+pub fn handle_key_event(key: KeyEvent) -> KeyAction {
+    let modifiers = match key.code {
+        KeyCode::Char(_) => key.modifiers.difference(KeyModifiers::SHIFT),
+        _ => key.modifiers,
+    };
+    keymap()
+        .iter()
+        .find(|binding| binding.code == key.code && binding.modifiers == modifiers)
+        .map(|binding| binding.action.clone())
+        .unwrap_or(KeyAction::None)
 }
 
 /// Maps keyboard events to confirmation actions
@@ -71,6 +626,8 @@ pub fn handle_confirm_input(key: KeyEvent) -> KeyAction {
         (KeyCode::Char('y'), KeyModifiers::NONE) => KeyAction::ConfirmTrash,
         (KeyCode::Char('Y'), KeyModifiers::NONE) => KeyAction::ConfirmTrash,
         (KeyCode::Enter, KeyModifiers::NONE) => KeyAction::ConfirmTrash,
+        // Only this file, without its sidecars: o
+        (KeyCode::Char('o'), KeyModifiers::NONE) => KeyAction::TrashAlone,
 
         // Cancel: n or Esc
         (KeyCode::Char('n'), KeyModifiers::NONE) => KeyAction::CancelTrash,
@@ -81,10 +638,117 @@ pub fn handle_confirm_input(key: KeyEvent) -> KeyAction {
     }
 }
 
+/// Keys of the move conflict prompt: r renames, o overwrites, c compares, s / Esc skips
+pub fn handle_conflict_input(key: KeyEvent) -> KeyAction {
+    let choice = match key.code {
+        KeyCode::Char('r') | KeyCode::Enter => ConflictChoice::Rename,
+        KeyCode::Char('o') => ConflictChoice::Overwrite,
+        KeyCode::Char('c') => ConflictChoice::Compare,
+        KeyCode::Char('s') | KeyCode::Esc => ConflictChoice::Skip,
+        _ => return KeyAction::None,
+    };
+    KeyAction::ResolveConflict(choice)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_keymap_has_no_duplicate_keys() {
+        for layout in KeyLayout::ALL {
+            let keymap = keymap_for(layout);
+            for (i, binding) in keymap.iter().enumerate() {
+                assert!(
+                    !keymap[..i]
+                        .iter()
+                        .any(|b| b.code == binding.code && b.modifiers == binding.modifiers),
+                    "{} is bound twice in {:?}",
+                    key_label(binding.code, binding.modifiers),
+                    layout
+                );
+                assert!(!binding.action.description().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn test_keymap_for_layout_positions() {
+        let action = |layout, c| {
+            keymap_for(layout)
+                .iter()
+                .find(|b| b.code == KeyCode::Char(c) && b.modifiers == KeyModifiers::NONE)
+                .map(|b| b.action.clone())
+        };
+        assert_eq!(keymap_for(KeyLayout::Letters), KEYMAP);
+
+        // AZERTY swaps a / q, keeps M since its spot types ? (help), and adds
+        // the unshifted digit row except where the key is taken (- zooms out)
+        assert_eq!(action(KeyLayout::Azerty, 'a'), Some(KeyAction::Quit));
+        assert_eq!(
+            action(KeyLayout::Azerty, 'q'),
+            Some(KeyAction::ToggleAutoAdvance)
+        );
+        assert_eq!(action(KeyLayout::Azerty, 'k'), Some(KeyAction::Keep));
+        assert_eq!(action(KeyLayout::Azerty, ','), Some(KeyAction::Unsure));
+        assert_eq!(action(KeyLayout::Azerty, 'M'), Some(KeyAction::RecordMacro));
+        assert_eq!(action(KeyLayout::Azerty, '?'), Some(KeyAction::Help));
+        assert_eq!(action(KeyLayout::Azerty, 'é'), Some(KeyAction::MoveTo(2)));
+        assert_eq!(action(KeyLayout::Azerty, '2'), Some(KeyAction::MoveTo(2)));
+        assert_eq!(
+            action(KeyLayout::Azerty, '-'),
+            Some(KeyAction::Image(ImageCommand::ZoomOut))
+        );
+
+        // Dvorak keeps the QWERTY hand positions: k's key types t
+        assert_eq!(action(KeyLayout::Dvorak, 't'), Some(KeyAction::Keep));
+        assert_eq!(action(KeyLayout::Dvorak, 'y'), Some(KeyAction::Trash));
+        assert_eq!(action(KeyLayout::Dvorak, 'h'), Some(KeyAction::Next));
+        assert_eq!(action(KeyLayout::Dvorak, 'c'), Some(KeyAction::Previous));
+        assert_eq!(action(KeyLayout::Dvorak, 'k'), None);
+        assert_eq!(action(KeyLayout::Dvorak, '1'), Some(KeyAction::MoveTo(1)));
+    }
+
+    #[test]
+    fn test_key_label() {
+        assert_eq!(key_label(KeyCode::Right, KeyModifiers::NONE), "→");
+        assert_eq!(
+            key_label(KeyCode::Char('z'), KeyModifiers::CONTROL),
+            "Ctrl+Z"
+        );
+        assert_eq!(key_label(KeyCode::Char('k'), KeyModifiers::NONE), "k");
+        assert_eq!(key_label(KeyCode::Left, KeyModifiers::SHIFT), "Shift+←");
+    }
+
+    #[test]
+    fn test_key_image_zoom_and_pan() {
+        let key = |code, modifiers| handle_key_event(KeyEvent::new(code, modifiers));
+        assert_eq!(
+            key(KeyCode::Char('+'), KeyModifiers::NONE),
+            KeyAction::Image(ImageCommand::ZoomIn)
+        );
+        assert_eq!(
+            key(KeyCode::Char('z'), KeyModifiers::NONE),
+            KeyAction::Image(ImageCommand::CycleFit)
+        );
+        assert_eq!(
+            key(KeyCode::Right, KeyModifiers::SHIFT),
+            KeyAction::Image(ImageCommand::PanRight)
+        );
+        // Plain arrows still decide
+        assert_eq!(key(KeyCode::Right, KeyModifiers::NONE), KeyAction::Keep);
+
+        assert_eq!(
+            key(KeyCode::PageDown, KeyModifiers::NONE),
+            KeyAction::ScrollPreview(ScrollCommand::PageDown)
+        );
+        assert_eq!(
+            key(KeyCode::Char('u'), KeyModifiers::CONTROL),
+            KeyAction::ScrollPreview(ScrollCommand::HalfPageUp)
+        );
+        assert_eq!(key(KeyCode::Char('u'), KeyModifiers::NONE), KeyAction::Undo);
+    }
+
     #[test]
     fn test_key_quit() {
         let key = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
@@ -128,15 +792,139 @@ mod tests {
 
         let key = KeyEvent::new(KeyCode::Char('i'), KeyModifiers::NONE);
         assert_eq!(handle_key_event(key), KeyAction::Previous);
+
+        let key = KeyEvent::new(KeyCode::Home, KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::Goto(0));
+
+        let key = KeyEvent::new(KeyCode::End, KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::Goto(usize::MAX));
+    }
+
+    #[test]
+    fn test_key_toggle_auto_advance() {
+        let key = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::ToggleAutoAdvance);
+
+        let key = KeyEvent::new(KeyCode::Char('Z'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::ToggleFocus);
+        // What a terminal sends for Shift+z
+        let key = KeyEvent::new(KeyCode::Char('Z'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key_event(key), KeyAction::ToggleFocus);
+
+        let key = KeyEvent::new(KeyCode::Char('L'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::ToggleQueuePanel);
+        let key = KeyEvent::new(KeyCode::Char('L'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key_event(key), KeyAction::ToggleQueuePanel);
+
+        let key = KeyEvent::new(KeyCode::Char('I'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::Inspector);
+        let key = KeyEvent::new(KeyCode::Char('I'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key_event(key), KeyAction::Inspector);
+
+        let key = KeyEvent::new(KeyCode::Char('P'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::PinPreview);
+        let key = KeyEvent::new(KeyCode::Char('P'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key_event(key), KeyAction::PinPreview);
+
+        let key = KeyEvent::new(KeyCode::Char('M'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::RecordMacro);
+        let key = KeyEvent::new(KeyCode::Char('M'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key_event(key), KeyAction::RecordMacro);
+        let key = KeyEvent::new(KeyCode::Char('@'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::PlayMacro);
+    }
+
+    #[test]
+    fn test_key_queue_reordering() {
+        let key = KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::SendToBack);
+
+        let key = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::PinToFront);
+    }
+
+    #[test]
+    fn test_macro_steps_round_trip() {
+        for action in [
+            KeyAction::Keep,
+            KeyAction::Trash,
+            KeyAction::Unsure,
+            KeyAction::Skip,
+            KeyAction::Next,
+            KeyAction::Previous,
+            KeyAction::SendToBack,
+            KeyAction::PinToFront,
+            KeyAction::Undo,
+            KeyAction::MoveTo(3),
+        ] {
+            let step = action.macro_step().unwrap();
+            assert_eq!(KeyAction::from_macro_step(&step), Some(action));
+        }
+        assert_eq!(KeyAction::MoveTo(2).macro_step().as_deref(), Some("move:2"));
+        assert_eq!(KeyAction::Quit.macro_step(), None);
+        assert_eq!(KeyAction::RecordMacro.macro_step(), None);
+        for step in ["", "quit", "move:", "move:0", "move:10", "move:x"] {
+            assert_eq!(KeyAction::from_macro_step(step), None, "{}", step);
+        }
+    }
+
+    #[test]
+    fn test_key_unsure() {
+        let key = KeyEvent::new(KeyCode::Char('m'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::Unsure);
+    }
+
+    #[test]
+    fn test_key_skip() {
+        let key = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::Skip);
     }
 
     #[test]
     fn test_key_undo() {
         let key = KeyEvent::new(KeyCode::Char('u'), KeyModifiers::NONE);
         assert_eq!(handle_key_event(key), KeyAction::Undo);
+    }
 
+    #[test]
+    fn test_key_pause_and_lock() {
+        let key = KeyEvent::new(KeyCode::Char('h'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::Pause);
+
+        let key = KeyEvent::new(KeyCode::Char('H'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key_event(key), KeyAction::Lock);
+    }
+
+    #[test]
+    fn test_key_overview() {
+        let key = KeyEvent::new(KeyCode::Char('D'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key_event(key), KeyAction::Overview);
+    }
+
+    #[test]
+    fn test_key_reveal_sensitive() {
+        let key = KeyEvent::new(KeyCode::Char('R'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key_event(key), KeyAction::RevealSensitive);
+    }
+
+    #[test]
+    fn test_key_cycle_sort() {
+        let key = KeyEvent::new(KeyCode::Char('O'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key_event(key), KeyAction::CycleSort);
+    }
+
+    #[test]
+    fn test_key_suspend_and_shell() {
         let key = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
-        assert_eq!(handle_key_event(key), KeyAction::Undo);
+        assert_eq!(handle_key_event(key), KeyAction::Suspend);
+
+        let key = KeyEvent::new(KeyCode::Char('!'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::Shell);
+
+        let key = KeyEvent::new(KeyCode::Char('E'), KeyModifiers::NONE);
+        assert_eq!(handle_key_event(key), KeyAction::ExportReport);
+        let key = KeyEvent::new(KeyCode::Char('E'), KeyModifiers::SHIFT);
+        assert_eq!(handle_key_event(key), KeyAction::ExportReport);
     }
 
     #[test]
@@ -170,6 +958,10 @@ mod tests {
         // Test Enter key
         let key = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
         assert_eq!(handle_confirm_input(key), KeyAction::ConfirmTrash);
+
+        // o leaves the sidecars out
+        let key = KeyEvent::new(KeyCode::Char('o'), KeyModifiers::NONE);
+        assert_eq!(handle_confirm_input(key), KeyAction::TrashAlone);
     }
 
     #[test]
@@ -196,4 +988,20 @@ mod tests {
         let key = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
         assert_eq!(handle_confirm_input(key), KeyAction::None);
     }
+    #[test]
+    fn test_conflict_keys() {
+        let press = |code| handle_conflict_input(KeyEvent::new(code, KeyModifiers::NONE));
+        let cases = [
+            (KeyCode::Char('r'), ConflictChoice::Rename),
+            (KeyCode::Enter, ConflictChoice::Rename),
+            (KeyCode::Char('o'), ConflictChoice::Overwrite),
+            (KeyCode::Char('c'), ConflictChoice::Compare),
+            (KeyCode::Char('s'), ConflictChoice::Skip),
+            (KeyCode::Esc, ConflictChoice::Skip),
+        ];
+        for (code, choice) in cases {
+            assert_eq!(press(code), KeyAction::ResolveConflict(choice));
+        }
+        assert_eq!(press(KeyCode::Char('q')), KeyAction::None);
+    }
 }
@@ -1,23 +1,74 @@
 // TUI module for rendering the terminal interface
+pub mod app;
 pub mod colors;
+pub mod glyphs;
+pub mod help;
 pub mod helpers;
 pub mod input;
+pub mod session;
 
 // Re-exports
+pub use app::{App, Effect};
 pub use colors::*;
-pub use helpers::{calculate_progress, format_file_size};
-pub use input::{handle_confirm_input, handle_key_event, KeyAction};
+pub use help::HelpState;
+pub use helpers::{
+    calculate_progress, current_size_label, display_name, display_width, format_file_size,
+    sanitize_for_display, sanitize_preview_line, truncate_middle,
+};
+pub use input::{handle_confirm_input, handle_conflict_input, handle_key_event, KeyAction};
 
 use crate::async_preview::{PreviewState, SyncPreviewManager};
-use crate::domain::{AppState, DecisionStatistics};
+use crate::domain::{
+    reclaimable_bytes, AppState, ApplyJob, ApplyOutcome, Decision, DecisionStatistics,
+    DefaultDecision, FileEntry, FileType, NamePattern, QueueTally, ReviewPhase, SessionGoal,
+};
 use crate::preview;
+use crate::sensitive::{self, Sensitivity};
 use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, BorderType, Borders, Clear, Gauge, Paragraph, Wrap},
+    widgets::{Bar, BarChart, BarGroup, Block, BorderType, Borders, Clear, Gauge, Paragraph, Wrap},
     Frame,
 };
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Header badge for names containing bidi control characters
+const BIDI_WARNING: &str = " ⚠ RTL override in name ";
+
+/// Header badge for names that are not valid UTF-8 and are shown lossily
+const NON_UTF8_BADGE: &str = " [non-UTF-8 name] ";
+
+/// Header badge for cloud placeholders, whose data is only downloaded when opened
+const PLACEHOLDER_BADGE: &str = " [online-only: trashing frees nothing here] ";
+
+/// Header badge for files already looked at but not decided on
+const SEEN_BADGE: &str = " [seen] ";
+
+/// Header badge shown while keep/trash stays on the current file
+const AUTO_ADVANCE_OFF_BADGE: &str = " [auto-advance off] ";
+
+/// Header badge for files the desktop has no record of ever opening
+const NEVER_OPENED_BADGE: &str = " [never opened] ";
+
+/// Header badge for files the current user can't move to the trash
+const CANNOT_DELETE_PREFIX: &str = " ⚠ needs sudo: ";
+
+/// Header badge for file types whose trash always needs confirming
+const ALWAYS_ASK_BADGE: &str = " [always ask] ";
+
+/// Header badge for files background apply already moved (undo can't bring them back)
+const IN_SYSTEM_TRASH_BADGE: &str = " [in system trash] ";
+
+/// Header badge for the metadata-only first pass
+const TRIAGE_BADGE: &str = " [triage] ";
+
+/// Header badge while going back over skipped files
+const SKIPPED_PASS_BADGE: &str = " [reviewing skipped] ";
+
+/// Header badge for files marked unsure during triage
+const UNSURE_BADGE: &str = " [unsure] ";
 
 /// UI view state
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -32,6 +83,38 @@ pub enum ViewState {
     ConfirmTrash,
     /// Welcome screen shown on first launch
     Welcome,
+    /// Prompt shown once the session goal is met
+    GoalReached,
+    /// Moving trashed files to the system trash, then the per-file results
+    Applying,
+    /// First-run setup questions, shown before the welcome screen
+    Setup,
+    /// Files matching the current one's name pattern, waiting for one decision
+    SelectSimilar,
+    /// Typing a content search, then waiting for it to finish
+    Search,
+    /// Editing the filter bar; the queue narrows with every key
+    Filter,
+    /// Bulk trash suggestions offered before the review starts (`--easy-wins`)
+    EasyWins,
+    /// The scanned files by age and by type
+    Overview,
+    /// A move found its name taken in the target directory
+    MoveConflict,
+    /// Fixes offered for kept files' names before applying (`--normalize-names`)
+    Renames,
+    /// The files about to be trashed, any of which can still be kept
+    Review,
+    /// Groups of identical files, keeping one of each (`--duplicates`)
+    Duplicates,
+    /// The screen is blanked until the session is resumed (`h` / `H`)
+    Paused,
+    /// Everything known about the current file's failed preview (`e`)
+    PreviewError,
+    /// A burst of decisions too quick to be deliberate, asking whether to undo it
+    RapidDecisions,
+    /// The sort and every filter hiding files, each of which can be turned off (`I`)
+    Inspector,
 }
 
 /// Renders the TUI (legacy, without async preview)
@@ -39,43 +122,200 @@ pub fn render(frame: &mut Frame, state: &AppState) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(4), // Header with progress
-            Constraint::Min(0),    // Content
-            Constraint::Length(3), // Footer
+            Constraint::Length(header_height(state)), // Header with progress
+            Constraint::Min(0),                       // Content
+            Constraint::Length(3),                    // Footer
         ])
         .split(frame.area());
 
     render_header_polished(frame, chunks[0], state);
-    render_content(frame, chunks[1], state);
-    render_footer_polished(frame, chunks[2]);
+    if state.phase == ReviewPhase::Triage {
+        render_triage_card(frame, chunks[1], state);
+    } else {
+        render_content(frame, chunks[1], state);
+    }
+    render_footer_polished(frame, chunks[2], &ViewState::Browsing, &state.move_targets);
 }
 
-/// Renders the TUI with async preview support
+/// Renders the TUI with async preview support; the footer lists the keys valid in `view`,
+/// `queue_panel` adds the queue list beside the preview and a `pinned` file's
+/// preview is shown to the left of the current one
 pub fn render_with_preview(
     frame: &mut Frame,
     state: &AppState,
     preview_manager: &mut SyncPreviewManager,
+    view: &ViewState,
+    queue_panel: bool,
+    pinned: Option<&FileEntry>,
 ) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(4), // Header with progress
-            Constraint::Min(0),    // Content
-            Constraint::Length(3), // Footer
+            Constraint::Length(header_height(state)), // Header with progress
+            Constraint::Min(0),                       // Content
+            Constraint::Length(3),                    // Footer
         ])
         .split(frame.area());
 
     render_header_polished(frame, chunks[0], state);
-    render_content_async(frame, chunks[1], state, preview_manager);
-    render_footer_polished(frame, chunks[2]);
+    let mut content = chunks[1];
+    if queue_panel && content.width >= QUEUE_PANEL_WIDTH * 2 {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Min(0), Constraint::Length(QUEUE_PANEL_WIDTH)])
+            .split(content);
+        content = columns[0];
+        render_queue_panel(frame, columns[1], state);
+    }
+    // Triage never touches the preview manager so nothing gets loaded
+    if state.phase == ReviewPhase::Triage {
+        render_triage_card(frame, content, state);
+    } else {
+        render_content_async(frame, content, state, preview_manager, pinned);
+    }
+    render_footer_polished(frame, chunks[2], view, &state.move_targets);
+}
+
+/// Width of the queue panel, borders included
+const QUEUE_PANEL_WIDTH: u16 = 32;
+
+/// Queue positions shown in a list of `rows` rows, keeping `position` roughly
+/// centred; O(1) however long the queue is
+fn queue_window(len: usize, position: usize, rows: usize) -> std::ops::Range<usize> {
+    let start = position
+        .saturating_sub(rows / 2)
+        .min(len.saturating_sub(rows));
+    start..(start + rows).min(len)
+}
+
+/// Renders the queue around the current file, building lines for the visible
+/// rows only so it stays fast with very long queues
+fn render_queue_panel(frame: &mut Frame, area: Rect, state: &AppState) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(BORDER_COLOR))
+        .title(format!(
+            " Queue {}/{} ",
+            (state.current_index + 1).min(state.queue.len()),
+            state.queue.len()
+        ));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let window = queue_window(
+        state.queue.len(),
+        state.current_index,
+        inner.height as usize,
+    );
+    let visible = &state.queue[window.clone()];
+    // Latest decision of each visible file, from one pass over the decisions
+    let mut decisions: HashMap<usize, &Decision> = HashMap::new();
+    for (index, decision) in state.decisions() {
+        if state.is_decided(*index) && visible.contains(index) {
+            decisions.insert(*index, decision);
+        }
+    }
+
+    let name_width = (inner.width as usize).saturating_sub(4);
+    let lines: Vec<Line> = window
+        .zip(visible)
+        .map(|(position, &index)| {
+            let (symbol, color) = match decisions.get(&index) {
+                Some(Decision::Keep) => ("✓", ACCENT_SECONDARY),
+                Some(Decision::Trash) => ("✗", ACCENT_PRIMARY),
+                Some(Decision::Move(_)) => ("→", ACCENT_HIGHLIGHT),
+                Some(Decision::Skip) => ("○", TEXT_SECONDARY),
+                None if state.is_seen(index) => ("·", TEXT_SECONDARY),
+                None => (" ", TEXT_SECONDARY),
+            };
+            let line = Line::from(vec![
+                Span::styled(format!(" {} ", symbol), Style::default().fg(color)),
+                Span::raw(display_name(&state.files[index].name, name_width)),
+            ]);
+            if position == state.current_index {
+                line.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                line
+            }
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(TEXT_PRIMARY)),
+        inner,
+    );
+}
+
+/// Renders focus mode: just the file name, its preview (or triage card) and the
+/// two swipe hints, with no header, progress bar or footer
+pub fn render_focus(frame: &mut Frame, state: &AppState, preview_manager: &mut SyncPreviewManager) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // File name
+            Constraint::Min(0),    // Preview
+            Constraint::Length(1), // Swipe hints
+        ])
+        .split(frame.area());
+
+    if let Some(file) = state.current_file() {
+        let name = Line::from(vec![
+            Span::styled(
+                sanitize_for_display(&file.name),
+                Style::default()
+                    .fg(TEXT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  {}", current_size_label(state)),
+                Style::default().fg(TEXT_SECONDARY),
+            ),
+        ]);
+        frame.render_widget(Paragraph::new(name).alignment(Alignment::Center), chunks[0]);
+    }
+    if state.phase == ReviewPhase::Triage {
+        render_triage_card(frame, chunks[1], state);
+    } else {
+        render_content_async(frame, chunks[1], state, preview_manager, None);
+    }
+
+    let hint = |text: &'static str, color| {
+        Span::styled(
+            text,
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        )
+    };
+    frame.render_widget(
+        Paragraph::new(hint(" ← Trash", ACCENT_PRIMARY)).alignment(Alignment::Left),
+        chunks[2],
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            "Z exits focus",
+            Style::default().fg(TEXT_SECONDARY),
+        ))
+        .alignment(Alignment::Center),
+        chunks[2],
+    );
+    frame.render_widget(
+        Paragraph::new(hint("Keep → ", ACCENT_SECONDARY)).alignment(Alignment::Right),
+        chunks[2],
+    );
 }
 
-/// Renders the summary screen at the end
-pub fn render_summary(frame: &mut Frame, stats: &DecisionStatistics) {
+/// Renders the summary screen at the end; `notice` (e.g. what was just copied)
+/// replaces the key hint
+pub fn render_summary(frame: &mut Frame, stats: &DecisionStatistics, notice: Option<&str>) {
     let area = frame.area();
 
     // Center the summary box
-    let summary_area = centered_rect(60, 50, area);
+    // Taller when there's a chart of freed space to show
+    let height = if stats.freed_by_type.is_empty() {
+        50
+    } else {
+        80
+    };
+    let summary_area = centered_rect(60, height, area);
 
     // Clear the background
     frame.render_widget(Clear, summary_area);
@@ -95,9 +335,8 @@ pub fn render_summary(frame: &mut Frame, stats: &DecisionStatistics) {
     let total = stats.total_files;
     let kept = stats.kept;
     let trashed = stats.trashed;
-    let remaining = total.saturating_sub(kept + trashed);
 
-    let lines = vec![
+    let mut lines = vec![
         Line::from(""),
         Line::from(Span::styled(
             "Summary",
@@ -136,760 +375,3937 @@ pub fn render_summary(frame: &mut Frame, stats: &DecisionStatistics) {
                     .add_modifier(Modifier::BOLD),
             ),
         ]),
-        Line::from(vec![
-            Span::styled("   ○ ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("Skipped:  "),
+    ];
+    if stats.moved > 0 {
+        lines.push(Line::from(vec![
+            Span::styled("   → ", Style::default().fg(ACCENT_HIGHLIGHT)),
+            Span::raw("Moved:    "),
             Span::styled(
-                format!("{}", remaining),
+                format!("{}", stats.moved),
+                Style::default()
+                    .fg(ACCENT_HIGHLIGHT)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]));
+    }
+    lines.push(Line::from(vec![
+        Span::styled("   ○ ", Style::default().fg(TEXT_SECONDARY)),
+        Span::raw("Skipped:  "),
+        Span::styled(
+            format!("{}", stats.skipped),
+            Style::default().fg(TEXT_SECONDARY),
+        ),
+    ]));
+    if stats.undecided > 0 {
+        lines.push(Line::from(vec![
+            Span::styled("   · ", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw("Undecided:"),
+            Span::styled(
+                format!("{}", stats.undecided),
                 Style::default().fg(TEXT_SECONDARY),
             ),
-        ]),
-        Line::from(""),
+        ]));
+    }
+    lines.extend([
         Line::from(""),
-        Line::from(Span::styled(
-            "Press any key to exit",
-            Style::default().fg(TEXT_SECONDARY),
-        )),
-    ];
+        Line::from(vec![
+            Span::raw("   Space freed: "),
+            Span::styled(
+                format_file_size(stats.freed_bytes),
+                Style::default()
+                    .fg(ACCENT_HIGHLIGHT)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+    ]);
+
+    // Freed space per file type below the counts, when anything was freed
+    let chart_height = if stats.freed_by_type.is_empty() { 0 } else { 7 };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(0),
+            Constraint::Length(chart_height),
+            Constraint::Length(2),
+        ])
+        .split(inner);
 
     let paragraph = Paragraph::new(lines)
         .alignment(Alignment::Center)
         .style(Style::default().fg(TEXT_PRIMARY));
 
-    frame.render_widget(paragraph, inner);
+    frame.render_widget(paragraph, chunks[0]);
+
+    if !stats.freed_by_type.is_empty() {
+        render_freed_chart(frame, stats, chunks[1]);
+    }
+
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(""),
+            Line::from(Span::styled(
+                notice.map(str::to_string).unwrap_or_else(|| {
+                    if stats.skipped > 0 {
+                        format!(
+                            "s review {} skipped · k / t copy kept / trashed paths · any other key applies",
+                            stats.skipped
+                        )
+                    } else {
+                        "k / t copy kept / trashed paths · any other key applies".to_string()
+                    }
+                }),
+                Style::default().fg(TEXT_SECONDARY),
+            )),
+        ])
+        .alignment(Alignment::Center),
+        chunks[2],
+    );
 }
 
-/// Renders the help overlay
-pub fn render_help_overlay(frame: &mut Frame) {
-    let area = frame.area();
-    let help_area = centered_rect(50, 70, area);
+/// Bar chart of space freed per file type
+fn render_freed_chart(frame: &mut Frame, stats: &DecisionStatistics, area: Rect) {
+    let bars: Vec<Bar> = stats
+        .freed_by_type
+        .iter()
+        .map(|(file_type, freed)| {
+            Bar::default()
+                .value(*freed)
+                .label(Line::from(format!("{:?}", file_type)))
+                .text_value(format_file_size(*freed))
+                .style(Style::default().fg(ACCENT_PRIMARY))
+                .value_style(
+                    Style::default()
+                        .fg(TEXT_PRIMARY)
+                        .bg(ACCENT_PRIMARY)
+                        .add_modifier(Modifier::BOLD),
+                )
+        })
+        .collect();
 
-    // Clear background
-    frame.render_widget(Clear, help_area);
+    let bar_width = 10;
+    let chart_width = (bars.len() as u16 * (bar_width + 1)).min(area.width);
+    let [chart_area] = Layout::horizontal([Constraint::Length(chart_width)])
+        .flex(ratatui::layout::Flex::Center)
+        .areas(area);
 
-    let block = Block::default()
-        .title(" Help ")
-        .title_alignment(Alignment::Center)
-        .borders(Borders::ALL)
-        .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
-        .style(Style::default().bg(BG_DARK));
+    frame.render_widget(
+        BarChart::default()
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(bar_width)
+            .bar_gap(1)
+            .label_style(Style::default().fg(TEXT_SECONDARY)),
+        chart_area,
+    );
+}
 
-    let inner = block.inner(help_area);
-    frame.render_widget(block, help_area);
+/// Renders the pause screen over the whole frame: no file names or previews,
+/// only how to resume
+pub fn render_pause_screen(frame: &mut Frame, pause: &app::PauseScreen, locked: bool) {
+    let area = frame.area();
+    frame.render_widget(Clear, area);
+    frame.render_widget(Block::default().style(Style::default().bg(BG_DARK)), area);
 
-    let help_lines = vec![
-        Line::from(""),
+    let masked = |input: &str| format!("{}_", "•".repeat(input.chars().count()));
+    let mut lines = vec![
         Line::from(Span::styled(
-            "Keyboard Shortcuts",
+            "⏸  Paused",
             Style::default()
-                .fg(ACCENT_HIGHLIGHT)
+                .fg(TEXT_PRIMARY)
                 .add_modifier(Modifier::BOLD),
         )),
         Line::from(""),
-        Line::from(vec![
-            Span::styled("  → ", Style::default().fg(ACCENT_SECONDARY)),
-            Span::raw("or "),
-            Span::styled("k", Style::default().fg(ACCENT_SECONDARY)),
-            Span::raw("     Keep file"),
-        ]),
-        Line::from(vec![
-            Span::styled("  ← ", Style::default().fg(ACCENT_PRIMARY)),
-            Span::raw("or "),
-            Span::styled("t", Style::default().fg(ACCENT_PRIMARY)),
-            Span::raw("     Trash file"),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  ↑↓ ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("or "),
-            Span::styled("i/j", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("   Navigate"),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  u ", Style::default().fg(ACCENT_HIGHLIGHT)),
-            Span::raw("or "),
-            Span::styled("Ctrl+Z", Style::default().fg(ACCENT_HIGHLIGHT)),
-            Span::raw("  Undo"),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  o ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("          Open file in editor"),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  q ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("or "),
-            Span::styled("Esc", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("     Quit"),
-        ]),
-        Line::from(vec![
-            Span::styled("  ?", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("           Toggle help"),
-        ]),
-        Line::from(""),
-        Line::from(""),
-        Line::from(Span::styled(
-            "Press ? or Esc to close",
-            Style::default().fg(TEXT_SECONDARY),
-        )),
     ];
+    if pause.setting {
+        lines.push(Line::from(Span::styled(
+            "Choose a passphrase for this session (Enter without one just pauses)",
+            Style::default().fg(TEXT_SECONDARY),
+        )));
+        lines.push(Line::from(Span::styled(
+            masked(&pause.input),
+            Style::default().fg(ACCENT_HIGHLIGHT),
+        )));
+    } else if locked {
+        lines.push(Line::from(Span::styled(
+            "Type the passphrase and press Enter to resume",
+            Style::default().fg(TEXT_SECONDARY),
+        )));
+        lines.push(Line::from(Span::styled(
+            masked(&pause.input),
+            Style::default().fg(ACCENT_HIGHLIGHT),
+        )));
+        if pause.wrong {
+            lines.push(Line::from(Span::styled(
+                "Wrong passphrase",
+                Style::default().fg(ACCENT_PRIMARY),
+            )));
+        }
+    } else {
+        lines.push(Line::from(Span::styled(
+            "Press any key to resume",
+            Style::default().fg(TEXT_SECONDARY),
+        )));
+    }
 
-    let paragraph = Paragraph::new(help_lines)
-        .alignment(Alignment::Center)
-        .style(Style::default().fg(TEXT_PRIMARY));
-
-    frame.render_widget(paragraph, inner);
+    let height = (lines.len() as u16).min(area.height);
+    let text_area = Rect {
+        y: area.y + (area.height - height) / 2,
+        height,
+        ..area
+    };
+    frame.render_widget(
+        Paragraph::new(lines).alignment(Alignment::Center),
+        text_area,
+    );
 }
 
-/// Renders the welcome dialog overlay
-pub fn render_welcome_overlay(frame: &mut Frame) {
-    let area = centered_rect(85, 85, frame.area());
-
-    // Clear background
+/// Renders the details of a failed preview; `notice` says where `b` saved them
+pub fn render_preview_failure_overlay(
+    frame: &mut Frame,
+    failure: &crate::diagnostics::PreviewFailure,
+    notice: Option<&str>,
+) {
+    let area = centered_rect(80, 80, frame.area());
     frame.render_widget(Clear, area);
 
-    // Create welcome content
-    let welcome_lines = vec![
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Welcome to fswp!",
-            Style::default()
-                .fg(ACCENT_HIGHLIGHT)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-        Line::from(vec![Span::raw(
-            "fswp helps you quickly review and organize files with a Tinder-like interface.",
-        )]),
-        Line::from(vec![Span::raw(
-            "Swipe through files, decide what to keep or trash, and declutter your directories.",
-        )]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Quick Start:",
+    let block = Block::default()
+        .title(" Preview Failed ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_PRIMARY))
+        .style(Style::default().bg(BG_DARK));
+
+    let mut lines = Vec::new();
+    for (heading, section) in failure.sections() {
+        lines.push(Line::from(Span::styled(
+            format!(" {}", heading),
             Style::default()
                 .fg(ACCENT_HIGHLIGHT)
                 .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  1. ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("Review the current file (preview shown below)"),
-        ]),
-        Line::from(vec![
-            Span::styled("  2. ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("Press "),
-            Span::styled(
-                "→ (Right Arrow)",
-                Style::default()
-                    .fg(ACCENT_SECONDARY)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(" to "),
-            Span::styled("keep", Style::default().fg(ACCENT_SECONDARY)),
-            Span::raw(" or "),
-            Span::styled(
-                "← (Left Arrow)",
-                Style::default()
-                    .fg(ACCENT_PRIMARY)
-                    .add_modifier(Modifier::BOLD),
-            ),
-            Span::raw(" to "),
-            Span::styled("trash", Style::default().fg(ACCENT_PRIMARY)),
-        ]),
-        Line::from(vec![
-            Span::styled("  3. ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("Continue until you've reviewed all files"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Essential Keyboard Shortcuts:",
+        )));
+        for line in section {
+            lines.push(Line::from(format!("   {}", sanitize_for_display(&line))));
+        }
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        notice.map_or_else(
+            || " b saves these details to a file for a bug report".to_string(),
+            |notice| format!(" {}", notice),
+        ),
+        Style::default().fg(TEXT_SECONDARY),
+    )));
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .style(Style::default().fg(TEXT_PRIMARY))
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Renders the scan overview: a histogram of file ages (bar height is the bytes,
+/// labels carry the file counts) above the files' size and count per type
+pub fn render_overview(frame: &mut Frame, overview: &crate::domain::ScanOverview) {
+    let overview_area = centered_rect(80, 80, frame.area());
+    frame.render_widget(Clear, overview_area);
+
+    let block = Block::default()
+        .title(" Scan Overview ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+    let inner = block.inner(overview_area);
+    frame.render_widget(block, overview_area);
+
+    let type_rows = overview.by_type.len() as u16 * 2;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Age heading
+            Constraint::Min(4),    // Age histogram
+            Constraint::Length(2), // Type heading
+            Constraint::Length(type_rows),
+        ])
+        .split(inner);
+
+    let heading = |text: &'static str| {
+        Paragraph::new(Line::from(Span::styled(
+            text,
             Style::default()
                 .fg(ACCENT_HIGHLIGHT)
                 .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  → / k  ", Style::default().fg(ACCENT_SECONDARY)),
-            Span::raw("      Keep file"),
-        ]),
-        Line::from(vec![
-            Span::styled("  ← / t  ", Style::default().fg(ACCENT_PRIMARY)),
-            Span::raw("      Trash file"),
-        ]),
-        Line::from(vec![
-            Span::styled("  ↑ / i  ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("      Previous file"),
-        ]),
-        Line::from(vec![
-            Span::styled("  ↓ / j  ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("      Next file"),
-        ]),
-        Line::from(vec![
-            Span::styled("  u      ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("      Undo last decision"),
-        ]),
-        Line::from(vec![
-            Span::styled("  o      ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("      Open file in editor"),
-        ]),
-        Line::from(vec![
-            Span::styled("  ?      ", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("      Show help (access this anytime)"),
-        ]),
-        Line::from(vec![
-            Span::styled("  q / Esc", Style::default().fg(TEXT_SECONDARY)),
-            Span::raw("      Quit application"),
-        ]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Safety Features:",
-            Style::default()
-                .fg(ACCENT_HIGHLIGHT)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  •  ", Style::default().fg(ACCENT_SECONDARY)),
-            Span::raw("Files are moved to your system "),
-            Span::styled("trash", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(" (not permanently deleted)"),
-        ]),
-        Line::from(vec![
-            Span::styled("  •  ", Style::default().fg(ACCENT_SECONDARY)),
-            Span::raw("Use "),
-            Span::styled("'u'", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(" to undo any decision before quitting"),
-        ]),
-        Line::from(vec![
-            Span::styled("  •  ", Style::default().fg(ACCENT_SECONDARY)),
-            Span::raw("Run with "),
-            Span::styled("--dry-run", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(" to preview without making changes"),
-        ]),
-        Line::from(vec![
-            Span::styled("  •  ", Style::default().fg(ACCENT_SECONDARY)),
-            Span::raw("Use "),
-            Span::styled("--yes", Style::default().add_modifier(Modifier::BOLD)),
-            Span::raw(" to skip confirmation dialogs"),
-        ]),
-        Line::from(""),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Press any key to start browsing...",
-            Style::default()
-                .fg(ACCENT_HIGHLIGHT)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-    ];
+        )))
+        .alignment(Alignment::Center)
+    };
+    frame.render_widget(heading("Last modified"), chunks[0]);
+    frame.render_widget(heading("By type"), chunks[2]);
 
-    let welcome_text = Paragraph::new(welcome_lines)
-        .block(
-            Block::default()
-                .title(Span::styled(
-                    " Welcome to fswp ",
+    let age_bars: Vec<Bar> = overview
+        .by_age
+        .iter()
+        .map(|(band, tally)| {
+            Bar::default()
+                .value(tally.bytes)
+                .label(Line::from(format!("{} {}", band, tally.count)))
+                .text_value(format_file_size(tally.bytes))
+                .style(Style::default().fg(ACCENT_HIGHLIGHT))
+                .value_style(
                     Style::default()
-                        .fg(ACCENT_HIGHLIGHT)
+                        .fg(TEXT_PRIMARY)
+                        .bg(ACCENT_HIGHLIGHT)
                         .add_modifier(Modifier::BOLD),
-                ))
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
-                .style(Style::default().bg(BG_DARK)),
-        )
-        .alignment(Alignment::Left)
-        .wrap(Wrap { trim: false });
+                )
+        })
+        .collect();
+    let bar_width = 10;
+    let chart_width = (age_bars.len() as u16 * (bar_width + 1)).min(chunks[1].width);
+    let [age_area] = Layout::horizontal([Constraint::Length(chart_width)])
+        .flex(ratatui::layout::Flex::Center)
+        .areas(chunks[1]);
+    frame.render_widget(
+        BarChart::default()
+            .data(BarGroup::default().bars(&age_bars))
+            .bar_width(bar_width)
+            .bar_gap(1)
+            .label_style(Style::default().fg(TEXT_SECONDARY)),
+        age_area,
+    );
 
-    frame.render_widget(welcome_text, area);
+    // Horizontal bars, so the ASCII-only value text can run past short bars
+    let type_bars: Vec<Bar> = overview
+        .by_type
+        .iter()
+        .map(|(file_type, tally)| {
+            Bar::default()
+                .value(tally.bytes)
+                .label(Line::from(format!("{:?}", file_type)))
+                .text_value(format!(
+                    "{}, {} files",
+                    format_file_size(tally.bytes),
+                    tally.count
+                ))
+                .style(Style::default().fg(ACCENT_SECONDARY))
+                .value_style(
+                    Style::default()
+                        .fg(TEXT_PRIMARY)
+                        .bg(ACCENT_SECONDARY)
+                        .add_modifier(Modifier::BOLD),
+                )
+        })
+        .collect();
+    frame.render_widget(
+        BarChart::default()
+            .direction(Direction::Horizontal)
+            .data(BarGroup::default().bars(&type_bars))
+            .bar_width(1)
+            .bar_gap(1)
+            .label_style(Style::default().fg(TEXT_SECONDARY)),
+        chunks[3],
+    );
 }
 
-/// Renders the confirmation dialog for trash action
-pub fn render_confirm_trash_overlay(frame: &mut Frame, file: &crate::domain::FileEntry) {
+/// Renders the apply screen: live progress while trashing, then the per-file results.
+/// `manifest` is (files hashed, files to hash) while the backup manifest is written.
+pub fn render_apply_screen(
+    frame: &mut Frame,
+    job: &ApplyJob,
+    files: &[FileEntry],
+    manifest: Option<(usize, usize)>,
+) {
     let area = frame.area();
-    let confirm_area = centered_rect(50, 60, area);
+    let apply_area = centered_rect(70, 70, area);
 
-    // Clear background
-    frame.render_widget(Clear, confirm_area);
+    frame.render_widget(Clear, apply_area);
 
+    let title = if job.is_finished() {
+        " Apply Results "
+    } else {
+        " Applying "
+    };
     let block = Block::default()
-        .title(" ⚠ Confirm Trash ")
+        .title(title)
         .title_alignment(Alignment::Center)
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
-        .border_style(Style::default().fg(ACCENT_PRIMARY))
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
         .style(Style::default().bg(BG_DARK));
 
-    let inner = block.inner(confirm_area);
-    frame.render_widget(block, confirm_area);
+    let inner = block.inner(apply_area);
+    frame.render_widget(block, apply_area);
 
-    let confirm_lines = vec![
-        Line::from(""),
-        Line::from(Span::styled(
-            "Are you sure you want to trash this file?",
-            Style::default()
-                .fg(TEXT_PRIMARY)
-                .add_modifier(Modifier::BOLD),
-        )),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  File: ", Style::default().fg(TEXT_SECONDARY)),
-            Span::styled(&file.name, Style::default().fg(TEXT_PRIMARY)),
-        ]),
-        Line::from(vec![
-            Span::styled("  Size: ", Style::default().fg(TEXT_SECONDARY)),
-            Span::styled(
-                format_file_size(file.size),
-                Style::default().fg(TEXT_PRIMARY),
-            ),
-        ]),
-        Line::from(vec![
-            Span::styled("  Type: ", Style::default().fg(TEXT_SECONDARY)),
-            Span::styled(
-                format!("{:?}", file.file_type),
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Status line
+            Constraint::Length(1), // Progress bar
+            Constraint::Length(1),
+            Constraint::Min(0),    // Results
+            Constraint::Length(1), // Key hint
+        ])
+        .split(inner);
+
+    let file_name = |index: usize| files.get(index).map_or("?", |f| f.name.as_str());
+    let status = match (job.current(), manifest) {
+        (_, Some((hashed, total))) if !job.cancelled => {
+            format!(
+                "Writing backup manifest: {} of {} files hashed",
+                hashed, total
+            )
+        }
+        (Some(index), _) => format!("Moving to trash: {}", file_name(index)),
+        (None, _) if job.cancelled => "Cancelled, remaining files were put back".to_string(),
+        (None, _) => "Done".to_string(),
+    };
+    let elapsed = job.elapsed().as_secs_f64().max(0.001);
+    let throughput = format!(
+        "{}/{} files · {}/s · {:.1} files/s",
+        job.done(),
+        job.total,
+        format_file_size(job.bytes_per_second() as u64),
+        job.done() as f64 / elapsed
+    );
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(Span::styled(
+                sanitize_for_display(&status),
                 Style::default().fg(TEXT_PRIMARY),
-            ),
-        ]),
-        Line::from(""),
-        Line::from(""),
-        Line::from(Span::styled(
-            "This file will be moved to trash.",
-            Style::default().fg(TEXT_SECONDARY),
-        )),
-        Line::from(Span::styled(
-            "You can undo this action with 'u'.",
-            Style::default().fg(TEXT_SECONDARY),
-        )),
-        Line::from(""),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("[Y]", Style::default().fg(ACCENT_SECONDARY)),
-            Span::raw("es  "),
-            Span::styled("[Enter]", Style::default().fg(ACCENT_SECONDARY)),
-            Span::raw("     "),
-            Span::styled("[N]", Style::default().fg(ACCENT_PRIMARY)),
-            Span::raw("o  "),
-            Span::styled("[Esc]", Style::default().fg(ACCENT_PRIMARY)),
-        ]),
-        Line::from(""),
-    ];
+            )),
+            Line::from(Span::styled(
+                throughput,
+                Style::default().fg(TEXT_SECONDARY),
+            )),
+        ])
+        .alignment(Alignment::Center),
+        chunks[0],
+    );
 
-    let paragraph = Paragraph::new(confirm_lines)
-        .alignment(Alignment::Center)
-        .style(Style::default().fg(TEXT_PRIMARY));
+    frame.render_widget(
+        Gauge::default()
+            .gauge_style(Style::default().fg(ACCENT_PRIMARY).bg(BG_DARK))
+            .ratio(job.progress()),
+        chunks[1],
+    );
 
-    frame.render_widget(paragraph, inner);
+    let mut lines: Vec<Line> = job
+        .results
+        .iter()
+        .map(|(index, outcome)| {
+            let name = sanitize_for_display(file_name(*index));
+            match outcome {
+                ApplyOutcome::Trashed => Line::from(vec![
+                    Span::styled(" ✓ ", Style::default().fg(ACCENT_SECONDARY)),
+                    Span::raw(name),
+                ]),
+                ApplyOutcome::Failed(e) => Line::from(vec![
+                    Span::styled(" ✗ ", Style::default().fg(ACCENT_PRIMARY)),
+                    Span::raw(name),
+                    Span::styled(
+                        format!(" — {}", sanitize_for_display(e)),
+                        Style::default().fg(TEXT_SECONDARY),
+                    ),
+                ]),
+                ApplyOutcome::Restored => Line::from(vec![
+                    Span::styled(" ↺ ", Style::default().fg(ACCENT_HIGHLIGHT)),
+                    Span::raw(name),
+                    Span::styled(" — put back", Style::default().fg(TEXT_SECONDARY)),
+                ]),
+            }
+        })
+        .collect();
+    match job.issues {
+        Some(ref issues) if issues.is_empty() => lines.push(Line::from(Span::styled(
+            " Verified: no issues",
+            Style::default().fg(ACCENT_SECONDARY),
+        ))),
+        Some(ref issues) => {
+            lines.push(Line::from(Span::styled(
+                format!(" Issues ({})", issues.len()),
+                Style::default()
+                    .fg(ACCENT_HIGHLIGHT)
+                    .add_modifier(Modifier::BOLD),
+            )));
+            lines.extend(issues.iter().map(|(index, anomaly)| {
+                Line::from(vec![
+                    Span::styled(" ⚠ ", Style::default().fg(ACCENT_HIGHLIGHT)),
+                    Span::raw(sanitize_for_display(file_name(*index))),
+                    Span::styled(
+                        format!(" — {}", anomaly.description()),
+                        Style::default().fg(TEXT_SECONDARY),
+                    ),
+                ])
+            }));
+        }
+        None => {}
+    }
+    // Most recent results (and any issues) last; show as many as fit
+    let rows = chunks[3].height as usize;
+    let lines = lines.split_off(lines.len().saturating_sub(rows));
+    frame.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(TEXT_PRIMARY)),
+        chunks[3],
+    );
+
+    let hint = if job.is_finished() {
+        "Press any key to exit"
+    } else {
+        "Esc to cancel (stops after the current file)"
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(hint, Style::default().fg(TEXT_SECONDARY)))
+            .alignment(Alignment::Center),
+        chunks[4],
+    );
 }
 
-/// Renders a loading overlay
-pub fn render_loading_overlay(frame: &mut Frame, file: &crate::domain::FileEntry) {
-    let area = frame.area();
-    let loading_area = centered_rect(40, 30, area);
+/// Renders the read-only replay of a session journal, centred on `position`
+pub fn render_replay(
+    frame: &mut Frame,
+    session: &str,
+    log: &crate::journal::SessionLog,
+    position: usize,
+) {
+    use crate::journal::JournalAction;
 
-    // Clear background
-    frame.render_widget(Clear, loading_area);
+    let records = &log.records;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3), // Title and step counter
+            Constraint::Min(0),    // Records
+            Constraint::Length(1), // Key hint
+        ])
+        .split(frame.area());
 
     let block = Block::default()
-        .title(" Loading Preview ")
-        .title_alignment(Alignment::Center)
+        .title(format!(" Replay: {} ", sanitize_for_display(session)))
         .borders(Borders::ALL)
         .border_type(BorderType::Rounded)
         .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
         .style(Style::default().bg(BG_DARK));
+    let mut step = if records.is_empty() {
+        "Empty session".to_string()
+    } else {
+        format!("Step {}/{} · read-only", position + 1, records.len())
+    };
+    if let Some(ref info) = log.info {
+        step = format!("{} · {}", step, sanitize_for_display(&info.describe()));
+    }
+    frame.render_widget(
+        Paragraph::new(Span::styled(step, Style::default().fg(TEXT_SECONDARY))).block(block),
+        chunks[0],
+    );
 
-    let inner = block.inner(loading_area);
-    frame.render_widget(block, loading_area);
-
-    // Simple animation based on current time
-    let spinners = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis();
-    let spinner_idx = (now / 100) as usize % spinners.len();
-    let spinner = spinners[spinner_idx];
+    // Keep the current record in view, roughly centred
+    let rows = chunks[1].height as usize;
+    let start = position
+        .saturating_sub(rows / 2)
+        .min(records.len().saturating_sub(rows));
+    let lines: Vec<Line> = records
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(rows)
+        .map(|(i, record)| {
+            let (symbol, label, color) = match record.action {
+                JournalAction::Keep => ("✓", "keep ", ACCENT_SECONDARY),
+                JournalAction::Trash => ("✗", "trash", ACCENT_PRIMARY),
+                JournalAction::Move => ("→", "move ", ACCENT_HIGHLIGHT),
+                JournalAction::Skip => ("○", "skip ", TEXT_SECONDARY),
+                JournalAction::Undo => ("↺", "undo ", ACCENT_HIGHLIGHT),
+                JournalAction::Applied => ("✗", "apply", TEXT_SECONDARY),
+            };
+            let time = chrono::DateTime::from_timestamp(record.time, 0)
+                .map(|t| {
+                    t.with_timezone(&chrono::Local)
+                        .format("%H:%M:%S")
+                        .to_string()
+                })
+                .unwrap_or_default();
+            let mut spans = vec![
+                Span::styled(format!(" {} ", time), Style::default().fg(TEXT_SECONDARY)),
+                Span::styled(format!("{} {} ", symbol, label), Style::default().fg(color)),
+                Span::raw(sanitize_for_display(&record.path.to_string_lossy())),
+            ];
+            if let Some(ref destination) = record.destination {
+                spans.push(Span::raw(format!(
+                    " → {}",
+                    sanitize_for_display(&destination.to_string_lossy())
+                )));
+            }
+            spans.push(Span::styled(
+                format!("  {}", format_file_size(record.size)),
+                Style::default().fg(TEXT_SECONDARY),
+            ));
+            let line = Line::from(spans);
+            if i == position {
+                line.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                line
+            }
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(TEXT_PRIMARY)),
+        chunks[1],
+    );
 
-    let lines = vec![
-        Line::from(""),
-        Line::from(vec![
-            Span::styled(
-                format!("  {} ", spinner),
-                Style::default().fg(ACCENT_HIGHLIGHT),
-            ),
-            Span::styled("Processing file", Style::default().fg(TEXT_PRIMARY)),
-        ]),
-        Line::from(""),
-        Line::from(vec![
-            Span::styled("  Name: ", Style::default().fg(TEXT_SECONDARY)),
-            Span::styled(
-                &file.name,
-                Style::default()
-                    .fg(TEXT_PRIMARY)
-                    .add_modifier(Modifier::BOLD),
-            ),
-        ]),
-        Line::from(vec![
-            Span::styled("  Size: ", Style::default().fg(TEXT_SECONDARY)),
-            Span::styled(
-                format_file_size(file.size),
-                Style::default().fg(TEXT_PRIMARY),
-            ),
-        ]),
-        Line::from(vec![
-            Span::styled("  Type: ", Style::default().fg(TEXT_SECONDARY)),
-            Span::styled(
-                format!("{:?}", file.file_type),
-                Style::default().fg(TEXT_PRIMARY),
-            ),
-        ]),
-        Line::from(""),
-        Line::from(Span::styled(
-            "  Please wait...",
-            Style::default()
-                .fg(TEXT_SECONDARY)
-                .add_modifier(Modifier::ITALIC),
+    frame.render_widget(
+        Paragraph::new(Span::styled(
+            " ↓/j next  ↑/i previous  q quit",
+            Style::default().fg(TEXT_SECONDARY),
         )),
-    ];
-
-    let paragraph = Paragraph::new(lines)
-        .alignment(Alignment::Left)
-        .style(Style::default().fg(TEXT_PRIMARY));
-
-    frame.render_widget(paragraph, inner);
+        chunks[2],
+    );
 }
 
-/// Helper to create a centered rect
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
+/// Renders the size explorer: the directory's subdirectories and its loose files,
+/// biggest first, each with its share of the total as a bar
+pub fn render_explorer(frame: &mut Frame, explorer: &crate::explorer::Explorer) {
+    let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Length(3), // Directory and total
+            Constraint::Min(0),    // Rows
+            Constraint::Length(1), // Notice or key hint
         ])
-        .split(r);
+        .split(frame.area());
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+    let block = Block::default()
+        .title(format!(
+            " Explore: {} ",
+            sanitize_for_display(&explorer.current.to_string_lossy())
+        ))
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+    let total = explorer.total();
+    let mut summary = format!(
+        "{} in {} files, {} folders",
+        format_file_size(total.bytes),
+        total.files,
+        total.directories
+    );
+    if explorer.is_measuring() {
+        summary.push_str(" · measuring…");
+    }
+    frame.render_widget(
+        Paragraph::new(Span::styled(summary, Style::default().fg(TEXT_SECONDARY))).block(block),
+        chunks[0],
+    );
+
+    const BAR_WIDTH: usize = 20;
+    let rows = chunks[1].height as usize;
+    let start = explorer
+        .cursor
+        .saturating_sub(rows / 2)
+        .min(explorer.rows.len().saturating_sub(rows));
+    let lines: Vec<Line> = if explorer.rows.is_empty() {
+        vec![Line::from(Span::styled(
+            " (empty)",
+            Style::default().fg(TEXT_SECONDARY),
+        ))]
+    } else {
+        explorer
+            .rows
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(rows)
+            .map(|(i, row)| {
+                let share = if total.bytes == 0 {
+                    0.0
+                } else {
+                    row.size.stats.bytes as f64 / total.bytes as f64
+                };
+                let filled = (share * BAR_WIDTH as f64).round() as usize;
+                let size = if row.size.done {
+                    format_file_size(row.size.stats.bytes)
+                } else {
+                    format!("{}…", format_file_size(row.size.stats.bytes))
+                };
+                let name = sanitize_for_display(&row.name);
+                let name = if row.loose {
+                    name
+                } else {
+                    format!("{}/", name)
+                };
+                let line = Line::from(vec![
+                    Span::raw(format!(" {:>10}  ", size)),
+                    Span::styled("█".repeat(filled), Style::default().fg(ACCENT_HIGHLIGHT)),
+                    Span::styled(
+                        "░".repeat(BAR_WIDTH - filled),
+                        Style::default().fg(TEXT_SECONDARY),
+                    ),
+                    Span::styled(
+                        format!(" {:>3.0}%  ", share * 100.0),
+                        Style::default().fg(TEXT_SECONDARY),
+                    ),
+                    Span::raw(name),
+                ]);
+                if i == explorer.cursor {
+                    line.style(Style::default().add_modifier(Modifier::REVERSED))
+                } else {
+                    line
+                }
+            })
+            .collect()
+    };
+    frame.render_widget(
+        Paragraph::new(lines).style(Style::default().fg(TEXT_PRIMARY)),
+        chunks[1],
+    );
+
+    let hint = match explorer.notice {
+        Some(ref notice) => format!(" {}", sanitize_for_display(notice)),
+        None => " ↑↓ move  Enter open  Backspace back  r review  q quit".to_string(),
+    };
+    frame.render_widget(
+        Paragraph::new(Span::styled(hint, Style::default().fg(TEXT_SECONDARY))),
+        chunks[2],
+    );
 }
 
-/// Renders the polished header with progress bar
-fn render_header_polished(frame: &mut Frame, area: Rect, state: &AppState) {
+/// Renders `fswp empty-trash`: fswp's files still in the trash under a heading per
+/// age group, each with a checkbox, its size and how long ago it was trashed
+pub fn render_empty_trash(
+    frame: &mut Frame,
+    list: &crate::retention::EmptyTrash,
+    now: chrono::DateTime<chrono::Utc>,
+) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(2), Constraint::Length(2)])
-        .split(area);
+        .constraints([
+            Constraint::Length(3), // Totals and selection
+            Constraint::Min(0),    // Groups and rows
+            Constraint::Length(1), // Confirmation, notice or key hint
+        ])
+        .split(frame.area());
 
-    // Title and file info
-    let (title_text, file_info) = if let Some(file) = state.current_file() {
-        let size_str = format_file_size(file.size);
-        let file_type = format!("{:?}", file.file_type);
-        (
-            format!(" File {}/{} ", state.current_index + 1, state.files.len()),
-            vec![
-                Span::styled(
-                    &file.name,
+    let block = Block::default()
+        .title(" Empty trash ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+    let total: u64 = list.rows.iter().map(|row| row.size).sum();
+    let (selected, selected_bytes) = list.selection();
+    let summary = format!(
+        "{} files fswp trashed ({}) · {} selected ({})",
+        list.rows.len(),
+        format_file_size(total),
+        selected,
+        format_file_size(selected_bytes)
+    );
+    frame.render_widget(
+        Paragraph::new(Span::styled(summary, Style::default().fg(TEXT_SECONDARY))).block(block),
+        chunks[0],
+    );
+
+    let groups = list.groups();
+    let mut lines = Vec::new();
+    let mut cursor_line = 0;
+    for (i, row) in list.rows.iter().enumerate() {
+        if i == 0 || list.rows[i - 1].age != row.age {
+            if let Some(&(age, files, bytes, chosen)) = groups.iter().find(|g| g.0 == row.age) {
+                lines.push(Line::from(Span::styled(
+                    format!(
+                        " {} · {} files · {} · {} selected",
+                        age.label(),
+                        files,
+                        format_file_size(bytes),
+                        chosen
+                    ),
                     Style::default()
-                        .fg(TEXT_PRIMARY)
+                        .fg(ACCENT_HIGHLIGHT)
                         .add_modifier(Modifier::BOLD),
-                ),
-                Span::raw("  "),
-                Span::styled(
-                    format!("({} • {})", size_str, file_type),
-                    Style::default().fg(TEXT_SECONDARY),
-                ),
-            ],
+                )));
+            }
+        }
+        let check = if row.selected { "[x]" } else { "[ ]" };
+        let line = Line::from(vec![
+            Span::raw(format!("   {} {:>10}  ", check, format_file_size(row.size))),
+            Span::styled(
+                format!("{:>5}  ", format!("{}d", (now - row.deleted).num_days())),
+                Style::default().fg(TEXT_SECONDARY),
+            ),
+            Span::raw(sanitize_for_display(&row.path.to_string_lossy())),
+        ]);
+        if i == list.cursor {
+            cursor_line = lines.len();
+            lines.push(line.style(Style::default().add_modifier(Modifier::REVERSED)));
+        } else {
+            lines.push(line);
+        }
+    }
+    let rows = chunks[1].height as usize;
+    let start = cursor_line
+        .saturating_sub(rows / 2)
+        .min(lines.len().saturating_sub(rows));
+    frame.render_widget(
+        Paragraph::new(lines.into_iter().skip(start).take(rows).collect::<Vec<_>>())
+            .style(Style::default().fg(TEXT_PRIMARY)),
+        chunks[1],
+    );
+
+    let (hint, color) = if list.confirming {
+        (
+            format!(
+                " Permanently delete {} files ({})? y to confirm, any other key to cancel",
+                selected,
+                format_file_size(selected_bytes)
+            ),
+            ACCENT_PRIMARY,
         )
+    } else if let Some(ref notice) = list.notice {
+        (format!(" {}", sanitize_for_display(notice)), TEXT_SECONDARY)
     } else {
         (
-            " Fswp ".to_string(),
-            vec![Span::styled(
-                "No files to review",
-                Style::default().fg(TEXT_SECONDARY),
-            )],
+            " ↑↓ move  Space select  g group  a all  d delete  q quit".to_string(),
+            TEXT_SECONDARY,
         )
     };
+    frame.render_widget(
+        Paragraph::new(Span::styled(hint, Style::default().fg(color))),
+        chunks[2],
+    );
+}
 
-    let title_line = Line::from(vec![Span::styled(
-        title_text,
-        Style::default()
-            .fg(ACCENT_HIGHLIGHT)
-            .add_modifier(Modifier::BOLD),
-    )]);
-
-    let info_line = Line::from(file_info);
-
-    let header = Paragraph::new(vec![title_line, info_line])
-        .block(
-            Block::default()
-                .borders(Borders::TOP | Borders::LEFT | Borders::RIGHT)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(BORDER_COLOR)),
-        )
-        .alignment(Alignment::Left);
-
-    frame.render_widget(header, chunks[0]);
-
-    // Progress bar
-    let total = state.files.len();
-    let processed = state.decisions_stack.len();
-    let progress = if total > 0 {
-        processed as f64 / total as f64
-    } else {
-        0.0
-    };
+/// Renders the help overlay: the keymap's bindings, filtered by the search text and scrolled,
+/// with the configured quick-move targets under the heading
+pub fn render_help_overlay(frame: &mut Frame, help: &HelpState, move_targets: &[(u8, PathBuf)]) {
+    let area = frame.area();
+    let help_area = centered_rect(70, 70, area);
 
-    let gauge = Gauge::default()
-        .block(
-            Block::default()
-                .borders(Borders::BOTTOM | Borders::LEFT | Borders::RIGHT)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(BORDER_COLOR)),
-        )
-        .gauge_style(Style::default().fg(ACCENT_SECONDARY).bg(BG_DARK))
-        .ratio(progress)
-        .label(format!(
-            "{}% ({}/{})",
-            (progress * 100.0) as u16,
-            processed,
-            total
-        ));
+    // Clear background
+    frame.render_widget(Clear, help_area);
 
-    frame.render_widget(gauge, chunks[1]);
-}
+    let block = Block::default()
+        .title(" Help ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
 
-/// Renders the main content area (synchronous version)
-fn render_content(frame: &mut Frame, area: Rect, state: &AppState) {
-    use crate::preview::PreviewContent;
+    let inner = block.inner(help_area);
+    frame.render_widget(block, help_area);
 
-    let content = if let Some(file) = state.current_file() {
-        // Generate file preview
-        let lines: Vec<Line> = match preview::generate_preview(file) {
-            Ok(PreviewContent::Text(text_lines)) => {
-                text_lines.into_iter().map(Line::from).collect()
-            }
-            Ok(PreviewContent::Styled(styled_lines)) => styled_lines,
-            Err(e) => vec![
-                Line::from(format!("Error generating preview: {}", e)),
-                Line::from(""),
-                Line::from(format!("File: {}", file.name)),
-                Line::from(format!("Path: {}", file.path.display())),
-                Line::from(format!("Size: {} bytes", file.size)),
-                Line::from(format!("Type: {:?}", file.file_type)),
-            ],
-        };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4), // Heading and search
+            Constraint::Min(0),    // Bindings
+            Constraint::Length(2), // Position and hints
+        ])
+        .split(inner);
 
-        Paragraph::new(lines)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(BORDER_COLOR))
-                    .title(format!(" {} ", file.name)),
-            )
-            .style(Style::default().fg(TEXT_PRIMARY))
-            .wrap(Wrap { trim: false })
+    let search = if help.filter.is_empty() {
+        Span::styled("Type to search", Style::default().fg(TEXT_SECONDARY))
     } else {
-        render_empty_state_widget()
+        Span::styled(
+            format!("Search: {}▏", sanitize_for_display(&help.filter)),
+            Style::default().fg(ACCENT_HIGHLIGHT),
+        )
     };
-
-    frame.render_widget(content, area);
-}
-
-/// Creates an empty state widget for when no files are present
-fn render_empty_state_widget() -> Paragraph<'static> {
-    let lines = vec![
-        Line::from(""),
+    let mut heading_lines = vec![
         Line::from(""),
         Line::from(Span::styled(
-            "No Files Found",
+            "Keyboard Shortcuts",
             Style::default()
                 .fg(ACCENT_HIGHLIGHT)
                 .add_modifier(Modifier::BOLD),
         )),
-        Line::from(""),
-        Line::from(Span::styled(
-            "The directory is empty or contains only hidden files.",
+        Line::from(search),
+    ];
+    if !move_targets.is_empty() {
+        heading_lines.push(Line::from(Span::styled(
+            format!("Move targets: {}", move_targets_label(move_targets)),
             Style::default().fg(TEXT_SECONDARY),
-        )),
+        )));
+    }
+    let heading = Paragraph::new(heading_lines).alignment(Alignment::Center);
+    frame.render_widget(heading, chunks[0]);
+
+    let entries = help.entries();
+    let key_width = entries
+        .iter()
+        .map(|entry| display_width(&entry.keys))
+        .max()
+        .unwrap_or(0);
+    let visible = chunks[1].height as usize;
+    let scroll = help.scroll.min(entries.len().saturating_sub(1));
+    let mut lines: Vec<Line> = entries
+        .iter()
+        .skip(scroll)
+        .take(visible)
+        .map(|entry| {
+            let padding = " ".repeat(key_width - display_width(&entry.keys));
+            Line::from(vec![
+                Span::styled(
+                    format!("  {}{}", entry.keys, padding),
+                    Style::default().fg(ACCENT_SECONDARY),
+                ),
+                Span::raw("   "),
+                Span::raw(entry.description),
+            ])
+        })
+        .collect();
+    if entries.is_empty() {
+        lines.push(Line::from(Span::styled(
+            "  No matching shortcuts",
+            Style::default().fg(TEXT_SECONDARY),
+        )));
+    }
+    frame.render_widget(Paragraph::new(lines), chunks[1]);
+
+    let position = if entries.is_empty() {
+        String::new()
+    } else {
+        let last = (scroll + visible).min(entries.len());
+        format!("{}–{} of {} · ", scroll + 1, last, entries.len())
+    };
+    let footer = Paragraph::new(vec![
         Line::from(""),
         Line::from(Span::styled(
-            "Try a different directory with visible files.",
+            format!("{}↑↓/PgUp/PgDn scroll · Esc close", position),
             Style::default().fg(TEXT_SECONDARY),
         )),
-    ];
-
-    Paragraph::new(lines)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(BORDER_COLOR))
-                .title(" Content "),
-        )
-        .alignment(Alignment::Center)
+    ])
+    .alignment(Alignment::Center);
+    frame.render_widget(footer, chunks[2]);
 }
 
-/// Renders the main content area with async preview loading
-fn render_content_async(
-    frame: &mut Frame,
-    area: Rect,
-    state: &AppState,
-    preview_manager: &mut SyncPreviewManager,
-) {
-    use crate::preview::PreviewContent;
-
-    if let Some(file) = state.current_file() {
-        // Get preview state from manager
-        let preview_state = preview_manager.request_preview(file);
-
-        match preview_state {
-            PreviewState::Loading => {
-                // Render an empty block for content first
-                let content_block = Block::default()
-                    .borders(Borders::ALL)
-                    .border_type(BorderType::Rounded)
-                    .border_style(Style::default().fg(BORDER_COLOR))
-                    .title(format!(" {} ", file.name));
-                frame.render_widget(content_block, area);
-
-                // Then render the loading overlay
-                render_loading_overlay(frame, file);
-            }
-            PreviewState::Ready(preview_content) => {
-                let lines = match preview_content {
-                    PreviewContent::Text(text_lines) => {
-                        text_lines.iter().map(|s| Line::from(s.clone())).collect()
-                    }
-                    PreviewContent::Styled(styled_lines) => styled_lines.clone(),
-                };
-
-                let paragraph = Paragraph::new(lines)
-                    .block(
-                        Block::default()
-                            .borders(Borders::ALL)
-                            .border_type(BorderType::Rounded)
-                            .border_style(Style::default().fg(BORDER_COLOR))
-                            .title(format!(" {} ", file.name)),
-                    )
-                    .style(Style::default().fg(TEXT_PRIMARY))
-                    .wrap(Wrap { trim: false });
-                frame.render_widget(paragraph, area);
-            }
-            PreviewState::Error(e) => {
-                let error_lines: Vec<Line> = vec![
-                    Line::from(""),
-                    Line::from(Span::styled(
-                        "  [!] Error generating preview",
-                        Style::default()
-                            .fg(ACCENT_PRIMARY)
-                            .add_modifier(Modifier::BOLD),
-                    )),
-                    Line::from(""),
-                    Line::from(format!("  {}", e)),
-                    Line::from(""),
-                    Line::from(format!("  File: {}", file.name)),
-                    Line::from(format!("  Path: {}", file.path.display())),
-                    Line::from(format!("  Size: {}", format_file_size(file.size))),
-                    Line::from(format!("  Type: {:?}", file.file_type)),
-                ];
+/// Renders the welcome dialog overlay
+pub fn render_welcome_overlay(frame: &mut Frame) {
+    let area = centered_rect(85, 85, frame.area());
 
-                let paragraph = Paragraph::new(error_lines)
-                    .block(
-                        Block::default()
-                            .borders(Borders::ALL)
-                            .border_type(BorderType::Rounded)
-                            .border_style(Style::default().fg(ACCENT_PRIMARY))
-                            .title(format!(" {} [!] ", file.name)),
-                    )
-                    .style(Style::default().fg(TEXT_PRIMARY))
-                    .wrap(Wrap { trim: false });
-                frame.render_widget(paragraph, area);
-            }
-        }
-    } else {
-        frame.render_widget(render_empty_state_widget(), area);
-    }
-}
+    // Clear background
+    frame.render_widget(Clear, area);
 
-/// Renders the polished footer with styled controls
-fn render_footer_polished(frame: &mut Frame, area: Rect) {
-    let controls = Line::from(vec![
-        Span::styled(
-            " ← ",
+    // Create welcome content
+    let welcome_lines = vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Welcome to fswp!",
             Style::default()
-                .fg(ACCENT_PRIMARY)
+                .fg(ACCENT_HIGHLIGHT)
                 .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled("Trash", Style::default().fg(TEXT_SECONDARY)),
-        Span::raw("  │  "),
-        Span::styled(
-            "→ ",
-            Style::default()
-                .fg(ACCENT_SECONDARY)
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::raw(
+            "fswp helps you quickly review and organize files with a Tinder-like interface.",
+        )]),
+        Line::from(vec![Span::raw(
+            "Swipe through files, decide what to keep or trash, and declutter your directories.",
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Quick Start:",
+            Style::default()
+                .fg(ACCENT_HIGHLIGHT)
                 .add_modifier(Modifier::BOLD),
-        ),
-        Span::styled("Keep", Style::default().fg(TEXT_SECONDARY)),
-        Span::raw("  │  "),
-        Span::styled("↑↓ ", Style::default().fg(TEXT_SECONDARY)),
-        Span::styled("Navigate", Style::default().fg(TEXT_SECONDARY)),
-        Span::raw("  │  "),
-        Span::styled("u ", Style::default().fg(ACCENT_HIGHLIGHT)),
-        Span::styled("Undo", Style::default().fg(TEXT_SECONDARY)),
-        Span::raw("  │  "),
-        Span::styled("? ", Style::default().fg(TEXT_SECONDARY)),
-        Span::styled("Help", Style::default().fg(TEXT_SECONDARY)),
-        Span::raw("  │  "),
-        Span::styled("q ", Style::default().fg(TEXT_SECONDARY)),
-        Span::styled("Quit", Style::default().fg(TEXT_SECONDARY)),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  1. ", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw("Review the current file (preview shown below)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  2. ", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw("Press "),
+            Span::styled(
+                "→ (Right Arrow)",
+                Style::default()
+                    .fg(ACCENT_SECONDARY)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" to "),
+            Span::styled("keep", Style::default().fg(ACCENT_SECONDARY)),
+            Span::raw(" or "),
+            Span::styled(
+                "← (Left Arrow)",
+                Style::default()
+                    .fg(ACCENT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" to "),
+            Span::styled("trash", Style::default().fg(ACCENT_PRIMARY)),
+        ]),
+        Line::from(vec![
+            Span::styled("  3. ", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw("Continue until you've reviewed all files"),
+        ]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Essential Keyboard Shortcuts:",
+            Style::default()
+                .fg(ACCENT_HIGHLIGHT)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  → / k  ", Style::default().fg(ACCENT_SECONDARY)),
+            Span::raw("      Keep file"),
+        ]),
+        Line::from(vec![
+            Span::styled("  ← / t  ", Style::default().fg(ACCENT_PRIMARY)),
+            Span::raw("      Trash file"),
+        ]),
+        Line::from(vec![
+            Span::styled("  ↑ / i  ", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw("      Previous file"),
+        ]),
+        Line::from(vec![
+            Span::styled("  ↓ / j  ", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw("      Next file"),
+        ]),
+        Line::from(vec![
+            Span::styled("  u      ", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw("      Undo last decision"),
+        ]),
+        Line::from(vec![
+            Span::styled("  o      ", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw("      Open file in editor"),
+        ]),
+        Line::from(vec![
+            Span::styled("  ?      ", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw("      Show help (access this anytime)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  q / Esc", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw("      Quit application"),
+        ]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Safety Features:",
+            Style::default()
+                .fg(ACCENT_HIGHLIGHT)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  •  ", Style::default().fg(ACCENT_SECONDARY)),
+            Span::raw("Files are moved to your system "),
+            Span::styled("trash", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" (not permanently deleted)"),
+        ]),
+        Line::from(vec![
+            Span::styled("  •  ", Style::default().fg(ACCENT_SECONDARY)),
+            Span::raw("Use "),
+            Span::styled("'u'", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to undo any decision before quitting"),
+        ]),
+        Line::from(vec![
+            Span::styled("  •  ", Style::default().fg(ACCENT_SECONDARY)),
+            Span::raw("Run with "),
+            Span::styled("--dry-run", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to preview without making changes"),
+        ]),
+        Line::from(vec![
+            Span::styled("  •  ", Style::default().fg(ACCENT_SECONDARY)),
+            Span::raw("Use "),
+            Span::styled("--yes", Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(" to skip confirmation dialogs"),
+        ]),
+        Line::from(""),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Press any key to start browsing...",
+            Style::default()
+                .fg(ACCENT_HIGHLIGHT)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(""),
+    ];
+
+    let welcome_text = Paragraph::new(welcome_lines)
+        .block(
+            Block::default()
+                .title(Span::styled(
+                    " Welcome to fswp ",
+                    Style::default()
+                        .fg(ACCENT_HIGHLIGHT)
+                        .add_modifier(Modifier::BOLD),
+                ))
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+                .style(Style::default().bg(BG_DARK)),
+        )
+        .alignment(Alignment::Left)
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(welcome_text, area);
+}
+
+/// Renders the confirmation dialog for trash action
+pub fn render_confirm_trash_overlay(
+    frame: &mut Frame,
+    file: &crate::domain::FileEntry,
+    size: &str,
+    access: Option<&crate::permissions::FileAccess>,
+    sidecars: &[&crate::domain::FileEntry],
+) {
+    let area = frame.area();
+    let confirm_area = centered_rect(50, 60, area);
+
+    // Clear background
+    frame.render_widget(Clear, confirm_area);
+
+    let block = Block::default()
+        .title(" ⚠ Confirm Trash ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_PRIMARY))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(confirm_area);
+    frame.render_widget(block, confirm_area);
+
+    let mut confirm_lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Are you sure you want to trash this file?",
+            Style::default()
+                .fg(TEXT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  File: ", Style::default().fg(TEXT_SECONDARY)),
+            Span::styled(
+                display_name(&file.name, (inner.width as usize).saturating_sub(10)),
+                Style::default().fg(TEXT_PRIMARY),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  Size: ", Style::default().fg(TEXT_SECONDARY)),
+            Span::styled(size.to_string(), Style::default().fg(TEXT_PRIMARY)),
+        ]),
+        Line::from(vec![
+            Span::styled("  Type: ", Style::default().fg(TEXT_SECONDARY)),
+            Span::styled(
+                format!("{:?}", file.file_type),
+                Style::default().fg(TEXT_PRIMARY),
+            ),
+        ]),
+        Line::from(""),
+    ];
+
+    if !sidecars.is_empty() {
+        let names: Vec<&str> = sidecars.iter().map(|file| file.name.as_str()).collect();
+        confirm_lines.push(Line::from(vec![
+            Span::styled("  Sidecars: ", Style::default().fg(TEXT_SECONDARY)),
+            Span::styled(
+                display_name(&names.join(", "), (inner.width as usize).saturating_sub(14)),
+                Style::default().fg(ACCENT_HIGHLIGHT),
+            ),
+        ]));
+        confirm_lines.push(Line::from(""));
+    }
+
+    if let Some(blocker) = access.and_then(|a| a.blocker) {
+        confirm_lines.push(Line::from(Span::styled(
+            format!("⚠ Will fail: {}", blocker.reason()),
+            Style::default()
+                .fg(ACCENT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )));
+        confirm_lines.push(Line::from(Span::styled(
+            "Run fswp with elevated permissions to trash this file.",
+            Style::default().fg(ACCENT_PRIMARY),
+        )));
+    }
+
+    if file.has_bidi_control() {
+        confirm_lines.push(Line::from(Span::styled(
+            "⚠ Name contains bidi override characters",
+            Style::default()
+                .fg(ACCENT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )));
+        confirm_lines.push(Line::from(Span::styled(
+            "The displayed extension may not be the real one.",
+            Style::default().fg(ACCENT_PRIMARY),
+        )));
+    }
+
+    let (what, only) = if sidecars.is_empty() {
+        ("This file will be moved to trash.".to_string(), Vec::new())
+    } else {
+        (
+            format!(
+                "This file and its {} sidecar{} will be moved to trash.",
+                sidecars.len(),
+                if sidecars.len() == 1 { "" } else { "s" }
+            ),
+            vec![
+                Span::raw("     "),
+                Span::styled("[O]", Style::default().fg(ACCENT_HIGHLIGHT)),
+                Span::raw("nly this file"),
+            ],
+        )
+    };
+    let mut keys = vec![
+        Span::styled("[Y]", Style::default().fg(ACCENT_SECONDARY)),
+        Span::raw("es  "),
+        Span::styled("[Enter]", Style::default().fg(ACCENT_SECONDARY)),
+    ];
+    keys.extend(only);
+    keys.extend([
+        Span::raw("     "),
+        Span::styled("[N]", Style::default().fg(ACCENT_PRIMARY)),
+        Span::raw("o  "),
+        Span::styled("[Esc]", Style::default().fg(ACCENT_PRIMARY)),
+    ]);
+    confirm_lines.extend([
+        Line::from(""),
+        Line::from(Span::styled(what, Style::default().fg(TEXT_SECONDARY))),
+        Line::from(Span::styled(
+            "You can undo this action with 'u'.",
+            Style::default().fg(TEXT_SECONDARY),
+        )),
+        Line::from(""),
+        Line::from(""),
+        Line::from(keys),
+        Line::from(""),
     ]);
 
-    let footer = Paragraph::new(controls)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .border_type(BorderType::Rounded)
-                .border_style(Style::default().fg(BORDER_COLOR)),
-        )
-        .alignment(Alignment::Center);
+    let paragraph = Paragraph::new(confirm_lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_PRIMARY));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Renders the prompt shown when the session goal is met
+pub fn render_goal_reached_overlay(frame: &mut Frame, state: &AppState) {
+    let area = frame.area();
+    let goal_area = centered_rect(50, 40, area);
+
+    frame.render_widget(Clear, goal_area);
+
+    let block = Block::default()
+        .title(" Goal Reached ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_SECONDARY))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(goal_area);
+    frame.render_widget(block, goal_area);
+
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "You hit your session goal!",
+            Style::default()
+                .fg(TEXT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            goal_label(state),
+            Style::default().fg(ACCENT_SECONDARY),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Apply your decisions and stop here?",
+            Style::default().fg(TEXT_SECONDARY),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("[Y]", Style::default().fg(ACCENT_SECONDARY)),
+            Span::raw("es  "),
+            Span::styled("[Enter]", Style::default().fg(ACCENT_SECONDARY)),
+            Span::raw("     "),
+            Span::styled("[N]", Style::default().fg(ACCENT_PRIMARY)),
+            Span::raw("o, keep going  "),
+            Span::styled("[Esc]", Style::default().fg(ACCENT_PRIMARY)),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_PRIMARY));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Renders the prompt after a burst of `count` decisions that came too quickly
+/// to be deliberate (a stuck key, something leaning on the keyboard)
+pub fn render_rapid_decisions_overlay(frame: &mut Frame, count: usize) {
+    let area = centered_rect(50, 40, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Slow Down? ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_PRIMARY))
+        .style(Style::default().bg(BG_DARK));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            "Did you mean that?",
+            Style::default()
+                .fg(TEXT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            format!("{} rapid decisions recorded", count),
+            Style::default().fg(ACCENT_HIGHLIGHT),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Undo all of them?",
+            Style::default().fg(TEXT_SECONDARY),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("[Y]", Style::default().fg(ACCENT_PRIMARY)),
+            Span::raw("es  "),
+            Span::styled("[Enter]", Style::default().fg(ACCENT_PRIMARY)),
+            Span::raw("     "),
+            Span::styled("[N]", Style::default().fg(ACCENT_SECONDARY)),
+            Span::raw("o, I meant it  "),
+            Span::styled("[Esc]", Style::default().fg(ACCENT_SECONDARY)),
+        ]),
+    ];
+    frame.render_widget(
+        Paragraph::new(lines)
+            .alignment(Alignment::Center)
+            .style(Style::default().fg(TEXT_PRIMARY)),
+        inner,
+    );
+}
+
+/// Most file names listed in the "select all like this" overlay
+const SELECTION_SAMPLE: usize = 8;
+
+/// Renders the files picked by "select all like this", waiting for one decision
+pub fn render_select_similar_overlay(
+    frame: &mut Frame,
+    state: &AppState,
+    pattern: &NamePattern,
+    matches: &[usize],
+) {
+    let area = frame.area();
+    let select_area = centered_rect(60, 60, area);
+
+    frame.render_widget(Clear, select_area);
+
+    let block = Block::default()
+        .title(" Select All Like This ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(select_area);
+    frame.render_widget(block, select_area);
+
+    let total: u64 = matches.iter().map(|&i| state.files[i].size).sum();
+    let name_width = (inner.width as usize).saturating_sub(6);
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            pattern.to_string(),
+            Style::default()
+                .fg(ACCENT_HIGHLIGHT)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            format!(
+                "{} undecided files · {}",
+                matches.len(),
+                format_file_size(total)
+            ),
+            Style::default().fg(TEXT_PRIMARY),
+        )),
+        Line::from(""),
+    ];
+    lines.extend(matches.iter().take(SELECTION_SAMPLE).map(|&i| {
+        Line::from(Span::styled(
+            display_name(&state.files[i].name, name_width),
+            Style::default().fg(TEXT_SECONDARY),
+        ))
+    }));
+    if matches.len() > SELECTION_SAMPLE {
+        lines.push(Line::from(Span::styled(
+            format!("… and {} more", matches.len() - SELECTION_SAMPLE),
+            Style::default().fg(TEXT_SECONDARY),
+        )));
+    }
+    lines.extend([
+        Line::from(""),
+        Line::from(Span::styled(
+            "One decision for all of them; 'u' undoes it in one step.",
+            Style::default().fg(TEXT_SECONDARY),
+        )),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("[→]", Style::default().fg(ACCENT_SECONDARY)),
+            Span::raw(" Keep all  "),
+            Span::styled("[←]", Style::default().fg(ACCENT_PRIMARY)),
+            Span::raw(" Trash all  "),
+            Span::styled("[1-9]", Style::default().fg(ACCENT_HIGHLIGHT)),
+            Span::raw(" Move all  "),
+            Span::styled("[Esc]", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw(" Back"),
+        ]),
+    ]);
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_PRIMARY));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Renders the easy wins screen: each suggested group with its file count and
+/// size, `cursor` marking the highlighted one, whose first files are listed below
+pub fn render_easy_wins_overlay(
+    frame: &mut Frame,
+    state: &AppState,
+    wins: &[crate::domain::EasyWin],
+    cursor: usize,
+) {
+    let area = frame.area();
+    let wins_area = centered_rect(60, 70, area);
+
+    frame.render_widget(Clear, wins_area);
+
+    let block = Block::default()
+        .title(" Easy Wins ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(wins_area);
+    frame.render_widget(block, wins_area);
+
+    let bytes = |win: &crate::domain::EasyWin| -> u64 {
+        win.files.iter().map(|&i| state.files[i].size).sum()
+    };
+    let marked: Vec<_> = wins.iter().filter(|win| win.accepted).collect();
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "Trash {} files ({}) before reviewing the rest?",
+                marked.iter().map(|win| win.files.len()).sum::<usize>(),
+                format_file_size(marked.iter().map(|win| bytes(win)).sum())
+            ),
+            Style::default()
+                .fg(TEXT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    for (i, win) in wins.iter().enumerate() {
+        let style = if i == cursor {
+            Style::default()
+                .fg(ACCENT_HIGHLIGHT)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(TEXT_PRIMARY)
+        };
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{} [{}] {}: {} files · {}",
+                if i == cursor { "›" } else { " " },
+                if win.accepted { "x" } else { " " },
+                win.kind,
+                win.files.len(),
+                format_file_size(bytes(win))
+            ),
+            style,
+        )));
+    }
+    lines.push(Line::from(""));
+
+    if let Some(win) = wins.get(cursor) {
+        let name_width = (inner.width as usize).saturating_sub(6) / 2;
+        let now = chrono::Utc::now();
+        lines.extend(win.files.iter().take(SELECTION_SAMPLE).map(|&i| {
+            let file = &state.files[i];
+            Line::from(Span::styled(
+                format!(
+                    "{} · {}",
+                    display_name(&file.name, name_width),
+                    win.kind.criterion(file, state.usage.get(&i), now)
+                ),
+                Style::default().fg(TEXT_SECONDARY),
+            ))
+        }));
+        if win.files.len() > SELECTION_SAMPLE {
+            lines.push(Line::from(Span::styled(
+                format!("… and {} more", win.files.len() - SELECTION_SAMPLE),
+                Style::default().fg(TEXT_SECONDARY),
+            )));
+        }
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::from(Span::styled(
+        "Marked groups are trashed as one batch; 'u' undoes it in one step.",
+        Style::default().fg(TEXT_SECONDARY),
+    )));
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_PRIMARY));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Renders the renames offered for kept files: old and new name with what's
+/// wrong, `cursor` marking the highlighted one
+pub fn render_renames_overlay(
+    frame: &mut Frame,
+    state: &AppState,
+    renames: &[crate::domain::RenameSuggestion],
+    cursor: usize,
+) {
+    let area = frame.area();
+    let renames_area = centered_rect(70, 70, area);
+
+    frame.render_widget(Clear, renames_area);
+
+    let block = Block::default()
+        .title(" Fix File Names ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(renames_area);
+    frame.render_widget(block, renames_area);
+
+    let marked = renames.iter().filter(|rename| rename.accepted).count();
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "Rename {} of {} kept files whose names break elsewhere?",
+                marked,
+                renames.len()
+            ),
+            Style::default()
+                .fg(TEXT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    // Keep the highlighted rename in view
+    let rows = (inner.height as usize)
+        .saturating_sub(lines.len() + 2)
+        .max(1);
+    let first = cursor.saturating_sub(rows - 1);
+    let name_width = (inner.width as usize).saturating_sub(12) / 3;
+    for (i, rename) in renames.iter().enumerate().skip(first).take(rows) {
+        let style = if i == cursor {
+            Style::default()
+                .fg(ACCENT_HIGHLIGHT)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(TEXT_PRIMARY)
+        };
+        let issues: Vec<String> = rename
+            .issues
+            .iter()
+            .map(|issue| issue.to_string())
+            .collect();
+        // Quoted, so leading and trailing spaces show
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{} [{}] \"{}\" → \"{}\" ({})",
+                if i == cursor { "›" } else { " " },
+                if rename.accepted { "x" } else { " " },
+                display_name(&state.files[rename.index].name, name_width),
+                display_name(&rename.new_name, name_width),
+                issues.join(", ")
+            ),
+            style,
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Renames never replace an existing file.",
+        Style::default().fg(TEXT_SECONDARY),
+    )));
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_PRIMARY));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Renders the files decided as trash before anything is deleted: name, size and
+/// whether each will still be trashed, `cursor` marking the highlighted one
+pub fn render_review_overlay(frame: &mut Frame, state: &AppState, review: &[usize], cursor: usize) {
+    let area = frame.area();
+    let review_area = centered_rect(70, 70, area);
+
+    frame.render_widget(Clear, review_area);
+
+    let block = Block::default()
+        .title(" Review Trash ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_PRIMARY))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(review_area);
+    frame.render_widget(block, review_area);
+
+    let trashed: Vec<usize> = review
+        .iter()
+        .copied()
+        .filter(|&index| state.decision_of(index) == Some(&Decision::Trash))
+        .collect();
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "Trash {} of {} files, freeing {}?",
+                trashed.len(),
+                review.len(),
+                format_file_size(reclaimable_bytes(
+                    trashed.iter().map(|&index| &state.files[index])
+                ))
+            ),
+            Style::default()
+                .fg(TEXT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    // Keep the highlighted file in view
+    let rows = (inner.height as usize)
+        .saturating_sub(lines.len() + 2)
+        .max(1);
+    let first = cursor.saturating_sub(rows - 1);
+    let name_width = (inner.width as usize).saturating_sub(24);
+    for (i, &index) in review.iter().enumerate().skip(first).take(rows) {
+        let file = &state.files[index];
+        let trash = trashed.contains(&index);
+        let color = if trash {
+            ACCENT_PRIMARY
+        } else {
+            ACCENT_SECONDARY
+        };
+        let style = if i == cursor {
+            Style::default().fg(color).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(TEXT_PRIMARY)
+        };
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{} {} {} ({})",
+                if i == cursor { "›" } else { " " },
+                if trash { "[trash]" } else { "[keep] " },
+                display_name(&file.name, name_width),
+                format_file_size(file.size)
+            ),
+            style,
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Nothing is deleted until you press Enter.",
+        Style::default().fg(TEXT_SECONDARY),
+    )));
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_PRIMARY));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Renders the filter inspector: the sort and each filter with how many files it
+/// hides, `cursor` marking the row Enter acts on; `notice` reports the last change
+pub fn render_inspector_overlay(
+    frame: &mut Frame,
+    rows: &[app::InspectorRow],
+    cursor: usize,
+    notice: Option<&str>,
+) {
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Active Filters ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+
+    let label_width = rows
+        .iter()
+        .map(|row| row.label.chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines = vec![Line::from("")];
+    for (i, row) in rows.iter().enumerate() {
+        let style = if i == cursor {
+            Style::default()
+                .fg(ACCENT_HIGHLIGHT)
+                .add_modifier(Modifier::BOLD)
+        } else if row.action.is_none() {
+            Style::default().fg(TEXT_SECONDARY)
+        } else {
+            Style::default().fg(TEXT_PRIMARY)
+        };
+        lines.push(Line::from(Span::styled(
+            format!(
+                " {} {:<width$}  {}",
+                if i == cursor { "›" } else { " " },
+                row.label,
+                row.detail,
+                width = label_width
+            ),
+            style,
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        notice.map_or_else(
+            || " Enter turns the highlighted filter off for this session".to_string(),
+            |notice| format!(" {}", notice),
+        ),
+        Style::default().fg(TEXT_SECONDARY),
+    )));
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .style(Style::default().fg(TEXT_PRIMARY))
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Renders the first of the duplicate groups left: its files oldest first, with
+/// `cursor` marking the one to keep
+pub fn render_duplicates_overlay(
+    frame: &mut Frame,
+    state: &AppState,
+    groups: &[crate::domain::DuplicateGroup],
+    cursor: usize,
+) {
+    let Some(group) = groups.first() else {
+        return;
+    };
+    let area = frame.area();
+    let duplicates_area = centered_rect(70, 70, area);
+
+    frame.render_widget(Clear, duplicates_area);
+
+    let block = Block::default()
+        .title(format!(" Duplicates ({} groups left) ", groups.len()))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(duplicates_area);
+    frame.render_widget(block, duplicates_area);
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            format!(
+                "{} identical files of {} each. Keep which one?",
+                group.files.len(),
+                format_file_size(group.size)
+            ),
+            Style::default()
+                .fg(TEXT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(Span::styled(
+            format!(
+                "Trashing the others frees {}.",
+                format_file_size(group.wasted_bytes(&state.files))
+            ),
+            Style::default().fg(TEXT_SECONDARY),
+        )),
+        Line::from(""),
+    ];
+    // Keep the highlighted file in view
+    let rows = (inner.height as usize)
+        .saturating_sub(lines.len() + 2)
+        .max(1);
+    let first = cursor.saturating_sub(rows - 1);
+    let path_width = (inner.width as usize).saturating_sub(24);
+    for (i, &index) in group.files.iter().enumerate().skip(first).take(rows) {
+        let file = &state.files[index];
+        let style = if i == cursor {
+            Style::default()
+                .fg(ACCENT_SECONDARY)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(TEXT_PRIMARY)
+        };
+        lines.push(Line::from(Span::styled(
+            format!(
+                "{} {} {} ({})",
+                if i == cursor { "›" } else { " " },
+                if i == cursor { "[keep] " } else { "[trash]" },
+                display_name(&file.path.to_string_lossy(), path_width),
+                file.modified_date.format("%Y-%m-%d")
+            ),
+            style,
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "The group is trashed as one batch; 'u' undoes it in one step.",
+        Style::default().fg(TEXT_SECONDARY),
+    )));
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_PRIMARY));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Renders the prompt for a move whose name is taken in the target directory, with
+/// both files side by side once compared
+pub fn render_move_conflict_overlay(
+    frame: &mut Frame,
+    state: &AppState,
+    conflict: &app::MoveConflict,
+) {
+    let area = frame.area();
+    let conflict_area = centered_rect(60, 60, area);
+
+    frame.render_widget(Clear, conflict_area);
+
+    let block = Block::default()
+        .title(" Name Taken ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(conflict_area);
+    frame.render_widget(block, conflict_area);
+
+    let file = &state.files[conflict.index];
+    let width = (inner.width as usize).saturating_sub(4);
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            display_name(
+                &format!("{} already has {}", conflict.dir.display(), file.name),
+                width,
+            ),
+            Style::default()
+                .fg(TEXT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    let row = |label: &str, entry: &FileEntry| {
+        Line::from(vec![
+            Span::styled(
+                format!("{:<10}", label),
+                Style::default().fg(TEXT_SECONDARY),
+            ),
+            Span::styled(
+                format!(
+                    "{:>10}   {}",
+                    format_file_size(entry.size),
+                    entry.modified_date.format("%Y-%m-%d %H:%M")
+                ),
+                Style::default().fg(TEXT_PRIMARY),
+            ),
+        ])
+    };
+    match conflict.compared {
+        Some((ref existing, identical)) => {
+            lines.push(row("Moving", file));
+            lines.push(row("Existing", existing));
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled(
+                if identical {
+                    "Same contents: the existing file is an identical copy"
+                } else {
+                    "Contents differ"
+                },
+                Style::default().fg(if identical {
+                    ACCENT_SECONDARY
+                } else {
+                    ACCENT_HIGHLIGHT
+                }),
+            )));
+        }
+        None => lines.push(Line::from(Span::styled(
+            "Press c to compare the two files",
+            Style::default().fg(TEXT_SECONDARY),
+        ))),
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "r: move as name (N) · o: trash the existing file and move · s: skip",
+        Style::default().fg(TEXT_SECONDARY),
+    )));
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_PRIMARY));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Renders the content search prompt, or its progress once the search is running
+pub fn render_search_overlay(frame: &mut Frame, input: &str, progress: Option<(usize, usize)>) {
+    let area = frame.area();
+    let search_area = centered_rect(50, 30, area);
+
+    frame.render_widget(Clear, search_area);
+
+    let block = Block::default()
+        .title(" Search File Contents ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(search_area);
+    frame.render_widget(block, search_area);
+
+    let status = match progress {
+        Some((done, total)) => format!("Searching… {}/{} text files", done, total),
+        None => "Text files in the queue that mention this are kept in view.".to_string(),
+    };
+    let lines = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("/ ", Style::default().fg(ACCENT_HIGHLIGHT)),
+            Span::styled(
+                input.to_string(),
+                Style::default()
+                    .fg(TEXT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                if progress.is_some() { "" } else { "_" },
+                Style::default().fg(ACCENT_HIGHLIGHT),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(status, Style::default().fg(TEXT_SECONDARY))),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("[Enter]", Style::default().fg(ACCENT_HIGHLIGHT)),
+            Span::raw(" Search (empty: show all)  "),
+            Span::styled("[Esc]", Style::default().fg(TEXT_SECONDARY)),
+            Span::raw(" Cancel"),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_PRIMARY));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Widest the undo toast gets
+const UNDO_TOAST_WIDTH: u16 = 56;
+
+/// Renders the undo toast for trash decisions still inside their grace period
+/// (`--apply-on-decide`): the latest file, a countdown and the undo key. `pending`
+/// comes from `DecisionEngine::within_grace`; `raised` moves it above the filter bar.
+pub fn render_undo_toast(
+    frame: &mut Frame,
+    state: &AppState,
+    pending: &[(usize, std::time::Duration)],
+    raised: bool,
+) {
+    let Some(&(index, left)) = pending.last() else {
+        return;
+    };
+    let area = frame.area();
+    let offset = if raised { 9 } else { 6 };
+    if area.height < offset + 1 || area.width < 20 {
+        return;
+    }
+    let width = UNDO_TOAST_WIDTH.min(area.width);
+    let toast_area = Rect::new(area.right() - width, area.bottom() - offset, width, 3);
+
+    frame.render_widget(Clear, toast_area);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_PRIMARY))
+        .style(Style::default().bg(BG_DARK));
+    let inner = block.inner(toast_area);
+    frame.render_widget(block, toast_area);
+
+    // Round up so the countdown never shows 0s while the file is still staged
+    let seconds = left.as_secs() + u64::from(left.subsec_nanos() > 0);
+    let more = match pending.len() {
+        1 => String::new(),
+        n => format!(" +{}", n - 1),
+    };
+    let suffix = format!("{} in {}s  ", more, seconds);
+    let name_width = (inner.width as usize)
+        .saturating_sub(display_width(&suffix) + display_width(" Trashing ") + 8);
+    let line = Line::from(vec![
+        Span::styled(" Trashing ", Style::default().fg(TEXT_SECONDARY)),
+        Span::styled(
+            display_name(&state.files[index].name, name_width),
+            Style::default().fg(TEXT_PRIMARY),
+        ),
+        Span::styled(suffix, Style::default().fg(TEXT_SECONDARY)),
+        Span::styled(
+            "u",
+            Style::default()
+                .fg(ACCENT_HIGHLIGHT)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(" Undo", Style::default().fg(TEXT_SECONDARY)),
+    ]);
+    frame.render_widget(Paragraph::new(line), inner);
+}
+
+/// Renders the filter bar just above the footer: the expression, how many files it
+/// leaves and why the last edit didn't parse, if it didn't
+pub fn render_filter_bar(
+    frame: &mut Frame,
+    state: &AppState,
+    input: &str,
+    error: Option<&str>,
+    editing: bool,
+) {
+    let area = frame.area();
+    if area.height < 7 {
+        return;
+    }
+    let bar_area = Rect::new(area.x, area.bottom() - 6, area.width, 3);
+
+    frame.render_widget(Clear, bar_area);
+
+    let border = if editing {
+        ACCENT_HIGHLIGHT
+    } else {
+        TEXT_SECONDARY
+    };
+    let block = Block::default()
+        .title(" Filter ")
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(border))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(bar_area);
+    frame.render_widget(block, bar_area);
+
+    let mut spans = vec![
+        Span::styled(
+            input.to_string(),
+            Style::default()
+                .fg(TEXT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(
+            if editing { "_" } else { "" },
+            Style::default().fg(ACCENT_HIGHLIGHT),
+        ),
+    ];
+    match error {
+        Some(error) => spans.push(Span::styled(
+            format!("  {}", error),
+            Style::default().fg(ACCENT_PRIMARY),
+        )),
+        None => spans.push(Span::styled(
+            format!(
+                "  {} of {} files",
+                state.queue.len(),
+                state.full_queue().len()
+            ),
+            Style::default().fg(TEXT_SECONDARY),
+        )),
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), inner);
+}
+
+/// Renders the first-run setup wizard's current question
+pub fn render_setup_wizard(frame: &mut Frame, wizard: &crate::setup::SetupWizard) {
+    use crate::setup::SETUP_STEPS;
+
+    let area = centered_rect(70, 60, frame.area());
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(
+            " Setup {}/{} ",
+            (wizard.step() + 1).min(SETUP_STEPS.len()),
+            SETUP_STEPS.len()
+        ))
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(step) = wizard.current() else {
+        return;
+    };
+
+    let mut lines = vec![
+        Line::from(""),
+        Line::from(Span::styled(
+            step.question,
+            Style::default()
+                .fg(TEXT_PRIMARY)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+    ];
+    for (i, (label, description)) in step.options.iter().enumerate() {
+        let selected = i == wizard.selected;
+        let marker = if selected { "▶ " } else { "  " };
+        let style = if selected {
+            Style::default()
+                .fg(ACCENT_SECONDARY)
+                .add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(TEXT_PRIMARY)
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}{}", marker, label), style),
+            Span::styled(
+                format!("  — {}", description),
+                Style::default().fg(TEXT_SECONDARY),
+            ),
+        ]));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled("[↑↓]", Style::default().fg(ACCENT_SECONDARY)),
+        Span::raw(" choose  "),
+        Span::styled("[Enter]", Style::default().fg(ACCENT_SECONDARY)),
+        Span::raw(" confirm  "),
+        Span::styled("[Esc]", Style::default().fg(ACCENT_PRIMARY)),
+        Span::raw(" use defaults"),
+    ]));
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(TEXT_PRIMARY))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, inner);
+}
+
+/// Renders a loading overlay
+pub fn render_loading_overlay(frame: &mut Frame, file: &crate::domain::FileEntry) {
+    let area = frame.area();
+    let loading_area = centered_rect(40, 30, area);
+
+    // Clear background
+    frame.render_widget(Clear, loading_area);
+
+    let block = Block::default()
+        .title(" Loading Preview ")
+        .title_alignment(Alignment::Center)
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .style(Style::default().bg(BG_DARK));
+
+    let inner = block.inner(loading_area);
+    frame.render_widget(block, loading_area);
+
+    // Simple animation based on current time
+    let spinners = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let spinner_idx = (now / 100) as usize % spinners.len();
+    let spinner = spinners[spinner_idx];
+
+    let lines = vec![
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                format!("  {} ", spinner),
+                Style::default().fg(ACCENT_HIGHLIGHT),
+            ),
+            Span::styled("Processing file", Style::default().fg(TEXT_PRIMARY)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  Name: ", Style::default().fg(TEXT_SECONDARY)),
+            Span::styled(
+                display_name(&file.name, (inner.width as usize).saturating_sub(8)),
+                Style::default()
+                    .fg(TEXT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  Size: ", Style::default().fg(TEXT_SECONDARY)),
+            Span::styled(
+                format_file_size(file.size),
+                Style::default().fg(TEXT_PRIMARY),
+            ),
+        ]),
+        Line::from(vec![
+            Span::styled("  Type: ", Style::default().fg(TEXT_SECONDARY)),
+            Span::styled(
+                format!("{:?}", file.file_type),
+                Style::default().fg(TEXT_PRIMARY),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(Span::styled(
+            "  Please wait...",
+            Style::default()
+                .fg(TEXT_SECONDARY)
+                .add_modifier(Modifier::ITALIC),
+        )),
+    ];
+
+    let paragraph = Paragraph::new(lines)
+        .alignment(Alignment::Left)
+        .style(Style::default().fg(TEXT_PRIMARY));
+
+    frame.render_widget(paragraph, inner);
+}
+
+/// Renders a path as a single sanitized line
+fn display_path(path: &std::path::Path) -> String {
+    sanitize_for_display(&path.display().to_string())
+}
+
+/// Sanitizes the text of every span in a styled preview line, keeping its styles
+fn sanitize_styled_line(line: &Line<'static>) -> Line<'static> {
+    if !line
+        .spans
+        .iter()
+        .any(|span| span.content.chars().any(|c| c.is_control()))
+    {
+        return line.clone();
+    }
+    let spans: Vec<Span<'static>> = line
+        .spans
+        .iter()
+        .map(|span| Span::styled(sanitize_preview_line(&span.content), span.style))
+        .collect();
+    Line::from(spans).style(line.style)
+}
+
+/// Builds the content block title, truncating the file name to fit the area
+fn content_title(file: &crate::domain::FileEntry, area: Rect) -> String {
+    // Leave room for the corners, padding and a possible "[!]" marker
+    let max_width = (area.width as usize).saturating_sub(8);
+    format!(" {} ", display_name(&file.name, max_width))
+}
+
+/// Header badge text for a file with usage history, e.g. " [opened 3× · 2024-03-02] "
+fn usage_badge(usage: &crate::usage::UsageInfo) -> String {
+    match usage.last_used {
+        Some(date) => format!(
+            " [opened {}× · {}] ",
+            usage.open_count,
+            date.format("%Y-%m-%d")
+        ),
+        None => format!(" [opened {}×] ", usage.open_count),
+    }
+}
+
+/// The header's queue widget, e.g. " 214 left · 38 img · 12 pdf · 1.9 GB "
+fn queue_stats_label(tally: &QueueTally) -> String {
+    let mut parts = vec![format!("{} left", tally.total.count)];
+    for (file_type, type_tally) in tally.by_type() {
+        let name = match file_type {
+            FileType::Text => "text",
+            FileType::Image => "img",
+            FileType::Pdf => "pdf",
+            FileType::Archive => "archives",
+            FileType::Audio => "audio",
+            FileType::Binary => "other",
+            FileType::Directory => "dirs",
+        };
+        parts.push(format!("{} {}", type_tally.count, name));
+    }
+    parts.push(format_file_size(tally.total.bytes));
+    format!(" {} ", parts.join(" · "))
+}
+
+/// What one column of the header's progress bar stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Segment {
+    /// Not reached yet
+    Pending,
+    /// Looked at and left undecided: a gap worth going back to
+    Skipped,
+    Keep,
+    Trash,
+    Move,
+}
+
+/// Queue positions drawn in progress bar column `column` of `width`; every
+/// position gets at least one column when the queue is shorter than the bar
+fn segment_range(column: usize, width: usize, len: usize) -> std::ops::Range<usize> {
+    let start = column * len / width;
+    let end = ((column + 1) * len / width).max(start + 1);
+    start.min(len)..end.min(len)
+}
+
+/// The progress bar, one segment per column in queue order. A column showing
+/// several files is `Skipped` if any of them was skipped, so gaps never hide;
+/// otherwise it shows what most of them are.
+pub fn progress_segments(state: &AppState, width: usize) -> Vec<Segment> {
+    if width == 0 {
+        return Vec::new();
+    }
+    let decisions: HashMap<usize, &Decision> = state
+        .decisions()
+        .iter()
+        .map(|(index, decision)| (*index, decision))
+        .collect();
+    (0..width)
+        .map(|column| {
+            // [Pending, Skipped, Keep, Trash, Move]
+            let mut counts = [0usize; 5];
+            for &index in &state.queue[segment_range(column, width, state.queue.len())] {
+                let slot = match decisions.get(&index) {
+                    Some(Decision::Keep) => 2,
+                    Some(Decision::Trash) => 3,
+                    Some(Decision::Move(_)) => 4,
+                    Some(Decision::Skip) => 1,
+                    None if state.is_seen(index) => 1,
+                    None => 0,
+                };
+                counts[slot] += 1;
+            }
+            if counts[1] > 0 {
+                return Segment::Skipped;
+            }
+            // Ties go to the later slot, so a trash doesn't hide behind a keep
+            let most = (0..5).max_by_key(|&slot| counts[slot]).unwrap_or(0);
+            [
+                Segment::Pending,
+                Segment::Skipped,
+                Segment::Keep,
+                Segment::Trash,
+                Segment::Move,
+            ][most]
+        })
+        .collect()
+}
+
+/// The queue position under a click at (`column`, `row`) when it lands on the
+/// header's progress bar in a frame of `area`
+pub fn queue_position_at(area: Rect, state: &AppState, column: u16, row: u16) -> Option<usize> {
+    // The bar is the row above the header's bottom border, inside the side borders
+    let bar_row = area.y + header_height(state) - 2;
+    let width = area.width.saturating_sub(2) as usize;
+    if row != bar_row || column <= area.x || state.queue.is_empty() {
+        return None;
+    }
+    let offset = (column - area.x - 1) as usize;
+    (offset < width).then(|| segment_range(offset, width, state.queue.len()).start)
+}
+
+/// Header height, with an extra row for the goal gauge when a goal is set
+fn header_height(state: &AppState) -> u16 {
+    if state.goal.is_some() {
+        5
+    } else {
+        4
+    }
+}
+
+/// Progress text for the session goal, e.g. "1.2 GB / 5.0 GB freed"
+fn goal_label(state: &AppState) -> String {
+    match state.goal {
+        Some(SessionGoal::FreeBytes(target)) => format!(
+            "{} / {} freed",
+            format_file_size(state.freed_bytes()),
+            format_file_size(target)
+        ),
+        Some(SessionGoal::ReviewFiles(target)) => {
+            format!("{} / {} files reviewed", state.decided_count(), target)
+        }
+        None => String::new(),
+    }
+}
+
+/// Helper to create a centered rect
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+/// Renders the polished header with progress bar
+fn render_header_polished(frame: &mut Frame, area: Rect, state: &AppState) {
+    let goal_rows = if state.goal.is_some() { 1 } else { 0 };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2),
+            Constraint::Length(goal_rows),
+            Constraint::Length(2),
+        ])
+        .split(area);
+
+    // Title and file info
+    let (title_text, badges, file_info) = if let Some(file) = state.current_file() {
+        let size_str = current_size_label(state);
+        let file_type = format!("{:?}", file.file_type);
+        let details = format!("({} • {})", size_str, file_type);
+        let mut badges = Vec::new();
+        if file.has_bidi_control() {
+            badges.push(Span::styled(
+                BIDI_WARNING,
+                Style::default()
+                    .fg(ACCENT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        if let Some(found) = state.current_sensitivity() {
+            badges.push(Span::styled(
+                format!(" [sensitive: {}] ", found),
+                Style::default()
+                    .fg(ACCENT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        if state.current_is_placeholder() {
+            badges.push(Span::styled(
+                PLACEHOLDER_BADGE,
+                Style::default().fg(ACCENT_HIGHLIGHT),
+            ));
+        } else if file.is_sparse() {
+            badges.push(Span::styled(
+                format!(
+                    " [sparse: {} on disk] ",
+                    format_file_size(file.allocated_size())
+                ),
+                Style::default().fg(TEXT_SECONDARY),
+            ));
+        }
+        if file.disk.shared > 0 {
+            badges.push(Span::styled(
+                format!(" [clone: {} shared] ", format_file_size(file.disk.shared)),
+                Style::default().fg(TEXT_SECONDARY),
+            ));
+        }
+        if file.has_other_links() {
+            badges.push(Span::styled(
+                format!(" [{} hard links] ", file.disk.links),
+                Style::default().fg(ACCENT_HIGHLIGHT),
+            ));
+        }
+        if file.has_lossy_name() {
+            badges.push(Span::styled(
+                NON_UTF8_BADGE,
+                Style::default().fg(ACCENT_HIGHLIGHT),
+            ));
+        }
+        if let Some(access) = state.current_access() {
+            if let Some(blocker) = access.blocker {
+                badges.push(Span::styled(
+                    format!("{}{} ", CANNOT_DELETE_PREFIX, blocker.reason()),
+                    Style::default()
+                        .fg(ACCENT_PRIMARY)
+                        .add_modifier(Modifier::BOLD),
+                ));
+            }
+            if let Some(ref ownership) = access.ownership {
+                badges.push(Span::styled(
+                    format!(" {}:{} ", ownership.owner, ownership.group),
+                    Style::default().fg(TEXT_SECONDARY),
+                ));
+            }
+        }
+        if let Some(usage) = state.current_usage() {
+            if usage.is_never_opened() {
+                badges.push(Span::styled(
+                    NEVER_OPENED_BADGE,
+                    Style::default().fg(ACCENT_HIGHLIGHT),
+                ));
+            } else {
+                badges.push(Span::styled(
+                    usage_badge(usage),
+                    Style::default().fg(TEXT_SECONDARY),
+                ));
+            }
+        }
+        if let Some(reason) = state.current_reason() {
+            badges.push(Span::styled(
+                format!(" [{}] ", sanitize_for_display(reason)),
+                Style::default().fg(ACCENT_PRIMARY),
+            ));
+        }
+        // An identical download copy outranks the per-type suggestion
+        let original = state.current_original();
+        if let Some(original) = original {
+            badges.push(Span::styled(
+                format!(
+                    " [identical copy of {}: suggest trash] ",
+                    display_name(&original.name, area.width as usize / 3)
+                ),
+                Style::default()
+                    .fg(ACCENT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        match state.current_default_decision() {
+            Some(DefaultDecision::SuggestKeep | DefaultDecision::SuggestTrash)
+                if original.is_some() => {}
+            Some(DefaultDecision::SuggestKeep) => badges.push(Span::styled(
+                " [suggest: keep] ",
+                Style::default().fg(ACCENT_SECONDARY),
+            )),
+            Some(DefaultDecision::SuggestTrash) => badges.push(Span::styled(
+                " [suggest: trash] ",
+                Style::default().fg(ACCENT_PRIMARY),
+            )),
+            Some(DefaultDecision::AlwaysAsk) => badges.push(Span::styled(
+                ALWAYS_ASK_BADGE,
+                Style::default().fg(ACCENT_HIGHLIGHT),
+            )),
+            None => {}
+        }
+        if state.phase == ReviewPhase::Triage {
+            badges.push(Span::styled(
+                TRIAGE_BADGE,
+                Style::default().fg(ACCENT_HIGHLIGHT),
+            ));
+        }
+        if state.phase == ReviewPhase::Skipped {
+            badges.push(Span::styled(
+                SKIPPED_PASS_BADGE,
+                Style::default().fg(ACCENT_HIGHLIGHT),
+            ));
+        }
+        if let Some(ref narrowing) = state.narrowing {
+            badges.push(Span::styled(
+                format!(
+                    " [{} · {} of {} files] ",
+                    narrowing.label,
+                    state.queue.len(),
+                    state.full_queue().len()
+                ),
+                Style::default().fg(ACCENT_HIGHLIGHT),
+            ));
+        }
+        if let Some(index) = state.current_file_index() {
+            if state.engine.is_applied(index) {
+                badges.push(Span::styled(
+                    IN_SYSTEM_TRASH_BADGE,
+                    Style::default().fg(ACCENT_PRIMARY),
+                ));
+            }
+            if state.is_seen(index) && !state.is_decided(index) {
+                badges.push(Span::styled(
+                    SEEN_BADGE,
+                    Style::default().fg(TEXT_SECONDARY),
+                ));
+            }
+            if state.is_unsure(index) && !state.is_decided(index) {
+                badges.push(Span::styled(
+                    UNSURE_BADGE,
+                    Style::default().fg(TEXT_SECONDARY),
+                ));
+            }
+        }
+        if !state.auto_advance {
+            badges.push(Span::styled(
+                AUTO_ADVANCE_OFF_BADGE,
+                Style::default().fg(TEXT_SECONDARY),
+            ));
+        }
+        // Borders and the gap before the details take 4 columns
+        let name_width = (area.width as usize).saturating_sub(display_width(&details) + 4);
+        (
+            format!(" File {}/{} ", state.current_index + 1, state.queue.len()),
+            badges,
+            vec![
+                Span::styled(
+                    display_name(&file.name, name_width),
+                    Style::default()
+                        .fg(TEXT_PRIMARY)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::raw("  "),
+                Span::styled(details, Style::default().fg(TEXT_SECONDARY)),
+            ],
+        )
+    } else {
+        (
+            " Fswp ".to_string(),
+            Vec::new(),
+            vec![Span::styled(
+                "No files to review",
+                Style::default().fg(TEXT_SECONDARY),
+            )],
+        )
+    };
+
+    let mut title_spans = vec![Span::styled(
+        title_text,
+        Style::default()
+            .fg(ACCENT_HIGHLIGHT)
+            .add_modifier(Modifier::BOLD),
+    )];
+    title_spans.extend(badges);
+    if state.engine.applied_count() > 0 {
+        title_spans.push(Span::styled(
+            format!(
+                " [applied: {} · pending: {}] ",
+                state.engine.applied_count(),
+                state.pending_trash_count()
+            ),
+            Style::default().fg(TEXT_SECONDARY),
+        ));
+    }
+    if let Some(ref fs_type) = state.degraded_fs {
+        title_spans.push(Span::styled(
+            format!(" [network fs: {} · degraded mode] ", fs_type),
+            Style::default().fg(ACCENT_HIGHLIGHT),
+        ));
+    }
+    if let Some(provider) = state.cloud_sync {
+        title_spans.push(Span::styled(
+            format!(" [{} folder · trash syncs to other devices] ", provider),
+            Style::default().fg(ACCENT_PRIMARY),
+        ));
+    }
+    if let Some(ref work) = state.background_work {
+        title_spans.push(Span::styled(
+            format!(" [{}] ", work),
+            Style::default().fg(TEXT_SECONDARY),
+        ));
+    }
+    // Notices often name a file, so they're escaped like the names themselves
+    if let Some(notice) = state.active_notice() {
+        title_spans.push(Span::styled(
+            format!(" [{}] ", sanitize_for_display(notice)),
+            Style::default().fg(ACCENT_PRIMARY),
+        ));
+    }
+    let title_line = Line::from(title_spans);
+
+    let info_line = Line::from(file_info);
+
+    let mut header_block = Block::default()
+        .borders(Borders::TOP | Borders::LEFT | Borders::RIGHT)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(BORDER_COLOR));
+    if state.show_queue_stats {
+        header_block = header_block.title_top(
+            Line::from(Span::styled(
+                queue_stats_label(state.engine.undecided()),
+                Style::default().fg(TEXT_SECONDARY),
+            ))
+            .right_aligned(),
+        );
+    }
+    let header = Paragraph::new(vec![title_line, info_line])
+        .block(header_block)
+        .alignment(Alignment::Left);
+
+    frame.render_widget(header, chunks[0]);
+
+    // Progress bar: a segment per file (or per run of files) in queue order,
+    // with the overall percentage in the bottom border
+    let total = state.files.len();
+    let processed = state.decided_count();
+    let progress = if total > 0 {
+        processed as f64 / total as f64
+    } else {
+        0.0
+    };
+    let block = Block::default()
+        .borders(Borders::BOTTOM | Borders::LEFT | Borders::RIGHT)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(BORDER_COLOR))
+        .title_bottom(
+            Line::from(format!(
+                " {}% ({}/{}) ",
+                (progress * 100.0) as u16,
+                processed,
+                total
+            ))
+            .centered(),
+        );
+    let bar_area = block.inner(chunks[2]);
+    let current = (!state.queue.is_empty()).then(|| {
+        let width = bar_area.width as usize;
+        (0..width)
+            .find(|&column| {
+                segment_range(column, width, state.queue.len()).contains(&state.current_index)
+            })
+            .unwrap_or(0)
+    });
+    let segments: Vec<Span> = progress_segments(state, bar_area.width as usize)
+        .into_iter()
+        .enumerate()
+        .map(|(column, segment)| {
+            let (symbol, color) = match segment {
+                Segment::Pending => ("·", BORDER_COLOR),
+                Segment::Skipped => ("▒", TEXT_SECONDARY),
+                Segment::Keep => ("█", ACCENT_SECONDARY),
+                Segment::Trash => ("█", ACCENT_PRIMARY),
+                Segment::Move => ("█", ACCENT_HIGHLIGHT),
+            };
+            let mut style = Style::default().fg(color).bg(BG_DARK);
+            if current == Some(column) {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+            Span::styled(symbol, style)
+        })
+        .collect();
+
+    frame.render_widget(block, chunks[2]);
+    frame.render_widget(Paragraph::new(Line::from(segments)), bar_area);
+
+    if let Some(goal_progress) = state.goal_progress() {
+        let goal_gauge = Gauge::default()
+            .block(
+                Block::default()
+                    .borders(Borders::LEFT | Borders::RIGHT)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(BORDER_COLOR)),
+            )
+            .gauge_style(Style::default().fg(ACCENT_HIGHLIGHT).bg(BG_DARK))
+            .ratio(goal_progress)
+            .label(format!("Goal: {}", goal_label(state)));
+
+        frame.render_widget(goal_gauge, chunks[1]);
+    }
+}
+
+/// Renders the main content area (synchronous version)
+fn render_content(frame: &mut Frame, area: Rect, state: &AppState) {
+    use crate::preview::PreviewContent;
+
+    let content = if let Some(file) = state.current_file() {
+        // Generate file preview
+        let lines: Vec<Line> = match preview::generate_preview(file) {
+            Ok(PreviewContent::Text(text_lines)) => text_lines
+                .iter()
+                .map(|s| Line::from(sanitize_preview_line(s)))
+                .collect(),
+            Ok(PreviewContent::Styled(styled_lines)) => {
+                styled_lines.iter().map(sanitize_styled_line).collect()
+            }
+            Err(e) => vec![
+                Line::from(format!(
+                    "Error generating preview: {}",
+                    sanitize_for_display(&e.to_string())
+                )),
+                Line::from(""),
+                Line::from(format!("File: {}", sanitize_for_display(&file.name))),
+                Line::from(format!("Path: {}", display_path(&file.path))),
+                Line::from(format!("Size: {} bytes", file.size)),
+                Line::from(format!("Type: {:?}", file.file_type)),
+            ],
+        };
+
+        Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(BORDER_COLOR))
+                    .title(content_title(file, area)),
+            )
+            .style(Style::default().fg(TEXT_PRIMARY))
+            .wrap(Wrap { trim: false })
+    } else {
+        render_empty_state_widget()
+    };
+
+    frame.render_widget(content, area);
+}
+
+/// Renders the metadata-only card used during the triage pass
+fn render_triage_card(frame: &mut Frame, area: Rect, state: &AppState) {
+    let Some(file) = state.current_file() else {
+        frame.render_widget(render_empty_state_widget(), area);
+        return;
+    };
+
+    let label = |text: &'static str| Span::styled(text, Style::default().fg(TEXT_SECONDARY));
+    let lines = vec![
+        Line::from(""),
+        Line::from(vec![
+            label("  Name:     "),
+            Span::styled(
+                sanitize_for_display(&file.name),
+                Style::default()
+                    .fg(TEXT_PRIMARY)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+        Line::from(vec![
+            label("  Size:     "),
+            Span::raw(current_size_label(state)),
+        ]),
+        Line::from(vec![
+            label("  Type:     "),
+            Span::raw(format!("{:?}", file.file_type)),
+        ]),
+        Line::from(vec![
+            label("  Modified: "),
+            Span::raw(file.modified_date.format("%Y-%m-%d %H:%M").to_string()),
+        ]),
+        Line::from(vec![
+            label("  Path:     "),
+            Span::raw(display_path(&file.path)),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled("  k ", Style::default().fg(ACCENT_SECONDARY)),
+            label("keep   "),
+            Span::styled("t ", Style::default().fg(ACCENT_PRIMARY)),
+            label("trash   "),
+            Span::styled("m ", Style::default().fg(ACCENT_HIGHLIGHT)),
+            label("unsure (preview later)"),
+        ]),
+    ];
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(BORDER_COLOR))
+                .title(" Triage "),
+        )
+        .style(Style::default().fg(TEXT_PRIMARY))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(paragraph, area);
+}
+
+/// Creates an empty state widget for when no files are present
+fn render_empty_state_widget() -> Paragraph<'static> {
+    let lines = vec![
+        Line::from(""),
+        Line::from(""),
+        Line::from(Span::styled(
+            "No Files Found",
+            Style::default()
+                .fg(ACCENT_HIGHLIGHT)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "The directory is empty or contains only hidden files.",
+            Style::default().fg(TEXT_SECONDARY),
+        )),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Try a different directory with visible files.",
+            Style::default().fg(TEXT_SECONDARY),
+        )),
+    ];
+
+    Paragraph::new(lines)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_type(BorderType::Rounded)
+                .border_style(Style::default().fg(BORDER_COLOR))
+                .title(" Content "),
+        )
+        .alignment(Alignment::Center)
+}
+
+/// Width the content area needs before a pinned preview is shown beside the current one
+const PINNED_SPLIT_MIN_WIDTH: u16 = 60;
+
+/// Preview content as display lines, stripped of control characters; with
+/// `redact`, lines that look sensitive are blacked out
+fn preview_lines(content: &preview::PreviewContent, redact: bool) -> Vec<Line<'static>> {
+    match content {
+        preview::PreviewContent::Text(text_lines) => text_lines
+            .iter()
+            .map(|s| match sensitive::in_line(s).filter(|_| redact) {
+                Some(found) => redacted_line(s, found),
+                None => Line::from(sanitize_preview_line(s)),
+            })
+            .collect(),
+        preview::PreviewContent::Styled(styled_lines) => {
+            styled_lines.iter().map(sanitize_styled_line).collect()
+        }
+    }
+}
+
+/// A preview as the lines the review pane shows, for printing outside the TUI
+/// (`fswp preview`); with `color`, styles become ANSI escape codes
+pub fn preview_to_ansi(
+    content: &preview::PreviewContent,
+    redact: bool,
+    color: bool,
+) -> Vec<String> {
+    use crossterm::style::{Attribute, Attributes, ContentStyle};
+
+    preview_lines(content, redact)
+        .iter()
+        .map(|line| {
+            line.spans
+                .iter()
+                .map(|span| {
+                    if !color {
+                        return span.content.to_string();
+                    }
+                    let style = line.style.patch(span.style);
+                    let mut attributes = Attributes::default();
+                    for (modifier, attribute) in [
+                        (Modifier::BOLD, Attribute::Bold),
+                        (Modifier::DIM, Attribute::Dim),
+                        (Modifier::ITALIC, Attribute::Italic),
+                        (Modifier::UNDERLINED, Attribute::Underlined),
+                        (Modifier::REVERSED, Attribute::Reverse),
+                        (Modifier::CROSSED_OUT, Attribute::CrossedOut),
+                    ] {
+                        if style.add_modifier.contains(modifier) {
+                            attributes.set(attribute);
+                        }
+                    }
+                    let style = ContentStyle {
+                        foreground_color: style.fg.map(Into::into),
+                        background_color: style.bg.map(Into::into),
+                        underline_color: None,
+                        attributes,
+                    };
+                    style.apply(span.content.as_ref()).to_string()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Rows `lines` take wrapped to `width` columns; word wrapping can take a few more
+fn wrapped_rows(lines: &[Line], width: u16) -> u16 {
+    let width = usize::from(width.max(1));
+    let rows: usize = lines
+        .iter()
+        .map(|line| line.width().div_ceil(width).max(1))
+        .sum();
+    u16::try_from(rows).unwrap_or(u16::MAX)
+}
+
+/// The preview pane's scroll position, e.g. " 20–39 of 300 · PgUp/PgDn "
+fn scroll_position_label(scroll: preview::PreviewScroll) -> String {
+    let last = scroll.offset.saturating_add(scroll.page).min(scroll.rows);
+    format!(
+        " {}–{} of {} · PgUp/PgDn ",
+        scroll.offset + 1,
+        last,
+        scroll.rows
+    )
+}
+
+/// A sensitive preview line blacked out, keeping its indentation, with what was hidden
+fn redacted_line(line: &str, found: Sensitivity) -> Line<'static> {
+    let indent = line.len() - line.trim_start().len();
+    let hidden = line.trim().chars().count().clamp(4, 40);
+    Line::from(vec![
+        Span::raw(" ".repeat(indent.min(40))),
+        Span::styled("█".repeat(hidden), Style::default().fg(BORDER_COLOR)),
+        Span::styled(
+            format!(" {} hidden · {} reveals", found, input::key_hint("R")),
+            Style::default().fg(TEXT_SECONDARY),
+        ),
+    ])
+}
+
+/// Renders the pinned file's preview in the left pane of a comparison
+fn render_pinned_preview(
+    frame: &mut Frame,
+    area: Rect,
+    file: &FileEntry,
+    preview_manager: &mut SyncPreviewManager,
+) {
+    let max_width = (area.width as usize).saturating_sub(16);
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(ACCENT_HIGHLIGHT))
+        .title(format!(" Pinned: {} ", display_name(&file.name, max_width)));
+    let lines = match preview_manager.pinned_preview(file) {
+        PreviewState::Loading => vec![Line::from(Span::styled(
+            "  Loading preview…",
+            Style::default().fg(TEXT_SECONDARY),
+        ))],
+        PreviewState::Ready(content) => preview_lines(content, true),
+        PreviewState::Error(e) => vec![Line::from(Span::styled(
+            format!("  [!] {}", sanitize_for_display(e)),
+            Style::default().fg(ACCENT_PRIMARY),
+        ))],
+    };
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .style(Style::default().fg(TEXT_PRIMARY))
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+/// Renders the main content area with async preview loading, split with the
+/// `pinned` file's preview on the left when another file is current
+fn render_content_async(
+    frame: &mut Frame,
+    mut area: Rect,
+    state: &AppState,
+    preview_manager: &mut SyncPreviewManager,
+    pinned: Option<&FileEntry>,
+) {
+    if let Some(file) = state.current_file() {
+        if let Some(pinned) = pinned.filter(|pinned| pinned.path != file.path) {
+            if area.width >= PINNED_SPLIT_MIN_WIDTH {
+                let columns = Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                    .split(area);
+                render_pinned_preview(frame, columns[0], pinned, preview_manager);
+                area = columns[1];
+            }
+        }
+
+        // Get preview state from manager
+        let preview_state = preview_manager.request_preview(file);
+
+        match preview_state {
+            PreviewState::Loading => {
+                // Render an empty block for content first
+                let content_block = Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(BORDER_COLOR))
+                    .title(content_title(file, area));
+                frame.render_widget(content_block, area);
+
+                // Then render the loading overlay
+                render_loading_overlay(frame, file);
+            }
+            PreviewState::Ready(preview_content) => {
+                let lines = preview_lines(preview_content, !state.current_revealed());
+                let page = area.height.saturating_sub(2);
+                let rows = wrapped_rows(&lines, area.width.saturating_sub(2));
+                let scroll = preview_manager.set_viewport(page, rows);
+                let mut block = Block::default()
+                    .borders(Borders::ALL)
+                    .border_type(BorderType::Rounded)
+                    .border_style(Style::default().fg(BORDER_COLOR))
+                    .title(content_title(file, area));
+                if scroll.overflows() {
+                    block = block.title_bottom(
+                        Line::from(Span::styled(
+                            scroll_position_label(scroll),
+                            Style::default().fg(TEXT_SECONDARY),
+                        ))
+                        .right_aligned(),
+                    );
+                }
+                let paragraph = Paragraph::new(lines)
+                    .block(block)
+                    .style(Style::default().fg(TEXT_PRIMARY))
+                    .wrap(Wrap { trim: false })
+                    .scroll((scroll.offset, 0));
+                frame.render_widget(paragraph, area);
+            }
+            PreviewState::Error(e) => {
+                let error_lines: Vec<Line> = vec![
+                    Line::from(""),
+                    Line::from(Span::styled(
+                        "  [!] Error generating preview",
+                        Style::default()
+                            .fg(ACCENT_PRIMARY)
+                            .add_modifier(Modifier::BOLD),
+                    )),
+                    Line::from(""),
+                    Line::from(format!("  {}", sanitize_for_display(e))),
+                    Line::from(""),
+                    Line::from(format!("  File: {}", sanitize_for_display(&file.name))),
+                    Line::from(format!("  Path: {}", display_path(&file.path))),
+                    Line::from(format!("  Size: {}", format_file_size(file.size))),
+                    Line::from(format!("  Type: {:?}", file.file_type)),
+                ];
+
+                let paragraph = Paragraph::new(error_lines)
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .border_type(BorderType::Rounded)
+                            .border_style(Style::default().fg(ACCENT_PRIMARY))
+                            .title(format!("{}[!] ", content_title(file, area))),
+                    )
+                    .style(Style::default().fg(TEXT_PRIMARY))
+                    .wrap(Wrap { trim: false });
+                frame.render_widget(paragraph, area);
+            }
+        }
+    } else {
+        frame.render_widget(render_empty_state_widget(), area);
+    }
+}
+
+/// Keys valid in a view, as (key, label, key color) for the footer
+pub fn footer_hints(view: &ViewState) -> Vec<(&'static str, &'static str, ratatui::style::Color)> {
+    match view {
+        ViewState::Browsing => vec![
+            ("←", "Trash", ACCENT_PRIMARY),
+            ("→", "Keep", ACCENT_SECONDARY),
+            ("↑↓", "Navigate", TEXT_SECONDARY),
+            (input::key_hint("u"), "Undo", ACCENT_HIGHLIGHT),
+            ("?", "Help", TEXT_SECONDARY),
+            (input::key_hint("q"), "Quit", TEXT_SECONDARY),
+        ],
+        ViewState::Help => vec![
+            ("type", "Search", TEXT_SECONDARY),
+            ("↑↓ PgUp/PgDn", "Scroll", TEXT_SECONDARY),
+            ("Esc", "Clear / close", TEXT_SECONDARY),
+        ],
+        ViewState::Summary => vec![
+            ("s", "Review skipped", ACCENT_SECONDARY),
+            ("k / t", "Copy kept / trashed paths", TEXT_SECONDARY),
+            ("any key", "Review and apply", ACCENT_HIGHLIGHT),
+        ],
+        ViewState::ConfirmTrash => vec![
+            ("y / Enter", "Confirm", ACCENT_PRIMARY),
+            ("n / Esc", "Back", TEXT_SECONDARY),
+        ],
+        ViewState::Welcome => vec![("any key", "Start reviewing", ACCENT_HIGHLIGHT)],
+        ViewState::GoalReached => vec![
+            ("y / Enter", "Apply and stop", ACCENT_SECONDARY),
+            ("n / Esc", "Keep going", TEXT_SECONDARY),
+        ],
+        ViewState::Applying => vec![
+            ("q / Esc", "Cancel", ACCENT_PRIMARY),
+            ("any key", "Exit when done", TEXT_SECONDARY),
+        ],
+        ViewState::Setup => vec![
+            ("↑↓", "Choose", TEXT_SECONDARY),
+            ("Enter", "Confirm", ACCENT_SECONDARY),
+            ("Esc", "Use defaults", TEXT_SECONDARY),
+        ],
+        ViewState::SelectSimilar => vec![
+            ("←", "Trash all", ACCENT_PRIMARY),
+            ("→", "Keep all", ACCENT_SECONDARY),
+            ("1-9", "Move all", ACCENT_HIGHLIGHT),
+            ("Esc", "Back", TEXT_SECONDARY),
+        ],
+        ViewState::Filter => vec![
+            ("type", "Filter", TEXT_SECONDARY),
+            ("Tab", "Next preset", TEXT_SECONDARY),
+            ("Enter", "Keep filter", ACCENT_HIGHLIGHT),
+            ("Esc", "Clear filter", TEXT_SECONDARY),
+        ],
+        ViewState::EasyWins => vec![
+            ("↑↓", "Choose", TEXT_SECONDARY),
+            ("← / →", "Trash / review", ACCENT_PRIMARY),
+            ("Enter", "Trash marked", ACCENT_HIGHLIGHT),
+            ("Esc", "Skip", TEXT_SECONDARY),
+        ],
+        ViewState::Duplicates => vec![
+            ("↑↓", "Choose", TEXT_SECONDARY),
+            ("Enter", "Keep it, trash the rest", ACCENT_HIGHLIGHT),
+            ("→", "Review later", ACCENT_SECONDARY),
+            ("Esc", "Skip all", TEXT_SECONDARY),
+        ],
+        ViewState::Overview => vec![("any key", "Back", TEXT_SECONDARY)],
+        ViewState::RapidDecisions => vec![
+            ("y / Enter", "Undo them all", ACCENT_PRIMARY),
+            ("n / Esc", "Keep them", TEXT_SECONDARY),
+        ],
+        ViewState::PreviewError => vec![
+            ("b", "Save for a bug report", ACCENT_HIGHLIGHT),
+            ("any key", "Back", TEXT_SECONDARY),
+        ],
+        // The pause screen replaces the whole frame, footer included
+        ViewState::Paused => Vec::new(),
+        ViewState::Renames => vec![
+            ("↑↓", "Choose", TEXT_SECONDARY),
+            ("← / →", "Leave / rename", ACCENT_HIGHLIGHT),
+            ("Enter", "Rename marked and apply", ACCENT_SECONDARY),
+            ("Esc", "Apply without renaming", TEXT_SECONDARY),
+        ],
+        ViewState::Review => vec![
+            ("↑↓", "Choose", TEXT_SECONDARY),
+            ("← / →", "Trash / keep", ACCENT_PRIMARY),
+            ("Enter", "Apply", ACCENT_HIGHLIGHT),
+            ("Esc", "Back to summary", TEXT_SECONDARY),
+        ],
+        ViewState::Inspector => vec![
+            ("↑↓", "Choose", TEXT_SECONDARY),
+            ("Enter", "Turn off / change", ACCENT_HIGHLIGHT),
+            ("Esc", "Back", TEXT_SECONDARY),
+        ],
+        ViewState::MoveConflict => vec![
+            ("r", "Rename", ACCENT_HIGHLIGHT),
+            ("o", "Overwrite", ACCENT_PRIMARY),
+            ("c", "Compare", TEXT_SECONDARY),
+            ("s / Esc", "Skip", TEXT_SECONDARY),
+        ],
+        ViewState::Search => vec![
+            ("type", "Search text", TEXT_SECONDARY),
+            ("Enter", "Search (empty: show all)", ACCENT_HIGHLIGHT),
+            ("Esc", "Cancel", TEXT_SECONDARY),
+        ],
+    }
+}
+
+/// The quick-move targets by key and folder name, e.g. "1 Receipts · 2 Keep"
+fn move_targets_label(targets: &[(u8, PathBuf)]) -> String {
+    targets
+        .iter()
+        .map(|(slot, dir)| {
+            let name = dir.file_name().map_or_else(
+                || dir.display().to_string(),
+                |name| name.to_string_lossy().into_owned(),
+            );
+            format!("{} {}", slot, sanitize_for_display(&name))
+        })
+        .collect::<Vec<_>>()
+        .join(" · ")
+}
+
+/// Renders the polished footer with the styled controls for the current view,
+/// and while browsing the quick-move targets in its top border
+fn render_footer_polished(
+    frame: &mut Frame,
+    area: Rect,
+    view: &ViewState,
+    move_targets: &[(u8, PathBuf)],
+) {
+    let mut spans = Vec::new();
+    for (i, (key, label, color)) in footer_hints(view).into_iter().enumerate() {
+        spans.push(Span::raw(if i == 0 { " " } else { "  │  " }));
+        spans.push(Span::styled(
+            format!("{} ", key),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ));
+        spans.push(Span::styled(label, Style::default().fg(TEXT_SECONDARY)));
+    }
+    let controls = Line::from(spans);
+
+    let mut block = Block::default()
+        .borders(Borders::ALL)
+        .border_type(BorderType::Rounded)
+        .border_style(Style::default().fg(BORDER_COLOR));
+    if *view == ViewState::Browsing && !move_targets.is_empty() {
+        block = block.title_top(Line::from(Span::styled(
+            format!(" Move: {} ", move_targets_label(move_targets)),
+            Style::default().fg(ACCENT_HIGHLIGHT),
+        )));
+    }
+    let footer = Paragraph::new(controls)
+        .block(block)
+        .alignment(Alignment::Center);
+
+    frame.render_widget(footer, area);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{FileEntry, FileType};
+    use chrono::{TimeZone, Utc};
+    use ratatui::{backend::TestBackend, Terminal};
+    use std::path::PathBuf;
+
+    fn create_test_entry(name: &str) -> FileEntry {
+        FileEntry {
+            path: PathBuf::from(name),
+            name: name.to_string(),
+            size: 1024,
+            modified_date: Utc::now(),
+            file_type: FileType::Text,
+            disk: Default::default(),
+        }
+    }
+
+    mod layout_tests {
+        use super::*;
+
+        #[test]
+        fn test_render_empty_state() {
+            let state = AppState::new(vec![]);
+            let backend = TestBackend::new(80, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            // Verify no panics and rendering succeeds
+            let buffer = terminal.backend().buffer().clone();
+            let content = buffer.content();
+
+            // Check for empty state message in content
+            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
+            // The new empty state shows "No Files Found" or contains "empty"
+            assert!(
+                buffer_str.contains("No Files") || buffer_str.contains("empty"),
+                "Expected empty state message, got: {}",
+                buffer_str
+            );
+        }
+
+        #[test]
+        fn test_render_explorer_rows_and_shares() {
+            let temp_dir = tempfile::TempDir::new().unwrap();
+            std::fs::create_dir(temp_dir.path().join("videos")).unwrap();
+            std::fs::write(temp_dir.path().join("videos/a.mp4"), vec![0; 3000]).unwrap();
+            std::fs::write(temp_dir.path().join("notes.txt"), vec![0; 1000]).unwrap();
+            let mut explorer = crate::explorer::Explorer::new(temp_dir.path(), false).unwrap();
+            while explorer.is_measuring() {
+                explorer.poll();
+                std::thread::sleep(std::time::Duration::from_millis(5));
+            }
+
+            let mut terminal = Terminal::new(TestBackend::new(80, 12)).unwrap();
+            terminal
+                .draw(|frame| render_explorer(frame, &explorer))
+                .unwrap();
+            let lines: Vec<String> = terminal
+                .backend()
+                .buffer()
+                .content()
+                .chunks(80)
+                .map(|row| row.iter().map(|c| c.symbol()).collect())
+                .collect();
+            assert!(lines[0].contains(" Explore: "));
+            assert!(lines[1].contains("in 2 files, 1 folders"), "{}", lines[1]);
+            assert!(lines[3].contains(" 75%  videos/"), "{}", lines[3]);
+            assert!(lines[4].contains(" 25%  (files here)"), "{}", lines[4]);
+            assert!(lines[11].contains("r review"));
+        }
+
+        #[test]
+        fn test_render_empty_trash_groups_and_confirmation() {
+            use crate::retention::{EmptyTrash, TrashedFile};
+            let now = Utc::now();
+            let trashed = [
+                TrashedFile {
+                    path: PathBuf::from("/tmp/big.iso"),
+                    deleted: now - chrono::Duration::days(120),
+                },
+                TrashedFile {
+                    path: PathBuf::from("/tmp/notes.txt"),
+                    deleted: now - chrono::Duration::days(1),
+                },
+            ];
+            let applied = |item: &TrashedFile, size| {
+                vec![crate::retention::TrashRecord {
+                    size,
+                    time: item.deleted,
+                }]
+            };
+            let ours = [
+                (
+                    PathBuf::from("/tmp/big.iso"),
+                    applied(&trashed[0], 3 * 1024 * 1024),
+                ),
+                (PathBuf::from("/tmp/notes.txt"), applied(&trashed[1], 2048)),
+            ]
+            .into_iter()
+            .collect();
+            let mut list = EmptyTrash::new(&trashed, &ours, |_, _| true, |_| None, now);
+            list.toggle();
+            list.confirming = true;
+
+            let mut terminal = Terminal::new(TestBackend::new(90, 10)).unwrap();
+            terminal
+                .draw(|frame| render_empty_trash(frame, &list, now))
+                .unwrap();
+            let lines: Vec<String> = terminal
+                .backend()
+                .buffer()
+                .content()
+                .chunks(90)
+                .map(|row| row.iter().map(|c| c.symbol()).collect())
+                .collect();
+            assert!(lines[1].contains("2 files fswp trashed (3.0 MB) · 1 selected (3.0 MB)"));
+            assert!(lines[3].contains("Over 3 months ago · 1 files · 3.0 MB · 1 selected"));
+            assert!(
+                lines[4].contains("[x]     3.0 MB   120d  /tmp/big.iso"),
+                "{}",
+                lines[4]
+            );
+            assert!(lines[5].contains("This week"));
+            assert!(lines[6].contains("[ ]"));
+            assert!(lines[9].contains("Permanently delete 1 files (3.0 MB)?"));
+        }
+
+        #[test]
+        fn test_render_with_files() {
+            let files = vec![
+                create_test_entry("file1.txt"),
+                create_test_entry("file2.rs"),
+            ];
+            let state = AppState::new(files);
+            let backend = TestBackend::new(80, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let content = buffer.content();
+            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
+
+            // Check for file info in buffer
+            assert!(buffer_str.contains("file1.txt"));
+            assert!(buffer_str.contains("File 1/2"));
+        }
+
+        #[test]
+        fn test_render_footer() {
+            let state = AppState::new(vec![create_test_entry("test.txt")]);
+            let backend = TestBackend::new(80, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let content = buffer.content();
+            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
+
+            // Check for controls in footer
+            assert!(buffer_str.contains("Trash"));
+            assert!(buffer_str.contains("Keep"));
+            assert!(buffer_str.contains("Quit"));
+        }
+
+        #[test]
+        fn test_render_footer_follows_view_state() {
+            let state = AppState::new(vec![create_test_entry("test.txt")]);
+            let mut preview_manager = SyncPreviewManager::new();
+            let backend = TestBackend::new(80, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render_with_preview(
+                        frame,
+                        &state,
+                        &mut preview_manager,
+                        &ViewState::ConfirmTrash,
+                        false,
+                        None,
+                    );
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let footer: String = (0..80).map(|x| buffer[(x, 22)].symbol()).collect();
+            assert!(footer.contains("y / Enter Confirm"));
+            assert!(footer.contains("n / Esc Back"));
+            assert!(!footer.contains("Navigate"));
+        }
+
+        #[test]
+        fn test_render_preview_scrolls() {
+            let temp_dir = tempfile::TempDir::new().unwrap();
+            let path = temp_dir.path().join("long.txt");
+            let text: String = (1..=100).map(|i| format!("row {:03}\n", i)).collect();
+            std::fs::write(&path, text).unwrap();
+            let state = AppState::new(vec![FileEntry::from_path(&path).unwrap()]);
+            let mut preview_manager = SyncPreviewManager::new();
+            let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+            let mut draw = |preview_manager: &mut SyncPreviewManager| {
+                terminal
+                    .draw(|frame| render_focus(frame, &state, preview_manager))
+                    .unwrap();
+                let buffer = terminal.backend().buffer().clone();
+                buffer
+                    .content()
+                    .iter()
+                    .map(|c| c.symbol())
+                    .collect::<String>()
+            };
+
+            let mut screen = draw(&mut preview_manager);
+            for _ in 0..40 {
+                if screen.contains("row 001") {
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(50));
+                screen = draw(&mut preview_manager);
+            }
+            assert!(screen.contains("row 001"));
+            assert!(screen.contains(" of 100 · PgUp/PgDn "));
+
+            preview_manager.scroll_preview(crate::preview::ScrollCommand::PageDown);
+            let screen = draw(&mut preview_manager);
+            assert!(!screen.contains("row 001"));
+            let page = preview_manager.preview_scroll().page;
+            assert!(screen.contains(&format!("row {:03}", page)));
+
+            preview_manager.reset();
+            assert_eq!(preview_manager.preview_scroll().offset, 0);
+        }
+
+        #[test]
+        fn test_queue_window() {
+            assert_eq!(queue_window(100_000, 0, 20), 0..20);
+            assert_eq!(queue_window(100_000, 50_000, 20), 49_990..50_010);
+            assert_eq!(queue_window(100_000, 99_999, 20), 99_980..100_000);
+            assert_eq!(queue_window(5, 3, 20), 0..5);
+            assert_eq!(queue_window(0, 0, 20), 0..0);
+        }
+
+        #[test]
+        fn test_render_queue_panel_with_large_queue() {
+            let files: Vec<FileEntry> = (0..100_000)
+                .map(|i| create_test_entry(&format!("file{:06}.txt", i)))
+                .collect();
+            let mut state = AppState::new(files);
+            state.record_decision(Decision::Keep).unwrap();
+            state.goto(99_998);
+            state.record_decision(Decision::Keep).unwrap();
+            state.goto(99_999);
+
+            let mut preview_manager = SyncPreviewManager::new();
+            let backend = TestBackend::new(100, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+            terminal
+                .draw(|frame| {
+                    render_with_preview(
+                        frame,
+                        &state,
+                        &mut preview_manager,
+                        &ViewState::Browsing,
+                        true,
+                        None,
+                    )
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let panel: Vec<String> = (0..24)
+                .map(|y| (68..100).map(|x| buffer[(x, y)].symbol()).collect())
+                .collect();
+            let text = panel.join("\n");
+            assert!(text.contains("Queue 100000/100000"), "{}", text);
+            assert!(text.contains("✓ file099998.txt"), "{}", text);
+            assert!(text.contains("file099999.txt"), "{}", text);
+            assert!(!text.contains("file000000.txt"), "{}", text);
+        }
+
+        #[test]
+        fn test_render_overview_shows_ages_and_types() {
+            let mut old = create_test_entry("old.zip");
+            old.file_type = FileType::Binary;
+            old.size = 5 * 1024 * 1024;
+            old.modified_date = Utc::now() - chrono::Duration::days(1000);
+            let files = vec![create_test_entry("notes.txt"), old];
+            let overview = crate::domain::ScanOverview::new(&files, Utc::now());
+
+            let backend = TestBackend::new(100, 30);
+            let mut terminal = Terminal::new(backend).unwrap();
+            terminal
+                .draw(|frame| render_overview(frame, &overview))
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let screen: String = (0..30)
+                .map(|y| {
+                    (0..100)
+                        .map(|x| buffer[(x, y)].symbol())
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            assert!(screen.contains("Scan Overview"), "{}", screen);
+            assert!(screen.contains("<1w 1"), "{}", screen);
+            assert!(screen.contains("<5y 1"), "{}", screen);
+            assert!(screen.contains("5.0 MB, 1 files"), "{}", screen);
+            let binary = screen.find("Binary").unwrap();
+            let text = screen.find("Text").unwrap();
+            assert!(binary < text, "{}", screen);
+        }
+
+        #[test]
+        fn test_render_pinned_preview_beside_current() {
+            let mut state = AppState::new(vec![
+                create_test_entry("draft.txt"),
+                create_test_entry("final.txt"),
+            ]);
+            let pinned = state.files[0].clone();
+            let mut preview_manager = SyncPreviewManager::new();
+            let backend = TestBackend::new(100, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+            let mut draw = |state: &AppState| {
+                terminal
+                    .draw(|frame| {
+                        render_with_preview(
+                            frame,
+                            state,
+                            &mut preview_manager,
+                            &ViewState::Browsing,
+                            false,
+                            Some(&pinned),
+                        )
+                    })
+                    .unwrap();
+                let buffer = terminal.backend().buffer().clone();
+                (0..24)
+                    .map(|y| {
+                        (0..100)
+                            .map(|x| buffer[(x, y)].symbol())
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+
+            // No split while the pinned file is the current one
+            let screen = draw(&state);
+            assert!(!screen.contains("Pinned:"), "{}", screen);
+
+            state.next();
+            let screen = draw(&state);
+            let row = screen
+                .lines()
+                .find(|row| row.contains("Pinned: draft.txt"))
+                .unwrap();
+            assert!(row.find("Pinned").unwrap() < row.find("final.txt").unwrap());
+        }
+
+        #[test]
+        fn test_render_focus_hides_header_and_footer() {
+            let state = AppState::new(vec![create_test_entry("notes.txt")]);
+            let mut preview_manager = SyncPreviewManager::new();
+            let backend = TestBackend::new(80, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| render_focus(frame, &state, &mut preview_manager))
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let row = |y| (0..80).map(|x| buffer[(x, y)].symbol()).collect::<String>();
+            assert!(row(0).contains("notes.txt  1.0 KB"));
+            assert!(row(23).starts_with(" ← Trash"));
+            assert!(row(23).trim_end().ends_with("Keep →"));
+            let all: String = (0..24).map(row).collect();
+            assert!(!all.contains("Quit"));
+            assert!(!all.contains("(1/1)"));
+        }
+
+        #[test]
+        fn test_render_header_progress() {
+            let files = vec![
+                create_test_entry("file1.txt"),
+                create_test_entry("file2.txt"),
+                create_test_entry("file3.txt"),
+            ];
+            let mut state = AppState::new(files);
+            state.next();
+
+            let backend = TestBackend::new(80, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let content = buffer.content();
+            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
+
+            // Check that we're on file 2 of 3
+            assert!(buffer_str.contains("File 2/3"));
+            assert!(buffer_str.contains("file2.txt"));
+        }
+
+        #[test]
+        fn test_render_header_truncates_long_name() {
+            let long_name = format!("{}-final.pdf", "very-long-download-name".repeat(5));
+            let state = AppState::new(vec![create_test_entry(&long_name)]);
+            let backend = TestBackend::new(80, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let content = buffer.content();
+            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
+
+            // The extension stays visible even though the name is too long
+            assert!(buffer_str.contains("…"));
+            assert!(buffer_str.contains("final.pdf"));
+        }
+
+        #[test]
+        fn test_render_header_warns_about_bidi_override() {
+            let state = AppState::new(vec![create_test_entry("invoice\u{202E}fdp.exe")]);
+            let backend = TestBackend::new(80, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let content = buffer.content();
+            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
+
+            assert!(buffer_str.contains("RTL override"));
+            assert!(!buffer_str.contains('\u{202E}'));
+        }
+
+        #[test]
+        fn test_render_hostile_name_is_escaped() {
+            let state = AppState::new(vec![create_test_entry("evil\x1b[2J\nname.txt")]);
+            let backend = TestBackend::new(80, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let content = buffer.content();
+            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
+
+            assert!(!buffer_str.chars().any(|c| c.is_control()));
+            assert!(buffer_str.contains("evil\\x1b[2J\\nname.txt"));
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_render_header_badges_non_utf8_name() {
+            use std::ffi::OsStr;
+            use std::os::unix::ffi::OsStrExt;
+
+            let mut entry = create_test_entry("caf\u{FFFD}.txt");
+            entry.path = OsStr::from_bytes(b"caf\xe9.txt").into();
+            let state = AppState::new(vec![entry]);
+            let backend = TestBackend::new(80, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let content = buffer.content();
+            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
+
+            assert!(buffer_str.contains("non-UTF-8 name"));
+        }
+
+        #[test]
+        fn test_render_header_seen_and_auto_advance_badges() {
+            let files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+            let mut state = AppState::new(files);
+            state.mark_seen();
+            state.toggle_auto_advance();
+
+            let backend = TestBackend::new(100, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let content = buffer.content();
+            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
+
+            assert!(buffer_str.contains("[seen]"));
+            assert!(buffer_str.contains("[auto-advance off]"));
+        }
+
+        #[test]
+        fn test_render_header_queue_stats() {
+            let mut files = vec![
+                create_test_entry("a.txt"),
+                create_test_entry("b.png"),
+                create_test_entry("c.png"),
+            ];
+            files[1].file_type = FileType::Image;
+            files[2].file_type = FileType::Image;
+            let mut state = AppState::new(files);
+            state.engine.set_dry_run(true);
+            state.show_queue_stats = true;
+            state.record_decision(Decision::Keep).unwrap();
+
+            let backend = TestBackend::new(100, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let buffer_str: String = buffer.content().iter().map(|c| c.symbol()).collect();
+            assert!(buffer_str.contains(" 2 left · 2 img · 2.0 KB "));
+        }
+
+        #[test]
+        fn test_render_triage_card_shows_metadata_only() {
+            let files = vec![create_test_entry("notes.txt"), create_test_entry("b.txt")];
+            let mut state = AppState::new_triage(files);
+            state.toggle_unsure();
+
+            let backend = TestBackend::new(100, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                })
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let content = buffer.content();
+            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
 
-    frame.render_widget(footer, area);
-}
+            assert!(buffer_str.contains("[triage]"));
+            assert!(buffer_str.contains("[unsure]"));
+            assert!(buffer_str.contains("Modified:"));
+            assert!(buffer_str.contains("1.0 KB"));
+            assert!(!buffer_str.contains("Error generating preview"));
+        }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::domain::{FileEntry, FileType};
-    use chrono::Utc;
-    use ratatui::{backend::TestBackend, Terminal};
-    use std::path::PathBuf;
+        #[test]
+        fn test_progress_segments_show_decisions_and_gaps() {
+            let files = ["a", "b", "c", "d", "e", "f"]
+                .into_iter()
+                .map(create_test_entry)
+                .collect();
+            let mut state = AppState::new(files);
+            state.engine.set_dry_run(true);
+            state.record_decision(Decision::Keep).unwrap();
+            state.goto(1);
+            state.record_decision(Decision::Trash).unwrap();
+            // c is looked at and skipped
+            state.goto(2);
+            state.mark_seen();
+            state.goto(3);
+            state.record_decision(Decision::Keep).unwrap();
 
-    fn create_test_entry(name: &str) -> FileEntry {
-        FileEntry {
-            path: PathBuf::from(name),
-            name: name.to_string(),
-            size: 1024,
-            modified_date: Utc::now(),
-            file_type: FileType::Text,
+            use Segment::*;
+            assert_eq!(
+                progress_segments(&state, 6),
+                vec![Keep, Trash, Skipped, Keep, Pending, Pending]
+            );
+            // Short queues get several columns per file
+            assert_eq!(
+                progress_segments(&state, 12)[..4],
+                [Keep, Keep, Trash, Trash]
+            );
+            // Squeezed, the gap still shows
+            assert_eq!(progress_segments(&state, 3), vec![Trash, Skipped, Pending]);
+            assert!(progress_segments(&state, 0).is_empty());
         }
-    }
 
-    mod layout_tests {
-        use super::*;
+        #[test]
+        fn test_queue_position_at_maps_clicks_on_the_bar() {
+            let files = (0..10)
+                .map(|i| create_test_entry(&format!("{}.txt", i)))
+                .collect();
+            let state = AppState::new(files);
+            let area = Rect::new(0, 0, 22, 24);
+
+            // Inside the side borders, on the row above the header's bottom border
+            assert_eq!(queue_position_at(area, &state, 1, 2), Some(0));
+            assert_eq!(queue_position_at(area, &state, 11, 2), Some(5));
+            assert_eq!(queue_position_at(area, &state, 20, 2), Some(9));
+            assert_eq!(queue_position_at(area, &state, 0, 2), None);
+            assert_eq!(queue_position_at(area, &state, 21, 2), None);
+            assert_eq!(queue_position_at(area, &state, 5, 1), None);
+        }
 
         #[test]
-        fn test_render_empty_state() {
-            let state = AppState::new(vec![]);
-            let backend = TestBackend::new(80, 24);
+        fn test_render_header_goal_gauge() {
+            let mut files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+            files[0].size = 1024 * 1024;
+            let mut state = AppState::new(files);
+            state.engine.set_dry_run(true);
+            state.goal = Some(SessionGoal::FreeBytes(4 * 1024 * 1024));
+            state
+                .record_decision(crate::domain::Decision::Trash)
+                .unwrap();
+
+            let backend = TestBackend::new(100, 24);
             let mut terminal = Terminal::new(backend).unwrap();
 
             terminal
@@ -898,33 +4314,28 @@ mod tests {
                 })
                 .unwrap();
 
-            // Verify no panics and rendering succeeds
             let buffer = terminal.backend().buffer().clone();
             let content = buffer.content();
-
-            // Check for empty state message in content
             let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
-            // The new empty state shows "No Files Found" or contains "empty"
-            assert!(
-                buffer_str.contains("No Files") || buffer_str.contains("empty"),
-                "Expected empty state message, got: {}",
-                buffer_str
-            );
+
+            assert!(buffer_str.contains("Goal: 1.0 MB / 4.0 MB freed"));
+            assert!(buffer_str.contains("(1/2)"));
         }
 
         #[test]
-        fn test_render_with_files() {
-            let files = vec![
-                create_test_entry("file1.txt"),
-                create_test_entry("file2.rs"),
-            ];
+        fn test_render_select_similar_overlay() {
+            let files = (1..=10)
+                .map(|i| create_test_entry(&format!("IMG_{:04}.jpg", i)))
+                .collect();
             let state = AppState::new(files);
-            let backend = TestBackend::new(80, 24);
+            let (pattern, matches) = state.files_like_current().unwrap();
+
+            let backend = TestBackend::new(100, 40);
             let mut terminal = Terminal::new(backend).unwrap();
 
             terminal
                 .draw(|frame| {
-                    render(frame, &state);
+                    render_select_similar_overlay(frame, &state, &pattern, &matches);
                 })
                 .unwrap();
 
@@ -932,20 +4343,27 @@ mod tests {
             let content = buffer.content();
             let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
 
-            // Check for file info in buffer
-            assert!(buffer_str.contains("file1.txt"));
-            assert!(buffer_str.contains("File 1/2"));
+            assert!(buffer_str.contains("IMG_####.jpg"));
+            assert!(buffer_str.contains("10 undecided files"));
+            assert!(buffer_str.contains("IMG_0008.jpg"));
+            assert!(!buffer_str.contains("IMG_0009.jpg"));
+            assert!(buffer_str.contains("and 2 more"));
         }
 
         #[test]
-        fn test_render_footer() {
-            let state = AppState::new(vec![create_test_entry("test.txt")]);
-            let backend = TestBackend::new(80, 24);
+        fn test_render_goal_reached_overlay() {
+            let mut state = AppState::new(vec![create_test_entry("a.txt")]);
+            state.goal = Some(SessionGoal::ReviewFiles(1));
+            state
+                .record_decision(crate::domain::Decision::Keep)
+                .unwrap();
+
+            let backend = TestBackend::new(100, 30);
             let mut terminal = Terminal::new(backend).unwrap();
 
             terminal
                 .draw(|frame| {
-                    render(frame, &state);
+                    render_goal_reached_overlay(frame, &state);
                 })
                 .unwrap();
 
@@ -953,38 +4371,552 @@ mod tests {
             let content = buffer.content();
             let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
 
-            // Check for controls in footer
-            assert!(buffer_str.contains("Trash"));
-            assert!(buffer_str.contains("Keep"));
-            assert!(buffer_str.contains("Quit"));
+            assert!(buffer_str.contains("Goal Reached"));
+            assert!(buffer_str.contains("1 / 1 files reviewed"));
         }
 
         #[test]
-        fn test_render_header_progress() {
+        fn test_preview_lines_redact_sensitive_lines() {
+            let content = preview::PreviewContent::Text(vec![
+                "name: Ada".to_string(),
+                "  card: 4111 1111 1111 1111".to_string(),
+            ]);
+            let text = |lines: Vec<Line>| -> Vec<String> {
+                lines.iter().map(|line| line.to_string()).collect()
+            };
+
+            let redacted = text(preview_lines(&content, true));
+            assert_eq!(redacted[0], "name: Ada");
+            assert!(redacted[1].starts_with("  ████"));
+            assert!(redacted[1].contains("card number hidden"));
+            assert!(!redacted[1].contains("4111"));
+
+            let revealed = text(preview_lines(&content, false));
+            assert_eq!(revealed[1], "  card: 4111 1111 1111 1111");
+        }
+
+        #[test]
+        fn test_preview_to_ansi() {
+            let content = preview::PreviewContent::Text(vec![
+                "tab\there\x1b[2J".to_string(),
+                "card: 4111 1111 1111 1111".to_string(),
+            ]);
+            let plain = preview_to_ansi(&content, true, false);
+            // Control characters are shown, never sent to the terminal
+            assert_eq!(plain[0], "tab    here\\x1b[2J");
+            assert!(!plain[1].contains("4111"));
+
+            let content = preview::PreviewContent::Styled(vec![Line::from(Span::styled(
+                "▀",
+                Style::default()
+                    .fg(ratatui::style::Color::Rgb(255, 0, 0))
+                    .bg(ratatui::style::Color::Rgb(0, 0, 255)),
+            ))]);
+            let colored = preview_to_ansi(&content, true, true);
+            assert!(colored[0].contains("\x1b[38;2;255;0;0m"));
+            assert!(colored[0].contains("\x1b[48;2;0;0;255m"));
+            assert!(colored[0].contains('▀'));
+            assert_eq!(preview_to_ansi(&content, true, false), ["▀"]);
+        }
+
+        #[test]
+        fn test_render_pause_screen_hides_the_session() {
+            let pause = app::PauseScreen {
+                input: "ab".to_string(),
+                wrong: true,
+                ..Default::default()
+            };
+            let backend = TestBackend::new(100, 30);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| render_pause_screen(frame, &pause, true))
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let buffer_str: String = buffer.content().iter().map(|c| c.symbol()).collect();
+
+            assert!(buffer_str.contains("Paused"));
+            assert!(buffer_str.contains("••_"));
+            assert!(buffer_str.contains("Wrong passphrase"));
+            assert!(!buffer_str.contains("ab"));
+        }
+
+        #[test]
+        fn test_render_setup_wizard() {
+            let mut wizard = crate::setup::SetupWizard::new();
+            wizard.confirm();
+            wizard.select_next();
+
+            let backend = TestBackend::new(100, 30);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| render_setup_wizard(frame, &wizard))
+                .unwrap();
+
+            let buffer = terminal.backend().buffer().clone();
+            let buffer_str: String = buffer.content().iter().map(|c| c.symbol()).collect();
+
+            assert!(buffer_str.contains("Setup 2/4"));
+            assert!(buffer_str.contains("When you browse past a file"));
+            assert!(buffer_str.contains("▶ Leave it unmarked"));
+        }
+
+        #[test]
+        fn test_render_header_usage_badges() {
+            let files = vec![create_test_entry("a.txt"), create_test_entry("b.txt")];
+            let mut state = AppState::new(files);
+            state
+                .usage
+                .insert(0, crate::usage::UsageInfo::never_opened());
+            state.usage.insert(
+                1,
+                crate::usage::UsageInfo {
+                    last_used: Some(Utc.with_ymd_and_hms(2024, 3, 2, 10, 0, 0).unwrap()),
+                    open_count: 3,
+                },
+            );
+
+            let backend = TestBackend::new(100, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+            assert!(buffer_str.contains("[never opened]"));
+
+            state.next();
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+            assert!(buffer_str.contains("[opened 3× · 2024-03-02]"));
+        }
+
+        #[test]
+        fn test_render_permission_warnings() {
+            use crate::permissions::{DeleteBlocker, FileAccess, Ownership};
+
+            let mut state = AppState::new(vec![create_test_entry("a.txt")]);
+            let access = FileAccess {
+                ownership: Some(Ownership {
+                    owner: "root".to_string(),
+                    group: "wheel".to_string(),
+                }),
+                blocker: Some(DeleteBlocker::ParentNotWritable),
+            };
+            state.access.insert(0, access.clone());
+
+            let backend = TestBackend::new(120, 40);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| {
+                    render(frame, &state);
+                    render_confirm_trash_overlay(
+                        frame,
+                        &state.files[0],
+                        &current_size_label(&state),
+                        Some(&access),
+                        &[],
+                    );
+                })
+                .unwrap();
+
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("needs sudo: no write permission on parent"));
+            assert!(buffer_str.contains("root:wheel"));
+            assert!(buffer_str.contains("Will fail: no write permission on parent"));
+        }
+
+        #[test]
+        fn test_render_header_degraded_mode_banner() {
+            let mut state = AppState::new(vec![create_test_entry("a.txt")]);
+            state.degraded_fs = Some("nfs4".to_string());
+
+            let backend = TestBackend::new(100, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("[network fs: nfs4 · degraded mode]"));
+        }
+
+        #[test]
+        fn test_render_apply_screen() {
             let files = vec![
-                create_test_entry("file1.txt"),
-                create_test_entry("file2.txt"),
-                create_test_entry("file3.txt"),
+                create_test_entry("a.txt"),
+                create_test_entry("b.txt"),
+                create_test_entry("c.txt"),
             ];
+            let mut job = ApplyJob::new(vec![0, 1, 2]);
+            job.take_next();
+            job.results.push((0, ApplyOutcome::Trashed));
+            job.take_next();
+            job.results
+                .push((1, ApplyOutcome::Failed("permission denied".to_string())));
+
+            let backend = TestBackend::new(100, 30);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| render_apply_screen(frame, &job, &files, Some((1, 3))))
+                .unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+            assert!(buffer_str.contains("Writing backup manifest: 1 of 3 files hashed"));
+
+            terminal
+                .draw(|frame| render_apply_screen(frame, &job, &files, None))
+                .unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("Applying"));
+            assert!(buffer_str.contains("Moving to trash: c.txt"));
+            assert!(buffer_str.contains("✓ a.txt"));
+            assert!(buffer_str.contains("b.txt — permission denied"));
+            assert!(buffer_str.contains("2/3 files"));
+            assert!(buffer_str.contains("Esc to cancel"));
+
+            job.take_next();
+            job.results.push((2, ApplyOutcome::Trashed));
+            job.issues = Some(vec![(2, crate::domain::Anomaly::TrashedStillPresent)]);
+            terminal
+                .draw(|frame| render_apply_screen(frame, &job, &files, None))
+                .unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("Apply Results"));
+            assert!(buffer_str.contains("Issues (1)"));
+            assert!(buffer_str.contains("c.txt — trashed, but a file exists at its path again"));
+        }
+
+        #[test]
+        fn test_render_header_background_apply_badges() {
+            let dir = tempfile::TempDir::new().unwrap();
+            let files = ["a.txt", "b.txt"]
+                .iter()
+                .map(|name| {
+                    let path = dir.path().join(name);
+                    std::fs::write(&path, b"content").unwrap();
+                    FileEntry::from_path(&path).unwrap()
+                })
+                .collect();
             let mut state = AppState::new(files);
-            state.next();
+            state
+                .record_batch(&[(0, crate::Decision::Trash), (1, crate::Decision::Trash)])
+                .unwrap();
+            // Simulate the trash move failing so the test doesn't touch the real trash
+            std::fs::remove_file(state.engine.get_staged_path(0)).unwrap();
+            state.engine.apply_in_background(1).unwrap();
 
-            let backend = TestBackend::new(80, 24);
+            let backend = TestBackend::new(120, 24);
             let mut terminal = Terminal::new(backend).unwrap();
 
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("[in system trash]"));
+            assert!(buffer_str.contains("[applied: 1 · pending: 1]"));
+        }
+
+        #[test]
+        fn test_render_header_notice() {
+            let mut state = AppState::new(vec![create_test_entry("a.txt")]);
+            state.show_notice("config reloaded: auto_advance");
+
+            let backend = TestBackend::new(120, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("[config reloaded: auto_advance]"));
+
+            state.show_notice("pinned evil\u{202e}gpj.exe");
+            state
+                .reasons
+                .insert(0, "same name as old\x07.txt".to_string());
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+            assert!(buffer_str.contains("[pinned evil\\u{202E}gpj.exe]"));
+            assert!(buffer_str.contains("[same name as old\\x07.txt]"));
+        }
+
+        #[test]
+        fn test_render_undo_toast() {
+            use std::time::Duration;
+            let state = AppState::new(vec![create_test_entry("a.txt"), create_test_entry("b.txt")]);
+            let pending = [
+                (0, Duration::from_millis(1500)),
+                (1, Duration::from_millis(3200)),
+            ];
+
+            let backend = TestBackend::new(120, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
             terminal
                 .draw(|frame| {
                     render(frame, &state);
+                    render_undo_toast(frame, &state, &pending, false);
                 })
                 .unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
 
-            let buffer = terminal.backend().buffer().clone();
-            let content = buffer.content();
-            let buffer_str: String = content.iter().map(|c| c.symbol()).collect();
+            // The latest decision, rounded up, with the others counted
+            assert!(buffer_str.contains("Trashing b.txt +1 in 4s  u Undo"));
+        }
 
-            // Check that we're on file 2 of 3
-            assert!(buffer_str.contains("File 2/3"));
-            assert!(buffer_str.contains("file2.txt"));
+        #[test]
+        fn test_render_replay() {
+            use crate::journal::{JournalAction, JournalRecord, SessionInfo, SessionLog};
+
+            let records = vec![
+                JournalRecord {
+                    time: 0,
+                    action: JournalAction::Trash,
+                    path: PathBuf::from("/home/me/old.log"),
+                    size: 2048,
+                    destination: None,
+                    prev: None,
+                },
+                JournalRecord {
+                    time: 10,
+                    action: JournalAction::Undo,
+                    path: PathBuf::from("/home/me/old.log"),
+                    size: 2048,
+                    destination: None,
+                    prev: None,
+                },
+                JournalRecord {
+                    time: 20,
+                    action: JournalAction::Move,
+                    path: PathBuf::from("/home/me/cat.gif"),
+                    size: 4096,
+                    destination: Some(PathBuf::from("/home/me/Keep")),
+                    prev: None,
+                },
+            ];
+            let log = SessionLog {
+                info: Some(SessionInfo {
+                    user: "alice".to_string(),
+                    host: "build-01".to_string(),
+                    version: "0.1.0".to_string(),
+                    annotation: Some("ticket-1234".to_string()),
+                    started: 0,
+                    chained: false,
+                    hash: None,
+                    dry_run: false,
+                }),
+                records,
+            };
+
+            let backend = TestBackend::new(100, 20);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal
+                .draw(|frame| render_replay(frame, "20240101-120000", &log, 1))
+                .unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("Replay: 20240101-120000"));
+            assert!(buffer_str.contains("Step 2/3"));
+            assert!(buffer_str.contains("alice@build-01 · fswp 0.1.0 · ticket-1234"));
+            assert!(buffer_str.contains("✗ trash /home/me/old.log"));
+            assert!(buffer_str.contains("↺ undo  /home/me/old.log"));
+            assert!(buffer_str.contains("→ move  /home/me/cat.gif → /home/me/Keep"));
+        }
+
+        #[test]
+        fn test_render_header_default_decision_badge() {
+            let mut state = AppState::new(vec![create_test_entry("a.txt")]);
+            state
+                .default_decisions
+                .insert(FileType::Text, DefaultDecision::SuggestTrash);
+
+            let backend = TestBackend::new(100, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("[suggest: trash]"));
+        }
+
+        #[test]
+        fn test_render_header_identical_copy_badge() {
+            let mut state = AppState::new(vec![
+                create_test_entry("a (1).txt"),
+                create_test_entry("a.txt"),
+            ]);
+            state.copies.insert(0, 1);
+            state
+                .default_decisions
+                .insert(FileType::Text, DefaultDecision::SuggestKeep);
+
+            let backend = TestBackend::new(120, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("[identical copy of a.txt: suggest trash]"));
+            assert!(!buffer_str.contains("[suggest: keep]"));
+
+            // The original's name is escaped like the current file's
+            let state = {
+                let mut state = AppState::new(vec![
+                    create_test_entry("a (1).txt"),
+                    create_test_entry("a\x1b[2J.txt"),
+                ]);
+                state.copies.insert(0, 1);
+                state
+            };
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+            assert!(buffer_str.contains("[identical copy of a\\x1b[2J.txt: suggest trash]"));
+        }
+
+        #[test]
+        fn test_render_header_sparse_and_hard_link_badges() {
+            let mut entry = create_test_entry("disk.img");
+            entry.size = 10 * 1024 * 1024;
+            entry.disk = crate::domain::DiskUsage {
+                allocated: Some(4096),
+                links: 3,
+                id: Some((1, 7)),
+                shared: 2048,
+            };
+            let state = AppState::new(vec![entry]);
+
+            let backend = TestBackend::new(120, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("[sparse: 4.0 KB on disk]"));
+            assert!(buffer_str.contains("[3 hard links]"));
+            assert!(buffer_str.contains("[clone: 2.0 KB shared]"));
+        }
+
+        #[test]
+        fn test_render_header_cloud_placeholder_badges() {
+            let mut entry = create_test_entry("video.mov");
+            entry.size = 10 * 1024 * 1024;
+            entry.disk.allocated = Some(0);
+            let mut state = AppState::new(vec![entry]);
+            state.cloud_sync = Some(crate::cloud::CloudProvider::OneDrive);
+
+            let backend = TestBackend::new(200, 24);
+            let mut terminal = Terminal::new(backend).unwrap();
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            let buffer_str: String = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|c| c.symbol())
+                .collect();
+
+            assert!(buffer_str.contains("[OneDrive folder · trash syncs to other devices]"));
+            assert!(buffer_str.contains(PLACEHOLDER_BADGE.trim()));
+            assert_eq!(current_size_label(&state), "10.0 MB online-only");
+            assert!(!buffer_str.contains("sparse"));
         }
 
         #[test]
@@ -994,7 +4926,7 @@ mod tests {
 
             terminal
                 .draw(|frame| {
-                    render_help_overlay(frame);
+                    render_help_overlay(frame, &HelpState::new(), &[]);
                 })
                 .unwrap();
 
@@ -1008,12 +4940,77 @@ mod tests {
             assert!(buffer_str.contains("Trash"));
         }
 
+        #[test]
+        fn test_render_move_targets_in_footer_and_help() {
+            let mut state = AppState::new(vec![create_test_entry("test.txt")]);
+            state.move_targets = vec![
+                (1, PathBuf::from("/home/me/Documents/Receipts")),
+                (3, PathBuf::from("/keep")),
+            ];
+            let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+            let render_str = |terminal: &mut Terminal<TestBackend>| {
+                let buffer = terminal.backend().buffer().clone();
+                buffer
+                    .content()
+                    .iter()
+                    .map(|c| c.symbol())
+                    .collect::<String>()
+            };
+
+            terminal.draw(|frame| render(frame, &state)).unwrap();
+            assert!(render_str(&mut terminal).contains("Move: 1 Receipts · 3 keep"));
+
+            terminal
+                .draw(|frame| render_help_overlay(frame, &HelpState::new(), &state.move_targets))
+                .unwrap();
+            assert!(render_str(&mut terminal).contains("Move targets: 1 Receipts · 3 keep"));
+        }
+
+        #[test]
+        fn test_render_help_overlay_filtered_and_paged() {
+            let backend = TestBackend::new(80, 16);
+            let mut terminal = Terminal::new(backend).unwrap();
+            let render_str = |terminal: &mut Terminal<TestBackend>, help: &HelpState| {
+                terminal
+                    .draw(|frame| render_help_overlay(frame, help, &[]))
+                    .unwrap();
+                let buffer = terminal.backend().buffer().clone();
+                buffer
+                    .content()
+                    .iter()
+                    .map(|c| c.symbol())
+                    .collect::<String>()
+            };
+
+            // A small terminal shows one page at a time
+            let buffer_str = render_str(&mut terminal, &HelpState::new());
+            assert!(buffer_str.contains("1–"));
+            assert!(!buffer_str.contains("Toggle help"));
+
+            let help = HelpState {
+                filter: "undo".to_string(),
+                scroll: 0,
+            };
+            let buffer_str = render_str(&mut terminal, &help);
+            assert!(buffer_str.contains("Search: undo"));
+            assert!(buffer_str.contains("Undo"));
+            assert!(buffer_str.contains("1–1 of 1"));
+        }
+
         #[test]
         fn test_render_summary() {
             let stats = DecisionStatistics {
                 total_files: 10,
-                kept: 6,
+                kept: 5,
                 trashed: 3,
+                moved: 1,
+                skipped: 1,
+                undecided: 0,
+                freed_bytes: 3 * 1024 * 1024,
+                freed_by_type: vec![
+                    (FileType::Image, 2 * 1024 * 1024),
+                    (FileType::Text, 1024 * 1024),
+                ],
             };
 
             let backend = TestBackend::new(80, 30);
@@ -1021,7 +5018,7 @@ mod tests {
 
             terminal
                 .draw(|frame| {
-                    render_summary(frame, &stats);
+                    render_summary(frame, &stats, None);
                 })
                 .unwrap();
 
@@ -1031,6 +5028,75 @@ mod tests {
 
             // Check for summary content
             assert!(buffer_str.contains("Summary") || buffer_str.contains("Complete"));
+            assert!(buffer_str.contains("Space freed: 3.0 MB"));
+            assert!(buffer_str.contains("Moved:    1"));
+            assert!(buffer_str.contains("Skipped:  1"));
+            assert!(buffer_str.contains("s review 1 skipped"));
+            assert!(!buffer_str.contains("Undecided"));
+        }
+
+        #[test]
+        fn test_render_summary_freed_chart() {
+            let render = |stats: &DecisionStatistics| {
+                let mut terminal = Terminal::new(TestBackend::new(80, 30)).unwrap();
+                terminal
+                    .draw(|frame| render_summary(frame, stats, None))
+                    .unwrap();
+                let buffer = terminal.backend().buffer().clone();
+                (0..buffer.area.height)
+                    .map(|y| {
+                        (0..buffer.area.width)
+                            .map(|x| buffer[(x, y)].symbol())
+                            .collect::<String>()
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            let trashed = DecisionStatistics {
+                total_files: 4,
+                kept: 2,
+                trashed: 2,
+                moved: 0,
+                skipped: 0,
+                undecided: 0,
+                freed_bytes: 3 * 1024 * 1024,
+                freed_by_type: vec![
+                    (FileType::Image, 2 * 1024 * 1024),
+                    (FileType::Pdf, 1024 * 1024),
+                ],
+            };
+            let rows = render(&trashed);
+            let labels = rows
+                .iter()
+                .find(|row| row.contains("Image"))
+                .expect("a label row");
+            assert!(labels.contains("Pdf"));
+            assert!(rows.iter().any(|row| row.contains("2.0 MB")));
+            assert!(rows.iter().any(|row| row.contains("1.0 MB")));
+            // The bigger type's bar is the taller one
+            let bar_rows = |label: &str| {
+                let column = labels[..labels.find(label).unwrap()].chars().count();
+                rows.iter()
+                    .filter(|row| row.chars().nth(column) == Some('█'))
+                    .count()
+            };
+            assert!(bar_rows("Image") > bar_rows("Pdf"));
+            assert!(bar_rows("Pdf") > 0);
+
+            let nothing = DecisionStatistics {
+                total_files: 4,
+                kept: 4,
+                trashed: 0,
+                moved: 0,
+                skipped: 0,
+                undecided: 0,
+                freed_bytes: 0,
+                freed_by_type: Vec::new(),
+            };
+            let rows = render(&nothing).concat();
+            assert!(rows.contains("Kept:"));
+            assert!(!rows.contains('█'));
+            assert!(!rows.contains("Image") && !rows.contains("Pdf"));
         }
 
         #[test]
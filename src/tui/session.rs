@@ -0,0 +1,514 @@
+// The session loop, generic over the terminal backend and the source of input events
+use super::app::{App, Effect};
+use super::glyphs;
+use super::{
+    current_size_label, render_apply_screen, render_confirm_trash_overlay,
+    render_duplicates_overlay, render_easy_wins_overlay, render_filter_bar, render_focus,
+    render_goal_reached_overlay, render_help_overlay, render_inspector_overlay,
+    render_move_conflict_overlay, render_overview, render_pause_screen,
+    render_preview_failure_overlay, render_rapid_decisions_overlay, render_renames_overlay,
+    render_review_overlay, render_search_overlay, render_select_similar_overlay,
+    render_setup_wizard, render_summary, render_undo_toast, render_welcome_overlay,
+    render_with_preview, ViewState,
+};
+use crate::async_preview::{PreviewState, SyncPreviewManager};
+use crate::config::ConfigWatcher;
+use crate::diagnostics::PreviewFailure;
+use crate::domain::ReviewPhase;
+use crate::manifest::ManifestWriter;
+use crate::preview::PreviewContent;
+use crate::usage::UsageWorker;
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{backend::Backend, layout::Rect, Terminal};
+use std::collections::VecDeque;
+use std::io;
+use std::time::Duration;
+
+/// With background apply, the latest decisions stay staged so `u` still works on them
+pub const BACKGROUND_APPLY_KEEP_RECENT: usize = 10;
+
+/// Where the session loop reads input from
+pub trait EventSource {
+    /// Waits up to `timeout` for the next event; `None` when nothing arrived
+    fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>>;
+}
+
+/// Input from the real terminal
+#[derive(Debug, Default)]
+pub struct TerminalEvents;
+
+impl EventSource for TerminalEvents {
+    fn next_event(&mut self, timeout: Duration) -> io::Result<Option<Event>> {
+        if event::poll(timeout)? {
+            event::read().map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// A fixed list of events for scripted sessions.
+///
+/// Events are delivered without waiting; once the script runs out, `next_event`
+/// fails with `UnexpectedEof` so a script that never quits can't hang.
+#[derive(Debug, Default)]
+pub struct ScriptedEvents {
+    events: VecDeque<Event>,
+}
+
+impl ScriptedEvents {
+    pub fn new(events: impl IntoIterator<Item = Event>) -> Self {
+        Self {
+            events: events.into_iter().collect(),
+        }
+    }
+
+    /// Presses of plain keys, without modifiers
+    pub fn keys(codes: impl IntoIterator<Item = KeyCode>) -> Self {
+        Self::new(
+            codes
+                .into_iter()
+                .map(|code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE))),
+        )
+    }
+
+    /// Events not yet delivered
+    pub fn remaining(&self) -> usize {
+        self.events.len()
+    }
+}
+
+impl EventSource for ScriptedEvents {
+    fn next_event(&mut self, _timeout: Duration) -> io::Result<Option<Event>> {
+        self.events
+            .pop_front()
+            .map(Some)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, "event script ended"))
+    }
+}
+
+/// Runs the session until `App::update` asks to exit.
+///
+/// Each iteration renders the current view, runs one step of any apply work,
+/// then feeds the next key to `App::update`. Effects that need the real
+/// terminal or the disk (opening files, the shell, saving the config) are passed
+/// to `on_effect`; `Exit` and the preview effects are handled here.
+pub fn run_session<B, E, F>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    preview_manager: &mut SyncPreviewManager,
+    events: &mut E,
+    mut on_effect: F,
+) -> io::Result<()>
+where
+    B: Backend,
+    E: EventSource,
+    F: FnMut(&mut Terminal<B>, &mut App, &mut SyncPreviewManager, Effect) -> io::Result<()>,
+{
+    let mut usage = UsageWorker::start();
+
+    loop {
+        if app.view != ViewState::Applying {
+            if let Some(reloaded) = app.config_watcher.as_mut().and_then(ConfigWatcher::poll) {
+                match reloaded {
+                    Ok(new_config) => {
+                        let changed = app.apply_config(new_config);
+                        if !changed.is_empty() {
+                            app.state
+                                .show_notice(format!("config reloaded: {}", changed.join(", ")));
+                        }
+                    }
+                    Err(e) => app.state.show_notice(format!("config not reloaded: {}", e)),
+                }
+            }
+        }
+
+        // Ask for last-used info for the file about to be shown, and for the easy
+        // wins on screen; it's looked up on a worker thread and joins the header
+        // and the suggestions once it arrives
+        let answers = usage.poll();
+        if !answers.is_empty() {
+            app.state.usage.extend(answers);
+            app.prune_easy_wins_by_usage();
+        }
+        let wanted = app.easy_wins_usage_wanted();
+        for index in app.state.current_file_index().into_iter().chain(wanted) {
+            if !app.state.usage.contains_key(&index) {
+                usage.request(index, &app.state.files[index].path);
+            }
+        }
+
+        let grace = app.user_config.undo_grace();
+
+        // Render based on current view state
+        terminal.draw(|frame| {
+            // Nothing of the session shows through the pause screen
+            if let (ViewState::Paused, Some(ref pause)) = (&app.view, &app.pause) {
+                render_pause_screen(frame, pause, app.passphrase.is_some());
+                glyphs::apply(frame.buffer_mut());
+                return;
+            }
+            if app.focus {
+                render_focus(frame, &app.state, preview_manager);
+            } else {
+                render_with_preview(
+                    frame,
+                    &app.state,
+                    preview_manager,
+                    &app.view,
+                    app.queue_panel,
+                    app.pinned.and_then(|index| app.state.files.get(index)),
+                );
+            }
+
+            // Render overlays
+            match app.view {
+                ViewState::Help => render_help_overlay(frame, &app.help, &app.state.move_targets),
+                ViewState::Summary => {
+                    let stats = app.state.engine.get_statistics();
+                    render_summary(frame, &stats, app.state.active_notice());
+                }
+                ViewState::ConfirmTrash => {
+                    if let Some(file) = app.state.current_file() {
+                        let sidecars: Vec<_> =
+                            app.sidecars.iter().map(|&i| &app.state.files[i]).collect();
+                        render_confirm_trash_overlay(
+                            frame,
+                            file,
+                            &current_size_label(&app.state),
+                            app.state.current_access(),
+                            &sidecars,
+                        );
+                    }
+                }
+                ViewState::Welcome => render_welcome_overlay(frame),
+                ViewState::GoalReached => render_goal_reached_overlay(frame, &app.state),
+                ViewState::RapidDecisions => {
+                    render_rapid_decisions_overlay(frame, app.rapid_count())
+                }
+                ViewState::Setup => {
+                    if let Some(ref wizard) = app.setup {
+                        render_setup_wizard(frame, wizard);
+                    }
+                }
+                ViewState::Applying => {
+                    if let Some(ref job) = app.apply_job {
+                        let manifest = app.manifest_writer.as_ref().map(ManifestWriter::progress);
+                        render_apply_screen(frame, job, &app.state.engine.files, manifest);
+                    }
+                }
+                ViewState::SelectSimilar => {
+                    if let Some((ref pattern, ref matches)) = app.selection {
+                        render_select_similar_overlay(frame, &app.state, pattern, matches);
+                    }
+                }
+                ViewState::EasyWins => render_easy_wins_overlay(
+                    frame,
+                    &app.state,
+                    &app.easy_wins,
+                    app.easy_wins_cursor,
+                ),
+                ViewState::Duplicates => render_duplicates_overlay(
+                    frame,
+                    &app.state,
+                    &app.duplicates,
+                    app.duplicates_cursor,
+                ),
+                ViewState::Overview => {
+                    if let Some(ref overview) = app.overview {
+                        render_overview(frame, overview);
+                    }
+                }
+                ViewState::PreviewError => {
+                    if let Some(ref failure) = app.preview_failure {
+                        render_preview_failure_overlay(frame, failure, app.state.active_notice());
+                    }
+                }
+                ViewState::Renames => {
+                    render_renames_overlay(frame, &app.state, &app.renames, app.renames_cursor)
+                }
+                ViewState::Review => {
+                    render_review_overlay(frame, &app.state, &app.review, app.review_cursor)
+                }
+                ViewState::Inspector => render_inspector_overlay(
+                    frame,
+                    &app.inspector_rows(),
+                    app.inspector_cursor,
+                    app.state.active_notice(),
+                ),
+                ViewState::MoveConflict => {
+                    if let Some(ref conflict) = app.move_conflict {
+                        render_move_conflict_overlay(frame, &app.state, conflict);
+                    }
+                }
+                ViewState::Search => {
+                    let progress = app.search.as_ref().map(|search| search.progress());
+                    render_search_overlay(frame, &app.search_input, progress);
+                }
+                ViewState::Filter | ViewState::Browsing | ViewState::Paused => {}
+            }
+            if app.filter_active() {
+                render_filter_bar(
+                    frame,
+                    &app.state,
+                    &app.filter_input,
+                    app.filter_error.as_deref(),
+                    app.view == ViewState::Filter,
+                );
+            }
+            if app.apply_on_decide && app.view == ViewState::Browsing {
+                render_undo_toast(
+                    frame,
+                    &app.state,
+                    &app.state.engine.within_grace(grace),
+                    app.filter_active(),
+                );
+            }
+            glyphs::apply(frame.buffer_mut());
+        })?;
+
+        // Note sensitive lines once the current file's preview is in
+        if let (Some(index), PreviewState::Ready(PreviewContent::Text(lines))) = (
+            app.state.current_file_index(),
+            preview_manager.current_state(),
+        ) {
+            if app.state.phase != ReviewPhase::Triage {
+                app.state.note_preview_lines(index, lines);
+            }
+        }
+
+        // Narrow the queue once a content search has gone through every file
+        if app.poll_search() {
+            preview_manager.reset();
+        }
+        app.poll_sizes();
+        app.poll_copies();
+        app.poll_duplicates();
+
+        // Trash one file per iteration so the apply screen stays live and cancellable,
+        // once the backup manifest is saved
+        let writing_manifest = app.poll_manifest();
+        let applying = !writing_manifest
+            && app.view == ViewState::Applying
+            && app.apply_job.as_ref().is_some_and(|job| !job.is_finished());
+        if applying {
+            if let Some(ref mut job) = app.apply_job {
+                app.state.engine.apply_next(job);
+                if job.is_finished() && app.verify {
+                    app.state.engine.verify(job);
+                }
+            }
+        }
+
+        // Background apply: trash older decisions between key presses while browsing
+        let background = app.background_apply
+            && app.view == ViewState::Browsing
+            && app
+                .state
+                .engine
+                .has_background_work(BACKGROUND_APPLY_KEEP_RECENT);
+        if background {
+            app.state
+                .engine
+                .apply_in_background(BACKGROUND_APPLY_KEEP_RECENT);
+        }
+
+        // Apply on decide: trash decisions whose undo grace period is over, one per
+        // iteration; the toast counts down the rest
+        let expired = app.apply_on_decide
+            && app.view == ViewState::Browsing
+            && app.state.engine.has_expired_work(grace);
+        if expired {
+            app.state.engine.apply_expired(grace);
+        }
+
+        // Handle input, polling slowly when nothing is animating to keep idle CPU low
+        // Triage renders no previews, so a pending load never animates there; a running
+        // search keeps its progress count moving, as do a directory being measured, the
+        // copies and duplicates being looked for and the backup manifest being hashed;
+        // a pending usage lookup polls quickly too, so its badge shows promptly
+        let animating = (app.state.phase != ReviewPhase::Triage && preview_manager.is_loading())
+            || usage.is_waiting()
+            || app.is_searching()
+            || app.is_sizing()
+            || app.is_finding()
+            || writing_manifest
+            || (app.apply_on_decide && !app.state.engine.within_grace(grace).is_empty());
+        let timeout = if applying || background || expired {
+            Duration::ZERO
+        } else {
+            app.user_config.poll_interval(animating)
+        };
+        let action = match events.next_event(timeout)? {
+            Some(Event::Key(key)) => app.action_for_key(key),
+            Some(Event::Mouse(mouse)) => {
+                let size = terminal.size()?;
+                app.action_for_click(mouse, Rect::new(0, 0, size.width, size.height))
+            }
+            _ => continue,
+        };
+
+        match app.update(action) {
+            Effect::None => {}
+            Effect::Exit => break,
+            Effect::ResetPreview => preview_manager.reset(),
+            Effect::ShowPreviewFailure => {
+                let failure = match (preview_manager.current_state(), app.state.current_file()) {
+                    (PreviewState::Error(error), Some(file)) => {
+                        Some(PreviewFailure::collect(file, error))
+                    }
+                    _ => None,
+                };
+                app.show_preview_failure(failure);
+            }
+            Effect::AdjustImage(command) => {
+                if let Some(file) = app.state.current_file().cloned() {
+                    if let Err(e) = preview_manager.adjust_image(&file, command) {
+                        app.state.show_notice(format!("can't zoom: {}", e));
+                    }
+                }
+            }
+            Effect::ScrollPreview(command) => preview_manager.scroll_preview(command),
+            effect => on_effect(terminal, app, preview_manager, effect)?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::UserConfig;
+    use crate::domain::{AppState, Decision, FileEntry};
+    use ratatui::backend::TestBackend;
+    use std::fs;
+    use tempfile::TempDir;
+
+    /// A dry-run session over `count` real files, ready to browse
+    fn session(dir: &TempDir, count: usize) -> App {
+        let files = (0..count)
+            .map(|i| {
+                let path = dir.path().join(format!("file{}.txt", i));
+                fs::write(&path, format!("contents of file {}", i)).unwrap();
+                FileEntry::from_path(&path).unwrap()
+            })
+            .collect();
+        let mut state = AppState::new(files);
+        state.engine.set_dry_run(true);
+        App::new(state, UserConfig::default())
+    }
+
+    fn screen(terminal: &Terminal<TestBackend>) -> String {
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol())
+            .collect()
+    }
+
+    #[test]
+    fn test_scripted_session_trash_undo_quit() {
+        let dir = TempDir::new().unwrap();
+        let mut app = session(&dir, 5);
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        let mut preview_manager = SyncPreviewManager::new();
+        // Trash two, undo one, keep one, quit to the summary, review, then apply
+        let mut events = ScriptedEvents::keys([
+            KeyCode::Char('t'),
+            KeyCode::Char('t'),
+            KeyCode::Char('u'),
+            KeyCode::Char('k'),
+            KeyCode::Char('q'),
+            KeyCode::Enter,
+            KeyCode::Enter,
+        ]);
+
+        run_session(
+            &mut terminal,
+            &mut app,
+            &mut preview_manager,
+            &mut events,
+            |_, _, _, effect| panic!("unexpected effect {:?}", effect),
+        )
+        .unwrap();
+
+        assert_eq!(events.remaining(), 0);
+        // Undo doesn't move the cursor, so the keep lands on the third file
+        assert_eq!(
+            app.state.decisions(),
+            &[(0, Decision::Trash), (2, Decision::Keep)]
+        );
+        assert!(!app.state.is_decided(1));
+        let stats = app.state.engine.get_statistics();
+        assert_eq!((stats.kept, stats.trashed), (1, 1));
+
+        // The last frame drawn before the final key was the trash review
+        assert_eq!(app.view, ViewState::Review);
+        assert!(screen(&terminal).contains("Trash 1 of 1 files"));
+        // Dry run: nothing left its place
+        assert!(dir.path().join("file0.txt").exists());
+    }
+
+    #[test]
+    fn test_scripted_session_click_on_progress_bar_jumps() {
+        use crossterm::event::{MouseButton, MouseEvent, MouseEventKind};
+
+        let dir = TempDir::new().unwrap();
+        let mut app = session(&dir, 5);
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        let mut preview_manager = SyncPreviewManager::new();
+        let click = |column, row| {
+            Event::Mouse(MouseEvent {
+                kind: MouseEventKind::Down(MouseButton::Left),
+                column,
+                row,
+                modifiers: KeyModifiers::NONE,
+            })
+        };
+        // 118 bar columns for 5 files: column 72 is in the fourth file's stretch;
+        // a click off the bar does nothing
+        let mut events = ScriptedEvents::new([click(72, 2), click(72, 10)]);
+
+        let result = run_session(
+            &mut terminal,
+            &mut app,
+            &mut preview_manager,
+            &mut events,
+            |_, _, _, effect| panic!("unexpected effect {:?}", effect),
+        );
+
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(app.state.current_index, 3);
+        assert!(screen(&terminal).contains("0% (0/5)"));
+    }
+
+    #[test]
+    fn test_scripted_session_hands_off_effects() {
+        let dir = TempDir::new().unwrap();
+        let mut app = session(&dir, 2);
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        let mut preview_manager = SyncPreviewManager::new();
+        let mut events = ScriptedEvents::keys([KeyCode::Char('o'), KeyCode::Char('!')]);
+
+        let mut handled = Vec::new();
+        let result = run_session(
+            &mut terminal,
+            &mut app,
+            &mut preview_manager,
+            &mut events,
+            |_, _, _, effect| {
+                handled.push(format!("{:?}", effect));
+                Ok(())
+            },
+        );
+
+        // The script never quits, so the loop stops when it runs out
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(handled.len(), 2);
+        assert!(handled[0].starts_with("Open("));
+        assert_eq!(handled[1], "Shell");
+    }
+}
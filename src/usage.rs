@@ -0,0 +1,380 @@
+//! Module for looking up when a file was last opened, using the desktop's own records
+//!
+//! - macOS: Spotlight metadata (`kMDItemLastUsedDate`, `kMDItemUseCount`) via `mdls`
+//! - Linux: the freedesktop recently-used list (`recently-used.xbel`), also used by GNOME Tracker
+//!
+//! Modification time can't tell "never opened" apart from "opened but never edited",
+//! which is the most useful signal when deciding what to throw away.
+
+use chrono::{DateTime, Utc};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::thread;
+
+/// Last-used information for a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageInfo {
+    pub last_used: Option<DateTime<Utc>>,
+    pub open_count: u32,
+}
+
+impl UsageInfo {
+    /// The usage source knows about the file system but has no record of this file
+    pub fn never_opened() -> Self {
+        Self {
+            last_used: None,
+            open_count: 0,
+        }
+    }
+
+    pub fn is_never_opened(&self) -> bool {
+        self.open_count == 0 && self.last_used.is_none()
+    }
+}
+
+/// Looks up usage information, caching results for the session
+#[derive(Debug, Default)]
+pub struct UsageLookup {
+    /// Parsed recently-used list, loaded on first use (Linux)
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    recent: Option<Option<HashMap<PathBuf, UsageInfo>>>,
+    cache: HashMap<PathBuf, Option<UsageInfo>>,
+}
+
+impl UsageLookup {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns usage info for `path`, or None when no usage source is available
+    pub fn lookup(&mut self, path: &Path) -> Option<UsageInfo> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if let Some(cached) = self.cache.get(&path) {
+            return cached.clone();
+        }
+        let info = self.query(&path);
+        self.cache.insert(path, info.clone());
+        info
+    }
+
+    #[cfg(target_os = "macos")]
+    fn query(&mut self, path: &Path) -> Option<UsageInfo> {
+        let output = std::process::Command::new("mdls")
+            .arg("-name")
+            .arg("kMDItemLastUsedDate")
+            .arg("-name")
+            .arg("kMDItemUseCount")
+            .arg(path)
+            .output()
+            .ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        Some(parse_mdls(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    #[cfg(target_os = "linux")]
+    fn query(&mut self, path: &Path) -> Option<UsageInfo> {
+        let recent = self.recent.get_or_insert_with(|| {
+            let xbel = dirs::data_dir()?.join("recently-used.xbel");
+            let contents = std::fs::read_to_string(xbel).ok()?;
+            Some(parse_xbel(&contents))
+        });
+        let recent = recent.as_ref()?;
+        Some(
+            recent
+                .get(path)
+                .cloned()
+                .unwrap_or_else(UsageInfo::never_opened),
+        )
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    fn query(&mut self, _path: &Path) -> Option<UsageInfo> {
+        None
+    }
+}
+
+/// Looks up usage on a worker thread, since a lookup can start a process (`mdls`)
+/// per file; answers come back through `poll`
+#[derive(Debug)]
+pub struct UsageWorker {
+    requests: Sender<(usize, PathBuf)>,
+    results: Receiver<(usize, Option<UsageInfo>)>,
+    cancel: Arc<AtomicBool>,
+    /// File indices already asked about, so each file is looked up once
+    requested: HashSet<usize>,
+    /// Requests sent but not answered yet
+    waiting: usize,
+}
+
+impl UsageWorker {
+    pub fn start() -> Self {
+        let (requests, pending) = mpsc::channel::<(usize, PathBuf)>();
+        let (sender, results) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let stop = Arc::clone(&cancel);
+        thread::spawn(move || {
+            let mut lookup = UsageLookup::new();
+            for (index, path) in pending {
+                if stop.load(Ordering::Relaxed) {
+                    break;
+                }
+                if sender.send((index, lookup.lookup(&path))).is_err() {
+                    break;
+                }
+            }
+        });
+        Self {
+            requests,
+            results,
+            cancel,
+            requested: HashSet::new(),
+            waiting: 0,
+        }
+    }
+
+    /// Asks for the usage of file `index` at `path`, unless it was asked for before
+    pub fn request(&mut self, index: usize, path: &Path) {
+        if self.requested.insert(index) && self.requests.send((index, path.to_path_buf())).is_ok() {
+            self.waiting += 1;
+        }
+    }
+
+    /// Usage found since the last poll, by file index; files no usage source
+    /// knows about are left out
+    pub fn poll(&mut self) -> Vec<(usize, UsageInfo)> {
+        let answers: Vec<_> = self.results.try_iter().collect();
+        self.waiting -= answers.len();
+        answers
+            .into_iter()
+            .filter_map(|(index, info)| Some((index, info?)))
+            .collect()
+    }
+
+    /// Whether a requested lookup hasn't been answered yet
+    pub fn is_waiting(&self) -> bool {
+        self.waiting > 0
+    }
+}
+
+impl Drop for UsageWorker {
+    /// The worker stops after its current lookup once nobody is waiting for answers
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Parses `mdls -name kMDItemLastUsedDate -name kMDItemUseCount` output
+#[cfg(any(target_os = "macos", test))]
+fn parse_mdls(output: &str) -> UsageInfo {
+    let mut info = UsageInfo::never_opened();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        if value == "(null)" {
+            continue;
+        }
+        match key.trim() {
+            "kMDItemLastUsedDate" => {
+                info.last_used = DateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S %z")
+                    .ok()
+                    .map(|date| date.with_timezone(&Utc));
+            }
+            "kMDItemUseCount" => {
+                info.open_count = value.parse().unwrap_or(0);
+            }
+            _ => {}
+        }
+    }
+    // Spotlight sometimes records a date without a count
+    if info.last_used.is_some() && info.open_count == 0 {
+        info.open_count = 1;
+    }
+    info
+}
+
+/// Parses a freedesktop `recently-used.xbel` file into usage info keyed by local path
+#[cfg(any(target_os = "linux", test))]
+fn parse_xbel(contents: &str) -> HashMap<PathBuf, UsageInfo> {
+    let mut recent = HashMap::new();
+    for bookmark in contents.split("<bookmark ").skip(1) {
+        let Some(path) = xml_attribute(bookmark, "href").and_then(file_uri_to_path) else {
+            continue;
+        };
+        let last_used = xml_attribute(bookmark, "visited")
+            .or_else(|| xml_attribute(bookmark, "modified"))
+            .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+            .map(|date| date.with_timezone(&Utc));
+        // Each application that opened the file records its own count
+        let open_count = bookmark
+            .split("<bookmark:application ")
+            .skip(1)
+            .filter_map(|app| xml_attribute(app, "count")?.parse::<u32>().ok())
+            .sum::<u32>()
+            .max(1);
+        recent.insert(
+            path,
+            UsageInfo {
+                last_used,
+                open_count,
+            },
+        );
+    }
+    recent
+}
+
+/// Returns the value of `name="..."` within a tag fragment
+#[cfg(any(target_os = "linux", test))]
+fn xml_attribute<'a>(fragment: &'a str, name: &str) -> Option<&'a str> {
+    let tag_end = fragment.find('>').unwrap_or(fragment.len());
+    let tag = &fragment[..tag_end];
+    let needle = format!("{}=\"", name);
+    let (start, _) = tag
+        .match_indices(&needle)
+        .find(|&(pos, _)| pos == 0 || tag.as_bytes()[pos - 1].is_ascii_whitespace())?;
+    let rest = &tag[start + needle.len()..];
+    let end = rest.find('"')?;
+    Some(&rest[..end])
+}
+
+/// Decodes a percent-encoded `file://` URI into a local path
+#[cfg(any(target_os = "linux", test))]
+fn file_uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Some(PathBuf::from(std::ffi::OsString::from_vec(decoded)))
+    }
+    #[cfg(not(unix))]
+    {
+        Some(PathBuf::from(
+            String::from_utf8_lossy(&decoded).into_owned(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const XBEL: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<xbel version="1.0" xmlns:bookmark="http://www.freedesktop.org/standards/desktop-bookmarks">
+  <bookmark href="file:///home/me/report%20final.pdf" added="2024-01-01T09:00:00Z" modified="2024-03-01T09:00:00Z" visited="2024-03-02T10:30:00.123456Z">
+    <info>
+      <metadata owner="http://freedesktop.org">
+        <bookmark:applications>
+          <bookmark:application name="Evince" exec="&apos;evince %u&apos;" modified="2024-03-01T09:00:00Z" count="3"/>
+          <bookmark:application name="Firefox" exec="&apos;firefox %u&apos;" modified="2024-02-01T09:00:00Z" count="2"/>
+        </bookmark:applications>
+      </metadata>
+    </info>
+  </bookmark>
+  <bookmark href="https://example.com/" added="2024-01-01T09:00:00Z" modified="2024-01-01T09:00:00Z" visited="2024-01-01T09:00:00Z"/>
+</xbel>"#;
+
+    #[test]
+    fn test_parse_xbel_sums_application_counts() {
+        let recent = parse_xbel(XBEL);
+        let info = recent
+            .get(Path::new("/home/me/report final.pdf"))
+            .expect("bookmark should be parsed");
+
+        assert_eq!(info.open_count, 5);
+        assert_eq!(
+            info.last_used,
+            Some(
+                Utc.with_ymd_and_hms(2024, 3, 2, 10, 30, 0).unwrap()
+                    + chrono::Duration::microseconds(123456)
+            )
+        );
+    }
+
+    #[test]
+    fn test_xml_attribute_matches_whole_name() {
+        let tag = r#"name="x" modified="2024" count="3"/>"#;
+        assert_eq!(xml_attribute(tag, "count"), Some("3"));
+        assert_eq!(xml_attribute(tag, "ified"), None);
+    }
+
+    #[test]
+    fn test_parse_xbel_skips_non_file_uris() {
+        assert_eq!(parse_xbel(XBEL).len(), 1);
+    }
+
+    #[test]
+    fn test_file_uri_to_path_decodes_percent_escapes() {
+        assert_eq!(
+            file_uri_to_path("file:///tmp/a%20b%25c"),
+            Some(PathBuf::from("/tmp/a b%c"))
+        );
+        assert_eq!(file_uri_to_path("https://example.com"), None);
+    }
+
+    #[test]
+    fn test_parse_mdls() {
+        let output = "kMDItemLastUsedDate = 2024-05-01 10:20:30 +0000\nkMDItemUseCount     = 7\n";
+        let info = parse_mdls(output);
+        assert_eq!(
+            info.last_used,
+            Some(Utc.with_ymd_and_hms(2024, 5, 1, 10, 20, 30).unwrap())
+        );
+        assert_eq!(info.open_count, 7);
+    }
+
+    #[test]
+    fn test_parse_mdls_never_opened() {
+        let output = "kMDItemLastUsedDate = (null)\nkMDItemUseCount     = (null)\n";
+        assert!(parse_mdls(output).is_never_opened());
+    }
+
+    #[test]
+    fn test_lookup_caches_results() {
+        let mut lookup = UsageLookup::new();
+        let first = lookup.lookup(Path::new("/nonexistent/file.txt"));
+        let second = lookup.lookup(Path::new("/nonexistent/file.txt"));
+        assert_eq!(first, second);
+        assert_eq!(lookup.cache.len(), 1);
+    }
+
+    #[test]
+    fn test_usage_worker_answers_each_file_once() {
+        let mut worker = UsageWorker::start();
+        worker.request(0, Path::new("/nonexistent/file.txt"));
+        worker.request(0, Path::new("/nonexistent/file.txt"));
+        assert!(worker.is_waiting());
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        let mut answers = Vec::new();
+        while worker.is_waiting() && std::time::Instant::now() < deadline {
+            answers.extend(worker.poll());
+            thread::sleep(std::time::Duration::from_millis(5));
+        }
+        assert!(!worker.is_waiting());
+        assert!(answers.len() <= 1);
+        assert!(answers.iter().all(|(index, _)| *index == 0));
+
+        worker.request(0, Path::new("/nonexistent/file.txt"));
+        assert!(!worker.is_waiting());
+    }
+}